@@ -0,0 +1,183 @@
+//! Alias naming and lookup
+//!
+//! Aliases live in `issues/.aliases.yaml`. With an `alias_namespace` set in
+//! the config, generated aliases are stored as `namespace/slug`, and a bare
+//! `slug` resolves to the current namespace first. Lookups ignore case.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::storage::Storage;
+
+/// Most title words that go into a generated alias
+const MAX_SLUG_WORDS: usize = 5;
+
+/// Alias generated from a title, e.g. `fix-auth-timeout`
+pub fn slug(title: &str) -> String {
+   Storage::slugify(title)
+      .split('-')
+      .take(MAX_SLUG_WORDS)
+      .collect::<Vec<_>>()
+      .join("-")
+}
+
+/// `alias` inside `namespace`, if there is one
+pub fn qualify(namespace: Option<&str>, alias: &str) -> String {
+   match namespace {
+      Some(ns) if !ns.is_empty() => format!("{ns}/{alias}"),
+      _ => alias.to_string(),
+   }
+}
+
+/// The alias without its namespace
+fn bare(alias: &str) -> &str {
+   alias.rsplit_once('/').map_or(alias, |(_, name)| name)
+}
+
+/// The issue `name` refers to: an exact alias first, then the alias in
+/// `namespace`, then either ignoring case, then a bare name found in exactly
+/// one namespace
+pub fn lookup(aliases: &HashMap<String, u32>, namespace: Option<&str>, name: &str) -> Option<u32> {
+   let qualified = qualify(namespace, name);
+   if let Some(&num) = aliases.get(name).or_else(|| aliases.get(&qualified)) {
+      return Some(num);
+   }
+
+   let unique = |matches: &mut dyn Iterator<Item = u32>| {
+      let nums: HashSet<u32> = matches.collect();
+      (nums.len() == 1).then(|| *nums.iter().next().unwrap())
+   };
+   let ignoring_case = |wanted: &str| {
+      unique(
+         &mut aliases
+            .iter()
+            .filter(|(alias, _)| alias.eq_ignore_ascii_case(wanted))
+            .map(|(_, &num)| num),
+      )
+   };
+   ignoring_case(name)
+      .or_else(|| ignoring_case(&qualified))
+      .or_else(|| {
+         unique(
+            &mut aliases
+               .iter()
+               .filter(|(alias, _)| bare(alias).eq_ignore_ascii_case(name))
+               .map(|(_, &num)| num),
+         )
+      })
+}
+
+/// Whether `alias` is taken by an issue other than `bug_num`, ignoring case
+pub fn taken(aliases: &HashMap<String, u32>, alias: &str, bug_num: u32) -> bool {
+   aliases
+      .iter()
+      .any(|(existing, &num)| num != bug_num && existing.eq_ignore_ascii_case(alias))
+}
+
+/// An alias that can't be resolved the way it reads
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AliasProblem {
+   pub aliases: Vec<String>,
+   pub message: String,
+}
+
+/// Collisions and dead ends among `aliases`, given the issues that exist
+pub fn problems(aliases: &HashMap<String, u32>, existing: &HashSet<u32>) -> Vec<AliasProblem> {
+   let mut problems = Vec::new();
+
+   let mut by_lower: BTreeMap<String, Vec<(&String, u32)>> = BTreeMap::new();
+   let mut by_bare: BTreeMap<String, Vec<(&String, u32)>> = BTreeMap::new();
+   for (alias, &num) in aliases {
+      by_lower
+         .entry(alias.to_lowercase())
+         .or_default()
+         .push((alias, num));
+      by_bare
+         .entry(bare(alias).to_lowercase())
+         .or_default()
+         .push((alias, num));
+   }
+
+   let mut report = |group: &[(&String, u32)], what: &str| {
+      let nums: HashSet<u32> = group.iter().map(|&(_, num)| num).collect();
+      if nums.len() > 1 {
+         let mut names: Vec<String> = group.iter().map(|(alias, _)| alias.to_string()).collect();
+         names.sort();
+         let mut targets: Vec<String> = nums.iter().map(|num| format!("#{num}")).collect();
+         targets.sort();
+         problems.push(AliasProblem {
+            message: format!("{} {what} but point to {}", names.join(", "), targets.join(", ")),
+            aliases: names,
+         });
+      }
+   };
+   for group in by_lower.values() {
+      report(group, "differ only in case");
+   }
+   for group in by_bare.values() {
+      // Duplicates within one namespace were reported above
+      let namespaces: HashSet<String> = group
+         .iter()
+         .map(|(alias, _)| {
+            alias
+               .rsplit_once('/')
+               .map_or("", |(ns, _)| ns)
+               .to_lowercase()
+         })
+         .collect();
+      if namespaces.len() > 1 {
+         report(group, "share a name across namespaces");
+      }
+   }
+
+   let mut sorted: Vec<(&String, &u32)> = aliases.iter().collect();
+   sorted.sort();
+   for (alias, &num) in sorted {
+      if alias.parse::<u32>().is_ok() {
+         problems.push(AliasProblem {
+            aliases: vec![alias.clone()],
+            message: format!("{alias} is a number, so it always means issue #{alias}, not #{num}"),
+         });
+      }
+      if !existing.contains(&num) {
+         problems.push(AliasProblem {
+            aliases: vec![alias.clone()],
+            message: format!("{alias} points to #{num}, which doesn't exist"),
+         });
+      }
+   }
+
+   problems
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn test_lookup_and_problems() {
+      assert_eq!(slug("Fix auth timeout on the login page"), "fix-auth-timeout-on-the");
+      assert_eq!(qualify(Some("web"), "login"), "web/login");
+
+      let aliases: HashMap<String, u32> = [
+         ("web/login".to_string(), 1),
+         ("api/login".to_string(), 2),
+         ("Schema".to_string(), 3),
+         ("api/deploy".to_string(), 4),
+      ]
+      .into();
+      assert_eq!(lookup(&aliases, Some("api"), "login"), Some(2));
+      assert_eq!(lookup(&aliases, None, "login"), None);
+      assert_eq!(lookup(&aliases, None, "schema"), Some(3));
+      assert_eq!(lookup(&aliases, None, "DEPLOY"), Some(4));
+      assert!(taken(&aliases, "schema", 1));
+      assert!(!taken(&aliases, "schema", 3));
+
+      let existing = HashSet::from([1, 2, 3]);
+      let problems = problems(&aliases, &existing);
+      assert_eq!(problems.len(), 2);
+      assert_eq!(problems[0].aliases, ["api/login", "web/login"]);
+      assert_eq!(problems[1].message, "api/deploy points to #4, which doesn't exist");
+   }
+}
@@ -0,0 +1,4163 @@
+//! Library interface for embedding the tracker in other Rust tools
+//!
+//! [`IssueTracker`] holds the tracker's operations and returns data instead
+//! of printing it; the CLI's [`Commands`](crate::commands::Commands) run
+//! them and print the results. Beside the methods below, each command's
+//! operation is available as a `*_data` method taking the command's
+//! arguments.
+//!
+//! ```no_run
+//! use agentx::api::{IssueTracker, NewIssue};
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let tracker = IssueTracker::open(".issues")?;
+//! let created = tracker.create(NewIssue::new("Fix login timeout").tag("auth"))?;
+//! tracker.start(&created.bug_num.to_string())?;
+//! for issue in tracker.query("status:active tag:auth")? {
+//!    println!("{}: {}", issue.id, issue.issue.metadata.title);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+   collections::{BTreeMap, HashMap, HashSet},
+   path::{Path, PathBuf},
+   sync::{
+      Arc,
+      atomic::{AtomicBool, Ordering},
+   },
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, DurationRound, SubsecRound, Utc};
+use clap::CommandFactory;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use smol_str::SmolStr;
+
+use crate::{
+   alias::{self, AliasProblem},
+   audit::{Actor, AuditEntry, AuditFilter},
+   batch::{self, BatchResult},
+   blockers::{BlockerAnalysis, analyze_blockers},
+   board::{self, BoardColumn, DEFAULT_COLUMNS, parse_columns},
+   body::{IssueBody, SectionKind},
+   brief::{Brief, brief},
+   bundle::{self, Bundle, Manifest, Relation, RelationStub},
+   calibration::{Calibration, EstimateHint, calibrate, hint},
+   cli::Cli,
+   commit_msg,
+   compact::{compact, outline, run_summarizer},
+   complete::{self, Candidates, Completion},
+   config::Config,
+   crypt,
+   cycles::{Cycle, CycleEdge, cycle, dependency_added},
+   digest::{Digest, digest, period_length},
+   export::{self, ExportFormat},
+   file_match::FileMatcher,
+   files,
+   flow::{
+      FlowMetrics, Granularity, SeriesPoint, SliceMetrics, flow_metrics, metrics_series,
+      parse_granularity, slice_metrics,
+   },
+   forecast::{Forecast, forecast},
+   fuzzy::rank_by_title,
+   gate::{self, GateResult},
+   git::head_branch,
+   github,
+   graph::IssueGraph,
+   history::{self, Revision},
+   impact::{MAX_RANKED_UNBLOCKS, rank_key, unblock_counts},
+   import::{self, ImportItem},
+   intake::{Draft, draft},
+   interactive::wizard::{display_preview, prompt_confirm, prompt_select},
+   issue::{Claim, Issue, IssueMetadata, IssueWithId, Priority, Status},
+   kinds::KindTemplate,
+   lint::{self, Finding},
+   merge::{IssueConflict, Side},
+   notify::{self, Alert},
+   order::{ExecutionOrder, order},
+   placeholders,
+   plan::{DailyBudget, Plan, next_sprint_tag, plan},
+   prompt::{PromptStatus, branch_issue},
+   query::Query,
+   references,
+   release::{self, ReleaseIssue, ReleaseStatus},
+   search::{self, SearchHit, Terms},
+   session::{Session, SessionSummary, summarize},
+   similarity::{DuplicatePair, SimilarIssue, find_duplicates, find_similar},
+   standup::{Standup, previous_working_day, standup},
+   storage::{BlocksRepair, Storage, VersionConflict},
+   tags::{TagStat, TagSuggestion, suggest_tags, tag_stats},
+   utils::{normalize_path, parse_duration, parse_effort, paths_overlap, repo_root},
+   watch::{Seen, WatchEvent, current_user},
+   workflow::{self, WorkflowState},
+};
+
+/// Fields for a new issue; everything but the title is optional
+#[derive(Debug, Clone)]
+pub struct NewIssue {
+   pub title:      String,
+   pub priority:   Priority,
+   pub tags:       Vec<String>,
+   pub files:      Vec<String>,
+   pub issue:      String,
+   pub impact:     String,
+   pub acceptance: String,
+   pub effort:     Option<String>,
+   pub context:    Option<String>,
+   pub milestone:  Option<String>,
+   pub kind:       Option<String>,
+}
+
+impl NewIssue {
+   pub fn new(title: impl Into<String>) -> Self {
+      Self {
+         title:      title.into(),
+         priority:   Priority::Medium,
+         tags:       Vec::new(),
+         files:      Vec::new(),
+         issue:      String::new(),
+         impact:     String::new(),
+         acceptance: String::new(),
+         effort:     None,
+         context:    None,
+         milestone:  None,
+         kind:       None,
+      }
+   }
+
+   pub fn priority(mut self, priority: Priority) -> Self {
+      self.priority = priority;
+      self
+   }
+
+   pub fn tag(mut self, tag: impl Into<String>) -> Self {
+      self.tags.push(tag.into());
+      self
+   }
+
+   pub fn file(mut self, file: impl Into<String>) -> Self {
+      self.files.push(file.into());
+      self
+   }
+
+   pub fn description(mut self, issue: impl Into<String>) -> Self {
+      self.issue = issue.into();
+      self
+   }
+
+   pub fn effort(mut self, effort: impl Into<String>) -> Self {
+      self.effort = Some(effort.into());
+      self
+   }
+
+   pub fn milestone(mut self, milestone: impl Into<String>) -> Self {
+      self.milestone = Some(milestone.into());
+      self
+   }
+
+   pub fn kind(mut self, kind: impl Into<String>) -> Self {
+      self.kind = Some(kind.into());
+      self
+   }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssueListResult {
+   pub status: String,
+   pub count:  usize,
+   pub issues: Vec<IssueWithId>,
+}
+
+/// Extra scoping for `list` and search beyond the status filter
+#[derive(Debug, Clone, Default)]
+pub struct ListScope {
+   /// Also include closed issues (closed issues double as the archive)
+   pub include_closed: bool,
+   /// `YYYY-MM-DD` or a relative offset such as `30d`
+   pub created_since:  Option<String>,
+   /// `YYYY-MM-DD` or a relative offset such as `30d`; implies `include_closed`
+   pub closed_since:   Option<String>,
+   /// Only issues with a file matching this glob, such as `src/tui/**`
+   pub file_glob:      Option<String>,
+   /// Only issues with a file matching this regex
+   pub file_regex:     Option<String>,
+}
+
+impl ListScope {
+   /// The status filter widened to closed issues when the scope asks for them
+   pub fn status<'a>(&self, status: &'a str) -> &'a str {
+      if status == "open" && (self.include_closed || self.closed_since.is_some()) {
+         "all"
+      } else {
+         status
+      }
+   }
+
+   /// `where_clause` with the date bounds and file patterns ANDed on
+   pub fn filter(&self, where_clause: Option<&str>) -> Result<Option<Query>> {
+      let mut terms = Vec::new();
+      if let Some(where_clause) = where_clause {
+         terms.push(Query::parse(&format!("({where_clause})"))?);
+      }
+      for (field, since) in [("created", &self.created_since), ("closed", &self.closed_since)] {
+         if let Some(since) = since {
+            terms.push(Query::since(field, since)?);
+         }
+      }
+      let root = repo_root().ok();
+      if let Some(glob) = &self.file_glob {
+         let matcher = FileMatcher::glob(glob, root.as_deref())?;
+         terms.push(Query::file(matcher, format!("file_glob:{glob:?}")));
+      }
+      if let Some(regex) = &self.file_regex {
+         let matcher = FileMatcher::regex(regex, root.as_deref())?;
+         terms.push(Query::file(matcher, format!("file_regex:{regex:?}")));
+      }
+      Ok(terms.into_iter().reduce(Query::and))
+   }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileIssue {
+   pub num:           u32,
+   pub title:         String,
+   pub priority:      String,
+   pub status:        String,
+   /// The issue's files that matched the looked-up path, or overlap the
+   /// files of the issue checked for conflicts
+   pub matched_files: Vec<String>,
+}
+
+/// An open issue as listed by `focus`, `ready` and `quick-wins`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankedIssue {
+   pub num:       u32,
+   pub title:     String,
+   pub priority:  Priority,
+   pub status:    Status,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub effort:    Option<String>,
+   pub files:     Vec<String>,
+   /// Number of open issues that transitively depend on this one
+   pub unblocks:  usize,
+   /// Days since the last status change or checkpoint (`quick-wins` only)
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub idle_days: Option<i64>,
+}
+
+impl RankedIssue {
+   pub fn new(issue_with_id: &IssueWithId, unblocks: usize) -> Self {
+      let meta = &issue_with_id.issue.metadata;
+      Self {
+         num: issue_with_id.id,
+         title: meta.title.to_string(),
+         priority: meta.priority,
+         status: meta.status,
+         effort: meta.effort.as_ref().map(|e| e.to_string()),
+         files: meta.files.iter().map(|f| f.to_string()).collect(),
+         unblocks,
+         idle_days: None,
+      }
+   }
+}
+
+/// Order of `quick-wins`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WinsSort {
+   /// Priority, boosted by how many issues each unblocks
+   #[default]
+   Impact,
+   /// Smallest estimate first
+   Effort,
+   /// Longest untouched first
+   Age,
+}
+
+impl std::str::FromStr for WinsSort {
+   type Err = anyhow::Error;
+
+   fn from_str(s: &str) -> Result<Self> {
+      match s.to_ascii_lowercase().as_str() {
+         "impact" => Ok(Self::Impact),
+         "effort" => Ok(Self::Effort),
+         "age" => Ok(Self::Age),
+         _ => anyhow::bail!("Unknown sort: {s}. Use: impact, effort, age"),
+      }
+   }
+}
+
+/// Outcome of compacting an issue's checkpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactResult {
+   pub bug_num:      u32,
+   /// Checkpoints folded into the history summary
+   pub compacted:    usize,
+   pub kept:         usize,
+   pub bytes_before: usize,
+   pub bytes_after:  usize,
+   /// Where the folded checkpoints were archived
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub archive:      Option<PathBuf>,
+}
+
+/// Outcome of `claim` or `release`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaimResult {
+   pub bug_num:    u32,
+   /// The lease now held, `None` after a release
+   pub claim:      Option<Claim>,
+   /// Another agent's live lease that `--force` took over
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub overridden: Option<Claim>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoctorResult {
+   pub alias_problems: Vec<AliasProblem>,
+   /// Issues whose `blocks` was out of step with `depends_on`
+   pub blocks_repairs: Vec<BlocksRepair>,
+   /// Paths recorded on open issues that no longer exist
+   pub missing_files:  Vec<MissingFile>,
+   /// Whether the `blocks` lists were rewritten
+   pub fixed:          bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissingFile {
+   pub bug_num: u32,
+   pub path:    String,
+}
+
+/// Outcome of `files remap`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilesRemapResult {
+   pub old:      String,
+   pub new:      String,
+   pub remapped: Vec<RemappedFiles>,
+   /// Whether the changes were only shown, not saved
+   pub dry_run:  bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemappedFiles {
+   pub bug_num: u32,
+   pub title:   String,
+   pub before:  Vec<String>,
+   pub after:   Vec<String>,
+}
+
+/// Outcome of `release cut`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseCutResult {
+   pub version:       String,
+   /// Whether the release was cut; not while `critical_open` has issues
+   pub cut:           bool,
+   pub critical_open: Vec<ReleaseIssue>,
+   /// Other open issues still targeting the version
+   pub carried_over:  Vec<ReleaseIssue>,
+   /// The changelog section, once cut
+   pub changelog:     Option<String>,
+   /// The changelog file the section was added to
+   pub written_to:    Option<String>,
+}
+
+/// How an issue changed over time, one entry per revision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssueDiffResult {
+   pub bug_num:   u32,
+   pub title:     String,
+   pub revisions: Vec<Revision>,
+}
+
+/// Outcome of a `crypt` command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CryptResult {
+   pub key_id:    String,
+   pub locked:    bool,
+   /// Where `crypt init` wrote the new key
+   #[serde(default, skip_serializing_if = "Option::is_none")]
+   pub key_file:  Option<PathBuf>,
+   /// Issue files encrypted or decrypted
+   pub rewritten: usize,
+}
+
+/// Why an issue sits where it does in `focus`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhyResult {
+   pub bug_num:     u32,
+   pub title:       String,
+   pub status:      Status,
+   pub priority:    Priority,
+   /// 1-based position in `focus` order; `None` once closed
+   pub rank:        Option<usize>,
+   pub open_issues: usize,
+   /// Priority and fan-out part of the sort key, lower ranks first
+   pub score:       i32,
+   pub unblocks:    usize,
+   pub age_days:    i64,
+   /// Days since the last status change or checkpoint
+   pub idle_days:   i64,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub effort:      Option<String>,
+   /// Open issues it waits on, directly or through their own dependencies
+   pub waiting_on:  Vec<u32>,
+   /// The issues ranked right above it
+   pub ahead:       Vec<RankedIssue>,
+   /// What the ranking takes into account, one line per factor
+   pub counted:     Vec<String>,
+   /// What is worth knowing but doesn't move the issue in `focus`
+   pub not_counted: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockedIssue {
+   pub num:      u32,
+   pub title:    String,
+   pub reason:   Option<String>,
+   pub priority: Priority,
+}
+
+/// An open issue in the dependency graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphNode {
+   pub id:         u32,
+   pub title:      String,
+   pub status:     Status,
+   pub priority:   Priority,
+   pub depends_on: Vec<u32>,
+   /// What `--cluster` groups nodes by
+   #[serde(default)]
+   pub tags:       Vec<String>,
+   #[serde(default)]
+   pub milestone:  Option<String>,
+}
+
+impl GraphNode {
+   fn new(issue_with_id: &IssueWithId) -> Self {
+      let meta = &issue_with_id.issue.metadata;
+      Self {
+         id:         issue_with_id.id,
+         title:      meta.title.to_string(),
+         status:     meta.status,
+         priority:   meta.priority,
+         depends_on: meta.depends_on.clone(),
+         tags:       meta.tags.iter().map(|t| t.to_string()).collect(),
+         milestone:  meta.milestone.as_ref().map(|m| m.to_string()),
+      }
+   }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CriticalPathResult {
+   pub length:        usize,
+   pub chain:         Vec<CriticalStep>,
+   /// Effort along the whole chain, in minutes
+   pub total_minutes: u32,
+   /// Issues on the chain counted at `effort.unestimated` for want of an
+   /// estimate
+   pub unestimated:   usize,
+   /// Dependency cycles, which are skipped when looking for the chain
+   pub cycles:        Vec<Vec<u32>>,
+}
+
+/// One issue on the critical path, with the effort up to and including it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CriticalStep {
+   #[serde(flatten)]
+   pub node:               GraphNode,
+   pub effort_minutes:     u32,
+   pub cumulative_minutes: u32,
+   /// Whether `effort_minutes` is the issue's own estimate rather than the
+   /// configured default
+   pub estimated:          bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CyclesResult {
+   /// Cycles left, each with the edge suggested for dropping
+   pub cycles:  Vec<Cycle>,
+   /// Edges dropped by `--fix`
+   pub dropped: Vec<CycleEdge>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportResult {
+   pub created:   Vec<u32>,
+   /// Issues created, or that a dry run would create
+   pub count:     usize,
+   /// Issues matched by `external_id` and changed to match the file
+   #[serde(skip_serializing_if = "Vec::is_empty", default)]
+   pub updated:   Vec<u32>,
+   /// Issues matched by `external_id` that already matched the file
+   #[serde(skip_serializing_if = "Vec::is_empty", default)]
+   pub unchanged: Vec<u32>,
+   /// Stopped early; the issues created until then were removed again
+   pub cancelled: bool,
+   /// Items not imported because of the cancellation
+   #[serde(default)]
+   pub skipped:   usize,
+   #[serde(default)]
+   pub dry_run:   bool,
+   /// What a dry run would create, or a cancelled import was creating
+   #[serde(skip_serializing_if = "Vec::is_empty", default)]
+   pub planned:   Vec<ImportPreview>,
+}
+
+/// An issue an import creates or updates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportPreview {
+   pub title:    String,
+   pub priority: Priority,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub kind:     Option<String>,
+   /// The issue with the same `external_id`, which the import updates
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub updates:  Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportUrlResult {
+   pub bug_num:    u32,
+   pub title:      String,
+   pub status:     String,
+   pub source_url: String,
+   pub path:       String,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub alias:      Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleResult {
+   pub bug_num:     u32,
+   pub path:        String,
+   /// Related issues recorded as stubs
+   pub relations:   usize,
+   pub checkpoints: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleImportResult {
+   pub bug_num:  u32,
+   pub title:    String,
+   pub path:     String,
+   /// Number of the issue in the repository it was bundled from
+   pub from:     u32,
+   /// Relations restored, with the local issue each stub matched
+   pub linked:   Vec<RelinkedStub>,
+   /// Relations dropped because no local issue matched their stub
+   pub unlinked: Vec<RelationStub>,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub alias:    Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelinkedStub {
+   pub relation: Relation,
+   pub from:     u32,
+   pub to:       u32,
+   pub title:    String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResult {
+   pub query:   String,
+   pub count:   usize,
+   pub results: Vec<SearchHit>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportResult {
+   pub format:    String,
+   pub mime_type: String,
+   pub count:     usize,
+   pub text:      String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsSeriesResult {
+   pub granularity: Granularity,
+   pub from:        DateTime<Utc>,
+   pub to:          DateTime<Utc>,
+   pub points:      Vec<SeriesPoint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsResult {
+   pub period:               String,
+   pub total_open:           usize,
+   pub total_closed:         usize,
+   pub opened_in_period:     usize,
+   pub closed_in_period:     usize,
+   pub avg_close_time_hours: i64,
+   /// Open issues per priority
+   pub by_priority:          BTreeMap<String, usize>,
+   /// Open issues per status
+   pub by_status:            BTreeMap<String, usize>,
+   pub flow:                 FlowMetrics,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub by_tag:               Option<Vec<SliceMetrics>>,
+   /// Per assignee, or for unassigned issues the agent of their latest
+   /// claim, live or lapsed
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub by_assignee:          Option<Vec<SliceMetrics>>,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub by_kind:              Option<Vec<SliceMetrics>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextResult {
+   pub active:         Vec<IssueWithId>,
+   pub blocked:        Vec<IssueWithId>,
+   pub high_priority:  Vec<IssueWithId>,
+   pub ready_to_start: Vec<IssueWithId>,
+   /// Open issues in scope, leaving out deferred ones
+   pub total_open:     usize,
+   /// Deferred (backlog) issues in scope, left out of every section
+   #[serde(default)]
+   pub deferred:       usize,
+   /// Number of open issues each issue transitively unblocks (non-zero only)
+   #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+   pub unblocks:       BTreeMap<u32, usize>,
+   /// Issues cut from each section by the cap, by section name
+   #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+   pub omitted:        BTreeMap<String, usize>,
+   /// In-progress and shown ready issues against `daily_capacity`, when set
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub budget:         Option<DailyBudget>,
+}
+
+/// Which issues `context` covers, and how many it shows per section
+#[derive(Debug, Clone, Default)]
+pub struct ContextScope {
+   /// Only issues with any of these tags
+   pub tags:      Vec<String>,
+   pub milestone: Option<String>,
+   /// Most issues per section; ready-to-start shows 5 unless given
+   pub max_items: Option<usize>,
+}
+
+impl ContextScope {
+   fn includes(&self, issue: &IssueWithId) -> bool {
+      let meta = &issue.issue.metadata;
+      (self.tags.is_empty()
+         || meta
+            .tags
+            .iter()
+            .any(|t| self.tags.iter().any(|w| t.eq_ignore_ascii_case(w))))
+         && self
+            .milestone
+            .as_ref()
+            .is_none_or(|m| meta.milestone.as_deref() == Some(m.as_str()))
+   }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShowResult {
+   pub num:            u32,
+   pub title:          String,
+   pub priority:       String,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub kind:           Option<String>,
+   pub status:         String,
+   pub body:           String,
+   pub tags:           Vec<String>,
+   pub files:          Vec<String>,
+   pub effort:         Option<String>,
+   pub created:        DateTime<Utc>,
+   pub started:        Option<DateTime<Utc>>,
+   pub closed:         Option<DateTime<Utc>>,
+   pub blocked_reason: Option<String>,
+   pub milestone:      Option<String>,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub assignee:       Option<String>,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub target_version: Option<String>,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub due:            Option<DateTime<Utc>>,
+   /// Issues the body mentions
+   #[serde(skip_serializing_if = "Vec::is_empty", default)]
+   pub references:     Vec<LinkedIssue>,
+   /// Issues whose bodies mention this one
+   #[serde(skip_serializing_if = "Vec::is_empty", default)]
+   pub referenced_by:  Vec<LinkedIssue>,
+   /// Where the issue was imported from
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub source_url:     Option<String>,
+   /// Key of the issue in the spec it was imported from
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub external_id:    Option<String>,
+   /// The issue's markdown file
+   pub path:           String,
+}
+
+/// Another issue a cross-reference points to or comes from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkedIssue {
+   pub num:    u32,
+   pub title:  String,
+   pub status: String,
+}
+
+impl LinkedIssue {
+   pub(crate) fn of(issue_with_id: &IssueWithId) -> Self {
+      Self {
+         num:    issue_with_id.id,
+         title:  issue_with_id.issue.metadata.title.to_string(),
+         status: issue_with_id.issue.metadata.status.to_string(),
+      }
+   }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateIssueResult {
+   pub bug_num:        u32,
+   pub title:          String,
+   pub path:           String,
+   /// Alias generated from the title when `auto_alias` is on
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub alias:          Option<String>,
+   pub similar_issues: Vec<SimilarIssue>,
+   pub suggested_tags: Vec<TagSuggestion>,
+   /// Corrected effort when estimates like this one run long or short
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub estimate_hint:  Option<EstimateHint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusUpdateResult {
+   pub bug_num:            u32,
+   pub status:             String,
+   pub message:            Option<String>,
+   /// Dependents the change left without open dependencies
+   #[serde(default, skip_serializing_if = "Vec::is_empty")]
+   pub unblocked:          Vec<UnblockedIssue>,
+   /// Blocked issues that were waiting on the closed issue
+   #[serde(default, skip_serializing_if = "Vec::is_empty")]
+   pub unblock_candidates: Vec<UnblockCandidate>,
+   /// Other in-progress issues touching the same files, on start
+   #[serde(default, skip_serializing_if = "Vec::is_empty")]
+   pub file_conflicts:     Vec<FileIssue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnblockedIssue {
+   pub bug_num:   u32,
+   pub title:     String,
+   /// Moved out of the backlog because `activate_unblocked` is set
+   pub activated: bool,
+}
+
+/// A blocked issue whose block reason or dependencies name a closed issue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnblockCandidate {
+   pub bug_num:   u32,
+   pub title:     String,
+   pub reason:    Option<String>,
+   /// Whether it was unblocked as part of the close
+   pub unblocked: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummaryCheckpoint {
+   pub at:   DateTime<Utc>,
+   pub note: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointedIssue {
+   pub bug_num:     u32,
+   pub title:       String,
+   pub checkpoints: Vec<SummaryCheckpoint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummaryResult {
+   pub since:        DateTime<Utc>,
+   pub hours:        u64,
+   pub started:      Vec<u32>,
+   pub closed:       Vec<u32>,
+   pub checkpointed: Vec<CheckpointedIssue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchResult {
+   pub bug_num:  u32,
+   pub watching: bool,
+   pub watchers: Vec<String>,
+}
+
+/// Changes to a watched issue since its watcher last looked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchedChange {
+   pub bug_num: u32,
+   pub title:   String,
+   pub events:  Vec<WatchEvent>,
+}
+
+/// A ref a bulk command couldn't apply to, and why
+pub(crate) type BulkError = (String, String);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependResult {
+   pub bug_num:    u32,
+   pub added:      Vec<u32>,
+   pub removed:    Vec<u32>,
+   pub depends_on: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefCandidate {
+   pub num:    u32,
+   pub title:  String,
+   pub status: String,
+   pub score:  u8,
+}
+
+/// How bug references that aren't a number or alias are resolved
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RefMode {
+   /// Only numbers and aliases are accepted
+   Exact,
+   /// Fall back to title matching; ambiguous matches are an error
+   #[default]
+   Fuzzy,
+   /// Fall back to title matching; ambiguous matches prompt for a choice
+   Interactive,
+}
+
+/// Stops a long operation (an import or batch) between items once
+/// cancelled; clones share the flag
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+   pub fn cancel(&self) {
+      self.0.store(true, Ordering::Relaxed);
+   }
+
+   pub fn is_cancelled(&self) -> bool {
+      self.0.load(Ordering::Relaxed)
+   }
+}
+
+/// An issue tracker rooted at an issues directory
+///
+/// Bug references accept numbers, aliases and (unless
+/// [`IssueTracker::exact_refs`] is set) title fragments, like the CLI.
+#[derive(Debug, Clone)]
+pub struct IssueTracker {
+   storage:  Storage,
+   config:   Config,
+   ref_mode: RefMode,
+   force:    bool,
+   /// Skip confirmations and the bulk change limit
+   yes:      bool,
+   agent:    Option<String>,
+   cancel:   CancelToken,
+}
+
+impl IssueTracker {
+   /// Open the tracker at `dir`, the directory holding `issues/`
+   ///
+   /// The default configuration is used rather than searching for an
+   /// `.agentxrc.yaml`; pass one explicitly with [`IssueTracker::with_config`].
+   pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+      let dir = dir.into();
+      if dir.exists() && !dir.is_dir() {
+         anyhow::bail!("Not a directory: {}", dir.display());
+      }
+      Ok(Self::new(Storage::new(dir)))
+   }
+
+   /// A tracker that keeps its issues in memory and never touches the disk,
+   /// for tests and throwaway use
+   pub fn in_memory() -> Self {
+      Self::new(Storage::in_memory())
+   }
+
+   /// A tracker over `storage`, with the default configuration
+   pub fn new(storage: Storage) -> Self {
+      let config = Config::default();
+      Self {
+         storage: storage.with_issue_prefix(&config.issue_prefix),
+         config,
+         ref_mode: RefMode::default(),
+         force: false,
+         yes: false,
+         agent: None,
+         cancel: CancelToken::default(),
+      }
+   }
+
+   /// Stop imports and batches between items once `cancel` is cancelled
+   pub fn with_cancel(mut self, cancel: CancelToken) -> Self {
+      self.cancel = cancel;
+      self
+   }
+
+   /// Act as `agent` for claims, instead of `$AGENTX_AGENT` or the git user
+   pub fn with_agent(mut self, agent: Option<&str>) -> Self {
+      self.agent = agent.map(String::from);
+      self
+   }
+
+   /// Credit the changes made through this tracker to `actor` in the
+   /// audit trail
+   pub fn with_actor(mut self, actor: Actor) -> Self {
+      self.storage = self.storage.with_actor(actor);
+      self
+   }
+
+   /// Allow status changes the transition rules would refuse
+   pub fn with_force(mut self, force: bool) -> Self {
+      self.force = force;
+      self
+   }
+
+   /// Apply bulk changes without asking, however many issues they touch
+   pub fn with_yes(mut self, yes: bool) -> Self {
+      self.yes = yes;
+      self
+   }
+
+   pub fn with_ref_mode(mut self, ref_mode: RefMode) -> Self {
+      self.ref_mode = ref_mode;
+      self
+   }
+
+   pub fn with_config(mut self, config: Config) -> Self {
+      self.storage = self.storage.with_issue_prefix(&config.issue_prefix);
+      self.config = config;
+      self
+   }
+
+   pub fn config(&self) -> &Config {
+      &self.config
+   }
+
+   pub fn storage(&self) -> &Storage {
+      &self.storage
+   }
+
+   /// How bug references that aren't a number or alias are resolved
+   pub fn ref_mode(&self) -> RefMode {
+      self.ref_mode
+   }
+
+   /// Act as `agent` when claiming, and when starting or checkpointing
+   /// claimed issues
+   pub fn as_agent(self, agent: &str) -> Self {
+      self.with_agent(Some(agent))
+   }
+
+   /// Credit changes to `actor` in the audit trail
+   pub fn as_actor(self, actor: &str) -> Self {
+      self.with_actor(Actor { name: actor.to_string(), via: Some("api".to_string()) })
+   }
+
+   /// Only accept numbers and aliases as bug references
+   pub fn exact_refs(self) -> Self {
+      self.with_ref_mode(RefMode::Exact)
+   }
+
+   pub fn resolve(&self, bug_ref: &str) -> Result<u32> {
+      self.resolve_ref(bug_ref)
+   }
+
+   pub fn get(&self, id: u32) -> Result<Issue> {
+      self.storage.load_issue(id)
+   }
+
+   pub fn show(&self, bug_ref: &str) -> Result<ShowResult> {
+      self.show_data(bug_ref)
+   }
+
+   pub fn create(&self, new: NewIssue) -> Result<CreateIssueResult> {
+      self.create_issue_data(
+         new.title,
+         &new.priority.to_string(),
+         new.tags,
+         new.files,
+         new.issue,
+         new.impact,
+         new.acceptance,
+         new.effort,
+         new.context,
+         new.milestone,
+         new.kind,
+      )
+   }
+
+   /// Edit an issue's metadata in place; status changes made through
+   /// `meta.status` are recorded as transitions
+   pub fn update(&self, id: u32, update: impl FnOnce(&mut IssueMetadata)) -> Result<()> {
+      self.storage.update_issue_metadata(id, update)
+   }
+
+   pub fn start(&self, bug_ref: &str) -> Result<StatusUpdateResult> {
+      self.start_data(bug_ref)
+   }
+
+   /// Take or renew an exclusive lease on an issue for `ttl` (e.g. `2h`)
+   pub fn claim(&self, bug_ref: &str, ttl: &str) -> Result<ClaimResult> {
+      self.claim_data(bug_ref, ttl)
+   }
+
+   pub fn release(&self, bug_ref: &str) -> Result<ClaimResult> {
+      self.release_data(bug_ref)
+   }
+
+   pub fn block(&self, bug_ref: &str, reason: impl Into<String>) -> Result<StatusUpdateResult> {
+      self.block_data(bug_ref, reason.into())
+   }
+
+   /// Close an issue; blocked issues waiting on it are reported in
+   /// `unblock_candidates` but left blocked
+   pub fn close(&self, bug_ref: &str, message: Option<String>) -> Result<StatusUpdateResult> {
+      self.close_data(bug_ref, message, false)
+   }
+
+   /// Close an issue and unblock the blocked issues waiting on it
+   pub fn close_unblocking(
+      &self,
+      bug_ref: &str,
+      message: Option<String>,
+   ) -> Result<StatusUpdateResult> {
+      self.close_data(bug_ref, message, true)
+   }
+
+   pub fn reopen(&self, bug_ref: &str) -> Result<StatusUpdateResult> {
+      self.open_data(bug_ref)
+   }
+
+   pub fn defer(&self, bug_ref: &str) -> Result<StatusUpdateResult> {
+      self.defer_data(bug_ref)
+   }
+
+   pub fn activate(&self, bug_ref: &str) -> Result<StatusUpdateResult> {
+      self.activate_data(bug_ref)
+   }
+
+   pub fn checkpoint(&self, bug_ref: &str, note: impl Into<String>) -> Result<StatusUpdateResult> {
+      self.checkpoint_data(bug_ref, note.into())
+   }
+
+   /// Watch an issue as the current git user
+   pub fn watch(&self, bug_ref: &str) -> Result<WatchResult> {
+      self.watch_data(bug_ref, false)
+   }
+
+   pub fn unwatch(&self, bug_ref: &str) -> Result<WatchResult> {
+      self.watch_data(bug_ref, true)
+   }
+
+   /// Issues by location: `open`, `closed` or `all`
+   pub fn list(&self, status: &str) -> Result<Vec<IssueWithId>> {
+      Ok(self.list_data(status, None)?.issues)
+   }
+
+   /// Open and closed issues matching a query expression (see [`Query`])
+   pub fn query(&self, expr: &str) -> Result<Vec<IssueWithId>> {
+      let query = Query::parse(expr)?;
+      Ok(self.list_data("all", Some(&query))?.issues)
+   }
+
+   pub fn context(&self) -> Result<ContextResult> {
+      self.context_data(&Default::default())
+   }
+
+   pub fn for_file(&self, path: &str) -> Result<Vec<FileIssue>> {
+      self.for_file_data(path)
+   }
+
+   /// Other in-progress issues whose files overlap this one's
+   pub fn file_conflicts(&self, bug_ref: &str) -> Result<Vec<FileIssue>> {
+      self.file_conflicts_data(bug_ref)
+   }
+
+   pub fn focus(&self) -> Result<Vec<RankedIssue>> {
+      self.focus_data()
+   }
+
+   /// Where an issue ranks in [`IssueTracker::focus`] order, and why
+   pub fn why(&self, bug_ref: &str) -> Result<WhyResult> {
+      self.why_data(bug_ref)
+   }
+
+   pub fn ready(&self) -> Result<Vec<RankedIssue>> {
+      self.ready_data()
+   }
+
+   pub fn blocked(&self) -> Result<Vec<BlockedIssue>> {
+      self.blocked_data()
+   }
+
+   /// Issues estimated at or under `threshold` (e.g. `1h`) that wait on no
+   /// unfinished dependency, ranked by priority and issues unblocked
+   pub fn quick_wins(&self, threshold: &str) -> Result<Vec<RankedIssue>> {
+      self.quick_wins_data(threshold, false, WinsSort::Impact)
+   }
+
+   /// Metrics over `day`, `week`, `month` or `all`
+   pub fn metrics(&self, period: &str) -> Result<MetricsResult> {
+      self.metrics_data(period, false, false, false)
+   }
+
+   /// Add and remove dependencies of `bug_ref`; adding one that would create a
+   /// cycle is an error
+   pub fn depend(&self, bug_ref: &str, add: &[&str], remove: &[&str]) -> Result<DependResult> {
+      let owned = |refs: &[&str]| refs.iter().map(|r| r.to_string()).collect();
+      self.depend_data(bug_ref, owned(add), owned(remove))
+   }
+
+   /// The dependency graph of open issues, optionally limited to the issues
+   /// connected to `focus`
+   pub fn graph(&self, focus: Option<&str>) -> Result<Vec<GraphNode>> {
+      self.deps_graph_data(focus, None)
+   }
+
+   pub fn critical_path(&self) -> Result<CriticalPathResult> {
+      self.critical_path_data()
+   }
+
+   /// Open issues that directly depend on `id`
+   pub fn dependents(&self, id: u32) -> Result<Vec<u32>> {
+      Ok(self.dependency_graph()?.dependents(id).to_vec())
+   }
+
+   /// The dependency graph of the open issues
+   pub fn dependency_graph(&self) -> Result<IssueGraph> {
+      Ok(IssueGraph::from_issues(&self.storage.list_open_issues()?))
+   }
+
+   /// How many open issues each open issue transitively unblocks
+   pub fn unblock_counts(&self) -> Result<HashMap<u32, usize>> {
+      Ok(unblock_counts(&self.storage.list_open_issues()?))
+   }
+
+   /// Issues started, closed or checkpointed in the last `hours`
+   pub fn summary(&self, hours: u64) -> Result<SummaryResult> {
+      self.summary_data(Some(hours))
+   }
+
+   /// Dependency cycles among open issues
+   pub fn cycles(&self) -> Result<Vec<Vec<u32>>> {
+      self.cycles_data()
+   }
+}
+
+// The operations behind the CLI's commands, taking their arguments the way
+// the commands do
+impl IssueTracker {
+   fn is_cancelled(&self) -> bool {
+      self.cancel.is_cancelled()
+   }
+
+   /// Who claims and releases issues: the configured agent,
+   /// `$AGENTX_AGENT`, or the git user
+   fn agent(&self) -> String {
+      self
+         .agent
+         .clone()
+         .or_else(|| std::env::var("AGENTX_AGENT").ok().filter(|a| !a.is_empty()))
+         .unwrap_or_else(current_user)
+   }
+
+   /// Whether a bulk change to `targets` may go ahead: refused beyond
+   /// `bulk_limit` issues without `--yes`, and confirmed at a terminal
+   /// after listing them
+   pub(crate) fn confirm_bulk(&self, action: &str, targets: &[u32], json: bool) -> Result<bool> {
+      if self.yes || targets.is_empty() {
+         return Ok(true);
+      }
+      let limit = self.config.bulk_limit;
+      if limit > 0 && targets.len() > limit {
+         anyhow::bail!(
+            "Refusing to {action} {} issues without --yes (bulk_limit is {limit})",
+            targets.len()
+         );
+      }
+      if json || self.ref_mode != RefMode::Interactive {
+         return Ok(true);
+      }
+      for &bug_num in targets {
+         let issue = self.storage.load_issue(bug_num)?;
+         println!(
+            "   {} [{}]: {}",
+            self.config.format_issue_ref(bug_num),
+            issue.metadata.status,
+            issue.metadata.title
+         );
+      }
+      let confirmed =
+         prompt_confirm(&format!("Go ahead and {action} these {} issues?", targets.len()), false)?;
+      if !confirmed {
+         println!("Cancelled; nothing was changed");
+      }
+      Ok(confirmed)
+   }
+
+   /// Title matches for a free-text reference, best first
+   ///
+   /// Open issues are searched first; closed issues are only considered when
+   /// nothing open matches.
+   pub fn ref_candidates_data(&self, bug_ref: &str) -> Result<Vec<RefCandidate>> {
+      let mut ranked = rank_by_title(bug_ref, self.storage.list_open_issues()?);
+      if ranked.is_empty() {
+         ranked = rank_by_title(bug_ref, self.storage.list_closed_issues()?);
+      }
+
+      Ok(ranked
+         .into_iter()
+         .map(|(score, issue_with_id)| RefCandidate {
+            num: issue_with_id.id,
+            title: issue_with_id.issue.metadata.title.to_string(),
+            status: issue_with_id.issue.metadata.status.to_string(),
+            score,
+         })
+         .collect())
+   }
+
+   /// Completions for the last of `words`, the arguments after `agentx`
+   pub fn completions_data(&self, words: &[String]) -> Result<Vec<Completion>> {
+      let open = self.storage.list_open_issues()?;
+      let closed = self.storage.list_closed_issues()?;
+      let all: Vec<&IssueWithId> = open.iter().chain(&closed).collect();
+
+      let mut aliases: Vec<(String, u32)> = self.storage.load_aliases()?.into_iter().collect();
+      aliases.sort();
+      let mut tags: Vec<String> = all
+         .iter()
+         .flat_map(|i| &i.issue.metadata.tags)
+         .map(|t| t.to_string())
+         .collect();
+      tags.sort();
+      tags.dedup();
+      let mut milestones: Vec<String> = all
+         .iter()
+         .filter_map(|i| i.issue.metadata.milestone.as_ref())
+         .map(|m| m.to_string())
+         .collect();
+      milestones.sort();
+      milestones.dedup();
+      let candidates = Candidates {
+         issues: all
+            .iter()
+            .map(|i| {
+               (i.id, i.issue.metadata.title.to_string(), i.issue.metadata.status.to_string())
+            })
+            .collect(),
+         aliases,
+         tags,
+         milestones,
+      };
+
+      let mut root = Cli::command();
+      root.build();
+      Ok(complete::complete(&root, words, &candidates))
+   }
+
+   /// Resolve a bug reference: a number, an alias, or (unless in exact mode) a
+   /// fragment of the issue title
+   pub fn resolve_ref(&self, bug_ref: &str) -> Result<u32> {
+      self.resolve_ref_with(bug_ref, false)
+   }
+
+   /// Resolve a bug reference for a command that closes the issue: a title
+   /// fragment matching more than one issue is ambiguous even when one match
+   /// ranks higher, so the user must pick (interactive mode) or it's an error
+   pub fn resolve_ref_strict(&self, bug_ref: &str) -> Result<u32> {
+      self.resolve_ref_with(bug_ref, true)
+   }
+
+   fn resolve_ref_with(&self, bug_ref: &str, strict: bool) -> Result<u32> {
+      let namespace = self.config.alias_namespace.as_deref();
+      let err = match self.storage.resolve_bug_ref(bug_ref, namespace) {
+         Ok(num) => return Ok(num),
+         Err(err) if self.ref_mode == RefMode::Exact => return Err(err),
+         Err(err) => err,
+      };
+
+      let candidates = self.ref_candidates_data(bug_ref)?;
+      let Some(best) = candidates.first() else {
+         return Err(err);
+      };
+      let best_count = candidates
+         .iter()
+         .take_while(|c| c.score == best.score)
+         .count();
+      if best_count == 1 && (!strict || candidates.len() == 1) {
+         return Ok(best.num);
+      }
+
+      if self.ref_mode == RefMode::Interactive {
+         let items: Vec<String> = candidates
+            .iter()
+            .map(|c| format!("{} {} [{}]", self.config.format_issue_ref(c.num), c.title, c.status))
+            .collect();
+         let choice = prompt_select(&format!("Which issue did you mean by '{bug_ref}'?"), &items)?;
+         return Ok(candidates[choice].num);
+      }
+
+      let listing: Vec<String> = candidates
+         .iter()
+         .take(10)
+         .map(|c| format!("  {} {}", self.config.format_issue_ref(c.num), c.title))
+         .collect();
+      anyhow::bail!(
+         "Ambiguous bug reference '{bug_ref}' matches {} issues:\n{}",
+         candidates.len(),
+         listing.join("\n")
+      )
+   }
+
+   /// Issues in `status`: `open` (everything not closed), `closed`, `all`,
+   /// or any other status by a name [`Status::parse`] takes, e.g. `active`
+   pub fn list_data(&self, status: &str, filter: Option<&Query>) -> Result<IssueListResult> {
+      let mut issues = match status {
+         "open" => self.storage.list_open_issues()?,
+         "closed" => self.storage.list_closed_issues()?,
+         "all" => {
+            let mut all = self.storage.list_open_issues()?;
+            all.extend(self.storage.list_closed_issues()?);
+            all
+         },
+         _ => {
+            let only = Status::parse(status).map_err(|_| {
+               anyhow::anyhow!(
+                  "Invalid status: {status}. Use: open, closed, all, or one of active, blocked, \
+                   done, backlog"
+               )
+            })?;
+            let mut issues = self.storage.list_open_issues()?;
+            issues.retain(|i| i.issue.metadata.status == only);
+            issues
+         },
+      };
+
+      if let Some(query) = filter {
+         issues = query.filter(issues);
+      }
+      issues.sort_by_key(IssueWithId::stable_key);
+
+      Ok(IssueListResult { status: status.to_string(), count: issues.len(), issues })
+   }
+
+   /// Issues matching every word of `query`, title matches first, then tag
+   /// and body matches; at most `limit`
+   pub fn search_data(
+      &self,
+      query: &str,
+      status: &str,
+      scope: &ListScope,
+      limit: Option<usize>,
+   ) -> Result<SearchResult> {
+      let filter = scope.filter(None)?;
+      let issues = self
+         .list_data(scope.status(status), filter.as_ref())?
+         .issues;
+      let results = search::search(&Terms::parse(query), &issues, limit);
+      Ok(SearchResult { query: query.to_string(), count: results.len(), results })
+   }
+
+   pub fn show_data(&self, bug_ref: &str) -> Result<ShowResult> {
+      let bug_num = self.resolve_ref(bug_ref)?;
+      let issue = self.storage.load_issue(bug_num)?;
+      let (references, referenced_by) = self.cross_references(bug_num, &issue)?;
+
+      Ok(ShowResult {
+         num:            bug_num,
+         title:          issue.metadata.title.to_string(),
+         priority:       issue.metadata.priority.to_string(),
+         status:         issue.metadata.status.to_string(),
+         body:           issue.body.to_string(),
+         tags:           issue.metadata.tags.iter().map(|s| s.to_string()).collect(),
+         files:          issue.metadata.files.iter().map(|s| s.to_string()).collect(),
+         effort:         issue.metadata.effort.as_ref().map(|s| s.to_string()),
+         created:        issue.metadata.created,
+         started:        issue.metadata.started,
+         closed:         issue.metadata.closed,
+         blocked_reason: issue
+            .metadata
+            .blocked_reason
+            .as_ref()
+            .map(|s| s.to_string()),
+         milestone:      issue.metadata.milestone.as_ref().map(|s| s.to_string()),
+         assignee:       issue.metadata.assignee.as_ref().map(|s| s.to_string()),
+         kind:           issue.metadata.kind.as_ref().map(|s| s.to_string()),
+         target_version: issue
+            .metadata
+            .target_version
+            .as_ref()
+            .map(|s| s.to_string()),
+         due:            issue.metadata.due,
+         references:     references.iter().map(LinkedIssue::of).collect(),
+         referenced_by:  referenced_by.iter().map(LinkedIssue::of).collect(),
+         source_url:     issue.metadata.source_url.as_ref().map(|s| s.to_string()),
+         external_id:    issue.metadata.external_id.as_ref().map(|s| s.to_string()),
+         path:           self.storage.find_issue_file(bug_num)?.display().to_string(),
+      })
+   }
+
+   /// The issues `issue` mentions and those mentioning it, open and closed
+   pub(crate) fn cross_references(
+      &self,
+      bug_num: u32,
+      issue: &Issue,
+   ) -> Result<(Vec<IssueWithId>, Vec<IssueWithId>)> {
+      let mut all = self.storage.list_open_issues()?;
+      all.extend(self.storage.list_closed_issues()?);
+      let (mut references, mut referenced_by) = (Vec::new(), Vec::new());
+      for other in all {
+         if issue.metadata.references.contains(&other.id) {
+            references.push(other.clone());
+         }
+         if other.issue.metadata.references.contains(&bug_num) {
+            referenced_by.push(other);
+         }
+      }
+      references.sort_by_key(|i| i.id);
+      referenced_by.sort_by_key(|i| i.id);
+      Ok((references, referenced_by))
+   }
+
+   /// An issue trimmed to about `budget` tokens, with at most its last
+   /// `checkpoints` checkpoints
+   pub fn brief_data(&self, bug_ref: &str, budget: usize, checkpoints: usize) -> Result<Brief> {
+      let bug_num = self.resolve_ref(bug_ref)?;
+      let issue = self.storage.load_issue(bug_num)?;
+      let issue_ref = self.config.format_issue_ref(bug_num);
+      Ok(brief(&issue, bug_num, &issue_ref, budget, checkpoints))
+   }
+
+   #[allow(clippy::too_many_arguments)]
+   pub fn create_issue_data(
+      &self,
+      title: String,
+      priority_str: &str,
+      tags: Vec<String>,
+      files: Vec<String>,
+      issue: String,
+      impact: String,
+      acceptance: String,
+      effort: Option<String>,
+      context: Option<String>,
+      milestone: Option<String>,
+      kind: Option<String>,
+   ) -> Result<CreateIssueResult> {
+      let priority = match priority_str {
+         "critical" => Priority::Critical,
+         "high" => Priority::High,
+         "medium" => Priority::Medium,
+         "low" => Priority::Low,
+         _ => anyhow::bail!("Invalid priority: {priority_str}"),
+      };
+
+      let env = placeholders::Env::at(&std::env::current_dir()?, Utc::now());
+      let fill = |text: String| {
+         if text.contains("{{") {
+            placeholders::expand(&text, |name| env.value(name))
+         } else {
+            text
+         }
+      };
+      let title = fill(title);
+      // Sections left empty start from the kind's template
+      let kind = kind.map(|k| self.config.kind(&k)).transpose()?;
+      let template = |text: String, pick: fn(&KindTemplate) -> &Option<String>| match kind {
+         Some(kind) if text.trim().is_empty() => pick(&kind.template).clone().unwrap_or(text),
+         _ => text,
+      };
+      let mut issue_obj = Issue::new(
+         title.clone(),
+         priority,
+         tags,
+         files,
+         fill(template(issue, |t| &t.issue)),
+         fill(template(impact, |t| &t.impact)),
+         fill(template(acceptance, |t| &t.acceptance)),
+         effort,
+         context.map(fill),
+      );
+      issue_obj.metadata.milestone = milestone.map(Into::into);
+      issue_obj.metadata.kind = kind.map(|k| k.name.as_str().into());
+      let similar_issues = find_similar(
+         &issue_obj,
+         &self.storage.list_open_issues()?,
+         self.config.similarity_threshold,
+      );
+      let suggested_tags = self.suggest_tags_data(
+         &issue_obj.metadata.title,
+         &issue_obj.metadata.files,
+         &issue_obj.metadata.tags,
+      )?;
+      let estimate_hint = match &issue_obj.metadata.effort {
+         Some(effort) => {
+            let tags: Vec<String> = issue_obj
+               .metadata
+               .tags
+               .iter()
+               .map(|t| t.to_string())
+               .collect();
+            hint(&self.calibration_data()?, effort, &tags)
+         },
+         None => None,
+      };
+
+      let (bug_num, path) = self.storage.create_issue(&issue_obj)?;
+      let alias = if self.config.auto_alias {
+         self.add_title_alias(bug_num, &title)?
+      } else {
+         None
+      };
+
+      Ok(CreateIssueResult {
+         bug_num,
+         title,
+         path: path.display().to_string(),
+         alias,
+         similar_issues,
+         suggested_tags,
+         estimate_hint,
+      })
+   }
+
+   /// Refuse a status change the configured transition rules don't allow,
+   /// unless forced
+   pub(crate) fn check_transition(&self, bug_num: u32, verb: &str, to: Status) -> Result<()> {
+      if self.force {
+         return Ok(());
+      }
+      let from = self.storage.load_issue(bug_num)?.metadata.status;
+      self
+         .config
+         .transitions
+         .check(from, to)
+         .map_err(|advice| anyhow::anyhow!("cannot {verb} #{bug_num}: it is {from}; {advice}"))
+   }
+
+   /// Dependencies of `bug_num` that are neither closed nor done
+   fn open_dependencies(&self, bug_num: u32) -> Result<Vec<IssueWithId>> {
+      let depends_on = self.storage.load_issue(bug_num)?.metadata.depends_on;
+      Ok(self
+         .storage
+         .list_open_issues()?
+         .into_iter()
+         .filter(|dep| depends_on.contains(&dep.id) && dep.issue.metadata.status != Status::Done)
+         .collect())
+   }
+
+   /// Refuse to start an issue that still waits on open dependencies, unless
+   /// forced; returns the dependencies `--force` went past
+   pub(crate) fn check_dependencies(&self, bug_num: u32) -> Result<Vec<IssueWithId>> {
+      let open = self.open_dependencies(bug_num)?;
+      if open.is_empty() || self.force {
+         return Ok(open);
+      }
+      anyhow::bail!(
+         "cannot start #{bug_num}: it depends on open {}; close {} first, or pass --force",
+         open
+            .iter()
+            .map(|dep| format!("#{} ({})", dep.id, dep.issue.metadata.title))
+            .collect::<Vec<_>>()
+            .join(", "),
+         if open.len() == 1 { "it" } else { "them" }
+      )
+   }
+
+   /// Refuse to `action` an issue another agent holds a live claim on,
+   /// unless forced; returns the claim `--force` went past. Callers hold the
+   /// store's lock from this check until their change is saved, so no other
+   /// agent's claim can land in between
+   pub(crate) fn check_claim(&self, bug_num: u32, action: &str) -> Result<Option<Claim>> {
+      let claim = self.storage.load_issue(bug_num)?.metadata.claim;
+      let Some(claim) = claim.filter(|c| c.is_live(Utc::now()) && *c.agent != self.agent()) else {
+         return Ok(None);
+      };
+      if self.force {
+         return Ok(Some(claim));
+      }
+      anyhow::bail!(
+         "cannot {action} #{bug_num}: claimed by {} until {}; wait for the lease to expire, or \
+          pass --force",
+         claim.agent,
+         claim.until.format("%Y-%m-%d %H:%M UTC")
+      )
+   }
+
+   /// Dependents of `bug_num` that no longer wait on anything now that it is
+   /// finished; with `activate_unblocked`, backlogged ones are moved to open
+   pub(crate) fn release_dependents(&self, bug_num: u32) -> Result<Vec<UnblockedIssue>> {
+      let open = self.storage.list_open_issues()?;
+      let unfinished: Vec<u32> = open
+         .iter()
+         .filter(|issue_with_id| issue_with_id.issue.metadata.status != Status::Done)
+         .map(|issue_with_id| issue_with_id.id)
+         .collect();
+
+      let mut unblocked = Vec::new();
+      for dependent in &open {
+         let meta = &dependent.issue.metadata;
+         if !meta.depends_on.contains(&bug_num)
+            || meta.depends_on.iter().any(|dep| unfinished.contains(dep))
+            || !matches!(meta.status, Status::NotStarted | Status::Backlog)
+         {
+            continue;
+         }
+
+         let activated = self.config.activate_unblocked
+            && meta.status == Status::Backlog
+            && self
+               .config
+               .transitions
+               .allows(Status::Backlog, Status::NotStarted);
+         if activated {
+            self.storage.update_issue_metadata(dependent.id, |meta| {
+               meta.status = Status::NotStarted;
+            })?;
+         }
+         unblocked.push(UnblockedIssue {
+            bug_num: dependent.id,
+            title: meta.title.to_string(),
+            activated,
+         });
+      }
+      Ok(unblocked)
+   }
+
+   /// Blocked issues that name `bug_num` in their block reason (as `#3`,
+   /// `ISSUE-3` or one of its aliases) or depend on it
+   pub(crate) fn unblock_candidates(&self, bug_num: u32) -> Result<Vec<UnblockCandidate>> {
+      let mut names =
+         vec![format!("#{bug_num}"), regex::escape(&self.config.format_issue_ref(bug_num))];
+      names.extend(
+         self
+            .storage
+            .load_aliases()?
+            .into_iter()
+            .filter(|&(_, num)| num == bug_num)
+            .map(|(alias, _)| regex::escape(&alias)),
+      );
+      let mentions = Regex::new(&format!(r"(?i)(^|[^\w#-])({})\b", names.join("|")))?;
+
+      Ok(self
+         .storage
+         .list_open_issues()?
+         .into_iter()
+         .filter(|issue_with_id| {
+            let meta = &issue_with_id.issue.metadata;
+            meta.status == Status::Blocked
+               && (meta.depends_on.contains(&bug_num)
+                  || meta
+                     .blocked_reason
+                     .as_ref()
+                     .is_some_and(|reason| mentions.is_match(reason)))
+         })
+         .map(|issue_with_id| UnblockCandidate {
+            bug_num:   issue_with_id.id,
+            title:     issue_with_id.issue.metadata.title.to_string(),
+            reason:    issue_with_id
+               .issue
+               .metadata
+               .blocked_reason
+               .as_ref()
+               .map(|r| r.to_string()),
+            unblocked: false,
+         })
+         .collect())
+   }
+
+   /// Put a blocked issue back in the status it was blocked from
+   pub(crate) fn unblock(&self, bug_num: u32) -> Result<()> {
+      self.storage.update_issue_metadata(bug_num, |meta| {
+         let before = meta
+            .transitions
+            .iter()
+            .rev()
+            .find(|t| t.to == Status::Blocked)
+            .map(|t| t.from)
+            .filter(|&from| self.config.transitions.allows(Status::Blocked, from))
+            .unwrap_or(Status::NotStarted);
+         meta.status = before;
+         meta.blocked_reason = None;
+      })
+   }
+
+   pub(crate) fn unblock_all(&self, candidates: &mut [UnblockCandidate]) -> Result<()> {
+      for candidate in candidates.iter_mut() {
+         self.unblock(candidate.bug_num)?;
+         candidate.unblocked = true;
+      }
+      Ok(())
+   }
+
+   /// Save an issue that was loaded as `base`
+   ///
+   /// Changes another process made meanwhile are merged in; when they overlap
+   /// ours, the user is asked how to resolve them if they're at a terminal and
+   /// the [`IssueConflict`] is returned otherwise.
+   pub(crate) fn save_edited(
+      &self,
+      base: &Issue,
+      issue: &Issue,
+      bug_num: u32,
+      is_open: bool,
+   ) -> Result<()> {
+      self.settle_conflict(self.storage.save_issue_from(base, issue, bug_num, is_open))
+   }
+
+   /// Finish a save that may have hit an [`IssueConflict`], asking how to
+   /// resolve it when interactive
+   pub(crate) fn settle_conflict(&self, saved: Result<PathBuf>) -> Result<()> {
+      match saved {
+         Ok(_) => Ok(()),
+         Err(err) if self.ref_mode == RefMode::Interactive => match err.downcast::<IssueConflict>()
+         {
+            Ok(conflict) => self.resolve_conflict(conflict),
+            Err(err) => Err(err),
+         },
+         Err(err) => Err(err),
+      }
+   }
+
+   fn resolve_conflict(&self, conflict: IssueConflict) -> Result<()> {
+      let IssueConflict { bug_num, is_open, mine, merge } = conflict;
+      eprintln!(
+         "⚠️  {} was changed by another process; {} change(s) overlap yours",
+         self.config.format_issue_ref(bug_num),
+         merge.conflicts().len()
+      );
+
+      let options = ["Keep mine", "Keep theirs", "Merge sections"];
+      let resolved = match prompt_select("How should the conflict be resolved?", &options)? {
+         0 => mine,
+         1 => return Ok(()),
+         _ => {
+            let mut choices = Vec::new();
+            for conflict in merge.conflicts() {
+               display_preview(&format!("Mine: {}", conflict.part), &conflict.mine);
+               display_preview(&format!("Theirs: {}", conflict.part), &conflict.theirs);
+               let choice =
+                  prompt_select(&format!("Keep which {}?", conflict.part), &["Mine", "Theirs"])?;
+               choices.push(if choice == 0 {
+                  Side::Mine
+               } else {
+                  Side::Theirs
+               });
+            }
+            merge.resolve(&choices)?
+         },
+      };
+      self.storage.save_issue(&resolved, bug_num, is_open)?;
+      Ok(())
+   }
+
+   /// Current version of an issue, for callers to hand back as
+   /// `expected_version`
+   pub fn issue_version(&self, bug_num: u32) -> Result<String> {
+      self.storage.issue_version(bug_num)
+   }
+
+   /// Run `change` unless the issue has changed since the caller read it at
+   /// version `expected`; the store stays locked from the check until the
+   /// change is saved. Without `expected`, just run `change`.
+   pub fn with_expected_version<T>(
+      &self,
+      bug_num: u32,
+      expected: Option<&str>,
+      change: impl FnOnce() -> Result<T>,
+   ) -> Result<T> {
+      let Some(expected) = expected else {
+         return change();
+      };
+      let _lock = self.storage.lock()?;
+      let current = self.storage.issue_version(bug_num)?;
+      if current != expected {
+         return Err(VersionConflict { bug_num, expected: expected.to_string(), current }.into());
+      }
+      change()
+   }
+
+   /// The markdown file an issue lives in
+   pub fn path_data(&self, bug_ref: &str) -> Result<(u32, PathBuf)> {
+      let bug_num = self.resolve_ref(bug_ref)?;
+      Ok((bug_num, self.storage.find_issue_file(bug_num)?))
+   }
+
+   /// Field and body changes to an issue, per git revision, after `since`
+   /// (a date, an age like `7d`, or a git revision)
+   pub fn diff_data(&self, bug_ref: &str, since: Option<&str>) -> Result<IssueDiffResult> {
+      let bug_num = self.resolve_ref(bug_ref)?;
+      let issue = self.storage.load_issue(bug_num)?;
+      let since = since
+         .map(|since| history::since_time(&self.storage, since))
+         .transpose()?;
+      let versions = history::versions(&self.storage, bug_num)?;
+
+      Ok(IssueDiffResult {
+         bug_num,
+         title: issue.metadata.title.to_string(),
+         revisions: history::revisions(&versions, since),
+      })
+   }
+
+   /// The latest `limit` audit entries matching the filters, oldest first;
+   /// `since` and `until` take a date, an age like `7d`, or a git revision
+   pub fn audit_data(
+      &self,
+      actor: Option<&str>,
+      issue: Option<&str>,
+      since: Option<&str>,
+      until: Option<&str>,
+      limit: usize,
+   ) -> Result<Vec<AuditEntry>> {
+      let filter = AuditFilter {
+         actor: actor.map(String::from),
+         issue: issue.map(|r| self.resolve_ref(r)).transpose()?,
+         since: since
+            .map(|s| history::since_time(&self.storage, s))
+            .transpose()?,
+         until: until
+            .map(|u| history::since_time(&self.storage, u))
+            .transpose()?,
+      };
+      let mut entries: Vec<AuditEntry> = self
+         .storage
+         .load_audit()?
+         .into_iter()
+         .filter(|e| filter.matches(e))
+         .collect();
+      entries.drain(..entries.len().saturating_sub(limit));
+      Ok(entries)
+   }
+
+   pub fn start_data(&self, bug_ref: &str) -> Result<StatusUpdateResult> {
+      let bug_num = self.resolve_ref(bug_ref)?;
+      self.check_transition(bug_num, "start", Status::InProgress)?;
+      self.check_dependencies(bug_num)?;
+      let lock = self.storage.lock()?;
+      self.check_claim(bug_num, "start")?;
+
+      self.storage.update_issue_metadata(bug_num, |meta| {
+         meta.status = Status::InProgress;
+         meta.started = Some(Utc::now());
+      })?;
+      drop(lock);
+
+      Ok(StatusUpdateResult {
+         bug_num,
+         status: Status::InProgress.to_string(),
+         message: None,
+         unblocked: Vec::new(),
+         unblock_candidates: Vec::new(),
+         file_conflicts: self.overlapping_issues(bug_num)?,
+      })
+   }
+
+   /// Take (or renew) an exclusive lease on an issue for `ttl`
+   pub fn claim_data(&self, bug_ref: &str, ttl: &str) -> Result<ClaimResult> {
+      let bug_num = self.resolve_ref(bug_ref)?;
+      let ttl = parse_duration(ttl)?;
+      if ttl <= Duration::zero() {
+         anyhow::bail!("The lease needs a positive --ttl");
+      }
+      let _lock = self.storage.lock()?;
+      let overridden = self.check_claim(bug_num, "claim")?;
+      let agent = self.agent();
+      let now = Utc::now().trunc_subsecs(0);
+
+      let mut claim = None;
+      self.storage.update_issue_metadata(bug_num, |meta| {
+         // Renewing keeps the original start of the lease
+         let since = meta
+            .claim
+            .as_ref()
+            .filter(|c| c.is_live(now) && *c.agent == agent)
+            .map_or(now, |c| c.since);
+         meta.claim = Some(Claim { agent: agent.as_str().into(), since, until: now + ttl });
+         claim = meta.claim.clone();
+      })?;
+      Ok(ClaimResult { bug_num, claim, overridden })
+   }
+
+   /// Give up a lease; another agent's live one needs `--force`
+   pub fn release_data(&self, bug_ref: &str) -> Result<ClaimResult> {
+      let bug_num = self.resolve_ref(bug_ref)?;
+      let _lock = self.storage.lock()?;
+      let overridden = self.check_claim(bug_num, "release")?;
+      self
+         .storage
+         .update_issue_metadata(bug_num, |meta| meta.claim = None)?;
+      Ok(ClaimResult { bug_num, claim: None, overridden })
+   }
+
+   pub fn block_data(&self, bug_ref: &str, reason: String) -> Result<StatusUpdateResult> {
+      let bug_num = self.resolve_ref(bug_ref)?;
+      self.check_transition(bug_num, "block", Status::Blocked)?;
+
+      self.storage.update_issue_metadata(bug_num, |meta| {
+         meta.status = Status::Blocked;
+         meta.blocked_reason = Some(reason.clone().into());
+      })?;
+
+      Ok(StatusUpdateResult {
+         bug_num,
+         status: Status::Blocked.to_string(),
+         message: Some(reason),
+         unblocked: Vec::new(),
+         unblock_candidates: Vec::new(),
+         file_conflicts: Vec::new(),
+      })
+   }
+
+   /// Close an issue; with `auto_unblock`, blocked issues that were waiting
+   /// on it are unblocked too
+   pub fn close_data(
+      &self,
+      bug_ref: &str,
+      message: Option<String>,
+      auto_unblock: bool,
+   ) -> Result<StatusUpdateResult> {
+      let bug_num = self.resolve_ref_strict(bug_ref)?;
+      self.check_transition(bug_num, "close", Status::Closed)?;
+
+      self.storage.update_issue_metadata(bug_num, |meta| {
+         meta.status = Status::Closed;
+         meta.closed = Some(Utc::now());
+      })?;
+
+      if let Some(note) = &message {
+         let base = self.storage.load_issue(bug_num)?;
+         let mut issue = base.clone();
+         issue.body.append(&format!("\n\n## Closed\n\n{}", note));
+         self.save_edited(&base, &issue, bug_num, true)?;
+      }
+
+      self.storage.move_issue(bug_num, false)?;
+      let unblocked = self.release_dependents(bug_num)?;
+      let mut unblock_candidates = self.unblock_candidates(bug_num)?;
+      if auto_unblock {
+         self.unblock_all(&mut unblock_candidates)?;
+      }
+
+      Ok(StatusUpdateResult {
+         bug_num,
+         status: Status::Closed.to_string(),
+         message,
+         unblocked,
+         unblock_candidates,
+         file_conflicts: Vec::new(),
+      })
+   }
+
+   pub fn open_data(&self, bug_ref: &str) -> Result<StatusUpdateResult> {
+      let bug_num = self.resolve_ref(bug_ref)?;
+      self.check_transition(bug_num, "reopen", Status::NotStarted)?;
+
+      self.storage.update_issue_metadata(bug_num, |meta| {
+         meta.status = Status::NotStarted;
+         meta.closed = None;
+      })?;
+
+      self.storage.move_issue(bug_num, true)?;
+
+      Ok(StatusUpdateResult {
+         bug_num,
+         status: Status::NotStarted.to_string(),
+         message: None,
+         unblocked: Vec::new(),
+         unblock_candidates: Vec::new(),
+         file_conflicts: Vec::new(),
+      })
+   }
+
+   pub fn defer_data(&self, bug_ref: &str) -> Result<StatusUpdateResult> {
+      let bug_num = self.resolve_ref(bug_ref)?;
+      self.check_transition(bug_num, "defer", Status::Backlog)?;
+
+      self.storage.update_issue_metadata(bug_num, |meta| {
+         meta.status = Status::Backlog;
+      })?;
+
+      Ok(StatusUpdateResult {
+         bug_num,
+         status: Status::Backlog.to_string(),
+         message: None,
+         unblocked: Vec::new(),
+         unblock_candidates: Vec::new(),
+         file_conflicts: Vec::new(),
+      })
+   }
+
+   pub fn activate_data(&self, bug_ref: &str) -> Result<StatusUpdateResult> {
+      let bug_num = self.resolve_ref(bug_ref)?;
+      self.check_transition(bug_num, "activate", Status::NotStarted)?;
+
+      self.storage.update_issue_metadata(bug_num, |meta| {
+         meta.status = Status::NotStarted;
+      })?;
+
+      Ok(StatusUpdateResult {
+         bug_num,
+         status: Status::NotStarted.to_string(),
+         message: None,
+         unblocked: Vec::new(),
+         unblock_candidates: Vec::new(),
+         file_conflicts: Vec::new(),
+      })
+   }
+
+   pub fn checkpoint_data(&self, bug_ref: &str, note: String) -> Result<StatusUpdateResult> {
+      let bug_num = self.resolve_ref(bug_ref)?;
+      let _lock = self.storage.lock()?;
+      self.check_claim(bug_num, "checkpoint")?;
+      let base = self.storage.load_issue(bug_num)?;
+      let mut issue = base.clone();
+
+      let timestamp = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+      issue
+         .body
+         .append(&format!("\n\n## Checkpoint - {}\n\n{}", timestamp, note));
+
+      let mut status_changed = false;
+      if note.starts_with("BLOCKED:") {
+         let reason = note
+            .strip_prefix("BLOCKED:")
+            .unwrap_or("")
+            .trim()
+            .to_string();
+         self.check_transition(bug_num, "block", Status::Blocked)?;
+         self.storage.update_issue_metadata(bug_num, |meta| {
+            meta.status = Status::Blocked;
+            meta.blocked_reason = Some(reason.into());
+         })?;
+         status_changed = true;
+      } else if note.starts_with("DONE:") || note.starts_with("COMPLETED:") {
+         self.check_transition(bug_num, "close", Status::Closed)?;
+         self.storage.update_issue_metadata(bug_num, |meta| {
+            meta.status = Status::Closed;
+            meta.closed = Some(Utc::now());
+         })?;
+         status_changed = true;
+      }
+
+      // Save where the issue lives now; a DONE note moves it to closed after
+      let is_open = base.metadata.status != Status::Closed;
+      self.save_edited(&base, &issue, bug_num, is_open)?;
+      let mut unblocked = Vec::new();
+      let mut unblock_candidates = Vec::new();
+      if is_open && self.storage.load_issue(bug_num)?.metadata.status == Status::Closed {
+         self.storage.move_issue(bug_num, false)?;
+         unblocked = self.release_dependents(bug_num)?;
+         unblock_candidates = self.unblock_candidates(bug_num)?;
+      }
+      self.compact_over_budget(bug_num)?;
+
+      Ok(StatusUpdateResult {
+         bug_num,
+         status: if status_changed {
+            "updated".to_string()
+         } else {
+            "checkpoint_added".to_string()
+         },
+         message: Some(note),
+         unblocked,
+         unblock_candidates,
+         file_conflicts: Vec::new(),
+      })
+   }
+
+   /// Fold all but the last `keep` checkpoints (default from the config)
+   /// into a history summary and archive them
+   pub fn compact_data(&self, bug_ref: &str, keep: Option<usize>) -> Result<CompactResult> {
+      let bug_num = self.resolve_ref(bug_ref)?;
+      self.compact_issue(bug_num, keep.unwrap_or(self.config.compact.keep))
+   }
+
+   fn compact_issue(&self, bug_num: u32, keep: usize) -> Result<CompactResult> {
+      let base = self.storage.load_issue(bug_num)?;
+      let bytes_before = base.body.to_string().len();
+      let summarizer = self.config.compact.summarizer.as_deref();
+      let compaction = compact(&base.body, keep, |previous, old| match summarizer {
+         Some(command) => run_summarizer(command, previous, old),
+         None => Ok(outline(previous, old)),
+      })?;
+      let Some(compaction) = compaction else {
+         return Ok(CompactResult {
+            bug_num,
+            compacted: 0,
+            kept: base.body.checkpoints().count(),
+            bytes_before,
+            bytes_after: bytes_before,
+            archive: None,
+         });
+      };
+
+      // Archive first, so a failed save never loses checkpoints
+      let archive = self
+         .storage
+         .archive_checkpoints(bug_num, &compaction.archived)?;
+      let mut issue = base.clone();
+      issue.body = compaction.body;
+      let is_open = issue.metadata.status != Status::Closed;
+      self.save_edited(&base, &issue, bug_num, is_open)?;
+
+      Ok(CompactResult {
+         bug_num,
+         compacted: compaction.compacted,
+         kept: compaction.kept,
+         bytes_before,
+         bytes_after: issue.body.to_string().len(),
+         archive: Some(archive),
+      })
+   }
+
+   /// Compact an issue whose body outgrew `compact.max_body_bytes`
+   pub(crate) fn compact_over_budget(&self, bug_num: u32) -> Result<Option<CompactResult>> {
+      let Some(budget) = self.config.compact.max_body_bytes else {
+         return Ok(None);
+      };
+      if self.storage.load_issue(bug_num)?.body.to_string().len() <= budget {
+         return Ok(None);
+      }
+      let result = self.compact_issue(bug_num, self.config.compact.keep)?;
+      Ok((result.compacted > 0).then_some(result))
+   }
+
+   /// In-progress, blocked, high-priority and ready issues within `scope`,
+   /// each section ranked by priority and issues unblocked (then number)
+   /// and cut to the scope's cap
+   pub fn context_data(&self, scope: &ContextScope) -> Result<ContextResult> {
+      let issues = self.storage.list_open_issues()?;
+
+      let mut in_progress = Vec::new();
+      let mut blocked = Vec::new();
+      let mut high_priority = Vec::new();
+      let mut ready = Vec::new();
+      let mut deferred = 0;
+
+      let scoped: Vec<&IssueWithId> = issues.iter().filter(|i| scope.includes(i)).collect();
+      for &issue_with_id in &scoped {
+         match issue_with_id.issue.metadata.status {
+            Status::InProgress => in_progress.push(issue_with_id.clone()),
+            Status::Blocked => blocked.push(issue_with_id.clone()),
+            Status::NotStarted => {
+               if matches!(
+                  issue_with_id.issue.metadata.priority,
+                  Priority::Critical | Priority::High
+               ) {
+                  high_priority.push(issue_with_id.clone());
+               }
+               ready.push(issue_with_id.clone());
+            },
+            Status::Backlog => deferred += 1,
+            _ => {},
+         }
+      }
+
+      let unblocks: BTreeMap<u32, usize> = unblock_counts(&issues)
+         .into_iter()
+         .filter(|(_, count)| *count > 0)
+         .collect();
+      let impact = |issue_with_id: &IssueWithId| {
+         let count = unblocks.get(&issue_with_id.id).copied().unwrap_or(0);
+         (rank_key(issue_with_id.issue.metadata.priority, count), issue_with_id.stable_key())
+      };
+      let total_open = scoped.len() - deferred;
+      let mut omitted = BTreeMap::new();
+      let mut cap = |name: &str, mut section: Vec<IssueWithId>, max: Option<usize>| {
+         section.sort_by_key(impact);
+         if let Some(max) = max
+            && section.len() > max
+         {
+            omitted.insert(name.to_string(), section.len() - max);
+            section.truncate(max);
+         }
+         section
+      };
+
+      let capacity = self.daily_capacity()?;
+      let in_progress_efforts: Vec<Option<SmolStr>> = in_progress
+         .iter()
+         .map(|i| i.issue.metadata.effort.clone())
+         .collect();
+      let active = cap("active", in_progress, scope.max_items);
+      let blocked = cap("blocked", blocked, scope.max_items);
+      let high_priority = cap("high_priority", high_priority, scope.max_items);
+      let ready_to_start = cap("ready_to_start", ready, Some(scope.max_items.unwrap_or(5)));
+      let budget = capacity.map(|minutes| {
+         let ready_efforts = ready_to_start
+            .iter()
+            .map(|i| i.issue.metadata.effort.as_deref());
+         DailyBudget::new(
+            minutes,
+            in_progress_efforts
+               .iter()
+               .map(|e| e.as_deref())
+               .chain(ready_efforts),
+         )
+      });
+
+      // Only the counts of issues shown
+      let shown: HashSet<u32> = [&active, &blocked, &high_priority, &ready_to_start]
+         .into_iter()
+         .flatten()
+         .map(|i| i.id)
+         .collect();
+      let unblocks = unblocks
+         .into_iter()
+         .filter(|(id, _)| shown.contains(id))
+         .collect();
+
+      Ok(ContextResult {
+         active,
+         blocked,
+         high_priority,
+         ready_to_start,
+         total_open,
+         deferred,
+         unblocks,
+         omitted,
+         budget,
+      })
+   }
+
+   /// `daily_capacity` in minutes, if set
+   pub(crate) fn daily_capacity(&self) -> Result<Option<u32>> {
+      self
+         .config
+         .daily_capacity
+         .as_deref()
+         .map(|capacity| {
+            parse_effort(capacity).with_context(|| format!("Invalid daily_capacity: {capacity}"))
+         })
+         .transpose()
+   }
+
+   /// All open issues in `focus` order: active and blocked ones first, then
+   /// by priority and how many issues each unblocks
+   fn focus_ranking(issues: &[IssueWithId]) -> Vec<RankedIssue> {
+      let unblocks = unblock_counts(issues);
+
+      let mut ranked: Vec<_> = issues
+         .iter()
+         .map(|issue_with_id| {
+            let meta = &issue_with_id.issue.metadata;
+            let count = unblocks.get(&issue_with_id.id).copied().unwrap_or(0);
+            let active = matches!(meta.status, Status::InProgress | Status::Blocked);
+            let key = (!active, rank_key(meta.priority, count), issue_with_id.stable_key());
+            (key, RankedIssue::new(issue_with_id, count))
+         })
+         .collect();
+
+      ranked.sort_by_key(|(key, _)| *key);
+      ranked.into_iter().map(|(_, issue)| issue).collect()
+   }
+
+   pub fn focus_data(&self) -> Result<Vec<RankedIssue>> {
+      let issues = self.storage.list_open_issues()?;
+      Ok(Self::focus_ranking(&issues).into_iter().take(5).collect())
+   }
+
+   /// Explain an issue's place in `focus` using the same ranking
+   pub fn why_data(&self, bug_ref: &str) -> Result<WhyResult> {
+      let bug_num = self.resolve_ref(bug_ref)?;
+      let issue = self.storage.load_issue(bug_num)?;
+      let meta = &issue.metadata;
+      let open = self.storage.list_open_issues()?;
+      let ranking = Self::focus_ranking(&open);
+
+      let position = ranking.iter().position(|r| r.num == bug_num);
+      let unblocks = position.map_or(0, |i| ranking[i].unblocks);
+      let (score, _) = rank_key(meta.priority, unblocks);
+      let ahead = position.map_or_else(Vec::new, |i| ranking[i.saturating_sub(2)..i].to_vec());
+
+      // Open dependencies, following each one's own dependencies
+      let mut waiting_on = Vec::new();
+      let mut to_visit = meta.depends_on.clone();
+      while let Some(dep) = to_visit.pop() {
+         if dep == bug_num || waiting_on.contains(&dep) {
+            continue;
+         }
+         if let Some(dep_issue) = open.iter().find(|i| i.id == dep)
+            && dep_issue.issue.metadata.status != Status::Done
+         {
+            waiting_on.push(dep);
+            to_visit.extend(&dep_issue.issue.metadata.depends_on);
+         }
+      }
+      waiting_on.sort_unstable();
+
+      let now = Utc::now();
+      let last_activity = issue.last_activity();
+      let age_days = (now - meta.created).num_days();
+      let idle_days = (now - last_activity).num_days();
+
+      let mut counted = Vec::new();
+      let mut not_counted = Vec::new();
+      if position.is_some() {
+         counted.push(match meta.status {
+            Status::InProgress | Status::Blocked => {
+               format!("{}: listed before every issue that isn't active or blocked", meta.status)
+            },
+            status => format!("{status}: listed after the active and blocked issues"),
+         });
+         counted.push(format!(
+            "{} priority: {} (critical 0, high 2, medium 4, low 6)",
+            meta.priority,
+            meta.priority.sort_key() * 2
+         ));
+         let bonus = unblocks.min(MAX_RANKED_UNBLOCKS);
+         counted.push(match unblocks {
+            0 => "unblocks nothing: no bonus".to_string(),
+            n if n > MAX_RANKED_UNBLOCKS => {
+               format!("unblocks {n} issues: -{bonus} (capped at {MAX_RANKED_UNBLOCKS})")
+            },
+            1 => "unblocks 1 issue: -1".to_string(),
+            n => format!("unblocks {n} issues: -{bonus}"),
+         });
+         counted.push("ties go to the issue that unblocks more, then the lower number".to_string());
+      }
+      let days_ago = |days: i64| match days {
+         0 => "today".to_string(),
+         1 => "yesterday".to_string(),
+         n => format!("{n} days ago"),
+      };
+      not_counted.push(format!(
+         "created {}, last status change or checkpoint {}",
+         days_ago(age_days),
+         days_ago(idle_days)
+      ));
+      not_counted.push(match &meta.effort {
+         Some(effort) => format!("effort {effort} (see quick-wins)"),
+         None => "no effort estimate".to_string(),
+      });
+      if !waiting_on.is_empty() {
+         let refs: Vec<String> = waiting_on
+            .iter()
+            .map(|&n| self.config.format_issue_ref(n))
+            .collect();
+         not_counted
+            .push(format!("waits on open {}; start refuses until they close", refs.join(", ")));
+      }
+
+      Ok(WhyResult {
+         bug_num,
+         title: meta.title.to_string(),
+         status: meta.status,
+         priority: meta.priority,
+         rank: position.map(|i| i + 1),
+         open_issues: ranking.len(),
+         score,
+         unblocks,
+         age_days,
+         idle_days,
+         effort: meta.effort.as_ref().map(|e| e.to_string()),
+         waiting_on,
+         ahead,
+         counted,
+         not_counted,
+      })
+   }
+
+   pub fn blocked_data(&self) -> Result<Vec<BlockedIssue>> {
+      Ok(self
+         .storage
+         .list_open_issues()?
+         .into_iter()
+         .filter(|issue_with_id| issue_with_id.issue.metadata.status == Status::Blocked)
+         .map(|issue_with_id| BlockedIssue {
+            num:      issue_with_id.id,
+            title:    issue_with_id.issue.metadata.title.to_string(),
+            reason:   issue_with_id
+               .issue
+               .metadata
+               .blocked_reason
+               .as_ref()
+               .map(|r| r.to_string()),
+            priority: issue_with_id.issue.metadata.priority,
+         })
+         .collect())
+   }
+
+   pub fn blocked_analysis_data(&self) -> Result<BlockerAnalysis> {
+      let mut issues = self.storage.list_open_issues()?;
+      issues.extend(self.storage.list_closed_issues()?);
+      Ok(analyze_blockers(&issues, Utc::now()))
+   }
+
+   /// Not-started issues ranked by priority and how many issues each unblocks
+   pub fn ready_data(&self) -> Result<Vec<RankedIssue>> {
+      let issues = self.storage.list_open_issues()?;
+      let unblocks = unblock_counts(&issues);
+
+      let mut ready: Vec<_> = issues
+         .iter()
+         .filter(|issue_with_id| issue_with_id.issue.metadata.status == Status::NotStarted)
+         .map(|issue_with_id| {
+            let count = unblocks.get(&issue_with_id.id).copied().unwrap_or(0);
+            let key =
+               (rank_key(issue_with_id.issue.metadata.priority, count), issue_with_id.stable_key());
+            (key, issue_with_id, count)
+         })
+         .collect();
+
+      ready.sort_by_key(|(key, ..)| *key);
+      Ok(ready
+         .into_iter()
+         .map(|(_, issue_with_id, count)| RankedIssue::new(issue_with_id, count))
+         .collect())
+   }
+
+   /// The issue being worked on: the one whose branch is checked out, else
+   /// the one most recently started
+   fn active_issue(&self) -> Result<u32> {
+      let open = self.storage.list_open_issues()?;
+      let prefix = &self.config.git_integration.branch_prefix;
+      if let Some(id) = head_branch(Path::new(".")).and_then(|b| branch_issue(&b, prefix, &open)) {
+         return Ok(id);
+      }
+      open
+         .into_iter()
+         .filter(|i| i.issue.metadata.status == Status::InProgress)
+         .max_by_key(|i| i.issue.metadata.started)
+         .map(|i| i.id)
+         .context("No active issue: check out its branch, start one, or name it")
+   }
+
+   /// Conventional commit message for issue `bug_ref`, or the active issue
+   pub fn commit_msg_data(&self, bug_ref: Option<&str>) -> Result<(u32, String)> {
+      let bug_num = match bug_ref {
+         Some(bug_ref) => self.resolve_ref(bug_ref)?,
+         None => self.active_issue()?,
+      };
+      let issue = self.storage.load_issue(bug_num)?;
+      Ok((bug_num, commit_msg::message(&issue, &self.config.format_issue_ref(bug_num))))
+   }
+
+   pub fn prompt_data(&self) -> Result<PromptStatus> {
+      let open = self.storage.list_open_issues()?;
+      let mut status = PromptStatus::from_issues(&open);
+      if let Some(branch) = head_branch(Path::new(".")) {
+         let prefix = &self.config.git_integration.branch_prefix;
+         if let Some(id) = branch_issue(&branch, prefix, &open) {
+            status.branch_issue = Some(id);
+            status.branch_ref = Some(self.config.format_issue_ref(id));
+         }
+      }
+      Ok(status)
+   }
+
+   /// Open issues whose files are `path`, live under it (when `path` is a
+   /// directory) or contain it (when an issue lists a whole directory)
+   pub fn for_file_data(&self, path: &str) -> Result<Vec<FileIssue>> {
+      let root = repo_root()?;
+      let wanted = normalize_path(path, Some(&root));
+      if wanted.is_empty() {
+         anyhow::bail!("No path given");
+      }
+
+      let mut issues: Vec<_> = self
+         .storage
+         .list_open_issues()?
+         .into_iter()
+         .filter_map(|issue_with_id| {
+            let matched_files: Vec<String> = issue_with_id
+               .issue
+               .metadata
+               .files
+               .iter()
+               .filter(|f| paths_overlap(&normalize_path(f, Some(&root)), &wanted))
+               .map(|f| f.to_string())
+               .collect();
+            (!matched_files.is_empty()).then_some((issue_with_id, matched_files))
+         })
+         .collect();
+      issues.sort_by_key(|(issue_with_id, _)| issue_with_id.stable_key());
+
+      Ok(issues
+         .into_iter()
+         .map(|(issue_with_id, matched_files)| FileIssue {
+            num: issue_with_id.id,
+            title: issue_with_id.issue.metadata.title.to_string(),
+            priority: issue_with_id.issue.metadata.priority.to_string(),
+            status: issue_with_id.issue.metadata.status.to_string(),
+            matched_files,
+         })
+         .collect())
+   }
+
+   /// An issue drafted from a pasted stack trace or bug report, with the
+   /// files it mentions that exist in the working tree
+   pub fn draft_data(&self, report: &str) -> Result<Draft> {
+      let root = repo_root()?;
+      let draft = draft(report, Some(&root), |path| root.join(path).is_file());
+      if draft.title.is_empty() {
+         anyhow::bail!("Nothing to create an issue from: the report is empty");
+      }
+      Ok(draft)
+   }
+
+   /// Other in-progress issues whose files overlap issue `bug_ref`'s, so
+   /// agents working in parallel can stay out of each other's way
+   pub fn file_conflicts_data(&self, bug_ref: &str) -> Result<Vec<FileIssue>> {
+      let bug_num = self.resolve_ref(bug_ref)?;
+      self.overlapping_issues(bug_num)
+   }
+
+   pub(crate) fn overlapping_issues(&self, bug_num: u32) -> Result<Vec<FileIssue>> {
+      let issue = self.storage.load_issue(bug_num)?;
+      if issue.metadata.files.is_empty() {
+         return Ok(Vec::new());
+      }
+      let root = repo_root()?;
+      let ours: Vec<String> = issue
+         .metadata
+         .files
+         .iter()
+         .map(|f| normalize_path(f, Some(&root)))
+         .collect();
+
+      Ok(self
+         .storage
+         .list_open_issues()?
+         .into_iter()
+         .filter(|other| other.id != bug_num && other.issue.metadata.status == Status::InProgress)
+         .filter_map(|other| {
+            let meta = &other.issue.metadata;
+            let matched_files: Vec<String> = meta
+               .files
+               .iter()
+               .filter(|f| {
+                  let theirs = normalize_path(f, Some(&root));
+                  ours.iter().any(|o| paths_overlap(o, &theirs))
+               })
+               .map(|f| f.to_string())
+               .collect();
+            (!matched_files.is_empty()).then(|| FileIssue {
+               num: other.id,
+               title: meta.title.to_string(),
+               priority: meta.priority.to_string(),
+               status: meta.status.to_string(),
+               matched_files,
+            })
+         })
+         .collect())
+   }
+
+   /// Run a batch of newline-delimited JSON commands as one transaction:
+   /// nothing runs unless every line parses, and a failing command or a
+   /// cancellation rolls back the ones before it
+   pub fn batch_data(&self, input: &str) -> Result<Vec<BatchResult>> {
+      let ops = batch::parse(input);
+      if ops.iter().any(|(_, op)| op.is_err()) {
+         return Ok(ops
+            .into_iter()
+            .map(|(line, op)| match op {
+               Ok(op) => BatchResult::failed(
+                  line,
+                  Some(op.name()),
+                  "not run: the batch has invalid lines".to_string(),
+               ),
+               Err(err) => BatchResult::failed(line, None, format!("{err:#}")),
+            })
+            .collect());
+      }
+
+      // Ambiguous refs fail rather than prompt halfway through a batch
+      let tracker = match self.ref_mode {
+         RefMode::Interactive => self.clone().with_ref_mode(RefMode::Fuzzy),
+         _ => self.clone(),
+      };
+      let _lock = self.storage.lock()?;
+      let snapshot = self.storage.snapshot()?;
+      let mut results = Vec::new();
+      let mut failed = None;
+      let mut cancelled = false;
+      for (line, op) in ops.iter().map(|(line, op)| (*line, op.as_ref().unwrap())) {
+         cancelled = cancelled || (failed.is_none() && self.is_cancelled());
+         if cancelled {
+            results.push(BatchResult::failed(line, Some(op.name()), "not run: cancelled".into()));
+            continue;
+         }
+         if let Some(failed) = failed {
+            results.push(BatchResult::failed(
+               line,
+               Some(op.name()),
+               format!("not run: line {failed} failed"),
+            ));
+            continue;
+         }
+         match op.run(&tracker) {
+            Ok(result) => results.push(BatchResult {
+               line,
+               op: Some(op.name().to_string()),
+               ok: true,
+               result: Some(result),
+               error: None,
+               rolled_back: false,
+            }),
+            Err(err) => {
+               failed = Some(line);
+               results.push(BatchResult::failed(line, Some(op.name()), format!("{err:#}")));
+            },
+         }
+      }
+
+      if failed.is_some() || cancelled {
+         self.storage.restore(&snapshot)?;
+         for result in results.iter_mut().filter(|r| r.ok) {
+            result.rolled_back = true;
+         }
+      }
+      Ok(results)
+   }
+
+   /// Check the issues in a YAML list against the import schema, then
+   /// create them all or none: a failure or cancellation part way removes
+   /// the issues already created. A dry run only lists what it would create.
+   pub fn import_data(&self, yaml_input: &str, dry_run: bool) -> Result<ImportResult> {
+      let items = import::parse(yaml_input, &self.config.kinds).map_err(|errors| {
+         let lines: Vec<String> = errors.iter().map(|e| format!("  {e}")).collect();
+         anyhow::anyhow!("Import not applied, {} problem(s):\n{}", errors.len(), lines.join("\n"))
+      })?;
+      let mut existing = self.storage.list_open_issues()?;
+      existing.extend(self.storage.list_closed_issues()?);
+      let by_external_id: HashMap<String, u32> = existing
+         .iter()
+         .filter_map(|i| Some((i.issue.metadata.external_id.as_ref()?.to_string(), i.id)))
+         .collect();
+      let target = |item: &ImportItem| {
+         item
+            .external_id
+            .as_ref()
+            .and_then(|id| by_external_id.get(id).copied())
+      };
+      let planned: Vec<ImportPreview> = items
+         .iter()
+         .map(|item| ImportPreview {
+            title:    item.title.clone(),
+            priority: item.priority.unwrap_or(Priority::Medium),
+            kind:     item.kind.clone(),
+            updates:  target(item),
+         })
+         .collect();
+      if dry_run {
+         return Ok(ImportResult {
+            created: Vec::new(),
+            count: planned.iter().filter(|p| p.updates.is_none()).count(),
+            updated: Vec::new(),
+            unchanged: Vec::new(),
+            cancelled: false,
+            skipped: 0,
+            dry_run: true,
+            planned,
+         });
+      }
+
+      let _lock = self.storage.lock()?;
+      let snapshot = self.storage.snapshot()?;
+      let total = items.len();
+      let mut created = Vec::new();
+      let mut updated = Vec::new();
+      let mut unchanged = Vec::new();
+      for item in items {
+         if self.is_cancelled() {
+            self.storage.restore(&snapshot)?;
+            return Ok(ImportResult {
+               created: Vec::new(),
+               count: 0,
+               updated: Vec::new(),
+               unchanged: Vec::new(),
+               cancelled: true,
+               skipped: total,
+               dry_run: false,
+               planned,
+            });
+         }
+         let title = item.title.clone();
+         if let Some(bug_num) = target(&item) {
+            match self.sync_imported(bug_num, item) {
+               Ok(true) => updated.push(bug_num),
+               Ok(false) => unchanged.push(bug_num),
+               Err(e) => {
+                  self.storage.restore(&snapshot).context(
+                     "Rolling back the import failed; check `git status` in the issues directory",
+                  )?;
+                  anyhow::bail!("Updating '{title}' failed, nothing was imported: {e:#}");
+               },
+            }
+            continue;
+         }
+         let external_id = item.external_id.clone();
+         let result = self.create_issue_data(
+            item.title,
+            &item.priority.unwrap_or(Priority::Medium).to_string(),
+            item.tags,
+            item.files,
+            item.issue.unwrap_or_default(),
+            item.impact.unwrap_or_default(),
+            item.acceptance.unwrap_or_default(),
+            item.effort,
+            item.context,
+            item.milestone,
+            item.kind,
+         );
+         let result = result.and_then(|result| {
+            if let Some(id) = external_id {
+               self.storage.update_issue_metadata(result.bug_num, |meta| {
+                  meta.external_id = Some(id.into())
+               })?;
+            }
+            Ok(result)
+         });
+         match result {
+            Ok(result) => created.push(result.bug_num),
+            Err(e) => {
+               self.storage.restore(&snapshot).context(
+                  "Rolling back the import failed; check `git status` in the issues directory",
+               )?;
+               anyhow::bail!("Creating '{title}' failed, nothing was imported: {e:#}");
+            },
+         }
+      }
+
+      Ok(ImportResult {
+         count: created.len(),
+         created,
+         updated,
+         unchanged,
+         cancelled: false,
+         skipped: 0,
+         dry_run: false,
+         planned: Vec::new(),
+      })
+   }
+
+   /// Make issue `bug_num` match an import item with its `external_id`:
+   /// title, priority, kind, tags, files, effort, context, milestone and
+   /// sections, leaving its status and history alone. Whether it changed.
+   fn sync_imported(&self, bug_num: u32, item: ImportItem) -> Result<bool> {
+      let base = self.storage.load_issue(bug_num)?;
+      let kind = item
+         .kind
+         .as_deref()
+         .map(|k| self.config.kind(k))
+         .transpose()?;
+      let mut issue = base.clone();
+      let meta = &mut issue.metadata;
+      meta.title = item.title.into();
+      meta.priority = item.priority.unwrap_or(Priority::Medium);
+      meta.kind = kind.map(|k| k.name.as_str().into());
+      meta.tags = item.tags.into_iter().map(Into::into).collect();
+      meta.files = item.files.into_iter().map(Into::into).collect();
+      meta.effort = item.effort.map(Into::into);
+      meta.context = item.context.map(Into::into);
+      meta.milestone = item.milestone.map(Into::into);
+      for (section, text, pick) in [
+         (SectionKind::Issue, item.issue, (|t| &t.issue) as fn(&KindTemplate) -> &Option<String>),
+         (SectionKind::Impact, item.impact, |t| &t.impact),
+         (SectionKind::Acceptance, item.acceptance, |t| &t.acceptance),
+      ] {
+         let text = text
+            .filter(|t| !t.trim().is_empty())
+            .or_else(|| kind.and_then(|k| pick(&k.template).clone()));
+         if let Some(text) = text
+            && issue.body.section(&section).map(|s| s.content()) != Some(text.trim())
+         {
+            issue.body.set(section, text.trim());
+         }
+      }
+      self.storage.normalize_files(&mut issue);
+      if issue.to_mdx() == base.to_mdx() {
+         return Ok(false);
+      }
+      let is_open = issue.metadata.status != Status::Closed;
+      self.save_edited(&base, &issue, bug_num, is_open)?;
+      Ok(true)
+   }
+
+   /// Fetch a GitHub issue and store it as a new local issue, refusing one
+   /// imported before
+   pub fn import_url_data(&self, url: &str) -> Result<ImportUrlResult> {
+      let url = github::IssueUrl::parse(url)?;
+      let source_url = url.web_url();
+      let mut all = self.storage.list_open_issues()?;
+      all.extend(self.storage.list_closed_issues()?);
+      if let Some(existing) = all
+         .iter()
+         .find(|i| i.issue.metadata.source_url.as_deref() == Some(source_url.as_str()))
+      {
+         anyhow::bail!(
+            "{} was already imported as {}",
+            url.short(),
+            self.config.format_issue_ref(existing.id)
+         );
+      }
+
+      let remote = github::fetch(&url, github::token_from_env().as_deref())?;
+      let default_priority = match self.config.default_priority.as_str() {
+         "critical" => Priority::Critical,
+         "high" => Priority::High,
+         "low" => Priority::Low,
+         _ => Priority::Medium,
+      };
+      let issue = github::to_issue(&remote, &url, default_priority);
+      let (bug_num, path) = self.storage.create_issue(&issue)?;
+      let title = issue.metadata.title.to_string();
+      let alias = if self.config.auto_alias {
+         self.add_title_alias(bug_num, &title)?
+      } else {
+         None
+      };
+      Ok(ImportUrlResult {
+         bug_num,
+         title,
+         status: issue.metadata.status.to_string(),
+         source_url,
+         path: path.display().to_string(),
+         alias,
+      })
+   }
+
+   /// Pack an issue, its archived checkpoints and stubs of its related
+   /// issues into a tar file, `issue-<N>.tar` unless `out` says otherwise
+   pub fn bundle_data(&self, bug_ref: &str, out: Option<&str>) -> Result<BundleResult> {
+      let bug_num = self.resolve_ref(bug_ref)?;
+      let issue = self.storage.load_issue(bug_num)?;
+      if crypt::is_encrypted(&issue.body.to_string()) {
+         anyhow::bail!(
+            "{} is encrypted; the key is needed to bundle it",
+            self.config.format_issue_ref(bug_num)
+         );
+      }
+      let mut all = self.storage.list_open_issues()?;
+      all.extend(self.storage.list_closed_issues()?);
+
+      let bundle = Bundle {
+         manifest:    Manifest {
+            format:       bundle::FORMAT,
+            num:          bug_num,
+            issue_prefix: self.config.issue_prefix.clone(),
+            exported:     Utc::now(),
+            relations:    bundle::stubs(&issue, &all),
+         },
+         issue:       issue.to_mdx(),
+         checkpoints: self.storage.read_checkpoint_archive(bug_num)?,
+      };
+      let path = out.map_or_else(|| format!("issue-{bug_num}.tar"), String::from);
+      let file =
+         std::fs::File::create(&path).with_context(|| format!("Failed to create {path}"))?;
+      bundle.write(file)?;
+      Ok(BundleResult {
+         bug_num,
+         path,
+         relations: bundle.manifest.relations.len(),
+         checkpoints: bundle.checkpoints.is_some(),
+      })
+   }
+
+   /// Store a bundled issue under the next free number, relinking its
+   /// relations to the matching local issues and renumbering its mentions
+   pub fn bundle_import_data(&self, file: &str) -> Result<BundleImportResult> {
+      let input = std::fs::File::open(file).with_context(|| format!("Failed to open {file}"))?;
+      let bundle = Bundle::read(std::io::BufReader::new(input))?;
+      let mut issue = self.storage.parse_issue(&bundle.issue)?;
+      let mut local = self.storage.list_open_issues()?;
+      local.extend(self.storage.list_closed_issues()?);
+      let mut map = bundle::relink(&bundle.manifest.relations, &local);
+
+      // Held until the issue is saved, so no other writer takes its number
+      let _lock = self.storage.lock()?;
+      let bug_num = self.storage.next_bug_number()?;
+      map.insert(bundle.manifest.num, bug_num);
+      let (linked, unlinked): (Vec<&RelationStub>, Vec<&RelationStub>) = bundle
+         .manifest
+         .relations
+         .iter()
+         .partition(|stub| map.contains_key(&stub.num));
+      let relinked = |relation| {
+         linked
+            .iter()
+            .filter(move |stub| stub.relation == relation)
+            .map(|stub| map[&stub.num])
+      };
+
+      let body = references::rewrite(
+         &issue.body.to_string(),
+         &bundle.manifest.issue_prefix,
+         &self.config.issue_prefix,
+         |num| map.get(&num).copied(),
+      );
+      issue.body = IssueBody::parse(&body);
+      let meta = &mut issue.metadata;
+      meta.depends_on = relinked(Relation::DependsOn).collect();
+      meta.depends_on.sort_unstable();
+      meta.depends_on.dedup();
+      // Filled in from the dependents' `depends_on` below
+      meta.blocks.clear();
+      meta.claim = None;
+      let is_open = meta.status != Status::Closed;
+      let title = meta.title.to_string();
+
+      let path = self.storage.save_issue(&issue, bug_num, is_open)?;
+      for dependent in relinked(Relation::Blocks) {
+         self.storage.update_issue_metadata(dependent, |meta| {
+            if !meta.depends_on.contains(&bug_num) {
+               meta.depends_on.push(bug_num);
+               meta.depends_on.sort_unstable();
+            }
+         })?;
+      }
+      if let Some(checkpoints) = &bundle.checkpoints {
+         self.storage.archive_checkpoints(bug_num, checkpoints)?;
+      }
+      let alias = if self.config.auto_alias {
+         self.add_title_alias(bug_num, &title)?
+      } else {
+         None
+      };
+
+      Ok(BundleImportResult {
+         bug_num,
+         title,
+         path: path.display().to_string(),
+         from: bundle.manifest.num,
+         linked: linked
+            .iter()
+            .map(|stub| RelinkedStub {
+               relation: stub.relation,
+               from:     stub.num,
+               to:       map[&stub.num],
+               title:    stub.title.clone(),
+            })
+            .collect(),
+         unlinked: unlinked.into_iter().cloned().collect(),
+         alias,
+      })
+   }
+
+   /// Add (or with `remove`, drop) the current user as a watcher of an issue
+   pub fn watch_data(&self, bug_ref: &str, remove: bool) -> Result<WatchResult> {
+      let bug_num = self.resolve_ref(bug_ref)?;
+      let me = current_user();
+
+      let mut watchers = Vec::new();
+      self.storage.update_issue_metadata(bug_num, |meta| {
+         meta.watchers.retain(|w| *w != me);
+         if !remove {
+            meta.watchers.push(me.as_str().into());
+         }
+         watchers = meta.watchers.iter().map(|w| w.to_string()).collect();
+      })?;
+
+      // Only changes from here on show up in the banner
+      let mut seen = self.storage.load_watch_seen()?;
+      let mine = seen.entry(me).or_default();
+      if remove {
+         mine.remove(&bug_num);
+      } else {
+         mine.insert(bug_num, Seen::of(&self.storage.load_issue(bug_num)?));
+      }
+      self.storage.save_watch_seen(&seen)?;
+
+      Ok(WatchResult { bug_num, watching: !remove, watchers })
+   }
+
+   /// Alerts for the `notify` rules not raised before, shown on the desktop
+   /// too when `desktop` and the config allow
+   pub fn notify_data(&self, desktop: bool) -> Result<Vec<Alert>> {
+      let now = Utc::now();
+      let mut state = self.storage.load_notify_state()?;
+      let mut issues = self.storage.list_open_issues()?;
+      if self.config.notify.watched {
+         issues.extend(self.storage.list_closed_issues()?);
+      }
+      let alerts = notify::check(&issues, &self.config.notify, &current_user(), &mut state, now);
+      self.storage.save_notify_state(&state)?;
+
+      if desktop && self.config.notify.desktop {
+         notify::show_desktop(&alerts, |n| self.config.format_issue_ref(n), now);
+      }
+      Ok(alerts)
+   }
+
+   /// Issues the current user watches
+   pub fn watched_data(&self) -> Result<Vec<IssueWithId>> {
+      let me = current_user();
+      let mut issues = self.storage.list_open_issues()?;
+      issues.extend(self.storage.list_closed_issues()?);
+      issues.retain(|i| i.issue.metadata.watchers.iter().any(|w| *w == me));
+      Ok(issues)
+   }
+
+   /// Changes to the current user's watched issues since they last saw them,
+   /// marking them as seen
+   pub fn watched_changes_data(&self) -> Result<Vec<WatchedChange>> {
+      let me = current_user();
+      let mut seen = self.storage.load_watch_seen()?;
+      let Some(mine) = seen.get_mut(&me) else {
+         return Ok(Vec::new());
+      };
+
+      let mut changes = Vec::new();
+      for issue in self.watched_data()? {
+         let Some(last) = mine.get(&issue.id) else {
+            continue;
+         };
+         let events = last.events(&issue.issue);
+         if !events.is_empty() {
+            mine.insert(issue.id, Seen::of(&issue.issue));
+            changes.push(WatchedChange {
+               bug_num: issue.id,
+               title: issue.issue.metadata.title.to_string(),
+               events,
+            });
+         }
+      }
+
+      if !changes.is_empty() {
+         self.storage.save_watch_seen(&seen)?;
+      }
+      Ok(changes)
+   }
+
+   /// Alias a new issue by its title in the configured namespace, numbering
+   /// it when another issue already has the slug
+   fn add_title_alias(&self, bug_num: u32, title: &str) -> Result<Option<String>> {
+      let slug = alias::slug(title);
+      if slug.is_empty() {
+         return Ok(None);
+      }
+
+      let mut aliases = self.storage.load_aliases()?;
+      let namespace = self.config.alias_namespace.as_deref();
+      let mut name = alias::qualify(namespace, &slug);
+      if alias::taken(&aliases, &name, bug_num) {
+         name = alias::qualify(namespace, &format!("{slug}-{bug_num}"));
+      }
+      aliases.insert(name.clone(), bug_num);
+      self.storage.save_aliases(&aliases)?;
+      Ok(Some(name))
+   }
+
+   /// Problems in the issue store; with `fix`, rebuild `blocks` lists from
+   /// `depends_on`
+   pub fn doctor_data(&self, fix: bool) -> Result<DoctorResult> {
+      let mut existing: HashSet<u32> = self
+         .storage
+         .list_open_issues()?
+         .iter()
+         .map(|i| i.id)
+         .collect();
+      existing.extend(self.storage.list_closed_issues()?.iter().map(|i| i.id));
+
+      let alias_problems = alias::problems(&self.storage.load_aliases()?, &existing);
+      let blocks_repairs = self.storage.repair_blocks(!fix)?;
+      let root = repo_root()?;
+      let missing_files = self
+         .storage
+         .list_open_issues()?
+         .iter()
+         .flat_map(|issue| {
+            files::missing(&issue.issue.metadata.files, |path| root.join(path).exists())
+               .into_iter()
+               .map(|path| MissingFile { bug_num: issue.id, path })
+         })
+         .collect();
+      Ok(DoctorResult { alias_problems, blocks_repairs, missing_files, fixed: fix })
+   }
+
+   /// Rewrite `files` entries under `old` to `new` on every issue, open or
+   /// closed
+   pub fn files_remap_data(
+      &self,
+      old: &str,
+      new: &str,
+      dry_run: bool,
+      json: bool,
+   ) -> Result<FilesRemapResult> {
+      let root = repo_root().ok();
+      let old = normalize_path(old, root.as_deref());
+      let new = normalize_path(new, root.as_deref());
+      if old.is_empty() {
+         anyhow::bail!("The old path can't be empty");
+      }
+
+      let mut issues = self.storage.list_open_issues()?;
+      issues.extend(self.storage.list_closed_issues()?);
+      let mut remapped: Vec<RemappedFiles> = issues
+         .iter()
+         .filter_map(|issue| {
+            let meta = &issue.issue.metadata;
+            let after: Vec<String> = meta
+               .files
+               .iter()
+               .map(|f| {
+                  files::remap(f, &old, &new, root.as_deref()).unwrap_or_else(|| f.to_string())
+               })
+               .collect();
+            let before: Vec<String> = meta.files.iter().map(|f| f.to_string()).collect();
+            (after != before).then(|| RemappedFiles {
+               bug_num: issue.id,
+               title: meta.title.to_string(),
+               before,
+               after,
+            })
+         })
+         .collect();
+      remapped.sort_by_key(|r| r.bug_num);
+
+      let targets: Vec<u32> = remapped.iter().map(|r| r.bug_num).collect();
+      if dry_run || !self.confirm_bulk("remap files on", &targets, json)? {
+         return Ok(FilesRemapResult { old, new, remapped, dry_run: true });
+      }
+      let failed = self.all_or_nothing(|| {
+         for change in &remapped {
+            let update = self.storage.update_issue_metadata(change.bug_num, |meta| {
+               meta.files = change.after.iter().map(SmolStr::from).collect();
+               meta.files.dedup();
+            });
+            if let Err(e) = update {
+               return Some((change.bug_num.to_string(), e.to_string()));
+            }
+         }
+         None
+      })?;
+      if let Some((bug_num, e)) = failed {
+         anyhow::bail!("Remapping files on {bug_num} failed, nothing was changed: {e}");
+      }
+      Ok(FilesRemapResult { old, new, remapped, dry_run: false })
+   }
+
+   /// Open issues breaking the `lint` rules
+   pub fn lint_data(&self) -> Result<Vec<Finding>> {
+      let issues = self.storage.list_open_issues()?;
+      let root = repo_root()?;
+      Ok(lint::lint(&issues, &self.config.lint, Utc::now(), |path| root.join(path).exists()))
+   }
+
+   /// Open and closed issues targeting release `version`
+   pub fn release_status_data(&self, version: &str) -> Result<ReleaseStatus> {
+      let mut issues = self.storage.list_open_issues()?;
+      issues.extend(self.storage.list_closed_issues()?);
+      let status = release::status(version, &issues);
+      if status.open.is_empty() && status.closed.is_empty() {
+         anyhow::bail!(
+            "No issues target {version}; set them with `agentx bulk-edit --target-version \
+             {version}`"
+         );
+      }
+      Ok(status)
+   }
+
+   /// Cut release `version` unless a critical issue for it is open: build
+   /// its changelog section, added to the `changelog` file when given
+   pub fn release_cut_data(
+      &self,
+      version: &str,
+      changelog: Option<&str>,
+   ) -> Result<ReleaseCutResult> {
+      let status = self.release_status_data(version)?;
+      let (critical_open, carried_over): (Vec<ReleaseIssue>, Vec<ReleaseIssue>) = status
+         .open
+         .iter()
+         .cloned()
+         .partition(|i| i.priority == Priority::Critical);
+      let mut result = ReleaseCutResult {
+         version: version.to_string(),
+         cut: critical_open.is_empty(),
+         critical_open,
+         carried_over,
+         changelog: None,
+         written_to: None,
+      };
+      if !result.cut {
+         return Ok(result);
+      }
+
+      let section = release::changelog(
+         version,
+         &status.closed,
+         &self.config.kinds,
+         Utc::now().date_naive(),
+         |n| self.config.format_issue_ref(n),
+      );
+      if let Some(path) = changelog {
+         let existing = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {path}")),
+         };
+         if release::has_section(&existing, version) {
+            anyhow::bail!("{path} already has a {version} section");
+         }
+         std::fs::write(path, release::insert_section(&existing, &section))
+            .with_context(|| format!("Failed to write {path}"))?;
+         result.written_to = Some(path.to_string());
+      }
+      result.changelog = Some(section);
+      Ok(result)
+   }
+
+   /// Issues, open or closed, matching `fail_on`, and whether there are at
+   /// most `max` of them
+   pub fn gate_data(&self, fail_on: &str, max: usize) -> Result<GateResult> {
+      let query = Query::parse(fail_on)?;
+      let mut issues = self.storage.list_open_issues()?;
+      issues.extend(self.storage.list_closed_issues()?);
+      Ok(gate::evaluate(&query, issues, max))
+   }
+
+   /// New, closed, blocked and stale issues over the last `period`, with
+   /// metrics compared to the period before
+   pub fn digest_data(&self, period: &str, stale_days: i64) -> Result<Digest> {
+      let length = period_length(period)?;
+      let mut issues = self.storage.list_open_issues()?;
+      issues.extend(self.storage.list_closed_issues()?);
+      // Whole seconds keep the JSON as stable as the markdown
+      let now = Utc::now().trunc_subsecs(0);
+      Ok(digest(&issues, period, length, stale_days, now))
+   }
+
+   /// Closed and checkpointed issues since `since` ago (default: the start
+   /// of the last working day), the issues in progress and the top `ready`
+   /// ready ones, and blocked issues
+   pub fn standup_data(&self, since: Option<&str>, ready: usize) -> Result<Standup> {
+      let now = Utc::now().trunc_subsecs(0);
+      let from = match since {
+         Some(since) => now - parse_duration(since)?,
+         None => {
+            let today = chrono::Local::now().date_naive();
+            previous_working_day(today)
+               .and_hms_opt(0, 0, 0)
+               .and_then(|start| start.and_local_timezone(chrono::Local).earliest())
+               .map_or(now - Duration::days(1), |start| start.with_timezone(&Utc))
+         },
+      };
+      let mut issues = self.storage.list_open_issues()?;
+      issues.extend(self.storage.list_closed_issues()?);
+      let ready: Vec<u32> = self
+         .ready_data()?
+         .into_iter()
+         .take(ready)
+         .map(|issue| issue.num)
+         .collect();
+      Ok(standup(&issues, &ready, from, now))
+   }
+
+   /// The issues `list` would show for the same filters, as one document
+   pub fn export_data(
+      &self,
+      status: &str,
+      scope: &ListScope,
+      where_clause: Option<&str>,
+      format: ExportFormat,
+   ) -> Result<ExportResult> {
+      let filter = scope.filter(where_clause)?;
+      let issues = self
+         .list_data(scope.status(status), filter.as_ref())?
+         .issues;
+      let text = export::render(&issues, format, |n| self.config.format_issue_ref(n))?;
+      Ok(ExportResult {
+         format: format!("{format:?}").to_lowercase(),
+         mime_type: format.mime_type().to_string(),
+         count: issues.len(),
+         text,
+      })
+   }
+
+   /// Create an encryption key for the issue store
+   pub fn crypt_init_data(&self) -> Result<CryptResult> {
+      let (config, key_file) = self.storage.init_crypt()?;
+      Ok(CryptResult {
+         key_id:    config.key_id,
+         locked:    config.locked,
+         key_file:  Some(key_file),
+         rewritten: 0,
+      })
+   }
+
+   /// Encrypt (`locked`) or decrypt every issue body
+   pub fn crypt_set_locked_data(&self, locked: bool) -> Result<CryptResult> {
+      let rewritten = self.storage.set_locked(locked)?;
+      let config = self
+         .storage
+         .crypt_config()?
+         .context("Encryption is not set up; run `agentx crypt init` first")?;
+      Ok(CryptResult { key_id: config.key_id, locked: config.locked, key_file: None, rewritten })
+   }
+
+   /// Open issues estimated at or under `threshold` that wait on no
+   /// unfinished dependency; with `only_ready`, just those not started yet
+   pub fn quick_wins_data(
+      &self,
+      threshold: &str,
+      only_ready: bool,
+      sort: WinsSort,
+   ) -> Result<Vec<RankedIssue>> {
+      let threshold_minutes = parse_effort(threshold)?;
+      let issues = self.storage.list_open_issues()?;
+      let unblocks = unblock_counts(&issues);
+      let unfinished: HashSet<u32> = issues
+         .iter()
+         .filter(|issue_with_id| issue_with_id.issue.metadata.status != Status::Done)
+         .map(|issue_with_id| issue_with_id.id)
+         .collect();
+      let now = Utc::now();
+
+      let mut quick: Vec<_> = issues
+         .iter()
+         .filter(|issue_with_id| {
+            let meta = &issue_with_id.issue.metadata;
+            !meta.depends_on.iter().any(|dep| unfinished.contains(dep))
+               && (!only_ready || meta.status == Status::NotStarted)
+         })
+         .filter_map(|issue_with_id| {
+            let minutes = issue_with_id
+               .issue
+               .metadata
+               .effort
+               .as_ref()
+               .and_then(|e| parse_effort(e).ok())
+               .filter(|&m| m <= threshold_minutes)?;
+            let count = unblocks.get(&issue_with_id.id).copied().unwrap_or(0);
+            Some((issue_with_id, minutes, count, issue_with_id.issue.last_activity()))
+         })
+         .collect();
+
+      match sort {
+         WinsSort::Impact => quick.sort_by_key(|(i, _, count, _)| {
+            (rank_key(i.issue.metadata.priority, *count), i.stable_key())
+         }),
+         WinsSort::Effort => quick.sort_by_key(|(i, minutes, ..)| (*minutes, i.stable_key())),
+         WinsSort::Age => quick.sort_by_key(|(i, _, _, active)| (*active, i.stable_key())),
+      }
+      Ok(quick
+         .into_iter()
+         .map(|(issue_with_id, _, count, active)| RankedIssue {
+            idle_days: Some((now - active).num_days()),
+            ..RankedIssue::new(issue_with_id, count)
+         })
+         .collect())
+   }
+
+   pub fn duplicates_data(
+      &self,
+      status: &str,
+      threshold: Option<f64>,
+   ) -> Result<Vec<DuplicatePair>> {
+      let threshold = threshold.unwrap_or(self.config.similarity_threshold);
+      if !(0.0..=1.0).contains(&threshold) {
+         anyhow::bail!("Invalid threshold: {threshold}. Use a value between 0.0 and 1.0");
+      }
+
+      let issues = self.list_data(status, None)?.issues;
+      Ok(find_duplicates(&issues, threshold))
+   }
+
+   /// Run a bulk change under the store lock; when `apply` reports a
+   /// failure, every issue file is put back as it was, so the change lands
+   /// whole or not at all
+   pub(crate) fn all_or_nothing(
+      &self,
+      apply: impl FnOnce() -> Option<BulkError>,
+   ) -> Result<Option<BulkError>> {
+      let _lock = self.storage.lock()?;
+      let snapshot = self.storage.snapshot()?;
+      let failure = apply();
+      if failure.is_some() {
+         self.storage.restore(&snapshot).context(
+            "Rolling back the bulk change failed; check `git status` in the issues directory",
+         )?;
+      }
+      Ok(failure)
+   }
+
+   /// Issues started, closed or checkpointed in the last `hours` (default 24)
+   ///
+   /// Checkpoints count by their own timestamp, so an issue whose only
+   /// checkpoints predate the window isn't reported.
+   pub fn summary_data(&self, hours: Option<u64>) -> Result<SummaryResult> {
+      let hours = hours.unwrap_or(24);
+      let since = Utc::now() - Duration::hours(hours as i64);
+
+      let open_issues = self.storage.list_open_issues()?;
+      let closed_issues = self.storage.list_closed_issues()?;
+
+      let started = open_issues
+         .iter()
+         .filter(|i| i.issue.metadata.started.is_some_and(|at| at > since))
+         .map(|i| i.id)
+         .collect();
+      let closed = closed_issues
+         .iter()
+         .filter(|i| i.issue.metadata.closed.is_some_and(|at| at > since))
+         .map(|i| i.id)
+         .collect();
+
+      let mut checkpointed = Vec::new();
+      for issue_with_id in open_issues.iter().chain(&closed_issues) {
+         let checkpoints: Vec<SummaryCheckpoint> = issue_with_id
+            .issue
+            .body
+            .checkpoints()
+            .filter_map(|c| {
+               let at = c.timestamp().filter(|at| *at > since)?;
+               Some(SummaryCheckpoint { at, note: c.content().to_string() })
+            })
+            .collect();
+         if !checkpoints.is_empty() {
+            checkpointed.push(CheckpointedIssue {
+               bug_num: issue_with_id.id,
+               title: issue_with_id.issue.metadata.title.to_string(),
+               checkpoints,
+            });
+         }
+      }
+
+      Ok(SummaryResult { since, hours, started, closed, checkpointed })
+   }
+
+   pub fn session_start_data(&self, note: Option<String>) -> Result<Session> {
+      if let Some(running) = self.storage.load_session()? {
+         anyhow::bail!(
+            "A session is already running since {}; end it with 'agentx session end'",
+            running.started.format("%Y-%m-%d %H:%M UTC")
+         );
+      }
+
+      // Checkpoints are only stamped to the minute; starting on a whole minute
+      // keeps one written right after `session start` inside the session
+      let started = Utc::now().duration_trunc(Duration::minutes(1))?;
+      let session = Session { started, note };
+      self.storage.save_session(Some(&session))?;
+      Ok(session)
+   }
+
+   /// The running session's activity so far, without ending it
+   pub fn session_status_data(&self) -> Result<Option<SessionSummary>> {
+      let Some(session) = self.storage.load_session()? else {
+         return Ok(None);
+      };
+      let mut issues = self.storage.list_open_issues()?;
+      issues.extend(self.storage.list_closed_issues()?);
+      Ok(Some(summarize(&session, Utc::now(), &issues)))
+   }
+
+   /// End the running session, writing its summary to the journal and, with
+   /// `annotate`, to every issue it touched
+   pub fn session_end_data(&self, annotate: bool) -> Result<SessionSummary> {
+      let summary = self.session_status_data()?.ok_or_else(|| {
+         anyhow::anyhow!("No session running; start one with 'agentx session start'")
+      })?;
+
+      self
+         .storage
+         .append_journal(&summary.to_markdown(|n| self.config.format_issue_ref(n)))?;
+
+      if annotate {
+         for activity in &summary.issues {
+            let base = self.storage.load_issue(activity.bug_num)?;
+            let mut issue = base.clone();
+            issue.body.append(&summary.issue_section(activity));
+            let is_open = issue.metadata.status != Status::Closed;
+            self.save_edited(&base, &issue, activity.bug_num, is_open)?;
+         }
+      }
+
+      self.storage.save_session(None)?;
+      Ok(summary)
+   }
+
+   pub fn depend_data(
+      &self,
+      bug_ref: &str,
+      add_deps: Vec<String>,
+      remove_deps: Vec<String>,
+   ) -> Result<DependResult> {
+      let bug_num = self.resolve_ref(bug_ref)?;
+
+      // Resolve all dependency references
+      let mut add_nums = Vec::new();
+      for dep_ref in &add_deps {
+         let dep_num = self.resolve_ref(dep_ref)?;
+         // Verify dependency exists
+         self.storage.load_issue(dep_num)?;
+         add_nums.push(dep_num);
+      }
+
+      let mut remove_nums = Vec::new();
+      for dep_ref in &remove_deps {
+         let dep_num = self.resolve_ref(dep_ref)?;
+         remove_nums.push(dep_num);
+      }
+
+      // Check for cycles before adding: one closes if the dependency already
+      // waits on this issue
+      let mut issues = self.storage.list_open_issues()?;
+      issues.extend(self.storage.list_closed_issues()?);
+      let graph = IssueGraph::from_issues(&issues);
+      for &dep_num in &add_nums {
+         if graph.reaches(dep_num, bug_num) {
+            anyhow::bail!(
+               "Adding {} as dependency would create a cycle ({} transitively depends on {})",
+               self.config.format_issue_ref(dep_num),
+               self.config.format_issue_ref(dep_num),
+               self.config.format_issue_ref(bug_num)
+            );
+         }
+      }
+
+      // Update dependencies
+      self.storage.update_issue_metadata(bug_num, |meta| {
+         // Add new dependencies
+         for dep_num in add_nums.iter() {
+            if !meta.depends_on.contains(dep_num) {
+               meta.depends_on.push(*dep_num);
+            }
+         }
+
+         // Remove dependencies
+         meta.depends_on.retain(|&d| !remove_nums.contains(&d));
+
+         // Sort for consistent ordering
+         meta.depends_on.sort_unstable();
+      })?;
+
+      // Load updated issue
+      let issue = self.storage.load_issue(bug_num)?;
+
+      Ok(DependResult {
+         bug_num,
+         added: add_nums,
+         removed: remove_nums,
+         depends_on: issue.metadata.depends_on,
+      })
+   }
+
+   pub fn tags_stats_data(&self) -> Result<Vec<TagStat>> {
+      let mut issues = self.storage.list_open_issues()?;
+      issues.extend(self.storage.list_closed_issues()?);
+      Ok(tag_stats(&issues))
+   }
+
+   /// Tags that historically co-occur with the given title keywords and file
+   /// paths, excluding `present`
+   pub fn suggest_tags_data(
+      &self,
+      title: &str,
+      files: &[impl AsRef<str>],
+      present: &[impl AsRef<str>],
+   ) -> Result<Vec<TagSuggestion>> {
+      let mut history = self.storage.list_open_issues()?;
+      history.extend(self.storage.list_closed_issues()?);
+      Ok(suggest_tags(title, files, present, &history))
+   }
+
+   /// The dependency chain among open issues with the most effort, plus
+   /// any cycles found; issues without an estimate count as
+   /// `effort.unestimated`
+   #[tracing::instrument(level = "debug", skip_all)]
+   pub fn critical_path_data(&self) -> Result<CriticalPathResult> {
+      let issues = self.storage.list_open_issues()?;
+      let issue_map: HashMap<u32, &IssueWithId> = issues.iter().map(|i| (i.id, i)).collect();
+      let mut efforts: HashMap<u32, (u32, bool)> = HashMap::new();
+      for issue in &issues {
+         let effort = issue.issue.metadata.effort.as_deref();
+         efforts.insert(issue.id, self.config.effort.minutes_or_default(effort)?);
+      }
+      let graph = IssueGraph::from_issues(&issues);
+      let cycles = graph.cycles();
+      let heaviest = graph.heaviest_chain(|id| efforts.get(&id).map_or(0, |&(minutes, _)| minutes));
+
+      let mut total_minutes = 0;
+      let chain: Vec<_> = heaviest
+         .iter()
+         .filter_map(|&id| issue_map.get(&id).copied())
+         .map(|issue| {
+            let (effort_minutes, estimated) = efforts[&issue.id];
+            total_minutes += effort_minutes;
+            CriticalStep {
+               node: GraphNode::new(issue),
+               effort_minutes,
+               cumulative_minutes: total_minutes,
+               estimated,
+            }
+         })
+         .collect();
+      let unestimated = chain.iter().filter(|step| !step.estimated).count();
+
+      Ok(CriticalPathResult { length: chain.len(), chain, total_minutes, unestimated, cycles })
+   }
+
+   /// Dependency cycles among open issues, each with the weakest edge in it
+   /// suggested for dropping
+   pub fn cycle_repairs_data(&self) -> Result<Vec<Cycle>> {
+      let open = self.storage.list_open_issues()?;
+      let mut versions: HashMap<u32, Vec<history::Version>> = HashMap::new();
+      let mut cycles = Vec::new();
+      for component in IssueGraph::from_issues(&open).cycles() {
+         for &id in &component {
+            // Outside git there is no history to date edges with, and the
+            // suggestion falls back to priorities
+            versions
+               .entry(id)
+               .or_insert_with(|| history::versions(&self.storage, id).unwrap_or_default());
+         }
+         cycles.push(cycle(&component, &open, |dependent, dependency| {
+            dependency_added(versions.get(&dependent)?, dependency)
+         }));
+      }
+      Ok(cycles)
+   }
+
+   /// Drop the suggested edge of every cycle until none are left
+   pub fn fix_cycles_data(&self) -> Result<CyclesResult> {
+      let mut dropped = Vec::new();
+      while let Some(cycle) = self.cycle_repairs_data()?.into_iter().next() {
+         let edge = cycle.suggested().clone();
+         self.drop_dependency(edge.dependent, edge.dependency)?;
+         dropped.push(edge);
+      }
+      Ok(CyclesResult { cycles: Vec::new(), dropped })
+   }
+
+   /// Remove `dependent`'s dependency on `dependency`; storage drops the
+   /// reverse link
+   pub(crate) fn drop_dependency(&self, dependent: u32, dependency: u32) -> Result<()> {
+      self.storage.update_issue_metadata(dependent, |meta| {
+         meta.depends_on.retain(|&d| d != dependency);
+      })
+   }
+
+   /// Open issues in the dependency graph; with `focus_issue`, only those
+   /// connected to it
+   #[tracing::instrument(level = "debug", skip_all)]
+   /// Open issues as graph nodes; with a focus issue, only those connected
+   /// to it, at most `depth` dependency steps away when given
+   pub fn deps_graph_data(
+      &self,
+      focus_issue: Option<&str>,
+      depth: Option<usize>,
+   ) -> Result<Vec<GraphNode>> {
+      let issues = self.storage.list_open_issues()?;
+
+      // If focus issue provided, filter to show only that issue and its dependencies
+      let relevant_issues: Vec<u32> = if let Some(ref_str) = focus_issue {
+         let focus_num = self.resolve_ref(ref_str)?;
+         IssueGraph::from_issues(&issues).closure(focus_num, depth)
+      } else {
+         if depth.is_some() {
+            anyhow::bail!("--depth needs --issue to count from");
+         }
+         issues.iter().map(|i| i.id).collect()
+      };
+
+      Ok(issues
+         .iter()
+         .filter(|i| relevant_issues.contains(&i.id))
+         .map(GraphNode::new)
+         .collect())
+   }
+
+   /// Metrics over `period`, optionally broken down per tag, per assignee
+   /// and per kind
+   pub fn metrics_data(
+      &self,
+      period: &str,
+      by_tag: bool,
+      by_assignee: bool,
+      by_kind: bool,
+   ) -> Result<MetricsResult> {
+      let open_issues = self.storage.list_open_issues()?;
+      let closed_issues = self.storage.list_closed_issues()?;
+
+      // Determine time period
+      let now = Utc::now();
+      let since = match period {
+         "day" => now - Duration::days(1),
+         "week" => now - Duration::weeks(1),
+         "month" => now - Duration::days(30),
+         "all" => Utc::now() - Duration::days(36500), // ~100 years
+         _ => anyhow::bail!("Invalid period: {}. Use: day, week, month, all", period),
+      };
+
+      // Count closed issues in period
+      let closed_in_period: Vec<_> = closed_issues
+         .iter()
+         .filter(|issue_with_id| {
+            if let Some(closed_time) = issue_with_id.issue.metadata.closed {
+               closed_time > since
+            } else {
+               false
+            }
+         })
+         .collect();
+
+      // Count opened issues in period
+      let opened_in_period: Vec<_> = open_issues
+         .iter()
+         .chain(closed_issues.iter())
+         .filter(|issue_with_id| issue_with_id.issue.metadata.created > since)
+         .collect();
+
+      // Calculate average time to close, in working hours with a calendar
+      let mut close_times = Vec::new();
+      for issue_with_id in &closed_in_period {
+         if let (Some(created), Some(closed)) =
+            (Some(issue_with_id.issue.metadata.created), issue_with_id.issue.metadata.closed)
+         {
+            let duration = match &self.config.calendar {
+               Some(calendar) => calendar.between(created, closed)?,
+               None => closed - created,
+            };
+            close_times.push(duration.num_hours());
+         }
+      }
+
+      let avg_close_time = if !close_times.is_empty() {
+         close_times.iter().sum::<i64>() / close_times.len() as i64
+      } else {
+         0
+      };
+
+      // Count open issues by priority and status
+      let mut by_priority = BTreeMap::new();
+      let mut by_status = BTreeMap::new();
+      for priority in [Priority::Critical, Priority::High, Priority::Medium, Priority::Low] {
+         by_priority.insert(priority.to_string(), 0);
+      }
+      for status in [Status::NotStarted, Status::InProgress, Status::Blocked, Status::Backlog] {
+         by_status.insert(status.to_string(), 0);
+      }
+      for issue_with_id in &open_issues {
+         *by_priority
+            .entry(issue_with_id.issue.metadata.priority.to_string())
+            .or_insert(0) += 1;
+         *by_status
+            .entry(issue_with_id.issue.metadata.status.to_string())
+            .or_insert(0) += 1;
+      }
+
+      let all_issues: Vec<_> = open_issues.iter().chain(&closed_issues).cloned().collect();
+      let flow = flow_metrics(&all_issues, since, now);
+      let by_tag = by_tag.then(|| {
+         slice_metrics(
+            &all_issues,
+            |i| {
+               i.issue
+                  .metadata
+                  .tags
+                  .iter()
+                  .map(|t| t.to_string())
+                  .collect()
+            },
+            since,
+            now,
+         )
+      });
+      let by_kind = by_kind.then(|| {
+         slice_metrics(
+            &all_issues,
+            |i| {
+               i.issue
+                  .metadata
+                  .kind
+                  .iter()
+                  .map(|k| k.to_string())
+                  .collect()
+            },
+            since,
+            now,
+         )
+      });
+      let by_assignee = by_assignee.then(|| {
+         slice_metrics(
+            &all_issues,
+            |i| {
+               let meta = &i.issue.metadata;
+               meta
+                  .assignee
+                  .clone()
+                  .or_else(|| meta.claim.as_ref().map(|c| c.agent.clone()))
+                  .map(|a| a.to_string())
+                  .into_iter()
+                  .collect()
+            },
+            since,
+            now,
+         )
+      });
+
+      Ok(MetricsResult {
+         period: period.to_string(),
+         total_open: open_issues.len(),
+         total_closed: closed_issues.len(),
+         opened_in_period: opened_in_period.len(),
+         closed_in_period: closed_in_period.len(),
+         avg_close_time_hours: avg_close_time,
+         by_priority,
+         by_status,
+         flow,
+         by_tag,
+         by_assignee,
+         by_kind,
+      })
+   }
+
+   /// The statuses the transition rules allow moving between, with the
+   /// issues in each and how long they stay
+   pub fn workflow_show_data(&self) -> Result<Vec<WorkflowState>> {
+      let mut all = self.storage.list_open_issues()?;
+      all.extend(self.storage.list_closed_issues()?);
+      Ok(workflow::workflow(&self.config.transitions, &all, Utc::now()))
+   }
+
+   /// Opened, closed, open and blocked counts per day or week, for plotting
+   /// burndown. `from` and `to` take a date, an age like `30d` or a git
+   /// revision; `to` defaults to now and `from` to 30 days or 12 weeks
+   /// before it.
+   pub fn metrics_series_data(
+      &self,
+      from: Option<&str>,
+      to: Option<&str>,
+      granularity: &str,
+   ) -> Result<MetricsSeriesResult> {
+      let granularity = parse_granularity(granularity)?;
+      let now = Utc::now();
+      let to = match to {
+         Some(to) => history::since_time(&self.storage, to)?,
+         None => now,
+      };
+      let from = match from {
+         Some(from) => history::since_time(&self.storage, from)?,
+         None => match granularity {
+            Granularity::Day => to - Duration::days(30),
+            Granularity::Week => to - Duration::weeks(12),
+         },
+      };
+      let mut issues = self.storage.list_open_issues()?;
+      issues.extend(self.storage.list_closed_issues()?);
+      let points = metrics_series(&issues, from, to, granularity, now)?;
+      Ok(MetricsSeriesResult { granularity, from, to, points })
+   }
+
+   pub fn forecast_data(
+      &self,
+      milestone: Option<&str>,
+      chain: Option<&str>,
+      weeks: u32,
+   ) -> Result<Forecast> {
+      let open_issues = self.storage.list_open_issues()?;
+
+      let (scope, remaining): (String, Vec<IssueWithId>) = if let Some(bug_ref) = chain {
+         // The issue plus everything it transitively depends on that is still open
+         let root = self.resolve_ref(bug_ref)?;
+         let mut chain = std::collections::BTreeSet::new();
+         let mut to_visit = vec![root];
+         while let Some(id) = to_visit.pop() {
+            let Some(issue_with_id) = open_issues.iter().find(|i| i.id == id) else {
+               continue;
+            };
+            if chain.insert(id) {
+               to_visit.extend(issue_with_id.issue.metadata.depends_on.iter().copied());
+            }
+         }
+         (
+            format!("chain {}", self.config.format_issue_ref(root)),
+            open_issues
+               .into_iter()
+               .filter(|i| chain.contains(&i.id))
+               .collect(),
+         )
+      } else if let Some(milestone) = milestone {
+         (
+            format!("milestone {milestone}"),
+            open_issues
+               .into_iter()
+               .filter(|i| {
+                  i.issue
+                     .metadata
+                     .milestone
+                     .as_deref()
+                     .is_some_and(|m| m.eq_ignore_ascii_case(milestone))
+               })
+               .collect(),
+         )
+      } else {
+         (
+            "open issues".to_string(),
+            open_issues
+               .into_iter()
+               .filter(|i| i.issue.metadata.status != Status::Backlog)
+               .collect(),
+         )
+      };
+
+      let closed_issues = self.storage.list_closed_issues()?;
+      forecast(scope, &remaining, &closed_issues, Utc::now(), weeks, self.config.calendar.as_ref())
+   }
+
+   /// Effort estimates of closed issues against their time in progress
+   pub fn calibration_data(&self) -> Result<Calibration> {
+      Ok(calibrate(&self.storage.list_closed_issues()?))
+   }
+
+   /// Open issues (or a milestone's, with their prerequisites) in batches
+   /// that respect dependencies
+   pub fn order_data(&self, milestone: Option<&str>) -> Result<ExecutionOrder> {
+      let open_issues = self.storage.list_open_issues()?;
+      let selected: Vec<IssueWithId> = match milestone {
+         Some(milestone) => open_issues
+            .iter()
+            .filter(|i| {
+               i.issue
+                  .metadata
+                  .milestone
+                  .as_deref()
+                  .is_some_and(|m| m.eq_ignore_ascii_case(milestone))
+            })
+            .cloned()
+            .collect(),
+         None => open_issues.clone(),
+      };
+      Ok(order(&selected, &open_issues))
+   }
+
+   /// Issues in a column per status, open/active/blocked/done unless
+   /// `columns` picks others
+   pub fn board_data(&self, columns: Option<&str>) -> Result<Vec<BoardColumn>> {
+      let statuses = match columns {
+         Some(spec) => parse_columns(spec)?,
+         None => DEFAULT_COLUMNS.to_vec(),
+      };
+      let mut issues = self.storage.list_open_issues()?;
+      if statuses.contains(&Status::Closed) {
+         issues.extend(self.storage.list_closed_issues()?);
+      }
+      Ok(board::columns(&issues, &statuses))
+   }
+
+   /// Pick open issues to fill `capacity` (e.g. `30h`) in dependency order,
+   /// optionally tagging them with the next `sprint-N` tag
+   pub fn plan_data(&self, capacity: &str, milestone: Option<&str>, tag: bool) -> Result<Plan> {
+      let capacity_minutes = parse_effort(capacity)?;
+      let open_issues = self.storage.list_open_issues()?;
+      let candidates: Vec<IssueWithId> = match milestone {
+         Some(milestone) => open_issues
+            .iter()
+            .filter(|i| {
+               i.issue
+                  .metadata
+                  .milestone
+                  .as_deref()
+                  .is_some_and(|m| m.eq_ignore_ascii_case(milestone))
+            })
+            .cloned()
+            .collect(),
+         None => open_issues.clone(),
+      };
+
+      let mut result = plan(&candidates, &open_issues, capacity_minutes);
+      if tag && !result.issues.is_empty() {
+         let mut all_issues = open_issues;
+         all_issues.extend(self.storage.list_closed_issues()?);
+         let sprint_tag = next_sprint_tag(&all_issues);
+         for planned in &result.issues {
+            self.storage.update_issue_metadata(planned.num, |meta| {
+               meta.tags.push(SmolStr::from(sprint_tag.as_str()));
+            })?;
+         }
+         result.sprint_tag = Some(sprint_tag);
+      }
+      Ok(result)
+   }
+
+   /// Dependency cycles among open issues
+   pub fn cycles_data(&self) -> Result<Vec<Vec<u32>>> {
+      Ok(IssueGraph::from_issues(&self.storage.list_open_issues()?).cycles())
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::issue::Status;
+
+   /// "Design schema" (#1) and "Write migration" (#2), which depends on it
+   fn schema_and_migration() -> IssueTracker {
+      let tracker = IssueTracker::in_memory();
+      tracker
+         .create(
+            NewIssue::new("Design schema")
+               .priority(Priority::High)
+               .effort("30m"),
+         )
+         .unwrap();
+      tracker
+         .create(NewIssue::new("Write migration").tag("db").effort("30m"))
+         .unwrap();
+      tracker.depend("2", &["1"], &[]).unwrap();
+      tracker
+   }
+
+   #[test]
+   fn test_create_and_show() {
+      let tracker = schema_and_migration();
+      let shown = tracker.show("schema").unwrap();
+      assert!(shown.path.ends_with("01-design-schema.mdx"));
+      assert_eq!(tracker.resolve("migration").unwrap(), 2);
+      assert!(!tracker.storage.base_dir().exists());
+   }
+
+   #[test]
+   fn test_close_needs_an_unambiguous_title() {
+      let tracker = schema_and_migration();
+      tracker.create(NewIssue::new("Design schema docs")).unwrap();
+      assert_eq!(tracker.resolve("design schema").unwrap(), 1);
+      assert!(tracker.close("design schema", None).is_err());
+      assert!(tracker.close("docs", None).is_ok());
+      assert!(tracker.resolve("sign").is_err());
+   }
+
+   #[test]
+   fn test_quick_wins_and_why() {
+      let tracker = schema_and_migration();
+      let wins = tracker.quick_wins("1h").unwrap();
+      assert_eq!(wins.iter().map(|w| w.num).collect::<Vec<_>>(), [1]);
+      let why = tracker.why("migration").unwrap();
+      assert_eq!((why.rank, why.waiting_on), (Some(2), vec![1]));
+   }
+
+   #[test]
+   fn test_query_and_list() {
+      let tracker = schema_and_migration();
+      tracker.start("schema").unwrap();
+      assert_eq!(tracker.query("status:active").unwrap()[0].id, 1);
+      assert_eq!(tracker.list("in_progress").unwrap()[0].id, 1);
+      assert!(tracker.list("someday").is_err());
+   }
+
+   #[test]
+   fn test_list_takes_status_synonyms() {
+      let tracker = schema_and_migration();
+      tracker.start("schema").unwrap();
+      tracker
+         .storage
+         .update_issue_metadata(2, |meta| meta.status = Status::Done)
+         .unwrap();
+      let ids = |status: &str| -> Vec<u32> {
+         tracker.list(status).unwrap().iter().map(|i| i.id).collect()
+      };
+      assert_eq!(ids("in_progress"), [1]);
+      assert_eq!(ids("active"), [1]);
+      assert_eq!(ids("In-Progress"), [1]);
+      assert_eq!(ids("done"), [2]);
+      assert_eq!(tracker.list_data("in_progress", None).unwrap().status, "in_progress");
+   }
+
+   #[test]
+   fn test_dependency_graph() {
+      let tracker = schema_and_migration();
+      assert_eq!(tracker.dependents(1).unwrap(), vec![2]);
+      assert_eq!(tracker.unblock_counts().unwrap()[&1], 1);
+      assert!(tracker.cycles().unwrap().is_empty());
+      assert!(tracker.start("migration").is_err());
+   }
+
+   #[test]
+   fn test_summary_skips_old_checkpoints() {
+      let tracker = schema_and_migration();
+      let mut old = tracker.get(2).unwrap();
+      old.body
+         .append("\n\n**Checkpoint** (2020-01-01 10:00): Long ago");
+      tracker.storage.save_issue(&old, 2, true).unwrap();
+      assert!(tracker.summary(24).unwrap().checkpointed.is_empty());
+
+      tracker.checkpoint("migration", "Drafted").unwrap();
+      let summary = tracker.summary(24).unwrap();
+      assert_eq!(summary.checkpointed[0].checkpoints[0].note, "Drafted");
+   }
+
+   #[test]
+   fn test_claims_and_file_conflicts() {
+      let tracker = IssueTracker::in_memory();
+      tracker
+         .create(NewIssue::new("Seed data").file("db/seeds"))
+         .unwrap();
+      tracker.start("seed").unwrap();
+      tracker
+         .create(NewIssue::new("Fixtures").file("db/seeds/users.sql"))
+         .unwrap();
+
+      let alpha = tracker.clone().as_agent("alpha");
+      let beta = tracker.clone().as_agent("beta");
+      alpha.claim("fixtures", "1h").unwrap();
+      assert!(beta.claim("fixtures", "1h").is_err());
+      assert!(beta.start("fixtures").is_err());
+      assert!(beta.release("fixtures").is_err());
+
+      let conflicts = alpha.start("fixtures").unwrap().file_conflicts;
+      assert_eq!(conflicts[0].num, 1);
+      assert_eq!(conflicts[0].matched_files, ["db/seeds"]);
+   }
+
+   #[test]
+   fn test_close_unblocking() {
+      let tracker = schema_and_migration();
+      tracker.create(NewIssue::new("Seed data")).unwrap();
+      tracker.start("seed").unwrap();
+      tracker.block("seed", "waiting on ISSUE-1").unwrap();
+
+      let closed = tracker.close_unblocking("schema", None).unwrap();
+      assert_eq!(closed.unblocked[0].bug_num, 2);
+      assert_eq!(closed.unblock_candidates[0].bug_num, 3);
+      assert_eq!(tracker.get(3).unwrap().metadata.status, Status::InProgress);
+      assert_eq!(tracker.list("closed").unwrap().len(), 1);
+      tracker.start("migration").unwrap();
+   }
+
+   #[test]
+   fn test_one_claim_wins_a_race() {
+      let tracker = IssueTracker::in_memory();
+      tracker.create(NewIssue::new("Contended")).unwrap();
+      let agents: Vec<_> = (0..8)
+         .map(|n| {
+            let agent = tracker.clone().as_agent(&format!("agent-{n}"));
+            std::thread::spawn(move || agent.claim("contended", "1h").is_ok())
+         })
+         .collect();
+      let won = agents
+         .into_iter()
+         .map(|agent| agent.join().unwrap())
+         .filter(|won| *won)
+         .count();
+      assert_eq!(won, 1);
+   }
+
+   #[test]
+   fn test_reimport_with_a_new_title_renames_the_file() {
+      let tracker = IssueTracker::in_memory();
+      let import = |title: &str| {
+         let yaml = format!(
+            "- title: {title}\n  external_id: SPEC-1\n  issue: Spec\n  impact: Spec\n  \
+             acceptance: Spec\n"
+         );
+         tracker.import_data(&yaml, false).unwrap()
+      };
+      assert_eq!(import("Draft spec").created, [1]);
+      assert_eq!(import("Final spec").updated, [1]);
+      assert_eq!(tracker.storage.list_all_bug_numbers().unwrap(), [1]);
+      assert!(
+         tracker
+            .show("1")
+            .unwrap()
+            .path
+            .ends_with("01-final-spec.mdx")
+      );
+   }
+}
@@ -0,0 +1,252 @@
+//! Audit trail of issue changes
+//!
+//! Every save that changes an issue appends a line to `issues/audit.jsonl`
+//! saying who made it (the actor), through which command or MCP tool, and
+//! what changed, so the changes of different agents sharing one tracker can
+//! be told apart. The actor is `$AGENTX_ACTOR`, else the MCP client's name,
+//! else the login name. `agentx audit` filters the trail by actor, issue and
+//! date.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+use crate::{body::SectionKind, issue::Issue, watch::current_user};
+
+/// Frontmatter keys that change as a side effect of others
+const DERIVED_KEYS: &[&str] = &["last_updated", "transitions"];
+
+/// Who is making changes, and through what
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Actor {
+   pub name: String,
+   /// Command or MCP tool, e.g. `close` or `mcp:issues_status`
+   pub via:  Option<String>,
+}
+
+impl Actor {
+   /// `$AGENTX_ACTOR`, else `client` (an MCP client's name), else the login
+   /// name
+   pub fn resolve(client: Option<&str>, via: Option<&str>) -> Self {
+      let name = std::env::var("AGENTX_ACTOR")
+         .ok()
+         .filter(|a| !a.trim().is_empty())
+         .or_else(|| client.filter(|c| !c.trim().is_empty()).map(String::from))
+         .or_else(|| std::env::var("USER").ok())
+         .or_else(|| std::env::var("USERNAME").ok())
+         .unwrap_or_else(current_user);
+      Self { name, via: via.map(String::from) }
+   }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+   pub at:      DateTime<Utc>,
+   pub actor:   String,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub via:     Option<String>,
+   pub issue:   u32,
+   /// What changed, e.g. `status: open → active` or `checkpoint added`
+   pub changes: Vec<String>,
+}
+
+/// Which entries `agentx audit` shows
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+   pub actor: Option<String>,
+   pub issue: Option<u32>,
+   pub since: Option<DateTime<Utc>>,
+   pub until: Option<DateTime<Utc>>,
+}
+
+impl AuditFilter {
+   pub fn matches(&self, entry: &AuditEntry) -> bool {
+      self
+         .actor
+         .as_ref()
+         .is_none_or(|a| entry.actor.eq_ignore_ascii_case(a))
+         && self.issue.is_none_or(|n| entry.issue == n)
+         && self.since.is_none_or(|since| entry.at >= since)
+         && self.until.is_none_or(|until| entry.at < until)
+   }
+}
+
+/// The entries in the text of an audit file, skipping lines that don't
+/// parse (such as a half-written last line)
+pub fn parse(text: &str) -> Vec<AuditEntry> {
+   text
+      .lines()
+      .filter_map(|line| serde_json::from_str(line).ok())
+      .collect()
+}
+
+/// One line of the audit file
+pub fn to_line(entry: &AuditEntry) -> Result<String> {
+   Ok(format!("{}\n", serde_json::to_string(entry)?))
+}
+
+/// What differs from `before` (`None` for a new issue) to `after`
+pub fn changes(before: Option<&Issue>, after: &Issue) -> Vec<String> {
+   let Some(before) = before else {
+      return vec!["created".to_string()];
+   };
+   let mut changes = frontmatter_changes(before, after);
+   changes.extend(body_changes(before, after));
+   changes
+}
+
+fn frontmatter_changes(before: &Issue, after: &Issue) -> Vec<String> {
+   let fields = |issue: &Issue| -> BTreeMap<String, Value> {
+      match serde_yaml::to_value(&issue.metadata) {
+         Ok(Value::Mapping(map)) => map
+            .into_iter()
+            .filter_map(|(k, v)| Some((k.as_str()?.to_string(), v)))
+            .filter(|(k, _)| !DERIVED_KEYS.contains(&k.as_str()))
+            .collect(),
+         _ => BTreeMap::new(),
+      }
+   };
+   let (old, new) = (fields(before), fields(after));
+   let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+   keys.sort_unstable();
+   keys.dedup();
+
+   let mut changes = Vec::new();
+   for key in keys {
+      let (from, to) = (old.get(key), new.get(key));
+      if from == to {
+         continue;
+      }
+      let change = match (from, to) {
+         (Some(Value::Sequence(from)), Some(Value::Sequence(to))) => list_change(from, to),
+         (None, Some(Value::Sequence(to))) => list_change(&[], to),
+         (Some(Value::Sequence(from)), None) => list_change(from, &[]),
+         _ => match (from.and_then(scalar), to.and_then(scalar)) {
+            (Some(from), Some(to)) => format!("{from} → {to}"),
+            (None, Some(to)) if from.is_none() => format!("set to {to}"),
+            (Some(_), None) if to.is_none() => "cleared".to_string(),
+            _ => "changed".to_string(),
+         },
+      };
+      changes.push(format!("{key}: {change}"));
+   }
+   changes
+}
+
+fn scalar(value: &Value) -> Option<String> {
+   match value {
+      Value::String(s) => Some(s.clone()),
+      Value::Number(n) => Some(n.to_string()),
+      Value::Bool(b) => Some(b.to_string()),
+      _ => None,
+   }
+}
+
+/// `+added -removed` for lists of scalars
+fn list_change(from: &[Value], to: &[Value]) -> String {
+   let added = to.iter().filter(|v| !from.contains(v)).filter_map(scalar);
+   let removed = from.iter().filter(|v| !to.contains(v)).filter_map(scalar);
+   let parts: Vec<String> = added
+      .map(|v| format!("+{v}"))
+      .chain(removed.map(|v| format!("-{v}")))
+      .collect();
+   if parts.is_empty() {
+      "reordered".to_string()
+   } else {
+      parts.join(" ")
+   }
+}
+
+fn body_changes(before: &Issue, after: &Issue) -> Vec<String> {
+   let by_kind = |issue: &Issue| -> BTreeMap<String, Vec<String>> {
+      let mut sections: BTreeMap<String, Vec<String>> = BTreeMap::new();
+      for section in issue.body.sections() {
+         let label = match &section.kind {
+            SectionKind::Preamble => "body".to_string(),
+            kind => kind.label().to_lowercase(),
+         };
+         sections
+            .entry(label)
+            .or_default()
+            .push(section.as_str().trim().to_string());
+      }
+      sections
+   };
+   let (old, new) = (by_kind(before), by_kind(after));
+   let mut labels: Vec<&String> = old.keys().chain(new.keys()).collect();
+   labels.sort_unstable();
+   labels.dedup();
+
+   let none = Vec::new();
+   labels
+      .into_iter()
+      .filter_map(|label| {
+         let (from, to) = (old.get(label).unwrap_or(&none), new.get(label).unwrap_or(&none));
+         if from == to {
+            None
+         } else if to.len() > from.len() && to.starts_with(from) {
+            Some(format!("{label} added"))
+         } else if to.is_empty() {
+            Some(format!("{label} removed"))
+         } else {
+            Some(format!("{label} edited"))
+         }
+      })
+      .collect()
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::issue::{Status, test_support::issue};
+
+   #[test]
+   fn test_changes_and_filter() {
+      let before = issue(1)
+         .title("Fix login")
+         .tags(&["auth"])
+         .body("Broken")
+         .impact("Users locked out")
+         .acceptance("Can log in")
+         .build()
+         .issue;
+      assert_eq!(changes(None, &before), ["created"]);
+
+      let mut after = before.clone();
+      after.metadata.set_status(Status::InProgress);
+      after.metadata.tags = vec!["auth".into(), "ui".into()];
+      after.metadata.effort = Some("2h".into());
+      after.metadata.last_updated = Some(Utc::now());
+      after
+         .body
+         .append("\n\n## Checkpoint - 2026-01-02 10:00\n\nFound it");
+      assert_eq!(changes(Some(&before), &after), [
+         "effort: set to 2h",
+         "status: open → active",
+         "tags: +ui",
+         "checkpoint added"
+      ]);
+      assert!(changes(Some(&after), &after).is_empty());
+
+      let entries = parse(
+         "{\"at\":\"2026-01-02T10:00:00Z\",\"actor\":\"claude\",\"via\":\"close\",\"issue\":3,\"\
+          changes\":[\"status: active → \
+          closed\"]}\n{\"at\":\"2026-01-03T10:00:00Z\",\"actor\":\"codex\",\"issue\":4,\"changes\"\
+          :[\"created\"]}\n{\"at\":",
+      );
+      assert_eq!(entries.len(), 2);
+      let filter = AuditFilter { actor: Some("Claude".into()), ..Default::default() };
+      assert_eq!(entries.iter().filter(|e| filter.matches(e)).count(), 1);
+      let filter =
+         AuditFilter { since: Some("2026-01-03T00:00:00Z".parse().unwrap()), ..Default::default() };
+      assert!(
+         entries
+            .iter()
+            .filter(|e| filter.matches(e))
+            .all(|e| e.issue == 4)
+      );
+   }
+}
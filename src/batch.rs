@@ -0,0 +1,247 @@
+//! Batch protocol for scripts
+//!
+//! `agentx batch -` reads newline-delimited JSON commands, such as
+//! `{"op":"close","ref":"12","message":"done"}`, and answers each with one
+//! JSON line. The batch is all or nothing: every line is parsed before
+//! anything runs, the store is locked for the whole run, and when one
+//! command fails the files are put back the way they were.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::api::IssueTracker;
+
+/// One command of a batch
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum BatchOp {
+   Create {
+      title:      String,
+      #[serde(default = "default_priority")]
+      priority:   String,
+      #[serde(default)]
+      tags:       Vec<String>,
+      #[serde(default)]
+      files:      Vec<String>,
+      #[serde(default)]
+      issue:      String,
+      #[serde(default)]
+      impact:     String,
+      #[serde(default)]
+      acceptance: String,
+      effort:     Option<String>,
+      context:    Option<String>,
+      milestone:  Option<String>,
+      kind:       Option<String>,
+   },
+   Show {
+      #[serde(rename = "ref", deserialize_with = "bug_ref")]
+      bug_ref: String,
+   },
+   Start {
+      #[serde(rename = "ref", deserialize_with = "bug_ref")]
+      bug_ref: String,
+   },
+   Block {
+      #[serde(rename = "ref", deserialize_with = "bug_ref")]
+      bug_ref: String,
+      reason:  String,
+   },
+   Close {
+      #[serde(rename = "ref", deserialize_with = "bug_ref")]
+      bug_ref:      String,
+      message:      Option<String>,
+      #[serde(default)]
+      auto_unblock: bool,
+   },
+   Open {
+      #[serde(rename = "ref", deserialize_with = "bug_ref")]
+      bug_ref: String,
+   },
+   Defer {
+      #[serde(rename = "ref", deserialize_with = "bug_ref")]
+      bug_ref: String,
+   },
+   Activate {
+      #[serde(rename = "ref", deserialize_with = "bug_ref")]
+      bug_ref: String,
+   },
+   Checkpoint {
+      #[serde(rename = "ref", deserialize_with = "bug_ref")]
+      bug_ref: String,
+      message: String,
+   },
+   Depend {
+      #[serde(rename = "ref", deserialize_with = "bug_ref")]
+      bug_ref: String,
+      #[serde(default)]
+      on:      Vec<String>,
+      #[serde(default)]
+      remove:  Vec<String>,
+   },
+   Claim {
+      #[serde(rename = "ref", deserialize_with = "bug_ref")]
+      bug_ref: String,
+      #[serde(default = "default_ttl")]
+      ttl:     String,
+   },
+   Release {
+      #[serde(rename = "ref", deserialize_with = "bug_ref")]
+      bug_ref: String,
+   },
+}
+
+fn default_priority() -> String {
+   "medium".to_string()
+}
+
+fn default_ttl() -> String {
+   "2h".to_string()
+}
+
+/// A ref given as `12` or `"12"`
+fn bug_ref<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+   match Value::deserialize(deserializer)? {
+      Value::String(s) => Ok(s),
+      Value::Number(n) => Ok(n.to_string()),
+      other => {
+         Err(serde::de::Error::custom(format!("ref must be a string or number, not {other}")))
+      },
+   }
+}
+
+impl BatchOp {
+   pub fn name(&self) -> &'static str {
+      match self {
+         Self::Create { .. } => "create",
+         Self::Show { .. } => "show",
+         Self::Start { .. } => "start",
+         Self::Block { .. } => "block",
+         Self::Close { .. } => "close",
+         Self::Open { .. } => "open",
+         Self::Defer { .. } => "defer",
+         Self::Activate { .. } => "activate",
+         Self::Checkpoint { .. } => "checkpoint",
+         Self::Depend { .. } => "depend",
+         Self::Claim { .. } => "claim",
+         Self::Release { .. } => "release",
+      }
+   }
+
+   /// Run the command, returning what its `--json` output would hold
+   pub fn run(&self, tracker: &IssueTracker) -> Result<Value> {
+      let value = match self.clone() {
+         Self::Create {
+            title,
+            priority,
+            tags,
+            files,
+            issue,
+            impact,
+            acceptance,
+            effort,
+            context,
+            milestone,
+            kind,
+         } => serde_json::to_value(tracker.create_issue_data(
+            title, &priority, tags, files, issue, impact, acceptance, effort, context, milestone,
+            kind,
+         )?)?,
+         Self::Show { bug_ref } => serde_json::to_value(tracker.show_data(&bug_ref)?)?,
+         Self::Start { bug_ref } => serde_json::to_value(tracker.start_data(&bug_ref)?)?,
+         Self::Block { bug_ref, reason } => {
+            serde_json::to_value(tracker.block_data(&bug_ref, reason)?)?
+         },
+         Self::Close { bug_ref, message, auto_unblock } => {
+            serde_json::to_value(tracker.close_data(&bug_ref, message, auto_unblock)?)?
+         },
+         Self::Open { bug_ref } => serde_json::to_value(tracker.open_data(&bug_ref)?)?,
+         Self::Defer { bug_ref } => serde_json::to_value(tracker.defer_data(&bug_ref)?)?,
+         Self::Activate { bug_ref } => serde_json::to_value(tracker.activate_data(&bug_ref)?)?,
+         Self::Checkpoint { bug_ref, message } => {
+            serde_json::to_value(tracker.checkpoint_data(&bug_ref, message)?)?
+         },
+         Self::Depend { bug_ref, on, remove } => {
+            serde_json::to_value(tracker.depend_data(&bug_ref, on, remove)?)?
+         },
+         Self::Claim { bug_ref, ttl } => serde_json::to_value(tracker.claim_data(&bug_ref, &ttl)?)?,
+         Self::Release { bug_ref } => serde_json::to_value(tracker.release_data(&bug_ref)?)?,
+      };
+      Ok(value)
+   }
+}
+
+/// The answer to one line of a batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchResult {
+   /// Line of the input, counting from 1
+   pub line:        usize,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub op:          Option<String>,
+   pub ok:          bool,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub result:      Option<Value>,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub error:       Option<String>,
+   /// Succeeded, then undone because a later command failed
+   #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+   pub rolled_back: bool,
+}
+
+impl BatchResult {
+   pub fn failed(line: usize, op: Option<&str>, error: String) -> Self {
+      Self {
+         line,
+         op: op.map(String::from),
+         ok: false,
+         result: None,
+         error: Some(error),
+         rolled_back: false,
+      }
+   }
+}
+
+/// The commands in `input`, skipping blank lines, each with its line number
+pub fn parse(input: &str) -> Vec<(usize, Result<BatchOp>)> {
+   input
+      .lines()
+      .enumerate()
+      .filter(|(_, line)| !line.trim().is_empty())
+      .map(|(i, line)| {
+         let op = serde_json::from_str(line).with_context(|| format!("Invalid command: {line}"));
+         (i + 1, op)
+      })
+      .collect()
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn test_parse_batch_lines() {
+      let input = [
+         r#"{"op":"close","ref":"12","message":"done"}"#,
+         "",
+         r#"{"op":"start","ref":7}"#,
+         r#"{"op":"start"}"#,
+         r#"{"op":"launch","ref":1}"#,
+      ]
+      .join("\n");
+      let ops = parse(&input);
+      assert_eq!(ops.iter().map(|(line, _)| *line).collect::<Vec<_>>(), [1, 3, 4, 5]);
+      match &ops[0].1 {
+         Ok(BatchOp::Close { bug_ref, message, auto_unblock }) => {
+            assert_eq!(
+               (bug_ref.as_str(), message.as_deref(), *auto_unblock),
+               ("12", Some("done"), false)
+            );
+         },
+         other => panic!("unexpected {other:?}"),
+      }
+      assert!(matches!(&ops[1].1, Ok(BatchOp::Start { bug_ref }) if bug_ref == "7"));
+      assert!(ops[2].1.is_err());
+      assert!(ops[3].1.is_err());
+   }
+}
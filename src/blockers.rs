@@ -0,0 +1,205 @@
+use std::collections::{BTreeMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+   flow::status_intervals,
+   issue::{IssueWithId, Status},
+};
+
+/// Jaro-winkler similarity above which two normalized reasons are grouped
+const SIMILARITY_THRESHOLD: f64 = 0.88;
+/// Word overlap (jaccard) above which two normalized reasons are grouped
+const OVERLAP_THRESHOLD: f64 = 0.5;
+
+/// Filler words that say nothing about what the blocker actually is
+const STOPWORDS: &[&str] = &[
+   "a", "an", "and", "are", "blocked", "by", "for", "from", "in", "is", "it", "of", "on", "still",
+   "the", "to", "until", "waiting", "we",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockerCluster {
+   /// Most common wording of the reason within the cluster
+   pub label:               String,
+   /// Every distinct wording grouped into the cluster
+   pub reasons:             Vec<String>,
+   pub issues:              Vec<u32>,
+   pub currently_blocked:   usize,
+   /// Total time the issues in the cluster spent blocked
+   pub total_blocked_hours: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BlockerAnalysis {
+   /// Issues that have ever recorded a blocked reason
+   pub issues_with_reason:        usize,
+   pub clusters:                  Vec<BlockerCluster>,
+   /// Blocked time on issues that never gave a reason
+   pub unexplained_blocked_hours: f64,
+}
+
+/// Lowercase the reason and drop issue references, numbers, punctuation and
+/// filler words, so "Waiting on #12 (API keys)" and "waiting for API key"
+/// normalize closely
+fn normalize(reason: &str) -> Vec<String> {
+   reason
+      .split(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | ':' | '(' | ')' | '/'))
+      .filter(|w| !w.chars().any(|c| c.is_ascii_digit()))
+      .map(|w| {
+         w.chars()
+            .filter(|c| c.is_alphanumeric() || *c == '-')
+            .collect::<String>()
+            .to_lowercase()
+      })
+      .map(|w| match w.strip_suffix('s') {
+         Some(stem) if stem.len() >= 3 => stem.to_string(),
+         _ => w,
+      })
+      .filter(|w| !w.is_empty() && !STOPWORDS.contains(&w.as_str()))
+      .collect()
+}
+
+struct Group<'a> {
+   key:     String,
+   words:   HashSet<String>,
+   members: Vec<(&'a IssueWithId, f64)>,
+}
+
+impl Group<'_> {
+   fn matches(&self, key: &str, words: &HashSet<String>) -> bool {
+      if strsim::jaro_winkler(&self.key, key) >= SIMILARITY_THRESHOLD {
+         return true;
+      }
+      let union = self.words.union(words).count();
+      union > 0 && self.words.intersection(words).count() as f64 / union as f64 >= OVERLAP_THRESHOLD
+   }
+}
+
+fn blocked_hours(issue: &IssueWithId, now: DateTime<Utc>) -> f64 {
+   status_intervals(issue, now)
+      .iter()
+      .filter(|i| i.status == Status::Blocked)
+      .map(|i| (i.end - i.start).num_minutes().max(0) as f64 / 60.0)
+      .sum()
+}
+
+/// Group the blocked reasons of `issues` into clusters of similar wording,
+/// most time lost first
+///
+/// An issue keeps its last blocked reason after being unblocked, so closed and
+/// previously blocked issues count towards the cluster of that reason.
+pub fn analyze_blockers(issues: &[IssueWithId], now: DateTime<Utc>) -> BlockerAnalysis {
+   let mut groups: Vec<Group> = Vec::new();
+   let mut analysis = BlockerAnalysis::default();
+
+   for issue in issues {
+      let hours = blocked_hours(issue, now);
+      let Some(reason) = issue.issue.metadata.blocked_reason.as_deref() else {
+         analysis.unexplained_blocked_hours += hours;
+         continue;
+      };
+      analysis.issues_with_reason += 1;
+
+      let words = normalize(reason);
+      let key = if words.is_empty() {
+         reason.trim().to_lowercase()
+      } else {
+         words.join(" ")
+      };
+      let words: HashSet<String> = words.into_iter().collect();
+
+      match groups.iter_mut().find(|g| g.matches(&key, &words)) {
+         Some(group) => group.members.push((issue, hours)),
+         None => groups.push(Group { key, words, members: vec![(issue, hours)] }),
+      }
+   }
+
+   analysis.clusters = groups
+      .into_iter()
+      .map(|group| {
+         let mut wordings: BTreeMap<String, usize> = BTreeMap::new();
+         for (issue, _) in &group.members {
+            let reason = issue
+               .issue
+               .metadata
+               .blocked_reason
+               .as_deref()
+               .unwrap_or_default();
+            *wordings.entry(reason.trim().to_string()).or_default() += 1;
+         }
+         let label = wordings
+            .iter()
+            .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.len().cmp(&a.0.len())))
+            .map(|(reason, _)| reason.clone())
+            .unwrap_or_default();
+
+         let mut issues: Vec<u32> = group.members.iter().map(|(i, _)| i.id).collect();
+         issues.sort_unstable();
+         BlockerCluster {
+            label,
+            reasons: wordings.into_keys().collect(),
+            issues,
+            currently_blocked: group
+               .members
+               .iter()
+               .filter(|(i, _)| i.issue.metadata.status == Status::Blocked)
+               .count(),
+            total_blocked_hours: group.members.iter().map(|(_, h)| h).sum(),
+         }
+      })
+      .collect();
+
+   analysis.clusters.sort_by(|a, b| {
+      b.total_blocked_hours
+         .total_cmp(&a.total_blocked_hours)
+         .then(b.issues.len().cmp(&a.issues.len()))
+         .then_with(|| a.label.cmp(&b.label))
+   });
+   analysis
+}
+
+#[cfg(test)]
+mod tests {
+   use chrono::Duration;
+
+   use super::*;
+   use crate::issue::test_support::issue;
+
+   #[test]
+   fn test_analyze_blockers_groups_similar_reasons() {
+      let now = Utc::now();
+      let ago = |hours| now - Duration::hours(hours);
+      let issues = vec![
+         issue(1)
+            .created(ago(11))
+            .moved(Status::Blocked, ago(10))
+            .blocked_reason("Waiting on API keys from vendor")
+            .build(),
+         issue(2)
+            .created(ago(21))
+            .moved(Status::Blocked, ago(20))
+            .blocked_reason("waiting for api key (#1)")
+            .build(),
+         issue(3)
+            .created(ago(6))
+            .moved(Status::Blocked, ago(5))
+            .blocked_reason("Design review pending")
+            .build(),
+         issue(4)
+            .created(ago(2))
+            .moved(Status::Blocked, ago(1))
+            .blocked_reason("Blocked by vendor API keys")
+            .build(),
+      ];
+
+      let analysis = analyze_blockers(&issues, now);
+      assert_eq!(analysis.issues_with_reason, 4);
+      assert_eq!(analysis.clusters.len(), 2);
+      assert_eq!(analysis.clusters[0].issues, vec![1, 2, 4]);
+      assert_eq!(analysis.clusters[0].total_blocked_hours, 31.0);
+      assert_eq!(analysis.clusters[0].currently_blocked, 3);
+      assert_eq!(analysis.clusters[1].label, "Design review pending");
+   }
+}
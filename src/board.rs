@@ -0,0 +1,187 @@
+//! Plain-text board
+//!
+//! `agentx board` prints status columns side by side, one fixed-width card
+//! per issue, without taking over the terminal the way the dashboard does.
+//! Cards are drawn in ASCII so the board reads the same in CI logs, and
+//! columns that don't fit the width wrap onto another row of columns.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::issue::{IssueWithId, Priority, Status};
+
+/// Columns shown when none are picked
+pub const DEFAULT_COLUMNS: [Status; 4] =
+   [Status::NotStarted, Status::InProgress, Status::Blocked, Status::Done];
+
+const MIN_CARD_WIDTH: usize = 18;
+const MAX_CARD_WIDTH: usize = 32;
+const GAP: &str = "  ";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Card {
+   pub num:      u32,
+   pub title:    String,
+   pub priority: Priority,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub effort:   Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardColumn {
+   pub status: Status,
+   /// Most important first
+   pub cards:  Vec<Card>,
+}
+
+/// Parse `--columns`, e.g. `open,active,blocked`
+pub fn parse_columns(spec: &str) -> Result<Vec<Status>> {
+   spec
+      .split(',')
+      .map(str::trim)
+      .filter(|name| !name.is_empty())
+      .map(Status::parse)
+      .collect()
+}
+
+/// Sort `issues` into a column per status in `statuses`
+pub fn columns(issues: &[IssueWithId], statuses: &[Status]) -> Vec<BoardColumn> {
+   statuses
+      .iter()
+      .map(|&status| {
+         let mut cards: Vec<Card> = issues
+            .iter()
+            .filter(|i| i.issue.metadata.status == status)
+            .map(|i| Card {
+               num:      i.id,
+               title:    i.issue.metadata.title.to_string(),
+               priority: i.issue.metadata.priority,
+               effort:   i.issue.metadata.effort.as_ref().map(|e| e.to_string()),
+            })
+            .collect();
+         cards.sort_by_key(|c| (c.priority, c.num));
+         BoardColumn { status, cards }
+      })
+      .collect()
+}
+
+/// Draw `columns` for a terminal `width` columns wide
+pub fn render(columns: &[BoardColumn], width: usize, issue_ref: impl Fn(u32) -> String) -> String {
+   let gap = GAP.len();
+   let count = columns.len().max(1);
+   let card_width =
+      (width.saturating_sub(gap * (count - 1)) / count).clamp(MIN_CARD_WIDTH, MAX_CARD_WIDTH);
+   let per_row = ((width + gap) / (card_width + gap)).max(1);
+
+   let mut out = String::new();
+   for row in columns.chunks(per_row) {
+      let drawn: Vec<Vec<String>> = row
+         .iter()
+         .map(|column| draw_column(column, card_width, &issue_ref))
+         .collect();
+      let height = drawn.iter().map(Vec::len).max().unwrap_or(0);
+      for line in 0..height {
+         let cells: Vec<String> = drawn
+            .iter()
+            .map(|lines| pad(lines.get(line).map_or("", String::as_str), card_width))
+            .collect();
+         out.push_str(cells.join(GAP).trim_end());
+         out.push('\n');
+      }
+      out.push('\n');
+   }
+   out
+}
+
+fn draw_column(
+   column: &BoardColumn,
+   width: usize,
+   issue_ref: &impl Fn(u32) -> String,
+) -> Vec<String> {
+   let inner = width - 4;
+   let border = format!("+{}+", "-".repeat(width - 2));
+   let mut lines = vec![
+      fit(&format!("{} ({})", column.status.to_string().to_uppercase(), column.cards.len()), width),
+      "=".repeat(width),
+   ];
+   for card in &column.cards {
+      let mut head = format!("{} {}", issue_ref(card.num), card.priority);
+      if let Some(effort) = &card.effort {
+         head.push_str(&format!(" {effort}"));
+      }
+      lines.push(border.clone());
+      lines.push(format!("| {} |", pad(&fit(&head, inner), inner)));
+      lines.push(format!("| {} |", pad(&fit(&card.title, inner), inner)));
+      lines.push(border.clone());
+   }
+   lines
+}
+
+/// `text` cut to `width` display columns, ending in an ellipsis if cut
+fn fit(text: &str, width: usize) -> String {
+   if text.width() <= width {
+      return text.to_string();
+   }
+   let mut out = String::new();
+   let mut used = 0;
+   for c in text.chars() {
+      let w = c.width().unwrap_or(0);
+      if used + w + 1 > width {
+         break;
+      }
+      out.push(c);
+      used += w;
+   }
+   out.push('…');
+   out
+}
+
+fn pad(text: &str, width: usize) -> String {
+   format!("{text}{}", " ".repeat(width.saturating_sub(text.width())))
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::issue::test_support::issue;
+
+   #[test]
+   fn test_board_columns_and_wrapping() {
+      let issues = vec![
+         issue(1)
+            .title("Fix the login redirect loop")
+            .priority(Priority::Low)
+            .effort("2h")
+            .build(),
+         issue(2)
+            .title("Crash on start")
+            .priority(Priority::Critical)
+            .effort("2h")
+            .build(),
+         issue(3)
+            .title("Speed up search")
+            .priority(Priority::High)
+            .status(Status::InProgress)
+            .effort("2h")
+            .build(),
+      ];
+      let statuses = parse_columns("open, active").unwrap();
+      assert!(parse_columns("open,later").is_err());
+      assert_eq!(parse_columns("todo, in-progress").unwrap(), statuses);
+      let board = columns(&issues, &statuses);
+      let nums: Vec<u32> = board[0].cards.iter().map(|c| c.num).collect();
+      assert_eq!(nums, [2, 1]);
+
+      let text = render(&board, 42, |n| format!("#{n}"));
+      let lines: Vec<&str> = text.lines().collect();
+      assert_eq!(lines[0], "OPEN (2)              ACTIVE (1)");
+      assert_eq!(lines[3], "| #2 critical 2h   |  | #3 high 2h       |");
+      assert_eq!(lines[8], "| Fix the login r… |");
+
+      // Too narrow for two cards: the second column goes below the first
+      let narrow = render(&board, 30, |n| format!("#{n}"));
+      assert!(narrow.lines().all(|l| l.width() <= 30));
+      assert!(narrow.contains("\n\nACTIVE (1)\n"));
+   }
+}
@@ -0,0 +1,415 @@
+//! Issue body split into its standard sections
+//!
+//! A section starts at a heading line, either `**Label**: text` or
+//! `## Label - detail`, and runs up to the next one. Text before the first
+//! heading and sections with labels we don't know are kept as they are, so a
+//! body always renders back to exactly what was parsed.
+//!
+//! A label ending in a language tag, as in `**Issue [de]**: ...`, holds a
+//! translation of the section; [`IssueBody::localized`] swaps translations
+//! in for display, and everything else reads the untagged sections.
+
+use std::fmt;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// What a section holds, from its heading label
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SectionKind {
+   /// Text before the first heading
+   Preamble,
+   Issue,
+   Impact,
+   Acceptance,
+   Context,
+   Checkpoint,
+   Closed,
+   Other(String),
+}
+
+impl SectionKind {
+   fn from_label(label: &str) -> Self {
+      match label {
+         "Issue" => Self::Issue,
+         "Impact" => Self::Impact,
+         "Acceptance" => Self::Acceptance,
+         "Context" => Self::Context,
+         "Checkpoint" => Self::Checkpoint,
+         "Closed" => Self::Closed,
+         other => Self::Other(other.to_string()),
+      }
+   }
+
+   /// Heading label, empty for the preamble
+   pub fn label(&self) -> &str {
+      match self {
+         Self::Preamble => "",
+         Self::Issue => "Issue",
+         Self::Impact => "Impact",
+         Self::Acceptance => "Acceptance",
+         Self::Context => "Context",
+         Self::Checkpoint => "Checkpoint",
+         Self::Closed => "Closed",
+         Self::Other(label) => label,
+      }
+   }
+
+   /// Sections that describe the issue, as opposed to its history
+   fn is_field(&self) -> bool {
+      matches!(self, Self::Issue | Self::Impact | Self::Acceptance | Self::Context)
+   }
+}
+
+/// One section, heading line included
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+   pub kind: SectionKind,
+   /// Language the section translates into, from a `[de]` label suffix
+   pub lang: Option<String>,
+   text:     String,
+}
+
+/// A label and its language tag: `Issue [de]` gives ("Issue", Some("de"))
+fn split_lang(label: &str) -> (&str, Option<&str>) {
+   label
+      .strip_suffix(']')
+      .and_then(|rest| rest.rsplit_once(" ["))
+      .filter(|(_, lang)| {
+         !lang.is_empty() && lang.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+      })
+      .map_or((label, None), |(label, lang)| (label.trim_end(), Some(lang)))
+}
+
+/// Label and detail of a heading line: `**Checkpoint** (2024-01-01): ...` and
+/// `## Checkpoint - 2024-01-01` both give ("Checkpoint", "2024-01-01")
+fn parse_heading(line: &str) -> Option<(&str, Option<&str>)> {
+   let line = line.trim_end();
+   if let Some(rest) = line.strip_prefix("## ") {
+      return Some(match rest.split_once(" - ") {
+         Some((label, detail)) => (label.trim(), Some(detail.trim())),
+         None => (rest.trim(), None),
+      });
+   }
+
+   let rest = line.strip_prefix("**")?;
+   let (label, after) = rest.split_once("**")?;
+   if label.is_empty() {
+      return None;
+   }
+   let detail = after
+      .trim_start()
+      .strip_prefix('(')
+      .and_then(|d| d.split_once(')'))
+      .map(|(detail, _)| detail.trim());
+   Some((label.trim(), detail))
+}
+
+impl Section {
+   fn heading(&self) -> Option<(&str, Option<&str>)> {
+      (self.kind != SectionKind::Preamble)
+         .then(|| parse_heading(self.text.lines().next().unwrap_or_default()))
+         .flatten()
+   }
+
+   /// Text after the heading label, e.g. the timestamp of a checkpoint
+   pub fn detail(&self) -> Option<&str> {
+      self.heading().and_then(|(_, detail)| detail)
+   }
+
+   /// When the section was written, read from its detail (checkpoints are
+   /// stamped in UTC as `2024-01-01 10:00`, with or without seconds)
+   pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+      let detail = self.detail()?;
+      ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"]
+         .iter()
+         .find_map(|format| NaiveDateTime::parse_from_str(detail, format).ok())
+         .or_else(|| {
+            NaiveDate::parse_from_str(detail, "%Y-%m-%d")
+               .ok()?
+               .and_hms_opt(0, 0, 0)
+         })
+         .map(|naive| naive.and_utc())
+   }
+
+   /// The section's text without its heading, trimmed
+   pub fn content(&self) -> &str {
+      if self.kind == SectionKind::Preamble {
+         return self.text.trim();
+      }
+      let (first, rest) = self.text.split_once('\n').unwrap_or((&self.text, ""));
+      if first.starts_with("## ") {
+         return rest.trim();
+      }
+
+      // `**Label** (detail): content` continues on the heading line
+      let after_label = first
+         .strip_prefix("**")
+         .and_then(|r| r.split_once("**"))
+         .map_or("", |(_, after)| after);
+      let inline = match after_label.trim_start().strip_prefix('(') {
+         Some(d) => d.split_once(')').map_or("", |(_, after)| after),
+         None => after_label,
+      };
+      let inline = inline.trim_start();
+      let inline = inline.strip_prefix(':').unwrap_or(inline);
+      self.text[first.len() - inline.len()..].trim()
+   }
+
+   /// The section exactly as written, heading included
+   pub fn as_str(&self) -> &str {
+      &self.text
+   }
+}
+
+/// An issue body as a sequence of sections
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IssueBody {
+   sections: Vec<Section>,
+}
+
+impl IssueBody {
+   pub fn parse(text: &str) -> Self {
+      let mut sections: Vec<Section> = Vec::new();
+      for line in text.split_inclusive('\n') {
+         if let Some((label, _)) = parse_heading(line) {
+            let (label, lang) = split_lang(label);
+            sections.push(Section {
+               kind: SectionKind::from_label(label),
+               lang: lang.map(str::to_string),
+               text: String::new(),
+            });
+         } else if sections.is_empty() {
+            sections.push(Section { kind: SectionKind::Preamble, lang: None, text: String::new() });
+         }
+         sections.last_mut().unwrap().text.push_str(line);
+      }
+      Self { sections }
+   }
+
+   pub fn sections(&self) -> &[Section] {
+      &self.sections
+   }
+
+   pub fn is_empty(&self) -> bool {
+      self.sections.iter().all(|s| s.text.is_empty())
+   }
+
+   /// First untranslated section of the given kind
+   pub fn section(&self, kind: &SectionKind) -> Option<&Section> {
+      self
+         .sections
+         .iter()
+         .find(|s| &s.kind == kind && s.lang.is_none())
+   }
+
+   /// Languages the body has translated sections in, sorted
+   pub fn languages(&self) -> Vec<&str> {
+      let mut langs: Vec<&str> = self
+         .sections
+         .iter()
+         .filter_map(|s| s.lang.as_deref())
+         .collect();
+      langs.sort_unstable();
+      langs.dedup();
+      langs
+   }
+
+   /// The body as read in `lang`: each section with a translation in `lang`
+   /// shows the translation under its usual heading, the rest fall back to
+   /// the original, and translations into other languages are left out
+   pub fn localized(&self, lang: &str) -> Self {
+      let mut sections = Vec::new();
+      for section in self.sections.iter().filter(|s| s.lang.is_none()) {
+         let translated = self
+            .sections
+            .iter()
+            .find(|t| t.kind == section.kind && t.lang.as_deref() == Some(lang));
+         match translated {
+            Some(t) if section.kind != SectionKind::Checkpoint => {
+               let tag = format!(" [{lang}]");
+               let (first, rest) = t.text.split_at(t.text.find('\n').unwrap_or(t.text.len()));
+               let text = format!("{}{rest}", first.replacen(&tag, "", 1));
+               sections.push(Section { kind: t.kind.clone(), lang: None, text });
+            },
+            _ => sections.push(section.clone()),
+         }
+      }
+      Self { sections }
+   }
+
+   pub fn issue(&self) -> Option<&str> {
+      self.section(&SectionKind::Issue).map(Section::content)
+   }
+
+   pub fn impact(&self) -> Option<&str> {
+      self.section(&SectionKind::Impact).map(Section::content)
+   }
+
+   pub fn acceptance(&self) -> Option<&str> {
+      self.section(&SectionKind::Acceptance).map(Section::content)
+   }
+
+   pub fn context(&self) -> Option<&str> {
+      self.section(&SectionKind::Context).map(Section::content)
+   }
+
+   /// Checkpoints in the order they were added
+   pub fn checkpoints(&self) -> impl Iterator<Item = &Section> {
+      self
+         .sections
+         .iter()
+         .filter(|s| s.kind == SectionKind::Checkpoint)
+   }
+
+   pub fn closed_note(&self) -> Option<&str> {
+      self.section(&SectionKind::Closed).map(Section::content)
+   }
+
+   /// Replace the content of the first section of `kind`, or add it as
+   /// `**Label**: content` after the other descriptive sections
+   pub fn set(&mut self, kind: SectionKind, content: &str) {
+      if let Some(index) = self
+         .sections
+         .iter()
+         .position(|s| s.kind == kind && s.lang.is_none())
+      {
+         let section = &self.sections[index];
+         let trailing = &section.text[section.text.trim_end().len()..];
+         let trailing = if trailing.is_empty() {
+            "\n\n"
+         } else {
+            trailing
+         };
+         let first = section.text.lines().next().unwrap_or_default();
+         let heading = if kind == SectionKind::Preamble {
+            String::new()
+         } else if first.starts_with("## ") {
+            format!("{first}\n\n")
+         } else {
+            match section.detail() {
+               Some(detail) => format!("**{}** ({detail}): ", kind.label()),
+               None => format!("**{}**: ", kind.label()),
+            }
+         };
+         let text = format!("{heading}{content}{trailing}");
+         self.sections[index].text = text;
+         return;
+      }
+
+      let index = self
+         .sections
+         .iter()
+         .rposition(|s| s.kind.is_field() || s.kind == SectionKind::Preamble)
+         .map_or(0, |i| i + 1);
+      if let Some(prev) = index.checked_sub(1).map(|i| &mut self.sections[i])
+         && !prev.text.ends_with("\n\n")
+      {
+         prev.text.push_str(if prev.text.ends_with('\n') {
+            "\n"
+         } else {
+            "\n\n"
+         });
+      }
+      let text = format!("**{}**: {content}\n\n", kind.label());
+      self
+         .sections
+         .insert(index, Section { kind, lang: None, text });
+   }
+
+   /// Append raw text, e.g. a new checkpoint, to the end of the body
+   pub fn append(&mut self, text: &str) {
+      *self = Self::parse(&format!("{self}{text}"));
+   }
+}
+
+impl fmt::Display for IssueBody {
+   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      for section in &self.sections {
+         f.write_str(&section.text)?;
+      }
+      Ok(())
+   }
+}
+
+impl From<&str> for IssueBody {
+   fn from(text: &str) -> Self {
+      Self::parse(text)
+   }
+}
+
+impl From<String> for IssueBody {
+   fn from(text: String) -> Self {
+      Self::parse(&text)
+   }
+}
+
+impl Serialize for IssueBody {
+   fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+      serializer.serialize_str(&self.to_string())
+   }
+}
+
+impl<'de> Deserialize<'de> for IssueBody {
+   fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+      Ok(Self::parse(&String::deserialize(deserializer)?))
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn test_body_round_trips_and_exposes_sections() {
+      let text = "Intro\n\n**Issue**: Times out\non slow links\n\n**Impact**: Users locked \
+                  out\n\n**Custom**: kept\n\n**Checkpoint** (2024-01-01 10:00): Found it\n\n## \
+                  Checkpoint - 2024-01-02 09:00\n\nLooking\n\n## Closed\n\nDone";
+      let mut body = IssueBody::parse(text);
+      assert_eq!(body.to_string(), text);
+      assert_eq!(body.issue(), Some("Times out\non slow links"));
+      assert_eq!(body.impact(), Some("Users locked out"));
+      assert_eq!(body.acceptance(), None);
+      assert_eq!(body.closed_note(), Some("Done"));
+      let checkpoints: Vec<(Option<&str>, &str)> = body
+         .checkpoints()
+         .map(|c| (c.detail(), c.content()))
+         .collect();
+      let first = body.checkpoints().next().unwrap().timestamp().unwrap();
+      assert_eq!(first.to_rfc3339(), "2024-01-01T10:00:00+00:00");
+      assert_eq!(checkpoints, [
+         (Some("2024-01-01 10:00"), "Found it"),
+         (Some("2024-01-02 09:00"), "Looking")
+      ]);
+
+      body.set(SectionKind::Impact, "Everyone locked out");
+      body.set(SectionKind::Acceptance, "Login works");
+      assert_eq!(body.impact(), Some("Everyone locked out"));
+      assert_eq!(body.acceptance(), Some("Login works"));
+      let rendered = body.to_string();
+      assert!(rendered.contains("locked out\n\n**Acceptance**: Login works\n\n**Custom**: kept"));
+      assert_eq!(IssueBody::parse(&rendered), body);
+   }
+
+   #[test]
+   fn test_translated_sections() {
+      let text = "**Issue**: Login times out\n\n**Issue [de]**: Anmeldung läuft ab\n\n**Impact**: \
+                  Users locked out\n\n## Impact [fr]\n\nBloqués\n\n**Checkpoint** (2024-01-01 \
+                  10:00): Found it\n";
+      let body = IssueBody::parse(text);
+      assert_eq!(body.to_string(), text);
+      assert_eq!(body.issue(), Some("Login times out"));
+      assert_eq!(body.languages(), ["de", "fr"]);
+
+      let de = body.localized("de");
+      assert_eq!(de.issue(), Some("Anmeldung läuft ab"));
+      assert_eq!(de.impact(), Some("Users locked out"));
+      assert!(
+         de.to_string()
+            .starts_with("**Issue**: Anmeldung läuft ab\n\n**Impact**:")
+      );
+      assert_eq!(body.localized("fr").impact(), Some("Bloqués"));
+      assert_eq!(body.localized("es").to_string(), body.localized("xx").to_string());
+      assert_eq!(body.localized("es").checkpoints().count(), 1);
+   }
+}
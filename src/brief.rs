@@ -0,0 +1,212 @@
+//! Token-budgeted issue renditions
+//!
+//! `agentx show --context-budget` gives an agent just enough of an issue to
+//! act on: the metadata and acceptance criteria in full, then the other
+//! sections and the latest checkpoints sharing what is left of the budget.
+//! Small sections fit whole and the rest are cut to an equal share, each
+//! cut marked with an ellipsis. Tokens are estimated at four characters
+//! each, which is close enough for English prose and code.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+   body::{Section, SectionKind},
+   issue::Issue,
+};
+
+/// Characters per token in the estimate
+const CHARS_PER_TOKEN: usize = 4;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Brief {
+   pub num:       u32,
+   pub budget:    usize,
+   /// Estimated tokens in `text`
+   pub tokens:    usize,
+   /// Whether anything was cut or left out to fit the budget
+   pub truncated: bool,
+   pub text:      String,
+}
+
+/// Estimated tokens in `text`
+pub fn estimate_tokens(text: &str) -> usize {
+   text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// `issue` in about `budget` tokens, with at most its last `checkpoints`
+/// checkpoints
+pub fn brief(issue: &Issue, num: u32, issue_ref: &str, budget: usize, checkpoints: usize) -> Brief {
+   let meta = &issue.metadata;
+   let mut text = format!("# {issue_ref}: {}\n\n", meta.title);
+   text.push_str(&format!("status: {} · priority: {}", meta.status, meta.priority));
+   if let Some(effort) = &meta.effort {
+      text.push_str(&format!(" · effort: {effort}"));
+   }
+   if let Some(milestone) = &meta.milestone {
+      text.push_str(&format!(" · milestone: {milestone}"));
+   }
+   text.push('\n');
+   let list = |items: &[smol_str::SmolStr]| {
+      items
+         .iter()
+         .map(|s| s.as_str())
+         .collect::<Vec<_>>()
+         .join(", ")
+   };
+   if !meta.tags.is_empty() {
+      text.push_str(&format!("tags: {}\n", list(&meta.tags)));
+   }
+   if !meta.files.is_empty() {
+      text.push_str(&format!("files: {}\n", list(&meta.files)));
+   }
+   if !meta.depends_on.is_empty() {
+      let deps: Vec<String> = meta.depends_on.iter().map(|d| format!("#{d}")).collect();
+      text.push_str(&format!("depends on: {}\n", deps.join(", ")));
+   }
+   if let Some(reason) = &meta.blocked_reason {
+      text.push_str(&format!("blocked: {reason}\n"));
+   }
+
+   let sections = issue.body.sections();
+   let all_checkpoints: Vec<&Section> = issue.body.checkpoints().collect();
+   let skipped = all_checkpoints.len().saturating_sub(checkpoints);
+   let acceptance = issue.body.section(&SectionKind::Acceptance);
+   let mut parts: Vec<(String, &str)> = sections
+      .iter()
+      .filter(|s| !matches!(s.kind, SectionKind::Acceptance | SectionKind::Checkpoint))
+      .filter(|s| !s.content().is_empty())
+      .map(|s| (heading(s), s.content()))
+      .chain(
+         all_checkpoints[skipped..]
+            .iter()
+            .map(|s| (heading(s), s.content())),
+      )
+      .collect();
+   let mut truncated = skipped > 0;
+
+   let budget_chars = budget * CHARS_PER_TOKEN;
+   let mut used = text.chars().count();
+   if let Some(acceptance) = acceptance {
+      let heading = heading(acceptance);
+      let room = budget_chars.saturating_sub(used + heading.chars().count());
+      let (content, cut) = cut(acceptance.content(), room);
+      truncated |= cut;
+      text.push_str(&format!("{heading}{content}\n"));
+      used = text.chars().count();
+   }
+
+   // Share what is left: the smallest parts first, each getting at most an
+   // equal split of the remainder, so only the large ones are cut
+   let marker = format!("\n… {skipped} earlier checkpoint(s) left out\n");
+   let mut fixed: usize = parts.iter().map(|(h, _)| h.chars().count() + 1).sum();
+   if skipped > 0 {
+      fixed += marker.chars().count();
+   }
+   let mut room = budget_chars.saturating_sub(used + fixed);
+   let mut order: Vec<usize> = (0..parts.len()).collect();
+   order.sort_by_key(|&i| parts[i].1.chars().count());
+   let mut shares = vec![0; parts.len()];
+   for (done, &i) in order.iter().enumerate() {
+      let share = (room / (parts.len() - done)).min(parts[i].1.chars().count());
+      shares[i] = share;
+      room -= share;
+   }
+   if skipped > 0 {
+      let first = parts.len() - (all_checkpoints.len() - skipped);
+      parts.insert(first, (String::new(), ""));
+      shares.insert(first, 0);
+   }
+   for ((heading, content), share) in parts.iter().zip(shares) {
+      if heading.is_empty() {
+         text.push_str(&marker);
+         continue;
+      }
+      let (content, cut) = cut(content, share);
+      truncated |= cut;
+      text.push_str(&format!("{heading}{content}\n"));
+   }
+
+   let text = text.trim_end().to_string();
+   Brief { num, budget, tokens: estimate_tokens(&text), truncated, text }
+}
+
+fn heading(section: &Section) -> String {
+   match (&section.kind, section.detail()) {
+      (SectionKind::Preamble, _) => "\n".to_string(),
+      (kind, Some(detail)) => format!("\n## {} - {detail}\n", kind.label()),
+      (kind, None) => format!("\n## {}\n", kind.label()),
+   }
+}
+
+/// `text` cut to about `chars` characters at a word boundary, ending in an
+/// ellipsis when anything was cut
+fn cut(text: &str, chars: usize) -> (String, bool) {
+   if text.chars().count() <= chars {
+      return (text.to_string(), false);
+   }
+   let end = text
+      .char_indices()
+      .nth(chars.saturating_sub(1))
+      .map_or(text.len(), |(i, _)| i);
+   let kept = &text[..end];
+   let kept = match kept.rfind(char::is_whitespace) {
+      Some(space) if space * 2 > kept.len() => &kept[..space],
+      _ => kept,
+   };
+   (format!("{}…", kept.trim_end()), true)
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::{
+      body::IssueBody,
+      issue::{Priority, test_support::issue},
+   };
+
+   #[test]
+   fn test_brief_fits_budget() {
+      let mut issue = issue(7)
+         .title("Slow search")
+         .priority(Priority::High)
+         .tags(&["perf"])
+         .files(&["src/search.rs"])
+         .body(&"word ".repeat(400))
+         .impact("Users wait")
+         .acceptance("Search under 100ms")
+         .build()
+         .issue;
+      let mut body = issue.body.to_string();
+      for day in 1..=4 {
+         body.push_str(&format!("\n\n## Checkpoint - 2024-01-0{day} 10:00\n\nStep {day}"));
+      }
+      issue.body = IssueBody::parse(&body);
+
+      let brief = brief(&issue, 7, "BUG-7", 150, 2);
+      assert!(brief.truncated);
+      assert!(brief.tokens <= 150, "{} tokens", brief.tokens);
+      assert!(
+         brief
+            .text
+            .starts_with("# BUG-7: Slow search\n\nstatus: open")
+      );
+      assert!(brief.text.contains("## Acceptance\nSearch under 100ms\n"));
+      assert!(brief.text.contains("## Impact\nUsers wait\n"));
+      assert!(brief.text.contains("word…\n"));
+      assert!(brief.text.contains("… 2 earlier checkpoint(s) left out"));
+      assert!(!brief.text.contains("Step 2"));
+      assert!(
+         brief
+            .text
+            .ends_with("## Checkpoint - 2024-01-04 10:00\nStep 4")
+      );
+
+      let whole = super::brief(&issue, 7, "BUG-7", 10_000, 10);
+      assert!(!whole.truncated);
+      assert!(
+         whole
+            .text
+            .contains(&"word ".repeat(400).trim_end().to_string())
+      );
+   }
+}
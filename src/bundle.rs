@@ -0,0 +1,228 @@
+//! Portable issue bundles
+//!
+//! `agentx bundle 12 --out issue-12.tar` packs one issue for moving it to
+//! another repository: its file, its archived checkpoints, and a stub (title,
+//! status, source URL) for each issue it depends on, blocks or mentions.
+//! `agentx bundle import issue-12.tar` stores it under the next free number
+//! there and relinks every stub to the local issue with the same source URL
+//! or, failing that, the same title. Mentions in the body are renumbered to
+//! match; relations whose stub matches nothing are dropped and reported.
+
+use std::{
+   collections::HashMap,
+   io::{Read, Write},
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::issue::{Issue, IssueWithId};
+
+/// Version of the bundle layout, refused on import when newer
+pub const FORMAT: u32 = 1;
+
+const MANIFEST: &str = "manifest.json";
+const ISSUE: &str = "issue.mdx";
+const CHECKPOINTS: &str = "checkpoints.md";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Relation {
+   DependsOn,
+   Blocks,
+   References,
+}
+
+/// An issue the bundled one is related to, described well enough to find
+/// its counterpart in another repository
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelationStub {
+   pub relation:   Relation,
+   pub num:        u32,
+   pub title:      String,
+   pub status:     String,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub source_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+   pub format:       u32,
+   /// Number of the issue in the repository it was bundled from
+   pub num:          u32,
+   /// Prefix of full issue refs there, for renumbering mentions
+   pub issue_prefix: String,
+   pub exported:     DateTime<Utc>,
+   pub relations:    Vec<RelationStub>,
+}
+
+/// The contents of a bundle file
+#[derive(Debug, Clone)]
+pub struct Bundle {
+   pub manifest:    Manifest,
+   /// The issue file, with its body in plain text
+   pub issue:       String,
+   pub checkpoints: Option<String>,
+}
+
+impl Bundle {
+   /// Write the bundle as a tar archive
+   pub fn write(&self, out: impl Write) -> Result<()> {
+      let mut tar = tar::Builder::new(out);
+      let manifest = serde_json::to_string_pretty(&self.manifest)?;
+      let mut entries = vec![(MANIFEST, manifest.as_str()), (ISSUE, self.issue.as_str())];
+      if let Some(checkpoints) = &self.checkpoints {
+         entries.push((CHECKPOINTS, checkpoints));
+      }
+      for (name, content) in entries {
+         let mut header = tar::Header::new_gnu();
+         header.set_size(content.len() as u64);
+         header.set_mode(0o644);
+         header.set_mtime(self.manifest.exported.timestamp().max(0) as u64);
+         header.set_cksum();
+         tar.append_data(&mut header, name, content.as_bytes())?;
+      }
+      tar.into_inner()?.flush()?;
+      Ok(())
+   }
+
+   /// Read a bundle written by [`Bundle::write`]
+   pub fn read(input: impl Read) -> Result<Self> {
+      let mut files = HashMap::new();
+      let mut tar = tar::Archive::new(input);
+      for entry in tar
+         .entries()
+         .context("Not a bundle: unreadable tar archive")?
+      {
+         let mut entry = entry?;
+         let name = entry.path()?.to_string_lossy().into_owned();
+         let mut content = String::new();
+         entry
+            .read_to_string(&mut content)
+            .with_context(|| format!("Bundle entry {name} is not UTF-8"))?;
+         files.insert(name, content);
+      }
+      let manifest: Manifest = serde_json::from_str(
+         files
+            .get(MANIFEST)
+            .context("Not a bundle: no manifest.json")?,
+      )
+      .context("Invalid bundle manifest")?;
+      if manifest.format > FORMAT {
+         anyhow::bail!(
+            "Bundle format {} is newer than this agentx supports ({FORMAT})",
+            manifest.format
+         );
+      }
+      let issue = files.remove(ISSUE).context("Not a bundle: no issue.mdx")?;
+      Ok(Self { manifest, issue, checkpoints: files.remove(CHECKPOINTS) })
+   }
+}
+
+/// Stubs for the issues `issue` depends on, blocks and mentions, looked up
+/// in `all`; an issue that is both blocked on and mentioned gets one stub
+/// per relation
+pub fn stubs(issue: &Issue, all: &[IssueWithId]) -> Vec<RelationStub> {
+   let meta = &issue.metadata;
+   let related = [
+      (Relation::DependsOn, &meta.depends_on),
+      (Relation::Blocks, &meta.blocks),
+      (Relation::References, &meta.references),
+   ];
+   related
+      .into_iter()
+      .flat_map(|(relation, nums)| nums.iter().map(move |&num| (relation, num)))
+      .filter_map(|(relation, num)| {
+         let other = &all.iter().find(|i| i.id == num)?.issue.metadata;
+         Some(RelationStub {
+            relation,
+            num,
+            title: other.title.to_string(),
+            status: other.status.to_string(),
+            source_url: other.source_url.as_ref().map(|u| u.to_string()),
+         })
+      })
+      .collect()
+}
+
+/// The local issue each stub's number stands for: the one with the same
+/// source URL, else the only one with the same title (ignoring case)
+pub fn relink(stubs: &[RelationStub], local: &[IssueWithId]) -> HashMap<u32, u32> {
+   let mut map = HashMap::new();
+   for stub in stubs {
+      let by_url = stub.source_url.as_deref().and_then(|url| {
+         local
+            .iter()
+            .find(|i| i.issue.metadata.source_url.as_deref() == Some(url))
+      });
+      let found = by_url.or_else(|| {
+         let mut same_title = local.iter().filter(|i| {
+            i.issue
+               .metadata
+               .title
+               .eq_ignore_ascii_case(stub.title.trim())
+         });
+         let first = same_title.next()?;
+         same_title.next().is_none().then_some(first)
+      });
+      if let Some(found) = found {
+         map.insert(stub.num, found.id);
+      }
+   }
+   map
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::issue::test_support::issue;
+
+   #[test]
+   fn test_bundle_round_trip_and_relink() {
+      let mut moved = issue(12).title("Crash on start").build();
+      moved.issue.metadata.depends_on = vec![3];
+      moved.issue.metadata.references = vec![3, 7];
+      let mut upstream = issue(7).title("Upgrade parser").build();
+      upstream.issue.metadata.source_url = Some("https://github.com/o/r/issues/9".into());
+      let all = vec![issue(3).title("Config loader").build(), upstream, moved.clone()];
+
+      let relations = stubs(&moved.issue, &all);
+      let kinds: Vec<(Relation, u32)> = relations.iter().map(|s| (s.relation, s.num)).collect();
+      assert_eq!(kinds, [
+         (Relation::DependsOn, 3),
+         (Relation::References, 3),
+         (Relation::References, 7)
+      ]);
+
+      let bundle = Bundle {
+         manifest:    Manifest {
+            format: FORMAT,
+            num: 12,
+            issue_prefix: "ISSUE".into(),
+            exported: Utc::now(),
+            relations,
+         },
+         issue:       moved.issue.to_mdx(),
+         checkpoints: Some("## Checkpoint\n\nDone".into()),
+      };
+      let mut tar = Vec::new();
+      bundle.write(&mut tar).unwrap();
+      let read = Bundle::read(tar.as_slice()).unwrap();
+      assert_eq!(read.issue, bundle.issue);
+      assert_eq!(read.checkpoints.as_deref(), Some("## Checkpoint\n\nDone"));
+      assert_eq!(read.manifest.relations.len(), 3);
+
+      // Relinked by source URL despite the new title, and by title; the
+      // second "config loader" makes that title ambiguous elsewhere
+      let mut renamed = issue(40).title("Parser upgrade").build();
+      renamed.issue.metadata.source_url = Some("https://github.com/o/r/issues/9".into());
+      let local = vec![issue(21).title("config loader").build(), renamed];
+      let map = relink(&read.manifest.relations, &local);
+      assert_eq!(map, HashMap::from([(3, 21), (7, 40)]));
+      let ambiguous =
+         vec![issue(21).title("Config loader").build(), issue(22).title("Config loader").build()];
+      assert!(relink(&read.manifest.relations, &ambiguous).is_empty());
+      assert!(Bundle::read(&b"not a tar"[..]).is_err());
+   }
+}
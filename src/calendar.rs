@@ -0,0 +1,205 @@
+//! Working calendar
+//!
+//! With a `calendar` in the config, durations about work count working time
+//! only: the average close time in `metrics`, `forecast` dates and due
+//! times such as `--due +3d`, where a day is a working day and a week the
+//! working days of one. Days outside `days` and the `holidays` have no
+//! working hours. The calendar runs on local time.
+
+use anyhow::{Context, Result};
+use chrono::{
+   DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{parse_due, parse_duration};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkCalendar {
+   /// Working days of the week
+   #[serde(default = "default_days")]
+   pub days:     Vec<Weekday>,
+   /// When a working day starts, e.g. `09:00`
+   #[serde(default = "default_start")]
+   pub start:    String,
+   /// When a working day ends, e.g. `17:00`
+   #[serde(default = "default_end")]
+   pub end:      String,
+   /// Days off on top of the weekend
+   #[serde(default)]
+   pub holidays: Vec<NaiveDate>,
+}
+
+fn default_days() -> Vec<Weekday> {
+   vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri]
+}
+
+fn default_start() -> String {
+   "09:00".to_string()
+}
+
+fn default_end() -> String {
+   "17:00".to_string()
+}
+
+impl Default for WorkCalendar {
+   fn default() -> Self {
+      Self {
+         days:     default_days(),
+         start:    default_start(),
+         end:      default_end(),
+         holidays: Vec::new(),
+      }
+   }
+}
+
+fn parse_time(s: &str) -> Result<NaiveTime> {
+   NaiveTime::parse_from_str(s.trim(), "%H:%M")
+      .with_context(|| format!("Invalid working time: {s}. Use HH:MM"))
+}
+
+/// Working hours of one day, checked
+#[derive(Debug, Clone, Copy)]
+struct Hours {
+   start: NaiveTime,
+   end:   NaiveTime,
+}
+
+impl WorkCalendar {
+   fn hours(&self) -> Result<Hours> {
+      let hours = Hours { start: parse_time(&self.start)?, end: parse_time(&self.end)? };
+      if hours.end <= hours.start {
+         anyhow::bail!("Working day ends ({}) before it starts ({})", self.end, self.start);
+      }
+      if self.days.is_empty() {
+         anyhow::bail!("The calendar has no working days");
+      }
+      Ok(hours)
+   }
+
+   fn is_working_day(&self, date: NaiveDate) -> bool {
+      self.days.contains(&date.weekday()) && !self.holidays.contains(&date)
+   }
+
+   /// Working time in one full working day
+   pub fn day_length(&self) -> Result<Duration> {
+      let hours = self.hours()?;
+      Ok(hours.end - hours.start)
+   }
+
+   /// Working time in a week without holidays
+   pub fn week_length(&self) -> Result<Duration> {
+      Ok(self.day_length()? * self.days.len() as i32)
+   }
+
+   fn between_naive(&self, hours: Hours, from: NaiveDateTime, to: NaiveDateTime) -> Duration {
+      let mut total = Duration::zero();
+      let mut date = from.date();
+      while date <= to.date() {
+         if self.is_working_day(date) {
+            let open = date.and_time(hours.start).max(from);
+            let close = date.and_time(hours.end).min(to);
+            if close > open {
+               total += close - open;
+            }
+         }
+         date += Duration::days(1);
+      }
+      total
+   }
+
+   fn after_naive(&self, hours: Hours, start: NaiveDateTime, work: Duration) -> NaiveDateTime {
+      if work <= Duration::zero() {
+         return start;
+      }
+      let mut left = work;
+      let mut date = start.date();
+      // There are working days and finitely many holidays, so this ends
+      loop {
+         if self.is_working_day(date) {
+            let open = date.and_time(hours.start).max(start);
+            let close = date.and_time(hours.end);
+            if close > open {
+               if left <= close - open {
+                  return open + left;
+               }
+               left -= close - open;
+            }
+         }
+         date += Duration::days(1);
+      }
+   }
+
+   /// Working time between `from` and `to`
+   pub fn between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Duration> {
+      let hours = self.hours()?;
+      Ok(self.between_naive(hours, local(from), local(to)))
+   }
+
+   /// When `work` of working time after `start` is done
+   pub fn after(&self, start: DateTime<Utc>, work: Duration) -> Result<DateTime<Utc>> {
+      let hours = self.hours()?;
+      Ok(utc(self.after_naive(hours, local(start), work)))
+   }
+
+   /// Parse a due time as [`parse_due`] does, with durations in working
+   /// time: `3d` is three working days and `1w` a working week
+   pub fn parse_due(&self, s: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+      let s = s.trim().trim_start_matches('+');
+      let Ok(wall) = parse_duration(s) else {
+         return parse_due(s, now);
+      };
+      let unit = s.trim_start_matches(|c: char| c.is_ascii_digit()).trim();
+      let work = match unit.chars().next().map(|c| c.to_ascii_lowercase()) {
+         Some('d') => self.day_length()? * wall.num_days() as i32,
+         Some('w') => self.week_length()? * wall.num_weeks() as i32,
+         _ => wall,
+      };
+      self.after(now, work)
+   }
+}
+
+fn local(at: DateTime<Utc>) -> NaiveDateTime {
+   at.with_timezone(&Local).naive_local()
+}
+
+fn utc(at: NaiveDateTime) -> DateTime<Utc> {
+   // A time skipped by a DST change is read as UTC rather than lost
+   Local
+      .from_local_datetime(&at)
+      .earliest()
+      .map_or_else(|| at.and_utc(), |at| at.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn at(s: &str) -> NaiveDateTime {
+      NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+   }
+
+   #[test]
+   fn test_working_time_skips_nights_weekends_and_holidays() {
+      let calendar = WorkCalendar {
+         holidays: vec![NaiveDate::from_ymd_opt(2026, 10, 12).unwrap()],
+         ..WorkCalendar::default()
+      };
+      let hours = calendar.hours().unwrap();
+
+      // Friday 16:00 to Tuesday 10:00, Monday off: 1h Friday, 1h Tuesday
+      let worked = calendar.between_naive(hours, at("2026-10-09 16:00"), at("2026-10-13 10:00"));
+      assert_eq!(worked, Duration::hours(2));
+
+      // A working day (8h) after Friday 16:00 skips the weekend and Monday
+      let due = calendar.after_naive(hours, at("2026-10-09 16:00"), Duration::hours(8));
+      assert_eq!(due, at("2026-10-13 16:00"));
+      // Starting at night counts from the next opening
+      let due = calendar.after_naive(hours, at("2026-10-13 20:00"), Duration::hours(2));
+      assert_eq!(due, at("2026-10-14 11:00"));
+
+      assert_eq!(calendar.week_length().unwrap(), Duration::hours(40));
+      let bad = WorkCalendar { end: "08:00".into(), ..WorkCalendar::default() };
+      assert!(bad.day_length().is_err());
+   }
+}
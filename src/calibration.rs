@@ -0,0 +1,314 @@
+//! Estimate calibration
+//!
+//! `agentx calibration` compares the effort estimates of closed issues with
+//! the time they actually spent in progress, overall, per size of estimate
+//! and per tag, to surface systematic bias such as "your 2h tasks take 5h".
+//! Estimates are working time, so an in-progress stretch counts at most a
+//! workday (8h) per 24 hours; issues closed without ever being started have
+//! no measured time and are left out. When a new issue's estimate falls in
+//! a biased slice, `new` suggests a corrected one.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+   flow::status_intervals,
+   issue::{IssueWithId, Status},
+   plan::hours,
+   utils::parse_effort,
+};
+
+/// Closed issues a slice needs before its bias counts
+pub const MIN_SAMPLES: usize = 3;
+
+/// Ratios of actual to estimated time within this range count as on target
+const ON_TARGET: (f64, f64) = (0.8, 1.25);
+
+const WORKDAY_MINUTES: i64 = 8 * 60;
+
+/// Upper bounds (in minutes, inclusive) and names of the size buckets
+const SIZES: &[(u32, &str)] =
+   &[(60, "≤1h"), (4 * 60, "1h–4h"), (8 * 60, "4h–1d"), (3 * 8 * 60, "1d–3d"), (u32::MAX, ">3d")];
+
+/// One closed issue's estimate next to its measured time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sample {
+   pub num:              u32,
+   pub estimate_minutes: u32,
+   pub actual_minutes:   u32,
+}
+
+impl Sample {
+   fn ratio(&self) -> f64 {
+      self.actual_minutes as f64 / self.estimate_minutes.max(1) as f64
+   }
+}
+
+/// How far actual time ran from the estimates within one slice
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bias {
+   pub name:                 String,
+   pub samples:              usize,
+   pub avg_estimate_minutes: u32,
+   pub avg_actual_minutes:   u32,
+   /// Median of actual over estimated time; above 1 means underestimates
+   pub ratio:                f64,
+   /// Enough samples and a ratio outside the on-target range
+   pub biased:               bool,
+}
+
+impl Bias {
+   fn new(name: String, samples: &[&Sample]) -> Self {
+      let count = samples.len().max(1) as u64;
+      let mean = |of: fn(&Sample) -> u32| {
+         (samples.iter().map(|s| u64::from(of(s))).sum::<u64>() / count) as u32
+      };
+      let mut ratios: Vec<f64> = samples.iter().map(|s| s.ratio()).collect();
+      ratios.sort_by(f64::total_cmp);
+      let ratio = match ratios.len() {
+         0 => 1.0,
+         n if n % 2 == 1 => ratios[n / 2],
+         n => (ratios[n / 2 - 1] + ratios[n / 2]) / 2.0,
+      };
+      Self {
+         name,
+         samples: samples.len(),
+         avg_estimate_minutes: mean(|s| s.estimate_minutes),
+         avg_actual_minutes: mean(|s| s.actual_minutes),
+         ratio,
+         biased: samples.len() >= MIN_SAMPLES && !(ON_TARGET.0..=ON_TARGET.1).contains(&ratio),
+      }
+   }
+
+   /// E.g. "1h–4h estimates (avg 2h) take 5h on average, 2.5× the estimate"
+   pub fn summary(&self, what: &str) -> String {
+      format!(
+         "{what} (avg {}) take {} on average, {:.1}× the estimate",
+         hours(self.avg_estimate_minutes),
+         hours(self.avg_actual_minutes),
+         self.ratio
+      )
+   }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Calibration {
+   pub samples: Vec<Sample>,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub overall: Option<Bias>,
+   /// Smallest estimates first
+   pub by_size: Vec<Bias>,
+   /// Most samples first
+   pub by_tag:  Vec<Bias>,
+}
+
+/// A corrected estimate for a new issue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EstimateHint {
+   /// Effort to use instead, e.g. `5h`
+   pub suggested: String,
+   pub ratio:     f64,
+   /// The slice the correction comes from, e.g. `tag api`
+   pub basis:     String,
+   pub samples:   usize,
+}
+
+/// Minutes spent in progress, counting at most a workday per 24 hours
+fn working_minutes(start: DateTime<Utc>, end: DateTime<Utc>) -> i64 {
+   let minutes = (end - start).num_minutes().max(0);
+   minutes / (24 * 60) * WORKDAY_MINUTES + (minutes % (24 * 60)).min(WORKDAY_MINUTES)
+}
+
+/// Estimate and in-progress time of `issue`, if it was closed with both
+pub fn sample(issue: &IssueWithId) -> Option<Sample> {
+   let meta = &issue.issue.metadata;
+   let closed = meta.closed?;
+   let estimate_minutes = parse_effort(meta.effort.as_deref()?).ok()?;
+   let actual: i64 = status_intervals(issue, closed)
+      .iter()
+      .filter(|i| i.status == Status::InProgress)
+      .map(|i| working_minutes(i.start, i.end))
+      .sum();
+   (estimate_minutes > 0 && actual > 0).then_some(Sample {
+      num: issue.id,
+      estimate_minutes,
+      actual_minutes: actual as u32,
+   })
+}
+
+fn size_name(minutes: u32) -> &'static str {
+   SIZES
+      .iter()
+      .find(|(limit, _)| minutes <= *limit)
+      .map_or(">3d", |(_, name)| name)
+}
+
+/// Calibration over the closed issues in `closed`
+pub fn calibrate(closed: &[IssueWithId]) -> Calibration {
+   let measured: Vec<(&IssueWithId, Sample)> = closed
+      .iter()
+      .filter_map(|issue| Some((issue, sample(issue)?)))
+      .collect();
+   let all: Vec<&Sample> = measured.iter().map(|(_, s)| s).collect();
+
+   let by_size = SIZES
+      .iter()
+      .filter_map(|(_, name)| {
+         let samples: Vec<&Sample> = all
+            .iter()
+            .copied()
+            .filter(|s| size_name(s.estimate_minutes) == *name)
+            .collect();
+         (!samples.is_empty()).then(|| Bias::new(name.to_string(), &samples))
+      })
+      .collect();
+
+   let mut tagged: BTreeMap<String, Vec<&Sample>> = BTreeMap::new();
+   for (issue, sample) in &measured {
+      for tag in &issue.issue.metadata.tags {
+         tagged.entry(tag.to_string()).or_default().push(sample);
+      }
+   }
+   let mut by_tag: Vec<Bias> = tagged
+      .into_iter()
+      .map(|(tag, samples)| Bias::new(tag, &samples))
+      .collect();
+   by_tag.sort_by_key(|b| std::cmp::Reverse(b.samples));
+
+   Calibration {
+      overall: (!all.is_empty()).then(|| Bias::new("all".to_string(), &all)),
+      by_size,
+      by_tag,
+      samples: measured.into_iter().map(|(_, s)| s).collect(),
+   }
+}
+
+/// `minutes` as an effort to type back in, rounded to a readable step
+fn effort_text(minutes: f64) -> String {
+   if minutes < 60.0 {
+      format!("{}m", ((minutes / 5.0).round() as u32 * 5).max(5))
+   } else if minutes < WORKDAY_MINUTES as f64 {
+      hours(((minutes / 30.0).round() * 30.0) as u32)
+   } else {
+      let days = (minutes / WORKDAY_MINUTES as f64 * 2.0).round() / 2.0;
+      if days.fract() == 0.0 {
+         format!("{days:.0}d")
+      } else {
+         format!("{days:.1}d")
+      }
+   }
+}
+
+/// A corrected estimate for a new issue estimated at `effort` with `tags`,
+/// from the issue's tag with the most closed issues, else its size
+/// bucket, else the overall bias; `None` when estimates like it are on target
+pub fn hint(calibration: &Calibration, effort: &str, tags: &[String]) -> Option<EstimateHint> {
+   let minutes = parse_effort(effort).ok()?;
+   let size = size_name(minutes);
+   let tag = calibration
+      .by_tag
+      .iter()
+      .find(|b| b.samples >= MIN_SAMPLES && tags.contains(&b.name));
+   let (bias, basis) = match tag {
+      Some(bias) => (bias, format!("tag {}", bias.name)),
+      None => match calibration
+         .by_size
+         .iter()
+         .find(|b| b.name == size && b.samples >= MIN_SAMPLES)
+      {
+         Some(bias) => (bias, format!("{size} estimates")),
+         None => (calibration.overall.as_ref()?, "all estimates".to_string()),
+      },
+   };
+   bias.biased.then(|| EstimateHint {
+      suggested: effort_text(minutes as f64 * bias.ratio),
+      ratio: bias.ratio,
+      basis,
+      samples: bias.samples,
+   })
+}
+
+#[cfg(test)]
+mod tests {
+   use chrono::Duration;
+
+   use super::*;
+   use crate::issue::test_support::issue;
+
+   #[test]
+   fn test_calibration_and_hint() {
+      // Each issue starts an hour after it was created
+      let created = Utc::now();
+      let started = created + Duration::hours(1);
+      let done = |active_hours| started + Duration::hours(active_hours);
+      let mut issues = vec![
+         issue(1)
+            .effort("2h")
+            .tags(&["api"])
+            .created(created)
+            .moved(Status::InProgress, started)
+            .moved(Status::Closed, done(5))
+            .closed(done(5))
+            .build(),
+         issue(2)
+            .effort("2h")
+            .tags(&["api"])
+            .created(created)
+            .moved(Status::InProgress, started)
+            .moved(Status::Closed, done(4))
+            .closed(done(4))
+            .build(),
+         issue(3)
+            .effort("3h")
+            .tags(&["api", "ui"])
+            .created(created)
+            .moved(Status::InProgress, started)
+            .moved(Status::Closed, done(6))
+            .closed(done(6))
+            .build(),
+         issue(4)
+            .effort("30m")
+            .tags(&["ui"])
+            .created(created)
+            .moved(Status::InProgress, started)
+            .moved(Status::Closed, done(0))
+            .closed(done(0))
+            .build(),
+         // Two calendar days in progress count as two workdays
+         issue(5)
+            .effort("2d")
+            .created(created)
+            .moved(Status::InProgress, started)
+            .moved(Status::Closed, done(48))
+            .closed(done(48))
+            .build(),
+      ];
+      issues[3].issue.metadata.transitions.clear();
+      let calibration = calibrate(&issues);
+
+      assert_eq!(calibration.samples.len(), 4);
+      assert_eq!(calibration.samples[3].actual_minutes, 2 * 8 * 60);
+      let sizes: Vec<&str> = calibration
+         .by_size
+         .iter()
+         .map(|b| b.name.as_str())
+         .collect();
+      assert_eq!(sizes, ["1h–4h", "1d–3d"]);
+      let small = &calibration.by_size[0];
+      assert_eq!((small.samples, small.ratio, small.biased), (3, 2.0, true));
+      assert_eq!(
+         small.summary("1h–4h estimates"),
+         "1h–4h estimates (avg 2.3h) take 5h on average, 2.0× the estimate"
+      );
+      assert_eq!(calibration.by_tag[0].name, "api");
+
+      let api = hint(&calibration, "2h", &["api".to_string()]).unwrap();
+      assert_eq!((api.suggested.as_str(), api.basis.as_str()), ("4h", "tag api"));
+      let sized = hint(&calibration, "90m", &[]).unwrap();
+      assert_eq!(sized.basis, "1h–4h estimates");
+      assert_eq!(effort_text(600.0), "1.5d");
+      assert_eq!(effort_text(20.0), "20m");
+   }
+}
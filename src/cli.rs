@@ -11,6 +11,48 @@ pub struct Cli {
    #[arg(long, short = 'i', global = true, help = "Force interactive mode")]
    pub interactive: bool,
 
+   #[arg(long, global = true, help = "Only accept issue numbers and aliases, not title fragments")]
+   pub exact: bool,
+
+   #[arg(long, global = true, help = "Allow status changes the transition rules refuse")]
+   pub force: bool,
+
+   #[arg(long, global = true, help = "Skip confirmation prompts and the bulk change limit")]
+   pub yes: bool,
+
+   #[arg(
+      long,
+      global = true,
+      help = "Use the global issue store for personal tasks (~/.local/share/agentx/issues) and \
+              the user-level config"
+   )]
+   pub global: bool,
+
+   #[arg(
+      long,
+      global = true,
+      help = "Agent name for claims (default: $AGENTX_AGENT, else the git user)"
+   )]
+   pub agent: Option<SmolStr>,
+
+   #[arg(
+      long,
+      global = true,
+      help = "Log spans (storage, parsing, graphs, git) to stderr; filter with $AGENTX_LOG"
+   )]
+   pub trace: bool,
+
+   #[arg(long, global = true, help = "Print where the time went when the command finishes")]
+   pub timings: bool,
+
+   #[arg(
+      long,
+      global = true,
+      help = "Read the fields a wizard would ask for from stdin instead, as json or yaml (new, \
+              checkpoint, depend)"
+   )]
+   pub stdin_form: Option<SmolStr>,
+
    #[command(subcommand)]
    pub command: Command,
 }
@@ -20,15 +62,115 @@ pub enum Command {
    /// List issues
    #[command(alias = "ls")]
    List {
-      #[arg(long, default_value = "open")]
+      #[arg(
+         long,
+         default_value = "open",
+         help = "open (not closed), closed, all, or one status: active, blocked, done, backlog"
+      )]
       status: SmolStr,
 
       #[arg(short, long)]
       verbose: bool,
+
+      #[arg(
+         long = "where",
+         help = "Filter with a query expression (e.g. 'priority>=high tag:api')"
+      )]
+      where_clause: Option<SmolStr>,
+
+      #[arg(
+         long,
+         visible_alias = "include-archived",
+         help = "Also list closed issues (closed issues are the archive)"
+      )]
+      include_closed: bool,
+
+      #[arg(long, help = "Only issues created on or after a date (YYYY-MM-DD or 30d)")]
+      created_since: Option<SmolStr>,
+
+      #[arg(long, help = "Only issues closed on or after a date; implies --include-closed")]
+      closed_since: Option<SmolStr>,
+
+      #[arg(long, help = "Only issues with a file matching a glob (e.g. 'src/tui/**')")]
+      file_glob: Option<SmolStr>,
+
+      #[arg(long, help = "Only issues with a file matching a regex")]
+      file_regex: Option<SmolStr>,
+
+      #[arg(
+         long,
+         help = "Group by priority (default), status, tag, milestone, assignee or none; JSON \
+                 becomes {group_by, groups} unless none"
+      )]
+      group_by: Option<SmolStr>,
+
+      #[arg(long, help = "Sort by created, updated, effort or priority (default: issue number)")]
+      sort: Option<SmolStr>,
+
+      #[arg(long, help = "Show titles in this language where translated (title_i18n)")]
+      lang: Option<SmolStr>,
+
+      #[arg(long, requires = "sort", default_value = "asc", help = "Sort order: asc or desc")]
+      order: SmolStr,
    },
 
-   /// Show full issue details
-   Show { bug_ref: SmolStr },
+   /// Search titles, tags and bodies, best matches first
+   Search {
+      #[arg(required = true, num_args = 1.., help = "Words that must all appear")]
+      query: Vec<SmolStr>,
+
+      #[arg(
+         long,
+         default_value = "open",
+         help = "Which issues to search: open, closed, all, or one status such as active"
+      )]
+      status: SmolStr,
+
+      #[arg(long, visible_alias = "include-archived", help = "Also search closed issues")]
+      include_closed: bool,
+
+      #[arg(long, default_value_t = 20, help = "Most results to show (0 for all)")]
+      limit: usize,
+   },
+
+   /// Show full issue details (pick from a list if no ref is given)
+   Show {
+      bug_ref: Option<SmolStr>,
+
+      #[arg(long, help = "Copy the issue ref to the clipboard (see copy_template)")]
+      copy: bool,
+
+      #[arg(long, help = "Open the issue file in $EDITOR")]
+      open: bool,
+
+      #[arg(long, help = "Print the raw MDX instead of rendering the markdown")]
+      raw: bool,
+
+      #[arg(
+         long,
+         conflicts_with_all = ["raw", "context_budget"],
+         help = "Show the title and sections in this language where translated, falling back to \
+                 the original"
+      )]
+      lang: Option<SmolStr>,
+
+      #[arg(
+         long,
+         value_name = "TOKENS",
+         conflicts_with = "raw",
+         help = "Trim the issue to about this many tokens for an LLM: metadata and acceptance \
+                 criteria, then the rest cut to fit"
+      )]
+      context_budget: Option<usize>,
+
+      #[arg(
+         long,
+         default_value_t = 3,
+         requires = "context_budget",
+         help = "Latest checkpoints to include with --context-budget"
+      )]
+      checkpoints: usize,
+   },
 
    /// Create a new issue (use -i for interactive mode)
    #[command(alias = "add")]
@@ -59,11 +201,34 @@ pub enum Command {
 
       #[arg(long)]
       context: Option<SmolStr>,
+
+      #[arg(long)]
+      milestone: Option<SmolStr>,
+
+      #[arg(
+         long,
+         help = "What sort of work this is: bug, feature, chore, spike, or a kind from the \
+                 config; its template fills the sections not given"
+      )]
+      kind: Option<SmolStr>,
+
+      #[arg(long, help = "Copy the new issue's ref to the clipboard (see copy_template)")]
+      copy: bool,
+
+      #[arg(long, help = "Open the new issue file in $EDITOR")]
+      open: bool,
+
+      #[arg(
+         long,
+         help = "Draft the issue from a stack trace or bug report on stdin: title from its first \
+                 line, files from the paths it mentions"
+      )]
+      from_stdin: bool,
    },
 
-   /// Mark issue as in-progress
+   /// Mark issue as in-progress (pick from ready issues if no ref is given)
    Start {
-      bug_ref: SmolStr,
+      bug_ref: Option<SmolStr>,
 
       #[arg(long, help = "Create git branch (overrides config)")]
       branch: bool,
@@ -80,9 +245,9 @@ pub enum Command {
       reason: SmolStr,
    },
 
-   /// Mark issue as closed
+   /// Mark issue as closed (pick from open issues if no ref is given)
    Close {
-      bug_ref: SmolStr,
+      bug_ref: Option<SmolStr>,
 
       #[arg(short, long)]
       message: Option<SmolStr>,
@@ -92,6 +257,12 @@ pub enum Command {
 
       #[arg(long, help = "Skip git commit (overrides config)")]
       no_commit: bool,
+
+      #[arg(long, help = "Unblock blocked issues that were waiting on this one")]
+      auto_unblock: bool,
+
+      #[arg(long, help = "Add the files changed in the worktree to the issue without asking")]
+      auto_files: bool,
    },
 
    /// Reopen a closed issue
@@ -103,25 +274,182 @@ pub enum Command {
    /// Activate issue from backlog
    Activate { bug_ref: SmolStr },
 
-   /// Add checkpoint to issue
-   Checkpoint { bug_ref: SmolStr, message: Vec<SmolStr> },
+   /// Add checkpoint to issue (without arguments, a wizard picks an
+   /// in-progress issue)
+   Checkpoint {
+      bug_ref: Option<SmolStr>,
+      message: Vec<SmolStr>,
+
+      #[arg(long, help = "Add the files changed in the worktree to the issue without asking")]
+      auto_files: bool,
+   },
+
+   /// Fold all but the last --keep checkpoints into a history summary,
+   /// archiving the originals
+   Compact {
+      bug_ref: SmolStr,
+
+      #[arg(long, help = "Checkpoints to keep as they are (default: compact.keep)")]
+      keep: Option<usize>,
+   },
+
+   /// Claim an issue for an agent with a lease that expires after --ttl;
+   /// others can't start or checkpoint it meanwhile without --force
+   Claim {
+      bug_ref: SmolStr,
+
+      #[arg(long, default_value = "2h", help = "How long the lease lasts (e.g. 30m, 2h, 1d)")]
+      ttl: SmolStr,
+   },
+
+   /// Print a conventional commit message from an issue (default: the one
+   /// whose branch is checked out, else the one started last), e.g. for
+   /// `git commit -eF <(agentx commit-msg)`
+   CommitMsg { bug_ref: Option<SmolStr> },
+
+   /// Pack an issue into a portable bundle, or with `import`, add a bundled
+   /// issue to this project under a new number
+   #[command(args_conflicts_with_subcommands = true)]
+   Bundle {
+      #[arg(required = true)]
+      bug_ref: Option<SmolStr>,
+
+      #[arg(long, help = "Bundle file to write (default: issue-<N>.tar)")]
+      out: Option<SmolStr>,
+
+      #[command(subcommand)]
+      action: Option<BundleAction>,
+   },
+
+   /// Release an agent's claim on an issue, or with `status`/`cut`, track
+   /// and cut releases from the issues targeting them (set with `bulk-edit
+   /// --target-version`)
+   #[command(args_conflicts_with_subcommands = true)]
+   Release {
+      #[arg(required = true)]
+      bug_ref: Option<SmolStr>,
+
+      #[command(subcommand)]
+      action: Option<ReleaseAction>,
+   },
 
    /// Show current work context
-   Context,
+   Context {
+      #[arg(long = "tag", value_delimiter = ',', help = "Only issues with any of these tags")]
+      tags: Vec<SmolStr>,
+
+      #[arg(long, help = "Only issues in this milestone")]
+      milestone: Option<SmolStr>,
 
-   /// Show top priority tasks
-   Focus,
+      #[arg(long, help = "Show at most this many issues per section (default: 5 ready to start)")]
+      max_items: Option<usize>,
+   },
+
+   /// Show top priority tasks, or time work on one with --pomodoro
+   Focus {
+      #[arg(long, help = "Run a pomodoro timer on the issue in progress")]
+      pomodoro: bool,
+
+      #[arg(long, requires = "pomodoro", help = "Issue to time instead of the one started last")]
+      issue: Option<SmolStr>,
+
+      #[arg(
+         long,
+         requires = "pomodoro",
+         default_value = "25m",
+         help = "Length of a work interval"
+      )]
+      work: SmolStr,
+
+      #[arg(
+         long = "break",
+         requires = "pomodoro",
+         default_value = "5m",
+         help = "Length of a break"
+      )]
+      rest: SmolStr,
+
+      #[arg(long, requires = "pomodoro", default_value_t = 1, help = "Work intervals to run")]
+      rounds: u32,
+   },
+
+   /// Print the path of an issue's markdown file
+   Path { bug_ref: SmolStr },
+
+   /// Show how an issue changed over time, from git history
+   Diff {
+      bug_ref: SmolStr,
+
+      #[arg(long, help = "Only changes after a date (YYYY-MM-DD), an age (7d) or a git revision")]
+      since: Option<SmolStr>,
+   },
+
+   /// Show who changed which issues and how, from the audit trail
+   Audit {
+      #[arg(long, help = "Only changes by this actor ($AGENTX_ACTOR, MCP client or login name)")]
+      actor: Option<SmolStr>,
+
+      #[arg(long, help = "Only changes to this issue")]
+      issue: Option<SmolStr>,
+
+      #[arg(long, help = "Only changes after a date (YYYY-MM-DD), an age (7d) or a git revision")]
+      since: Option<SmolStr>,
+
+      #[arg(long, help = "Only changes before a date (YYYY-MM-DD), an age (7d) or a git revision")]
+      until: Option<SmolStr>,
+
+      #[arg(long, default_value = "50", help = "Show at most this many of the latest changes")]
+      limit: usize,
+   },
+
+   /// Explain where an issue ranks in focus and why
+   Why { bug_ref: SmolStr },
 
    /// Show blocked tasks
-   Blocked,
+   Blocked {
+      #[arg(long, help = "Group blocked reasons and report time lost per recurring blocker")]
+      analyze: bool,
+   },
 
    /// Show tasks ready to start
    Ready,
 
-   /// Import multiple issues from YAML
+   /// List open issues touching a file or directory
+   ForFile {
+      #[arg(help = "Path to look up (read from stdin when omitted)")]
+      path: Option<SmolStr>,
+   },
+
+   /// Maintain the file paths recorded on issues
+   Files {
+      #[command(subcommand)]
+      action: FilesAction,
+   },
+
+   /// Import multiple issues from YAML, or with `url`, one GitHub issue
+   #[command(args_conflicts_with_subcommands = true)]
    Import {
       #[arg(long)]
       file: Option<SmolStr>,
+
+      #[arg(long, help = "Check the file and list what would be created, without creating it")]
+      dry_run: bool,
+
+      #[command(subcommand)]
+      action: Option<ImportAction>,
+   },
+
+   /// Run newline-delimited JSON commands from a file or stdin (`-`) as one
+   /// transaction, printing a JSON result per line
+   Batch {
+      #[arg(value_name = "FILE", default_value = "-")]
+      source: SmolStr,
+   },
+
+   /// Inspect the status workflow the `transitions` config allows
+   Workflow {
+      #[command(subcommand)]
+      action: WorkflowAction,
    },
 
    /// Manage bug aliases
@@ -130,13 +458,74 @@ pub enum Command {
       action: AliasAction,
    },
 
+   /// Get notified when an issue changes status or gets a checkpoint (lists
+   /// watched issues if no ref is given)
+   Watch {
+      bug_ref: Option<SmolStr>,
+
+      #[arg(long, help = "Stop watching the issue", requires = "bug_ref")]
+      remove: bool,
+   },
+
    /// Show agent usage guide
    Guide,
 
-   /// Show quick wins (low-effort tasks)
+   /// Check for alias collisions, stale reverse dependency links and other
+   /// problems in the issue store
+   Doctor {
+      #[arg(long, help = "Rebuild blocks lists from depends_on")]
+      fix: bool,
+   },
+
+   /// Check open issues against the `lint` rules; exits 1 when anything is
+   /// found, for CI
+   Lint,
+
+   /// Fail CI when too many issues match a query, e.g. critical issues still
+   /// open; exits 1 and lists them
+   Gate {
+      #[arg(
+         long = "fail-on",
+         value_name = "QUERY",
+         help = "Query expression for issues that fail the gate (e.g. \"priority:critical \
+                 status:open\")"
+      )]
+      fail_on: String,
+
+      #[arg(long, default_value = "0", help = "Matching issues allowed before the gate fails")]
+      max: usize,
+   },
+
+   /// Encrypt issue bodies at rest
+   Crypt {
+      #[command(subcommand)]
+      action: CryptAction,
+   },
+
+   /// Show quick wins (low-effort tasks not waiting on open dependencies)
    QuickWins {
       #[arg(long, default_value = "1h")]
       threshold: SmolStr,
+
+      #[arg(long, help = "Only issues not started yet")]
+      only_ready: bool,
+
+      #[arg(
+         long,
+         default_value = "impact",
+         help = "Order: impact (priority, boosted by issues unblocked), effort or age"
+      )]
+      sort: SmolStr,
+   },
+
+   /// Scan for likely duplicate issues
+   #[command(alias = "dupes")]
+   Duplicates {
+      #[arg(long, default_value = "open", help = "Which issues to scan: open, closed, all")]
+      status: SmolStr,
+
+      #[arg(long, help = "Minimum similarity 0.0-1.0 (default: similarity_threshold from config)")]
+      threshold: Option<f64>,
    },
 
    /// Start multiple issues at once
@@ -148,6 +537,41 @@ pub enum Command {
 
       #[arg(short, long)]
       message: Option<SmolStr>,
+
+      #[arg(long, help = "Unblock blocked issues that were waiting on the closed ones")]
+      auto_unblock: bool,
+   },
+
+   /// Edit priority or tags on multiple issues at once
+   BulkEdit {
+      bug_refs: Vec<SmolStr>,
+
+      #[arg(long = "where", help = "Select open issues with a query expression")]
+      where_clause: Option<SmolStr>,
+
+      #[arg(long)]
+      priority: Option<SmolStr>,
+
+      #[arg(long = "add-tag", value_delimiter = ',')]
+      add_tags: Vec<SmolStr>,
+
+      #[arg(long = "remove-tag", value_delimiter = ',')]
+      remove_tags: Vec<SmolStr>,
+
+      #[arg(long, help = "Set the milestone (empty to clear)")]
+      milestone: Option<SmolStr>,
+
+      #[arg(long, help = "Set the release the issues target, e.g. v1.3 (empty to clear)")]
+      target_version: Option<SmolStr>,
+
+      #[arg(
+         long,
+         help = "Set the due time: a date, RFC 3339 time or duration like 3d (empty to clear)"
+      )]
+      due: Option<SmolStr>,
+
+      #[arg(long, help = "Set who the issues are assigned to (empty to clear)")]
+      assignee: Option<SmolStr>,
    },
 
    /// Show session summary (what changed recently)
@@ -156,6 +580,12 @@ pub enum Command {
       hours: Option<u64>,
    },
 
+   /// Time a work session and summarize what moved during it
+   Session {
+      #[command(subcommand)]
+      action: SessionAction,
+   },
+
    /// Show issue dependencies (what it depends on, what depends on it)
    Dependencies { bug_ref: SmolStr },
 
@@ -184,19 +614,176 @@ pub enum Command {
       list: bool,
    },
 
+   /// Tag analytics and suggestions
+   Tags {
+      #[command(subcommand)]
+      action: TagsAction,
+   },
+
    /// Find longest dependency chain (critical path)
    CriticalPath,
 
+   /// List dependency cycles and the weakest edge in each; --fix removes
+   /// them
+   Cycles {
+      #[arg(
+         long,
+         help = "Drop an edge from each cycle, asking which at a terminal (the suggested ones \
+                 with --yes)"
+      )]
+      fix: bool,
+   },
+
    /// Visualize dependency graph as ASCII art
    DepsGraph {
       #[arg(long, help = "Show only this issue and its dependencies")]
       issue: Option<SmolStr>,
+
+      #[arg(long, help = "With --issue, stop this many dependency steps from it")]
+      depth: Option<usize>,
+
+      #[arg(long, help = "Group issues into clusters by tag or milestone")]
+      cluster: Option<SmolStr>,
+
+      #[arg(
+         long,
+         help = "Output format: ascii, mermaid, dot, svg (default: from --out's extension, else \
+                 ascii)"
+      )]
+      format: Option<SmolStr>,
+
+      #[arg(long, help = "Write the graph to a file instead of stdout")]
+      out: Option<SmolStr>,
    },
 
    /// Show performance metrics
    Metrics {
       #[arg(long, default_value = "week", help = "Time period: day, week, month, all")]
+      period:      SmolStr,
+      #[arg(long, help = "Break down open, closed, close time and blocked time per tag")]
+      by_tag:      bool,
+      #[arg(long, help = "Break down open, closed, close time and blocked time per assignee")]
+      by_assignee: bool,
+      #[arg(long, help = "Break down open, closed, close time and blocked time per kind")]
+      by_kind:     bool,
+   },
+
+   /// Print status columns side by side as plain-text cards, for narrow
+   /// terminals and CI logs
+   Board {
+      #[arg(
+         long,
+         help = "Columns to show, e.g. open,active,blocked (default: open,active,blocked,done)"
+      )]
+      columns: Option<SmolStr>,
+
+      #[arg(long, help = "Redraw the board as issues change, until interrupted")]
+      watch: bool,
+   },
+
+   /// Print an execution order of open issues, in batches that can run in
+   /// parallel
+   Order {
+      #[arg(long, help = "Only this milestone's issues (and what they depend on)")]
+      milestone: Option<SmolStr>,
+
+      #[arg(long, value_name = "FORMAT", help = "Export the batches as a mermaid or dot graph")]
+      export: Option<SmolStr>,
+   },
+
+   /// Propose a sprint: ready issues by priority, in dependency order, up to a
+   /// capacity
+   Plan {
+      #[arg(long, help = "Effort budget, e.g. 30h or 5d")]
+      capacity: SmolStr,
+
+      #[arg(long, help = "Only plan issues in this milestone")]
+      milestone: Option<SmolStr>,
+
+      #[arg(long, help = "Tag the planned issues with the next sprint-N tag")]
+      tag: bool,
+   },
+
+   /// Summarize new, closed, blocked and stale issues for a period (for
+   /// cron/CI)
+   Digest {
+      #[arg(long, default_value = "week", help = "Time period: day, week, month")]
       period: SmolStr,
+
+      #[arg(long, help = "Write the digest to a file instead of stdout")]
+      out: Option<SmolStr>,
+
+      #[arg(long, default_value = "14", help = "Days without activity before an issue is stale")]
+      stale_days: i64,
+   },
+
+   /// Print yesterday's progress, today's plan and blockers for a standup
+   Standup {
+      #[arg(long, help = "Look back this long, e.g. 24h (default: since the last working day)")]
+      since: Option<SmolStr>,
+
+      #[arg(long, default_value = "3", help = "Ready issues to plan after those in progress")]
+      ready: usize,
+
+      #[arg(long, default_value = "text", help = "Output format: text, markdown")]
+      format: SmolStr,
+   },
+
+   /// Raise desktop notifications for issues due soon, blocked too long or
+   /// changed while watched (for cron)
+   Notify {
+      #[arg(long, help = "Keep running, checking on this interval (e.g. 15m)")]
+      every: Option<SmolStr>,
+
+      #[arg(long, help = "Only print the alerts")]
+      no_desktop: bool,
+   },
+
+   /// Compare effort estimates with actual time in progress, per size and
+   /// tag
+   Calibration,
+
+   /// Export the issues `list` would show as JSON, YAML, markdown or CSV
+   Export {
+      #[arg(long, help = "json (default), yaml, markdown or csv; inferred from --out's extension")]
+      format: Option<SmolStr>,
+
+      #[arg(long, help = "Write the export to a file instead of stdout")]
+      out: Option<SmolStr>,
+
+      #[arg(long, default_value = "open")]
+      status: SmolStr,
+
+      #[arg(
+         long = "where",
+         help = "Filter with a query expression (e.g. 'priority>=high tag:api')"
+      )]
+      where_clause: Option<SmolStr>,
+
+      #[arg(
+         long,
+         visible_alias = "include-archived",
+         help = "Also export closed issues (closed issues are the archive)"
+      )]
+      include_closed: bool,
+
+      #[arg(long, help = "Only issues created on or after a date (YYYY-MM-DD or 30d)")]
+      created_since: Option<SmolStr>,
+
+      #[arg(long, help = "Only issues closed on or after a date; implies --include-closed")]
+      closed_since: Option<SmolStr>,
+   },
+
+   /// Forecast completion dates from recent velocity
+   Forecast {
+      #[arg(long, conflicts_with = "chain", help = "Only issues in this milestone")]
+      milestone: Option<SmolStr>,
+
+      #[arg(long, help = "Only this issue and its open dependencies")]
+      chain: Option<SmolStr>,
+
+      #[arg(long, default_value = "8", help = "Weeks of close history to measure velocity over")]
+      weeks: u32,
    },
 
    /// Generate shell completions
@@ -205,14 +792,53 @@ pub enum Command {
       shell: SmolStr,
    },
 
-   /// Initialize config file
-   Init {
-      #[arg(long, help = "Create in home directory instead of current directory")]
-      global: bool,
+   /// Complete the last of `words` for the scripts from `completions`
+   #[command(name = "__complete", hide = true)]
+   Complete {
+      shell: SmolStr,
+
+      #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+      words: Vec<String>,
    },
 
-   /// Start MCP server on stdio
-   Serve,
+   /// Initialize config file (the user-level one with --global)
+   Init,
+
+   /// Start MCP server on stdio, or the web dashboard with --web
+   Serve {
+      #[arg(long, help = "Serve the web dashboard instead (needs the `web` feature)")]
+      web: bool,
+
+      #[arg(
+         long,
+         requires = "web",
+         default_value = "127.0.0.1:7878",
+         help = "Address to listen on"
+      )]
+      addr: String,
+
+      #[arg(
+         long,
+         requires = "web",
+         help = "Token allowing changes from the dashboard (or AGENTX_WEB_TOKEN); read-only \
+                 without"
+      )]
+      token: Option<String>,
+   },
+
+   /// Print a compact status segment for shell prompts
+   Prompt {
+      #[arg(long, value_name = "SHELL", help = "Print setup for zsh, bash, fish or starship")]
+      init: Option<SmolStr>,
+   },
+
+   /// Keep issues in memory and serve them to other agentx invocations
+   Daemon {
+      #[arg(long, help = "Stop the running daemon")]
+      stop:   bool,
+      #[arg(long, help = "Show whether a daemon is running", conflicts_with = "stop")]
+      status: bool,
+   },
 
    /// Launch interactive TUI dashboard
    #[command(alias = "dash")]
@@ -225,6 +851,71 @@ pub enum Command {
    },
 }
 
+impl Command {
+   /// Whether the command changes issues or tracker state, and so is refused
+   /// in read-only mode
+   pub fn is_mutating(&self) -> bool {
+      match self {
+         Self::New { .. }
+         | Self::Start { .. }
+         | Self::Block { .. }
+         | Self::Close { .. }
+         | Self::Open { .. }
+         | Self::Defer { .. }
+         | Self::Activate { .. }
+         | Self::Checkpoint { .. }
+         | Self::Compact { .. }
+         | Self::Batch { .. }
+         | Self::BulkStart { .. }
+         | Self::BulkClose { .. }
+         | Self::BulkEdit { .. }
+         | Self::Depend { .. }
+         | Self::Crypt { .. }
+         | Self::Claim { .. }
+         | Self::Init { .. } => true,
+         Self::Show { open, .. } => *open,
+         Self::Alias { action } => !matches!(action, AliasAction::List),
+         Self::Watch { bug_ref, .. } => bug_ref.is_some(),
+         Self::Session { action } => !matches!(action, SessionAction::Status),
+         Self::Tag { add, remove, .. } => !add.is_empty() || !remove.is_empty(),
+         Self::Plan { tag, .. } => *tag,
+         Self::Cycles { fix, .. } => *fix,
+         Self::Focus { pomodoro, .. } => *pomodoro,
+         Self::Doctor { fix } => *fix,
+         Self::Files { action: FilesAction::Remap { dry_run, .. } } => !dry_run,
+         Self::Import { dry_run, .. } => !dry_run,
+         Self::Release { action, .. } => action.is_none(),
+         Self::Bundle { action, .. } => action.is_some(),
+         _ => false,
+      }
+   }
+}
+
+#[derive(Subcommand)]
+pub enum WorkflowAction {
+   /// Draw the status graph, with the issues in each status and their
+   /// average stay
+   Show {
+      #[arg(long, default_value = "ascii", help = "Output format: ascii, mermaid")]
+      format: SmolStr,
+   },
+}
+
+#[derive(Subcommand)]
+pub enum FilesAction {
+   /// Rewrite paths under OLD to NEW across all issues, after a rename
+   Remap {
+      #[arg(help = "Renamed file or directory, e.g. src/ui/")]
+      old: SmolStr,
+
+      #[arg(help = "Its new path, e.g. src/tui/")]
+      new: SmolStr,
+
+      #[arg(long, help = "Show what would change without saving")]
+      dry_run: bool,
+   },
+}
+
 #[derive(Subcommand)]
 pub enum AliasAction {
    /// List all aliases
@@ -235,4 +926,81 @@ pub enum AliasAction {
 
    /// Remove an alias
    Remove { alias: SmolStr },
+
+   /// Rename an alias
+   Rename { old: SmolStr, new: SmolStr },
+}
+
+#[derive(Subcommand)]
+pub enum CryptAction {
+   /// Create a key for this issue store (kept outside the repository)
+   Init,
+
+   /// Encrypt every issue body; frontmatter stays readable
+   Lock,
+
+   /// Decrypt every issue body
+   Unlock,
+}
+
+#[derive(Subcommand)]
+pub enum SessionAction {
+   /// Start a work session
+   Start {
+      /// What the session is for
+      note: Option<String>,
+   },
+
+   /// Show what moved so far in the running session
+   Status,
+
+   /// End the session and write its summary to the journal
+   End {
+      #[arg(long, help = "Also add the summary to each issue the session touched")]
+      annotate: bool,
+   },
+}
+
+#[derive(Subcommand)]
+pub enum BundleAction {
+   /// Add a bundled issue, relinking its relations to issues here with the
+   /// same source URL or title
+   Import { file: SmolStr },
+}
+
+#[derive(Subcommand)]
+pub enum ImportAction {
+   /// Import a GitHub issue by its URL; set GITHUB_TOKEN (or GH_TOKEN) for
+   /// private repositories
+   Url { url: SmolStr },
+}
+
+#[derive(Subcommand)]
+pub enum ReleaseAction {
+   /// Open and closed issues for a version, with the effort left
+   Status { version: SmolStr },
+
+   /// Check that no critical issue for a version is open and write its
+   /// changelog section; exits 1 if one is
+   Cut {
+      version: SmolStr,
+
+      #[arg(long, help = "Add the section to this changelog file instead of printing it")]
+      changelog: Option<SmolStr>,
+   },
+}
+
+#[derive(Subcommand)]
+pub enum TagsAction {
+   /// Show per-tag counts, open vs closed, and average time to close
+   Stats,
+
+   /// Suggest tags for a title and file list based on tagging history
+   Suggest {
+      #[arg(long)]
+      title: SmolStr,
+
+      #[arg(long = "file")]
+      files: Vec<SmolStr>,
+   },
 }
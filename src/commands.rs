@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Duration, Utc};
@@ -8,97 +8,253 @@ use serde_json::json;
 use smol_str::SmolStr;
 
 use crate::{
+   alias,
+   api::{
+      BulkError, CancelToken, CompactResult, ContextScope, CreateIssueResult, CyclesResult,
+      DependResult, GraphNode, IssueTracker, LinkedIssue, ListScope, RefMode, UnblockCandidate,
+      UnblockedIssue, WinsSort,
+   },
+   audit::Actor,
+   board,
+   bundle::Relation,
+   calibration::MIN_SAMPLES,
+   compact,
    config::Config,
+   cycles::{Cycle, CycleEdge},
+   export::ExportFormat,
+   files,
+   flow::StageStats,
+   forecast::ForecastMethod,
+   gate,
    git::GitOps,
-   issue::{Issue, IssueWithId, Priority, Status},
+   graph::{self, ClusterBy, GraphFormat},
+   history::LineChange,
+   impact::unblocks_note,
+   interactive::{
+      is_interactive_terminal,
+      wizard::{prompt_multi_select, prompt_optional, prompt_select},
+   },
+   issue::{Claim, IssueMetadata, IssueWithId, Priority, Status},
+   listing::{self, GroupBy, ListView},
+   markdown,
+   plan::{DailyBudget, hours},
+   pomodoro::{self, Interval, Phase, Pomodoro},
+   query::Query,
+   release::ReleaseIssue,
+   search::Terms,
    storage::Storage,
-   utils::parse_effort,
+   utils::{
+      copy_to_clipboard, format_hours, normalize_path, open_in_editor, parse_duration, short_age,
+   },
+   workflow,
 };
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct IssueListResult {
-   pub status: String,
-   pub count:  usize,
-   pub issues: Vec<IssueWithId>,
+/// `list --verbose` line with an issue's age and how long since it changed
+fn age_line(meta: &IssueMetadata, now: DateTime<Utc>) -> String {
+   let updated = match short_age(meta.updated_at(), now) {
+      age if age == "now" => "just now".to_string(),
+      age => format!("{age} ago"),
+   };
+   format!("       age {} · updated {updated}", short_age(meta.created, now))
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ContextResult {
-   pub active:         Vec<IssueWithId>,
-   pub blocked:        Vec<IssueWithId>,
-   pub high_priority:  Vec<IssueWithId>,
-   pub ready_to_start: Vec<IssueWithId>,
-   pub total_open:     usize,
+/// `context` and `focus` line weighing today's work against the capacity
+fn print_budget(budget: &DailyBudget) {
+   let unestimated = match budget.unestimated {
+      0 => String::new(),
+      n => format!(", {n} without an estimate"),
+   };
+   println!(
+      "⏱️  Planned {} of {} capacity ({} issues{unestimated})",
+      hours(budget.planned_minutes),
+      hours(budget.capacity_minutes),
+      budget.issues
+   );
+   if budget.overcommitted {
+      println!(
+         "⚠️  Overcommitted by {}: defer or hand off some of it",
+         hours(budget.planned_minutes - budget.capacity_minutes)
+      );
+   }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ShowResult {
-   pub num:            u32,
-   pub title:          String,
-   pub priority:       String,
-   pub status:         String,
-   pub body:           String,
-   pub tags:           Vec<String>,
-   pub files:          Vec<String>,
-   pub effort:         Option<String>,
-   pub created:        DateTime<Utc>,
-   pub started:        Option<DateTime<Utc>>,
-   pub closed:         Option<DateTime<Utc>>,
-   pub blocked_reason: Option<String>,
+/// Worktree changes offered for an issue's `files`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChangedFiles {
+   pub added:     Vec<String>,
+   /// Changed files left off the issue
+   pub suggested: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CreateIssueResult {
-   pub bug_num: u32,
-   pub title:   String,
-   pub path:    String,
+/// A status change made along with a checkpoint's note
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckpointMark {
+   /// Block the issue for this reason
+   Blocked(String),
+   /// Mark the issue done
+   Done,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StatusUpdateResult {
-   pub bug_num: u32,
-   pub status:  String,
-   pub message: Option<String>,
+impl CheckpointMark {
+   /// The change asked for by a `BLOCKED:`, `DONE:` or `FIXED:` prefix on
+   /// a note
+   pub fn from_note(note: &str) -> Option<Self> {
+      let upper = note.to_uppercase();
+      if upper.starts_with("BLOCKED:") {
+         let reason = note
+            .strip_prefix("BLOCKED:")
+            .or_else(|| note.strip_prefix("blocked:"))
+            .unwrap_or(note)
+            .trim()
+            .to_string();
+         Some(Self::Blocked(reason))
+      } else if upper.starts_with("FIXED:") || upper.starts_with("DONE:") {
+         Some(Self::Done)
+      } else {
+         None
+      }
+   }
 }
 
+/// The CLI's commands: each runs an [`IssueTracker`] operation and prints
+/// what it returns
 #[derive(Debug, Clone)]
 pub struct Commands {
-   storage: Storage,
-   config:  Config,
+   tracker: IssueTracker,
 }
 
 impl Commands {
    pub fn new(storage: Storage) -> Self {
-      Self { storage, config: Config::load() }
+      Self { tracker: IssueTracker::new(storage).with_config(Config::load()) }
+   }
+
+   /// The operations behind the commands, on the same storage and settings
+   pub fn tracker(&self) -> &IssueTracker {
+      &self.tracker
+   }
+
+   /// Stop imports and batches between items once `cancel` is cancelled
+   pub fn with_cancel(mut self, cancel: CancelToken) -> Self {
+      self.tracker = self.tracker.with_cancel(cancel);
+      self
+   }
+
+   /// Act as `agent` for claims, instead of `$AGENTX_AGENT` or the git user
+   pub fn with_agent(mut self, agent: Option<&str>) -> Self {
+      self.tracker = self.tracker.with_agent(agent);
+      self
+   }
+
+   /// Credit the changes made through these commands to `actor` in the
+   /// audit trail
+   pub fn with_actor(mut self, actor: Actor) -> Self {
+      self.tracker = self.tracker.with_actor(actor);
+      self
+   }
+
+   /// Allow status changes the transition rules would refuse
+   pub fn with_force(mut self, force: bool) -> Self {
+      self.tracker = self.tracker.with_force(force);
+      self
+   }
+
+   /// Apply bulk changes without asking, however many issues they touch
+   pub fn with_yes(mut self, yes: bool) -> Self {
+      self.tracker = self.tracker.with_yes(yes);
+      self
+   }
+
+   pub fn with_ref_mode(mut self, ref_mode: RefMode) -> Self {
+      self.tracker = self.tracker.with_ref_mode(ref_mode);
+      self
+   }
+
+   pub fn with_config(mut self, config: Config) -> Self {
+      self.tracker = self.tracker.with_config(config);
+      self
    }
 
    pub fn config(&self) -> &Config {
-      &self.config
+      self.tracker.config()
    }
 
-   pub fn list_data(&self, status: &str) -> Result<IssueListResult> {
-      let issues = match status {
-         "open" => self.storage.list_open_issues()?,
-         "closed" => self.storage.list_closed_issues()?,
-         _ => anyhow::bail!("Invalid status: {status}"),
-      };
+   pub fn storage(&self) -> &Storage {
+      self.tracker.storage()
+   }
+
+   pub fn search(
+      &self,
+      query: &str,
+      status: &str,
+      scope: &ListScope,
+      limit: Option<usize>,
+      json: bool,
+   ) -> Result<()> {
+      let result = self.tracker.search_data(query, status, scope, limit)?;
+      if json {
+         println!("{}", serde_json::to_string_pretty(&result)?);
+         return Ok(());
+      }
+      if result.results.is_empty() {
+         println!("No issues match \"{query}\"");
+         return Ok(());
+      }
 
-      Ok(IssueListResult {
-         status: status.to_string(),
-         count:  issues.len(),
-         issues,
-      })
+      let terms = Terms::parse(query);
+      let mark = |m: &str| m.bold().yellow().to_string();
+      for hit in &result.results {
+         println!(
+            "{} {}: {} {}",
+            if hit.status == "closed" {
+               "🗑️"
+            } else {
+               "•"
+            },
+            self.config().format_issue_ref(hit.num).bold(),
+            terms.highlight(&hit.title, mark),
+            format!("[{}, {}]", hit.priority, hit.status).dimmed()
+         );
+         if !hit.tags.is_empty() {
+            let tags: Vec<String> = hit
+               .tags
+               .iter()
+               .map(|t| terms.highlight(&format!("#{t}"), mark))
+               .collect();
+            println!("    {}", tags.join(" "));
+         }
+         if let Some(snippet) = &hit.snippet {
+            println!("    {}", terms.highlight(snippet, mark));
+         }
+      }
+      println!("\n{} match(es)", result.count);
+      Ok(())
    }
 
-   pub fn list(&self, status: &str, verbose: bool, json: bool) -> Result<()> {
-      let result = self.list_data(status)?;
+   pub fn list(
+      &self,
+      status: &str,
+      verbose: bool,
+      where_clause: Option<&str>,
+      scope: &ListScope,
+      view: &ListView,
+      json: bool,
+   ) -> Result<()> {
+      let filter = scope.filter(where_clause)?;
+      let status = scope.status(status);
+      let mut result = self.tracker.list_data(status, filter.as_ref())?;
+      if let Some(lang) = &view.lang {
+         for issue_with_id in &mut result.issues {
+            issue_with_id.issue = issue_with_id.issue.localized(lang);
+         }
+      }
+      if let Some(key) = view.sort {
+         listing::sort(&mut result.issues, key, view.descending);
+      }
+      let grouping = view.group_by.filter(|&by| by != GroupBy::Priority || json);
 
       if json {
-         let data: Vec<_> = result
-            .issues
-            .iter()
-            .map(|issue_with_id| {
-               json!({
+         let entry = |issue_with_id: &IssueWithId| {
+            json!({
                    "num": issue_with_id.id,
                    "title": issue_with_id.issue.metadata.title,
                    "priority": issue_with_id.issue.metadata.priority.to_string(),
@@ -107,19 +263,44 @@ impl Commands {
                    "effort": issue_with_id.issue.metadata.effort,
                    "blocked_reason": issue_with_id.issue.metadata.blocked_reason,
                    "tags": issue_with_id.issue.metadata.tags,
-               })
+                   "created": issue_with_id.issue.metadata.created,
+                   "last_updated": issue_with_id.issue.metadata.updated_at(),
             })
-            .collect();
+         };
+         let data = match grouping.filter(|&by| by != GroupBy::None) {
+            Some(by) => {
+               let groups: Vec<_> = listing::group(&result.issues, by, Utc::now())
+                  .into_iter()
+                  .map(|group| {
+                     json!({
+                         "name": group.name,
+                         "count": group.issues.len(),
+                         "issues": group.issues.into_iter().map(entry).collect::<Vec<_>>(),
+                     })
+                  })
+                  .collect();
+               json!({ "group_by": by.name(), "groups": groups })
+            },
+            None => json!(result.issues.iter().map(entry).collect::<Vec<_>>()),
+         };
          println!("{}", serde_json::to_string_pretty(&data)?);
          return Ok(());
       }
 
       if result.issues.is_empty() {
-         println!("No {} issues found", result.status);
+         match &filter {
+            Some(query) => println!("No {} issues match: {query}", result.status),
+            None => println!("No {} issues found", result.status),
+         }
          return Ok(());
       }
 
-      let use_colors = self.config.colored_output;
+      let use_colors = self.config().colored_output;
+      let now = Utc::now();
+      if let Some(by) = grouping {
+         self.print_grouped(status, &result.issues, by, verbose);
+         return Ok(());
+      }
 
       // Separate backlog from active issues
       let (active_issues, backlog_issues): (Vec<_>, Vec<_>) = result
@@ -186,7 +367,7 @@ impl Commands {
             let line = format!(
                "  {} {}: {}{}",
                marker,
-               self.config.format_issue_ref(issue_with_id.id),
+               self.config().format_issue_ref(issue_with_id.id),
                issue_with_id.issue.metadata.title,
                tags_str
             );
@@ -214,7 +395,8 @@ impl Commands {
                }
             }
 
-            if verbose && !issue_with_id.issue.metadata.files.is_empty() {
+            if verbose {
+               println!("{}", age_line(&issue_with_id.issue.metadata, now));
                for file in &issue_with_id.issue.metadata.files {
                   println!("       → {file}");
                }
@@ -263,7 +445,7 @@ impl Commands {
                   let line = format!(
                      "  {} {}: {}{}",
                      marker,
-                     self.config.format_issue_ref(issue_with_id.id),
+                     self.config().format_issue_ref(issue_with_id.id),
                      issue_with_id.issue.metadata.title,
                      tags_str
                   );
@@ -274,6 +456,14 @@ impl Commands {
                      println!("{}", line);
                   }
 
+                  if verbose {
+                     let age = age_line(&issue_with_id.issue.metadata, now);
+                     if use_colors {
+                        println!("{}", age.dimmed());
+                     } else {
+                        println!("{age}");
+                     }
+                  }
                   if verbose && !issue_with_id.issue.metadata.files.is_empty() {
                      for file in &issue_with_id.issue.metadata.files {
                         let file_line = format!("       → {file}");
@@ -293,45 +483,132 @@ impl Commands {
       Ok(())
    }
 
-   pub fn show_data(&self, bug_ref: &str) -> Result<ShowResult> {
-      let bug_num = self.storage.resolve_bug_ref(bug_ref)?;
-      let issue = self.storage.load_issue(bug_num)?;
+   /// `list` grouped by something other than priority: one section per
+   /// group, each line carrying the priority instead
+   fn print_grouped(&self, status: &str, issues: &[IssueWithId], by: GroupBy, verbose: bool) {
+      let use_colors = self.config().colored_output;
+      let now = Utc::now();
+      println!("\n{}", "=".repeat(80));
+      println!("{} ISSUES ({})", status.to_uppercase(), issues.len());
+      println!("{}\n", "=".repeat(80));
+
+      for group in listing::group(issues, by, Utc::now()) {
+         if by != GroupBy::None {
+            let header = format!("{} ({})", group.name.to_uppercase(), group.issues.len());
+            if use_colors {
+               println!("{}", header.bold());
+            } else {
+               println!("{header}");
+            }
+            println!("{}", "-".repeat(80));
+         }
+
+         for issue_with_id in group.issues {
+            let meta = &issue_with_id.issue.metadata;
+            let tags: String = meta.tags.iter().map(|t| format!(" #{t}")).collect();
+            let line = format!(
+               "  {} {:10} {}: {}{tags}",
+               meta.status.marker(),
+               format!("[{}]", meta.priority.to_string().to_uppercase()),
+               self.config().format_issue_ref(issue_with_id.id),
+               meta.title
+            );
+            if !use_colors {
+               println!("{line}");
+            } else if meta.status == Status::Backlog {
+               println!("{}", line.dimmed());
+            } else {
+               println!("{}", match meta.priority {
+                  Priority::Critical => line.red(),
+                  Priority::High => line.yellow(),
+                  Priority::Medium => line.normal(),
+                  Priority::Low => line.bright_black(),
+               });
+            }
+
+            if meta.status == Status::Blocked
+               && let Some(reason) = &meta.blocked_reason
+            {
+               println!("       Blocked: {reason}");
+            }
+            if verbose {
+               println!("{}", age_line(meta, now));
+               for file in &meta.files {
+                  println!("       → {file}");
+               }
+            }
+         }
+         println!();
+      }
+   }
 
-      Ok(ShowResult {
-         num:            bug_num,
-         title:          issue.metadata.title.to_string(),
-         priority:       issue.metadata.priority.to_string(),
-         status:         issue.metadata.status.to_string(),
-         body:           issue.body.clone(),
-         tags:           issue.metadata.tags.iter().map(|s| s.to_string()).collect(),
-         files:          issue.metadata.files.iter().map(|s| s.to_string()).collect(),
-         effort:         issue.metadata.effort.as_ref().map(|s| s.to_string()),
-         created:        issue.metadata.created,
-         started:        issue.metadata.started,
-         closed:         issue.metadata.closed,
-         blocked_reason: issue.metadata.blocked_reason.as_ref().map(|s| s.to_string()),
-      })
+   pub fn show_brief(
+      &self,
+      bug_ref: &str,
+      budget: usize,
+      checkpoints: usize,
+      json: bool,
+   ) -> Result<()> {
+      let brief = self.tracker.brief_data(bug_ref, budget, checkpoints)?;
+      if json {
+         println!("{}", serde_json::to_string_pretty(&brief)?);
+      } else {
+         println!("{}", brief.text);
+      }
+      Ok(())
    }
 
-   pub fn show(&self, bug_ref: &str, json: bool) -> Result<()> {
-      let bug_num = self.storage.resolve_bug_ref(bug_ref)?;
-      let issue = self.storage.load_issue(bug_num)?;
+   /// Print an issue, with its body rendered as markdown unless `raw`
+   pub fn show(&self, bug_ref: &str, raw: bool, lang: Option<&str>, json: bool) -> Result<()> {
+      let bug_num = self.tracker.resolve_ref(bug_ref)?;
+      let mut issue = self.storage().load_issue(bug_num)?;
+      if let Some(lang) = lang {
+         issue = issue.localized(lang);
+      }
 
       if json {
          let output = json!({
              "metadata": issue.metadata,
              "body": issue.body,
+             "path": self.storage().find_issue_file(bug_num)?,
          });
          println!("{}", serde_json::to_string_pretty(&output)?);
-      } else {
+      } else if raw {
          print!("{}", issue.to_mdx());
+      } else {
+         let yaml = serde_yaml::to_string(&issue.metadata)?;
+         println!(
+            "{}",
+            format!("{}: {}", self.config().format_issue_ref(bug_num), issue.metadata.title).bold()
+         );
+         println!("{}", yaml.trim_end().dimmed());
+         println!();
+         print!("{}", markdown::render(&issue.body.to_string(), markdown::terminal_width()));
+
+         let (references, referenced_by) = self.tracker.cross_references(bug_num, &issue)?;
+         for (label, linked) in [("Mentions", references), ("Mentioned by", referenced_by)] {
+            if linked.is_empty() {
+               continue;
+            }
+            println!("\n🔗 {label}:");
+            for other in &linked {
+               println!(
+                  "   {} [{}]: {}",
+                  self.config().format_issue_ref(other.id).cyan(),
+                  other.issue.metadata.status,
+                  other.issue.metadata.title
+               );
+            }
+         }
       }
 
       Ok(())
    }
 
+   /// Create an issue and print it; the result carries the number it was
+   /// given
    #[allow(clippy::too_many_arguments)]
-   pub fn create_issue_data(
+   pub fn create_issue(
       &self,
       title: String,
       priority_str: &str,
@@ -342,114 +619,352 @@ impl Commands {
       acceptance: String,
       effort: Option<String>,
       context: Option<String>,
+      milestone: Option<String>,
+      kind: Option<String>,
+      json: bool,
    ) -> Result<CreateIssueResult> {
-      let priority = match priority_str {
-         "critical" => Priority::Critical,
-         "high" => Priority::High,
-         "medium" => Priority::Medium,
-         "low" => Priority::Low,
-         _ => anyhow::bail!("Invalid priority: {priority_str}"),
-      };
+      let result = self.tracker.create_issue_data(
+         title,
+         priority_str,
+         tags,
+         files,
+         issue,
+         impact,
+         acceptance,
+         effort,
+         context,
+         milestone,
+         kind,
+      )?;
+      let similar = &result.similar_issues[..result.similar_issues.len().min(3)];
+
+      if json {
+         let output = json!({
+             "bug_num": result.bug_num,
+             "title": result.title,
+             "path": result.path,
+             "alias": result.alias,
+             "similar_issues": similar,
+             "suggested_tags": result.suggested_tags,
+             "estimate_hint": result.estimate_hint,
+         });
+         println!("{}", serde_json::to_string_pretty(&output)?);
+      } else {
+         if !similar.is_empty() {
+            eprintln!("\n⚠️  Similar issues found:");
+            for s in similar {
+               eprintln!("   #{}: {} ({:.0}% similar)", s.id, s.title, s.similarity * 100.0);
+            }
+            eprintln!();
+         }
+         println!("✓ Created {} → {}", self.config().format_issue_ref(result.bug_num), result.path);
+         if let Some(alias) = &result.alias {
+            println!("🏷️  Alias: {alias}");
+         }
+         if !result.suggested_tags.is_empty() {
+            let tags: Vec<_> = result
+               .suggested_tags
+               .iter()
+               .map(|s| s.tag.as_str())
+               .collect();
+            println!(
+               "💡 Suggested tags: #{} (agentx tag {} --add {})",
+               tags.join(" #"),
+               result.bug_num,
+               tags.join(",")
+            );
+         }
+         if let Some(hint) = &result.estimate_hint {
+            println!(
+               "💡 Issues like this ({}, {} closed) take {:.1}× their estimate: consider {}",
+               hint.basis, hint.samples, hint.ratio, hint.suggested
+            );
+         }
+      }
 
-      let bug_num = self.storage.next_bug_number()?;
-      let issue_obj =
-         Issue::new(title.clone(), priority, tags, files, issue, impact, acceptance, effort, context);
+      Ok(result)
+   }
 
-      let path = self.storage.save_issue(&issue_obj, bug_num, true)?;
+   /// Add dependencies and an assignee to a just-created issue, as the
+   /// new-issue wizard and `--stdin-form` ask for them
+   pub fn link_new_issue(
+      &self,
+      bug_num: u32,
+      depends_on: Vec<String>,
+      assignee: Option<&str>,
+   ) -> Result<()> {
+      let bug_ref = bug_num.to_string();
+      if !depends_on.is_empty() {
+         self.tracker.depend_data(&bug_ref, depends_on, Vec::new())?;
+      }
+      if let Some(assignee) = assignee {
+         self
+            .storage()
+            .update_issue_metadata(bug_num, |meta| meta.assignee = Some(assignee.into()))?;
+      }
+      Ok(())
+   }
 
-      Ok(CreateIssueResult {
-         bug_num,
-         title,
-         path: path.display().to_string(),
-      })
+   /// Put an issue on the system clipboard, formatted with the configured
+   /// `copy_template`
+   pub fn copy_issue(&self, bug_num: u32) -> Result<()> {
+      let issue = self.storage().load_issue(bug_num)?;
+      let text = self.config().format_copy(bug_num, &issue.metadata.title);
+      copy_to_clipboard(&text)?;
+      eprintln!("📋 Copied: {text}");
+      Ok(())
    }
 
-   #[allow(clippy::too_many_arguments)]
-   pub fn create_issue(
+   /// Files changed in the worktree that `bug_num` doesn't list yet: all
+   /// added with `auto_files`, picked at a terminal, and left as suggestions
+   /// otherwise
+   fn settle_changed_files(
       &self,
-      title: String,
-      priority_str: &str,
-      tags: Vec<String>,
-      files: Vec<String>,
-      issue: String,
-      impact: String,
-      acceptance: String,
-      effort: Option<String>,
-      context: Option<String>,
+      bug_num: u32,
+      auto_files: bool,
       json: bool,
-   ) -> Result<()> {
-      let priority = match priority_str {
-         "critical" => Priority::Critical,
-         "high" => Priority::High,
-         "medium" => Priority::Medium,
-         "low" => Priority::Low,
-         _ => anyhow::bail!("Invalid priority: {priority_str}"),
+   ) -> Result<ChangedFiles> {
+      let Ok(git) = GitOps::open(".") else {
+         return Ok(ChangedFiles::default());
+      };
+      let (Ok(changed), Some(workdir)) = (git.changed_files(), git.workdir()) else {
+         return Ok(ChangedFiles::default());
       };
+      let workdir = workdir
+         .canonicalize()
+         .unwrap_or_else(|_| workdir.to_path_buf());
+      let issues_dir = self.storage().issues_dir();
+      let issues_dir = issues_dir.canonicalize().unwrap_or(issues_dir);
+      let skip = normalize_path(&issues_dir.to_string_lossy(), Some(&workdir));
+      let issue = self.storage().load_issue(bug_num)?;
+      let mut suggested = files::suggest(&changed, &issue.metadata.files, Some(&skip));
+      if suggested.is_empty() {
+         return Ok(ChangedFiles::default());
+      }
 
-      // Check for similar issues
-      let existing_issues = self.storage.list_open_issues()?;
-      let mut similar = Vec::new();
+      let added: Vec<String> = if auto_files {
+         std::mem::take(&mut suggested)
+      } else if !json && self.tracker.ref_mode() == RefMode::Interactive {
+         let defaults = vec![true; suggested.len()];
+         let picked =
+            prompt_multi_select("Add changed files to the issue?", &suggested, &defaults)?;
+         let added = picked.iter().map(|&i| suggested[i].clone()).collect();
+         suggested.clear();
+         added
+      } else {
+         Vec::new()
+      };
+      if !added.is_empty() {
+         self.storage().update_issue_metadata(bug_num, |meta| {
+            meta.files.extend(added.iter().map(SmolStr::from));
+         })?;
+      }
+      Ok(ChangedFiles { added, suggested })
+   }
 
-      for existing in &existing_issues {
-         let similarity = strsim::jaro_winkler(
-            &title.to_lowercase(),
-            &existing.issue.metadata.title.to_lowercase(),
+   fn print_changed_files(&self, bug_num: u32, changed: &ChangedFiles) {
+      if !changed.added.is_empty() {
+         println!(
+            "📎 Added {} changed files to {}",
+            changed.added.len(),
+            self.config().format_issue_ref(bug_num)
          );
-         if similarity > 0.8 {
-            similar.push((existing.id, &existing.issue.metadata.title, similarity));
+      }
+      if !changed.suggested.is_empty() {
+         println!("💡 Changed files not on the issue (use --auto-files):");
+         for path in &changed.suggested {
+            println!("   {path}");
          }
       }
+   }
 
-      // Sort by similarity descending
-      similar.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap());
+   /// Unblock `candidates` when asked to, offer to at a terminal, and list
+   /// the ones left blocked otherwise
+   fn settle_unblock_candidates(
+      &self,
+      candidates: &mut [UnblockCandidate],
+      auto_unblock: bool,
+      json: bool,
+   ) -> Result<()> {
+      if candidates.is_empty() {
+         return Ok(());
+      }
+      if auto_unblock {
+         return self.tracker.unblock_all(candidates);
+      }
+      if json {
+         return Ok(());
+      }
 
-      if !similar.is_empty() && !json {
-         eprintln!("\n⚠️  Similar issues found:");
-         for (id, sim_title, score) in similar.iter().take(3) {
-            eprintln!("   #{}: {} ({:.0}% similar)", id, sim_title, score * 100.0);
+      let items: Vec<String> = candidates
+         .iter()
+         .map(|c| {
+            let reason = c.reason.as_deref().unwrap_or("no reason given");
+            format!("{} {} ({reason})", self.config().format_issue_ref(c.bug_num), c.title)
+         })
+         .collect();
+      if self.tracker.ref_mode() == RefMode::Interactive {
+         let defaults = vec![true; items.len()];
+         for i in prompt_multi_select("Unblock these issues?", &items, &defaults)? {
+            self.tracker.unblock(candidates[i].bug_num)?;
+            candidates[i].unblocked = true;
+         }
+      } else {
+         println!("💡 Blocked issues that may be unblocked now (use --auto-unblock):");
+         for item in &items {
+            println!("   {item}");
          }
-         eprintln!();
       }
+      Ok(())
+   }
+
+   fn print_unblock_candidates(&self, candidates: &[UnblockCandidate]) {
+      for candidate in candidates.iter().filter(|c| c.unblocked) {
+         println!(
+            "🔓 Unblocked {}: {}",
+            self.config().format_issue_ref(candidate.bug_num),
+            candidate.title
+         );
+      }
+   }
 
-      let bug_num = self.storage.next_bug_number()?;
-      let issue_obj =
-         Issue::new(title, priority, tags, files, issue, impact, acceptance, effort, context);
+   fn print_unblocked(&self, unblocked: &[UnblockedIssue]) {
+      for issue in unblocked {
+         println!(
+            "🔓 Unblocked {}: {}{}",
+            self.config().format_issue_ref(issue.bug_num),
+            issue.title,
+            if issue.activated {
+               " (activated from backlog)"
+            } else {
+               ""
+            }
+         );
+      }
+   }
 
-      let path = self.storage.save_issue(&issue_obj, bug_num, true)?;
+   /// Print the path of an issue's markdown file
+   pub fn path(&self, bug_ref: &str, json: bool) -> Result<()> {
+      let (bug_num, path) = self.tracker.path_data(bug_ref)?;
 
       if json {
          let output = json!({
              "bug_num": bug_num,
-             "path": path.display().to_string(),
-             "similar_issues": similar.iter().take(3).map(|(id, title, score)| {
-                 json!({
-                     "id": id,
-                     "title": title,
-                     "similarity": score,
-                 })
-             }).collect::<Vec<_>>(),
+             "path": path,
          });
          println!("{}", serde_json::to_string_pretty(&output)?);
       } else {
-         println!("✓ Created {} → {}", self.config.format_issue_ref(bug_num), path.display());
+         println!("{}", path.display());
       }
 
       Ok(())
    }
 
-   pub fn start_data(&self, bug_ref: &str) -> Result<StatusUpdateResult> {
-      let bug_num = self.storage.resolve_bug_ref(bug_ref)?;
+   pub fn diff(&self, bug_ref: &str, since: Option<&str>, json: bool) -> Result<()> {
+      let result = self.tracker.diff_data(bug_ref, since)?;
 
-      self.storage.update_issue_metadata(bug_num, |meta| {
-         meta.status = Status::InProgress;
-         meta.started = Some(Utc::now());
-      })?;
+      if json {
+         println!("{}", serde_json::to_string_pretty(&result)?);
+         return Ok(());
+      }
+
+      println!(
+         "{}",
+         format!("{}: {}", self.config().format_issue_ref(result.bug_num), result.title).bold()
+      );
+      if result.revisions.is_empty() {
+         println!("\nNo changes in that range");
+         return Ok(());
+      }
+
+      for revision in &result.revisions {
+         println!();
+         println!(
+            "{} {}  {}",
+            revision.rev.as_deref().unwrap_or("working").yellow(),
+            revision.time.format("%Y-%m-%d %H:%M").to_string().dimmed(),
+            revision.summary
+         );
+         if revision.created {
+            println!("  {}", "created".green());
+         }
+         for field in &revision.fields {
+            println!(
+               "  {}: {} → {}",
+               field.field.bold(),
+               field.before.as_deref().unwrap_or("(none)").red(),
+               field.after.as_deref().unwrap_or("(none)").green()
+            );
+         }
+         for (i, hunk) in revision.body.iter().enumerate() {
+            if i > 0 {
+               println!("  {}", "⋯".dimmed());
+            }
+            for line in hunk {
+               match line.change {
+                  LineChange::Added => println!("  {}", format!("+ {}", line.text).green()),
+                  LineChange::Removed => println!("  {}", format!("- {}", line.text).red()),
+                  LineChange::Unchanged => println!("  {}", format!("  {}", line.text).dimmed()),
+               }
+            }
+         }
+      }
+
+      Ok(())
+   }
+
+   pub fn audit(
+      &self,
+      actor: Option<&str>,
+      issue: Option<&str>,
+      since: Option<&str>,
+      until: Option<&str>,
+      limit: usize,
+      json: bool,
+   ) -> Result<()> {
+      let entries = self.tracker.audit_data(actor, issue, since, until, limit)?;
+
+      if json {
+         println!("{}", serde_json::to_string_pretty(&entries)?);
+         return Ok(());
+      }
+      if entries.is_empty() {
+         println!("No recorded changes match");
+         return Ok(());
+      }
+
+      let width = entries
+         .iter()
+         .map(|e| e.actor.chars().count())
+         .max()
+         .unwrap_or(0);
+      for entry in &entries {
+         println!(
+            "{}  {}  {} {}",
+            entry
+               .at
+               .with_timezone(&chrono::Local)
+               .format("%Y-%m-%d %H:%M")
+               .to_string()
+               .dimmed(),
+            format!("{:width$}", entry.actor).cyan(),
+            self.config().format_issue_ref(entry.issue).bold(),
+            entry
+               .via
+               .as_deref()
+               .map(|via| format!("({via})").dimmed().to_string())
+               .unwrap_or_default()
+         );
+         for change in &entry.changes {
+            println!("    {change}");
+         }
+      }
+      Ok(())
+   }
 
-      Ok(StatusUpdateResult {
-         bug_num,
-         status:  "in_progress".to_string(),
-         message: None,
-      })
+   /// Open an issue's markdown file in `$VISUAL` / `$EDITOR`
+   pub fn open_issue(&self, bug_num: u32) -> Result<()> {
+      open_in_editor(&self.storage().find_issue_file(bug_num)?)
    }
 
    pub fn start(
@@ -459,13 +974,44 @@ impl Commands {
       no_branch_flag: bool,
       json: bool,
    ) -> Result<()> {
-      let bug_num = self.storage.resolve_bug_ref(bug_ref)?;
-      let issue = self.storage.load_issue(bug_num)?;
+      let bug_num = self.tracker.resolve_ref(bug_ref)?;
+      self
+         .tracker
+         .check_transition(bug_num, "start", Status::InProgress)?;
+      let forced_past = self.tracker.check_dependencies(bug_num)?;
+      let lock = self.storage().lock()?;
+      let claimed = self.tracker.check_claim(bug_num, "start")?;
+      let issue = self.storage().load_issue(bug_num)?;
+
+      if !json {
+         for dep in &forced_past {
+            eprintln!(
+               "⚠️  Still depends on open {}: {}",
+               self.config().format_issue_ref(dep.id),
+               dep.issue.metadata.title
+            );
+         }
+         self.warn_claim(claimed.as_ref());
+      }
 
-      self.storage.update_issue_metadata(bug_num, |meta| {
+      self.storage().update_issue_metadata(bug_num, |meta| {
          meta.status = Status::InProgress;
          meta.started = Some(Utc::now());
       })?;
+      drop(lock);
+
+      let file_conflicts = self.tracker.overlapping_issues(bug_num)?;
+      if !json && !file_conflicts.is_empty() {
+         eprintln!("⚠️  Other issues in progress touch the same files:");
+         for other in &file_conflicts {
+            eprintln!(
+               "   {}: {} ({})",
+               self.config().format_issue_ref(other.num),
+               other.title,
+               other.matched_files.join(", ")
+            );
+         }
+      }
 
       // Determine if we should create a branch
       let should_create_branch = if no_branch_flag {
@@ -473,7 +1019,7 @@ impl Commands {
       } else if branch_flag {
          true
       } else {
-         self.config.git_integration.enabled && self.config.git_integration.auto_branch
+         self.config().git_integration.enabled && self.config().git_integration.auto_branch
       };
 
       let mut branch_created = None;
@@ -483,7 +1029,7 @@ impl Commands {
             Ok(git) => {
                let branch_name = format!(
                   "{}{}",
-                  self.config.git_integration.branch_prefix,
+                  self.config().git_integration.branch_prefix,
                   Storage::slugify(&issue.metadata.title)
                );
 
@@ -511,10 +1057,11 @@ impl Commands {
              "bug_num": bug_num,
              "status": "active",
              "branch_created": branch_created,
+             "file_conflicts": file_conflicts,
          });
          println!("{}", serde_json::to_string_pretty(&output)?);
       } else {
-         println!("🔄 {} marked as IN PROGRESS", self.config.format_issue_ref(bug_num));
+         println!("🔄 {} marked as IN PROGRESS", self.config().format_issue_ref(bug_num));
          if let Some(branch) = branch_created {
             println!("🌿 Created git branch: {}", branch);
          }
@@ -523,171 +1070,112 @@ impl Commands {
       Ok(())
    }
 
-   pub fn block_data(&self, bug_ref: &str, reason: String) -> Result<StatusUpdateResult> {
-      let bug_num = self.storage.resolve_bug_ref(bug_ref)?;
-
-      self.storage.update_issue_metadata(bug_num, |meta| {
-         meta.status = Status::Blocked;
-         meta.blocked_reason = Some(reason.clone().into());
-      })?;
-
-      Ok(StatusUpdateResult {
-         bug_num,
-         status:  "blocked".to_string(),
-         message: Some(reason),
-      })
+   fn warn_claim(&self, claim: Option<&Claim>) {
+      if let Some(claim) = claim {
+         eprintln!(
+            "⚠️  Claimed by {} until {}",
+            claim.agent,
+            claim.until.format("%Y-%m-%d %H:%M UTC")
+         );
+      }
    }
 
-   pub fn block(&self, bug_ref: &str, reason: String, json: bool) -> Result<()> {
-      let bug_num = self.storage.resolve_bug_ref(bug_ref)?;
-
-      self.storage.update_issue_metadata(bug_num, |meta| {
-         meta.status = Status::Blocked;
-         meta.blocked_reason = Some(reason.clone().into());
-      })?;
-
+   pub fn claim(&self, bug_ref: &str, ttl: &str, json: bool) -> Result<()> {
+      let result = self.tracker.claim_data(bug_ref, ttl)?;
       if json {
-         let output = json!({
-             "bug_num": bug_num,
-             "status": "blocked",
-             "reason": reason,
-         });
-         println!("{}", serde_json::to_string_pretty(&output)?);
-      } else {
-         println!("🚫 {} marked as BLOCKED: {reason}", self.config.format_issue_ref(bug_num));
+         println!("{}", serde_json::to_string_pretty(&result)?);
+         return Ok(());
+      }
+      if let Some(previous) = &result.overridden {
+         println!("⚠️  Took over the claim of {}", previous.agent);
+      }
+      if let Some(claim) = &result.claim {
+         println!(
+            "🔒 {} claimed by {} until {}",
+            self.config().format_issue_ref(result.bug_num),
+            claim.agent,
+            claim.until.format("%Y-%m-%d %H:%M UTC")
+         );
       }
-
       Ok(())
    }
 
-   pub fn close_data(&self, bug_ref: &str, message: Option<String>) -> Result<StatusUpdateResult> {
-      let bug_num = self.storage.resolve_bug_ref(bug_ref)?;
-
-      self.storage.update_issue_metadata(bug_num, |meta| {
-         meta.status = Status::Closed;
-         meta.closed = Some(Utc::now());
-      })?;
-
-      if let Some(note) = &message {
-         let mut issue = self.storage.load_issue(bug_num)?;
-         issue.body.push_str(&format!("\n\n## Closed\n\n{}", note));
-         self.storage.save_issue(&issue, bug_num, false)?;
+   pub fn release(&self, bug_ref: &str, json: bool) -> Result<()> {
+      let result = self.tracker.release_data(bug_ref)?;
+      if json {
+         println!("{}", serde_json::to_string_pretty(&result)?);
+         return Ok(());
       }
-
-      Ok(StatusUpdateResult {
-         bug_num,
-         status: "closed".to_string(),
-         message,
-      })
-   }
-
-   pub fn open_data(&self, bug_ref: &str) -> Result<StatusUpdateResult> {
-      let bug_num = self.storage.resolve_bug_ref(bug_ref)?;
-
-      self.storage.update_issue_metadata(bug_num, |meta| {
-         meta.status = Status::NotStarted;
-         meta.closed = None;
-      })?;
-
-      self.storage.move_issue(bug_num, true)?;
-
-      Ok(StatusUpdateResult {
-         bug_num,
-         status:  "open".to_string(),
-         message: None,
-      })
-   }
-
-   pub fn defer_data(&self, bug_ref: &str) -> Result<StatusUpdateResult> {
-      let bug_num = self.storage.resolve_bug_ref(bug_ref)?;
-
-      self.storage.update_issue_metadata(bug_num, |meta| {
-         meta.status = Status::Backlog;
-      })?;
-
-      Ok(StatusUpdateResult {
-         bug_num,
-         status:  "backlog".to_string(),
-         message: None,
-      })
+      if let Some(previous) = &result.overridden {
+         println!("⚠️  Released the claim of {}", previous.agent);
+      }
+      println!("🔓 Released {}", self.config().format_issue_ref(result.bug_num));
+      Ok(())
    }
 
-   pub fn activate_data(&self, bug_ref: &str) -> Result<StatusUpdateResult> {
-      let bug_num = self.storage.resolve_bug_ref(bug_ref)?;
+   pub fn block(&self, bug_ref: &str, reason: String, json: bool) -> Result<()> {
+      let bug_num = self.tracker.resolve_ref(bug_ref)?;
+      self
+         .tracker
+         .check_transition(bug_num, "block", Status::Blocked)?;
 
-      self.storage.update_issue_metadata(bug_num, |meta| {
-         meta.status = Status::NotStarted;
+      self.storage().update_issue_metadata(bug_num, |meta| {
+         meta.status = Status::Blocked;
+         meta.blocked_reason = Some(reason.clone().into());
       })?;
 
-      Ok(StatusUpdateResult {
-         bug_num,
-         status:  "open".to_string(),
-         message: None,
-      })
-   }
-
-   pub fn checkpoint_data(&self, bug_ref: &str, note: String) -> Result<StatusUpdateResult> {
-      let bug_num = self.storage.resolve_bug_ref(bug_ref)?;
-      let mut issue = self.storage.load_issue(bug_num)?;
-
-      let timestamp = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
-      issue
-         .body
-         .push_str(&format!("\n\n## Checkpoint - {}\n\n{}", timestamp, note));
-
-      let mut status_changed = false;
-      if note.starts_with("BLOCKED:") {
-         let reason = note.strip_prefix("BLOCKED:").unwrap_or("").trim().to_string();
-         self.storage.update_issue_metadata(bug_num, |meta| {
-            meta.status = Status::Blocked;
-            meta.blocked_reason = Some(reason.into());
-         })?;
-         status_changed = true;
-      } else if note.starts_with("DONE:") || note.starts_with("COMPLETED:") {
-         self.storage.update_issue_metadata(bug_num, |meta| {
-            meta.status = Status::Closed;
-            meta.closed = Some(Utc::now());
-         })?;
-         status_changed = true;
+      if json {
+         let output = json!({
+             "bug_num": bug_num,
+             "status": "blocked",
+             "reason": reason,
+         });
+         println!("{}", serde_json::to_string_pretty(&output)?);
+      } else {
+         println!("🚫 {} marked as BLOCKED: {reason}", self.config().format_issue_ref(bug_num));
       }
 
-      self.storage.save_issue(&issue, bug_num, false)?;
-
-      Ok(StatusUpdateResult {
-         bug_num,
-         status:  if status_changed { "updated".to_string() } else { "checkpoint_added".to_string() },
-         message: Some(note),
-      })
+      Ok(())
    }
 
+   #[allow(clippy::too_many_arguments)]
    pub fn close(
       &self,
       bug_ref: &str,
       message: Option<String>,
       commit_flag: bool,
       no_commit_flag: bool,
+      auto_unblock: bool,
+      auto_files: bool,
       json: bool,
    ) -> Result<()> {
-      let bug_num = self.storage.resolve_bug_ref(bug_ref)?;
+      let bug_num = self.tracker.resolve_ref(bug_ref)?;
+      self
+         .tracker
+         .check_transition(bug_num, "close", Status::Closed)?;
+      let changed_files = self.settle_changed_files(bug_num, auto_files, json)?;
 
       // Update metadata
-      self.storage.update_issue_metadata(bug_num, |meta| {
+      self.storage().update_issue_metadata(bug_num, |meta| {
          meta.status = Status::Closed;
          meta.closed = Some(Utc::now());
       })?;
 
       // Add close note if provided
       if let Some(note) = &message {
-         let mut issue = self.storage.load_issue(bug_num)?;
+         let base = self.storage().load_issue(bug_num)?;
+         let mut issue = base.clone();
          let timestamp = Utc::now().format("%Y-%m-%d").to_string();
          issue
             .body
-            .push_str(&format!("\n\n---\n\n**Closed** ({timestamp}): {note}\n"));
-         self.storage.save_issue(&issue, bug_num, true)?;
+            .append(&format!("\n\n---\n\n**Closed** ({timestamp}): {note}\n"));
+         self.tracker.save_edited(&base, &issue, bug_num, true)?;
       }
 
       // Move to closed directory
-      self.storage.move_issue(bug_num, false)?;
+      self.storage().move_issue(bug_num, false)?;
+      let unblocked = self.tracker.release_dependents(bug_num)?;
+      let mut unblock_candidates = self.tracker.unblock_candidates(bug_num)?;
 
       // Determine if we should create a commit
       let should_commit = if no_commit_flag {
@@ -695,8 +1183,8 @@ impl Commands {
       } else if commit_flag {
          true
       } else {
-         self.config.git_integration.enabled
-            && self.config.git_integration.commit_prefix_format.is_some()
+         self.config().git_integration.enabled
+            && self.config().git_integration.commit_prefix_format.is_some()
       };
 
       let mut commit_created = None;
@@ -708,7 +1196,7 @@ impl Commands {
                match git.has_staged_changes() {
                   Ok(true) => {
                      let commit_message = if let Some(ref format) =
-                        self.config.git_integration.commit_prefix_format
+                        self.config().git_integration.commit_prefix_format
                      {
                         let prefix = format.replace("{id}", &bug_num.to_string());
                         if let Some(msg) = &message {
@@ -718,7 +1206,7 @@ impl Commands {
                         }
                      } else {
                         message.clone().unwrap_or_else(|| {
-                           format!("Close {}", self.config.format_issue_ref(bug_num))
+                           format!("Close {}", self.config().format_issue_ref(bug_num))
                         })
                      };
 
@@ -754,33 +1242,45 @@ impl Commands {
       }
 
       if json {
+         self.settle_unblock_candidates(&mut unblock_candidates, auto_unblock, true)?;
          let output = json!({
              "bug_num": bug_num,
              "status": "closed",
              "commit_created": commit_created,
+             "unblocked": unblocked,
+             "unblock_candidates": unblock_candidates,
+             "files_added": changed_files.added,
+             "files_suggested": changed_files.suggested,
          });
          println!("{}", serde_json::to_string_pretty(&output)?);
       } else {
-         println!("✓ {} marked as CLOSED", self.config.format_issue_ref(bug_num));
+         println!("✓ {} marked as CLOSED", self.config().format_issue_ref(bug_num));
+         self.print_changed_files(bug_num, &changed_files);
          if let Some(commit_id) = commit_created {
             println!("📝 Created git commit: {}", &commit_id[..8]);
          }
+         self.print_unblocked(&unblocked);
+         self.settle_unblock_candidates(&mut unblock_candidates, auto_unblock, false)?;
+         self.print_unblock_candidates(&unblock_candidates);
       }
 
       Ok(())
    }
 
    pub fn open(&self, bug_ref: &str, json: bool) -> Result<()> {
-      let bug_num = self.storage.resolve_bug_ref(bug_ref)?;
+      let bug_num = self.tracker.resolve_ref(bug_ref)?;
+      self
+         .tracker
+         .check_transition(bug_num, "reopen", Status::NotStarted)?;
 
       // Update metadata
-      self.storage.update_issue_metadata(bug_num, |meta| {
+      self.storage().update_issue_metadata(bug_num, |meta| {
          meta.status = Status::NotStarted;
          meta.closed = None;
       })?;
 
       // Move to open directory
-      self.storage.move_issue(bug_num, true)?;
+      self.storage().move_issue(bug_num, true)?;
 
       if json {
          let output = json!({
@@ -789,16 +1289,19 @@ impl Commands {
          });
          println!("{}", serde_json::to_string_pretty(&output)?);
       } else {
-         println!("↻ {} marked as OPEN", self.config.format_issue_ref(bug_num));
+         println!("↻ {} marked as OPEN", self.config().format_issue_ref(bug_num));
       }
 
       Ok(())
    }
 
    pub fn defer(&self, bug_ref: &str, json: bool) -> Result<()> {
-      let bug_num = self.storage.resolve_bug_ref(bug_ref)?;
+      let bug_num = self.tracker.resolve_ref(bug_ref)?;
+      self
+         .tracker
+         .check_transition(bug_num, "defer", Status::Backlog)?;
 
-      self.storage.update_issue_metadata(bug_num, |meta| {
+      self.storage().update_issue_metadata(bug_num, |meta| {
          meta.status = Status::Backlog;
       })?;
 
@@ -809,16 +1312,19 @@ impl Commands {
          });
          println!("{}", serde_json::to_string_pretty(&output)?);
       } else {
-         println!("💤 {} moved to BACKLOG", self.config.format_issue_ref(bug_num));
+         println!("💤 {} moved to BACKLOG", self.config().format_issue_ref(bug_num));
       }
 
       Ok(())
    }
 
    pub fn activate(&self, bug_ref: &str, json: bool) -> Result<()> {
-      let bug_num = self.storage.resolve_bug_ref(bug_ref)?;
+      let bug_num = self.tracker.resolve_ref(bug_ref)?;
+      self
+         .tracker
+         .check_transition(bug_num, "activate", Status::NotStarted)?;
 
-      self.storage.update_issue_metadata(bug_num, |meta| {
+      self.storage().update_issue_metadata(bug_num, |meta| {
          meta.status = Status::NotStarted;
       })?;
 
@@ -829,46 +1335,83 @@ impl Commands {
          });
          println!("{}", serde_json::to_string_pretty(&output)?);
       } else {
-         println!("⭕ {} activated from BACKLOG", self.config.format_issue_ref(bug_num));
+         println!("⭕ {} activated from BACKLOG", self.config().format_issue_ref(bug_num));
       }
 
       Ok(())
    }
 
-   pub fn checkpoint(&self, bug_ref: &str, note: String, json: bool) -> Result<()> {
-      let bug_num = self.storage.resolve_bug_ref(bug_ref)?;
-      let mut issue = self.storage.load_issue(bug_num)?;
-
-      // Auto-detect status changes from checkpoint message
-      let mut status_changed = false;
-      if note.starts_with("BLOCKED:") || note.to_uppercase().starts_with("BLOCKED:") {
-         let reason = note
-            .strip_prefix("BLOCKED:")
-            .or_else(|| note.strip_prefix("blocked:"))
-            .unwrap_or(&note)
-            .trim()
-            .to_string();
+   pub fn checkpoint(
+      &self,
+      bug_ref: &str,
+      note: String,
+      auto_files: bool,
+      json: bool,
+   ) -> Result<()> {
+      let mark = CheckpointMark::from_note(&note);
+      self.checkpoint_marked(bug_ref, note, mark, auto_files, json)
+   }
 
-         issue.metadata.status = Status::Blocked;
-         issue.metadata.blocked_reason = Some(reason.into());
-         status_changed = true;
-      } else if note.starts_with("FIXED:")
-         || note.to_uppercase().starts_with("FIXED:")
-         || note.starts_with("DONE:")
-         || note.to_uppercase().starts_with("DONE:")
-      {
-         issue.metadata.status = Status::Done;
-         status_changed = true;
+   /// Add a checkpoint, changing the status as `mark` says rather than as
+   /// the note's prefix does
+   pub fn checkpoint_marked(
+      &self,
+      bug_ref: &str,
+      note: String,
+      mark: Option<CheckpointMark>,
+      auto_files: bool,
+      json: bool,
+   ) -> Result<()> {
+      let bug_num = self.tracker.resolve_ref(bug_ref)?;
+      let claimed = self.tracker.check_claim(bug_num, "checkpoint")?;
+      if !json {
+         self.warn_claim(claimed.as_ref());
+      }
+      let changed_files = self.settle_changed_files(bug_num, auto_files, json)?;
+
+      // Locked from the claim check to the save; the prompts before and any
+      // conflict resolution after run without it
+      let lock = self.storage().lock()?;
+      self.tracker.check_claim(bug_num, "checkpoint")?;
+      let base = self.storage().load_issue(bug_num)?;
+      let mut issue = base.clone();
+
+      let status_changed = mark.is_some();
+      match mark {
+         Some(CheckpointMark::Blocked(reason)) => {
+            self
+               .tracker
+               .check_transition(bug_num, "block", Status::Blocked)?;
+            issue.metadata.set_status(Status::Blocked);
+            issue.metadata.blocked_reason = Some(reason.into());
+         },
+         Some(CheckpointMark::Done) => {
+            self
+               .tracker
+               .check_transition(bug_num, "mark done", Status::Done)?;
+            issue.metadata.set_status(Status::Done);
+         },
+         None => {},
       }
 
       let timestamp = Utc::now().format("%Y-%m-%d %H:%M").to_string();
       let checkpoint = format!("\n\n**Checkpoint** ({timestamp}): {note}");
 
-      issue.body.push_str(&checkpoint);
+      issue.body.append(&checkpoint);
 
       // Determine if open or closed
       let is_open = issue.metadata.status != Status::Closed;
-      self.storage.save_issue(&issue, bug_num, is_open)?;
+      let saved = self
+         .storage()
+         .save_issue_from(&base, &issue, bug_num, is_open);
+      drop(lock);
+      self.tracker.settle_conflict(saved)?;
+      let unblocked = if status_changed && issue.metadata.status == Status::Done {
+         self.tracker.release_dependents(bug_num)?
+      } else {
+         Vec::new()
+      };
+      let compacted = self.tracker.compact_over_budget(bug_num)?;
 
       if json {
          let output = json!({
@@ -877,54 +1420,62 @@ impl Commands {
              "timestamp": timestamp,
              "status_changed": status_changed,
              "new_status": if status_changed { Some(issue.metadata.status.to_string()) } else { None },
+             "unblocked": unblocked,
+             "compacted": compacted,
+             "files_added": changed_files.added,
+             "files_suggested": changed_files.suggested,
          });
          println!("{}", serde_json::to_string_pretty(&output)?);
       } else {
-         println!("✓ Added checkpoint to {}", self.config.format_issue_ref(bug_num));
+         println!("✓ Added checkpoint to {}", self.config().format_issue_ref(bug_num));
          if status_changed {
             println!("  Status updated to: {}", issue.metadata.status);
          }
+         self.print_changed_files(bug_num, &changed_files);
+         self.print_unblocked(&unblocked);
+         if let Some(result) = &compacted {
+            self.print_compacted(result);
+         }
       }
 
       Ok(())
    }
 
-   pub fn context_data(&self) -> Result<ContextResult> {
-      let issues = self.storage.list_open_issues()?;
-
-      let mut in_progress = Vec::new();
-      let mut blocked = Vec::new();
-      let mut high_priority = Vec::new();
-      let mut ready = Vec::new();
-
-      for issue_with_id in issues.iter() {
-         match issue_with_id.issue.metadata.status {
-            Status::InProgress => in_progress.push(issue_with_id.clone()),
-            Status::Blocked => blocked.push(issue_with_id.clone()),
-            Status::NotStarted => {
-               if matches!(
-                  issue_with_id.issue.metadata.priority,
-                  Priority::Critical | Priority::High
-               ) {
-                  high_priority.push(issue_with_id.clone());
-               }
-               ready.push(issue_with_id.clone());
-            },
-            _ => {},
-         }
+   pub fn compact(&self, bug_ref: &str, keep: Option<usize>, json: bool) -> Result<()> {
+      let result = self.tracker.compact_data(bug_ref, keep)?;
+      if json {
+         println!("{}", serde_json::to_string_pretty(&result)?);
+         return Ok(());
       }
+      if result.compacted == 0 {
+         println!(
+            "Nothing to compact: {} has {} checkpoint(s)",
+            self.config().format_issue_ref(result.bug_num),
+            result.kept
+         );
+         return Ok(());
+      }
+      self.print_compacted(&result);
+      Ok(())
+   }
 
-      Ok(ContextResult {
-         active: in_progress,
-         blocked,
-         high_priority,
-         ready_to_start: ready.into_iter().take(5).collect(),
-         total_open: issues.len(),
-      })
+   fn print_compacted(&self, result: &CompactResult) {
+      println!(
+         "🗜️  Compacted {} checkpoint(s) of {} into the {}, kept the last {} ({} → {} bytes)",
+         result.compacted,
+         self.config().format_issue_ref(result.bug_num),
+         compact::SUMMARY_LABEL.to_lowercase(),
+         result.kept,
+         result.bytes_before,
+         result.bytes_after
+      );
+      if let Some(archive) = &result.archive {
+         println!("   Originals archived in {}", archive.display());
+      }
    }
 
-   pub fn context(&self, json: bool) -> Result<()> {
-      let context_data = self.context_data()?;
+   pub fn context(&self, scope: &ContextScope, json: bool) -> Result<()> {
+      let context_data = self.tracker.context_data(scope)?;
 
       if json {
          println!("{}", serde_json::to_string_pretty(&context_data)?);
@@ -941,6 +1492,12 @@ impl Commands {
       let high_priority = &context_data.high_priority;
       let ready = &context_data.ready_to_start;
       let total_open = context_data.total_open;
+      let unblocks = &context_data.unblocks;
+      let more = |section: &str| {
+         if let Some(omitted) = context_data.omitted.get(section) {
+            println!("   ... and {omitted} more");
+         }
+      };
 
       println!("\n{}", "=".repeat(80));
       println!("CURRENT CONTEXT");
@@ -951,10 +1508,11 @@ impl Commands {
          for issue_with_id in in_progress {
             println!(
                "   {}: {}",
-               self.config.format_issue_ref(issue_with_id.id),
+               self.config().format_issue_ref(issue_with_id.id),
                issue_with_id.issue.metadata.title
             );
          }
+         more("active");
          println!();
       }
 
@@ -963,13 +1521,14 @@ impl Commands {
          for issue_with_id in blocked {
             println!(
                "   {}: {}",
-               self.config.format_issue_ref(issue_with_id.id),
+               self.config().format_issue_ref(issue_with_id.id),
                issue_with_id.issue.metadata.title
             );
             if let Some(reason) = &issue_with_id.issue.metadata.blocked_reason {
                println!("      → {}", reason);
             }
          }
+         more("blocked");
          println!();
       }
 
@@ -977,70 +1536,52 @@ impl Commands {
          println!("⚠️  HIGH PRIORITY QUEUE ({}):", high_priority.len());
          for issue_with_id in high_priority {
             println!(
-               "   [{}] {}: {}",
-               issue_with_id.issue.metadata.priority.to_string().to_uppercase(),
-               self.config.format_issue_ref(issue_with_id.id),
-               issue_with_id.issue.metadata.title
+               "   [{}] {}: {}{}",
+               issue_with_id
+                  .issue
+                  .metadata
+                  .priority
+                  .to_string()
+                  .to_uppercase(),
+               self.config().format_issue_ref(issue_with_id.id),
+               issue_with_id.issue.metadata.title,
+               unblocks_note(unblocks.get(&issue_with_id.id).copied().unwrap_or(0))
             );
          }
+         more("high_priority");
          println!();
       }
 
       if !ready.is_empty() {
          println!("✓ READY TO START ({} tasks):", ready.len());
-         for issue_with_id in ready.iter().take(5) {
+         for issue_with_id in ready {
             println!(
-               "   {}: {}",
-               self.config.format_issue_ref(issue_with_id.id),
-               issue_with_id.issue.metadata.title
+               "   {}: {}{}",
+               self.config().format_issue_ref(issue_with_id.id),
+               issue_with_id.issue.metadata.title,
+               unblocks_note(unblocks.get(&issue_with_id.id).copied().unwrap_or(0))
             );
          }
-         if ready.len() > 5 {
-            println!("   ... and {} more", ready.len() - 5);
-         }
+         more("ready_to_start");
          println!();
       }
 
       println!("Total open issues: {}", total_open);
+      if context_data.deferred > 0 {
+         println!("Deferred to backlog: {}", context_data.deferred);
+      }
+      if let Some(budget) = &context_data.budget {
+         print_budget(budget);
+      }
 
       Ok(())
    }
 
    pub fn focus(&self, json: bool) -> Result<()> {
-      let issues = self.storage.list_open_issues()?;
-
-      let mut focus_issues: Vec<_> = issues
-         .iter()
-         .map(|issue_with_id| {
-            let sort_key = match issue_with_id.issue.metadata.status {
-               Status::InProgress | Status::Blocked => -1,
-               _ => issue_with_id.issue.metadata.priority.sort_key() as i32,
-            };
-
-            (issue_with_id, sort_key)
-         })
-         .collect();
-
-      focus_issues.sort_by_key(|(_, key)| *key);
-      let focus_issues: Vec<_> = focus_issues
-         .iter()
-         .take(5)
-         .map(|(issue_with_id, _)| issue_with_id)
-         .collect();
+      let focus_issues = self.tracker.focus_data()?;
 
       if json {
-         let data: Vec<_> = focus_issues
-            .iter()
-            .map(|issue_with_id| {
-               json!({
-                   "num": issue_with_id.id,
-                   "title": issue_with_id.issue.metadata.title,
-                   "priority": issue_with_id.issue.metadata.priority.to_string(),
-                   "status": issue_with_id.issue.metadata.status.to_string(),
-               })
-            })
-            .collect();
-         println!("{}", serde_json::to_string_pretty(&data)?);
+         println!("{}", serde_json::to_string_pretty(&focus_issues)?);
          return Ok(());
       }
 
@@ -1048,258 +1589,619 @@ impl Commands {
       println!("FOCUS - Top Priority Tasks");
       println!("{}\n", "=".repeat(80));
 
-      for issue_with_id in focus_issues {
-         let marker = issue_with_id.issue.metadata.status.marker();
-         let priority_label = format!(
-            "[{}]",
-            issue_with_id
-               .issue
-               .metadata
-               .priority
-               .to_string()
-               .to_uppercase()
-         );
+      for issue in &focus_issues {
+         let priority_label = format!("[{}]", issue.priority.to_string().to_uppercase());
          println!(
-            "{} {:10} {}: {}",
-            marker,
+            "{} {:10} {}: {}{}",
+            issue.status.marker(),
             priority_label,
-            self.config.format_issue_ref(issue_with_id.id),
-            issue_with_id.issue.metadata.title
+            self.config().format_issue_ref(issue.num),
+            issue.title,
+            unblocks_note(issue.unblocks)
          );
       }
 
+      // Blocked issues can't be worked on today, so only the rest count
+      if let Some(capacity) = self.tracker.daily_capacity()? {
+         let today = focus_issues
+            .iter()
+            .filter(|i| matches!(i.status, Status::InProgress | Status::NotStarted))
+            .map(|i| i.effort.as_deref());
+         println!();
+         print_budget(&DailyBudget::new(capacity, today));
+      }
+
       Ok(())
    }
 
-   pub fn blocked(&self, json: bool) -> Result<()> {
-      let issues = self.storage.list_open_issues()?;
+   /// Time `rounds` pomodoros of `work` (e.g. `25m`) with `rest` breaks on
+   /// `bug_ref`, or on the issue started last. Each finished interval asks
+   /// for a progress note, checkpoints it and logs it to the journal.
+   pub fn pomodoro(
+      &self,
+      bug_ref: Option<&str>,
+      work: &str,
+      rest: &str,
+      rounds: u32,
+      json: bool,
+   ) -> Result<()> {
+      let work = parse_duration(work)?;
+      let rest = parse_duration(rest)?;
+      if work <= Duration::zero() || rounds == 0 {
+         anyhow::bail!("A pomodoro needs a positive length and at least one round");
+      }
+      let bug_num = match bug_ref {
+         Some(bug_ref) => self.tracker.resolve_ref(bug_ref)?,
+         None => self
+            .storage()
+            .list_open_issues()?
+            .into_iter()
+            .filter(|i| i.issue.metadata.status == Status::InProgress)
+            .max_by_key(|i| i.issue.metadata.started)
+            .map(|i| i.id)
+            .context("No issue in progress: start one or name it with --issue")?,
+      };
+      let title = self
+         .storage()
+         .load_issue(bug_num)?
+         .metadata
+         .title
+         .to_string();
+      let issue_ref = |n| self.config().format_issue_ref(n);
+
+      let mut intervals = Vec::new();
+      for round in 1..=rounds {
+         let started = Utc::now();
+         self.count_down(&Pomodoro {
+            bug_num,
+            title: title.clone(),
+            phase: Phase::Work,
+            round,
+            rounds,
+            started,
+            ends: started + work,
+         })?;
 
-      let blocked_issues: Vec<_> = issues
-         .iter()
-         .filter(|issue_with_id| issue_with_id.issue.metadata.status == Status::Blocked)
-         .collect();
+         // Ring the terminal bell
+         eprint!("\x07");
+         let note = if is_interactive_terminal() {
+            let note = prompt_optional(&format!("Pomodoro {round} done. Progress note"), None)?;
+            Some(note.trim().to_string()).filter(|n| !n.is_empty())
+         } else {
+            None
+         };
+         let interval = Interval { bug_num, round, started, ended: Utc::now(), note };
+         self
+            .tracker
+            .checkpoint_data(&bug_num.to_string(), interval.checkpoint())?;
+         self
+            .storage()
+            .append_journal(&interval.journal_line(issue_ref, &title))?;
+         if !json {
+            println!("✓ Checkpointed {}: {}", issue_ref(bug_num), interval.checkpoint());
+         }
+         intervals.push(interval);
+
+         if round < rounds && rest > Duration::zero() {
+            let started = Utc::now();
+            self.count_down(&Pomodoro {
+               bug_num,
+               title: title.clone(),
+               phase: Phase::Break,
+               round,
+               rounds,
+               started,
+               ends: started + rest,
+            })?;
+            eprint!("\x07");
+         }
+      }
 
       if json {
-         let data: Vec<_> = blocked_issues
-            .iter()
-            .map(|issue_with_id| {
-               json!({
-                   "num": issue_with_id.id,
-                   "title": issue_with_id.issue.metadata.title,
-                   "reason": issue_with_id.issue.metadata.blocked_reason,
-                   "priority": issue_with_id.issue.metadata.priority.to_string(),
-               })
-            })
-            .collect();
-         println!("{}", serde_json::to_string_pretty(&data)?);
-         return Ok(());
+         println!("{}", serde_json::to_string_pretty(&intervals)?);
       }
+      Ok(())
+   }
 
-      if blocked_issues.is_empty() {
-         println!("No blocked tasks");
+   /// Show `pomodoro` counting down until it ends, recording it for the TUI
+   fn count_down(&self, pomodoro: &Pomodoro) -> Result<()> {
+      self.storage().save_pomodoro(Some(pomodoro))?;
+      let issue_ref = |n| self.config().format_issue_ref(n);
+      while let Some(status) = pomodoro.status(Utc::now(), issue_ref) {
+         eprint!("\r{status} {}   ", pomodoro.title);
+         std::thread::sleep(std::time::Duration::from_secs(1));
+      }
+      eprintln!("\r{} {}   ", pomodoro::clock(0), pomodoro.title);
+      self.storage().save_pomodoro(None)
+   }
+
+   pub fn why(&self, bug_ref: &str, json: bool) -> Result<()> {
+      let why = self.tracker.why_data(bug_ref)?;
+
+      if json {
+         println!("{}", serde_json::to_string_pretty(&why)?);
          return Ok(());
       }
 
-      println!("\n{}", "=".repeat(80));
-      println!("BLOCKED TASKS ({})", blocked_issues.len());
-      println!("{}\n", "=".repeat(80));
+      println!(
+         "{} {}: {}",
+         why.status.marker(),
+         self.config().format_issue_ref(why.bug_num),
+         why.title
+      );
+      match why.rank {
+         Some(rank) => println!(
+            "Ranked {rank} of {} open issues in focus (score {}, lower first)",
+            why.open_issues, why.score
+         ),
+         None => println!("Not ranked: the issue is {}", why.status),
+      }
 
-      for issue_with_id in blocked_issues {
+      if !why.counted.is_empty() {
          println!(
-            "🚫 {}: {}",
-            self.config.format_issue_ref(issue_with_id.id),
-            issue_with_id.issue.metadata.title
+            "
+Counted:"
          );
-         if let Some(reason) = &issue_with_id.issue.metadata.blocked_reason {
-            println!("   Reason: {reason}");
+         for line in &why.counted {
+            println!("  • {line}");
          }
+      }
+      println!(
+         "
+Not counted:"
+      );
+      for line in &why.not_counted {
+         println!("  • {line}");
+      }
+
+      if !why.ahead.is_empty() {
          println!(
-            "   Priority: {}\n",
-            issue_with_id
-               .issue
-               .metadata
-               .priority
-               .to_string()
-               .to_uppercase()
+            "
+Ranked just above:"
          );
+         for issue in &why.ahead {
+            println!(
+               "  {} [{}] {}: {}{}",
+               issue.status.marker(),
+               issue.priority,
+               self.config().format_issue_ref(issue.num),
+               issue.title,
+               unblocks_note(issue.unblocks)
+            );
+         }
       }
 
       Ok(())
    }
 
-   pub fn ready(&self, json: bool) -> Result<()> {
-      let issues = self.storage.list_open_issues()?;
-
-      let mut ready_issues: Vec<_> = issues
-         .iter()
-         .filter(|issue_with_id| issue_with_id.issue.metadata.status == Status::NotStarted)
-         .collect();
-
-      ready_issues.sort_by_key(|issue_with_id| issue_with_id.issue.metadata.priority.sort_key());
+   pub fn blocked(&self, json: bool) -> Result<()> {
+      let blocked_issues = self.tracker.blocked_data()?;
 
       if json {
-         let data: Vec<_> = ready_issues
-            .iter()
-            .map(|issue_with_id| {
-               json!({
-                   "num": issue_with_id.id,
-                   "title": issue_with_id.issue.metadata.title,
-                   "priority": issue_with_id.issue.metadata.priority.to_string(),
-                   "files": issue_with_id.issue.metadata.files,
-               })
-            })
-            .collect();
-         println!("{}", serde_json::to_string_pretty(&data)?);
+         println!("{}", serde_json::to_string_pretty(&blocked_issues)?);
          return Ok(());
       }
 
-      if ready_issues.is_empty() {
-         println!("No tasks ready to start");
+      if blocked_issues.is_empty() {
+         println!("No blocked tasks");
          return Ok(());
       }
 
       println!("\n{}", "=".repeat(80));
-      println!("READY TO START ({} tasks)", ready_issues.len());
+      println!("BLOCKED TASKS ({})", blocked_issues.len());
       println!("{}\n", "=".repeat(80));
 
-      for issue_with_id in ready_issues {
-         let priority_label = format!(
-            "[{}]",
-            issue_with_id
-               .issue
-               .metadata
-               .priority
-               .to_string()
-               .to_uppercase()
-         );
-         println!(
-            "⭕ {:10} {}: {}",
-            priority_label,
-            self.config.format_issue_ref(issue_with_id.id),
-            issue_with_id.issue.metadata.title
-         );
-         if !issue_with_id.issue.metadata.files.is_empty() {
-            println!("   Files: {}", issue_with_id.issue.metadata.files.join(", "));
+      for issue in blocked_issues {
+         println!("🚫 {}: {}", self.config().format_issue_ref(issue.num), issue.title);
+         if let Some(reason) = &issue.reason {
+            println!("   Reason: {reason}");
          }
+         println!("   Priority: {}\n", issue.priority.to_string().to_uppercase());
       }
 
       Ok(())
    }
 
-   pub fn import(&self, file: Option<String>, json: bool) -> Result<()> {
-      let yaml_input = if let Some(path) = file {
-         std::fs::read_to_string(path)?
-      } else {
-         use std::io::Read;
-         let mut buffer = String::new();
-         std::io::stdin().read_to_string(&mut buffer)?;
-         buffer
-      };
-
-      let data: Vec<serde_yaml::Value> =
-         serde_yaml::from_str(&yaml_input).context("Failed to parse YAML input")?;
-
-      let mut created = Vec::new();
-
-      for item in data {
-         let obj = item.as_mapping().context("Item must be a mapping")?;
+   pub fn blocked_analysis(&self, json: bool) -> Result<()> {
+      let analysis = self.tracker.blocked_analysis_data()?;
 
-         let title = obj
-            .get("title")
-            .and_then(|v| v.as_str())
-            .context("Missing title")?
-            .to_string();
-
-         let priority_str = obj
-            .get("priority")
-            .and_then(|v| v.as_str())
-            .unwrap_or("medium");
-
-         let tags: Vec<String> = obj
-            .get("tags")
-            .and_then(|v| v.as_sequence())
-            .map(|seq| {
-               seq.iter()
-                  .filter_map(|v| v.as_str().map(String::from))
-                  .collect()
-            })
-            .unwrap_or_default();
-
-         let files: Vec<String> = obj
-            .get("files")
-            .and_then(|v| v.as_sequence())
-            .map(|seq| {
-               seq.iter()
-                  .filter_map(|v| v.as_str().map(String::from))
-                  .collect()
-            })
-            .unwrap_or_default();
-
-         let issue = obj
-            .get("issue")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
-
-         let impact = obj
-            .get("impact")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
+      if json {
+         println!("{}", serde_json::to_string_pretty(&analysis)?);
+         return Ok(());
+      }
 
-         let acceptance = obj
-            .get("acceptance")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
+      if analysis.clusters.is_empty() {
+         println!("No blocked reasons recorded");
+         return Ok(());
+      }
 
-         let effort = obj.get("effort").and_then(|v| v.as_str()).map(String::from);
-
-         let context = obj
-            .get("context")
-            .and_then(|v| v.as_str())
-            .map(String::from);
-
-         self.create_issue(
-            title,
-            priority_str,
-            tags,
-            files,
-            issue,
-            impact,
-            acceptance,
-            effort,
-            context,
-            false,
-         )?;
+      println!("\n{}", "=".repeat(80));
+      println!(
+         "RECURRING BLOCKERS - {} reasons across {} issues",
+         analysis.clusters.len(),
+         analysis.issues_with_reason
+      );
+      println!("{}\n", "=".repeat(80));
 
-         let bug_num = self.storage.next_bug_number()? - 1;
-         created.push(bug_num);
+      for cluster in &analysis.clusters {
+         let refs: Vec<_> = cluster
+            .issues
+            .iter()
+            .map(|id| self.config().format_issue_ref(*id))
+            .collect();
+         println!(
+            "🚫 {} ({} issues, {} lost)",
+            cluster.label,
+            cluster.issues.len(),
+            format_hours(cluster.total_blocked_hours)
+         );
+         println!("   Issues: {}", refs.join(", "));
+         if cluster.currently_blocked > 0 {
+            println!("   Still blocked: {}", cluster.currently_blocked);
+         }
+         if cluster.reasons.len() > 1 {
+            for reason in cluster.reasons.iter().filter(|r| **r != cluster.label) {
+               println!("   Also: {reason}");
+            }
+         }
+         println!();
       }
 
-      if json {
-         let output = json!({
-             "created": created,
-             "count": created.len(),
-         });
-         println!("{}", serde_json::to_string_pretty(&output)?);
-      } else {
-         println!("\n✓ Created {} issues", created.len());
+      if analysis.unexplained_blocked_hours > 0.0 {
+         println!("Blocked without a reason: {}", format_hours(analysis.unexplained_blocked_hours));
       }
 
       Ok(())
    }
 
-   pub fn alias_list(&self, json: bool) -> Result<()> {
-      let aliases = self.storage.load_aliases()?;
+   pub fn ready(&self, json: bool) -> Result<()> {
+      let ready_issues = self.tracker.ready_data()?;
 
       if json {
-         println!("{}", serde_json::to_string_pretty(&aliases)?);
+         println!("{}", serde_json::to_string_pretty(&ready_issues)?);
          return Ok(());
       }
 
-      if aliases.is_empty() {
-         println!("No aliases defined");
+      if ready_issues.is_empty() {
+         println!("No tasks ready to start");
+         return Ok(());
+      }
+
+      println!("\n{}", "=".repeat(80));
+      println!("READY TO START ({} tasks)", ready_issues.len());
+      println!("{}\n", "=".repeat(80));
+
+      for issue in ready_issues {
+         let priority_label = format!("[{}]", issue.priority.to_string().to_uppercase());
+         println!(
+            "⭕ {:10} {}: {}{}",
+            priority_label,
+            self.config().format_issue_ref(issue.num),
+            issue.title,
+            unblocks_note(issue.unblocks)
+         );
+         if !issue.files.is_empty() {
+            println!("   Files: {}", issue.files.join(", "));
+         }
+      }
+
+      Ok(())
+   }
+
+   /// Print the commit message alone, ready for `git commit -F`
+   pub fn commit_msg(&self, bug_ref: Option<&str>, json: bool) -> Result<()> {
+      let (bug_num, message) = self.tracker.commit_msg_data(bug_ref)?;
+      if json {
+         let output = json!({ "bug_num": bug_num, "message": message });
+         println!("{}", serde_json::to_string_pretty(&output)?);
+      } else {
+         print!("{message}");
+      }
+      Ok(())
+   }
+
+   /// Print the prompt segment, nothing at all if the tracker is unreadable
+   pub fn prompt(&self, json: bool) -> Result<()> {
+      let Ok(status) = self.tracker.prompt_data() else {
+         return Ok(());
+      };
+      if json {
+         println!("{}", serde_json::to_string_pretty(&status)?);
+      } else {
+         println!("{}", status.render());
+      }
+      Ok(())
+   }
+
+   pub fn for_file(&self, path: Option<&str>, json: bool) -> Result<()> {
+      let path = match path {
+         Some(path) => path.to_string(),
+         None => {
+            let mut line = String::new();
+            std::io::stdin().read_line(&mut line)?;
+            line.trim().to_string()
+         },
+      };
+      let issues = self.tracker.for_file_data(&path)?;
+
+      if json {
+         println!("{}", serde_json::to_string_pretty(&issues)?);
+         return Ok(());
+      }
+
+      if issues.is_empty() {
+         println!("No open issues for {path}");
+         return Ok(());
+      }
+
+      println!("\n{}", "=".repeat(80));
+      println!("ISSUES FOR {path} ({})", issues.len());
+      println!("{}\n", "=".repeat(80));
+
+      for issue in &issues {
+         println!(
+            "{:10} {}: {} ({})",
+            format!("[{}]", issue.priority.to_uppercase()),
+            self.config().format_issue_ref(issue.num),
+            issue.title,
+            issue.status
+         );
+         println!("   Files: {}", issue.matched_files.join(", "));
+      }
+
+      Ok(())
+   }
+
+   /// Run a batch from `source` (a file, or `-` for stdin), printing one JSON
+   /// result per line
+   pub fn batch(&self, source: &str) -> Result<()> {
+      let input = if source == "-" {
+         use std::io::Read;
+         let mut buffer = String::new();
+         std::io::stdin().read_to_string(&mut buffer)?;
+         buffer
+      } else {
+         std::fs::read_to_string(source).with_context(|| format!("Failed to read {source}"))?
+      };
+
+      let results = self.tracker.batch_data(&input)?;
+      for result in &results {
+         println!("{}", serde_json::to_string(result)?);
+      }
+      if results.iter().any(|r| !r.ok) {
+         anyhow::bail!("Batch not applied; nothing was changed");
+      }
+      Ok(())
+   }
+
+   pub fn import(&self, file: Option<String>, dry_run: bool, json: bool) -> Result<()> {
+      let yaml_input = if let Some(path) = file {
+         std::fs::read_to_string(path)?
+      } else {
+         use std::io::Read;
+         let mut buffer = String::new();
+         std::io::stdin().read_to_string(&mut buffer)?;
+         buffer
+      };
+
+      let result = self.tracker.import_data(&yaml_input, dry_run)?;
+      if json {
+         println!("{}", serde_json::to_string_pretty(&result)?);
+      } else if result.dry_run {
+         let updates = result.planned.len() - result.count;
+         println!("Would create {} issues and update {updates}:", result.count);
+         for preview in &result.planned {
+            let kind = preview
+               .kind
+               .as_ref()
+               .map_or(String::new(), |k| format!(", {k}"));
+            let target = preview.updates.map_or(String::new(), |n| {
+               format!(" → updates {}", self.config().format_issue_ref(n))
+            });
+            println!("   {} ({}{kind}){target}", preview.title, preview.priority);
+         }
+         println!("\n(dry run, nothing was changed)");
+      } else {
+         for &bug_num in &result.created {
+            println!("✓ Created {}", self.config().format_issue_ref(bug_num));
+         }
+         for &bug_num in &result.updated {
+            println!("✓ Updated {}", self.config().format_issue_ref(bug_num));
+         }
+         println!("\n✓ Created {} issues", result.count);
+         if !result.updated.is_empty() || !result.unchanged.is_empty() {
+            println!(
+               "✓ Updated {}, {} already up to date",
+               result.updated.len(),
+               result.unchanged.len()
+            );
+         }
+      }
+
+      Ok(())
+   }
+
+   pub fn import_url(&self, url: &str, json: bool) -> Result<()> {
+      let result = self.tracker.import_url_data(url)?;
+      if json {
+         println!("{}", serde_json::to_string_pretty(&result)?);
+         return Ok(());
+      }
+      println!(
+         "✓ Imported {} as {}: {}",
+         result.source_url,
+         self.config().format_issue_ref(result.bug_num),
+         result.title
+      );
+      if result.status == "closed" {
+         println!("  {}", "Closed on GitHub, so stored as closed".dimmed());
+      }
+      if let Some(alias) = &result.alias {
+         println!("  Alias: {alias}");
+      }
+      Ok(())
+   }
+
+   pub fn bundle(&self, bug_ref: &str, out: Option<&str>, json: bool) -> Result<()> {
+      let result = self.tracker.bundle_data(bug_ref, out)?;
+      if json {
+         println!("{}", serde_json::to_string_pretty(&result)?);
+         return Ok(());
+      }
+      println!("✓ Bundled {} into {}", self.config().format_issue_ref(result.bug_num), result.path);
+      let mut contents = vec![format!("{} related issue stub(s)", result.relations)];
+      if result.checkpoints {
+         contents.push("archived checkpoints".to_string());
+      }
+      println!("  {}", format!("With {}", contents.join(" and ")).dimmed());
+      Ok(())
+   }
+
+   pub fn bundle_import(&self, file: &str, json: bool) -> Result<()> {
+      let result = self.tracker.bundle_import_data(file)?;
+      if json {
+         println!("{}", serde_json::to_string_pretty(&result)?);
+         return Ok(());
+      }
+      println!(
+         "✓ Imported #{} as {}: {}",
+         result.from,
+         self.config().format_issue_ref(result.bug_num),
+         result.title
+      );
+      let relation_name = |relation| match relation {
+         Relation::DependsOn => "depends on",
+         Relation::Blocks => "blocks",
+         Relation::References => "mentions",
+      };
+      for stub in &result.linked {
+         println!(
+            "  {} #{} → {} ({})",
+            relation_name(stub.relation),
+            stub.from,
+            self.config().format_issue_ref(stub.to),
+            stub.title
+         );
+      }
+      for stub in &result.unlinked {
+         println!(
+            "  {} #{} ({}): {}",
+            relation_name(stub.relation),
+            stub.num,
+            stub.title,
+            "no matching issue here, dropped".yellow()
+         );
+      }
+      if let Some(alias) = &result.alias {
+         println!("  Alias: {alias}");
+      }
+      Ok(())
+   }
+
+   pub fn watch(&self, bug_ref: &str, remove: bool, json: bool) -> Result<()> {
+      let result = self.tracker.watch_data(bug_ref, remove)?;
+      let issue_ref = self.config().format_issue_ref(result.bug_num);
+
+      if json {
+         println!("{}", serde_json::to_string_pretty(&result)?);
+      } else if result.watching {
+         println!("👀 Watching {issue_ref}");
+      } else {
+         println!("✓ Stopped watching {issue_ref}");
+      }
+
+      Ok(())
+   }
+
+   /// Check the `notify` rules once, or every `every` (e.g. `15m`) until
+   /// stopped; prints nothing when there is nothing new, so cron stays quiet
+   pub fn notify(&self, every: Option<&str>, desktop: bool, json: bool) -> Result<()> {
+      let every = every
+         .map(|e| parse_duration(e)?.to_std().context("Invalid interval"))
+         .transpose()?;
+      loop {
+         let alerts = self.tracker.notify_data(desktop)?;
+         if json {
+            if every.is_none() || !alerts.is_empty() {
+               println!("{}", serde_json::to_string(&alerts)?);
+            }
+         } else {
+            let now = Utc::now();
+            for alert in &alerts {
+               println!(
+                  "{} {}: {}",
+                  self.config().format_issue_ref(alert.bug_num()),
+                  alert.title(),
+                  alert.describe(now)
+               );
+            }
+         }
+         match every {
+            Some(every) => std::thread::sleep(every),
+            None => return Ok(()),
+         }
+      }
+   }
+
+   pub fn watched(&self, json: bool) -> Result<()> {
+      let issues = self.tracker.watched_data()?;
+
+      if json {
+         println!("{}", serde_json::to_string_pretty(&issues)?);
+         return Ok(());
+      }
+
+      if issues.is_empty() {
+         println!("Not watching any issues");
+         return Ok(());
+      }
+
+      println!("\nWatching:");
+      for issue in &issues {
+         let meta = &issue.issue.metadata;
+         println!(
+            "  {} {} {} [{}]",
+            meta.status.marker(),
+            self.config().format_issue_ref(issue.id),
+            meta.title,
+            meta.status
+         );
+      }
+
+      Ok(())
+   }
+
+   /// Print the "watched changes" banner, if anything changed
+   pub fn watched_banner(&self) -> Result<()> {
+      let changes = self.tracker.watched_changes_data()?;
+      if changes.is_empty() {
+         return Ok(());
+      }
+
+      eprintln!("{}", "👀 Watched changes:".bold());
+      for change in &changes {
+         let events: Vec<String> = change.events.iter().map(ToString::to_string).collect();
+         eprintln!(
+            "  {} {}: {}",
+            self.config().format_issue_ref(change.bug_num),
+            change.title,
+            events.join("; ")
+         );
+      }
+      eprintln!();
+      Ok(())
+   }
+
+   pub fn alias_list(&self, json: bool) -> Result<()> {
+      let aliases = self.storage().load_aliases()?;
+
+      if json {
+         println!("{}", serde_json::to_string_pretty(&aliases)?);
+         return Ok(());
+      }
+
+      if aliases.is_empty() {
+         println!("No aliases defined");
          return Ok(());
       }
 
@@ -1308,21 +2210,21 @@ impl Commands {
       items.sort_by_key(|(k, _)| *k);
 
       for (alias, bug_num) in items {
-         println!("  {alias} → {}", self.config.format_issue_ref(*bug_num));
+         println!("  {alias} → {}", self.config().format_issue_ref(*bug_num));
       }
 
       Ok(())
    }
 
    pub fn alias_add(&self, bug_ref: &str, alias: &str, json: bool) -> Result<()> {
-      let bug_num = self.storage.resolve_bug_ref(bug_ref)?;
+      let bug_num = self.tracker.resolve_ref(bug_ref)?;
 
       // Verify bug exists
-      self.storage.find_issue_file(bug_num)?;
+      self.storage().find_issue_file(bug_num)?;
 
-      let mut aliases = self.storage.load_aliases()?;
+      let mut aliases = self.storage().load_aliases()?;
       aliases.insert(alias.to_string(), bug_num);
-      self.storage.save_aliases(&aliases)?;
+      self.storage().save_aliases(&aliases)?;
 
       if json {
          let output = json!({
@@ -1331,20 +2233,20 @@ impl Commands {
          });
          println!("{}", serde_json::to_string_pretty(&output)?);
       } else {
-         println!("✓ Created alias: {alias} → {}", self.config.format_issue_ref(bug_num));
+         println!("✓ Created alias: {alias} → {}", self.config().format_issue_ref(bug_num));
       }
 
       Ok(())
    }
 
    pub fn alias_remove(&self, alias: &str, json: bool) -> Result<()> {
-      let mut aliases = self.storage.load_aliases()?;
+      let mut aliases = self.storage().load_aliases()?;
 
       let bug_num = aliases
          .remove(alias)
          .ok_or_else(|| anyhow::anyhow!("Alias '{alias}' not found"))?;
 
-      self.storage.save_aliases(&aliases)?;
+      self.storage().save_aliases(&aliases)?;
 
       if json {
          let output = json!({
@@ -1359,38 +2261,457 @@ impl Commands {
       Ok(())
    }
 
-   pub fn quick_wins(&self, threshold: &str, json: bool) -> Result<()> {
-      let threshold_minutes = parse_effort(threshold)?;
-      let issues = self.storage.list_open_issues()?;
+   pub fn alias_rename(&self, old: &str, new: &str, json: bool) -> Result<()> {
+      let mut aliases = self.storage().load_aliases()?;
+      let namespace = self.config().alias_namespace.as_deref();
+
+      let bug_num = alias::lookup(&aliases, namespace, old)
+         .ok_or_else(|| anyhow::anyhow!("Alias '{old}' not found"))?;
+      let old_key = aliases
+         .keys()
+         .find(|key| {
+            aliases[*key] == bug_num
+               && (key.eq_ignore_ascii_case(old)
+                  || key.eq_ignore_ascii_case(&alias::qualify(namespace, old)))
+         })
+         .or_else(|| aliases.keys().find(|key| aliases[*key] == bug_num))
+         .cloned()
+         .ok_or_else(|| anyhow::anyhow!("Alias '{old}' not found"))?;
+      if alias::taken(&aliases, new, bug_num) {
+         anyhow::bail!("Alias '{new}' already points to another issue");
+      }
+
+      aliases.remove(&old_key);
+      aliases.insert(new.to_string(), bug_num);
+      self.storage().save_aliases(&aliases)?;
+
+      if json {
+         let output = json!({
+             "renamed": old_key,
+             "alias": new,
+             "bug_num": bug_num,
+         });
+         println!("{}", serde_json::to_string_pretty(&output)?);
+      } else {
+         println!(
+            "✓ Renamed alias: {old_key} → {new} ({})",
+            self.config().format_issue_ref(bug_num)
+         );
+      }
+
+      Ok(())
+   }
+
+   /// Check the issue store for problems, fixing what can be fixed with `fix`
+   pub fn doctor(&self, fix: bool, json: bool) -> Result<()> {
+      let result = self.tracker.doctor_data(fix)?;
+
+      if json {
+         println!("{}", serde_json::to_string_pretty(&result)?);
+         return Ok(());
+      }
+
+      if result.alias_problems.is_empty()
+         && result.blocks_repairs.is_empty()
+         && result.missing_files.is_empty()
+      {
+         println!("✓ No problems found");
+         return Ok(());
+      }
+
+      if !result.alias_problems.is_empty() {
+         println!("Aliases ({}):", result.alias_problems.len());
+         for problem in &result.alias_problems {
+            println!("  ⚠️  {}", problem.message);
+         }
+         println!("\nFix with `agentx alias rename` or `agentx alias remove`.");
+      }
+
+      if !result.blocks_repairs.is_empty() {
+         if !result.alias_problems.is_empty() {
+            println!();
+         }
+         let refs = |nums: &[u32]| {
+            if nums.is_empty() {
+               "(none)".to_string()
+            } else {
+               nums
+                  .iter()
+                  .map(|n| self.config().format_issue_ref(*n))
+                  .collect::<Vec<_>>()
+                  .join(", ")
+            }
+         };
+         println!("Reverse dependency links ({}):", result.blocks_repairs.len());
+         for repair in &result.blocks_repairs {
+            let mark = if result.fixed { "✓" } else { "⚠️ " };
+            println!(
+               "  {mark} {} lists blocks: {}; depends_on says: {}",
+               self.config().format_issue_ref(repair.bug_num),
+               refs(&repair.stored),
+               refs(&repair.rebuilt)
+            );
+         }
+         if result.fixed {
+            println!("\n✓ Rebuilt blocks from depends_on");
+         } else {
+            println!("\nFix with `agentx doctor --fix`.");
+         }
+      }
+
+      if !result.missing_files.is_empty() {
+         if !result.alias_problems.is_empty() || !result.blocks_repairs.is_empty() {
+            println!();
+         }
+         println!("Missing files ({}):", result.missing_files.len());
+         for missing in &result.missing_files {
+            println!("  ⚠️  {}: {}", self.config().format_issue_ref(missing.bug_num), missing.path);
+         }
+         println!("\nAfter a rename, fix with `agentx files remap <old> <new>`.");
+      }
+
+      Ok(())
+   }
+
+   /// Rewrite paths after a file or directory rename
+   pub fn files_remap(&self, old: &str, new: &str, dry_run: bool, json: bool) -> Result<()> {
+      let result = self.tracker.files_remap_data(old, new, dry_run, json)?;
+
+      if json {
+         println!("{}", serde_json::to_string_pretty(&result)?);
+         return Ok(());
+      }
+
+      if result.remapped.is_empty() {
+         println!("No issue has files under {}", result.old);
+         return Ok(());
+      }
+      let verb = if result.dry_run {
+         "Would remap"
+      } else {
+         "✓ Remapped"
+      };
+      println!("{verb} files on {} issues:", result.remapped.len());
+      for change in &result.remapped {
+         println!("   {}: {}", self.config().format_issue_ref(change.bug_num), change.title);
+         for (before, after) in change.before.iter().zip(&change.after) {
+            if before != after {
+               println!("      {before} → {after}");
+            }
+         }
+      }
+      Ok(())
+   }
+
+   /// Print what breaks the `lint` rules as a table; returns whether
+   /// everything passed, for the exit status
+   pub fn lint(&self, json: bool) -> Result<bool> {
+      let findings = self.tracker.lint_data()?;
 
-      let quick: Vec<_> = issues
+      if json {
+         println!("{}", serde_json::to_string_pretty(&findings)?);
+         return Ok(findings.is_empty());
+      }
+
+      if findings.is_empty() {
+         println!("✓ No lint findings");
+         return Ok(true);
+      }
+
+      let refs: Vec<String> = findings
          .iter()
-         .filter(|issue_with_id| {
-            issue_with_id
-               .issue
-               .metadata
-               .effort
-               .as_ref()
-               .and_then(|e| parse_effort(e).ok())
-               .map(|m| m <= threshold_minutes)
-               .unwrap_or(false)
-         })
+         .map(|f| self.config().format_issue_ref(f.bug_num))
          .collect();
+      let rules: Vec<String> = findings.iter().map(|f| f.rule.to_string()).collect();
+      let ref_width = refs
+         .iter()
+         .map(String::len)
+         .max()
+         .unwrap_or(0)
+         .max("ISSUE".len());
+      let rule_width = rules
+         .iter()
+         .map(String::len)
+         .max()
+         .unwrap_or(0)
+         .max("RULE".len());
+      println!(
+         "{:<ref_width$}  {:<rule_width$}  {}",
+         "ISSUE".bold(),
+         "RULE".bold(),
+         "PROBLEM".bold()
+      );
+      for ((finding, issue_ref), rule) in findings.iter().zip(&refs).zip(&rules) {
+         println!(
+            "{issue_ref:<ref_width$}  {:<rule_width$}  {} ({})",
+            rule.yellow(),
+            finding.message,
+            finding.title.dimmed()
+         );
+      }
+      let issues: HashSet<u32> = findings.iter().map(|f| f.bug_num).collect();
+      println!("\n{} finding(s) in {} issue(s)", findings.len(), issues.len());
+      Ok(false)
+   }
+
+   pub fn release_status(&self, version: &str, json: bool) -> Result<()> {
+      let status = self.tracker.release_status_data(version)?;
 
       if json {
-         let data: Vec<_> = quick
-            .iter()
-            .map(|issue_with_id| {
-               json!({
-                   "num": issue_with_id.id,
-                   "title": issue_with_id.issue.metadata.title,
-                   "priority": issue_with_id.issue.metadata.priority.to_string(),
-                   "effort": issue_with_id.issue.metadata.effort,
-                   "files": issue_with_id.issue.metadata.files,
-               })
-            })
-            .collect();
-         println!("{}", serde_json::to_string_pretty(&data)?);
+         println!("{}", serde_json::to_string_pretty(&status)?);
+         return Ok(());
+      }
+
+      let total = status.open.len() + status.closed.len();
+      println!(
+         "📦 {}: {}/{total} closed ({:.0}%)",
+         status.version.bold(),
+         status.closed.len(),
+         status.closed.len() as f64 * 100.0 / total as f64
+      );
+      if !status.open.is_empty() {
+         let mut remaining = Vec::new();
+         if status.remaining_minutes > 0 {
+            remaining.push(format!("{} estimated", hours(status.remaining_minutes)));
+         }
+         if status.unestimated > 0 {
+            remaining.push(format!("{} issue(s) without an estimate", status.unestimated));
+         }
+         println!("   Remaining: {}", remaining.join(", "));
+      }
+      for (heading, issues) in [("Open", &status.open), ("Closed", &status.closed)] {
+         if issues.is_empty() {
+            continue;
+         }
+         println!("\n{heading} ({}):", issues.len());
+         for issue in issues {
+            let mut notes = vec![issue.priority.to_string(), issue.status.clone()];
+            notes.extend(issue.effort.clone());
+            let line = format!(
+               "   {} {} ({})",
+               self.config().format_issue_ref(issue.bug_num),
+               issue.title,
+               notes.join(", ")
+            );
+            if issue.priority == Priority::Critical && heading == "Open" {
+               println!("{}", line.red());
+            } else {
+               println!("{line}");
+            }
+         }
+      }
+      Ok(())
+   }
+
+   /// Cut a release, printing its changelog section unless written to a
+   /// file; returns whether it was cut, for the exit status
+   pub fn release_cut(&self, version: &str, changelog: Option<&str>, json: bool) -> Result<bool> {
+      let result = self.tracker.release_cut_data(version, changelog)?;
+
+      if json {
+         println!("{}", serde_json::to_string_pretty(&result)?);
+         return Ok(result.cut);
+      }
+
+      let list = |issues: &[ReleaseIssue]| {
+         for issue in issues {
+            eprintln!(
+               "   {} {} ({})",
+               self.config().format_issue_ref(issue.bug_num),
+               issue.title,
+               issue.status
+            );
+         }
+      };
+      if !result.cut {
+         eprintln!(
+            "{} Can't cut {version}: {} critical issue(s) still open",
+            "✗".red(),
+            result.critical_open.len()
+         );
+         list(&result.critical_open);
+         return Ok(false);
+      }
+
+      match (&result.written_to, &result.changelog) {
+         (Some(path), _) => eprintln!("✓ Added the {version} section to {path}"),
+         (None, Some(section)) => print!("{section}"),
+         (None, None) => {},
+      }
+      if !result.carried_over.is_empty() {
+         eprintln!("⚠️  {} open issue(s) still target {version}:", result.carried_over.len());
+         list(&result.carried_over);
+      }
+      Ok(true)
+   }
+
+   /// Print the gate's verdict with the issues behind it; returns whether
+   /// it passed, for the exit status
+   pub fn gate(&self, fail_on: &str, max: usize, json: bool) -> Result<bool> {
+      let result = self.tracker.gate_data(fail_on, max)?;
+
+      // Workflow commands share stdout, so JSON output goes without them
+      if gate::in_github_actions() && !result.passed && !json {
+         for issue in &result.issues {
+            println!(
+               "{}",
+               gate::github_annotation(issue, &self.config().format_issue_ref(issue.bug_num))
+            );
+         }
+      }
+
+      if json {
+         println!("{}", serde_json::to_string_pretty(&result)?);
+         return Ok(result.passed);
+      }
+
+      let verdict = format!(
+         "{} issue(s) match `{}` (at most {} allowed)",
+         result.count, result.query, result.max
+      );
+      if result.passed {
+         println!("{} Gate passed: {verdict}", "✓".green());
+      } else {
+         println!("{} Gate failed: {verdict}", "✗".red());
+      }
+
+      let now = Utc::now();
+      for issue in &result.issues {
+         let mut notes = vec![issue.priority.clone(), issue.status.clone()];
+         notes.push(format!("opened {}d ago", (now - issue.created).num_days()));
+         if !issue.tags.is_empty() {
+            notes.push(format!("#{}", issue.tags.join(" #")));
+         }
+         println!(
+            "   {} {} ({})",
+            self.config().format_issue_ref(issue.bug_num).bold(),
+            issue.title,
+            notes.join(", ").dimmed()
+         );
+         for file in &issue.files {
+            println!("      {}", file.dimmed());
+         }
+      }
+      Ok(result.passed)
+   }
+
+   /// Print the digest, or write it to `out` for cron and CI jobs
+   pub fn digest(
+      &self,
+      period: &str,
+      stale_days: i64,
+      out: Option<&str>,
+      json: bool,
+   ) -> Result<()> {
+      let result = self.tracker.digest_data(period, stale_days)?;
+      let text = if json {
+         format!("{}\n", serde_json::to_string_pretty(&result)?)
+      } else {
+         result.to_markdown(|n| self.config().format_issue_ref(n))
+      };
+
+      match out {
+         Some(path) => {
+            std::fs::write(path, text).with_context(|| format!("Failed to write {path}"))?;
+            eprintln!("✓ Wrote {period} digest to {path}");
+         },
+         None => print!("{text}"),
+      }
+      Ok(())
+   }
+
+   /// Print the standup as plain text or markdown (`format`)
+   pub fn standup(
+      &self,
+      since: Option<&str>,
+      ready: usize,
+      format: &str,
+      json: bool,
+   ) -> Result<()> {
+      let markdown = match format {
+         "text" => false,
+         "markdown" | "md" => true,
+         _ => anyhow::bail!("Unknown standup format: {format}. Use: text, markdown"),
+      };
+      let result = self.tracker.standup_data(since, ready)?;
+      if json {
+         println!("{}", serde_json::to_string_pretty(&result)?);
+      } else {
+         print!("{}", result.render(markdown, |n| self.config().format_issue_ref(n)));
+      }
+      Ok(())
+   }
+
+   /// Print the export, or write it to `out`
+   pub fn export(
+      &self,
+      status: &str,
+      scope: &ListScope,
+      where_clause: Option<&str>,
+      format: ExportFormat,
+      out: Option<&str>,
+   ) -> Result<()> {
+      let result = self
+         .tracker
+         .export_data(status, scope, where_clause, format)?;
+      match out {
+         Some(path) => {
+            std::fs::write(path, &result.text)
+               .with_context(|| format!("Failed to write {path}"))?;
+            eprintln!("✓ Wrote {} issues to {path}", result.count);
+         },
+         None => print!("{}", result.text),
+      }
+      Ok(())
+   }
+
+   pub fn crypt_init(&self, json: bool) -> Result<()> {
+      let result = self.tracker.crypt_init_data()?;
+
+      if json {
+         println!("{}", serde_json::to_string_pretty(&result)?);
+         return Ok(());
+      }
+
+      println!("✓ Created key {}", result.key_id);
+      if let Some(key_file) = &result.key_file {
+         println!("  Stored in {}", key_file.display());
+      }
+      println!(
+         "  Back it up: encrypted issues can't be read without it. Run `agentx crypt lock` to \
+          encrypt issue bodies."
+      );
+      Ok(())
+   }
+
+   pub fn crypt_set_locked(&self, locked: bool, json: bool) -> Result<()> {
+      let result = self.tracker.crypt_set_locked_data(locked)?;
+
+      if json {
+         println!("{}", serde_json::to_string_pretty(&result)?);
+         return Ok(());
+      }
+
+      let (verb, state) = if locked {
+         ("Encrypted", "locked")
+      } else {
+         ("Decrypted", "unlocked")
+      };
+      println!("✓ {verb} {} issue bodies; the store is {state}", result.rewritten);
+      Ok(())
+   }
+
+   pub fn quick_wins(
+      &self,
+      threshold: &str,
+      only_ready: bool,
+      sort: WinsSort,
+      json: bool,
+   ) -> Result<()> {
+      let quick = self.tracker.quick_wins_data(threshold, only_ready, sort)?;
+
+      if json {
+         println!("{}", serde_json::to_string_pretty(&quick)?);
          return Ok(());
       }
 
@@ -1403,258 +2724,499 @@ impl Commands {
       println!("QUICK WINS - {} tasks ≤ {threshold}", quick.len());
       println!("{}\n", "=".repeat(80));
 
-      for issue_with_id in quick {
-         let marker = issue_with_id.issue.metadata.status.marker();
-         let priority_label = format!(
-            "[{}]",
-            issue_with_id
-               .issue
-               .metadata
-               .priority
-               .to_string()
-               .to_uppercase()
+      for issue in quick {
+         let priority_label = format!("[{}]", issue.priority.to_string().to_uppercase());
+         let idle = match issue.idle_days {
+            Some(days) if sort == WinsSort::Age => format!(" · idle {days}d"),
+            _ => String::new(),
+         };
+         println!(
+            "{} {:10} ({:>5}) {}: {}{}{idle}",
+            issue.status.marker(),
+            priority_label,
+            issue.effort.as_deref().unwrap_or("?"),
+            self.config().format_issue_ref(issue.num),
+            issue.title,
+            unblocks_note(issue.unblocks)
          );
-         let effort = issue_with_id
-            .issue
-            .metadata
-            .effort
-            .as_deref()
-            .unwrap_or("?");
 
+         if !issue.files.is_empty() {
+            println!("          Files: {}", issue.files.join(", "));
+         }
+      }
+
+      Ok(())
+   }
+
+   pub fn duplicates(&self, status: &str, threshold: Option<f64>, json: bool) -> Result<()> {
+      let pairs = self.tracker.duplicates_data(status, threshold)?;
+
+      if json {
+         println!("{}", serde_json::to_string_pretty(&pairs)?);
+         return Ok(());
+      }
+
+      if pairs.is_empty() {
+         println!("No likely duplicates among {status} issues");
+         return Ok(());
+      }
+
+      println!("\n{}", "=".repeat(80));
+      println!("LIKELY DUPLICATES - {} pairs", pairs.len());
+      println!("{}\n", "=".repeat(80));
+
+      for pair in &pairs {
          println!(
-            "{} {:10} ({:>5}) {}: {}",
-            marker,
-            priority_label,
-            effort,
-            self.config.format_issue_ref(issue_with_id.id),
-            issue_with_id.issue.metadata.title
+            "{:>3.0}%  {}: {}",
+            pair.similarity * 100.0,
+            self.config().format_issue_ref(pair.first),
+            pair.first_title
          );
+         println!("      {}: {}\n", self.config().format_issue_ref(pair.second), pair.second_title);
+      }
+
+      Ok(())
+   }
+
+   pub fn bulk_start(&self, bug_refs: Vec<String>, json: bool) -> Result<()> {
+      let mut results = Vec::new();
+      let (targets, mut errors) = self.resolve_bulk(bug_refs, false);
+
+      if errors.is_empty() {
+         errors.extend(self.tracker.all_or_nothing(|| {
+            for (bug_ref, bug_num) in targets {
+               let update = self
+                  .tracker
+                  .check_transition(bug_num, "start", Status::InProgress)
+                  .and_then(|()| self.tracker.check_dependencies(bug_num))
+                  .and_then(|_| {
+                     self.storage().update_issue_metadata(bug_num, |meta| {
+                        meta.status = Status::InProgress;
+                        meta.started = Some(Utc::now());
+                     })
+                  });
+               match update {
+                  Ok(()) => results.push(bug_num),
+                  Err(e) => return Some((bug_ref, e.to_string())),
+               }
+            }
+            None
+         })?);
+      }
+      let rolled_back = Self::rolled_back(&mut results, &errors);
 
-         if !issue_with_id.issue.metadata.files.is_empty() {
-            println!("          Files: {}", issue_with_id.issue.metadata.files.join(", "));
+      if json {
+         let output = json!({
+             "started": results,
+             "rolled_back": rolled_back,
+             "errors": errors,
+         });
+         println!("{}", serde_json::to_string_pretty(&output)?);
+      } else {
+         if !results.is_empty() {
+            println!("🔄 Started {} issues:", results.len());
+            for bug_num in &results {
+               println!("   {}", self.config().format_issue_ref(*bug_num));
+            }
          }
+         self.print_bulk_errors(&rolled_back, &errors);
+      }
+      Self::bulk_outcome(&errors)
+   }
+
+   pub fn bulk_close(
+      &self,
+      bug_refs: Vec<String>,
+      message: Option<String>,
+      auto_unblock: bool,
+      json: bool,
+   ) -> Result<()> {
+      let mut results = Vec::new();
+      let mut unblocked = Vec::new();
+      let mut unblock_candidates: Vec<UnblockCandidate> = Vec::new();
+      let (targets, mut errors) = self.resolve_bulk(bug_refs, true);
+      let nums: Vec<u32> = targets.iter().map(|&(_, n)| n).collect();
+      if errors.is_empty() && !self.tracker.confirm_bulk("close", &nums, json)? {
+         return Ok(());
+      }
+
+      if errors.is_empty() {
+         errors.extend(self.tracker.all_or_nothing(|| {
+            for (bug_ref, bug_num) in targets {
+               match self.close_one(bug_num, message.as_deref()) {
+                  Ok((released, candidates)) => {
+                     results.push(bug_num);
+                     unblocked.extend(released);
+                     for candidate in candidates {
+                        if !unblock_candidates
+                           .iter()
+                           .any(|c| c.bug_num == candidate.bug_num)
+                        {
+                           unblock_candidates.push(candidate);
+                        }
+                     }
+                  },
+                  Err(e) => return Some((bug_ref, e.to_string())),
+               }
+            }
+            None
+         })?);
+      }
+      let rolled_back = Self::rolled_back(&mut results, &errors);
+      if !rolled_back.is_empty() {
+         unblocked.clear();
+         unblock_candidates.clear();
+      }
+
+      // Issues closed later in the batch aren't waiting on anything anymore
+      unblocked.retain(|issue: &UnblockedIssue| !results.contains(&issue.bug_num));
+      unblock_candidates.retain(|c| !results.contains(&c.bug_num));
+      if json {
+         self.settle_unblock_candidates(&mut unblock_candidates, auto_unblock, true)?;
+         let output = json!({
+             "closed": results,
+             "unblocked": unblocked,
+             "unblock_candidates": unblock_candidates,
+             "rolled_back": rolled_back,
+             "errors": errors,
+         });
+         println!("{}", serde_json::to_string_pretty(&output)?);
+      } else {
+         if !results.is_empty() {
+            println!("✓ Closed {} issues:", results.len());
+            for bug_num in &results {
+               println!("   {}", self.config().format_issue_ref(*bug_num));
+            }
+         }
+         self.print_unblocked(&unblocked);
+         self.settle_unblock_candidates(&mut unblock_candidates, auto_unblock, false)?;
+         self.print_unblock_candidates(&unblock_candidates);
+         self.print_bulk_errors(&rolled_back, &errors);
+      }
+      Self::bulk_outcome(&errors)
+   }
+
+   /// Close one issue of a `bulk-close`, returning the dependents it
+   /// released and the blocked issues that may be unblocked now
+   fn close_one(
+      &self,
+      bug_num: u32,
+      message: Option<&str>,
+   ) -> Result<(Vec<UnblockedIssue>, Vec<UnblockCandidate>)> {
+      self
+         .tracker
+         .check_transition(bug_num, "close", Status::Closed)?;
+      self.storage().update_issue_metadata(bug_num, |meta| {
+         meta.status = Status::Closed;
+         meta.closed = Some(Utc::now());
+      })?;
+
+      // Add close note if provided
+      if let Some(note) = message
+         && let Ok(base) = self.storage().load_issue(bug_num)
+      {
+         let timestamp = Utc::now().format("%Y-%m-%d").to_string();
+         let mut issue = base.clone();
+         issue
+            .body
+            .append(&format!("\n\n---\n\n**Closed** ({timestamp}): {note}\n"));
+         self.tracker.save_edited(&base, &issue, bug_num, true)?;
       }
 
-      Ok(())
+      // Move to closed directory
+      self.storage().move_issue(bug_num, false)?;
+      let released = self.tracker.release_dependents(bug_num)?;
+      Ok((released, self.tracker.unblock_candidates(bug_num)?))
    }
 
-   pub fn bulk_start(&self, bug_refs: Vec<String>, json: bool) -> Result<()> {
-      let mut results = Vec::new();
+   /// Resolve the refs given to a bulk command, each issue once, with the
+   /// refs that don't resolve as errors
+   /// Resolve each of `bug_refs`, dropping repeats; `strict` is for bulk
+   /// changes that close issues (see [`IssueTracker::resolve_ref_strict`])
+   fn resolve_bulk(
+      &self,
+      bug_refs: Vec<String>,
+      strict: bool,
+   ) -> (Vec<(String, u32)>, Vec<BulkError>) {
+      let mut targets: Vec<(String, u32)> = Vec::new();
       let mut errors = Vec::new();
-
       for bug_ref in bug_refs {
-         match self.storage.resolve_bug_ref(&bug_ref) {
-            Ok(bug_num) => {
-               if let Err(e) = self.storage.update_issue_metadata(bug_num, |meta| {
-                  meta.status = Status::InProgress;
-                  meta.started = Some(Utc::now());
-               }) {
-                  errors.push((bug_ref, e.to_string()));
-               } else {
-                  results.push(bug_num);
-               }
-            },
-            Err(e) => {
-               errors.push((bug_ref, e.to_string()));
-            },
+         let resolved = if strict {
+            self.tracker.resolve_ref_strict(&bug_ref)
+         } else {
+            self.tracker.resolve_ref(&bug_ref)
+         };
+         match resolved {
+            Ok(bug_num) if targets.iter().any(|&(_, n)| n == bug_num) => {},
+            Ok(bug_num) => targets.push((bug_ref, bug_num)),
+            Err(e) => errors.push((bug_ref, e.to_string())),
          }
       }
+      (targets, errors)
+   }
 
-      if json {
-         let output = json!({
-             "started": results,
-             "errors": errors,
-         });
-         println!("{}", serde_json::to_string_pretty(&output)?);
+   /// The issues a failed bulk change applied before it was rolled back,
+   /// taken out of `results`
+   fn rolled_back(results: &mut Vec<u32>, errors: &[BulkError]) -> Vec<u32> {
+      if errors.is_empty() {
+         Vec::new()
       } else {
-         if !results.is_empty() {
-            println!("🔄 Started {} issues:", results.len());
-            for bug_num in &results {
-               println!("   {}", self.config.format_issue_ref(*bug_num));
-            }
-         }
+         std::mem::take(results)
+      }
+   }
 
-         if !errors.is_empty() {
-            println!("\n❌ Errors:");
-            for (bug_ref, error) in &errors {
-               println!("   {bug_ref}: {error}");
-            }
+   fn print_bulk_errors(&self, rolled_back: &[u32], errors: &[BulkError]) {
+      if !rolled_back.is_empty() {
+         let refs: Vec<String> = rolled_back
+            .iter()
+            .map(|&n| self.config().format_issue_ref(n))
+            .collect();
+         println!("↩️  Rolled back {} issues after the error: {}", refs.len(), refs.join(", "));
+      }
+      if !errors.is_empty() {
+         println!("\n❌ Errors:");
+         for (bug_ref, error) in errors {
+            println!("   {bug_ref}: {error}");
          }
       }
+   }
 
+   fn bulk_outcome(errors: &[BulkError]) -> Result<()> {
+      if !errors.is_empty() {
+         anyhow::bail!("Nothing was changed");
+      }
       Ok(())
    }
 
-   pub fn bulk_close(
+   #[allow(clippy::too_many_arguments)]
+   pub fn bulk_edit(
       &self,
       bug_refs: Vec<String>,
-      message: Option<String>,
+      where_clause: Option<&str>,
+      priority: Option<&str>,
+      add_tags: Vec<String>,
+      remove_tags: Vec<String>,
+      milestone: Option<&str>,
+      target_version: Option<&str>,
+      due: Option<&str>,
+      assignee: Option<&str>,
       json: bool,
    ) -> Result<()> {
-      let mut results = Vec::new();
-      let mut errors = Vec::new();
+      if priority.is_none()
+         && add_tags.is_empty()
+         && remove_tags.is_empty()
+         && milestone.is_none()
+         && target_version.is_none()
+         && due.is_none()
+         && assignee.is_none()
+      {
+         anyhow::bail!(
+            "Nothing to change: specify --priority, --add-tag, --remove-tag, --milestone, \
+             --target-version, --due or --assignee"
+         );
+      }
+      // An empty --due clears the due time
+      let due = due
+         .map(|d| match d.trim() {
+            "" => Ok(None),
+            d => self.config().parse_due(d, Utc::now()).map(Some),
+         })
+         .transpose()?;
+
+      let priority = priority
+         .map(|p| match p {
+            "critical" => Ok(Priority::Critical),
+            "high" => Ok(Priority::High),
+            "medium" => Ok(Priority::Medium),
+            "low" => Ok(Priority::Low),
+            _ => Err(anyhow::anyhow!("Invalid priority: {p}")),
+         })
+         .transpose()?;
 
-      for bug_ref in bug_refs {
-         match self.storage.resolve_bug_ref(&bug_ref) {
-            Ok(bug_num) => {
-               // Update metadata
-               if let Err(e) = self.storage.update_issue_metadata(bug_num, |meta| {
-                  meta.status = Status::Closed;
-                  meta.closed = Some(Utc::now());
-               }) {
-                  errors.push((bug_ref.clone(), e.to_string()));
-                  continue;
-               }
+      // Normalize tags: lowercase, trim, remove # prefix if present
+      let normalize_tag =
+         |t: &String| -> SmolStr { t.trim().trim_start_matches('#').to_lowercase().into() };
+      let add_tags: Vec<SmolStr> = add_tags.iter().map(normalize_tag).collect();
+      let remove_tags: Vec<SmolStr> = remove_tags.iter().map(normalize_tag).collect();
+
+      let (targets, mut errors) = self.resolve_bulk(bug_refs, false);
+      let mut targets: Vec<u32> = targets.into_iter().map(|(_, n)| n).collect();
+
+      if let Some(expr) = where_clause {
+         let query = Query::parse(expr)?;
+         let matching = query.filter(self.storage().list_open_issues()?);
+         targets.extend(matching.iter().map(|i| i.id));
+      }
+
+      targets.sort_unstable();
+      targets.dedup();
+
+      if targets.is_empty() && errors.is_empty() {
+         anyhow::bail!("No issues selected: pass issue references and/or --where <expr>");
+      }
+      if errors.is_empty() && !self.tracker.confirm_bulk("edit", &targets, json)? {
+         return Ok(());
+      }
 
-               // Add close note if provided
-               if let Some(note) = &message
-                  && let Ok(mut issue) = self.storage.load_issue(bug_num)
-               {
-                  let timestamp = Utc::now().format("%Y-%m-%d").to_string();
-                  issue
-                     .body
-                     .push_str(&format!("\n\n---\n\n**Closed** ({timestamp}): {note}\n"));
-                  if let Err(e) = self.storage.save_issue(&issue, bug_num, true) {
-                     errors.push((bug_ref.clone(), e.to_string()));
-                     continue;
+      let mut results = Vec::new();
+      if errors.is_empty() {
+         errors.extend(self.tracker.all_or_nothing(|| {
+            for bug_num in targets {
+               let update = self.storage().update_issue_metadata(bug_num, |meta| {
+                  if let Some(priority) = priority {
+                     meta.priority = priority;
                   }
-               }
+                  for tag in &add_tags {
+                     if !meta.tags.contains(tag) {
+                        meta.tags.push(tag.clone());
+                     }
+                  }
+                  meta.tags.retain(|t| !remove_tags.contains(t));
+                  meta.tags.sort();
+                  if let Some(milestone) = milestone {
+                     let milestone = milestone.trim();
+                     meta.milestone = (!milestone.is_empty()).then(|| milestone.into());
+                  }
+                  if let Some(version) = target_version {
+                     let version = version.trim();
+                     meta.target_version = (!version.is_empty()).then(|| version.into());
+                  }
+                  if let Some(due) = due {
+                     meta.due = due;
+                  }
+                  if let Some(assignee) = assignee {
+                     let assignee = assignee.trim();
+                     meta.assignee = (!assignee.is_empty()).then(|| assignee.into());
+                  }
+               });
 
-               // Move to closed directory
-               if let Err(e) = self.storage.move_issue(bug_num, false) {
-                  errors.push((bug_ref, e.to_string()));
-               } else {
-                  results.push(bug_num);
+               match update {
+                  Ok(()) => results.push(bug_num),
+                  Err(e) => return Some((bug_num.to_string(), e.to_string())),
                }
-            },
-            Err(e) => {
-               errors.push((bug_ref, e.to_string()));
-            },
-         }
+            }
+            None
+         })?);
       }
+      let rolled_back = Self::rolled_back(&mut results, &errors);
 
       if json {
          let output = json!({
-             "closed": results,
+             "updated": results,
+             "rolled_back": rolled_back,
              "errors": errors,
          });
          println!("{}", serde_json::to_string_pretty(&output)?);
       } else {
          if !results.is_empty() {
-            println!("✓ Closed {} issues:", results.len());
+            println!("✓ Updated {} issues:", results.len());
             for bug_num in &results {
-               println!("   {}", self.config.format_issue_ref(*bug_num));
-            }
-         }
-
-         if !errors.is_empty() {
-            println!("\n❌ Errors:");
-            for (bug_ref, error) in &errors {
-               println!("   {bug_ref}: {error}");
+               println!("   {}", self.config().format_issue_ref(*bug_num));
             }
          }
+         self.print_bulk_errors(&rolled_back, &errors);
       }
-
-      Ok(())
+      Self::bulk_outcome(&errors)
    }
 
    pub fn summary(&self, hours: Option<u64>, json: bool) -> Result<()> {
-      let hours = hours.unwrap_or(24);
-      let since = Utc::now() - Duration::hours(hours as i64);
-
-      let all_issues = self.storage.list_open_issues()?;
-      let closed_issues = self.storage.list_closed_issues()?;
-
-      let mut started = Vec::new();
-      let mut closed = Vec::new();
-      let mut checkpointed = Vec::new();
-
-      // Check open issues for recent activity
-      for issue_with_id in &all_issues {
-         if let Some(started_time) = issue_with_id.issue.metadata.started
-            && started_time > since
-         {
-            started.push(issue_with_id);
-         }
-
-         // Check for recent checkpoints in body
-         if issue_with_id.issue.body.contains("**Checkpoint**") {
-            // Simple heuristic: if body contains checkpoint, include it
-            checkpointed.push(issue_with_id);
-         }
-      }
-
-      // Check closed issues
-      for issue_with_id in &closed_issues {
-         if let Some(closed_time) = issue_with_id.issue.metadata.closed
-            && closed_time > since
-         {
-            closed.push(issue_with_id);
-         }
-      }
+      let summary = self.tracker.summary_data(hours)?;
+      let hours = summary.hours;
 
       if json {
-         let output = json!({
-             "since": since.to_rfc3339(),
-             "hours": hours,
-             "started": started.iter().map(|i| i.id).collect::<Vec<_>>(),
-             "closed": closed.iter().map(|i| i.id).collect::<Vec<_>>(),
-             "checkpointed": checkpointed.iter().map(|i| i.id).collect::<Vec<_>>(),
-         });
-         println!("{}", serde_json::to_string_pretty(&output)?);
+         println!("{}", serde_json::to_string_pretty(&summary)?);
          return Ok(());
       }
 
+      let title = |id: u32| {
+         self
+            .storage()
+            .load_issue(id)
+            .map(|i| i.metadata.title.to_string())
+            .unwrap_or_default()
+      };
+
       println!("\n{}", "=".repeat(80));
       println!("SESSION SUMMARY - Last {hours} hours");
       println!("{}\n", "=".repeat(80));
 
-      if !started.is_empty() {
-         println!("🔄 Started ({}):", started.len());
-         for issue_with_id in &started {
-            println!(
-               "   {}: {}",
-               self.config.format_issue_ref(issue_with_id.id),
-               issue_with_id.issue.metadata.title
-            );
+      if !summary.started.is_empty() {
+         println!("🔄 Started ({}):", summary.started.len());
+         for &id in &summary.started {
+            println!("   {}: {}", self.config().format_issue_ref(id), title(id));
          }
          println!();
       }
 
-      if !closed.is_empty() {
-         println!("✅ Closed ({}):", closed.len());
-         for issue_with_id in &closed {
-            println!(
-               "   {}: {}",
-               self.config.format_issue_ref(issue_with_id.id),
-               issue_with_id.issue.metadata.title
-            );
+      if !summary.closed.is_empty() {
+         println!("✅ Closed ({}):", summary.closed.len());
+         for &id in &summary.closed {
+            println!("   {}: {}", self.config().format_issue_ref(id), title(id));
          }
          println!();
       }
 
-      if !checkpointed.is_empty() {
-         println!("📝 Checkpointed ({}):", checkpointed.len());
-         for issue_with_id in &checkpointed {
-            println!(
-               "   {}: {}",
-               self.config.format_issue_ref(issue_with_id.id),
-               issue_with_id.issue.metadata.title
-            );
+      if !summary.checkpointed.is_empty() {
+         println!("📝 Checkpointed ({}):", summary.checkpointed.len());
+         for issue in &summary.checkpointed {
+            println!("   {}: {}", self.config().format_issue_ref(issue.bug_num), issue.title);
+            for checkpoint in &issue.checkpoints {
+               println!("      {} {}", checkpoint.at.format("%m-%d %H:%M"), checkpoint.note);
+            }
          }
          println!();
       }
 
-      if started.is_empty() && closed.is_empty() && checkpointed.is_empty() {
+      if summary.started.is_empty() && summary.closed.is_empty() && summary.checkpointed.is_empty()
+      {
          println!("No activity in the last {hours} hours");
       }
 
       Ok(())
    }
 
+   pub fn session_start(&self, note: Option<String>, json: bool) -> Result<()> {
+      let session = self.tracker.session_start_data(note)?;
+
+      if json {
+         println!("{}", serde_json::to_string_pretty(&session)?);
+      } else {
+         println!("⏱️  Session started at {}", session.started.format("%H:%M UTC"));
+      }
+
+      Ok(())
+   }
+
+   pub fn session_status(&self, json: bool) -> Result<()> {
+      let summary = self.tracker.session_status_data()?;
+
+      if json {
+         println!("{}", serde_json::to_string_pretty(&summary)?);
+      } else if let Some(summary) = summary {
+         print!("{}", summary.to_markdown(|n| self.config().format_issue_ref(n)));
+      } else {
+         println!("No session running");
+      }
+
+      Ok(())
+   }
+
+   pub fn session_end(&self, annotate: bool, json: bool) -> Result<()> {
+      let summary = self.tracker.session_end_data(annotate)?;
+
+      if json {
+         println!("{}", serde_json::to_string_pretty(&summary)?);
+         return Ok(());
+      }
+
+      print!("{}", summary.to_markdown(|n| self.config().format_issue_ref(n)));
+      println!("✓ Session ended; summary written to {}", self.storage().journal_file().display());
+      Ok(())
+   }
+
    pub fn dependencies(&self, bug_ref: &str, json: bool) -> Result<()> {
-      let bug_num = self.storage.resolve_bug_ref(bug_ref)?;
-      let issue = self.storage.load_issue(bug_num)?;
+      let bug_num = self.tracker.resolve_ref(bug_ref)?;
+      let issue = self.storage().load_issue(bug_num)?;
 
       // Find what this issue depends on
       let depends_on: Vec<_> = issue
@@ -1663,7 +3225,7 @@ impl Commands {
          .iter()
          .filter_map(|&dep_num| {
             self
-               .storage
+               .storage()
                .load_issue(dep_num)
                .ok()
                .map(|dep_issue| (dep_num, dep_issue))
@@ -1671,11 +3233,12 @@ impl Commands {
          .collect();
 
       // Find what depends on this issue
-      let all_issues = self.storage.list_open_issues()?;
+      let all_issues = self.storage().list_open_issues()?;
       let blocks: Vec<_> = all_issues
          .iter()
          .filter(|issue_with_id| issue_with_id.issue.metadata.depends_on.contains(&bug_num))
          .collect();
+      let (_, referenced_by) = self.tracker.cross_references(bug_num, &issue)?;
 
       if json {
          let output = json!({
@@ -1697,6 +3260,7 @@ impl Commands {
                      "status": issue_with_id.issue.metadata.status.to_string(),
                  })
              }).collect::<Vec<_>>(),
+             "referenced_by": referenced_by.iter().map(LinkedIssue::of).collect::<Vec<_>>(),
          });
          println!("{}", serde_json::to_string_pretty(&output)?);
          return Ok(());
@@ -1705,7 +3269,7 @@ impl Commands {
       println!("\n{}", "=".repeat(80));
       println!(
          "DEPENDENCIES - {}: {}",
-         self.config.format_issue_ref(bug_num),
+         self.config().format_issue_ref(bug_num),
          issue.metadata.title
       );
       println!("{}\n", "=".repeat(80));
@@ -1715,7 +3279,7 @@ impl Commands {
          for (dep_num, dep) in &depends_on {
             println!(
                "   {} [{}]: {}",
-               self.config.format_issue_ref(*dep_num),
+               self.config().format_issue_ref(*dep_num),
                dep.metadata.status,
                dep.metadata.title
             );
@@ -1730,7 +3294,7 @@ impl Commands {
          for issue_with_id in &blocks {
             println!(
                "   {} [{}]: {}",
-               self.config.format_issue_ref(issue_with_id.id),
+               self.config().format_issue_ref(issue_with_id.id),
                issue_with_id.issue.metadata.status,
                issue_with_id.issue.metadata.title
             );
@@ -1740,6 +3304,19 @@ impl Commands {
          println!("⬆️  Blocks: (none)\n");
       }
 
+      if !referenced_by.is_empty() {
+         println!("🔗 Referenced by ({}):", referenced_by.len());
+         for issue_with_id in &referenced_by {
+            println!(
+               "   {} [{}]: {}",
+               self.config().format_issue_ref(issue_with_id.id),
+               issue_with_id.issue.metadata.status,
+               issue_with_id.issue.metadata.title
+            );
+         }
+         println!();
+      }
+
       Ok(())
    }
 
@@ -1750,87 +3327,20 @@ impl Commands {
       remove_deps: Vec<String>,
       json: bool,
    ) -> Result<()> {
-      let bug_num = self.storage.resolve_bug_ref(bug_ref)?;
-
-      // Resolve all dependency references
-      let mut add_nums = Vec::new();
-      for dep_ref in &add_deps {
-         let dep_num = self.storage.resolve_bug_ref(dep_ref)?;
-         // Verify dependency exists
-         self.storage.load_issue(dep_num)?;
-         add_nums.push(dep_num);
-      }
-
-      let mut remove_nums = Vec::new();
-      for dep_ref in &remove_deps {
-         let dep_num = self.storage.resolve_bug_ref(dep_ref)?;
-         remove_nums.push(dep_num);
-      }
-
-      // Check for cycles before adding
-      for &dep_num in &add_nums {
-         if self.would_create_cycle(bug_num, dep_num)? {
-            anyhow::bail!(
-               "Adding {} as dependency would create a cycle ({} transitively depends on {})",
-               self.config.format_issue_ref(dep_num),
-               self.config.format_issue_ref(dep_num),
-               self.config.format_issue_ref(bug_num)
-            );
-         }
-      }
-
-      // Update dependencies
-      self.storage.update_issue_metadata(bug_num, |meta| {
-         // Add new dependencies
-         for dep_num in add_nums.iter() {
-            if !meta.depends_on.contains(dep_num) {
-               meta.depends_on.push(*dep_num);
-            }
-         }
-
-         // Remove dependencies
-         meta.depends_on.retain(|&d| !remove_nums.contains(&d));
-
-         // Sort for consistent ordering
-         meta.depends_on.sort_unstable();
-      })?;
-
-      // Update reverse dependencies (blocks field)
-      for &dep_num in &add_nums {
-         self.storage.update_issue_metadata(dep_num, |meta| {
-            if !meta.blocks.contains(&bug_num) {
-               meta.blocks.push(bug_num);
-            }
-            meta.blocks.sort_unstable();
-         })?;
-      }
-
-      for &dep_num in &remove_nums {
-         self.storage.update_issue_metadata(dep_num, |meta| {
-            meta.blocks.retain(|&b| b != bug_num);
-         })?;
-      }
-
-      // Load updated issue
-      let issue = self.storage.load_issue(bug_num)?;
+      let result = self.tracker.depend_data(bug_ref, add_deps, remove_deps)?;
+      let DependResult { bug_num, added: add_nums, removed: remove_nums, depends_on } = &result;
 
       if json {
-         let output = json!({
-             "bug_num": bug_num,
-             "added": add_nums,
-             "removed": remove_nums,
-             "depends_on": issue.metadata.depends_on,
-         });
-         println!("{}", serde_json::to_string_pretty(&output)?);
+         println!("{}", serde_json::to_string_pretty(&result)?);
       } else {
-         println!("✓ Updated dependencies for {}", self.config.format_issue_ref(bug_num));
+         println!("✓ Updated dependencies for {}", self.config().format_issue_ref(*bug_num));
 
          if !add_nums.is_empty() {
             println!(
                "  Added: {}",
                add_nums
                   .iter()
-                  .map(|n| self.config.format_issue_ref(*n))
+                  .map(|n| self.config().format_issue_ref(*n))
                   .collect::<Vec<_>>()
                   .join(", ")
             );
@@ -1841,20 +3351,18 @@ impl Commands {
                "  Removed: {}",
                remove_nums
                   .iter()
-                  .map(|n| self.config.format_issue_ref(*n))
+                  .map(|n| self.config().format_issue_ref(*n))
                   .collect::<Vec<_>>()
                   .join(", ")
             );
          }
 
-         if !issue.metadata.depends_on.is_empty() {
+         if !depends_on.is_empty() {
             println!(
                "  Now depends on: {}",
-               issue
-                  .metadata
-                  .depends_on
+               depends_on
                   .iter()
-                  .map(|n| self.config.format_issue_ref(*n))
+                  .map(|n| self.config().format_issue_ref(*n))
                   .collect::<Vec<_>>()
                   .join(", ")
             );
@@ -1874,8 +3382,8 @@ impl Commands {
       list_only: bool,
       json: bool,
    ) -> Result<()> {
-      let bug_num = self.storage.resolve_bug_ref(bug_ref)?;
-      let issue = self.storage.load_issue(bug_num)?;
+      let bug_num = self.tracker.resolve_ref(bug_ref)?;
+      let issue = self.storage().load_issue(bug_num)?;
 
       if list_only {
          if json {
@@ -1885,7 +3393,7 @@ impl Commands {
             });
             println!("{}", serde_json::to_string_pretty(&output)?);
          } else {
-            println!("Tags for {}:", self.config.format_issue_ref(bug_num));
+            println!("Tags for {}:", self.config().format_issue_ref(bug_num));
             if issue.metadata.tags.is_empty() {
                println!("  (no tags)");
             } else {
@@ -1908,7 +3416,7 @@ impl Commands {
       let remove_tags: Vec<String> = remove_tags.iter().map(|t| normalize_tag(t)).collect();
 
       // Update tags
-      self.storage.update_issue_metadata(bug_num, |meta| {
+      self.storage().update_issue_metadata(bug_num, |meta| {
          // Add new tags
          for tag in &add_tags {
             let tag_smol = SmolStr::from(tag.as_str());
@@ -1929,7 +3437,7 @@ impl Commands {
       })?;
 
       // Load updated issue
-      let updated_issue = self.storage.load_issue(bug_num)?;
+      let updated_issue = self.storage().load_issue(bug_num)?;
 
       if json {
          let output = json!({
@@ -1940,7 +3448,7 @@ impl Commands {
          });
          println!("{}", serde_json::to_string_pretty(&output)?);
       } else {
-         println!("✓ Updated tags for {}", self.config.format_issue_ref(bug_num));
+         println!("✓ Updated tags for {}", self.config().format_issue_ref(bug_num));
 
          if !add_tags.is_empty() {
             println!(
@@ -1983,56 +3491,86 @@ impl Commands {
       Ok(())
    }
 
-   fn would_create_cycle(&self, bug_num: u32, dep_num: u32) -> Result<bool> {
-      // Check if dep_num transitively depends on bug_num
-      // If so, adding bug_num -> dep_num would create a cycle
+   pub fn tags_stats(&self, json: bool) -> Result<()> {
+      let stats = self.tracker.tags_stats_data()?;
 
-      let mut visited = std::collections::HashSet::new();
-      let mut stack = vec![dep_num];
+      if json {
+         println!("{}", serde_json::to_string_pretty(&stats)?);
+         return Ok(());
+      }
 
-      while let Some(current) = stack.pop() {
-         if current == bug_num {
-            return Ok(true); // Cycle detected
-         }
+      if stats.is_empty() {
+         println!("No tagged issues");
+         return Ok(());
+      }
 
-         if visited.contains(&current) {
-            continue;
-         }
-         visited.insert(current);
+      println!("\n{}", "=".repeat(80));
+      println!("TAG STATS - {} tags", stats.len());
+      println!("{}\n", "=".repeat(80));
+      println!("  {:<24} {:>6} {:>6} {:>8} {:>12}", "TAG", "TOTAL", "OPEN", "CLOSED", "AVG CLOSE");
 
-         // Add all dependencies of current to stack
-         if let Ok(issue) = self.storage.load_issue(current) {
-            for &dep in &issue.metadata.depends_on {
-               if !visited.contains(&dep) {
-                  stack.push(dep);
-               }
-            }
-         }
+      for stat in &stats {
+         let avg_close = stat
+            .avg_close_hours
+            .map(format_hours)
+            .unwrap_or_else(|| "-".to_string());
+         println!(
+            "  {:<24} {:>6} {:>6} {:>8} {:>12}",
+            format!("#{}", stat.tag),
+            stat.count,
+            stat.open,
+            stat.closed,
+            avg_close
+         );
       }
 
-      Ok(false)
+      Ok(())
    }
 
-   pub fn critical_path(&self, json: bool) -> Result<()> {
-      let issues = self.storage.list_open_issues()?;
+   pub fn tags_suggest(&self, title: &str, files: Vec<String>, json: bool) -> Result<()> {
+      let suggestions = self
+         .tracker
+         .suggest_tags_data(title, &files, &[] as &[&str])?;
+
+      if json {
+         println!("{}", serde_json::to_string_pretty(&suggestions)?);
+         return Ok(());
+      }
 
-      // Build dependency graph using Tarjan's algorithm for robustness
-      // Find strongly connected components (cycles) and longest acyclic path
+      if suggestions.is_empty() {
+         println!("No tag suggestions (not enough tagged history for these keywords/files)");
+         return Ok(());
+      }
+
+      println!("Suggested tags:");
+      for suggestion in &suggestions {
+         println!(
+            "  #{:<20} {:>3.0}%  via {}",
+            suggestion.tag,
+            suggestion.confidence * 100.0,
+            suggestion.reasons.join(", ")
+         );
+      }
+
+      Ok(())
+   }
 
-      let issue_map: std::collections::HashMap<u32, &crate::issue::IssueWithId> =
-         issues.iter().map(|i| (i.id, i)).collect();
+   pub fn critical_path(&self, json: bool) -> Result<()> {
+      let result = self.tracker.critical_path_data()?;
 
-      // Detect cycles using Tarjan's algorithm
-      let cycles = Self::find_cycles(&issues);
+      if json {
+         println!("{}", serde_json::to_string_pretty(&result)?);
+         return Ok(());
+      }
 
-      if !cycles.is_empty() && !json {
+      if !result.cycles.is_empty() {
          println!("\n⚠️  Warning: Dependency cycles detected:");
-         for cycle in &cycles {
+         for cycle in &result.cycles {
             println!(
                "   {}",
                cycle
                   .iter()
-                  .map(|id| self.config.format_issue_ref(*id))
+                  .map(|id| self.config().format_issue_ref(*id))
                   .collect::<Vec<_>>()
                   .join(" → ")
             );
@@ -2040,532 +3578,702 @@ impl Commands {
          println!();
       }
 
-      // Find longest path (critical path)
-      let mut longest_chain = Vec::new();
-      let mut visited = std::collections::HashSet::new();
-
-      fn find_chain(
-         issue_id: u32,
-         issues: &[crate::issue::IssueWithId],
-         visited: &mut std::collections::HashSet<u32>,
-         current_chain: &mut Vec<u32>,
-         longest: &mut Vec<u32>,
-      ) {
-         if visited.contains(&issue_id) {
-            return; // Cycle or already visited
-         }
-
-         visited.insert(issue_id);
-         current_chain.push(issue_id);
-
-         if current_chain.len() > longest.len() {
-            *longest = current_chain.clone();
-         }
+      if result.chain.is_empty() {
+         println!("No dependency chains found");
+         return Ok(());
+      }
 
-         // Find all issues that depend on this one
-         for issue_with_id in issues {
-            if issue_with_id.issue.metadata.depends_on.contains(&issue_id) {
-               find_chain(issue_with_id.id, issues, visited, current_chain, longest);
-            }
-         }
+      println!("\n{}", "=".repeat(80));
+      println!(
+         "CRITICAL PATH - Most effort through dependencies ({} issues, {})",
+         result.length,
+         hours(result.total_minutes)
+      );
+      println!("{}\n", "=".repeat(80));
 
-         current_chain.pop();
-         visited.remove(&issue_id);
+      for (i, step) in result.chain.iter().enumerate() {
+         let arrow = if i == 0 { "▶" } else { "↓" };
+         let node = &step.node;
+         let effort = if step.estimated {
+            hours(step.effort_minutes)
+         } else {
+            format!("~{}", hours(step.effort_minutes))
+         };
+         println!(
+            "{} {} [{}] [{}] {:>6} {:>6}: {}",
+            arrow,
+            self.config().format_issue_ref(node.id),
+            node.status,
+            node.priority,
+            effort,
+            hours(step.cumulative_minutes),
+            node.title
+         );
       }
 
-      // Try starting from each issue
-      for issue_with_id in &issues {
-         let mut current_chain = Vec::new();
-         find_chain(
-            issue_with_id.id,
-            &issues,
-            &mut visited,
-            &mut current_chain,
-            &mut longest_chain,
+      if result.unestimated > 0 {
+         println!(
+            "\n~ {} issue(s) without an estimate counted as {}",
+            result.unestimated,
+            self.config().effort.unestimated
          );
       }
 
-      if json {
-         let chain_details: Vec<_> = longest_chain
-            .iter()
-            .filter_map(|&id| issue_map.get(&id).copied())
-            .map(|issue_with_id| {
-               json!({
-                   "num": issue_with_id.id,
-                   "title": issue_with_id.issue.metadata.title,
-                   "status": issue_with_id.issue.metadata.status.to_string(),
-                   "priority": issue_with_id.issue.metadata.priority.to_string(),
-               })
-            })
-            .collect();
+      Ok(())
+   }
 
-         let output = json!({
-             "length": longest_chain.len(),
-             "chain": chain_details,
-         });
-         println!("{}", serde_json::to_string_pretty(&output)?);
+   /// List dependency cycles; with `fix`, drop an edge from each, asking
+   /// which at a terminal unless `yes`
+   pub fn cycles(&self, fix: bool, yes: bool, json: bool) -> Result<()> {
+      let interactive = self.tracker.ref_mode() == RefMode::Interactive && !json;
+      let result = if fix && yes {
+         self.tracker.fix_cycles_data()?
+      } else if fix && interactive {
+         self.fix_cycles_interactively()?
+      } else {
+         CyclesResult { cycles: self.tracker.cycle_repairs_data()?, dropped: Vec::new() }
+      };
+
+      if json {
+         println!("{}", serde_json::to_string_pretty(&result)?);
          return Ok(());
       }
 
-      if longest_chain.is_empty() {
-         println!("No dependency chains found");
+      if !interactive {
+         for edge in &result.dropped {
+            println!("✓ Dropped {}", self.format_edge(edge));
+         }
+      }
+      if result.cycles.is_empty() {
+         println!("✓ No dependency cycles");
          return Ok(());
       }
+      if !result.dropped.is_empty() {
+         println!();
+      }
+      for cycle in &result.cycles {
+         self.print_cycle(cycle);
+      }
+      if fix {
+         println!("💡 Use --yes to drop the suggested edges without asking");
+      } else {
+         println!("💡 Run `agentx cycles --fix` to remove them");
+      }
+      Ok(())
+   }
 
-      println!("\n{}", "=".repeat(80));
-      println!("CRITICAL PATH - Longest dependency chain ({} issues)", longest_chain.len());
-      println!("{}\n", "=".repeat(80));
-
-      for (i, &bug_id) in longest_chain.iter().enumerate() {
-         if let Some(&issue_with_id) = issue_map.get(&bug_id) {
-            let arrow = if i == 0 { "▶" } else { "↓" };
-            println!(
-               "{} {} [{}] [{}]: {}",
-               arrow,
-               self.config.format_issue_ref(bug_id),
-               issue_with_id.issue.metadata.status,
-               issue_with_id.issue.metadata.priority,
-               issue_with_id.issue.metadata.title
-            );
+   fn fix_cycles_interactively(&self) -> Result<CyclesResult> {
+      let mut dropped = Vec::new();
+      let mut kept: Vec<Cycle> = Vec::new();
+      loop {
+         let cycles = self.tracker.cycle_repairs_data()?;
+         let Some(cycle) = cycles
+            .into_iter()
+            .find(|c| !kept.iter().any(|k| k.component == c.component))
+         else {
+            break;
+         };
+         self.print_cycle(&cycle);
+
+         // The suggestion goes first, so it is the default
+         let mut edges: Vec<&CycleEdge> = vec![cycle.suggested()];
+         edges.extend(
+            cycle
+               .edges
+               .iter()
+               .filter(|e| !std::ptr::eq(*e, cycle.suggested())),
+         );
+         let mut items: Vec<String> = edges
+            .iter()
+            .map(|e| format!("Drop {}", self.format_edge(e)))
+            .collect();
+         items[0].push_str(" (suggested)");
+         items.push("Keep this cycle".to_string());
+
+         let choice = prompt_select("Which dependency should go?", &items)?;
+         match edges.get(choice) {
+            Some(&edge) => {
+               self
+                  .tracker
+                  .drop_dependency(edge.dependent, edge.dependency)?;
+               println!("✓ Dropped {}\n", self.format_edge(edge));
+               dropped.push(edge.clone());
+            },
+            None => {
+               println!();
+               kept.push(cycle);
+            },
          }
       }
-
-      Ok(())
+      Ok(CyclesResult { cycles: kept, dropped })
    }
 
-   pub fn deps_graph(&self, focus_issue: Option<&str>, json: bool) -> Result<()> {
-      let issues = self.storage.list_open_issues()?;
+   fn format_edge(&self, edge: &CycleEdge) -> String {
+      format!(
+         "{} ({}) → {} ({})",
+         self.config().format_issue_ref(edge.dependent),
+         edge.dependent_priority,
+         self.config().format_issue_ref(edge.dependency),
+         edge.dependency_priority
+      )
+   }
 
-      if issues.is_empty() {
-         println!("No open issues found");
-         return Ok(());
+   fn print_cycle(&self, cycle: &Cycle) {
+      let refs: Vec<String> = cycle
+         .issues
+         .iter()
+         .chain(cycle.issues.first())
+         .map(|id| self.config().format_issue_ref(*id))
+         .collect();
+      println!("🔁 Dependency cycle (→ depends on): {}", refs.join(" → "));
+      let tangled: Vec<String> = cycle
+         .component
+         .iter()
+         .filter(|id| !cycle.issues.contains(id))
+         .map(|id| self.config().format_issue_ref(*id))
+         .collect();
+      if !tangled.is_empty() {
+         println!("   Also tangled in it: {}", tangled.join(", "));
       }
+      println!("   💡 Drop {}: {}", self.format_edge(cycle.suggested()), cycle.reason);
+   }
 
-      // Build issue map
-      let issue_map: std::collections::HashMap<u32, &crate::issue::IssueWithId> =
-         issues.iter().map(|i| (i.id, i)).collect();
-
-      // If focus issue provided, filter to show only that issue and its dependencies
-      let relevant_issues: Vec<u32> = if let Some(ref_str) = focus_issue {
-         let focus_num = self.storage.resolve_bug_ref(ref_str)?;
-         self.get_dependency_closure(focus_num, &issues)
-      } else {
-         issues.iter().map(|i| i.id).collect()
-      };
+   /// Show the dependency graph as ASCII art, or export it as Mermaid, DOT
+   /// or SVG to stdout or `out`, optionally grouped into clusters
+   pub fn deps_graph(
+      &self,
+      focus_issue: Option<&str>,
+      depth: Option<usize>,
+      cluster: Option<ClusterBy>,
+      format: GraphFormat,
+      out: Option<&str>,
+      json: bool,
+   ) -> Result<()> {
+      let nodes = self.tracker.deps_graph_data(focus_issue, depth)?;
 
       if json {
-         let graph_data: Vec<_> = relevant_issues
-            .iter()
-            .filter_map(|&id| issue_map.get(&id))
-            .map(|issue_with_id| {
-               json!({
-                   "id": issue_with_id.id,
-                   "title": issue_with_id.issue.metadata.title,
-                   "status": issue_with_id.issue.metadata.status.to_string(),
-                   "depends_on": issue_with_id.issue.metadata.depends_on,
-               })
-            })
-            .collect();
-
-         println!("{}", serde_json::to_string_pretty(&graph_data)?);
+         println!("{}", serde_json::to_string_pretty(&nodes)?);
          return Ok(());
       }
 
-      // ASCII art visualization
-      self.render_ascii_graph(&relevant_issues, &issue_map)?;
+      let issue_ref = |n| self.config().format_issue_ref(n);
+      let text = match format {
+         GraphFormat::Ascii => {
+            if out.is_some() {
+               anyhow::bail!("--out needs --format mermaid, dot or svg");
+            }
+            if nodes.is_empty() {
+               println!("No open issues found");
+               return Ok(());
+            }
+            return self.render_ascii_graph(&nodes, cluster);
+         },
+         GraphFormat::Mermaid => graph::mermaid(&nodes, issue_ref, cluster),
+         GraphFormat::Dot => graph::dot(&nodes, issue_ref, cluster),
+         GraphFormat::Svg => graph::svg(&nodes, issue_ref, cluster)?,
+      };
+
+      match out {
+         Some(path) => {
+            std::fs::write(path, text).with_context(|| format!("Failed to write {path}"))?;
+            eprintln!("✓ Wrote dependency graph of {} issues to {path}", nodes.len());
+         },
+         None => print!("{text}"),
+      }
       Ok(())
    }
 
-   fn get_dependency_closure(&self, root: u32, issues: &[crate::issue::IssueWithId]) -> Vec<u32> {
-      let mut result = std::collections::HashSet::new();
-      let mut to_visit = vec![root];
-
-      while let Some(id) = to_visit.pop() {
-         if result.contains(&id) {
-            continue;
-         }
-         result.insert(id);
+   fn render_ascii_graph(&self, nodes: &[GraphNode], cluster: Option<ClusterBy>) -> Result<()> {
+      let critical: Vec<u32> = self
+         .tracker
+         .critical_path_data()?
+         .chain
+         .iter()
+         .map(|step| step.node.id)
+         .collect();
+      let render = |nodes: &[GraphNode]| {
+         graph::ascii(
+            nodes,
+            |n| self.config().format_issue_ref(n),
+            &critical,
+            self.config().colored_output,
+         )
+      };
 
-         // Add dependencies (what this issue depends on)
-         if let Some(issue_with_id) = issues.iter().find(|i| i.id == id) {
-            for &dep in &issue_with_id.issue.metadata.depends_on {
-               if !result.contains(&dep) {
-                  to_visit.push(dep);
-               }
-            }
-         }
+      println!("\n{}", "=".repeat(80));
+      println!("DEPENDENCY GRAPH");
+      println!("{}\n", "=".repeat(80));
 
-         // Add dependents (what depends on this issue)
-         for issue_with_id in issues {
-            if issue_with_id.issue.metadata.depends_on.contains(&id)
-               && !result.contains(&issue_with_id.id)
-            {
-               to_visit.push(issue_with_id.id);
-            }
-         }
+      let Some(by) = cluster else {
+         print!("{}", render(nodes));
+         return Ok(());
+      };
+      // Each cluster is laid out on its own; edges leaving it aren't drawn
+      for (name, members) in graph::clusters(nodes, by) {
+         let heading = match name {
+            Some(name) => format!("{by}: {name}"),
+            None => format!("no {by}"),
+         };
+         println!("── {heading} ({} issues) {}\n", members.len(), "─".repeat(40));
+         let members: Vec<GraphNode> = members.into_iter().cloned().collect();
+         println!("{}", render(&members));
       }
+      Ok(())
+   }
 
-      let mut vec: Vec<_> = result.into_iter().collect();
-      vec.sort();
-      vec
+   pub fn workflow_show(&self, format: &str, json: bool) -> Result<()> {
+      let states = self.tracker.workflow_show_data()?;
+      if json {
+         println!("{}", serde_json::to_string_pretty(&states)?);
+         return Ok(());
+      }
+      match format {
+         "ascii" => print!("{}", workflow::ascii(&states)),
+         "mermaid" | "mmd" => print!("{}", workflow::mermaid(&states)),
+         _ => anyhow::bail!("Unknown workflow format: {format}. Use: ascii, mermaid"),
+      }
+      Ok(())
    }
 
-   fn render_ascii_graph(
+   pub fn metrics(
       &self,
-      issue_ids: &[u32],
-      issue_map: &std::collections::HashMap<u32, &crate::issue::IssueWithId>,
+      period: &str,
+      by_tag: bool,
+      by_assignee: bool,
+      by_kind: bool,
+      json: bool,
    ) -> Result<()> {
-      println!("\n{}", "=".repeat(80));
-      println!("DEPENDENCY GRAPH");
-      println!("{}\n", "=".repeat(80));
+      let metrics = self
+         .tracker
+         .metrics_data(period, by_tag, by_assignee, by_kind)?;
 
-      // Build layers for topological layout
-      let layers = self.compute_graph_layers(issue_ids, issue_map);
+      if json {
+         println!("{}", serde_json::to_string_pretty(&metrics)?);
+         return Ok(());
+      }
 
-      // Render each layer
-      for (level, layer_issues) in layers.iter().enumerate() {
-         if level > 0 {
-            // Draw arrows between layers
-            for _ in 0..layer_issues.len() {
-               print!("     │     ");
-            }
-            println!();
-            for _ in 0..layer_issues.len() {
-               print!("     ▼     ");
-            }
-            println!("\n");
-         }
+      let avg_close_time = metrics.avg_close_time_hours;
+      let flow = &metrics.flow;
 
-         // Draw boxes for issues in this layer
-         for &id in layer_issues {
-            if let Some(issue_with_id) = issue_map.get(&id) {
-               let status_marker = issue_with_id.issue.metadata.status.marker();
-               let title = if issue_with_id.issue.metadata.title.len() > 20 {
-                  format!("{}...", &issue_with_id.issue.metadata.title[..17])
-               } else {
-                  issue_with_id.issue.metadata.title.as_str().to_string()
-               };
+      println!("\n{}", "=".repeat(80));
+      println!("PERFORMANCE METRICS - {}", period.to_uppercase());
+      println!("{}\n", "=".repeat(80));
 
-               let box_width = 30;
-               let line1 = format!("┌{}┐", "─".repeat(box_width - 2));
-               let line2 = format!(
-                  "│ {} #{:<2} {:>20} │",
-                  status_marker,
-                  id,
-                  format!("[{}]", issue_with_id.issue.metadata.priority)
-               );
-               let line3 = format!("│ {:<28} │", title);
-               let line4 = format!("└{}┘", "─".repeat(box_width - 2));
-
-               if self.config.colored_output {
-                  use colored::Colorize;
-                  let colored_box = match issue_with_id.issue.metadata.priority {
-                     Priority::Critical => {
-                        format!("{}\n{}\n{}\n{}", line1, line2, line3, line4).red()
-                     },
-                     Priority::High => {
-                        format!("{}\n{}\n{}\n{}", line1, line2, line3, line4).yellow()
-                     },
-                     Priority::Medium => {
-                        format!("{}\n{}\n{}\n{}", line1, line2, line3, line4).normal()
-                     },
-                     Priority::Low => {
-                        format!("{}\n{}\n{}\n{}", line1, line2, line3, line4).bright_black()
-                     },
-                  };
+      println!("📊 Overview:");
+      println!("  Total open issues:   {}", metrics.total_open);
+      println!("  Total closed issues: {}", metrics.total_closed);
+      println!("  Opened in period:    {}", metrics.opened_in_period);
+      println!("  Closed in period:    {}", metrics.closed_in_period);
+      println!();
 
-                  if issue_with_id.issue.metadata.status == Status::Backlog {
-                     print!("{}", colored_box.dimmed());
-                  } else {
-                     print!("{}", colored_box);
-                  }
-               } else {
-                  println!("{}", line1);
-                  println!("{}", line2);
-                  println!("{}", line3);
-                  println!("{}", line4);
-               }
+      if avg_close_time > 0 {
+         let days = avg_close_time / 24;
+         let hours = avg_close_time % 24;
+         println!("⏱️  Average time to close: {} days {} hours", days, hours);
+         println!();
+      }
 
-               // Show what this depends on
-               if !issue_with_id.issue.metadata.depends_on.is_empty() {
-                  let deps_str = issue_with_id
-                     .issue
-                     .metadata
-                     .depends_on
-                     .iter()
-                     .map(|d| format!("#{}", d))
-                     .collect::<Vec<_>>()
-                     .join(", ");
-                  println!("  └─> depends on: {}", deps_str);
-               }
+      println!("🎯 By Priority:");
+      for priority in [Priority::Critical, Priority::High, Priority::Medium, Priority::Low] {
+         let count = metrics.by_priority[&priority.to_string()];
+         if count > 0 {
+            println!("  {:10} {}", format!("{}:", priority), count);
+         }
+      }
+      println!();
 
-               println!();
-            }
+      println!("📋 By Status:");
+      for (status, count) in &metrics.by_status {
+         if *count > 0 {
+            println!("  {:15} {}", format!("{}:", status), count);
          }
+      }
 
+      let stage = |label: &str, stats: Option<&StageStats>| {
+         if let Some(stats) = stats {
+            println!(
+               "  {label:22} median {:>7}  p90 {:>7}  max {:>7}  (n={})",
+               format_hours(stats.median_hours),
+               format_hours(stats.p90_hours),
+               format_hours(stats.max_hours),
+               stats.count
+            );
+         }
+      };
+      if flow.ready_to_active.is_some() || flow.active_to_closed.is_some() || flow.blocked.is_some()
+      {
          println!();
+         println!("⏳ Flow:");
+         stage("Ready → in progress:", flow.ready_to_active.as_ref());
+         stage("In progress → closed:", flow.active_to_closed.as_ref());
+         stage("Time blocked:", flow.blocked.as_ref());
       }
 
-      Ok(())
-   }
-
-   fn compute_graph_layers(
-      &self,
-      issue_ids: &[u32],
-      issue_map: &std::collections::HashMap<u32, &crate::issue::IssueWithId>,
-   ) -> Vec<Vec<u32>> {
-      let mut layers: Vec<Vec<u32>> = Vec::new();
-      let mut assigned = std::collections::HashSet::new();
-      let mut remaining: Vec<u32> = issue_ids.to_vec();
-
-      // Assign issues to layers based on dependencies
-      while !remaining.is_empty() {
-         let mut current_layer = Vec::new();
-
-         for &id in &remaining {
-            if let Some(issue_with_id) = issue_map.get(&id) {
-               // Can be in this layer if all dependencies are already assigned
-               let all_deps_assigned = issue_with_id
-                  .issue
-                  .metadata
-                  .depends_on
-                  .iter()
-                  .all(|dep| assigned.contains(dep) || !issue_ids.contains(dep));
+      if !flow.time_in_status.is_empty() {
+         println!();
+         println!("🕒 Time in status:");
+         for (status, stats) in &flow.time_in_status {
+            stage(&format!("{status}:"), Some(stats));
+         }
+      }
 
-               if all_deps_assigned {
-                  current_layer.push(id);
-               }
+      if !flow.longest_blocked.is_empty() {
+         println!();
+         println!("🚧 Longest blocked:");
+         for outlier in &flow.longest_blocked {
+            let still = if outlier.still_blocked {
+               " (still blocked)"
+            } else {
+               ""
+            };
+            println!(
+               "  {:>7}  {}: {}{still}",
+               format_hours(outlier.blocked_hours),
+               self.config().format_issue_ref(outlier.id),
+               outlier.title
+            );
+            if let Some(reason) = &outlier.reason {
+               println!("           Reason: {reason}");
             }
          }
+      }
 
-         if current_layer.is_empty() && !remaining.is_empty() {
-            // Cycle detected, just add all remaining to avoid infinite loop
-            current_layer = remaining.clone();
+      for (title, column, slices) in [
+         ("🏷️  By Tag:", "TAG", &metrics.by_tag),
+         ("👤 By Assignee:", "ASSIGNEE", &metrics.by_assignee),
+         ("🧩 By Kind:", "KIND", &metrics.by_kind),
+      ] {
+         let Some(slices) = slices else {
+            continue;
+         };
+         println!();
+         println!("{title}");
+         if slices.is_empty() {
+            println!("  Nothing in this period");
+            continue;
          }
-
-         for &id in &current_layer {
-            assigned.insert(id);
+         let width = slices
+            .iter()
+            .map(|s| s.name.chars().count())
+            .chain([column.len()])
+            .max()
+            .unwrap_or(0);
+         println!(
+            "  {column:width$}  {:>5}  {:>6}  {:>9}  {:>8}",
+            "OPEN", "CLOSED", "AVG CLOSE", "BLOCKED"
+         );
+         for slice in slices {
+            let blocked = if slice.blocked_hours > 0.0 {
+               format_hours(slice.blocked_hours)
+            } else {
+               "-".to_string()
+            };
+            println!(
+               "  {:width$}  {:>5}  {:>6}  {:>9}  {:>8}",
+               slice.name,
+               slice.open,
+               slice.closed_in_period,
+               slice.avg_close_hours.map_or("-".to_string(), format_hours),
+               blocked
+            );
          }
-
-         remaining.retain(|id| !assigned.contains(id));
-         current_layer.sort();
-         layers.push(current_layer);
       }
 
-      layers
+      Ok(())
    }
 
-   pub fn metrics(&self, period: &str, json: bool) -> Result<()> {
-      let open_issues = self.storage.list_open_issues()?;
-      let closed_issues = self.storage.list_closed_issues()?;
+   pub fn calibration(&self, json: bool) -> Result<()> {
+      let calibration = self.tracker.calibration_data()?;
+      if json {
+         println!("{}", serde_json::to_string_pretty(&calibration)?);
+         return Ok(());
+      }
 
-      // Determine time period
-      let now = Utc::now();
-      let since = match period {
-         "day" => now - Duration::days(1),
-         "week" => now - Duration::weeks(1),
-         "month" => now - Duration::days(30),
-         "all" => Utc::now() - Duration::days(36500), // ~100 years
-         _ => anyhow::bail!("Invalid period: {}. Use: day, week, month, all", period),
+      let Some(overall) = &calibration.overall else {
+         println!("No closed issues with both an estimate and time in progress yet");
+         return Ok(());
       };
+      println!("\n🎯 Estimate Calibration ({} closed issues)", overall.samples);
+      println!("  {}", overall.summary("Estimates"));
 
-      // Count closed issues in period
-      let closed_in_period: Vec<_> = closed_issues
-         .iter()
-         .filter(|issue_with_id| {
-            if let Some(closed_time) = issue_with_id.issue.metadata.closed {
-               closed_time > since
+      for (title, slices) in
+         [("📏 By Size:", &calibration.by_size), ("🏷️  By Tag:", &calibration.by_tag)]
+      {
+         if slices.is_empty() {
+            continue;
+         }
+         println!("\n{title}");
+         let width = slices
+            .iter()
+            .map(|b| b.name.chars().count())
+            .max()
+            .unwrap_or(0);
+         for bias in slices {
+            let verdict = if bias.samples < MIN_SAMPLES {
+               "too few to tell".dimmed().to_string()
+            } else if !bias.biased {
+               "on target".green().to_string()
+            } else if bias.ratio > 1.0 {
+               "underestimated".red().to_string()
             } else {
-               false
-            }
-         })
-         .collect();
+               "overestimated".yellow().to_string()
+            };
+            println!(
+               "  {:width$}  {:>3} issues  est {:>5}  actual {:>5}  {:>4.1}×  {verdict}",
+               bias.name,
+               bias.samples,
+               hours(bias.avg_estimate_minutes),
+               hours(bias.avg_actual_minutes),
+               bias.ratio,
+               width = width
+            );
+         }
+      }
 
-      // Count opened issues in period
-      let opened_in_period: Vec<_> = open_issues
+      let biased: Vec<String> = calibration
+         .by_size
          .iter()
-         .chain(closed_issues.iter())
-         .filter(|issue_with_id| issue_with_id.issue.metadata.created > since)
+         .filter(|b| b.biased)
+         .map(|b| b.summary(&format!("Your {} tasks", b.name)))
+         .chain(
+            calibration
+               .by_tag
+               .iter()
+               .filter(|b| b.biased)
+               .map(|b| b.summary(&format!("Tasks tagged {}", b.name))),
+         )
          .collect();
-
-      // Calculate average time to close
-      let mut close_times = Vec::new();
-      for issue_with_id in &closed_in_period {
-         if let (Some(created), Some(closed)) =
-            (Some(issue_with_id.issue.metadata.created), issue_with_id.issue.metadata.closed)
-         {
-            let duration = closed - created;
-            close_times.push(duration.num_hours());
+      if !biased.is_empty() {
+         println!();
+         for line in biased {
+            println!("💡 {line}");
          }
       }
+      Ok(())
+   }
 
-      let avg_close_time = if !close_times.is_empty() {
-         close_times.iter().sum::<i64>() / close_times.len() as i64
-      } else {
-         0
+   /// Print the board, redrawing it as issues change with `watch`
+   pub fn board(&self, columns: Option<&str>, watch: bool, json: bool) -> Result<()> {
+      if json {
+         println!("{}", serde_json::to_string_pretty(&self.tracker.board_data(columns)?)?);
+         return Ok(());
+      }
+
+      let draw = || -> Result<String> {
+         let columns = self.tracker.board_data(columns)?;
+         Ok(board::render(&columns, markdown::terminal_width(), |n| {
+            self.config().format_issue_ref(n)
+         }))
       };
+      if !watch {
+         print!("{}", draw()?);
+         return Ok(());
+      }
 
-      // Count by priority
-      let mut priority_counts = HashMap::new();
-      for issue_with_id in &open_issues {
-         *priority_counts
-            .entry(issue_with_id.issue.metadata.priority)
-            .or_insert(0) += 1;
+      let mut shown = String::new();
+      loop {
+         let text = draw()?;
+         if text != shown {
+            print!("\x1b[2J\x1b[H{text}");
+            println!("{}", "Refreshing every 2s, Ctrl-C to stop".dimmed());
+            shown = text;
+         }
+         std::thread::sleep(std::time::Duration::from_secs(2));
       }
+   }
 
-      // Count by status
-      let mut status_counts = HashMap::new();
-      for issue_with_id in &open_issues {
-         *status_counts
-            .entry(issue_with_id.issue.metadata.status)
-            .or_insert(0) += 1;
+   /// Print the execution order, or export it as a `mermaid` or `dot` graph
+   pub fn order(&self, milestone: Option<&str>, export: Option<&str>, json: bool) -> Result<()> {
+      let result = self.tracker.order_data(milestone)?;
+      let issue_ref = |n| self.config().format_issue_ref(n);
+
+      match export {
+         Some("mermaid") => {
+            print!("{}", result.to_mermaid(issue_ref));
+            return Ok(());
+         },
+         Some("dot") => {
+            print!("{}", result.to_dot(issue_ref));
+            return Ok(());
+         },
+         Some(other) => anyhow::bail!("Unknown export format: {other}. Use: mermaid, dot"),
+         None => {},
       }
 
       if json {
-         let output = json!({
-             "period": period,
-             "total_open": open_issues.len(),
-             "total_closed": closed_issues.len(),
-             "opened_in_period": opened_in_period.len(),
-             "closed_in_period": closed_in_period.len(),
-             "avg_close_time_hours": avg_close_time,
-             "by_priority": {
-                 "critical": priority_counts.get(&Priority::Critical).unwrap_or(&0),
-                 "high": priority_counts.get(&Priority::High).unwrap_or(&0),
-                 "medium": priority_counts.get(&Priority::Medium).unwrap_or(&0),
-                 "low": priority_counts.get(&Priority::Low).unwrap_or(&0),
-             },
-             "by_status": {
-                 "open": status_counts.get(&Status::NotStarted).unwrap_or(&0),
-                 "active": status_counts.get(&Status::InProgress).unwrap_or(&0),
-                 "blocked": status_counts.get(&Status::Blocked).unwrap_or(&0),
-                 "backlog": status_counts.get(&Status::Backlog).unwrap_or(&0),
-             },
-         });
-         println!("{}", serde_json::to_string_pretty(&output)?);
+         println!("{}", serde_json::to_string_pretty(&result)?);
          return Ok(());
       }
 
-      println!("\n{}", "=".repeat(80));
-      println!("PERFORMANCE METRICS - {}", period.to_uppercase());
-      println!("{}\n", "=".repeat(80));
-
-      println!("📊 Overview:");
-      println!("  Total open issues:   {}", open_issues.len());
-      println!("  Total closed issues: {}", closed_issues.len());
-      println!("  Opened in period:    {}", opened_in_period.len());
-      println!("  Closed in period:    {}", closed_in_period.len());
-      println!();
-
-      if avg_close_time > 0 {
-         let days = avg_close_time / 24;
-         let hours = avg_close_time % 24;
-         println!("⏱️  Average time to close: {} days {} hours", days, hours);
-         println!();
+      if result.batches.is_empty() && result.unordered.is_empty() {
+         println!("No open issues to order");
+         return Ok(());
       }
 
-      println!("🎯 By Priority:");
-      for priority in [Priority::Critical, Priority::High, Priority::Medium, Priority::Low] {
-         let count = priority_counts.get(&priority).unwrap_or(&0);
-         if *count > 0 {
-            println!("  {:10} {}", format!("{}:", priority), count);
+      println!("\n{}", "=".repeat(80));
+      println!("EXECUTION ORDER - {} batches", result.batches.len());
+      println!("{}", "=".repeat(80));
+      for (i, batch) in result.batches.iter().enumerate() {
+         let parallel = if batch.len() > 1 {
+            " (in parallel)"
+         } else {
+            ""
+         };
+         println!("\n{}", format!("Batch {}{parallel}", i + 1).bold());
+         for issue in batch {
+            let after = if issue.depends_on.is_empty() {
+               String::new()
+            } else {
+               let refs: Vec<String> = issue.depends_on.iter().map(|n| issue_ref(*n)).collect();
+               format!(" (after {})", refs.join(", ")).dimmed().to_string()
+            };
+            let prerequisite = if issue.prerequisite {
+               " [prerequisite]".yellow().to_string()
+            } else {
+               String::new()
+            };
+            println!(
+               "  {} [{}] {}{prerequisite}{after}",
+               issue_ref(issue.num),
+               issue.priority,
+               issue.title
+            );
          }
       }
-      println!();
 
-      println!("📋 By Status:");
-      for (status, count) in &status_counts {
-         if *count > 0 {
-            println!("  {:15} {}", format!("{}:", status), count);
+      if !result.unordered.is_empty() {
+         println!("\n⚠️  In or behind a dependency cycle, so not ordered:");
+         for issue in &result.unordered {
+            println!("  {} {}", issue_ref(issue.num), issue.title);
          }
+         println!("Run `agentx critical-path` to see the cycles");
       }
 
       Ok(())
    }
 
-   // Tarjan's algorithm for finding strongly connected components (cycles)
-   fn find_cycles(issues: &[crate::issue::IssueWithId]) -> Vec<Vec<u32>> {
-      let mut index = 0;
-      let mut stack = Vec::new();
-      let mut indices: std::collections::HashMap<u32, usize> = std::collections::HashMap::new();
-      let mut lowlinks: std::collections::HashMap<u32, usize> = std::collections::HashMap::new();
-      let mut on_stack: std::collections::HashSet<u32> = std::collections::HashSet::new();
-      let mut cycles = Vec::new();
-
-      let issue_map: std::collections::HashMap<u32, &crate::issue::IssueWithId> =
-         issues.iter().map(|i| (i.id, i)).collect();
-
-      #[allow(clippy::too_many_arguments)]
-      fn strongconnect(
-         v: u32,
-         issue_map: &std::collections::HashMap<u32, &crate::issue::IssueWithId>,
-         index: &mut usize,
-         stack: &mut Vec<u32>,
-         indices: &mut std::collections::HashMap<u32, usize>,
-         lowlinks: &mut std::collections::HashMap<u32, usize>,
-         on_stack: &mut std::collections::HashSet<u32>,
-         cycles: &mut Vec<Vec<u32>>,
-      ) {
-         indices.insert(v, *index);
-         lowlinks.insert(v, *index);
-         *index += 1;
-         stack.push(v);
-         on_stack.insert(v);
-
-         // Find the issue
-         if let Some(&issue_with_id) = issue_map.get(&v) {
-            // Check all dependencies
-            for &dep in &issue_with_id.issue.metadata.depends_on {
-               if !indices.contains_key(&dep) {
-                  // Dependency not visited
-                  strongconnect(dep, issue_map, index, stack, indices, lowlinks, on_stack, cycles);
-                  let dep_lowlink = lowlinks[&dep];
-                  let v_lowlink = lowlinks[&v];
-                  lowlinks.insert(v, v_lowlink.min(dep_lowlink));
-               } else if on_stack.contains(&dep) {
-                  // Dependency on stack - part of current SCC
-                  let dep_index = indices[&dep];
-                  let v_lowlink = lowlinks[&v];
-                  lowlinks.insert(v, v_lowlink.min(dep_index));
-               }
-            }
-         }
+   pub fn plan(
+      &self,
+      capacity: &str,
+      milestone: Option<&str>,
+      tag: bool,
+      json: bool,
+   ) -> Result<()> {
+      let result = self.tracker.plan_data(capacity, milestone, tag)?;
 
-         // If v is a root node, pop the stack and generate an SCC
-         if lowlinks[&v] == indices[&v] {
-            let mut scc = Vec::new();
-            loop {
-               let w = stack.pop().unwrap();
-               on_stack.remove(&w);
-               scc.push(w);
-               if w == v {
-                  break;
-               }
-            }
+      if json {
+         println!("{}", serde_json::to_string_pretty(&result)?);
+         return Ok(());
+      }
 
-            // Only report cycles (SCCs with more than one node)
-            if scc.len() > 1 {
-               scc.reverse();
-               cycles.push(scc);
-            }
+      println!("\n{}", "=".repeat(80));
+      println!(
+         "SPRINT PLAN - {} of {} ({} issues)",
+         hours(result.planned_minutes),
+         hours(result.capacity_minutes),
+         result.issues.len()
+      );
+      println!("{}\n", "=".repeat(80));
+
+      if result.issues.is_empty() {
+         println!("Nothing fits: no ready issue with an estimate is small enough");
+      } else {
+         println!(
+            "  {:>3}  {:<10} {:<9} {:>6} {:>6}  Title",
+            "#", "Issue", "Priority", "Effort", "Total"
+         );
+         for (i, issue) in result.issues.iter().enumerate() {
+            let after = if issue.after.is_empty() {
+               String::new()
+            } else {
+               let refs: Vec<String> = issue
+                  .after
+                  .iter()
+                  .map(|n| self.config().format_issue_ref(*n))
+                  .collect();
+               format!(" (after {})", refs.join(", ")).dimmed().to_string()
+            };
+            println!(
+               "  {:>3}  {:<10} {:<9} {:>6} {:>6}  {}{after}",
+               i + 1,
+               self.config().format_issue_ref(issue.num),
+               issue.priority.to_string(),
+               hours(issue.effort_minutes),
+               hours(issue.cumulative_minutes),
+               issue.title
+            );
          }
       }
 
-      for issue_with_id in issues {
-         if !indices.contains_key(&issue_with_id.id) {
-            strongconnect(
-               issue_with_id.id,
-               &issue_map,
-               &mut index,
-               &mut stack,
-               &mut indices,
-               &mut lowlinks,
-               &mut on_stack,
-               &mut cycles,
+      if !result.skipped.is_empty() {
+         println!("\nLeft out:");
+         for skipped in &result.skipped {
+            println!(
+               "  {} {} - {}",
+               self.config().format_issue_ref(skipped.num),
+               skipped.title,
+               skipped.reason.dimmed()
             );
          }
       }
 
-      cycles
+      if let Some(tag) = &result.sprint_tag {
+         println!("\n🏷️  Tagged {} issues #{tag}", result.issues.len());
+      }
+
+      Ok(())
+   }
+
+   pub fn forecast(
+      &self,
+      milestone: Option<&str>,
+      chain: Option<&str>,
+      weeks: u32,
+      json: bool,
+   ) -> Result<()> {
+      let result = self.tracker.forecast_data(milestone, chain, weeks)?;
+
+      if json {
+         println!("{}", serde_json::to_string_pretty(&result)?);
+         return Ok(());
+      }
+
+      println!("\n{}", "=".repeat(80));
+      println!("FORECAST - {}", result.scope.to_uppercase());
+      println!("{}\n", "=".repeat(80));
+
+      let effort_hours = result.remaining_effort_minutes as f64 / 60.0;
+      println!("📦 Remaining: {} issues, ~{effort_hours:.1}h estimated", result.remaining_issues);
+      if result.unestimated > 0 {
+         println!("   ({} without an estimate)", result.unestimated);
+      }
+
+      let rate = |r: f64| match result.method {
+         ForecastMethod::Effort => format!("{:.1}h/week", r / 60.0),
+         ForecastMethod::Count => format!("{r:.1} issues/week"),
+      };
+      println!(
+         "📈 Velocity (last {} weeks, by {}): {} median, {} – {}",
+         result.lookback_weeks,
+         match result.method {
+            ForecastMethod::Effort => "effort",
+            ForecastMethod::Count => "issue count",
+         },
+         rate(result.weekly_rate.median),
+         rate(result.weekly_rate.pessimistic),
+         rate(result.weekly_rate.optimistic)
+      );
+      println!();
+
+      let date = |d: Option<DateTime<Utc>>| {
+         d.map(|d| d.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| "no recent throughput".to_string())
+      };
+      println!("🗓️  Estimated completion:");
+      println!("  Optimistic:  {}", date(result.optimistic));
+      println!("  Median:      {}", date(result.median));
+      println!("  Pessimistic: {}", date(result.pessimistic));
+
+      Ok(())
    }
 }
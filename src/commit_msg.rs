@@ -0,0 +1,194 @@
+//! Commit messages from issues
+//!
+//! `agentx commit-msg` turns an issue into a conventional commit message:
+//! the type comes from its tags (`bug` → `fix`, `feature` → `feat`, ...),
+//! the scope from the directory its files share, the subject from its title
+//! and the body from its acceptance criteria, with a `Refs:` trailer naming
+//! the issue. Meant for a `prepare-commit-msg` hook or
+//! `git commit -eF <(agentx commit-msg)`.
+
+use crate::{issue::Issue, utils::strip_line_suffix};
+
+/// Longest header (`type(scope): subject`) before the subject is shortened
+const MAX_HEADER: usize = 72;
+
+/// Commit types and the tags that select them, first match wins
+const TYPES: &[(&str, &[&str])] = &[
+   ("fix", &["bug", "fix", "bugfix", "regression", "hotfix"]),
+   ("feat", &["feature", "feat", "enhancement"]),
+   ("perf", &["perf", "performance"]),
+   ("refactor", &["refactor", "cleanup", "tech-debt"]),
+   ("docs", &["docs", "documentation"]),
+   ("test", &["test", "tests", "testing"]),
+   ("build", &["build", "deps", "dependencies"]),
+   ("ci", &["ci"]),
+   ("chore", &["chore"]),
+];
+
+/// Directories too generic to name a scope
+const GENERIC_DIRS: &[&str] = &["src", "lib", "crates", "packages", "app", "pkg", "internal"];
+
+/// The commit message for `issue`, referred to as `issue_ref`
+pub fn message(issue: &Issue, issue_ref: &str) -> String {
+   let meta = &issue.metadata;
+   let kind = commit_type(meta.tags.iter().map(|t| t.as_str()), &meta.title);
+   let files: Vec<&str> = meta.files.iter().map(|f| f.as_str()).collect();
+   let prefix = match scope(&files) {
+      Some(scope) => format!("{kind}({scope}): "),
+      None => format!("{kind}: "),
+   };
+   let subject = shorten(&subject(&meta.title), MAX_HEADER.saturating_sub(prefix.len()));
+
+   let mut text = format!("{prefix}{subject}\n");
+   if let Some(acceptance) = issue.body.acceptance().map(str::trim)
+      && !acceptance.is_empty()
+   {
+      text.push('\n');
+      text.push_str(acceptance);
+      text.push('\n');
+   }
+   text.push_str(&format!("\nRefs: {issue_ref}\n"));
+   text
+}
+
+/// The type the first matching tag selects; a title starting with "Fix"
+/// makes it `fix`, anything else `chore`
+fn commit_type<'a>(tags: impl Iterator<Item = &'a str> + Clone, title: &str) -> &'static str {
+   for (kind, names) in TYPES {
+      if tags
+         .clone()
+         .any(|tag| names.iter().any(|n| tag.eq_ignore_ascii_case(n)))
+      {
+         return kind;
+      }
+   }
+   let first = title.split_whitespace().next().unwrap_or("").to_lowercase();
+   if matches!(first.as_str(), "fix" | "fixes" | "fixed") {
+      "fix"
+   } else {
+      "chore"
+   }
+}
+
+/// The last meaningful directory all `files` share; for files directly in a
+/// generic directory like `src`, a lone file's stem
+fn scope(files: &[&str]) -> Option<String> {
+   let paths: Vec<Vec<&str>> = files
+      .iter()
+      .map(|f| {
+         strip_line_suffix(f)
+            .trim_start_matches("./")
+            .split('/')
+            .collect()
+      })
+      .filter(|parts: &Vec<&str>| !parts.is_empty() && !parts[0].is_empty())
+      .collect();
+   let first = paths.first()?;
+   // Directories only: the last part of each path is the file itself
+   let mut common = first.len() - 1;
+   for path in &paths[1..] {
+      common = common.min(path.len() - 1);
+      common = (0..common).find(|&i| path[i] != first[i]).unwrap_or(common);
+   }
+   let dir = first[..common]
+      .iter()
+      .rev()
+      .find(|d| !GENERIC_DIRS.contains(d));
+   match dir {
+      Some(dir) => Some(dir.to_string()),
+      None if paths.len() == 1 => {
+         let name = first.last()?;
+         let stem = name.split('.').next().filter(|s| !s.is_empty())?;
+         Some(stem.to_string())
+      },
+      None => None,
+   }
+}
+
+/// The title as a subject: lowercase start (unless it opens with an
+/// acronym) and no trailing period
+fn subject(title: &str) -> String {
+   let title = title.trim().trim_end_matches('.');
+   let mut chars = title.chars();
+   let Some(first) = chars.next() else {
+      return String::new();
+   };
+   let acronym = chars.next().is_some_and(char::is_uppercase);
+   if acronym {
+      title.to_string()
+   } else {
+      first
+         .to_lowercase()
+         .chain(title[first.len_utf8()..].chars())
+         .collect()
+   }
+}
+
+/// `text` cut at a word boundary to fit `max` characters, with `…`
+fn shorten(text: &str, max: usize) -> String {
+   if text.chars().count() <= max {
+      return text.to_string();
+   }
+   let mut out = String::new();
+   for word in text.split_whitespace() {
+      let len = out.chars().count() + word.chars().count() + usize::from(!out.is_empty());
+      if len + 1 > max {
+         break;
+      }
+      if !out.is_empty() {
+         out.push(' ');
+      }
+      out.push_str(word);
+   }
+   if out.is_empty() {
+      out = text.chars().take(max.saturating_sub(1)).collect();
+   }
+   out.push('…');
+   out
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::issue::test_support::issue;
+
+   #[test]
+   fn test_message() {
+      let login = issue(1)
+         .title("Fix login timeout.")
+         .tags(&["auth", "bug"])
+         .files(&["src/auth/session.rs:40", "src/auth/token.rs"])
+         .acceptance("- Sessions last 30 minutes\n- Tokens refresh")
+         .build()
+         .issue;
+      assert_eq!(
+         message(&login, "ISSUE-12"),
+         "fix(auth): fix login timeout\n\n- Sessions last 30 minutes\n- Tokens refresh\n\nRefs: \
+          ISSUE-12\n"
+      );
+
+      let export = issue(1)
+         .title("CSV export for reports")
+         .tags(&["feature"])
+         .files(&["src/export.rs"])
+         .build()
+         .issue;
+      assert_eq!(
+         message(&export, "ISSUE-3"),
+         "feat(export): CSV export for reports\n\nRefs: ISSUE-3\n"
+      );
+
+      let chore = issue(1)
+         .title("Bump the toolchain")
+         .files(&["Cargo.toml", "src/main.rs"])
+         .build()
+         .issue;
+      assert!(message(&chore, "ISSUE-4").starts_with("chore: bump the toolchain\n"));
+
+      let long = "Make the dependency graph layout stable across runs when issues share a layer";
+      let header = message(&issue(1).title(long).build().issue, "ISSUE-5");
+      let header = header.lines().next().unwrap();
+      assert!(header.chars().count() <= MAX_HEADER);
+      assert_eq!(header, "chore: make the dependency graph layout stable across runs when issues…");
+   }
+}
@@ -0,0 +1,199 @@
+//! Checkpoint compaction
+//!
+//! Issues worked on for a long time pile up checkpoints until the body no
+//! longer fits in an agent's context. Compacting folds all but the latest
+//! checkpoints into a single "History summary" section, and the originals are
+//! archived next to the issues so nothing is lost. The summary is one line per
+//! checkpoint, or whatever a configured summarizer command makes of them.
+
+use std::{
+   io::Write,
+   process::{Command, Stdio},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::body::{IssueBody, Section, SectionKind};
+
+/// Label of the section old checkpoints are folded into
+pub const SUMMARY_LABEL: &str = "History summary";
+
+/// Longest checkpoint line kept in the built-in summary
+const SUMMARY_LINE_CHARS: usize = 120;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactConfig {
+   /// Latest checkpoints left as they are
+   #[serde(default = "default_keep")]
+   pub keep:           usize,
+   /// Shell command that reads the previous summary and the checkpoints on
+   /// stdin and prints the new summary
+   #[serde(default)]
+   pub summarizer:     Option<String>,
+   /// Compact after a checkpoint leaves the body larger than this many bytes
+   #[serde(default)]
+   pub max_body_bytes: Option<usize>,
+}
+
+fn default_keep() -> usize {
+   5
+}
+
+impl Default for CompactConfig {
+   fn default() -> Self {
+      Self { keep: default_keep(), summarizer: None, max_body_bytes: None }
+   }
+}
+
+/// A compacted body and what was taken out of it
+pub struct Compaction {
+   pub body:      IssueBody,
+   /// The folded checkpoints, exactly as written
+   pub archived:  String,
+   pub compacted: usize,
+   pub kept:      usize,
+}
+
+/// Fold all but the last `keep` checkpoints of `body` into its history
+/// summary; `summarize` gets the previous summary, if any, and the folded
+/// checkpoints. `None` if there is nothing to fold.
+pub fn compact(
+   body: &IssueBody,
+   keep: usize,
+   summarize: impl FnOnce(Option<&str>, &[&Section]) -> Result<String>,
+) -> Result<Option<Compaction>> {
+   let checkpoints: Vec<&Section> = body.checkpoints().collect();
+   let folded = checkpoints.len().saturating_sub(keep);
+   if folded == 0 {
+      return Ok(None);
+   }
+   let old = &checkpoints[..folded];
+   let is_summary = |s: &Section| s.kind == SectionKind::Other(SUMMARY_LABEL.to_string());
+   let previous = body.sections().iter().find(|s| is_summary(s));
+
+   let summary = summarize(previous.map(Section::content), old)?;
+   let summary = summary.trim();
+   if summary.is_empty() {
+      anyhow::bail!("The summary came out empty; keeping the checkpoints");
+   }
+
+   // The summary takes the place of the earliest section it replaces
+   let mut text = String::new();
+   let mut placed = false;
+   for section in body.sections() {
+      let replaced = is_summary(section) || old.iter().any(|c| std::ptr::eq(*c, section));
+      if !replaced {
+         text.push_str(section.as_str());
+         continue;
+      }
+      if !placed {
+         if !text.is_empty() && !text.ends_with("\n\n") {
+            text.push_str(if text.ends_with('\n') { "\n" } else { "\n\n" });
+         }
+         text.push_str(&format!("## {SUMMARY_LABEL}\n\n{summary}\n\n"));
+         placed = true;
+      }
+   }
+
+   Ok(Some(Compaction {
+      body:      IssueBody::parse(text.trim_end()),
+      archived:  old.iter().map(|c| c.as_str()).collect(),
+      compacted: folded,
+      kept:      checkpoints.len() - folded,
+   }))
+}
+
+/// The built-in summary: the previous one followed by a line per checkpoint
+pub fn outline(previous: Option<&str>, checkpoints: &[&Section]) -> String {
+   let mut lines: Vec<String> = previous.map(|p| p.to_string()).into_iter().collect();
+   for checkpoint in checkpoints {
+      let first = checkpoint
+         .content()
+         .lines()
+         .next()
+         .unwrap_or_default()
+         .trim();
+      let mut note: String = first.chars().take(SUMMARY_LINE_CHARS).collect();
+      if note.len() < first.len() || checkpoint.content().lines().nth(1).is_some() {
+         note.push('…');
+      }
+      lines.push(match checkpoint.detail() {
+         Some(when) => format!("- {when}: {note}"),
+         None => format!("- {note}"),
+      });
+   }
+   lines.join("\n")
+}
+
+/// Run `command` through the shell with the previous summary and the
+/// checkpoints on stdin, returning what it prints
+pub fn run_summarizer(
+   command: &str,
+   previous: Option<&str>,
+   checkpoints: &[&Section],
+) -> Result<String> {
+   let mut input = String::new();
+   if let Some(previous) = previous {
+      input.push_str(&format!("## {SUMMARY_LABEL}\n\n{previous}\n\n"));
+   }
+   for checkpoint in checkpoints {
+      input.push_str(checkpoint.as_str());
+   }
+
+   let mut child = Command::new("sh")
+      .args(["-c", command])
+      .stdin(Stdio::piped())
+      .stdout(Stdio::piped())
+      .spawn()
+      .with_context(|| format!("Failed to run summarizer: {command}"))?;
+   child
+      .stdin
+      .take()
+      .context("Summarizer stdin unavailable")?
+      .write_all(input.as_bytes())?;
+   let output = child.wait_with_output()?;
+   if !output.status.success() {
+      anyhow::bail!("Summarizer failed ({}): {command}", output.status);
+   }
+   String::from_utf8(output.stdout).context("Summarizer output is not UTF-8")
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn test_compact_folds_old_checkpoints() {
+      let body = IssueBody::parse(
+         "**Issue**: Slow\n\n## Checkpoint - 2024-01-01 10:00\n\nProfiled\nmore detail\n\n## \
+          Checkpoint - 2024-01-02 10:00\n\nFound the loop\n\n## Checkpoint - 2024-01-03 \
+          10:00\n\nFixed",
+      );
+      let compaction = compact(&body, 1, |previous, old| Ok(outline(previous, old)))
+         .unwrap()
+         .unwrap();
+      assert_eq!((compaction.compacted, compaction.kept), (2, 1));
+      assert!(compaction.archived.contains("Profiled\nmore detail"));
+      assert_eq!(
+         compaction.body.to_string(),
+         "**Issue**: Slow\n\n## History summary\n\n- 2024-01-01 10:00: Profiled…\n- 2024-01-02 \
+          10:00: Found the loop\n\n## Checkpoint - 2024-01-03 10:00\n\nFixed"
+      );
+
+      // A second pass extends the summary instead of adding another
+      let mut body = compaction.body;
+      body.append("\n\n## Checkpoint - 2024-01-04 10:00\n\nShipped");
+      let again = compact(&body, 1, |previous, old| Ok(outline(previous, old)))
+         .unwrap()
+         .unwrap();
+      let text = again.body.to_string();
+      assert_eq!(text.matches(SUMMARY_LABEL).count(), 1);
+      assert!(text.contains("Found the loop\n- 2024-01-03 10:00: Fixed\n\n## Checkpoint"));
+      assert!(
+         compact(&again.body, 1, |_, _| Ok(String::new()))
+            .unwrap()
+            .is_none()
+      );
+   }
+}
@@ -0,0 +1,300 @@
+//! Dynamic shell completion
+//!
+//! The scripts from `agentx completions` call back into
+//! `agentx __complete <shell> -- <words>` on every TAB, the way kubectl's
+//! do, so values that live in the issue store complete too: issue numbers
+//! (with their titles), aliases, tags and milestones. The words are walked
+//! against the clap definition to find out what the last one is: a
+//! subcommand, a flag, a flag's value or a positional argument. When
+//! nothing is offered the shell falls back to completing file names.
+
+use clap::{Arg, ArgAction, Command};
+
+/// The issue store's values offered for completion
+#[derive(Debug, Clone, Default)]
+pub struct Candidates {
+   /// Number, title and status; open issues first
+   pub issues:     Vec<(u32, String, String)>,
+   pub aliases:    Vec<(String, u32)>,
+   pub tags:       Vec<String>,
+   pub milestones: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+   pub value:       String,
+   pub description: Option<String>,
+}
+
+impl Completion {
+   fn new(value: impl Into<String>, description: Option<String>) -> Self {
+      Self { value: value.into(), description }
+   }
+}
+
+/// What a word on the command line holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+   Refs,
+   Aliases,
+   Tags,
+   Milestones,
+   Priorities,
+   Nothing,
+}
+
+/// Completions for the last of `words` (the arguments after `agentx`),
+/// given the built CLI definition `root`
+pub fn complete(root: &Command, words: &[String], candidates: &Candidates) -> Vec<Completion> {
+   let (current, before) = match words.split_last() {
+      Some((current, before)) => (current.as_str(), before),
+      None => ("", words),
+   };
+
+   let mut cmd = root;
+   let mut positionals = 0;
+   let mut pending: Option<&Arg> = None;
+   let mut escaped = false;
+   for word in before {
+      if pending.take().is_some() {
+         continue;
+      }
+      if word == "--" {
+         escaped = true;
+      } else if let Some(long) = word.strip_prefix("--").filter(|_| !escaped) {
+         if !long.contains('=') {
+            pending = find(cmd, |a| a.get_long() == Some(long)).filter(|a| takes_value(a));
+         }
+      } else if let Some(short) = word.strip_prefix('-').filter(|s| !s.is_empty() && !escaped) {
+         let last = short.chars().last();
+         pending = find(cmd, |a| a.get_short() == last).filter(|a| takes_value(a));
+      } else if let Some(sub) = cmd.find_subcommand(word).filter(|_| positionals == 0) {
+         cmd = sub;
+      } else {
+         positionals += 1;
+      }
+   }
+
+   if let Some(arg) = pending {
+      return values(cmd, arg, current, candidates);
+   }
+   if current.starts_with('-') && !escaped {
+      return flags(cmd, current);
+   }
+   if cmd.has_subcommands() && positionals == 0 {
+      return cmd
+         .get_subcommands()
+         .filter(|sub| !sub.is_hide_set() && sub.get_name().starts_with(current))
+         .map(|sub| Completion::new(sub.get_name(), sub.get_about().map(|a| a.to_string())))
+         .collect();
+   }
+
+   // Past the last positional, a list (like bulk-start's refs) takes the rest
+   let args: Vec<&Arg> = cmd.get_positionals().collect();
+   let arg = args.get(positionals).or_else(|| {
+      args
+         .last()
+         .filter(|arg| matches!(arg.get_action(), ArgAction::Append))
+   });
+   match arg {
+      Some(arg) => values(cmd, arg, current, candidates),
+      None => Vec::new(),
+   }
+}
+
+fn find(cmd: &Command, matches: impl Fn(&Arg) -> bool) -> Option<&Arg> {
+   cmd.get_arguments().find(|a| matches(a))
+}
+
+fn takes_value(arg: &Arg) -> bool {
+   arg.get_action().takes_values()
+}
+
+fn flags(cmd: &Command, current: &str) -> Vec<Completion> {
+   cmd.get_arguments()
+      .filter(|a| !a.is_hide_set())
+      .filter_map(|a| Some((a.get_long()?, a)))
+      .map(|(long, a)| (format!("--{long}"), a))
+      .filter(|(flag, _)| flag.starts_with(current))
+      .map(|(flag, a)| Completion::new(flag, a.get_help().map(|h| h.to_string())))
+      .collect()
+}
+
+fn values(cmd: &Command, arg: &Arg, current: &str, candidates: &Candidates) -> Vec<Completion> {
+   let possible = arg.get_possible_values();
+   if !possible.is_empty() {
+      return possible
+         .iter()
+         .filter(|v| !v.is_hide_set() && v.get_name().starts_with(current))
+         .map(|v| Completion::new(v.get_name(), v.get_help().map(|h| h.to_string())))
+         .collect();
+   }
+   offer(kind(cmd, arg), current, candidates)
+}
+
+/// What `arg` of `cmd` takes, from its name
+fn kind(cmd: &Command, arg: &Arg) -> Kind {
+   match (cmd.get_name(), arg.get_id().as_str()) {
+      (_, "bug_ref" | "bug_refs" | "on" | "issue") | ("depend", "remove") => Kind::Refs,
+      ("remove", "alias") | ("rename", "old") => Kind::Aliases,
+      (_, "tags" | "add" | "remove" | "add_tags" | "remove_tags") => Kind::Tags,
+      (_, "milestone") => Kind::Milestones,
+      (_, "priority") => Kind::Priorities,
+      _ => Kind::Nothing,
+   }
+}
+
+fn offer(kind: Kind, current: &str, candidates: &Candidates) -> Vec<Completion> {
+   let completions: Vec<Completion> = match kind {
+      Kind::Refs => candidates
+         .issues
+         .iter()
+         .map(|(num, title, status)| {
+            Completion::new(num.to_string(), Some(format!("{title} [{status}]")))
+         })
+         .chain(candidates.aliases.iter().map(|(alias, num)| {
+            let title = candidates
+               .issues
+               .iter()
+               .find(|(n, ..)| n == num)
+               .map_or(String::new(), |(_, title, _)| format!(" {title}"));
+            Completion::new(alias, Some(format!("#{num}{title}")))
+         }))
+         .collect(),
+      Kind::Aliases => candidates
+         .aliases
+         .iter()
+         .map(|(alias, num)| Completion::new(alias, Some(format!("#{num}"))))
+         .collect(),
+      Kind::Tags => candidates
+         .tags
+         .iter()
+         .map(|t| Completion::new(t, None))
+         .collect(),
+      Kind::Milestones => candidates
+         .milestones
+         .iter()
+         .map(|m| Completion::new(m, None))
+         .collect(),
+      Kind::Priorities => ["critical", "high", "medium", "low"]
+         .into_iter()
+         .map(|p| Completion::new(p, None))
+         .collect(),
+      Kind::Nothing => Vec::new(),
+   };
+   completions
+      .into_iter()
+      .filter(|c| c.value.starts_with(current))
+      .collect()
+}
+
+/// Completions as `shell` reads them: bare values for bash,
+/// `value:description` for zsh and `value<TAB>description` for fish
+pub fn format(shell: &str, completions: &[Completion]) -> String {
+   let mut out = String::new();
+   for completion in completions {
+      let description = completion
+         .description
+         .as_deref()
+         .map(|d| d.replace(['\n', '\t'], " "));
+      let line = match (shell, description) {
+         ("zsh", Some(d)) => format!("{}:{d}", completion.value.replace(':', "\\:")),
+         ("zsh", None) => completion.value.replace(':', "\\:"),
+         ("fish", Some(d)) => format!("{}\t{d}", completion.value),
+         _ => completion.value.clone(),
+      };
+      out.push_str(&line);
+      out.push('\n');
+   }
+   out
+}
+
+/// Completion script for `shell` that asks `agentx __complete`, or `None`
+/// for shells only the static clap scripts support
+pub fn script(shell: &str) -> Option<&'static str> {
+   match shell {
+      "bash" => Some(BASH),
+      "zsh" => Some(ZSH),
+      "fish" => Some(FISH),
+      _ => None,
+   }
+}
+
+const BASH: &str = r#"_agentx() {
+    local IFS=$'\n'
+    COMPREPLY=($(agentx __complete bash -- "${COMP_WORDS[@]:1:COMP_CWORD}" 2>/dev/null))
+}
+complete -o default -F _agentx agentx
+"#;
+
+const ZSH: &str = r#"#compdef agentx
+_agentx() {
+    local -a completions
+    completions=("${(@f)$(agentx __complete zsh -- "${(@)words[2,CURRENT]}" 2>/dev/null)}")
+    completions=(${completions:#})
+    if (( ${#completions} )); then
+        _describe 'agentx' completions
+    else
+        _files
+    fi
+}
+compdef _agentx agentx
+"#;
+
+const FISH: &str = r#"function __agentx_complete
+    set -l tokens (commandline -opc) (commandline -ct)
+    set -l out (agentx __complete fish -- $tokens[2..-1] 2>/dev/null)
+    if test (count $out) -eq 0
+        __fish_complete_path (commandline -ct)
+    else
+        printf '%s\n' $out
+    end
+end
+complete -c agentx -f -a '(__agentx_complete)'
+"#;
+
+#[cfg(test)]
+mod tests {
+   use clap::CommandFactory;
+
+   use super::*;
+   use crate::cli::Cli;
+
+   fn values(words: &[&str]) -> Vec<String> {
+      let mut root = Cli::command();
+      root.build();
+      let candidates = Candidates {
+         issues:     vec![
+            (12, "Fix login".into(), "open".into()),
+            (3, "Old crash".into(), "closed".into()),
+         ],
+         aliases:    vec![("login".into(), 12)],
+         tags:       vec!["auth".into(), "ui".into()],
+         milestones: vec!["v1".into()],
+      };
+      let words: Vec<String> = words.iter().map(|w| w.to_string()).collect();
+      complete(&root, &words, &candidates)
+         .into_iter()
+         .map(|c| c.value)
+         .collect()
+   }
+
+   #[test]
+   fn test_complete_walks_the_cli() {
+      assert!(values(&["sh"]).contains(&"show".to_string()));
+      assert_eq!(values(&["show", ""]), ["12", "3", "login"]);
+      assert_eq!(values(&["--json", "show", "1"]), ["12"]);
+      assert_eq!(values(&["tag", "12", "--add", ""]), ["auth", "ui"]);
+      assert_eq!(values(&["depend", "12", "--on", "l"]), ["login"]);
+      assert_eq!(values(&["new", "--milestone", ""]), ["v1"]);
+      assert_eq!(values(&["bulk-start", "12", ""]), ["12", "3", "login"]);
+      assert_eq!(values(&["alias", "remove", ""]), ["login"]);
+      assert!(values(&["show", "--co"]).contains(&"--context-budget".to_string()));
+      assert!(values(&["for-file", ""]).is_empty());
+
+      let completion = [Completion::new("a:b", Some("Title\tx".into()))];
+      assert_eq!(format("zsh", &completion), "a\\:b:Title x\n");
+      assert_eq!(format("fish", &completion), "a:b\tTitle x\n");
+      assert_eq!(format("bash", &completion), "a:b\n");
+   }
+}
@@ -1,8 +1,20 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::{
+   calendar::WorkCalendar,
+   compact::CompactConfig,
+   kinds::{self, KindConfig, default_kinds},
+   lint::LintConfig,
+   notify::NotifyConfig,
+   transitions::TransitionRules,
+   utils::{EffortScale, set_effort_scale},
+   watch::WatchConfig,
+};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
    #[serde(default = "default_priority")]
@@ -23,11 +35,83 @@ pub struct Config {
    #[serde(default = "default_issue_prefix")]
    pub issue_prefix: String,
 
+   /// Give new issues an alias made from their title
+   #[serde(default = "default_auto_alias")]
+   pub auto_alias: bool,
+
+   /// Namespace generated aliases go in (`web/fix-login`); bare aliases
+   /// resolve to it first
+   #[serde(default)]
+   pub alias_namespace: Option<String>,
+
    #[serde(default)]
    pub git_integration: GitIntegration,
 
    #[serde(default)]
    pub templates_dir: Option<PathBuf>,
+
+   /// Minimum similarity (0.0-1.0) for an issue to be reported as a likely
+   /// duplicate
+   #[serde(default = "default_similarity_threshold")]
+   pub similarity_threshold: f64,
+
+   /// What `--copy` puts on the clipboard; `{ref}`, `{id}` and `{title}` are
+   /// filled in
+   #[serde(default = "default_copy_template")]
+   pub copy_template: String,
+
+   /// Minutes per T-shirt size and story point
+   #[serde(default)]
+   pub effort: EffortScale,
+
+   /// How watchers of an issue are notified of its changes
+   #[serde(default)]
+   pub watch: WatchConfig,
+
+   /// Status changes `start`, `close` and friends allow without `--force`
+   #[serde(default)]
+   pub transitions: TransitionRules,
+
+   /// Move backlogged issues to open once the last of their dependencies is
+   /// closed
+   #[serde(default)]
+   pub activate_unblocked: bool,
+
+   /// Refuse every change to issues; `AGENTX_READONLY=1` does the same
+   #[serde(default)]
+   pub read_only: bool,
+
+   /// How `compact` folds old checkpoints, and the body size that triggers
+   /// it after a checkpoint
+   #[serde(default)]
+   pub compact: CompactConfig,
+
+   /// Rules `agentx notify` raises desktop notifications for
+   #[serde(default)]
+   pub notify: NotifyConfig,
+
+   /// Most issues `bulk-close` and `bulk-edit` change without `--yes`; 0
+   /// turns the limit off
+   #[serde(default = "default_bulk_limit")]
+   pub bulk_limit: usize,
+
+   /// Rules `agentx lint` checks open issues against
+   #[serde(default)]
+   pub lint: LintConfig,
+
+   /// Kinds an issue can be, with their templates and changelog headings
+   #[serde(default = "default_kinds")]
+   pub kinds: Vec<KindConfig>,
+
+   /// Work a day holds (e.g. `6h`), which `context` and `focus` weigh
+   /// today's planned issues against
+   #[serde(default)]
+   pub daily_capacity: Option<String>,
+
+   /// Working days, hours and holidays; when set, close times, forecasts
+   /// and due durations count working time only
+   #[serde(default)]
+   pub calendar: Option<WorkCalendar>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -84,10 +168,26 @@ fn default_issue_prefix() -> String {
    "ISSUE".to_string()
 }
 
+fn default_auto_alias() -> bool {
+   true
+}
+
 fn default_branch_prefix() -> String {
    "issue-".to_string()
 }
 
+fn default_similarity_threshold() -> f64 {
+   0.8
+}
+
+fn default_bulk_limit() -> usize {
+   10
+}
+
+fn default_copy_template() -> String {
+   "{ref}".to_string()
+}
+
 impl Default for Config {
    fn default() -> Self {
       Self {
@@ -97,35 +197,113 @@ impl Default for Config {
          issues_location:       None,
          colored_output:        default_colored_output(),
          issue_prefix:          default_issue_prefix(),
+         auto_alias:            default_auto_alias(),
+         alias_namespace:       None,
          git_integration:       GitIntegration::default(),
          templates_dir:         None,
+         similarity_threshold:  default_similarity_threshold(),
+         copy_template:         default_copy_template(),
+         effort:                EffortScale::default(),
+         watch:                 WatchConfig::default(),
+         transitions:           TransitionRules::default(),
+         activate_unblocked:    false,
+         read_only:             false,
+         compact:               CompactConfig::default(),
+         notify:                NotifyConfig::default(),
+         bulk_limit:            default_bulk_limit(),
+         lint:                  LintConfig::default(),
+         kinds:                 default_kinds(),
+         daily_capacity:        None,
+         calendar:              None,
       }
    }
 }
 
 impl Config {
+   /// The configured kind named `name`
+   pub fn kind(&self, name: &str) -> Result<&KindConfig> {
+      kinds::find(&self.kinds, name)
+   }
+
+   /// Parse a due time, counting durations in working time when a
+   /// calendar is set
+   pub fn parse_due(&self, s: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+      match &self.calendar {
+         Some(calendar) => calendar.parse_due(s, now),
+         None => crate::utils::parse_due(s, now),
+      }
+   }
+
    /// Get the formatted issue reference (e.g., "ISSUE-1" or "BUG-1")
    pub fn format_issue_ref(&self, num: u32) -> String {
       format!("{}-{}", self.issue_prefix, num)
    }
+
+   /// Whether issues may only be read, from `read_only` or `AGENTX_READONLY`
+   pub fn is_read_only(&self) -> bool {
+      self.read_only
+         || std::env::var("AGENTX_READONLY")
+            .is_ok_and(|v| !matches!(v.trim().to_lowercase().as_str(), "" | "0" | "false" | "no"))
+   }
+
+   /// Fill in `copy_template` for an issue (e.g. "ISSUE-12: Fix login")
+   pub fn format_copy(&self, num: u32, title: &str) -> String {
+      self
+         .copy_template
+         .replace("{ref}", &self.format_issue_ref(num))
+         .replace("{id}", &num.to_string())
+         .replace("{title}", title)
+   }
 }
 
 impl Config {
    /// Load config from .agentxrc.yaml
    /// Searches from current directory up to root
+   ///
+   /// Also installs the configured effort scale used by `parse_effort`.
    pub fn load() -> Self {
-      Self::find_and_load().unwrap_or_default()
+      let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+      Self::load_from(&cwd)
    }
 
-   fn find_and_load() -> Result<Self> {
-      let mut current_dir = std::env::current_dir()?;
+   /// Load the config governing `dir`, searching from it up to root, as
+   /// [`Config::load`] does from the current directory
+   pub fn load_from(dir: &Path) -> Self {
+      let config = Self::find_and_load(dir).unwrap_or_default();
+      set_effort_scale(config.effort.clone());
+      config
+   }
+
+   /// Load the user-level config, for the global store
+   pub fn load_global() -> Self {
+      let config = user_config_path()
+         .filter(|path| path.exists())
+         .and_then(|path| Self::read(&path).ok())
+         .unwrap_or_default();
+      set_effort_scale(config.effort.clone());
+      config
+   }
+
+   fn read(path: &Path) -> Result<Self> {
+      let content = std::fs::read_to_string(path)?;
+      Ok(serde_yaml::from_str(&content)?)
+   }
+
+   fn find_and_load(dir: &Path) -> Result<Self> {
+      // Before searching, so the search doesn't take the old file for a
+      // project config
+      let user_config = user_config_path();
+      if let (Some(home_dir), Some(user_config)) = (dirs::home_dir(), &user_config) {
+         migrate(&home_dir.join(".agentxrc.yaml"), user_config);
+      }
+
+      let mut current_dir = dir.to_path_buf();
 
       loop {
          let config_path = current_dir.join(".agentxrc.yaml");
 
          if config_path.exists() {
-            let content = std::fs::read_to_string(&config_path)?;
-            return Ok(serde_yaml::from_str(&content)?);
+            return Self::read(&config_path);
          }
 
          // Move to parent directory
@@ -134,12 +312,12 @@ impl Config {
          }
       }
 
-      // Also check home directory
-      if let Some(home_dir) = dirs::home_dir() {
-         let config_path = home_dir.join(".agentxrc.yaml");
+      // Then the user-level config, or where it used to be if it couldn't
+      // be moved
+      let legacy = dirs::home_dir().map(|home_dir| home_dir.join(".agentxrc.yaml"));
+      for config_path in [user_config, legacy].into_iter().flatten() {
          if config_path.exists() {
-            let content = std::fs::read_to_string(&config_path)?;
-            return Ok(serde_yaml::from_str(&content)?);
+            return Self::read(&config_path);
          }
       }
 
@@ -147,32 +325,122 @@ impl Config {
    }
 
    pub fn resolve_issues_directory(&self) -> PathBuf {
+      let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+      self.resolve_issues_directory_in(&cwd)
+   }
+
+   /// The issues directory for a project at `dir`, which stands in for the
+   /// current directory
+   pub fn resolve_issues_directory_in(&self, dir: &Path) -> PathBuf {
       match &self.issues_location {
-         Some(IssuesLocation::Cwd) | None => {
-            std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
-         },
+         Some(IssuesLocation::Cwd) | None => dir.to_path_buf(),
          Some(IssuesLocation::Fixed { path }) => path.clone(),
-         Some(IssuesLocation::Home { folder }) => {
-            if let Some(home_dir) = dirs::home_dir() {
-               home_dir.join(".agentx").join(folder)
-            } else {
-               std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
-            }
+         Some(IssuesLocation::Home { folder }) => match data_home() {
+            Some(data_home) => {
+               let path = data_home.join("projects").join(folder);
+               match dirs::home_dir() {
+                  Some(home_dir) => migrate(&home_dir.join(".agentx").join(folder), &path),
+                  None => path,
+               }
+            },
+            None => dir.to_path_buf(),
          },
       }
    }
+
+   /// Whether the issues directory is set by `issues_location` rather than
+   /// following the project directory
+   pub fn has_fixed_location(&self) -> bool {
+      matches!(
+         self.issues_location,
+         Some(IssuesLocation::Fixed { .. } | IssuesLocation::Home { .. })
+      )
+   }
+}
+
+/// The user-level config: `$XDG_CONFIG_HOME/agentx/config.yaml`, which
+/// used to be `~/.agentxrc.yaml`
+pub fn user_config_path() -> Option<PathBuf> {
+   dirs::config_dir().map(|dir| dir.join("agentx").join("config.yaml"))
+}
+
+/// Where agentx keeps user data: `$XDG_DATA_HOME/agentx`, holding the
+/// global store's `issues/` and `projects/<folder>` for `issues_location`
+/// `home` (once `~/.agentx/<folder>`)
+pub fn data_home() -> Option<PathBuf> {
+   dirs::data_dir().map(|dir| dir.join("agentx"))
+}
+
+/// The directory holding the global store's `issues/`, for personal tasks
+/// not tied to a repository
+pub fn global_store_dir() -> Result<PathBuf> {
+   data_home().ok_or_else(|| anyhow::anyhow!("Could not determine the user data directory"))
+}
+
+/// Move `old` to `new` if only the old one exists, returning the path to
+/// use: `new`, or `old` when it couldn't be moved
+fn migrate(old: &Path, new: &Path) -> PathBuf {
+   if new.exists() || !old.exists() {
+      return new.to_path_buf();
+   }
+   let moved = new
+      .parent()
+      .map_or(Ok(()), std::fs::create_dir_all)
+      .and_then(|()| std::fs::rename(old, new));
+   match moved {
+      Ok(()) => {
+         eprintln!("Moved {} to {}", old.display(), new.display());
+         new.to_path_buf()
+      },
+      Err(e) => {
+         eprintln!("Could not move {} to {}: {e}", old.display(), new.display());
+         old.to_path_buf()
+      },
+   }
 }
 
 #[cfg(test)]
 mod tests {
    use super::*;
 
+   #[test]
+   fn test_migrate() {
+      let dir = std::env::temp_dir().join(format!("agentx-migrate-{}", std::process::id()));
+      let old = dir.join(".agentxrc.yaml");
+      let new = dir.join("config").join("agentx").join("config.yaml");
+      std::fs::create_dir_all(&dir).unwrap();
+      std::fs::write(&old, "issue_prefix: OLD\n").unwrap();
+
+      assert_eq!(migrate(&old, &new), new);
+      assert!(!old.exists());
+      assert_eq!(Config::read(&new).unwrap().issue_prefix, "OLD");
+
+      // The new location wins once it exists
+      std::fs::write(&old, "issue_prefix: STALE\n").unwrap();
+      assert_eq!(migrate(&old, &new), new);
+      assert!(old.exists());
+      std::fs::remove_dir_all(&dir).unwrap();
+   }
+
+   #[test]
+   fn test_resolve_issues_directory_in() {
+      let project = Path::new("/work/project");
+      let mut config = Config::default();
+      assert_eq!(config.resolve_issues_directory_in(project), project);
+      assert!(!config.has_fixed_location());
+
+      config.issues_location = Some(IssuesLocation::Fixed { path: PathBuf::from("/srv/issues") });
+      assert_eq!(config.resolve_issues_directory_in(project), Path::new("/srv/issues"));
+      assert!(config.has_fixed_location());
+   }
+
    #[test]
    fn test_default_config() {
       let config = Config::default();
       assert_eq!(config.default_priority, "medium");
       assert_eq!(config.default_effort_unit, "hours");
       assert!(config.auto_status_detection);
+      assert_eq!(config.format_copy(12, "Fix login"), "ISSUE-12");
    }
 
    #[test]
@@ -184,8 +452,24 @@ mod tests {
          issues_location:       Some(IssuesLocation::Home { folder: "myproject".to_string() }),
          colored_output:        true,
          issue_prefix:          "ISSUE".to_string(),
+         auto_alias:            true,
+         alias_namespace:       Some("web".to_string()),
          git_integration:       GitIntegration::default(),
          templates_dir:         None,
+         similarity_threshold:  0.8,
+         copy_template:         "{ref}: {title}".to_string(),
+         effort:                EffortScale::default(),
+         watch:                 WatchConfig::default(),
+         transitions:           TransitionRules::default(),
+         activate_unblocked:    true,
+         read_only:             false,
+         compact:               CompactConfig::default(),
+         notify:                NotifyConfig::default(),
+         bulk_limit:            10,
+         lint:                  LintConfig::default(),
+         kinds:                 default_kinds(),
+         daily_capacity:        Some("6h".to_string()),
+         calendar:              Some(WorkCalendar::default()),
       };
 
       let yaml = serde_yaml::to_string(&config).unwrap();
@@ -0,0 +1,210 @@
+//! Encryption at rest for issue bodies
+//!
+//! `agentx crypt init` creates a key outside the repository and records its
+//! id in `issues/.crypt.yaml`. While the store is locked, issue bodies are
+//! written as AES-256-GCM ciphertext and only the frontmatter stays readable,
+//! so listing, filtering and focus keep working without the key. Storage
+//! decrypts bodies transparently when the key is available, either from
+//! `AGENTX_KEY` or from the key file.
+
+use std::{
+   fs,
+   path::{Path, PathBuf},
+};
+
+use aes_gcm::{
+   Aes256Gcm, Nonce,
+   aead::{Aead, AeadCore, KeyInit, OsRng, rand_core::RngCore},
+};
+use anyhow::{Context, Result};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use serde::{Deserialize, Serialize};
+
+use crate::store::IssueStore;
+
+const CONFIG_FILE: &str = ".crypt.yaml";
+const KEY_ENV: &str = "AGENTX_KEY";
+const NONCE_LEN: usize = 12;
+
+/// First line of an encrypted body; the base64 ciphertext follows it
+pub const MARKER: &str = "<!-- agentx:encrypted -->";
+
+/// Contents of `issues/.crypt.yaml`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CryptConfig {
+   pub key_id: String,
+   #[serde(default)]
+   pub locked: bool,
+}
+
+/// An AES-256 key
+#[derive(Clone)]
+pub struct Key([u8; 32]);
+
+impl Key {
+   pub fn generate() -> Self {
+      Self(Aes256Gcm::generate_key(OsRng).into())
+   }
+
+   fn from_base64(text: &str) -> Result<Self> {
+      let bytes = STANDARD
+         .decode(text.trim())
+         .context("Key is not valid base64")?;
+      let bytes: [u8; 32] = bytes
+         .try_into()
+         .map_err(|_| anyhow::anyhow!("Key must be 32 bytes"))?;
+      Ok(Self(bytes))
+   }
+
+   pub fn to_base64(&self) -> String {
+      STANDARD.encode(self.0)
+   }
+
+   fn cipher(&self) -> Aes256Gcm {
+      Aes256Gcm::new(&self.0.into())
+   }
+}
+
+pub fn config_path(issues_dir: &Path) -> PathBuf {
+   issues_dir.join(CONFIG_FILE)
+}
+
+/// The encryption settings `store` keeps, or `None` if `crypt init` was
+/// never run
+pub fn load_config(store: &dyn IssueStore, issues_dir: &Path) -> Result<Option<CryptConfig>> {
+   store
+      .read_string(&config_path(issues_dir))?
+      .map(|content| serde_yaml::from_str(&content).context("Failed to parse .crypt.yaml"))
+      .transpose()
+}
+
+pub fn save_config(
+   store: &dyn IssueStore,
+   issues_dir: &Path,
+   config: &CryptConfig,
+) -> Result<PathBuf> {
+   let path = config_path(issues_dir);
+   store.write(&path, serde_yaml::to_string(config)?.as_bytes())?;
+   Ok(path)
+}
+
+/// A fresh id to name a key file by
+pub fn new_key_id() -> String {
+   format!("{:016x}", OsRng.next_u64())
+}
+
+/// Where the key with `key_id` is kept, outside any repository
+pub fn key_file(key_id: &str) -> Result<PathBuf> {
+   let dir = dirs::config_dir().context("Could not find a config directory for the key")?;
+   Ok(dir
+      .join("agentx")
+      .join("keys")
+      .join(format!("{key_id}.key")))
+}
+
+pub fn store_key(key_id: &str, key: &Key) -> Result<PathBuf> {
+   let path = key_file(key_id)?;
+   if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+   }
+   fs::write(&path, key.to_base64())?;
+   #[cfg(unix)]
+   {
+      use std::os::unix::fs::PermissionsExt;
+      fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+   }
+   Ok(path)
+}
+
+/// The key for `key_id`, from `AGENTX_KEY` or the key file, if either is
+/// available
+pub fn load_key(key_id: &str) -> Result<Option<Key>> {
+   if let Ok(text) = std::env::var(KEY_ENV) {
+      return Key::from_base64(&text)
+         .map(Some)
+         .context("Invalid AGENTX_KEY");
+   }
+   let path = key_file(key_id)?;
+   if !path.exists() {
+      return Ok(None);
+   }
+   let text = fs::read_to_string(&path)?;
+   Key::from_base64(&text)
+      .map(Some)
+      .with_context(|| format!("Invalid key file {}", path.display()))
+}
+
+/// The key for `key_id`, failing with a hint when it isn't available
+pub fn require_key(key_id: &str) -> Result<Key> {
+   load_key(key_id)?.ok_or_else(|| {
+      anyhow::anyhow!(
+         "Issue bodies are encrypted and key {key_id} isn't available; set {KEY_ENV} or copy the \
+          key file to {}",
+         key_file(key_id)
+            .map_or_else(|_| "the agentx config directory".into(), |p| p.display().to_string())
+      )
+   })
+}
+
+pub fn is_encrypted(body: &str) -> bool {
+   body.trim_start().starts_with(MARKER)
+}
+
+/// Whether `body` is exactly one encrypted blob, with nothing added to it
+pub fn is_sealed(body: &str) -> bool {
+   body
+      .trim_start()
+      .strip_prefix(MARKER)
+      .is_some_and(|rest| !rest.trim().is_empty() && !rest.trim().contains(char::is_whitespace))
+}
+
+pub fn encrypt(key: &Key, body: &str) -> Result<String> {
+   let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+   let ciphertext = key
+      .cipher()
+      .encrypt(&nonce, body.as_bytes())
+      .map_err(|_| anyhow::anyhow!("Failed to encrypt issue body"))?;
+   let mut blob = nonce.to_vec();
+   blob.extend(ciphertext);
+   Ok(format!("{MARKER}\n{}\n", STANDARD.encode(blob)))
+}
+
+pub fn decrypt(key: &Key, body: &str) -> Result<String> {
+   let encoded = body
+      .trim_start()
+      .strip_prefix(MARKER)
+      .context("Issue body is not encrypted")?;
+   let blob = STANDARD
+      .decode(encoded.trim())
+      .context("Encrypted issue body is not valid base64")?;
+   if blob.len() < NONCE_LEN {
+      anyhow::bail!("Encrypted issue body is truncated");
+   }
+   let (nonce, ciphertext) = blob.split_at(NONCE_LEN);
+   let plaintext = key
+      .cipher()
+      .decrypt(Nonce::from_slice(nonce), ciphertext)
+      .map_err(|_| anyhow::anyhow!("Failed to decrypt issue body; is this the right key?"))?;
+   String::from_utf8(plaintext).context("Decrypted issue body is not UTF-8")
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn test_encrypt_round_trip() {
+      let key = Key::generate();
+      let body = "## Issue\nLogin times out\n\n## Checkpoints\n- done\n";
+      let sealed = encrypt(&key, body).unwrap();
+      assert!(is_encrypted(&sealed));
+      assert!(is_sealed(&sealed));
+      assert!(!sealed.contains("Login"));
+      assert_eq!(decrypt(&key, &sealed).unwrap(), body);
+
+      assert!(!is_sealed(&format!("{sealed}\n## Checkpoint\nmore\n")));
+      assert!(decrypt(&Key::generate(), &sealed).is_err());
+      let restored = Key::from_base64(&key.to_base64()).unwrap();
+      assert_eq!(decrypt(&restored, &sealed).unwrap(), body);
+   }
+}
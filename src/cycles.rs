@@ -0,0 +1,189 @@
+//! Dependency cycle repair
+//!
+//! A cycle in `depends_on` means none of its issues can ever be started, so
+//! one edge has to go. `agentx cycles` walks one loop through every strongly
+//! connected group of open issues and suggests the weakest edge in it: the
+//! one added most recently, according to git, since that is usually the
+//! edge that closed the loop; when history can't tell the edges apart, the
+//! one joining the least important pair of issues.
+
+use std::collections::{HashMap, HashSet, VecDeque, hash_map::Entry};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+   history::Version,
+   issue::{IssueWithId, Priority},
+};
+
+/// `dependent` depends on `dependency`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CycleEdge {
+   pub dependent:           u32,
+   pub dependency:          u32,
+   pub dependent_priority:  Priority,
+   pub dependency_priority: Priority,
+   /// When the dependency was last added, if git history knows
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub added:               Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cycle {
+   /// Issues in the loop, each depending on the next and the last on the
+   /// first
+   pub issues:    Vec<u32>,
+   /// Every open issue caught in the same tangle, which can hold more than
+   /// one loop
+   pub component: Vec<u32>,
+   pub edges:     Vec<CycleEdge>,
+   /// Index into `edges` of the edge to drop
+   pub weakest:   usize,
+   /// Why that edge was picked
+   pub reason:    String,
+}
+
+impl Cycle {
+   pub fn suggested(&self) -> &CycleEdge {
+      &self.edges[self.weakest]
+   }
+}
+
+/// The shortest loop through the lowest-numbered issue of `component`, a
+/// strongly connected group of `open` issues
+pub fn loop_through(component: &[u32], open: &[IssueWithId]) -> Vec<u32> {
+   let members: HashSet<u32> = component.iter().copied().collect();
+   let depends_on: HashMap<u32, &[u32]> = open
+      .iter()
+      .filter(|i| members.contains(&i.id))
+      .map(|i| (i.id, i.issue.metadata.depends_on.as_slice()))
+      .collect();
+   let Some(&start) = component.iter().min() else {
+      return Vec::new();
+   };
+
+   let mut came_from: HashMap<u32, u32> = HashMap::new();
+   let mut queue = VecDeque::from([start]);
+   while let Some(id) = queue.pop_front() {
+      for &dep in depends_on.get(&id).copied().unwrap_or_default() {
+         if !members.contains(&dep) {
+            continue;
+         }
+         if dep == start {
+            let mut path = vec![id];
+            while let Some(&prev) = came_from.get(path.last().unwrap()) {
+               path.push(prev);
+            }
+            path.reverse();
+            return path;
+         }
+         if let Entry::Vacant(entry) = came_from.entry(dep) {
+            entry.insert(id);
+            queue.push_back(dep);
+         }
+      }
+   }
+   Vec::new()
+}
+
+/// When `versions` of an issue last gained a dependency on `dependency`
+pub fn dependency_added(versions: &[Version], dependency: u32) -> Option<DateTime<Utc>> {
+   let mut added = None;
+   let mut had = false;
+   for version in versions {
+      let has = version.issue.metadata.depends_on.contains(&dependency);
+      if has && !had {
+         added = Some(version.time);
+      }
+      had = has;
+   }
+   added
+}
+
+/// The loop through `component` with its weakest edge picked, dating edges
+/// with `added`
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn cycle(
+   component: &[u32],
+   open: &[IssueWithId],
+   added: impl Fn(u32, u32) -> Option<DateTime<Utc>>,
+) -> Cycle {
+   let priority: HashMap<u32, Priority> = open
+      .iter()
+      .map(|i| (i.id, i.issue.metadata.priority))
+      .collect();
+   let issues = loop_through(component, open);
+   let edges: Vec<CycleEdge> = issues
+      .iter()
+      .zip(issues.iter().cycle().skip(1))
+      .map(|(&dependent, &dependency)| CycleEdge {
+         dependent,
+         dependency,
+         dependent_priority: priority[&dependent],
+         dependency_priority: priority[&dependency],
+         added: added(dependent, dependency),
+      })
+      .collect();
+
+   // Priority orders most important first, so the weakest pair is the one
+   // whose more important end ranks lowest
+   let pair = |edge: &CycleEdge| {
+      let (a, b) = (edge.dependent_priority, edge.dependency_priority);
+      (a.min(b), a.max(b))
+   };
+   let weakest = (0..edges.len())
+      .max_by_key(|&i| (edges[i].added, pair(&edges[i]), std::cmp::Reverse(i)))
+      .unwrap_or(0);
+
+   let newest = edges[weakest].added;
+   let dated_apart = newest.is_some() && edges.iter().filter(|e| e.added == newest).count() == 1;
+   let reason = match newest {
+      Some(at) if dated_apart => format!("added most recently, {}", at.format("%Y-%m-%d %H:%M")),
+      _ => {
+         let (high, low) = pair(&edges[weakest]);
+         format!("lowest-priority pair ({high}/{low})")
+      },
+   };
+
+   let mut component = component.to_vec();
+   component.sort_unstable();
+   Cycle { issues, component, edges, weakest, reason }
+}
+
+#[cfg(test)]
+mod tests {
+   use chrono::Duration;
+
+   use super::*;
+   use crate::issue::test_support::issue;
+
+   #[test]
+   fn test_cycle_suggests_weakest_edge() {
+      let open = vec![
+         issue(1)
+            .priority(Priority::Critical)
+            .depends_on(&[2])
+            .build(),
+         issue(2).priority(Priority::Low).depends_on(&[3, 4]).build(),
+         issue(3).priority(Priority::Low).depends_on(&[1]).build(),
+         issue(4).priority(Priority::High).build(),
+      ];
+      let component = [3, 1, 2];
+      assert_eq!(loop_through(&component, &open), [1, 2, 3]);
+
+      let undated = cycle(&component, &open, |_, _| None);
+      let edge = undated.suggested();
+      assert_eq!((edge.dependent, edge.dependency), (2, 3));
+      assert_eq!(undated.reason, "lowest-priority pair (low/low)");
+      assert_eq!(undated.component, [1, 2, 3]);
+
+      let now = Utc::now();
+      let dated = cycle(&component, &open, |dependent, _| {
+         Some(now - Duration::days(if dependent == 3 { 1 } else { 10 }))
+      });
+      let edge = dated.suggested();
+      assert_eq!((edge.dependent, edge.dependency), (3, 1));
+      assert!(dated.reason.starts_with("added most recently"));
+   }
+}
@@ -0,0 +1,367 @@
+//! Background process that keeps the parsed issue set in memory
+//!
+//! `agentx daemon` listens on a Unix socket inside the issues directory and
+//! answers one JSON request per line. The CLI's [`Storage`] asks it for the
+//! open and closed issue lists before falling back to parsing the files, and
+//! tells it to drop its cache after every write. Edits made outside agentx are
+//! picked up by a file watcher. Clients that send `subscribe` get a `changed`
+//! line pushed whenever the issue set changes.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::issue::IssueWithId;
+
+/// Socket file, relative to the directory holding `issues/`
+pub const SOCKET_FILE: &str = "issues/.agentx.sock";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum Request {
+   List { closed: bool },
+   Invalidate,
+   Subscribe,
+   Status,
+   Shutdown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Response {
+   Issues { issues: Vec<IssueWithId> },
+   Ok,
+   Changed { generation: u64 },
+   Status(DaemonStatus),
+   Error { message: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonStatus {
+   pub pid:        u32,
+   pub started:    DateTime<Utc>,
+   /// Bumped every time the cache is invalidated
+   pub generation: u64,
+   /// Issue counts currently held in memory, `None` if not loaded yet
+   pub open:       Option<usize>,
+   pub closed:     Option<usize>,
+}
+
+pub fn socket_path(base_dir: &Path) -> PathBuf {
+   base_dir.join(SOCKET_FILE)
+}
+
+/// Send one request to the daemon serving `base_dir`, `None` if no daemon is
+/// running or it did not answer
+pub fn request(base_dir: &Path, request: &Request) -> Option<Response> {
+   imp::request(base_dir, request)
+}
+
+/// Issue list from a running daemon, `None` if there is none
+#[tracing::instrument(level = "debug", skip(base_dir))]
+pub fn cached_issues(base_dir: &Path, closed: bool) -> Option<Vec<IssueWithId>> {
+   match request(base_dir, &Request::List { closed })? {
+      Response::Issues { issues } => Some(issues),
+      _ => None,
+   }
+}
+
+/// Tell a running daemon its cache is stale; does nothing if none is running
+pub fn invalidate(base_dir: &Path) {
+   request(base_dir, &Request::Invalidate);
+}
+
+/// Serve `base_dir` in the foreground until asked to shut down
+pub fn run(base_dir: &Path) -> anyhow::Result<()> {
+   imp::run(base_dir)
+}
+
+/// Block until the daemon reports a change, calling `on_change` for each;
+/// returns when the daemon goes away
+pub fn subscribe(base_dir: &Path, on_change: impl FnMut(u64)) -> anyhow::Result<()> {
+   imp::subscribe(base_dir, on_change)
+}
+
+#[cfg(unix)]
+mod imp {
+   use std::{
+      fs,
+      io::{BufRead, BufReader, Write},
+      os::unix::net::{UnixListener, UnixStream},
+      path::{Path, PathBuf},
+      sync::{Arc, Mutex},
+      time::Duration,
+   };
+
+   use anyhow::{Context, Result};
+   use chrono::Utc;
+   use notify::{EventKind, RecursiveMode, Watcher};
+
+   use super::{DaemonStatus, Request, Response, socket_path};
+   use crate::{issue::IssueWithId, storage::Storage};
+
+   /// How long a client waits on the daemon before parsing files itself
+   const CLIENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+   #[derive(Default)]
+   struct Cache {
+      open:       Option<Vec<IssueWithId>>,
+      closed:     Option<Vec<IssueWithId>>,
+      generation: u64,
+   }
+
+   struct State {
+      storage:     Storage,
+      socket:      PathBuf,
+      status:      Mutex<DaemonStatus>,
+      cache:       Mutex<Cache>,
+      subscribers: Mutex<Vec<UnixStream>>,
+   }
+
+   impl State {
+      fn issues(&self, closed: bool) -> Result<Vec<IssueWithId>> {
+         let mut cache = self.cache.lock().unwrap();
+         let slot = if closed {
+            &mut cache.closed
+         } else {
+            &mut cache.open
+         };
+         if let Some(issues) = slot {
+            return Ok(issues.clone());
+         }
+         let issues = if closed {
+            self.storage.list_closed_issues()?
+         } else {
+            self.storage.list_open_issues()?
+         };
+         *slot = Some(issues.clone());
+         Ok(issues)
+      }
+
+      fn invalidate(&self) {
+         let generation = {
+            let mut cache = self.cache.lock().unwrap();
+            cache.open = None;
+            cache.closed = None;
+            cache.generation += 1;
+            cache.generation
+         };
+         let Ok(mut line) = serde_json::to_string(&Response::Changed { generation }) else {
+            return;
+         };
+         line.push('\n');
+         self
+            .subscribers
+            .lock()
+            .unwrap()
+            .retain_mut(|s| s.write_all(line.as_bytes()).is_ok());
+      }
+
+      fn status(&self) -> DaemonStatus {
+         let cache = self.cache.lock().unwrap();
+         DaemonStatus {
+            generation: cache.generation,
+            open: cache.open.as_ref().map(Vec::len),
+            closed: cache.closed.as_ref().map(Vec::len),
+            ..self.status.lock().unwrap().clone()
+         }
+      }
+   }
+
+   fn connect(base_dir: &Path) -> Option<UnixStream> {
+      let stream = UnixStream::connect(socket_path(base_dir)).ok()?;
+      stream.set_read_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+      stream.set_write_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+      Some(stream)
+   }
+
+   fn send(stream: &mut UnixStream, request: &Request) -> Option<()> {
+      let mut line = serde_json::to_string(request).ok()?;
+      line.push('\n');
+      stream.write_all(line.as_bytes()).ok()
+   }
+
+   pub(super) fn request(base_dir: &Path, request: &Request) -> Option<Response> {
+      let mut stream = connect(base_dir)?;
+      send(&mut stream, request)?;
+      let mut line = String::new();
+      BufReader::new(stream).read_line(&mut line).ok()?;
+      serde_json::from_str(&line).ok()
+   }
+
+   pub(super) fn subscribe(base_dir: &Path, mut on_change: impl FnMut(u64)) -> Result<()> {
+      let mut stream = connect(base_dir).context("No daemon running")?;
+      send(&mut stream, &Request::Subscribe).context("Failed to subscribe")?;
+      stream.set_read_timeout(None)?;
+      for line in BufReader::new(stream).lines() {
+         if let Ok(Response::Changed { generation }) = serde_json::from_str(&line?) {
+            on_change(generation);
+         }
+      }
+      Ok(())
+   }
+
+   fn handle(state: &State, stream: UnixStream) -> Result<()> {
+      let mut writer = stream.try_clone()?;
+      for line in BufReader::new(stream).lines() {
+         let line = line?;
+         if line.trim().is_empty() {
+            continue;
+         }
+         let request: Request = match serde_json::from_str(&line) {
+            Ok(request) => request,
+            Err(e) => {
+               let message = format!("Invalid request: {e}");
+               writeln!(writer, "{}", serde_json::to_string(&Response::Error { message })?)?;
+               continue;
+            },
+         };
+         let response = match request {
+            Request::List { closed } => match state.issues(closed) {
+               Ok(issues) => Response::Issues { issues },
+               Err(e) => Response::Error { message: format!("{e:#}") },
+            },
+            Request::Invalidate => {
+               state.invalidate();
+               Response::Ok
+            },
+            Request::Subscribe => {
+               state.subscribers.lock().unwrap().push(writer.try_clone()?);
+               continue;
+            },
+            Request::Status => Response::Status(state.status()),
+            Request::Shutdown => {
+               writeln!(writer, "{}", serde_json::to_string(&Response::Ok)?)?;
+               let _ = fs::remove_file(&state.socket);
+               std::process::exit(0);
+            },
+         };
+         writeln!(writer, "{}", serde_json::to_string(&response)?)?;
+      }
+      Ok(())
+   }
+
+   pub(super) fn run(base_dir: &Path) -> Result<()> {
+      let socket = socket_path(base_dir);
+      if socket.exists() {
+         if UnixStream::connect(&socket).is_ok() {
+            anyhow::bail!("A daemon is already serving {}", base_dir.display());
+         }
+         // Left behind by a daemon that did not shut down cleanly
+         fs::remove_file(&socket)?;
+      }
+      let issues_dir = socket.parent().context("Invalid socket path")?;
+      fs::create_dir_all(issues_dir)?;
+      let listener = UnixListener::bind(&socket)
+         .with_context(|| format!("Failed to bind {}", socket.display()))?;
+
+      let state = Arc::new(State {
+         storage:     Storage::new(base_dir),
+         socket:      socket.clone(),
+         status:      Mutex::new(DaemonStatus {
+            pid:        std::process::id(),
+            started:    Utc::now(),
+            generation: 0,
+            open:       None,
+            closed:     None,
+         }),
+         cache:       Mutex::new(Cache::default()),
+         subscribers: Mutex::new(Vec::new()),
+      });
+
+      let watched = Arc::clone(&state);
+      let socket_file = socket.clone();
+      let mut watcher =
+         notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else { return };
+            let relevant = matches!(
+               event.kind,
+               EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+            ) && event.paths.iter().any(|p| *p != socket_file);
+            if relevant {
+               watched.invalidate();
+            }
+         })?;
+      watcher.watch(issues_dir, RecursiveMode::Recursive)?;
+
+      println!("agentx daemon serving {} (pid {})", base_dir.display(), std::process::id());
+      println!("Socket: {}", socket.display());
+
+      for stream in listener.incoming() {
+         let Ok(stream) = stream else { continue };
+         let state = Arc::clone(&state);
+         std::thread::spawn(move || {
+            let _ = handle(&state, stream);
+         });
+      }
+      Ok(())
+   }
+}
+
+#[cfg(not(unix))]
+mod imp {
+   use std::path::Path;
+
+   use anyhow::Result;
+
+   use super::{Request, Response};
+
+   pub(super) fn request(_base_dir: &Path, _request: &Request) -> Option<Response> {
+      None
+   }
+
+   pub(super) fn subscribe(_base_dir: &Path, _on_change: impl FnMut(u64)) -> Result<()> {
+      anyhow::bail!("The daemon requires Unix domain sockets")
+   }
+
+   pub(super) fn run(_base_dir: &Path) -> Result<()> {
+      anyhow::bail!("The daemon requires Unix domain sockets")
+   }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+   use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+   use super::*;
+   use crate::{issue::test_support::issue, storage::Storage};
+
+   #[test]
+   fn test_daemon_serves_and_refreshes_issues() {
+      let nanos = SystemTime::now()
+         .duration_since(UNIX_EPOCH)
+         .unwrap()
+         .as_nanos();
+      let dir = std::env::temp_dir().join(format!("agentx-daemon-{nanos}"));
+      let storage = Storage::new(&dir);
+      storage
+         .save_issue(&issue(1).title("First").build().issue, 1, true)
+         .unwrap();
+
+      assert!(cached_issues(&dir, false).is_none());
+      let served = dir.clone();
+      std::thread::spawn(move || run(&served));
+      let mut issues = None;
+      for _ in 0..100 {
+         issues = cached_issues(&dir, false);
+         if issues.is_some() {
+            break;
+         }
+         std::thread::sleep(Duration::from_millis(20));
+      }
+      assert_eq!(issues.unwrap().len(), 1);
+
+      storage
+         .save_issue(&issue(2).title("Second").build().issue, 2, true)
+         .unwrap();
+      invalidate(&dir);
+      assert_eq!(cached_issues(&dir, false).unwrap().len(), 2);
+      match request(&dir, &Request::Status) {
+         Some(Response::Status(status)) => assert!(status.generation >= 1),
+         other => panic!("unexpected response: {other:?}"),
+      }
+
+      let _ = std::fs::remove_dir_all(&dir);
+   }
+}
@@ -0,0 +1,277 @@
+//! Periodic digests
+//!
+//! `agentx digest` sums up a day, week or month for people who don't follow
+//! the tracker closely: issues opened, closed and blocked, open issues gone
+//! stale, and how the period's numbers compare with the period before. The
+//! markdown only depends on the issues and the clock, so cron and CI jobs can
+//! commit or mail it as-is.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::issue::{IssueWithId, Priority, Status};
+
+/// Length of a `day`, `week` or `month` period
+pub fn period_length(period: &str) -> anyhow::Result<Duration> {
+   Ok(match period {
+      "day" => Duration::days(1),
+      "week" => Duration::weeks(1),
+      "month" => Duration::days(30),
+      _ => anyhow::bail!("Invalid period: {period}. Use: day, week, month"),
+   })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestIssue {
+   pub num:      u32,
+   pub title:    String,
+   pub priority: Priority,
+   pub status:   Status,
+   /// Blocked reason, or how long a stale issue has been idle
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub note:     Option<String>,
+}
+
+/// A number for this period next to the same number for the period before
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct MetricDelta {
+   pub current:  i64,
+   pub previous: i64,
+   pub change:   i64,
+}
+
+impl MetricDelta {
+   fn new(current: i64, previous: i64) -> Self {
+      Self { current, previous, change: current - previous }
+   }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestMetrics {
+   pub opened:          MetricDelta,
+   pub closed:          MetricDelta,
+   /// Average hours from creation to close, over the issues closed
+   pub avg_close_hours: MetricDelta,
+   /// Issues still open when the period ended
+   pub open_at_end:     MetricDelta,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Digest {
+   pub period:     String,
+   pub from:       DateTime<Utc>,
+   pub to:         DateTime<Utc>,
+   pub stale_days: i64,
+   pub new:        Vec<DigestIssue>,
+   pub closed:     Vec<DigestIssue>,
+   pub blocked:    Vec<DigestIssue>,
+   pub stale:      Vec<DigestIssue>,
+   pub metrics:    DigestMetrics,
+}
+
+/// The digest of the `length` before `now`, for open and closed `issues`
+pub fn digest(
+   issues: &[IssueWithId],
+   period: &str,
+   length: Duration,
+   stale_days: i64,
+   now: DateTime<Utc>,
+) -> Digest {
+   let from = now - length;
+   let within = |at: DateTime<Utc>| at > from && at <= now;
+   let entry = |issue: &IssueWithId, note: Option<String>| DigestIssue {
+      num: issue.id,
+      title: issue.issue.metadata.title.to_string(),
+      priority: issue.issue.metadata.priority,
+      status: issue.issue.metadata.status,
+      note,
+   };
+
+   let mut new = Vec::new();
+   let mut closed = Vec::new();
+   let mut blocked = Vec::new();
+   let mut stale = Vec::new();
+   for issue in issues {
+      let meta = &issue.issue.metadata;
+      if within(meta.created) {
+         new.push(entry(issue, None));
+      }
+      match meta.closed {
+         Some(at) => {
+            if within(at) {
+               closed.push(entry(issue, None));
+            }
+         },
+         None if meta.status == Status::Blocked => {
+            blocked.push(entry(issue, meta.blocked_reason.as_ref().map(|r| r.to_string())));
+         },
+         None if meta.status != Status::Backlog => {
+            let idle = (now - issue.issue.last_activity()).num_days();
+            if idle >= stale_days {
+               stale.push(entry(issue, Some(format!("idle {idle} days"))));
+            }
+         },
+         None => {},
+      }
+   }
+   for section in [&mut new, &mut closed, &mut blocked, &mut stale] {
+      section.sort_by_key(|issue| issue.num);
+   }
+
+   let current = window(issues, from, now);
+   let previous = window(issues, from - length, from);
+   Digest {
+      period: period.to_string(),
+      from,
+      to: now,
+      stale_days,
+      new,
+      closed,
+      blocked,
+      stale,
+      metrics: DigestMetrics {
+         opened:          MetricDelta::new(current.opened, previous.opened),
+         closed:          MetricDelta::new(current.closed, previous.closed),
+         avg_close_hours: MetricDelta::new(current.avg_close_hours, previous.avg_close_hours),
+         open_at_end:     MetricDelta::new(current.open_at_end, previous.open_at_end),
+      },
+   }
+}
+
+struct Window {
+   opened:          i64,
+   closed:          i64,
+   avg_close_hours: i64,
+   open_at_end:     i64,
+}
+
+fn window(issues: &[IssueWithId], from: DateTime<Utc>, to: DateTime<Utc>) -> Window {
+   let within = |at: DateTime<Utc>| at > from && at <= to;
+   let opened = issues
+      .iter()
+      .filter(|i| within(i.issue.metadata.created))
+      .count();
+   let close_hours: Vec<i64> = issues
+      .iter()
+      .filter_map(|i| {
+         let meta = &i.issue.metadata;
+         meta
+            .closed
+            .filter(|&at| within(at))
+            .map(|at| (at - meta.created).num_hours())
+      })
+      .collect();
+   let open_at_end = issues
+      .iter()
+      .filter(|i| {
+         let meta = &i.issue.metadata;
+         meta.created <= to && meta.closed.is_none_or(|at| at > to)
+      })
+      .count();
+
+   Window {
+      opened:          opened as i64,
+      closed:          close_hours.len() as i64,
+      avg_close_hours: if close_hours.is_empty() {
+         0
+      } else {
+         close_hours.iter().sum::<i64>() / close_hours.len() as i64
+      },
+      open_at_end:     open_at_end as i64,
+   }
+}
+
+impl Digest {
+   /// Markdown for a file or an email, with issues referred to by `issue_ref`
+   pub fn to_markdown(&self, issue_ref: impl Fn(u32) -> String) -> String {
+      let mut text = format!(
+         "# Digest: {} {} to {}\n\n## Numbers\n\n| | This {} | Previous | Change \
+          |\n|---|---:|---:|---:|\n",
+         self.period,
+         self.from.format("%Y-%m-%d"),
+         self.to.format("%Y-%m-%d"),
+         self.period
+      );
+      for (label, metric) in [
+         ("Opened", self.metrics.opened),
+         ("Closed", self.metrics.closed),
+         ("Avg. hours to close", self.metrics.avg_close_hours),
+         ("Open at end", self.metrics.open_at_end),
+      ] {
+         text.push_str(&format!(
+            "| {label} | {} | {} | {:+} |\n",
+            metric.current, metric.previous, metric.change
+         ));
+      }
+
+      let stale = format!("Stale, idle {}+ days", self.stale_days);
+      for (heading, issues) in [
+         ("New", &self.new),
+         ("Closed", &self.closed),
+         ("Blocked", &self.blocked),
+         (stale.as_str(), &self.stale),
+      ] {
+         text.push_str(&format!("\n## {heading} ({})\n\n", issues.len()));
+         if issues.is_empty() {
+            text.push_str("None\n");
+         }
+         for issue in issues {
+            text.push_str(&format!(
+               "- {} {} ({}, {})",
+               issue_ref(issue.num),
+               issue.title,
+               issue.priority,
+               issue.status
+            ));
+            if let Some(note) = &issue.note {
+               text.push_str(&format!(" - {note}"));
+            }
+            text.push('\n');
+         }
+      }
+      text
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::issue::test_support::issue;
+
+   #[test]
+   fn test_digest_sections_and_deltas() {
+      let now = Utc::now();
+      let days_ago = |days| now - Duration::days(days);
+      let fresh = issue(1).title("Fresh").created(days_ago(2)).build();
+      let done = issue(2)
+         .title("Done")
+         .created(days_ago(10))
+         .status(Status::Closed)
+         .closed(days_ago(1))
+         .build();
+      let mut stuck = issue(3)
+         .title("Stuck")
+         .created(days_ago(9))
+         .status(Status::Blocked)
+         .build();
+      stuck.issue.metadata.blocked_reason = Some("waiting on vendor".into());
+      let old = issue(4).title("Old").created(days_ago(30)).build();
+
+      let digest = digest(&[fresh, done, stuck, old], "week", Duration::weeks(1), 14, now);
+      let nums = |issues: &[DigestIssue]| issues.iter().map(|i| i.num).collect::<Vec<_>>();
+      assert_eq!(nums(&digest.new), [1]);
+      assert_eq!(nums(&digest.closed), [2]);
+      assert_eq!(nums(&digest.blocked), [3]);
+      assert_eq!(nums(&digest.stale), [4]);
+      assert_eq!(digest.metrics.closed.change, 1);
+      // #2 and #3 were opened the week before
+      assert_eq!(digest.metrics.opened.previous, 2);
+      assert_eq!(digest.metrics.open_at_end.current, 3);
+      assert_eq!(digest.metrics.open_at_end.previous, 3);
+
+      let markdown = digest.to_markdown(|n| format!("#{n}"));
+      assert!(markdown.contains("| Closed | 1 | 0 | +1 |"));
+      assert!(markdown.contains("- #3 Stuck (medium, blocked) - waiting on vendor"));
+      assert!(markdown.contains("- #4 Old (medium, open) - idle 30 days"));
+   }
+}
@@ -0,0 +1,256 @@
+//! Tracker export
+//!
+//! `agentx export` (and the `issues_export` MCP tool) writes the issues
+//! matching a filter as one document: JSON or YAML with every frontmatter
+//! field and the body, a markdown report, or a CSV sheet of the main fields.
+//! Agents use it to snapshot the tracker into docs or hand-offs; over MCP,
+//! long documents come back in chunks.
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::issue::{IssueMetadata, IssueWithId};
+
+/// Bytes per chunk of an export served over MCP
+pub const CHUNK_BYTES: usize = 50_000;
+
+const CSV_COLUMNS: &[&str] = &[
+   "num",
+   "title",
+   "title_i18n",
+   "status",
+   "priority",
+   "tags",
+   "depends_on",
+   "effort",
+   "milestone",
+   "created",
+   "closed",
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+   #[default]
+   Json,
+   Yaml,
+   Markdown,
+   Csv,
+}
+
+impl ExportFormat {
+   pub fn mime_type(self) -> &'static str {
+      match self {
+         Self::Json => "application/json",
+         Self::Yaml => "application/yaml",
+         Self::Markdown => "text/markdown",
+         Self::Csv => "text/csv",
+      }
+   }
+}
+
+/// Parse `--format`
+pub fn parse_format(s: &str) -> Result<ExportFormat> {
+   Ok(match s.to_ascii_lowercase().as_str() {
+      "json" => ExportFormat::Json,
+      "yaml" | "yml" => ExportFormat::Yaml,
+      "markdown" | "md" => ExportFormat::Markdown,
+      "csv" => ExportFormat::Csv,
+      _ => anyhow::bail!("Unknown export format: {s}. Use: json, yaml, markdown, csv"),
+   })
+}
+
+/// The format an `--out` file's extension asks for, if any
+pub fn format_for_path(path: &str) -> Option<ExportFormat> {
+   let (_, ext) = path.rsplit_once('.')?;
+   parse_format(ext).ok()
+}
+
+#[derive(Serialize)]
+struct ExportedIssue<'a> {
+   num:      u32,
+   #[serde(flatten)]
+   metadata: &'a IssueMetadata,
+   body:     String,
+}
+
+/// The issues as one document in `format`
+pub fn render(
+   issues: &[IssueWithId],
+   format: ExportFormat,
+   issue_ref: impl Fn(u32) -> String,
+) -> Result<String> {
+   let exported = || -> Vec<ExportedIssue<'_>> {
+      issues
+         .iter()
+         .map(|i| ExportedIssue {
+            num:      i.id,
+            metadata: &i.issue.metadata,
+            body:     i.issue.body.to_string(),
+         })
+         .collect()
+   };
+   Ok(match format {
+      ExportFormat::Json => format!("{}\n", serde_json::to_string_pretty(&exported())?),
+      ExportFormat::Yaml => serde_yaml::to_string(&exported())?,
+      ExportFormat::Markdown => markdown(issues, issue_ref),
+      ExportFormat::Csv => csv(issues),
+   })
+}
+
+fn markdown(issues: &[IssueWithId], issue_ref: impl Fn(u32) -> String) -> String {
+   let mut out = format!("# Issues ({})\n", issues.len());
+   for issue in issues {
+      let meta = &issue.issue.metadata;
+      out.push_str(&format!("\n## {}: {}\n\n", issue_ref(issue.id), meta.title));
+      for (lang, title) in &meta.title_i18n {
+         out.push_str(&format!("- **Title [{lang}]:** {title}\n"));
+      }
+      out.push_str(&format!("- **Status:** {}\n", meta.status));
+      out.push_str(&format!("- **Priority:** {}\n", meta.priority));
+      if !meta.tags.is_empty() {
+         out.push_str(&format!("- **Tags:** {}\n", meta.tags.join(", ")));
+      }
+      if let Some(effort) = &meta.effort {
+         out.push_str(&format!("- **Effort:** {effort}\n"));
+      }
+      if let Some(milestone) = &meta.milestone {
+         out.push_str(&format!("- **Milestone:** {milestone}\n"));
+      }
+      if !meta.depends_on.is_empty() {
+         let deps: Vec<String> = meta.depends_on.iter().map(|&n| issue_ref(n)).collect();
+         out.push_str(&format!("- **Depends on:** {}\n", deps.join(", ")));
+      }
+      let body = issue.issue.body.to_string();
+      if !body.trim().is_empty() {
+         out.push('\n');
+      }
+      // The body's headings go below the issue's own
+      for line in body.trim().lines() {
+         if line.starts_with('#') {
+            out.push_str("##");
+         }
+         out.push_str(line);
+         out.push('\n');
+      }
+   }
+   out
+}
+
+fn csv(issues: &[IssueWithId]) -> String {
+   let mut out = format!("{}\n", CSV_COLUMNS.join(","));
+   for issue in issues {
+      let meta = &issue.issue.metadata;
+      let list = |items: Vec<String>| items.join(";");
+      let row = [
+         issue.id.to_string(),
+         meta.title.to_string(),
+         list(
+            meta
+               .title_i18n
+               .iter()
+               .map(|(lang, title)| format!("{lang}: {title}"))
+               .collect(),
+         ),
+         meta.status.to_string(),
+         meta.priority.to_string(),
+         list(meta.tags.iter().map(|t| t.to_string()).collect()),
+         list(meta.depends_on.iter().map(|n| n.to_string()).collect()),
+         meta.effort.as_deref().unwrap_or_default().to_string(),
+         meta.milestone.as_deref().unwrap_or_default().to_string(),
+         meta.created.to_rfc3339(),
+         meta.closed.map(|c| c.to_rfc3339()).unwrap_or_default(),
+      ];
+      let fields: Vec<String> = row.iter().map(|f| csv_field(f)).collect();
+      out.push_str(&fields.join(","));
+      out.push('\n');
+   }
+   out
+}
+
+/// Quote a field holding a comma, quote or line break, doubling its quotes
+fn csv_field(field: &str) -> String {
+   if field.contains([',', '"', '\n', '\r']) {
+      format!("\"{}\"", field.replace('"', "\"\""))
+   } else {
+      field.to_string()
+   }
+}
+
+/// Chunk `index` of `text` cut at line ends into pieces of at most about
+/// `size` bytes, with the number of chunks; `None` past the last
+pub fn chunk(text: &str, size: usize, index: usize) -> Option<(&str, usize)> {
+   let mut bounds = vec![0];
+   let mut start = 0;
+   while text.len() - start > size {
+      let limit = text.floor_char_boundary(start + size);
+      let end = match text[start..limit].rfind('\n') {
+         Some(newline) => start + newline + 1,
+         None => limit.max(text.ceil_char_boundary(start + 1)),
+      };
+      bounds.push(end);
+      start = end;
+   }
+   bounds.push(text.len());
+   let count = bounds.len() - 1;
+   (index < count).then(|| (&text[bounds[index]..bounds[index + 1]], count))
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::issue::{Priority, test_support::issue};
+
+   #[test]
+   fn test_render_and_chunk() {
+      let mut fixture = issue(7)
+         .title("Fix \"login\", again")
+         .priority(Priority::High)
+         .tags(&["auth", "ui"])
+         .body("Broken")
+         .impact("Users locked out")
+         .acceptance("Can log in")
+         .effort("2h")
+         .depends_on(&[3])
+         .build();
+      fixture
+         .issue
+         .metadata
+         .title_i18n
+         .insert("de".into(), "Login reparieren".into());
+      let issues = [fixture];
+      let issue_ref = |n: u32| format!("#{n}");
+
+      let csv = render(&issues, ExportFormat::Csv, issue_ref).unwrap();
+      let row = csv.lines().nth(1).unwrap();
+      assert!(row.starts_with(
+         "7,\"Fix \"\"login\"\", again\",de: Login reparieren,open,high,auth;ui,3,2h,,"
+      ));
+
+      let json: serde_json::Value =
+         serde_json::from_str(&render(&issues, ExportFormat::Json, issue_ref).unwrap()).unwrap();
+      assert_eq!((json[0]["num"].as_u64(), json[0]["effort"].as_str()), (Some(7), Some("2h")));
+      assert!(
+         json[0]["body"]
+            .as_str()
+            .unwrap()
+            .contains("Users locked out")
+      );
+
+      let markdown = render(&issues, ExportFormat::Markdown, issue_ref).unwrap();
+      assert!(markdown.contains("## #7: Fix \"login\", again\n"));
+      assert!(markdown.contains("- **Title [de]:** Login reparieren\n"));
+      assert!(markdown.contains("- **Depends on:** #3\n"));
+      assert!(
+         !markdown
+            .lines()
+            .any(|l| l.starts_with("## ") && !l.starts_with("## #7"))
+      );
+
+      let text = "one\ntwo\nthree\n";
+      assert_eq!(chunk(text, 9, 0), Some(("one\ntwo\n", 2)));
+      assert_eq!(chunk(text, 9, 1), Some(("three\n", 2)));
+      assert_eq!(chunk(text, 9, 2), None);
+      assert_eq!(parse_format("MD").unwrap(), ExportFormat::Markdown);
+      assert_eq!(format_for_path("snapshot.yml"), Some(ExportFormat::Yaml));
+   }
+}
@@ -0,0 +1,143 @@
+//! Glob and regex filters on issue files
+//!
+//! `list --file-glob 'src/tui/**'`, `--file-regex`, and the `file_glob:` and
+//! `file_regex:` query fields keep the issues with a file matching a pattern.
+//! Stored entries are compared the way `for-file` compares them: forward
+//! slashes, no `./`, relative to the repository root and without a `:line`
+//! suffix, so `/repo/src/tui/app.rs:40` matches `src/tui/**`.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use smol_str::SmolStr;
+
+use crate::utils::{normalize_path, strip_line_suffix};
+
+/// A glob or regex over normalized file paths
+#[derive(Debug, Clone)]
+pub struct FileMatcher {
+   regex: Regex,
+   /// Root that absolute stored paths are made relative to
+   root:  Option<PathBuf>,
+}
+
+impl FileMatcher {
+   /// A glob matching whole paths: `*` and `?` stay within a directory, `**`
+   /// crosses directories, `[abc]` and `{a,b}` pick alternatives
+   pub fn glob(pattern: &str, root: Option<&Path>) -> Result<Self> {
+      let pattern = normalize_path(pattern, root);
+      let regex = Regex::new(&glob_to_regex(&pattern)?)
+         .with_context(|| format!("Invalid file glob: {pattern}"))?;
+      Ok(Self { regex, root: root.map(Path::to_path_buf) })
+   }
+
+   /// A regex searched for anywhere in the path; anchor it with `^`/`$`
+   pub fn regex(pattern: &str, root: Option<&Path>) -> Result<Self> {
+      let regex = Regex::new(pattern).with_context(|| format!("Invalid file regex: {pattern}"))?;
+      Ok(Self { regex, root: root.map(Path::to_path_buf) })
+   }
+
+   /// Whether a stored `files` entry matches
+   pub fn matches_path(&self, stored: &str) -> bool {
+      let path = normalize_path(strip_line_suffix(stored), self.root.as_deref());
+      self.regex.is_match(&path)
+   }
+
+   /// Whether any of an issue's files matches
+   pub fn matches(&self, files: &[SmolStr]) -> bool {
+      files.iter().any(|f| self.matches_path(f))
+   }
+}
+
+/// An anchored regex for `glob`
+fn glob_to_regex(glob: &str) -> Result<String> {
+   let mut out = String::from("^");
+   let chars: Vec<char> = glob.chars().collect();
+   let mut in_braces = false;
+   let mut i = 0;
+   while i < chars.len() {
+      match chars[i] {
+         '*' if chars.get(i + 1) == Some(&'*') => {
+            let at_start = i == 0 || chars[i - 1] == '/';
+            match chars.get(i + 2) {
+               // `**/` also matches no directory at all
+               Some('/') if at_start => {
+                  out.push_str("(?:.*/)?");
+                  i += 1;
+               },
+               // A trailing `/**` also matches the directory itself
+               None if i > 0 && chars[i - 1] == '/' => {
+                  out.pop();
+                  out.push_str("(?:/.*)?");
+               },
+               _ => out.push_str(".*"),
+            }
+            i += 1;
+         },
+         '*' => out.push_str("[^/]*"),
+         '?' => out.push_str("[^/]"),
+         '[' => {
+            let end = chars[i..]
+               .iter()
+               .position(|&c| c == ']')
+               .with_context(|| format!("Unclosed '[' in file glob: {glob}"))?;
+            let class: String = chars[i + 1..i + end].iter().collect();
+            let class = class
+               .strip_prefix('!')
+               .map_or(class.clone(), |rest| format!("^{rest}"));
+            out.push_str(&format!("[{}]", class.replace('\\', "\\\\")));
+            i += end;
+         },
+         '{' if !in_braces => {
+            in_braces = true;
+            out.push_str("(?:");
+         },
+         ',' if in_braces => out.push('|'),
+         '}' if in_braces => {
+            in_braces = false;
+            out.push(')');
+         },
+         c => out.push_str(&regex::escape(&c.to_string())),
+      }
+      i += 1;
+   }
+   if in_braces {
+      anyhow::bail!("Unclosed '{{' in file glob: {glob}");
+   }
+   out.push('$');
+   Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn test_globs_and_regexes() {
+      let root = Path::new("/repo");
+      let tui = FileMatcher::glob("src/tui/**", Some(root)).unwrap();
+      assert!(tui.matches_path("src/tui/app.rs"));
+      assert!(tui.matches_path("./src/tui/widgets/kanban.rs:40"));
+      assert!(tui.matches_path("/repo/src/tui/mod.rs"));
+      assert!(tui.matches_path("src/tui"));
+      assert!(!tui.matches_path("src/tuix/app.rs"));
+      assert!(!tui.matches_path("/elsewhere/src/tui/app.rs"));
+
+      let rust = FileMatcher::glob("**/*.{rs,toml}", None).unwrap();
+      assert!(rust.matches_path("Cargo.toml"));
+      assert!(rust.matches_path("src/a/b.rs"));
+      assert!(!rust.matches_path("src/a/b.md"));
+      let one_level = FileMatcher::glob("src/*.rs", None).unwrap();
+      assert!(one_level.matches_path("src/main.rs"));
+      assert!(!one_level.matches_path("src/tui/app.rs"));
+      let class = FileMatcher::glob("docs/[!a]?.md", None).unwrap();
+      assert!(class.matches_path("docs/b1.md") && !class.matches_path("docs/a1.md"));
+      assert!(FileMatcher::glob("src/{a,b", None).is_err());
+
+      let tests = FileMatcher::regex(r"(^|/)tests?/", Some(root)).unwrap();
+      assert!(tests.matches(&["README.md".into(), "/repo/crates/x/tests/it.rs".into()]));
+      assert!(!tests.matches(&["src/contest.rs".into()]));
+      assert!(FileMatcher::regex("(", None).is_err());
+   }
+}
@@ -0,0 +1,95 @@
+//! Issue `files` entries
+//!
+//! `agentx files remap old/ new/` rewrites entries across every issue after a
+//! file or directory is renamed, keeping their `:line` suffixes, and `doctor`
+//! reports the entries of open issues whose path no longer exists.
+//! `checkpoint` and `close` offer the files changed in the worktree for
+//! `files`, so the mapping stays current without bookkeeping.
+
+use std::path::Path;
+
+use smol_str::SmolStr;
+
+use crate::utils::{normalize_path, strip_line_suffix};
+
+/// `entry` after moving `from` to `to`, if it names `from` or a path inside
+/// it; both are normalized paths
+pub fn remap(entry: &str, from: &str, to: &str, root: Option<&Path>) -> Option<String> {
+   let entry = entry.trim();
+   let path = strip_line_suffix(entry);
+   let suffix = &entry[path.len()..];
+   let path = normalize_path(path, root);
+   let rest = path
+      .strip_prefix(from)
+      .filter(|rest| rest.is_empty() || rest.starts_with('/'))?;
+   let remapped = format!("{to}{rest}");
+   Some(format!("{}{suffix}", remapped.trim_start_matches('/')))
+}
+
+/// The paths in `files` that `exists` says are gone, without line suffixes
+pub fn missing(files: &[SmolStr], exists: impl Fn(&str) -> bool) -> Vec<String> {
+   let mut missing: Vec<String> = files
+      .iter()
+      .map(|f| strip_line_suffix(f))
+      .filter(|path| !path.is_empty() && !exists(path))
+      .map(String::from)
+      .collect();
+   missing.dedup();
+   missing
+}
+
+/// Changed paths worth adding to an issue listing `files`: those it doesn't
+/// list yet, outside `skip` (the issue store itself)
+pub fn suggest(changed: &[String], files: &[SmolStr], skip: Option<&str>) -> Vec<String> {
+   changed
+      .iter()
+      .filter(|path| {
+         let inside = |dir: &str| {
+            path.as_str() == dir || path.strip_prefix(dir).is_some_and(|r| r.starts_with('/'))
+         };
+         !skip.is_some_and(|dir| !dir.is_empty() && inside(dir))
+            && !files
+               .iter()
+               .any(|f| inside(&normalize_path(strip_line_suffix(f), None)))
+      })
+      .cloned()
+      .collect()
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn test_remap_and_missing() {
+      let root = Path::new("/repo");
+      assert_eq!(
+         remap("src/ui/app.rs:40", "src/ui", "src/tui", Some(root)).as_deref(),
+         Some("src/tui/app.rs:40")
+      );
+      assert_eq!(
+         remap("/repo/src/ui", "src/ui", "src/tui", Some(root)).as_deref(),
+         Some("src/tui")
+      );
+      assert_eq!(remap("./src/ui/a/b.rs", "src/ui", "app", None).as_deref(), Some("app/a/b.rs"));
+      assert_eq!(
+         remap("src/main.rs:3:7", "src/main.rs", "src/bin/cli.rs", None).as_deref(),
+         Some("src/bin/cli.rs:3:7")
+      );
+      assert_eq!(remap("src/uix/app.rs", "src/ui", "src/tui", None), None);
+      assert_eq!(remap("lib/ui/app.rs", "src/ui", "src/tui", None), None);
+      // Moving a directory to the root
+      assert_eq!(remap("pkg/src/a.rs", "pkg", "", None).as_deref(), Some("src/a.rs"));
+
+      let files: Vec<SmolStr> = vec!["src/a.rs:1".into(), "src/a.rs:9".into(), "src/b.rs".into()];
+      assert_eq!(missing(&files, |p| p == "src/b.rs"), ["src/a.rs"]);
+      assert!(missing(&files, |_| true).is_empty());
+
+      let changed: Vec<String> =
+         ["issues/open/01-x.mdx", "src/a.rs", "src/tui/app.rs", "README.md"]
+            .map(String::from)
+            .into();
+      let listed: Vec<SmolStr> = vec!["./src/a.rs:3".into(), "src/tui".into()];
+      assert_eq!(suggest(&changed, &listed, Some("issues")), ["README.md"]);
+   }
+}
@@ -0,0 +1,469 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::issue::{IssueWithId, Status};
+
+/// Number of longest-blocked issues reported as outliers
+const MAX_OUTLIERS: usize = 5;
+
+/// Distribution of durations, in hours
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageStats {
+   pub count:        usize,
+   pub median_hours: f64,
+   pub p90_hours:    f64,
+   pub max_hours:    f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockedOutlier {
+   pub id:            u32,
+   pub title:         String,
+   pub blocked_hours: f64,
+   pub still_blocked: bool,
+   pub reason:        Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FlowMetrics {
+   /// Created (ready) until first started
+   pub ready_to_active:  Option<StageStats>,
+   /// First started until closed
+   pub active_to_closed: Option<StageStats>,
+   /// Total time each issue spent blocked
+   pub blocked:          Option<StageStats>,
+   /// Per-issue total time in each status
+   pub time_in_status:   BTreeMap<String, StageStats>,
+   pub longest_blocked:  Vec<BlockedOutlier>,
+}
+
+/// Metrics for the issues sharing one tag or assignee
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SliceMetrics {
+   pub name:             String,
+   /// Issues not done or closed
+   pub open:             usize,
+   pub closed_in_period: usize,
+   /// Mean hours from creation to close over those closed in the period
+   pub avg_close_hours:  Option<f64>,
+   /// Hours spent blocked within the period, summed over the issues
+   pub blocked_hours:    f64,
+}
+
+/// Most points a series may have
+pub const MAX_POINTS: usize = 400;
+
+/// Width of each point in a metrics series
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+   #[default]
+   Day,
+   Week,
+}
+
+/// Parse a series granularity
+pub fn parse_granularity(s: &str) -> Result<Granularity> {
+   Ok(match s.to_ascii_lowercase().as_str() {
+      "day" | "daily" => Granularity::Day,
+      "week" | "weekly" => Granularity::Week,
+      _ => anyhow::bail!("Unknown granularity: {s}. Use: day, week"),
+   })
+}
+
+/// One day or week of a metrics series
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeriesPoint {
+   /// First day of the period (weeks start on Monday)
+   pub start:    NaiveDate,
+   /// Issues created within the period
+   pub opened:   usize,
+   /// Issues closed within the period
+   pub closed:   usize,
+   /// Issues open at the end of the period
+   pub net_open: usize,
+   /// Issues blocked at the end of the period
+   pub blocked:  usize,
+}
+
+/// Name of the slice for issues without a tag or assignee
+pub const NO_SLICE: &str = "none";
+
+/// A span of time an issue spent in one status
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatusInterval {
+   pub status: Status,
+   pub start:  DateTime<Utc>,
+   pub end:    DateTime<Utc>,
+}
+
+fn hours(start: DateTime<Utc>, end: DateTime<Utc>) -> f64 {
+   (end - start).num_minutes().max(0) as f64 / 60.0
+}
+
+impl StageStats {
+   fn from_hours(mut samples: Vec<f64>) -> Option<Self> {
+      if samples.is_empty() {
+         return None;
+      }
+      samples.sort_by(f64::total_cmp);
+      let at = |q: f64| samples[((samples.len() - 1) as f64 * q).round() as usize];
+      Some(Self {
+         count:        samples.len(),
+         median_hours: at(0.5),
+         p90_hours:    at(0.9),
+         max_hours:    samples[samples.len() - 1],
+      })
+   }
+}
+
+/// Reconstruct the statuses an issue went through from its transition log
+///
+/// Issues without a log (created before transitions were recorded) fall back
+/// to the `started`/`closed` timestamps. The final interval of a still-open
+/// issue runs until `now`.
+pub fn status_intervals(issue: &IssueWithId, now: DateTime<Utc>) -> Vec<StatusInterval> {
+   let meta = &issue.issue.metadata;
+   let mut intervals = Vec::new();
+
+   if meta.transitions.is_empty() {
+      let mut start = meta.created;
+      if let Some(started) = meta.started {
+         intervals.push(StatusInterval { status: Status::NotStarted, start, end: started });
+         start = started;
+      }
+      let status = if meta.started.is_some() {
+         Status::InProgress
+      } else {
+         Status::NotStarted
+      };
+      match meta.closed {
+         Some(closed) => intervals.push(StatusInterval { status, start, end: closed }),
+         None => intervals.push(StatusInterval { status: meta.status, start, end: now }),
+      }
+      return intervals;
+   }
+
+   let mut status = meta.transitions[0].from;
+   let mut start = meta.created;
+   for transition in &meta.transitions {
+      intervals.push(StatusInterval { status, start, end: transition.at });
+      status = transition.to;
+      start = transition.at;
+   }
+   if !matches!(status, Status::Closed | Status::Done) {
+      intervals.push(StatusInterval { status, start, end: now });
+   }
+   intervals
+}
+
+/// Flow metrics over intervals that ended (or are still running) after `since`
+pub fn flow_metrics(
+   issues: &[IssueWithId],
+   since: DateTime<Utc>,
+   now: DateTime<Utc>,
+) -> FlowMetrics {
+   let mut ready_to_active = Vec::new();
+   let mut active_to_closed = Vec::new();
+   let mut blocked = Vec::new();
+   let mut in_status: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+   let mut outliers = Vec::new();
+
+   for issue in issues {
+      let meta = &issue.issue.metadata;
+      let intervals = status_intervals(issue, now);
+      let recent: Vec<_> = intervals.iter().filter(|i| i.end > since).collect();
+      if recent.is_empty() {
+         continue;
+      }
+
+      let first_active = intervals
+         .iter()
+         .find(|i| i.status == Status::InProgress)
+         .map(|i| i.start);
+      if let Some(active) = first_active.filter(|at| *at > since) {
+         ready_to_active.push(hours(meta.created, active));
+      }
+      if let (Some(active), Some(closed)) = (first_active, meta.closed.filter(|at| *at > since)) {
+         active_to_closed.push(hours(active, closed));
+      }
+
+      let mut per_status: BTreeMap<String, f64> = BTreeMap::new();
+      for interval in &recent {
+         *per_status.entry(interval.status.to_string()).or_default() +=
+            hours(interval.start, interval.end);
+      }
+      for (status, total) in per_status {
+         in_status.entry(status).or_default().push(total);
+      }
+
+      let blocked_hours: f64 = recent
+         .iter()
+         .filter(|i| i.status == Status::Blocked)
+         .map(|i| hours(i.start, i.end))
+         .sum();
+      if blocked_hours > 0.0 {
+         blocked.push(blocked_hours);
+         outliers.push(BlockedOutlier {
+            id: issue.id,
+            title: meta.title.to_string(),
+            blocked_hours,
+            still_blocked: meta.status == Status::Blocked,
+            reason: meta.blocked_reason.as_ref().map(|r| r.to_string()),
+         });
+      }
+   }
+
+   outliers.sort_by(|a, b| {
+      b.blocked_hours
+         .total_cmp(&a.blocked_hours)
+         .then(a.id.cmp(&b.id))
+   });
+   outliers.truncate(MAX_OUTLIERS);
+
+   FlowMetrics {
+      ready_to_active:  StageStats::from_hours(ready_to_active),
+      active_to_closed: StageStats::from_hours(active_to_closed),
+      blocked:          StageStats::from_hours(blocked),
+      time_in_status:   in_status
+         .into_iter()
+         .filter_map(|(status, samples)| StageStats::from_hours(samples).map(|s| (status, s)))
+         .collect(),
+      longest_blocked:  outliers,
+   }
+}
+
+/// Metrics per value `slice` gives each issue, such as its tags; an issue
+/// with several counts toward each, and those with none go under
+/// [`NO_SLICE`]. Slices are alphabetical with `none` last, and those with
+/// nothing to report over the period are left out.
+pub fn slice_metrics(
+   issues: &[IssueWithId],
+   slice: impl Fn(&IssueWithId) -> Vec<String>,
+   since: DateTime<Utc>,
+   now: DateTime<Utc>,
+) -> Vec<SliceMetrics> {
+   #[derive(Default)]
+   struct Totals {
+      open:          usize,
+      close_hours:   Vec<f64>,
+      blocked_hours: f64,
+   }
+
+   let mut totals: BTreeMap<String, Totals> = BTreeMap::new();
+   for issue in issues {
+      let meta = &issue.issue.metadata;
+      let closed = meta.closed.filter(|at| *at > since);
+      let blocked_hours: f64 = status_intervals(issue, now)
+         .iter()
+         .filter(|i| i.status == Status::Blocked && i.end > since)
+         .map(|i| hours(i.start.max(since), i.end))
+         .sum();
+
+      let mut names = slice(issue);
+      if names.is_empty() {
+         names.push(NO_SLICE.to_string());
+      }
+      for name in names {
+         let entry = totals.entry(name).or_default();
+         if !matches!(meta.status, Status::Done | Status::Closed) {
+            entry.open += 1;
+         }
+         if let Some(closed) = closed {
+            entry.close_hours.push(hours(meta.created, closed));
+         }
+         entry.blocked_hours += blocked_hours;
+      }
+   }
+
+   let mut slices: Vec<SliceMetrics> = totals
+      .into_iter()
+      .filter(|(_, t)| t.open > 0 || !t.close_hours.is_empty() || t.blocked_hours > 0.0)
+      .map(|(name, t)| SliceMetrics {
+         name,
+         open: t.open,
+         closed_in_period: t.close_hours.len(),
+         avg_close_hours: (!t.close_hours.is_empty())
+            .then(|| t.close_hours.iter().sum::<f64>() / t.close_hours.len() as f64),
+         blocked_hours: t.blocked_hours,
+      })
+      .collect();
+   slices.sort_by_key(|s| s.name == NO_SLICE);
+   slices
+}
+
+/// Opened, closed, open and blocked counts per day or week from the period
+/// holding `from` through the one holding `to`; the last period's open and
+/// blocked counts are as of `now` if it hasn't ended yet
+pub fn metrics_series(
+   issues: &[IssueWithId],
+   from: DateTime<Utc>,
+   to: DateTime<Utc>,
+   granularity: Granularity,
+   now: DateTime<Utc>,
+) -> Result<Vec<SeriesPoint>> {
+   if from > to {
+      anyhow::bail!("Series starts after it ends ({} > {})", from.date_naive(), to.date_naive());
+   }
+   let (mut start, step) = match granularity {
+      Granularity::Day => (from.date_naive(), Duration::days(1)),
+      Granularity::Week => {
+         let day = from.date_naive();
+         (day - Duration::days(day.weekday().num_days_from_monday().into()), Duration::weeks(1))
+      },
+   };
+   let intervals: Vec<Vec<StatusInterval>> =
+      issues.iter().map(|i| status_intervals(i, now)).collect();
+
+   let mut points = Vec::new();
+   while start <= to.date_naive() {
+      if points.len() == MAX_POINTS {
+         anyhow::bail!(
+            "Series would have more than {MAX_POINTS} points; narrow from/to or use weeks"
+         );
+      }
+      let begin = start.and_time(Default::default()).and_utc();
+      let end = begin + step;
+      let at = end.min(now);
+      let within = |time: DateTime<Utc>| time >= begin && time < end;
+      points.push(SeriesPoint {
+         start,
+         opened: issues
+            .iter()
+            .filter(|i| within(i.issue.metadata.created))
+            .count(),
+         closed: issues
+            .iter()
+            .filter(|i| i.issue.metadata.closed.is_some_and(within))
+            .count(),
+         net_open: issues
+            .iter()
+            .filter(|i| {
+               let meta = &i.issue.metadata;
+               meta.created < at && meta.closed.is_none_or(|closed| closed >= at)
+            })
+            .count(),
+         blocked: intervals
+            .iter()
+            .filter(|spans| {
+               spans.iter().any(|s| {
+                  s.status == Status::Blocked && s.start < at && (s.end > at || s.end == now)
+               })
+            })
+            .count(),
+      });
+      start += step;
+   }
+   Ok(points)
+}
+
+#[cfg(test)]
+mod tests {
+   use chrono::Duration;
+
+   use super::*;
+   use crate::issue::{Priority, test_support::issue};
+
+   #[test]
+   fn test_flow_from_transitions() {
+      let now = Utc::now();
+      let created = now - Duration::hours(100);
+      let after = |hours| created + Duration::hours(hours);
+      let issues = vec![
+         issue(1)
+            .title("Flow")
+            .created(created)
+            .moved(Status::InProgress, after(10))
+            .moved(Status::Blocked, after(20))
+            .moved(Status::InProgress, after(50))
+            .moved(Status::Closed, after(60))
+            .closed(after(60))
+            .build(),
+      ];
+
+      let flow = flow_metrics(&issues, now - Duration::days(30), now);
+      assert_eq!(flow.ready_to_active.unwrap().median_hours, 10.0);
+      assert_eq!(flow.active_to_closed.unwrap().median_hours, 50.0);
+      assert_eq!(flow.blocked.unwrap().max_hours, 30.0);
+      assert_eq!(flow.time_in_status["active"].median_hours, 20.0);
+      assert_eq!(flow.longest_blocked[0].id, 1);
+
+      let mut issues = issues;
+      issues[0].issue.metadata.tags = vec!["ui".into()];
+      issues.push(
+         issue(2)
+            .title("Other")
+            .priority(Priority::Low)
+            .tags(&["api", "ui"])
+            .created(now - Duration::hours(5))
+            .build(),
+      );
+      issues.push(IssueWithId { id: 3, issue: issues[1].issue.clone() });
+      issues[2].issue.metadata.tags.clear();
+
+      // Only the last 10 hours of the blocked stretch fall in the period
+      let since = created + Duration::hours(40);
+      let slices = slice_metrics(
+         &issues,
+         |i| {
+            i.issue
+               .metadata
+               .tags
+               .iter()
+               .map(|t| t.to_string())
+               .collect()
+         },
+         since,
+         now,
+      );
+      let names: Vec<&str> = slices.iter().map(|s| s.name.as_str()).collect();
+      assert_eq!(names, ["api", "ui", NO_SLICE]);
+      let ui = &slices[1];
+      assert_eq!((ui.open, ui.closed_in_period), (1, 1));
+      assert_eq!((ui.avg_close_hours, ui.blocked_hours), (Some(60.0), 10.0));
+      assert_eq!(slices[2].open, 1);
+   }
+
+   #[test]
+   fn test_metrics_series() {
+      let at = |day: u32, hour: u32| {
+         NaiveDate::from_ymd_opt(2026, 1, day)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+            .and_utc()
+      };
+      let issues = [
+         issue(1)
+            .created(at(12, 9))
+            .status(Status::Closed)
+            .closed(at(14, 10))
+            .build(),
+         issue(2)
+            .created(at(13, 8))
+            .moved(Status::Blocked, at(13, 12))
+            .build(),
+      ];
+
+      // Monday the 12th through Thursday the 15th, ending mid-day
+      let now = at(15, 12);
+      let days = metrics_series(&issues, at(12, 0), now, Granularity::Day, now).unwrap();
+      let counts: Vec<(usize, usize, usize, usize)> = days
+         .iter()
+         .map(|p| (p.opened, p.closed, p.net_open, p.blocked))
+         .collect();
+      assert_eq!(counts, [(1, 0, 1, 0), (1, 0, 2, 1), (0, 1, 1, 1), (0, 0, 1, 1)]);
+
+      let weeks = metrics_series(&issues, at(14, 0), now, Granularity::Week, now).unwrap();
+      assert_eq!(weeks.len(), 1);
+      assert_eq!(weeks[0].start, at(12, 0).date_naive());
+      assert_eq!((weeks[0].opened, weeks[0].closed, weeks[0].net_open), (2, 1, 1));
+
+      assert!(metrics_series(&issues, now, at(12, 0), Granularity::Day, now).is_err());
+      assert_eq!(parse_granularity("Weekly").unwrap(), Granularity::Week);
+      assert!(parse_granularity("month").is_err());
+   }
+}
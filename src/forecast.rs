@@ -0,0 +1,202 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{calendar::WorkCalendar, issue::IssueWithId, utils::parse_effort};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForecastMethod {
+   /// Remaining estimated effort divided by effort closed per week
+   Effort,
+   /// Remaining issue count divided by issues closed per week
+   Count,
+}
+
+/// Weekly throughput (issues or effort minutes, depending on the method)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RateRange {
+   pub optimistic:  f64,
+   pub median:      f64,
+   pub pessimistic: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Forecast {
+   pub scope:                    String,
+   pub remaining_issues:         usize,
+   pub remaining_effort_minutes: u32,
+   /// Remaining issues without an effort estimate (filled in with the
+   /// historical average when forecasting by effort)
+   pub unestimated:              usize,
+   pub method:                   ForecastMethod,
+   pub lookback_weeks:           u32,
+   pub weekly_rate:              RateRange,
+   pub optimistic:               Option<DateTime<Utc>>,
+   pub median:                   Option<DateTime<Utc>>,
+   pub pessimistic:              Option<DateTime<Utc>>,
+}
+
+fn effort_minutes(issue: &IssueWithId) -> Option<u32> {
+   issue
+      .issue
+      .metadata
+      .effort
+      .as_deref()
+      .and_then(|e| parse_effort(e).ok())
+}
+
+/// Nearest-rank quantile of an ascending slice
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+   if sorted.is_empty() {
+      return 0.0;
+   }
+   let idx = ((sorted.len() - 1) as f64 * q).round() as usize;
+   sorted[idx]
+}
+
+/// Spread weekly throughput samples into a range
+///
+/// The median is the mean rate over the whole window, which stays stable when
+/// most weeks had no closes. The optimistic and pessimistic bounds are the
+/// 80th and 20th percentile weeks, clamped around the mean; an idle 20th
+/// percentile falls back to half the mean.
+fn rate_range(samples: &[f64]) -> RateRange {
+   let mean = samples.iter().sum::<f64>() / samples.len().max(1) as f64;
+   let mut sorted = samples.to_vec();
+   sorted.sort_by(f64::total_cmp);
+
+   let low = quantile(&sorted, 0.2);
+   RateRange {
+      optimistic:  quantile(&sorted, 0.8).max(mean),
+      median:      mean,
+      pessimistic: if low > 0.0 { low.min(mean) } else { mean / 2.0 },
+   }
+}
+
+/// Forecast when `remaining` will be done from how fast `closed` issues were
+/// closed over the last `lookback_weeks` weeks
+///
+/// Effort is used when the history has estimates and at least half of the
+/// remaining issues are estimated; otherwise issue counts are used. With a
+/// `calendar`, each week of work is a working week from `now`, so weekends
+/// and holidays push the dates out.
+pub fn forecast(
+   scope: String,
+   remaining: &[IssueWithId],
+   closed: &[IssueWithId],
+   now: DateTime<Utc>,
+   lookback_weeks: u32,
+   calendar: Option<&WorkCalendar>,
+) -> Result<Forecast> {
+   let lookback_weeks = lookback_weeks.max(1);
+   let window_start = now - Duration::weeks(lookback_weeks as i64);
+
+   let mut count_samples = vec![0.0; lookback_weeks as usize];
+   let mut effort_samples = vec![0.0; lookback_weeks as usize];
+   let mut historical_efforts = Vec::new();
+   for issue in closed {
+      let Some(closed_at) = issue.issue.metadata.closed else {
+         continue;
+      };
+      if closed_at < window_start || closed_at > now {
+         continue;
+      }
+      let week = ((now - closed_at).num_days() / 7).min(lookback_weeks as i64 - 1) as usize;
+      count_samples[week] += 1.0;
+      if let Some(minutes) = effort_minutes(issue) {
+         effort_samples[week] += minutes as f64;
+         historical_efforts.push(minutes);
+      }
+   }
+
+   let estimates: Vec<u32> = remaining.iter().filter_map(effort_minutes).collect();
+   let unestimated = remaining.len() - estimates.len();
+   let avg_effort = if historical_efforts.is_empty() {
+      0
+   } else {
+      historical_efforts.iter().sum::<u32>() / historical_efforts.len() as u32
+   };
+   let remaining_effort_minutes = estimates.iter().sum::<u32>() + unestimated as u32 * avg_effort;
+
+   let use_effort = !historical_efforts.is_empty() && estimates.len() * 2 >= remaining.len();
+   let (method, weekly_rate, amount) = if use_effort {
+      (ForecastMethod::Effort, rate_range(&effort_samples), remaining_effort_minutes as f64)
+   } else {
+      (ForecastMethod::Count, rate_range(&count_samples), remaining.len() as f64)
+   };
+
+   let week = match calendar {
+      Some(calendar) => calendar.week_length()?,
+      None => Duration::weeks(1),
+   };
+   let finish = |rate: f64| -> Result<Option<DateTime<Utc>>> {
+      if amount == 0.0 {
+         return Ok(Some(now));
+      }
+      if rate <= 0.0 {
+         return Ok(None);
+      }
+      let work = Duration::minutes((amount / rate * week.num_minutes() as f64) as i64);
+      match calendar {
+         Some(calendar) => calendar.after(now, work).map(Some),
+         None => Ok(Some(now + work)),
+      }
+   };
+
+   Ok(Forecast {
+      scope,
+      remaining_issues: remaining.len(),
+      remaining_effort_minutes,
+      unestimated,
+      method,
+      lookback_weeks,
+      optimistic: finish(weekly_rate.optimistic)?,
+      median: finish(weekly_rate.median)?,
+      pessimistic: finish(weekly_rate.pessimistic)?,
+      weekly_rate,
+   })
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::issue::test_support::issue;
+
+   #[test]
+   fn test_forecast_by_count_orders_ranges() {
+      let now = Utc::now();
+      let closed: Vec<_> = (0..8)
+         .map(|i| {
+            issue(i)
+               .closed(now - Duration::days(i as i64 * 3 + 1))
+               .build()
+         })
+         .collect();
+      let remaining: Vec<_> = (100..104).map(|i| issue(i).build()).collect();
+
+      let f = forecast("open".into(), &remaining, &closed, now, 4, None).unwrap();
+      assert_eq!(f.method, ForecastMethod::Count);
+      assert!((f.weekly_rate.median - 2.0).abs() < 1e-9);
+      let (opt, med, pes) = (f.optimistic.unwrap(), f.median.unwrap(), f.pessimistic.unwrap());
+      assert!(opt <= med && med <= pes);
+
+      // Two working weeks, however the weekend falls
+      let calendar = WorkCalendar::default();
+      let f = forecast("open".into(), &remaining, &closed, now, 4, Some(&calendar)).unwrap();
+      let days = (f.median.unwrap() - now).num_days();
+      assert!((11..=15).contains(&days), "{days} days");
+   }
+
+   #[test]
+   fn test_forecast_without_history_has_no_dates() {
+      let now = Utc::now();
+      let remaining = vec![issue(1).effort("2h").build()];
+      let f = forecast("open".into(), &remaining, &[], now, 8, None).unwrap();
+      assert_eq!(f.method, ForecastMethod::Count);
+      assert!(f.median.is_none());
+
+      let f = forecast("empty".into(), &[], &[], now, 8, None).unwrap();
+      assert_eq!(f.median, Some(now));
+   }
+}
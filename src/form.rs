@@ -0,0 +1,212 @@
+//! Forms on stdin
+//!
+//! `--stdin-form json|yaml` is the non-terminal way into the commands that
+//! otherwise open a wizard: `new`, `checkpoint` and `depend` read every
+//! field the wizard would ask for as one document on stdin, so automation
+//! without a TTY (CI containers, scripts) goes through the same entry
+//! points. Unknown fields are errors, as in `import`.
+
+use std::io::Read;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, de::DeserializeOwned};
+
+use crate::{
+   commands::{CheckpointMark, Commands},
+   issue::Priority,
+};
+
+/// How the form on stdin is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormFormat {
+   Json,
+   Yaml,
+}
+
+impl FormFormat {
+   pub fn parse(s: &str) -> Result<Self> {
+      match s {
+         "json" => Ok(Self::Json),
+         "yaml" | "yml" => Ok(Self::Yaml),
+         _ => anyhow::bail!("Unknown --stdin-form: {s}. Use json or yaml"),
+      }
+   }
+
+   /// Parse a form from `source`
+   pub fn parse_form<T: DeserializeOwned>(self, source: &str) -> Result<T> {
+      match self {
+         Self::Json => serde_json::from_str(source).context("Invalid JSON form"),
+         Self::Yaml => serde_yaml::from_str(source).context("Invalid YAML form"),
+      }
+   }
+
+   /// Read a form from stdin
+   pub fn read<T: DeserializeOwned>(self) -> Result<T> {
+      let mut source = String::new();
+      std::io::stdin()
+         .read_to_string(&mut source)
+         .context("Failed to read the form from stdin")?;
+      self.parse_form(&source)
+   }
+}
+
+/// `agentx new`: the new-issue wizard's fields
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NewForm {
+   pub title:      String,
+   #[serde(default)]
+   pub priority:   Option<Priority>,
+   #[serde(default)]
+   pub kind:       Option<String>,
+   #[serde(default)]
+   pub tags:       Vec<String>,
+   #[serde(default)]
+   pub files:      Vec<String>,
+   #[serde(default)]
+   pub issue:      Option<String>,
+   #[serde(default)]
+   pub impact:     Option<String>,
+   #[serde(default)]
+   pub acceptance: Option<String>,
+   #[serde(default)]
+   pub effort:     Option<String>,
+   #[serde(default)]
+   pub context:    Option<String>,
+   #[serde(default)]
+   pub milestone:  Option<String>,
+   /// References of the issues the new one depends on
+   #[serde(default)]
+   pub depends_on: Vec<String>,
+   /// Who the new issue is assigned to
+   #[serde(default)]
+   pub assignee:   Option<String>,
+}
+
+impl NewForm {
+   /// Create the issue, filling sections left out from the kind's template;
+   /// returns its number
+   pub fn apply(self, commands: &Commands, json: bool) -> Result<u32> {
+      let template = self
+         .kind
+         .as_deref()
+         .map(|k| commands.config().kind(k))
+         .transpose()?
+         .map(|k| k.template.clone())
+         .unwrap_or_default();
+      let section = |given: Option<String>, template: Option<String>, field: &str| {
+         given
+            .filter(|s| !s.trim().is_empty())
+            .or(template)
+            .ok_or_else(|| anyhow::anyhow!("The form is missing `{field}`"))
+      };
+      let issue = section(self.issue, template.issue, "issue")?;
+      let impact = section(self.impact, template.impact, "impact")?;
+      let acceptance = section(self.acceptance, template.acceptance, "acceptance")?;
+      if self.title.trim().is_empty() {
+         anyhow::bail!("The form's `title` is empty");
+      }
+
+      let created = commands.create_issue(
+         self.title,
+         &self.priority.unwrap_or(Priority::Medium).to_string(),
+         self.tags,
+         self.files,
+         issue,
+         impact,
+         acceptance,
+         self.effort,
+         self.context,
+         self.milestone,
+         self.kind,
+         json,
+      )?;
+      commands.link_new_issue(created.bug_num, self.depends_on, self.assignee.as_deref())?;
+      Ok(created.bug_num)
+   }
+}
+
+/// `agentx checkpoint`: the checkpoint wizard's fields
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CheckpointForm {
+   /// Issue reference, else the one on the command line
+   #[serde(default)]
+   pub issue:      Option<String>,
+   pub note:       String,
+   /// Block the issue for this reason
+   #[serde(default)]
+   pub blocked:    Option<String>,
+   /// Mark the issue done
+   #[serde(default)]
+   pub done:       bool,
+   #[serde(default)]
+   pub auto_files: bool,
+}
+
+impl CheckpointForm {
+   pub fn apply(self, commands: &Commands, bug_ref: Option<&str>, json: bool) -> Result<()> {
+      let bug_ref = form_ref(self.issue.as_deref(), bug_ref)?;
+      if self.note.trim().is_empty() {
+         anyhow::bail!("The form's `note` is empty");
+      }
+      let mark = match (self.blocked, self.done) {
+         (Some(_), true) => anyhow::bail!("The form can't mark the issue both blocked and done"),
+         (Some(reason), false) => Some(CheckpointMark::Blocked(reason)),
+         (None, true) => Some(CheckpointMark::Done),
+         (None, false) => None,
+      };
+      commands.checkpoint_marked(&bug_ref, self.note, mark, self.auto_files, json)
+   }
+}
+
+/// `agentx depend`: the dependency wizard's fields
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DependForm {
+   /// Issue reference, else the one on the command line
+   #[serde(default)]
+   pub issue:  Option<String>,
+   #[serde(default)]
+   pub on:     Vec<String>,
+   #[serde(default)]
+   pub remove: Vec<String>,
+}
+
+impl DependForm {
+   pub fn apply(self, commands: &Commands, bug_ref: Option<&str>, json: bool) -> Result<()> {
+      let bug_ref = form_ref(self.issue.as_deref(), bug_ref)?;
+      commands.depend(&bug_ref, self.on, self.remove, json)
+   }
+}
+
+/// The form's issue reference, else the command line's
+fn form_ref(form: Option<&str>, given: Option<&str>) -> Result<String> {
+   form
+      .or(given)
+      .filter(|r| !r.is_empty())
+      .map(String::from)
+      .ok_or_else(|| anyhow::anyhow!("The form needs an `issue`, or give it on the command line"))
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn test_forms_parse_strictly_in_both_formats() {
+      let yaml = "title: Fix login\npriority: high\ndepends_on: ['3']\nassignee: ci\n";
+      let form: NewForm = FormFormat::Yaml.parse_form(yaml).unwrap();
+      assert_eq!(form.priority, Some(Priority::High));
+      assert_eq!(form.depends_on, ["3"]);
+
+      let json = r#"{"issue": "12", "note": "Waiting on review", "blocked": "review"}"#;
+      let form: CheckpointForm = FormFormat::Json.parse_form(json).unwrap();
+      assert_eq!(form.blocked.as_deref(), Some("review"));
+      assert!(!form.done);
+
+      let typo = FormFormat::Yaml.parse_form::<DependForm>("issue: '4'\ndepends_on: ['3']\n");
+      assert!(format!("{:#}", typo.unwrap_err()).contains("unknown field `depends_on`"));
+      assert!(FormFormat::parse("toml").is_err());
+   }
+}
@@ -0,0 +1,235 @@
+//! Frontmatter checks
+//!
+//! Issue files are edited by hand and by agents, and serde alone either
+//! rejects a bad value with little to go on or quietly drops a key it
+//! doesn't know. Storage checks the raw frontmatter of every file it reads
+//! and every issue it saves: times must be RFC 3339, `depends_on`, `blocks`
+//! and `references` must hold issue numbers, and an issue can't depend on
+//! itself. Unknown keys are only warned about, since saving drops them.
+
+use std::fmt;
+
+use anyhow::Result;
+use serde_yaml::Value;
+
+/// Keys the frontmatter may hold, as written by [`crate::issue::IssueMetadata`]
+pub const KEYS: &[&str] = &[
+   "title",
+   "title_i18n",
+   "priority",
+   "kind",
+   "status",
+   "created",
+   "last_updated",
+   "tags",
+   "files",
+   "effort",
+   "context",
+   "started",
+   "blocked_reason",
+   "closed",
+   "depends_on",
+   "blocks",
+   "references",
+   "milestone",
+   "target_version",
+   "due",
+   "transitions",
+   "watchers",
+   "assignee",
+   "claim",
+   "source_url",
+   "external_id",
+];
+
+const TIME_KEYS: &[&str] = &["created", "last_updated", "started", "closed", "due"];
+const ID_KEYS: &[&str] = &["depends_on", "blocks", "references"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+   /// Kept from being read or saved
+   Error,
+   /// Read and saved, but lost or ignored
+   Warning,
+}
+
+/// Something wrong with one frontmatter key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+   pub key:      String,
+   pub message:  String,
+   pub severity: Severity,
+}
+
+impl fmt::Display for Problem {
+   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      write!(f, "`{}`: {}", self.key, self.message)
+   }
+}
+
+fn error(key: &str, message: String) -> Problem {
+   Problem { key: key.to_string(), message, severity: Severity::Error }
+}
+
+/// Problems in the frontmatter `yaml` of issue `bug_num`, when known; YAML
+/// that doesn't parse is left for serde to report
+pub fn check(yaml: &str, bug_num: Option<u32>) -> Vec<Problem> {
+   let Ok(Value::Mapping(map)) = serde_yaml::from_str::<Value>(yaml) else {
+      return Vec::new();
+   };
+   let mut problems = Vec::new();
+   for (key, value) in &map {
+      let Some(key) = key.as_str() else {
+         continue;
+      };
+      if !KEYS.contains(&key) {
+         let closest = KEYS
+            .iter()
+            .map(|known| (strsim::jaro_winkler(key, known), known))
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .filter(|(similarity, _)| *similarity >= 0.85);
+         let message = match closest {
+            Some((_, known)) => format!("unknown key, dropped on save (did you mean `{known}`?)"),
+            None => "unknown key, dropped on save".to_string(),
+         };
+         problems.push(Problem { key: key.to_string(), message, severity: Severity::Warning });
+      } else if TIME_KEYS.contains(&key) {
+         problems.extend(check_time(key, value));
+      } else if ID_KEYS.contains(&key) {
+         problems.extend(check_ids(key, value, bug_num));
+      } else if key == "transitions"
+         && let Value::Sequence(transitions) = value
+      {
+         for (i, transition) in transitions.iter().enumerate() {
+            problems.extend(check_time(&format!("transitions[{i}].at"), &transition["at"]));
+         }
+      } else if key == "claim" {
+         for field in ["since", "until"] {
+            problems.extend(check_time(&format!("claim.{field}"), &value[field]));
+         }
+      }
+   }
+   problems
+}
+
+fn check_time(key: &str, value: &Value) -> Option<Problem> {
+   let message = match value {
+      Value::Null => return None,
+      Value::String(s) if chrono::DateTime::parse_from_rfc3339(s).is_ok() => return None,
+      Value::String(s) => format!("{s:?} is not an RFC 3339 time, e.g. 2026-11-01T09:00:00Z"),
+      _ => "should be an RFC 3339 time, e.g. 2026-11-01T09:00:00Z".to_string(),
+   };
+   Some(error(key, message))
+}
+
+fn check_ids(key: &str, value: &Value, bug_num: Option<u32>) -> Vec<Problem> {
+   let Value::Sequence(ids) = value else {
+      return vec![error(key, "should be a list of issue numbers".to_string())];
+   };
+   let mut problems = Vec::new();
+   for id in ids {
+      match id.as_u64().map(u32::try_from) {
+         Some(Ok(id)) if key == "depends_on" && Some(id) == bug_num => {
+            problems.push(error(key, format!("#{id} can't depend on itself")));
+         },
+         Some(Ok(_)) => {},
+         _ => {
+            let shown = serde_yaml::to_string(id).unwrap_or_default();
+            let shown = shown.trim();
+            // A ref like `ISSUE-4` was probably meant as its number
+            let hint = id
+               .as_str()
+               .and_then(|s| s.rsplit(|c: char| !c.is_ascii_digit()).next())
+               .filter(|digits| !digits.is_empty())
+               .map(|digits| format!("; use {digits}"))
+               .unwrap_or_default();
+            problems.push(error(key, format!("{shown} is not an issue number{hint}")));
+         },
+      }
+   }
+   problems
+}
+
+/// Fail with every error in `problems`, one per line
+pub fn refuse_errors(problems: &[Problem]) -> Result<()> {
+   let errors: Vec<String> = problems
+      .iter()
+      .filter(|p| p.severity == Severity::Error)
+      .map(|p| format!("  {p}"))
+      .collect();
+   if !errors.is_empty() {
+      anyhow::bail!("Invalid frontmatter:\n{}", errors.join("\n"));
+   }
+   Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::issue::{Claim, Priority, Status, Transition, test_support::issue};
+
+   #[test]
+   fn test_frontmatter_checks() {
+      let yaml = "title: Fix it\nstatus: open\nmilestne: v1\nowner: bob\ndue: next week\ncreated: \
+                  2026-10-01T09:00:00Z\ndepends_on: [3, -2, ISSUE-4, 7]\nblocks: 5\n";
+      let problems: Vec<String> = check(yaml, Some(7)).iter().map(|p| p.to_string()).collect();
+      assert_eq!(problems, [
+         "`milestne`: unknown key, dropped on save (did you mean `milestone`?)",
+         "`owner`: unknown key, dropped on save",
+         "`due`: \"next week\" is not an RFC 3339 time, e.g. 2026-11-01T09:00:00Z",
+         "`depends_on`: -2 is not an issue number",
+         "`depends_on`: ISSUE-4 is not an issue number; use 4",
+         "`depends_on`: #7 can't depend on itself",
+         "`blocks`: should be a list of issue numbers",
+      ]);
+      let err = refuse_errors(&check(yaml, None)).unwrap_err().to_string();
+      assert!(err.starts_with("Invalid frontmatter:\n  `due`"));
+      assert!(!err.contains("milestne"));
+
+      assert!(check("title: [unclosed", None).is_empty());
+   }
+
+   #[test]
+   fn test_keys_cover_metadata() {
+      let mut issue = issue(9)
+         .title("Every field")
+         .priority(Priority::High)
+         .tags(&["api"])
+         .files(&["src/lib.rs"])
+         .body("issue")
+         .impact("impact")
+         .acceptance("acceptance")
+         .effort("2h")
+         .build()
+         .issue;
+      let meta = &mut issue.metadata;
+      meta.context = Some("context".into());
+      let now = chrono::Utc::now();
+      meta.title_i18n.insert("de".into(), "Jedes Feld".into());
+      meta.kind = Some("bug".into());
+      meta.last_updated = Some(now);
+      meta.started = Some(now);
+      meta.blocked_reason = Some("waiting".into());
+      meta.closed = Some(now);
+      meta.depends_on = vec![1];
+      meta.blocks = vec![2];
+      meta.references = vec![3];
+      meta.milestone = Some("v1".into());
+      meta.target_version = Some("1.0".into());
+      meta.due = Some(now);
+      meta.transitions =
+         vec![Transition { from: Status::NotStarted, to: Status::InProgress, at: now }];
+      meta.watchers = vec!["bob".into()];
+      meta.assignee = Some("carol".into());
+      meta.claim = Some(Claim { agent: "a".into(), since: now, until: now });
+      meta.source_url = Some("https://example.com".into());
+      meta.external_id = Some("x".into());
+
+      let yaml = serde_yaml::to_string(meta).unwrap();
+      assert_eq!(check(&yaml, Some(9)), []);
+      let Value::Mapping(map) = serde_yaml::from_str::<Value>(&yaml).unwrap() else {
+         panic!("frontmatter is not a mapping");
+      };
+      assert_eq!(map.len(), KEYS.len());
+   }
+}
@@ -55,9 +55,66 @@ pub fn filter_by_tags_exact(issues: Vec<IssueWithId>, tags: &[String]) -> Vec<Is
       .collect()
 }
 
+/// Lowercase `text` and split it into words at every run of
+/// non-alphanumerics, so "Auth-token refresh" and "auth token" compare equal
+/// word-wise
+fn normalize_words(text: &str) -> Vec<String> {
+   text
+      .split(|c: char| !c.is_alphanumeric())
+      .filter(|w| !w.is_empty())
+      .map(str::to_lowercase)
+      .collect()
+}
+
+/// Score how well a free-text reference matches an issue title
+///
+/// Each query word must match a whole title word or the start of one, so
+/// "tok" matches "token" but "in" doesn't match "login". Returns `None`
+/// unless every query word matches. Higher is better:
+/// - 3: the title equals the query
+/// - 2: the query words match consecutive title words, in order
+/// - 1: all query words match, in any order
+pub fn title_match_score(query: &str, title: &str) -> Option<u8> {
+   let query = normalize_words(query);
+   if query.is_empty() {
+      return None;
+   }
+   let title = normalize_words(title);
+   let matches = |q: &String, t: &String| t.starts_with(q.as_str());
+
+   if title == query {
+      Some(3)
+   } else if title
+      .windows(query.len())
+      .any(|run| query.iter().zip(run).all(|(q, t)| matches(q, t)))
+   {
+      Some(2)
+   } else if query.iter().all(|q| title.iter().any(|t| matches(q, t))) {
+      Some(1)
+   } else {
+      None
+   }
+}
+
+/// Rank issues by how well their titles match `query`, best first
+///
+/// Issues that don't match at all are dropped; ties follow
+/// [`IssueWithId::stable_key`].
+pub fn rank_by_title(query: &str, issues: Vec<IssueWithId>) -> Vec<(u8, IssueWithId)> {
+   let mut ranked: Vec<_> = issues
+      .into_iter()
+      .filter_map(|issue| {
+         title_match_score(query, &issue.issue.metadata.title).map(|score| (score, issue))
+      })
+      .collect();
+   ranked.sort_by_cached_key(|(score, issue)| (std::cmp::Reverse(*score), issue.stable_key()));
+   ranked
+}
+
 #[cfg(test)]
 mod tests {
    use super::*;
+   use crate::issue::{Priority, test_support::issue};
 
    #[test]
    fn test_fuzzy_match_tag() {
@@ -67,4 +124,46 @@ mod tests {
       assert!(fuzzy_match_tag("SEC", "security"));
       assert!(!fuzzy_match_tag("xyz", "security"));
    }
+
+   #[test]
+   fn test_title_match_score() {
+      assert_eq!(title_match_score("auth token", "Auth token"), Some(3));
+      assert_eq!(title_match_score("auth token", "Auth-token refresh fails"), Some(2));
+      assert_eq!(title_match_score("token auth", "Auth-token refresh fails"), Some(1));
+      assert_eq!(title_match_score("session", "Auth-token refresh fails"), None);
+      assert_eq!(title_match_score("  ", "Anything"), None);
+   }
+
+   #[test]
+   fn test_title_match_needs_word_prefixes() {
+      assert_eq!(title_match_score("in", "Fix login redirect"), None);
+      assert_eq!(title_match_score("ken", "Auth token"), None);
+      assert_eq!(title_match_score("tok", "Auth token"), Some(2));
+      assert_eq!(title_match_score("auth tok", "Auth token refresh"), Some(2));
+      assert_eq!(title_match_score("redirect fix", "Fix login redirect"), Some(1));
+   }
+
+   #[test]
+   fn test_rank_by_title_breaks_ties_by_stable_key() {
+      let issues = vec![
+         issue(1)
+            .title("Cache misses")
+            .priority(Priority::Low)
+            .build(),
+         issue(2)
+            .title("Cache eviction")
+            .priority(Priority::Critical)
+            .build(),
+         issue(3).title("Cache").build(),
+         issue(4)
+            .title("Login cache")
+            .priority(Priority::Critical)
+            .build(),
+      ];
+      let order: Vec<(u8, u32)> = rank_by_title("cache", issues)
+         .into_iter()
+         .map(|(score, i)| (score, i.id))
+         .collect();
+      assert_eq!(order, vec![(3, 3), (2, 2), (2, 4), (2, 1)]);
+   }
 }
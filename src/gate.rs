@@ -0,0 +1,145 @@
+//! CI gates
+//!
+//! `agentx gate --fail-on <query> [--max N]` fails a pipeline when more than
+//! `N` issues (0 by default) match a query expression, such as critical
+//! issues still open before a release. The matching issues are listed so
+//! the log says why; under GitHub Actions each one also becomes an error
+//! annotation on the run, pinned to the issue's first file when it has one.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{issue::IssueWithId, query::Query};
+
+/// Outcome of a gate: the issues matching the query, and whether there were
+/// few enough of them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GateResult {
+   pub query:  String,
+   pub max:    usize,
+   pub count:  usize,
+   pub passed: bool,
+   pub issues: Vec<GateIssue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GateIssue {
+   pub bug_num:  u32,
+   pub title:    String,
+   pub priority: String,
+   pub status:   String,
+   #[serde(skip_serializing_if = "Vec::is_empty", default)]
+   pub tags:     Vec<String>,
+   #[serde(skip_serializing_if = "Vec::is_empty", default)]
+   pub files:    Vec<String>,
+   pub created:  DateTime<Utc>,
+}
+
+/// Check `issues` against `query`, allowing at most `max` matches
+pub fn evaluate(query: &Query, issues: Vec<IssueWithId>, max: usize) -> GateResult {
+   let mut matching = query.filter(issues);
+   matching.sort_by_key(|i| (i.issue.metadata.priority, i.id));
+   let issues: Vec<GateIssue> = matching
+      .iter()
+      .map(|i| {
+         let meta = &i.issue.metadata;
+         GateIssue {
+            bug_num:  i.id,
+            title:    meta.title.to_string(),
+            priority: meta.priority.to_string(),
+            status:   meta.status.to_string(),
+            tags:     meta.tags.iter().map(|t| t.to_string()).collect(),
+            files:    meta.files.iter().map(|f| f.to_string()).collect(),
+            created:  meta.created,
+         }
+      })
+      .collect();
+   GateResult {
+      query: query.source().to_string(),
+      max,
+      count: issues.len(),
+      passed: issues.len() <= max,
+      issues,
+   }
+}
+
+/// Whether this is a GitHub Actions run, where workflow commands turn into
+/// annotations
+pub fn in_github_actions() -> bool {
+   std::env::var("GITHUB_ACTIONS").is_ok_and(|v| v == "true")
+}
+
+/// GitHub Actions `::error` workflow command for an issue that failed the
+/// gate
+pub fn github_annotation(issue: &GateIssue, issue_ref: &str) -> String {
+   let mut properties =
+      vec![format!("title={}", escape_property(&format!("agentx gate: {issue_ref}")))];
+   // Pin the annotation to the first file, at its `:line` if it has one
+   if let Some(file) = issue.files.first() {
+      let (path, line) = match file.split_once(':') {
+         Some((path, line)) if line.bytes().all(|b| b.is_ascii_digit()) && !line.is_empty() => {
+            (path, Some(line))
+         },
+         _ => (file.as_str(), None),
+      };
+      properties.insert(0, format!("file={}", escape_property(path)));
+      if let Some(line) = line {
+         properties.insert(1, format!("line={line}"));
+      }
+   }
+   let message = format!("{issue_ref} ({}, {}): {}", issue.priority, issue.status, issue.title);
+   format!("::error {}::{}", properties.join(","), escape_data(&message))
+}
+
+fn escape_data(text: &str) -> String {
+   text
+      .replace('%', "%25")
+      .replace('\r', "%0D")
+      .replace('\n', "%0A")
+}
+
+fn escape_property(text: &str) -> String {
+   escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::issue::{Priority, test_support::issue};
+
+   #[test]
+   fn test_evaluate_and_annotate() {
+      let issues = vec![
+         issue(1)
+            .title("Data loss on save")
+            .priority(Priority::Critical)
+            .files(&["src/save.rs:42"])
+            .build(),
+         issue(2)
+            .title("Typo in footer")
+            .priority(Priority::Low)
+            .build(),
+         issue(3)
+            .title("Crash, 100% CPU")
+            .priority(Priority::Critical)
+            .build(),
+      ];
+      let query = Query::parse("priority:critical status:open").unwrap();
+
+      let result = evaluate(&query, issues.clone(), 0);
+      assert!(!result.passed);
+      let nums: Vec<u32> = result.issues.iter().map(|i| i.bug_num).collect();
+      assert_eq!(nums, [1, 3]);
+      assert!(evaluate(&query, issues, 2).passed);
+
+      assert_eq!(
+         github_annotation(&result.issues[0], "ISSUE-1"),
+         "::error file=src/save.rs,line=42,title=agentx gate%3A ISSUE-1::ISSUE-1 (critical, \
+          open): Data loss on save"
+      );
+      assert_eq!(
+         github_annotation(&result.issues[1], "ISSUE-3"),
+         "::error title=agentx gate%3A ISSUE-3::ISSUE-3 (critical, open): Crash, 100%25 CPU"
+      );
+   }
+}
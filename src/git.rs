@@ -8,11 +8,13 @@ pub struct GitOps {
 }
 
 impl GitOps {
+   #[tracing::instrument(level = "debug", skip_all)]
    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
       let repo = Repository::discover(path).context("Not a git repository")?;
       Ok(Self { repo })
    }
 
+   #[tracing::instrument(level = "debug", skip(self))]
    pub fn create_branch(&self, branch_name: &str) -> Result<String> {
       // Get current HEAD commit
       let head = self.repo.head().context("Failed to get HEAD")?;
@@ -50,6 +52,7 @@ impl GitOps {
       Ok(branch_name.to_string())
    }
 
+   #[tracing::instrument(level = "debug", skip_all)]
    pub fn current_branch(&self) -> Result<String> {
       let head = self.repo.head().context("Failed to get HEAD")?;
       let branch_name = head
@@ -58,6 +61,7 @@ impl GitOps {
       Ok(branch_name.to_string())
    }
 
+   #[tracing::instrument(level = "debug", skip_all)]
    pub fn create_commit(&self, message: &str) -> Result<String> {
       let mut index = self.repo.index().context("Failed to get index")?;
 
@@ -91,6 +95,7 @@ impl GitOps {
       Ok(commit_id.to_string())
    }
 
+   #[tracing::instrument(level = "debug", skip_all)]
    pub fn has_staged_changes(&self) -> Result<bool> {
       let statuses = self.repo.statuses(None)?;
 
@@ -108,4 +113,71 @@ impl GitOps {
 
       Ok(false)
    }
+
+   /// Paths with staged or unstaged changes, new files included, relative to
+   /// the repository root; deleted files are left out
+   #[tracing::instrument(level = "debug", skip_all)]
+   pub fn changed_files(&self) -> Result<Vec<String>> {
+      let mut options = git2::StatusOptions::new();
+      options
+         .include_untracked(true)
+         .recurse_untracked_dirs(true)
+         .renames_head_to_index(true);
+      let statuses = self.repo.statuses(Some(&mut options))?;
+
+      let mut files: Vec<String> = statuses
+         .iter()
+         .filter(|entry| {
+            let status = entry.status();
+            !status.is_ignored()
+               && !status.contains(git2::Status::INDEX_DELETED)
+               && !status.contains(git2::Status::WT_DELETED)
+         })
+         .filter_map(|entry| {
+            let renamed = entry
+               .head_to_index()
+               .and_then(|d| d.new_file().path().map(Path::to_path_buf));
+            renamed.or_else(|| entry.path().map(Into::into))
+         })
+         .map(|path| path.to_string_lossy().replace('\\', "/"))
+         .collect();
+      files.sort();
+      files.dedup();
+      Ok(files)
+   }
+
+   /// Root of the working tree
+   pub fn workdir(&self) -> Option<&Path> {
+      self.repo.workdir()
+   }
+}
+
+/// Branch checked out in the repository containing `dir`, read straight from
+/// `.git/HEAD`
+///
+/// Much cheaper than opening the repository through libgit2, for callers like
+/// the shell prompt that run on every redraw. `None` when outside a
+/// repository or on a detached HEAD.
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn head_branch(dir: &Path) -> Option<String> {
+   let mut dir = dir.canonicalize().ok()?;
+   let git_dir = loop {
+      let dot_git = dir.join(".git");
+      if dot_git.is_dir() {
+         break dot_git;
+      }
+      // Worktrees and submodules point at their git dir from a `.git` file
+      if let Ok(content) = std::fs::read_to_string(&dot_git) {
+         let target = content.strip_prefix("gitdir:")?.trim();
+         break dir.join(target);
+      }
+      if !dir.pop() {
+         return None;
+      }
+   };
+   let head = std::fs::read_to_string(git_dir.join("HEAD")).ok()?;
+   head
+      .trim()
+      .strip_prefix("ref: refs/heads/")
+      .map(str::to_string)
 }
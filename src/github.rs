@@ -0,0 +1,251 @@
+//! Importing GitHub issues
+//!
+//! `agentx import url https://github.com/owner/repo/issues/123` fetches the
+//! issue through the REST API and stores it as a local issue: the title and
+//! body as they are, labels as tags (with `critical`, `p1`-style labels
+//! setting the priority), and a closed issue closed locally too. The URL is
+//! kept in `source_url`, so the same issue isn't imported twice. Public
+//! repositories need no token; `GITHUB_TOKEN` or `GH_TOKEN` is sent when
+//! set, for private ones and higher rate limits.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::issue::{Issue, Priority, Status};
+
+/// A GitHub issue, located by its web URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssueUrl {
+   /// `https://api.github.com`, or `https://<host>/api/v3` for GitHub
+   /// Enterprise
+   pub api_base: String,
+   pub owner:    String,
+   pub repo:     String,
+   pub number:   u64,
+}
+
+impl IssueUrl {
+   /// Parse `https://<host>/<owner>/<repo>/issues/<number>` (pull request
+   /// URLs work too); query strings and fragments are ignored
+   pub fn parse(url: &str) -> Result<Self> {
+      let invalid = || {
+         anyhow::anyhow!(
+            "Not a GitHub issue URL: {url}. Expected https://github.com/<owner>/<repo>/issues/<number>"
+         )
+      };
+      let rest = url
+         .trim()
+         .strip_prefix("https://")
+         .or_else(|| url.trim().strip_prefix("http://"))
+         .ok_or_else(invalid)?;
+      let rest = rest.split(['?', '#']).next().unwrap_or(rest);
+      let parts: Vec<&str> = rest.trim_end_matches('/').split('/').collect();
+      let [host, owner, repo, kind, number] = parts[..] else {
+         return Err(invalid());
+      };
+      if !matches!(kind, "issues" | "pull") || owner.is_empty() || repo.is_empty() {
+         return Err(invalid());
+      }
+      let number = number.parse().map_err(|_| invalid())?;
+      let api_base = if host.eq_ignore_ascii_case("github.com")
+         || host.eq_ignore_ascii_case("www.github.com")
+      {
+         "https://api.github.com".to_string()
+      } else {
+         format!("https://{host}/api/v3")
+      };
+      Ok(Self { api_base, owner: owner.to_string(), repo: repo.to_string(), number })
+   }
+
+   /// The issue's web URL, as stored in `source_url`
+   pub fn web_url(&self) -> String {
+      let host = match self.api_base.strip_suffix("/api/v3") {
+         Some(base) => base.to_string(),
+         None => "https://github.com".to_string(),
+      };
+      format!("{host}/{}/{}/issues/{}", self.owner, self.repo, self.number)
+   }
+
+   /// `owner/repo#123`
+   pub fn short(&self) -> String {
+      format!("{}/{}#{}", self.owner, self.repo, self.number)
+   }
+}
+
+/// The fields of the API's issue object that are imported
+#[derive(Debug, Clone, Deserialize)]
+pub struct RemoteIssue {
+   pub title:      String,
+   #[serde(default)]
+   pub body:       Option<String>,
+   #[serde(default)]
+   pub labels:     Vec<Label>,
+   pub state:      String,
+   pub created_at: DateTime<Utc>,
+   #[serde(default)]
+   pub closed_at:  Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Label {
+   pub name: String,
+}
+
+/// Fetch an issue, authenticating with `token` when given
+pub fn fetch(url: &IssueUrl, token: Option<&str>) -> Result<RemoteIssue> {
+   let endpoint =
+      format!("{}/repos/{}/{}/issues/{}", url.api_base, url.owner, url.repo, url.number);
+   let mut request = ureq::get(&endpoint)
+      .timeout(Duration::from_secs(15))
+      .set("Accept", "application/vnd.github+json")
+      .set("User-Agent", "agentx");
+   if let Some(token) = token {
+      request = request.set("Authorization", &format!("Bearer {token}"));
+   }
+   match request.call() {
+      Ok(response) => response
+         .into_json()
+         .with_context(|| format!("Unexpected response for {}", url.short())),
+      Err(ureq::Error::Status(404, _)) => anyhow::bail!(
+         "{} not found{}",
+         url.short(),
+         if token.is_some() {
+            ""
+         } else {
+            " (private repository? set GITHUB_TOKEN)"
+         }
+      ),
+      Err(ureq::Error::Status(code @ (401 | 403), _)) => anyhow::bail!(
+         "GitHub refused the request ({code}): check GITHUB_TOKEN, or wait out the rate limit"
+      ),
+      Err(e) => Err(e).with_context(|| format!("Failed to fetch {}", url.short())),
+   }
+}
+
+/// The token from `GITHUB_TOKEN` or `GH_TOKEN`, if either is set
+pub fn token_from_env() -> Option<String> {
+   ["GITHUB_TOKEN", "GH_TOKEN"]
+      .iter()
+      .filter_map(|var| std::env::var(var).ok())
+      .find(|token| !token.trim().is_empty())
+}
+
+/// The local issue for `remote`, found at `url`; labels that don't name a
+/// priority leave it at `default_priority`
+pub fn to_issue(remote: &RemoteIssue, url: &IssueUrl, default_priority: Priority) -> Issue {
+   let priority = remote
+      .labels
+      .iter()
+      .find_map(|l| label_priority(&l.name))
+      .unwrap_or(default_priority);
+   let tags: Vec<String> = remote
+      .labels
+      .iter()
+      .filter(|l| label_priority(&l.name).is_none())
+      .map(|l| tag_of(&l.name))
+      .filter(|t| !t.is_empty())
+      .collect();
+   let body = remote
+      .body
+      .as_deref()
+      .map(str::trim)
+      .filter(|b| !b.is_empty())
+      .unwrap_or("(no description)");
+
+   let mut issue = Issue::new(
+      remote.title.trim().to_string(),
+      priority,
+      tags,
+      vec![],
+      body.to_string(),
+      String::new(),
+      String::new(),
+      None,
+      None,
+   );
+   issue.metadata.created = remote.created_at;
+   issue.metadata.source_url = Some(url.web_url().into());
+   if remote.state == "closed" {
+      issue.metadata.set_status(Status::Closed);
+      issue.metadata.closed = Some(remote.closed_at.unwrap_or_else(Utc::now));
+   }
+   issue
+}
+
+/// The priority a label like `critical`, `priority: high` or `P2` names
+fn label_priority(label: &str) -> Option<Priority> {
+   let name = label.to_lowercase();
+   let name = name
+      .trim_start_matches("priority")
+      .trim_start_matches([':', '/', '-', ' '])
+      .trim();
+   Some(match name {
+      "critical" | "p0" | "urgent" | "blocker" => Priority::Critical,
+      "high" | "p1" => Priority::High,
+      "medium" | "p2" => Priority::Medium,
+      "low" | "p3" | "p4" => Priority::Low,
+      _ => return None,
+   })
+}
+
+/// A label as a tag: lowercase, spaces and colons as dashes
+fn tag_of(label: &str) -> String {
+   let tag: String = label
+      .trim()
+      .to_lowercase()
+      .chars()
+      .map(|c| {
+         if c.is_whitespace() || c == ':' {
+            '-'
+         } else {
+            c
+         }
+      })
+      .collect();
+   let mut out = String::with_capacity(tag.len());
+   for c in tag.chars() {
+      if c == '-' && out.ends_with('-') {
+         continue;
+      }
+      out.push(c);
+   }
+   out.trim_matches('-').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn test_parse_url_and_map_issue() {
+      let url = IssueUrl::parse("https://github.com/rust-lang/rust/issues/123?x=1#issuecomment-9")
+         .unwrap();
+      assert_eq!(url.api_base, "https://api.github.com");
+      assert_eq!((url.owner.as_str(), url.repo.as_str(), url.number), ("rust-lang", "rust", 123));
+      assert_eq!(url.web_url(), "https://github.com/rust-lang/rust/issues/123");
+      let enterprise = IssueUrl::parse("https://git.corp.example/team/app/pull/7/").unwrap();
+      assert_eq!(enterprise.api_base, "https://git.corp.example/api/v3");
+      assert_eq!(enterprise.web_url(), "https://git.corp.example/team/app/issues/7");
+      assert!(IssueUrl::parse("https://github.com/rust-lang/rust").is_err());
+      assert!(IssueUrl::parse("https://github.com/o/r/issues/abc").is_err());
+
+      let remote: RemoteIssue = serde_json::from_str(
+         r#"{"title": " Crash on start ", "body": "Steps:\n1. run", "state": "closed",
+             "labels": [{"name": "Priority: High"}, {"name": "good first issue"}, {"name": "area:ui"}],
+             "created_at": "2026-01-02T03:04:05Z", "closed_at": "2026-02-01T00:00:00Z"}"#,
+      )
+      .unwrap();
+      let issue = to_issue(&remote, &url, Priority::Medium);
+      let meta = &issue.metadata;
+      assert_eq!(meta.title, "Crash on start");
+      assert_eq!(meta.priority, Priority::High);
+      assert_eq!(meta.tags, ["good-first-issue", "area-ui"]);
+      assert_eq!(meta.status, Status::Closed);
+      assert_eq!(meta.closed.unwrap().to_rfc3339(), "2026-02-01T00:00:00+00:00");
+      assert_eq!(meta.source_url.as_deref(), Some("https://github.com/rust-lang/rust/issues/123"));
+      assert_eq!(issue.body.issue(), Some("Steps:\n1. run"));
+   }
+}
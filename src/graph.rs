@@ -0,0 +1,925 @@
+//! Dependency graph
+//!
+//! [`IssueGraph`] holds the `depends_on` edges between a set of issues and
+//! answers the questions asked of them: cycles, whether one issue waits on
+//! another, the issues connected to one, layers and the heaviest chain. The
+//! CLI, MCP server, library API and TUI build one per invocation rather
+//! than walking `depends_on` themselves.
+//!
+//! `agentx deps-graph --format mermaid|dot|svg` writes the graph in a form
+//! that can go into PR descriptions and docs; the ASCII view stays the
+//! default, with every edge routed in box-drawing lines, the critical path
+//! drawn heavy and cycles dashed. Edges run from a dependency to the issue
+//! waiting on it, so work flows top to bottom. SVG comes from Graphviz's `dot`
+//! when it is installed, and otherwise from a simple layered layout drawn here.
+//! `--cluster tag|milestone` groups big graphs into labeled clusters, and
+//! `--depth N` keeps a focused graph to the issues near the focus.
+
+use std::{
+   collections::{BTreeMap, HashMap, HashSet},
+   io::{ErrorKind, Write},
+   process::{Command, Stdio},
+};
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+
+use crate::{
+   api::GraphNode,
+   issue::{IssueWithId, Priority, Status},
+};
+
+/// The dependency edges of a set of issues
+#[derive(Debug, Clone, Default)]
+pub struct IssueGraph {
+   /// Each issue's dependencies, which may lie outside the graph
+   depends_on: BTreeMap<u32, Vec<u32>>,
+   /// The issues in the graph that depend on each issue, by number
+   dependents: HashMap<u32, Vec<u32>>,
+}
+
+impl IssueGraph {
+   /// A graph of issues and their dependencies
+   pub fn new<'a>(edges: impl IntoIterator<Item = (u32, &'a [u32])>) -> Self {
+      let depends_on: BTreeMap<u32, Vec<u32>> = edges
+         .into_iter()
+         .map(|(id, deps)| (id, deps.to_vec()))
+         .collect();
+      let mut dependents: HashMap<u32, Vec<u32>> = HashMap::new();
+      for (&id, deps) in &depends_on {
+         for &dep in deps {
+            dependents.entry(dep).or_default().push(id);
+         }
+      }
+      Self { depends_on, dependents }
+   }
+
+   pub fn from_issues(issues: &[IssueWithId]) -> Self {
+      Self::new(
+         issues
+            .iter()
+            .map(|i| (i.id, i.issue.metadata.depends_on.as_slice())),
+      )
+   }
+
+   pub fn from_nodes(nodes: &[GraphNode]) -> Self {
+      Self::new(nodes.iter().map(|n| (n.id, n.depends_on.as_slice())))
+   }
+
+   pub fn contains(&self, id: u32) -> bool {
+      self.depends_on.contains_key(&id)
+   }
+
+   /// Issues in the graph, by number
+   pub fn ids(&self) -> impl Iterator<Item = u32> + '_ {
+      self.depends_on.keys().copied()
+   }
+
+   /// What `id` depends on directly
+   pub fn depends_on(&self, id: u32) -> &[u32] {
+      self.depends_on.get(&id).map_or(&[], Vec::as_slice)
+   }
+
+   /// Issues in the graph depending on `id` directly, by number
+   pub fn dependents(&self, id: u32) -> &[u32] {
+      self.dependents.get(&id).map_or(&[], Vec::as_slice)
+   }
+
+   /// Whether `from` is `to` or waits on it through a chain of
+   /// dependencies; adding `to -> from` would then close a cycle
+   pub fn reaches(&self, from: u32, to: u32) -> bool {
+      let mut seen = HashSet::new();
+      let mut stack = vec![from];
+      while let Some(id) = stack.pop() {
+         if id == to {
+            return true;
+         }
+         if seen.insert(id) {
+            stack.extend(self.depends_on(id));
+         }
+      }
+      false
+   }
+
+   /// Issues depending on `id` directly or through a chain, without `id`
+   pub fn transitive_dependents(&self, id: u32) -> HashSet<u32> {
+      let mut seen = HashSet::new();
+      let mut stack = self.dependents(id).to_vec();
+      while let Some(next) = stack.pop() {
+         if next != id && seen.insert(next) {
+            stack.extend(self.dependents(next));
+         }
+      }
+      seen
+   }
+
+   /// `root` and every issue connected to it through dependencies in either
+   /// direction, at most `depth` steps away when given, sorted
+   pub fn closure(&self, root: u32, depth: Option<usize>) -> Vec<u32> {
+      let mut seen = HashSet::from([root]);
+      let mut frontier = vec![root];
+      let mut steps = 0;
+      while !frontier.is_empty() && depth.is_none_or(|depth| steps < depth) {
+         let mut next = Vec::new();
+         for id in frontier {
+            for &near in self.depends_on(id).iter().chain(self.dependents(id)) {
+               if seen.insert(near) {
+                  next.push(near);
+               }
+            }
+         }
+         frontier = next;
+         steps += 1;
+      }
+      let mut closure: Vec<u32> = seen.into_iter().collect();
+      closure.sort_unstable();
+      closure
+   }
+
+   /// Groups of issues depending on each other in a loop (strongly
+   /// connected components of more than one issue), by Tarjan's algorithm
+   pub fn cycles(&self) -> Vec<Vec<u32>> {
+      let mut tarjan = Tarjan {
+         graph:    self,
+         next:     0,
+         index:    HashMap::new(),
+         lowlink:  HashMap::new(),
+         stack:    Vec::new(),
+         on_stack: HashSet::new(),
+         cycles:   Vec::new(),
+      };
+      for id in self.ids() {
+         if !tarjan.index.contains_key(&id) {
+            tarjan.visit(id);
+         }
+      }
+      tarjan.cycles
+   }
+
+   /// Layers of issues: each one below its deepest dependency in the graph,
+   /// so issues with nothing to wait on come first. Issues on a cycle go
+   /// after everything else.
+   pub fn layers(&self) -> Vec<Vec<u32>> {
+      let mut depth: HashMap<u32, usize> = HashMap::new();
+      let mut remaining: Vec<u32> = self.ids().collect();
+      while !remaining.is_empty() {
+         let ready: Vec<(u32, usize)> = remaining
+            .iter()
+            .filter_map(|&id| {
+               self
+                  .depends_on(id)
+                  .iter()
+                  .filter(|dep| self.contains(**dep))
+                  .try_fold(0, |deepest, dep| depth.get(dep).map(|d| deepest.max(d + 1)))
+                  .map(|d| (id, d))
+            })
+            .collect();
+         if ready.is_empty() {
+            let below = depth.values().max().map_or(0, |d| d + 1);
+            depth.extend(remaining.drain(..).map(|id| (id, below)));
+            break;
+         }
+         remaining.retain(|id| !ready.iter().any(|(r, _)| r == id));
+         depth.extend(ready);
+      }
+
+      let mut layers: Vec<Vec<u32>> = vec![Vec::new(); depth.values().max().map_or(0, |d| d + 1)];
+      for id in self.ids() {
+         layers[depth[&id]].push(id);
+      }
+      layers
+   }
+
+   /// The chain of issues, each waiting on the one before, with the most
+   /// `weight` in total, e.g. minutes of effort; issues on a cycle are left
+   /// out. Ties go to the chain found first, by issue number.
+   pub fn heaviest_chain(&self, weight: impl Fn(u32) -> u32) -> Vec<u32> {
+      let cyclic: HashSet<u32> = self.cycles().into_iter().flatten().collect();
+      // Heaviest chain ending at each issue, as its total and the
+      // dependency it continues from
+      let mut best: HashMap<u32, (u32, Option<u32>)> = HashMap::new();
+
+      fn visit(
+         graph: &IssueGraph,
+         id: u32,
+         cyclic: &HashSet<u32>,
+         weight: &impl Fn(u32) -> u32,
+         best: &mut HashMap<u32, (u32, Option<u32>)>,
+      ) -> u32 {
+         if let Some(&(total, _)) = best.get(&id) {
+            return total;
+         }
+         let mut from = None;
+         let mut heaviest = 0;
+         for &dep in graph.depends_on(id) {
+            if dep == id || cyclic.contains(&dep) || !graph.contains(dep) {
+               continue;
+            }
+            let total = visit(graph, dep, cyclic, weight, best);
+            if from.is_none() || total > heaviest {
+               (from, heaviest) = (Some(dep), total);
+            }
+         }
+         let total = heaviest + weight(id);
+         best.insert(id, (total, from));
+         total
+      }
+
+      let mut end: Option<(u32, u32)> = None;
+      for id in self.ids().filter(|id| !cyclic.contains(id)) {
+         let total = visit(self, id, &cyclic, &weight, &mut best);
+         if end.is_none_or(|(_, heaviest)| total > heaviest) {
+            end = Some((id, total));
+         }
+      }
+
+      let mut chain = Vec::new();
+      let mut next = end.map(|(id, _)| id);
+      while let Some(id) = next {
+         chain.push(id);
+         next = best[&id].1;
+      }
+      chain.reverse();
+      chain
+   }
+}
+
+/// State of Tarjan's strongly connected components search
+struct Tarjan<'a> {
+   graph:    &'a IssueGraph,
+   next:     usize,
+   index:    HashMap<u32, usize>,
+   lowlink:  HashMap<u32, usize>,
+   stack:    Vec<u32>,
+   on_stack: HashSet<u32>,
+   cycles:   Vec<Vec<u32>>,
+}
+
+impl Tarjan<'_> {
+   fn visit(&mut self, id: u32) {
+      self.index.insert(id, self.next);
+      self.lowlink.insert(id, self.next);
+      self.next += 1;
+      self.stack.push(id);
+      self.on_stack.insert(id);
+
+      for &dep in self.graph.depends_on(id) {
+         if !self.index.contains_key(&dep) {
+            self.visit(dep);
+            let low = self.lowlink[&id].min(self.lowlink[&dep]);
+            self.lowlink.insert(id, low);
+         } else if self.on_stack.contains(&dep) {
+            let low = self.lowlink[&id].min(self.index[&dep]);
+            self.lowlink.insert(id, low);
+         }
+      }
+
+      if self.lowlink[&id] == self.index[&id] {
+         let mut component = Vec::new();
+         while let Some(member) = self.stack.pop() {
+            self.on_stack.remove(&member);
+            component.push(member);
+            if member == id {
+               break;
+            }
+         }
+         if component.len() > 1 {
+            component.reverse();
+            self.cycles.push(component);
+         }
+      }
+   }
+}
+
+/// Longest title shown in a node, in characters
+const LABEL_CHARS: usize = 32;
+
+const NODE_WIDTH: usize = 240;
+const NODE_HEIGHT: usize = 44;
+const H_GAP: usize = 32;
+const V_GAP: usize = 56;
+const MARGIN: usize = 16;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GraphFormat {
+   #[default]
+   Ascii,
+   Mermaid,
+   Dot,
+   Svg,
+}
+
+/// What `--cluster` groups issues by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClusterBy {
+   Tag,
+   Milestone,
+}
+
+impl std::fmt::Display for ClusterBy {
+   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+      match self {
+         Self::Tag => write!(f, "tag"),
+         Self::Milestone => write!(f, "milestone"),
+      }
+   }
+}
+
+/// Parse `--cluster`
+pub fn parse_cluster(s: &str) -> Result<ClusterBy> {
+   Ok(match s.to_ascii_lowercase().as_str() {
+      "tag" | "tags" => ClusterBy::Tag,
+      "milestone" | "milestones" => ClusterBy::Milestone,
+      _ => anyhow::bail!("Unknown cluster: {s}. Use: tag, milestone"),
+   })
+}
+
+/// The nodes grouped by their first tag or their milestone, clusters by
+/// name and the nodes without one last
+pub fn clusters(nodes: &[GraphNode], by: ClusterBy) -> Vec<(Option<&str>, Vec<&GraphNode>)> {
+   let mut named: BTreeMap<&str, Vec<&GraphNode>> = BTreeMap::new();
+   let mut rest = Vec::new();
+   for node in nodes {
+      let name = match by {
+         ClusterBy::Tag => node.tags.first(),
+         ClusterBy::Milestone => node.milestone.as_ref(),
+      };
+      match name {
+         Some(name) => named.entry(name.as_str()).or_default().push(node),
+         None => rest.push(node),
+      }
+   }
+   let mut clusters: Vec<_> = named
+      .into_iter()
+      .map(|(name, members)| (Some(name), members))
+      .collect();
+   if !rest.is_empty() {
+      clusters.push((None, rest));
+   }
+   clusters
+}
+
+/// Parse `--format`
+pub fn parse_format(s: &str) -> Result<GraphFormat> {
+   Ok(match s.to_ascii_lowercase().as_str() {
+      "ascii" | "text" => GraphFormat::Ascii,
+      "mermaid" | "mmd" => GraphFormat::Mermaid,
+      "dot" | "graphviz" | "gv" => GraphFormat::Dot,
+      "svg" => GraphFormat::Svg,
+      _ => anyhow::bail!("Unknown graph format: {s}. Use: ascii, mermaid, dot, svg"),
+   })
+}
+
+/// The format an `--out` file's extension asks for, if any
+pub fn format_for_path(path: &str) -> Option<GraphFormat> {
+   let (_, ext) = path.rsplit_once('.')?;
+   parse_format(ext).ok().filter(|f| *f != GraphFormat::Ascii)
+}
+
+/// Fill color per status, shared by every format
+fn fill(status: Status) -> &'static str {
+   match status {
+      Status::NotStarted => "#e8f0fe",
+      Status::InProgress => "#fff4c2",
+      Status::Blocked => "#fde2e1",
+      Status::Done => "#dcf5e3",
+      Status::Closed => "#eeeeee",
+      Status::Backlog => "#f3f3f3",
+   }
+}
+
+fn class_name(status: Status) -> String {
+   status.to_string().replace([' ', '-'], "_")
+}
+
+/// `#12: Title`, with the title cut to fit a node
+fn label(node: &GraphNode, issue_ref: &impl Fn(u32) -> String) -> String {
+   let title = if node.title.chars().count() > LABEL_CHARS {
+      let kept: String = node.title.chars().take(LABEL_CHARS - 1).collect();
+      format!("{}…", kept.trim_end())
+   } else {
+      node.title.clone()
+   };
+   format!("{}: {title}", issue_ref(node.id))
+}
+
+/// Dependency edges between the nodes shown, as (dependency, dependent)
+fn edges(nodes: &[GraphNode]) -> Vec<(u32, u32)> {
+   let mut edges: Vec<(u32, u32)> = nodes
+      .iter()
+      .flat_map(|n| n.depends_on.iter().map(move |&dep| (dep, n.id)))
+      .filter(|(dep, _)| nodes.iter().any(|n| n.id == *dep))
+      .collect();
+   edges.sort_unstable();
+   edges
+}
+
+/// Mermaid flowchart source, as GitHub renders in a ```mermaid block;
+/// clusters become subgraphs
+pub fn mermaid(
+   nodes: &[GraphNode],
+   issue_ref: impl Fn(u32) -> String,
+   cluster: Option<ClusterBy>,
+) -> String {
+   let mut out = String::from("flowchart TD\n");
+   let node_line = |node: &GraphNode, indent: &str| {
+      let text = label(node, &issue_ref).replace('"', "#quot;");
+      format!("{indent}i{}[\"{text}\"]:::{}\n", node.id, class_name(node.status))
+   };
+   match cluster {
+      Some(by) => {
+         for (i, (name, members)) in clusters(nodes, by).into_iter().enumerate() {
+            let title = match name {
+               Some(name) => format!("{by}: {name}").replace('"', "#quot;"),
+               None => format!("no {by}"),
+            };
+            out.push_str(&format!("  subgraph c{i}[\"{title}\"]\n"));
+            for node in members {
+               out.push_str(&node_line(node, "    "));
+            }
+            out.push_str("  end\n");
+         }
+      },
+      None => {
+         for node in nodes {
+            out.push_str(&node_line(node, "  "));
+         }
+      },
+   }
+   for (dep, dependent) in edges(nodes) {
+      out.push_str(&format!("  i{dep} --> i{dependent}\n"));
+   }
+   let mut statuses: Vec<Status> = nodes.iter().map(|n| n.status).collect();
+   statuses.sort_by_key(|s| s.to_string());
+   statuses.dedup();
+   for status in statuses {
+      out.push_str(&format!("  classDef {} fill:{}\n", class_name(status), fill(status)));
+   }
+   out
+}
+
+/// Graphviz DOT source; clusters become `cluster_` subgraphs, which `dot`
+/// draws boxed and labeled
+pub fn dot(
+   nodes: &[GraphNode],
+   issue_ref: impl Fn(u32) -> String,
+   cluster: Option<ClusterBy>,
+) -> String {
+   let mut out = String::from(
+      "digraph deps {\n  rankdir=TB;\n  node [shape=box, style=\"rounded,filled\"];\n",
+   );
+   let escape = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"");
+   let node_line = |node: &GraphNode, indent: &str| {
+      format!(
+         "{indent}i{} [label=\"{}\", fillcolor=\"{}\"];\n",
+         node.id,
+         escape(&label(node, &issue_ref)),
+         fill(node.status)
+      )
+   };
+   match cluster {
+      Some(by) => {
+         for (i, (name, members)) in clusters(nodes, by).into_iter().enumerate() {
+            let title = match name {
+               Some(name) => format!("{by}: {name}"),
+               None => format!("no {by}"),
+            };
+            out.push_str(&format!(
+               "  subgraph cluster_{i} {{\n    label=\"{}\";\n",
+               escape(&title)
+            ));
+            for node in members {
+               out.push_str(&node_line(node, "    "));
+            }
+            out.push_str("  }\n");
+         }
+      },
+      None => {
+         for node in nodes {
+            out.push_str(&node_line(node, "  "));
+         }
+      },
+   }
+   for (dep, dependent) in edges(nodes) {
+      out.push_str(&format!("  i{dep} -> i{dependent};\n"));
+   }
+   out.push_str("}\n");
+   out
+}
+
+/// SVG drawn by Graphviz when `dot` is on the PATH, otherwise by
+/// [`layout_svg`], which leaves clusters out
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn svg(
+   nodes: &[GraphNode],
+   issue_ref: impl Fn(u32) -> String,
+   cluster: Option<ClusterBy>,
+) -> Result<String> {
+   let source = dot(nodes, &issue_ref, cluster);
+   let mut child = match Command::new("dot")
+      .arg("-Tsvg")
+      .stdin(Stdio::piped())
+      .stdout(Stdio::piped())
+      .stderr(Stdio::null())
+      .spawn()
+   {
+      Ok(child) => child,
+      Err(e) if e.kind() == ErrorKind::NotFound => return Ok(layout_svg(nodes, issue_ref)),
+      Err(e) => return Err(e).context("Failed to run dot"),
+   };
+   child
+      .stdin
+      .take()
+      .context("dot stdin unavailable")?
+      .write_all(source.as_bytes())?;
+   let output = child.wait_with_output()?;
+   if !output.status.success() {
+      anyhow::bail!("dot failed ({})", output.status);
+   }
+   String::from_utf8(output.stdout).context("dot output is not UTF-8")
+}
+
+/// SVG from a layered layout, for when Graphviz isn't installed
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn layout_svg(nodes: &[GraphNode], issue_ref: impl Fn(u32) -> String) -> String {
+   let layers = IssueGraph::from_nodes(nodes).layers();
+   let widest = layers.iter().map(Vec::len).max().unwrap_or(0);
+   let width = MARGIN * 2 + widest * NODE_WIDTH + widest.saturating_sub(1) * H_GAP;
+   let height = MARGIN * 2 + layers.len() * NODE_HEIGHT + layers.len().saturating_sub(1) * V_GAP;
+
+   // Each layer is centered, so narrow layers sit under wide ones
+   let mut at: HashMap<u32, (usize, usize)> = HashMap::new();
+   for (row, layer) in layers.iter().enumerate() {
+      let span = layer.len() * NODE_WIDTH + layer.len().saturating_sub(1) * H_GAP;
+      let left = (width - span) / 2;
+      for (col, id) in layer.iter().enumerate() {
+         at.insert(*id, (left + col * (NODE_WIDTH + H_GAP), MARGIN + row * (NODE_HEIGHT + V_GAP)));
+      }
+   }
+
+   let mut out = format!(
+      "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+       viewBox=\"0 0 {width} {height}\" font-family=\"Helvetica, Arial, sans-serif\" \
+       font-size=\"13\">\n"
+   );
+   out.push_str(
+      "<defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" markerWidth=\"8\" \
+       markerHeight=\"8\" orient=\"auto\"><path d=\"M0,0 L10,5 L0,10 z\" \
+       fill=\"#555\"/></marker></defs>\n",
+   );
+   for (dep, dependent) in edges(nodes) {
+      let (fx, fy) = at[&dep];
+      let (tx, ty) = at[&dependent];
+      out.push_str(&format!(
+         "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{ty}\" stroke=\"#555\" \
+          marker-end=\"url(#arrow)\"/>\n",
+         fx + NODE_WIDTH / 2,
+         fy + NODE_HEIGHT,
+         tx + NODE_WIDTH / 2,
+      ));
+   }
+   for node in nodes {
+      let (x, y) = at[&node.id];
+      out.push_str(&format!(
+         "<rect x=\"{x}\" y=\"{y}\" width=\"{NODE_WIDTH}\" height=\"{NODE_HEIGHT}\" rx=\"6\" \
+          fill=\"{}\" stroke=\"#888\"/>\n<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" \
+          dominant-baseline=\"middle\">{}</text>\n",
+         fill(node.status),
+         x + NODE_WIDTH / 2,
+         y + NODE_HEIGHT / 2,
+         escape_xml(&label(node, &issue_ref)),
+      ));
+   }
+   out.push_str("</svg>\n");
+   out
+}
+
+/// How an edge is drawn by [`ascii`]; where edges meet, the later kind wins
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum EdgeKind {
+   #[default]
+   Plain,
+   Critical,
+   Cycle,
+}
+
+/// Directions a cell of an edge lane connects to
+const UP: u8 = 1;
+const DOWN: u8 = 2;
+const LEFT: u8 = 4;
+const RIGHT: u8 = 8;
+
+/// Box-drawing character joining `dirs`: heavy on the critical path,
+/// dashed within a cycle
+fn glyph(dirs: u8, kind: EdgeKind) -> char {
+   let [
+      vertical,
+      horizontal,
+      down_right,
+      up_right,
+      down_left,
+      up_left,
+      tee_down,
+      tee_up,
+      tee_right,
+      tee_left,
+      cross,
+   ] = match kind {
+      EdgeKind::Plain => ['│', '─', '┌', '└', '┐', '┘', '┬', '┴', '├', '┤', '┼'],
+      EdgeKind::Critical => ['┃', '━', '┏', '┗', '┓', '┛', '┳', '┻', '┣', '┫', '╋'],
+      EdgeKind::Cycle => ['╎', '╌', '┌', '└', '┐', '┘', '┬', '┴', '├', '┤', '┼'],
+   };
+   match (dirs & UP != 0, dirs & DOWN != 0, dirs & LEFT != 0, dirs & RIGHT != 0) {
+      (false, false, false, false) => ' ',
+      (true, true, true, true) => cross,
+      (true, true, false, true) => tee_right,
+      (true, true, true, false) => tee_left,
+      (false, true, true, true) => tee_down,
+      (true, false, true, true) => tee_up,
+      (false, true, false, true) => down_right,
+      (true, false, false, true) => up_right,
+      (false, true, true, false) => down_left,
+      (true, false, true, false) => up_left,
+      (_, _, false, false) => vertical,
+      (false, false, ..) => horizontal,
+   }
+}
+
+fn paint(text: &str, kind: EdgeKind, color: bool) -> String {
+   match kind {
+      _ if !color => text.to_string(),
+      EdgeKind::Plain => text.to_string(),
+      EdgeKind::Critical => text.yellow().bold().to_string(),
+      EdgeKind::Cycle => text.red().to_string(),
+   }
+}
+
+/// The graph as text for a terminal: one issue per line in layer order,
+/// each dependency routed down its own lane on the left into the `▶` of
+/// the issue waiting on it. Edges along `critical` are heavy and edges
+/// within a cycle dashed; with `color` they are also yellow and red, and
+/// issues take the color of their priority.
+pub fn ascii(
+   nodes: &[GraphNode],
+   issue_ref: impl Fn(u32) -> String,
+   critical: &[u32],
+   color: bool,
+) -> String {
+   let graph = IssueGraph::from_nodes(nodes);
+   let rows: Vec<u32> = graph.layers().concat();
+   let row_of: HashMap<u32, usize> = rows
+      .iter()
+      .enumerate()
+      .map(|(row, &id)| (id, row))
+      .collect();
+   let cycles: Vec<HashSet<u32>> = graph
+      .cycles()
+      .into_iter()
+      .map(|cycle| cycle.into_iter().collect())
+      .collect();
+   let on_path: HashSet<(u32, u32)> = critical.windows(2).map(|w| (w[0], w[1])).collect();
+
+   // Each edge as its top and bottom row, the dependent's row and its kind;
+   // short edges get the lanes nearest the issues, and edges meeting at an
+   // issue can share one
+   let mut spans: Vec<(usize, usize, usize, EdgeKind)> = edges(nodes)
+      .into_iter()
+      .filter(|(dep, dependent)| dep != dependent)
+      .map(|(dep, dependent)| {
+         let (from, to) = (row_of[&dep], row_of[&dependent]);
+         let kind = if cycles
+            .iter()
+            .any(|c| c.contains(&dep) && c.contains(&dependent))
+         {
+            EdgeKind::Cycle
+         } else if on_path.contains(&(dep, dependent)) {
+            EdgeKind::Critical
+         } else {
+            EdgeKind::Plain
+         };
+         (from.min(to), from.max(to), to, kind)
+      })
+      .collect();
+   spans.sort_by_key(|&(top, bottom, ..)| (bottom - top, top));
+   let mut lanes: Vec<Vec<(usize, usize, usize, EdgeKind)>> = Vec::new();
+   for span in spans {
+      let free = lanes.iter().position(|lane| {
+         lane
+            .iter()
+            .all(|&(top, bottom, ..)| bottom <= span.0 || top >= span.1)
+      });
+      match free {
+         Some(lane) => lanes[lane].push(span),
+         None => lanes.push(vec![span]),
+      }
+   }
+
+   let nodes_by_id: HashMap<u32, &GraphNode> = nodes.iter().map(|n| (n.id, n)).collect();
+   let mut out = String::new();
+   for (row, id) in rows.iter().enumerate() {
+      // Two cells per lane, the farthest lane leftmost
+      let mut cells = vec![(0u8, EdgeKind::Plain); lanes.len() * 2];
+      let mut arrow = None;
+      for (i, lane) in lanes.iter().enumerate() {
+         let at = (lanes.len() - 1 - i) * 2;
+         for &(top, bottom, target, kind) in lane.iter().filter(|s| (s.0..=s.1).contains(&row)) {
+            let mark = |cell: &mut (u8, EdgeKind), dirs: u8| {
+               cell.0 |= dirs;
+               cell.1 = cell.1.max(kind);
+            };
+            if row != top && row != bottom {
+               mark(&mut cells[at], UP | DOWN);
+               continue;
+            }
+            mark(&mut cells[at], RIGHT | if row == top { DOWN } else { UP });
+            for cell in &mut cells[at + 1..] {
+               mark(cell, LEFT | RIGHT);
+            }
+            if row == target {
+               arrow = arrow.max(Some(kind));
+            }
+         }
+      }
+
+      for &(dirs, kind) in &cells {
+         out.push_str(&paint(&glyph(dirs, kind).to_string(), kind, color));
+      }
+      match (arrow, cells.last()) {
+         (Some(kind), _) => out.push_str(&paint("▶", kind, color)),
+         (None, Some(&(dirs, kind))) if dirs & RIGHT != 0 => {
+            out.push_str(&paint(&glyph(LEFT | RIGHT, kind).to_string(), kind, color));
+         },
+         _ => out.push(' '),
+      }
+
+      let node = nodes_by_id[id];
+      let text = format!(
+         "{} {} [{}] {}",
+         node.status.marker(),
+         issue_ref(node.id),
+         node.priority,
+         node.title
+      );
+      let text = match node.priority {
+         _ if !color => text,
+         Priority::Critical => text.red().to_string(),
+         Priority::High => text.yellow().to_string(),
+         Priority::Medium => text,
+         Priority::Low => text.bright_black().to_string(),
+      };
+      if color && node.status == Status::Backlog {
+         out.push_str(&format!(" {}\n", text.dimmed()));
+      } else {
+         out.push_str(&format!(" {text}\n"));
+      }
+   }
+
+   let kinds: HashSet<EdgeKind> = lanes.iter().flatten().map(|span| span.3).collect();
+   let mut legend = Vec::new();
+   if kinds.contains(&EdgeKind::Critical) {
+      legend.push(paint("━ critical path", EdgeKind::Critical, color));
+   }
+   if kinds.contains(&EdgeKind::Cycle) {
+      legend.push(paint("╌ dependency cycle", EdgeKind::Cycle, color));
+   }
+   if !legend.is_empty() {
+      out.push_str(&format!("\n{}\n", legend.join("   ")));
+   }
+   out
+}
+
+fn escape_xml(text: &str) -> String {
+   text
+      .replace('&', "&amp;")
+      .replace('<', "&lt;")
+      .replace('>', "&gt;")
+      .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn node(id: u32, title: &str, status: Status, depends_on: &[u32]) -> GraphNode {
+      GraphNode {
+         id,
+         title: title.to_string(),
+         status,
+         priority: Priority::Medium,
+         depends_on: depends_on.to_vec(),
+         tags: Vec::new(),
+         milestone: None,
+      }
+   }
+
+   #[test]
+   fn test_issue_graph_queries() {
+      // 1 <- 2 <- 3 <- 4, a loop 5 <-> 6 hanging off 4, and 7 on its own
+      let edges: [(u32, &[u32]); 7] =
+         [(1, &[]), (2, &[1]), (3, &[2, 9]), (4, &[3]), (5, &[4, 6]), (6, &[5]), (7, &[])];
+      let graph = IssueGraph::new(edges);
+
+      assert_eq!(graph.cycles(), [vec![5, 6]]);
+      assert!(graph.reaches(4, 1));
+      assert!(!graph.reaches(1, 4));
+      assert!(graph.reaches(6, 6));
+      assert_eq!(graph.dependents(1), [2]);
+      assert_eq!(graph.depends_on(3), [2, 9]);
+      let mut below: Vec<u32> = graph.transitive_dependents(3).into_iter().collect();
+      below.sort_unstable();
+      assert_eq!(below, [4, 5, 6]);
+      assert_eq!(graph.closure(2, None), [1, 2, 3, 4, 5, 6, 9]);
+      assert_eq!(graph.closure(2, Some(1)), [1, 2, 3]);
+      assert_eq!(graph.closure(2, Some(0)), [2]);
+      assert_eq!(graph.closure(7, None), [7]);
+      // The loop is left out; weighing 7 heavier makes it the whole chain
+      assert_eq!(graph.heaviest_chain(|_| 1), [1, 2, 3, 4]);
+      assert_eq!(graph.heaviest_chain(|id| if id == 7 { 10 } else { 2 }), [7]);
+      assert_eq!(graph.layers(), [vec![1, 7], vec![2], vec![3], vec![4], vec![5, 6]]);
+   }
+
+   #[test]
+   fn test_graph_exports() {
+      let nodes = vec![
+         node(1, "Schema", Status::InProgress, &[]),
+         node(2, "API \"v2\"", Status::NotStarted, &[1, 9]),
+         node(3, "UI <form>", Status::Blocked, &[1, 2]),
+      ];
+      let issue_ref = |n: u32| format!("#{n}");
+
+      let mermaid = mermaid(&nodes, issue_ref, None);
+      assert!(mermaid.starts_with("flowchart TD\n"));
+      assert!(mermaid.contains("i2[\"#2: API #quot;v2#quot;\"]"));
+      assert!(mermaid.contains("  i1 --> i3\n  i2 --> i3\n"));
+      assert!(!mermaid.contains("i9"));
+
+      let dot = dot(&nodes, issue_ref, None);
+      assert!(dot.contains("i2 [label=\"#2: API \\\"v2\\\"\""));
+      assert!(dot.contains("  i1 -> i2;\n"));
+
+      assert_eq!(IssueGraph::from_nodes(&nodes).layers(), [vec![1], vec![2], vec![3]]);
+      let svg = layout_svg(&nodes, issue_ref);
+      assert!(svg.contains("#3: UI &lt;form&gt;"));
+      assert_eq!(svg.matches("<line").count(), 3);
+
+      // A cycle lands below everything else instead of looping
+      let cyclic = [node(1, "a", Status::NotStarted, &[2]), node(2, "b", Status::NotStarted, &[1])];
+      assert_eq!(IssueGraph::from_nodes(&cyclic).layers(), [vec![1, 2]]);
+
+      assert_eq!(parse_format("DOT").unwrap(), GraphFormat::Dot);
+      assert!(parse_format("png").is_err());
+      assert_eq!(format_for_path("docs/deps.svg"), Some(GraphFormat::Svg));
+      assert_eq!(format_for_path("deps.txt"), None);
+   }
+
+   #[test]
+   fn test_ascii_edge_routing() {
+      // 1 -> 2 -> 3 on the critical path, 1 -> 3 beside it, 4 <-> 5 a loop
+      let nodes = [
+         node(1, "Schema", Status::NotStarted, &[]),
+         node(2, "API", Status::NotStarted, &[1]),
+         node(3, "UI", Status::NotStarted, &[1, 2]),
+         node(4, "a", Status::NotStarted, &[5]),
+         node(5, "b", Status::NotStarted, &[4]),
+      ];
+      let text = ascii(&nodes, |n| format!("#{n}"), &[1, 2, 3], false);
+      let lines: Vec<&str> = text.lines().collect();
+      assert_eq!(lines[0], "┌─┳━━ ⭕ #1 [medium] Schema");
+      assert_eq!(lines[1], "│ ┣━▶ ⭕ #2 [medium] API");
+      assert_eq!(lines[2], "└─┻━▶ ⭕ #3 [medium] UI");
+      assert_eq!(lines[3], "┌╌┬╌▶ ⭕ #4 [medium] a");
+      assert_eq!(lines[4], "└╌┴╌▶ ⭕ #5 [medium] b");
+      assert_eq!(lines[5], "");
+      assert_eq!(lines[6], "━ critical path   ╌ dependency cycle");
+   }
+
+   #[test]
+   fn test_graph_clusters() {
+      let mut nodes = vec![
+         node(1, "Schema", Status::NotStarted, &[]),
+         node(2, "API", Status::NotStarted, &[1]),
+         node(3, "Docs", Status::NotStarted, &[]),
+      ];
+      nodes[0].tags = vec!["db".into(), "api".into()];
+      nodes[1].tags = vec!["api".into()];
+      nodes[1].milestone = Some("v1".into());
+      let issue_ref = |n: u32| format!("#{n}");
+
+      let by_tag: Vec<(Option<&str>, Vec<u32>)> = clusters(&nodes, ClusterBy::Tag)
+         .into_iter()
+         .map(|(name, members)| (name, members.iter().map(|n| n.id).collect()))
+         .collect();
+      assert_eq!(by_tag, [(Some("api"), vec![2]), (Some("db"), vec![1]), (None, vec![3])]);
+
+      let mermaid = mermaid(&nodes, issue_ref, Some(ClusterBy::Milestone));
+      assert!(mermaid.contains("  subgraph c0[\"milestone: v1\"]\n    i2[\"#2: API\"]"));
+      assert!(mermaid.contains("  subgraph c1[\"no milestone\"]\n"));
+      assert!(mermaid.contains("  i1 --> i2\n"));
+
+      let dot = dot(&nodes, issue_ref, Some(ClusterBy::Tag));
+      assert!(dot.contains("  subgraph cluster_1 {\n    label=\"tag: db\";\n    i1 ["));
+
+      assert_eq!(parse_cluster("Milestone").unwrap(), ClusterBy::Milestone);
+      assert!(parse_cluster("owner").is_err());
+   }
+}
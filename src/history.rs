@@ -0,0 +1,320 @@
+//! An issue's history, read back from git
+//!
+//! Each commit that changed an issue's file is one revision, and edits not
+//! committed yet form a last revision from the working tree. Consecutive
+//! revisions are compared field by field and line by line, which is what
+//! `agentx diff` prints.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use git2::{Repository, Sort, Tree};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use similar::{ChangeTag, TextDiff};
+
+use crate::{
+   issue::Issue,
+   storage::{CLOSED_DIR, OPEN_DIR, Storage},
+};
+
+/// Unchanged body lines kept around each change
+const CONTEXT_LINES: usize = 2;
+
+/// Frontmatter that only repeats what other fields already show
+const SKIPPED_FIELDS: &[&str] = &["transitions"];
+
+/// The issue as one commit, or the working tree, left it
+pub struct Version {
+   /// Short commit id, `None` for uncommitted changes
+   pub rev:     Option<String>,
+   pub time:    DateTime<Utc>,
+   pub summary: String,
+   pub issue:   Issue,
+}
+
+/// A frontmatter field that changed between two revisions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldChange {
+   pub field:  String,
+   pub before: Option<String>,
+   pub after:  Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineChange {
+   Added,
+   Removed,
+   Unchanged,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffLine {
+   pub change: LineChange,
+   pub text:   String,
+}
+
+/// What one revision changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Revision {
+   /// Short commit id, `None` for uncommitted changes
+   pub rev:     Option<String>,
+   pub time:    DateTime<Utc>,
+   pub summary: String,
+   /// The revision that created the issue; it has no changes to show
+   #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+   pub created: bool,
+   pub fields:  Vec<FieldChange>,
+   /// Changed body lines with a little context, one list per hunk
+   pub body:    Vec<Vec<DiffLine>>,
+}
+
+/// Every distinct version of issue `bug_num`, oldest first
+#[tracing::instrument(level = "debug", skip(storage))]
+pub fn versions(storage: &Storage, bug_num: u32) -> Result<Vec<Version>> {
+   let (repo, base) = open(storage)?;
+   let mut versions: Vec<Version> = Vec::new();
+   let mut last: Option<String> = None;
+
+   if repo.head().is_ok() {
+      let mut walk = repo.revwalk()?;
+      walk.push_head()?;
+      walk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+      for oid in walk {
+         let commit = repo.find_commit(oid?)?;
+         let Some(content) = issue_at(&repo, &commit.tree()?, &base, bug_num) else {
+            continue;
+         };
+         if last.as_deref() == Some(content.as_str()) {
+            continue;
+         }
+         versions.push(Version {
+            rev:     Some(oid_short(&commit.id())),
+            time:    Utc
+               .timestamp_opt(commit.time().seconds(), 0)
+               .single()
+               .unwrap_or_default(),
+            summary: commit.summary().unwrap_or_default().to_string(),
+            issue:   storage.parse_issue(&content)?,
+         });
+         last = Some(content);
+      }
+   }
+
+   if let Ok(path) = storage.find_issue_file(bug_num) {
+      let content = storage.read_file(&path)?;
+      if last.as_deref() != Some(content.as_str()) {
+         let issue = storage.parse_issue(&content)?;
+         versions.push(Version {
+            rev: None,
+            time: issue
+               .metadata
+               .last_updated
+               .unwrap_or(issue.metadata.created),
+            summary: "uncommitted changes".to_string(),
+            issue,
+         });
+      }
+   }
+
+   Ok(versions)
+}
+
+/// When `since` starts: a date (`YYYY-MM-DD`), an age (`30d`, `12h`, `2w`)
+/// or a git revision
+pub fn since_time(storage: &Storage, since: &str) -> Result<DateTime<Utc>> {
+   if let Some(time) = parse_date(since) {
+      return Ok(time);
+   }
+   let (repo, _) = open(storage)?;
+   let commit = repo
+      .revparse_single(since)
+      .and_then(|object| object.peel_to_commit())
+      .with_context(|| {
+         format!("Unknown --since {since}: use YYYY-MM-DD, an age like 7d, or a git revision")
+      })?;
+   Ok(Utc
+      .timestamp_opt(commit.time().seconds(), 0)
+      .single()
+      .unwrap_or_default())
+}
+
+/// What each version changed, keeping only those after `since`
+pub fn revisions(versions: &[Version], since: Option<DateTime<Utc>>) -> Vec<Revision> {
+   let start = since.map_or(0, |since| versions.partition_point(|v| v.time <= since));
+   (start..versions.len())
+      .map(|i| {
+         let version = &versions[i];
+         let (fields, body) = match i.checked_sub(1).map(|prev| &versions[prev].issue) {
+            Some(before) => (
+               field_changes(before, &version.issue),
+               body_diff(&before.body.to_string(), &version.issue.body.to_string()),
+            ),
+            None => (Vec::new(), Vec::new()),
+         };
+         Revision {
+            rev: version.rev.clone(),
+            time: version.time,
+            summary: version.summary.clone(),
+            created: i == 0,
+            fields,
+            body,
+         }
+      })
+      .collect()
+}
+
+/// Frontmatter fields that differ between `before` and `after`
+pub fn field_changes(before: &Issue, after: &Issue) -> Vec<FieldChange> {
+   let fields = |issue: &Issue| match serde_json::to_value(&issue.metadata) {
+      Ok(Value::Object(map)) => map,
+      _ => serde_json::Map::new(),
+   };
+   let (before, after) = (fields(before), fields(after));
+
+   let mut names: Vec<&String> = after.keys().chain(before.keys()).collect();
+   names.sort();
+   names.dedup();
+   names
+      .into_iter()
+      .filter(|name| !SKIPPED_FIELDS.contains(&name.as_str()))
+      .filter(|name| before.get(*name) != after.get(*name))
+      .map(|name| FieldChange {
+         field:  name.clone(),
+         before: before.get(name).and_then(display),
+         after:  after.get(name).and_then(display),
+      })
+      .collect()
+}
+
+/// Line diff of two bodies, grouped into hunks
+pub fn body_diff(before: &str, after: &str) -> Vec<Vec<DiffLine>> {
+   let diff = TextDiff::from_lines(before, after);
+   diff
+      .grouped_ops(CONTEXT_LINES)
+      .iter()
+      .map(|group| {
+         group
+            .iter()
+            .flat_map(|op| diff.iter_changes(op))
+            .map(|change| DiffLine {
+               change: match change.tag() {
+                  ChangeTag::Insert => LineChange::Added,
+                  ChangeTag::Delete => LineChange::Removed,
+                  ChangeTag::Equal => LineChange::Unchanged,
+               },
+               text:   change.value().trim_end_matches('\n').to_string(),
+            })
+            .collect()
+      })
+      .collect()
+}
+
+fn display(value: &Value) -> Option<String> {
+   match value {
+      Value::Null => None,
+      Value::String(text) => Some(text.clone()),
+      Value::Array(items) => Some(
+         items
+            .iter()
+            .map(|item| display(item).unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join(", "),
+      ),
+      other => Some(other.to_string()),
+   }
+}
+
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+   if let Ok(day) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+      return Some(Utc.from_utc_datetime(&day.and_hms_opt(0, 0, 0)?));
+   }
+   let age = value.strip_prefix('-').unwrap_or(value);
+   let split = age.find(|c: char| !c.is_ascii_digit())?;
+   let (num, unit) = age.split_at(split);
+   let num: i64 = num.parse().ok()?;
+   let delta = match unit {
+      "h" => Duration::hours(num),
+      "d" => Duration::days(num),
+      "w" => Duration::weeks(num),
+      _ => return None,
+   };
+   Some(Utc::now() - delta)
+}
+
+/// The repository holding the issues, and the issue store's path inside it
+fn open(storage: &Storage) -> Result<(Repository, PathBuf)> {
+   let repo = Repository::discover(storage.base_dir())
+      .context("Issue history needs the issues to be in a git repository")?;
+   let workdir = repo
+      .workdir()
+      .context("Issue history needs a git working tree")?
+      .canonicalize()?;
+   let base = storage
+      .base_dir()
+      .canonicalize()?
+      .strip_prefix(&workdir)
+      .map(Path::to_path_buf)
+      .context("The issues directory is outside the git repository")?;
+   Ok((repo, base))
+}
+
+/// Content of issue `bug_num`'s file in `tree`, open or closed
+fn issue_at(repo: &Repository, tree: &Tree, base: &Path, bug_num: u32) -> Option<String> {
+   let prefix = format!("{bug_num:02}-");
+   for dir in [OPEN_DIR, CLOSED_DIR] {
+      let Ok(entry) = tree.get_path(&base.join(dir)) else {
+         continue;
+      };
+      let Ok(object) = entry.to_object(repo) else {
+         continue;
+      };
+      let Some(files) = object.as_tree() else {
+         continue;
+      };
+      for file in files {
+         let name = file.name().unwrap_or_default();
+         if name.starts_with(&prefix) && (name.ends_with(".mdx") || name.ends_with(".md")) {
+            let blob = file.to_object(repo).ok()?.peel_to_blob().ok()?;
+            return String::from_utf8(blob.content().to_vec()).ok();
+         }
+      }
+   }
+   None
+}
+
+fn oid_short(oid: &git2::Oid) -> String {
+   oid.to_string()[..7].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::issue::{Status, test_support::issue};
+
+   #[test]
+   fn test_field_changes_and_body_diff() {
+      let before = issue(1).title("Fix login").body("Times out").build().issue;
+      let mut after = before.clone();
+      after.metadata.set_status(Status::InProgress);
+      after.metadata.tags = vec!["auth".into(), "web".into()];
+      after.body.append("\n\n**Checkpoint**: found it");
+
+      let fields = field_changes(&before, &after);
+      let names: Vec<&str> = fields.iter().map(|f| f.field.as_str()).collect();
+      assert_eq!(names, ["status", "tags"]);
+      assert_eq!(fields[1].before, None);
+      assert_eq!(fields[1].after.as_deref(), Some("auth, web"));
+
+      let hunks = body_diff(&before.body.to_string(), &after.body.to_string());
+      assert_eq!(hunks.len(), 1);
+      let added: Vec<&str> = hunks[0]
+         .iter()
+         .filter(|line| line.change == LineChange::Added)
+         .map(|line| line.text.as_str())
+         .collect();
+      assert!(added.contains(&"**Checkpoint**: found it"));
+   }
+}
@@ -0,0 +1,66 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use crate::{
+   graph::IssueGraph,
+   issue::{IssueWithId, Priority},
+};
+
+/// Most dependents that count towards an issue's rank; past this, more
+/// unblocked issues stop outweighing priority
+pub const MAX_RANKED_UNBLOCKS: usize = 4;
+
+/// Number of open issues each issue transitively unblocks, i.e. how many
+/// issues in `open` depend on it directly or through a chain of dependencies
+pub fn unblock_counts(open: &[IssueWithId]) -> HashMap<u32, usize> {
+   let graph = IssueGraph::from_issues(open);
+   graph
+      .ids()
+      .map(|id| (id, graph.transitive_dependents(id).len()))
+      .collect()
+}
+
+/// Sort key for picking what to work on next, lower first
+///
+/// Every unblocked issue is worth half a priority level, up to two levels, so a
+/// medium issue holding up four others ranks alongside a critical one.
+pub fn rank_key(priority: Priority, unblocks: usize) -> (i32, Reverse<usize>) {
+   let key = priority.sort_key() as i32 * 2 - unblocks.min(MAX_RANKED_UNBLOCKS) as i32;
+   (key, Reverse(unblocks))
+}
+
+/// " (unblocks N issues)" suffix for listings, empty if the issue unblocks
+/// nothing
+pub fn unblocks_note(unblocks: usize) -> String {
+   match unblocks {
+      0 => String::new(),
+      1 => " (unblocks 1 issue)".to_string(),
+      n => format!(" (unblocks {n} issues)"),
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::issue::test_support::issue;
+
+   #[test]
+   fn test_unblock_counts_are_transitive() {
+      // 1 <- 2 <- 3, 1 <- 4, and a 5 <-> 6 cycle
+      let open = vec![
+         issue(1).build(),
+         issue(2).depends_on(&[1]).build(),
+         issue(3).depends_on(&[2]).build(),
+         issue(4).depends_on(&[1]).build(),
+         issue(5).depends_on(&[6]).build(),
+         issue(6).depends_on(&[5]).build(),
+      ];
+      let counts = unblock_counts(&open);
+      assert_eq!(counts[&1], 3);
+      assert_eq!(counts[&2], 1);
+      assert_eq!(counts[&3], 0);
+      assert_eq!(counts[&5], 1);
+
+      assert!(rank_key(Priority::Medium, 4) < rank_key(Priority::High, 0));
+      assert!(rank_key(Priority::Critical, 0) < rank_key(Priority::Medium, 3));
+   }
+}
@@ -0,0 +1,286 @@
+//! YAML import schema
+//!
+//! `agentx import` reads a list of issues, bare or under an `issues:` key.
+//! Each item is an [`ImportItem`]: `title` and the `issue`, `impact` and
+//! `acceptance` sections are required (a kind's template can supply the
+//! sections), anything else is optional, and unknown fields are errors.
+//! The whole file is checked before an issue is created, and every problem
+//! is reported with the line and column it sits at. An item's optional
+//! `external_id` ties it to an issue, so importing the file again updates
+//! that issue rather than creating another.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+   issue::Priority,
+   kinds::{self, KindConfig},
+   utils::parse_effort,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ImportItem {
+   #[serde(default)]
+   pub external_id: Option<String>,
+   pub title:       String,
+   #[serde(default)]
+   pub priority:    Option<Priority>,
+   #[serde(default)]
+   pub kind:        Option<String>,
+   #[serde(default)]
+   pub tags:        Vec<String>,
+   #[serde(default)]
+   pub files:       Vec<String>,
+   #[serde(default)]
+   pub issue:       Option<String>,
+   #[serde(default)]
+   pub impact:      Option<String>,
+   #[serde(default)]
+   pub acceptance:  Option<String>,
+   #[serde(default)]
+   pub effort:      Option<String>,
+   #[serde(default)]
+   pub context:     Option<String>,
+   #[serde(default)]
+   pub milestone:   Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Wrapped {
+   issues: Vec<ImportItem>,
+}
+
+/// A problem with the file, at a 1-based line and column when known
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportError {
+   /// 1-based position of the item in the list
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub item:    Option<usize>,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub line:    Option<usize>,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub column:  Option<usize>,
+   pub message: String,
+}
+
+impl fmt::Display for ImportError {
+   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      match (self.line, self.column) {
+         (Some(line), Some(column)) => write!(f, "line {line}, column {column}")?,
+         (Some(line), None) => write!(f, "line {line}")?,
+         _ => f.write_str("input")?,
+      }
+      if let Some(item) = self.item {
+         write!(f, " (item {item})")?;
+      }
+      write!(f, ": {}", self.message)
+   }
+}
+
+/// Parse and check `source`; `kinds` are the kinds an item may name
+pub fn parse(source: &str, kinds: &[KindConfig]) -> Result<Vec<ImportItem>, Vec<ImportError>> {
+   let starts = item_starts(source);
+   let item_at = |line: usize| {
+      starts
+         .iter()
+         .rposition(|&start| start <= line)
+         .map(|i| i + 1)
+   };
+
+   let wrapped =
+      serde_yaml::from_str::<serde_yaml::Value>(source).is_ok_and(|value| value.is_mapping());
+   let items = if wrapped {
+      serde_yaml::from_str::<Wrapped>(source).map(|w| w.issues)
+   } else {
+      serde_yaml::from_str::<Vec<ImportItem>>(source)
+   };
+   let items = items.map_err(|err| {
+      let location = err.location();
+      let line = location.as_ref().map(|l| l.line());
+      // The location is already in the message when serde_yaml knows it
+      let message = err.to_string();
+      let message = match message.rsplit_once(" at line ") {
+         Some((message, _)) if location.is_some() => message.to_string(),
+         _ => message,
+      };
+      vec![ImportError {
+         message: without_item_path(&message),
+         item: line.and_then(item_at),
+         line,
+         column: location.map(|l| l.column()),
+      }]
+   })?;
+
+   let mut errors = Vec::new();
+   for (index, item) in items.iter().enumerate() {
+      let mut problems = item.problems(kinds);
+      if let Some(id) = &item.external_id
+         && let Some(first) = items[..index]
+            .iter()
+            .position(|other| other.external_id.as_ref() == Some(id))
+      {
+         problems.push((
+            "external_id",
+            format!("external_id '{id}' is already used by item {}", first + 1),
+         ));
+      }
+      for (field, message) in problems {
+         let (line, column) = field_position(source, &starts, index, field);
+         errors.push(ImportError { item: Some(index + 1), line, column, message });
+      }
+   }
+   if errors.is_empty() {
+      Ok(items)
+   } else {
+      Err(errors)
+   }
+}
+
+impl ImportItem {
+   /// Field and message for each value the schema's types let through but
+   /// agentx can't use
+   fn problems(&self, kinds: &[KindConfig]) -> Vec<(&'static str, String)> {
+      let mut problems = Vec::new();
+      if self.title.trim().is_empty() {
+         problems.push(("title", "title is empty".to_string()));
+      }
+      let kind = match self.kind.as_deref().map(|kind| kinds::find(kinds, kind)) {
+         Some(Err(e)) => {
+            problems.push(("kind", e.to_string()));
+            None
+         },
+         Some(Ok(kind)) => Some(kind),
+         None => None,
+      };
+      if let Some(effort) = &self.effort
+         && let Err(e) = parse_effort(effort)
+      {
+         problems.push(("effort", format!("{e:#}")));
+      }
+      let template = kind.map(|k| &k.template);
+      for (field, text, from_template) in [
+         ("issue", &self.issue, template.and_then(|t| t.issue.as_ref())),
+         ("impact", &self.impact, template.and_then(|t| t.impact.as_ref())),
+         ("acceptance", &self.acceptance, template.and_then(|t| t.acceptance.as_ref())),
+      ] {
+         if text.as_deref().is_none_or(|t| t.trim().is_empty()) && from_template.is_none() {
+            problems.push((field, format!("missing field `{field}`")));
+         }
+      }
+      problems
+   }
+}
+
+/// `.[2].priority: unknown variant` as `priority: unknown variant`; the
+/// item number is reported on its own, counting from 1
+fn without_item_path(message: &str) -> String {
+   let Some((path, rest)) = message.split_once(": ") else {
+      return message.to_string();
+   };
+   if path.contains(' ') || !path.contains('[') {
+      return message.to_string();
+   }
+   match path
+      .rsplit_once(']')
+      .map(|(_, field)| field.trim_start_matches('.'))
+   {
+      Some("") => rest.to_string(),
+      Some(field) => format!("{field}: {rest}"),
+      None => message.to_string(),
+   }
+}
+
+/// 1-based lines where the list's items start, for block-style lists
+fn item_starts(source: &str) -> Vec<usize> {
+   let is_item = |line: &str| {
+      let rest = line.trim_start();
+      rest == "-" || rest.starts_with("- ")
+   };
+   let indent = |line: &str| line.len() - line.trim_start().len();
+   let Some(first) = source.lines().find(|l| is_item(l)) else {
+      return Vec::new();
+   };
+   let depth = indent(first);
+   source
+      .lines()
+      .enumerate()
+      .filter(|(_, line)| is_item(line) && indent(line) == depth)
+      .map(|(index, _)| index + 1)
+      .collect()
+}
+
+/// Where `field` of item `index` is written, else where the item starts
+fn field_position(
+   source: &str,
+   starts: &[usize],
+   index: usize,
+   field: &str,
+) -> (Option<usize>, Option<usize>) {
+   let Some(&start) = starts.get(index) else {
+      return (None, None);
+   };
+   let end = starts.get(index + 1).copied().unwrap_or(usize::MAX);
+   let key = format!("{field}:");
+   source
+      .lines()
+      .enumerate()
+      .map(|(i, line)| (i + 1, line))
+      .filter(|(line_no, _)| (start..end).contains(line_no))
+      .find_map(|(line_no, line)| {
+         let body = line.trim_start().trim_start_matches('-').trim_start();
+         body
+            .starts_with(&key)
+            .then(|| (Some(line_no), Some(line.len() - body.len() + 1)))
+      })
+      .unwrap_or((Some(start), None))
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::kinds::default_kinds;
+
+   #[test]
+   fn test_parse_reports_positions() {
+      let kinds = default_kinds();
+      let good = "issues:\n  - title: Fix login\n    issue: Broken\n    impact: Users\n    \
+                  acceptance: Works\n  - title: Login crash\n    kind: bug\n    impact: Users\n";
+      let items = parse(good, &kinds).unwrap();
+      assert_eq!(items.len(), 2);
+      assert_eq!(items[1].kind.as_deref(), Some("bug"));
+
+      let twice = "- external_id: AUTH-1\n  title: Fix login\n  kind: bug\n  impact: Users\n- \
+                   external_id: AUTH-1\n  title: Fix logout\n  kind: bug\n  impact: Users\n";
+      let errors = parse(twice, &kinds).unwrap_err();
+      assert_eq!(
+         errors[0].to_string(),
+         "line 5, column 3 (item 2): external_id 'AUTH-1' is already used by item 1"
+      );
+
+      let typo = "- title: Fix login\n  priorty: high\n";
+      let errors = parse(typo, &kinds).unwrap_err();
+      assert_eq!((errors[0].item, errors[0].line), (Some(1), Some(2)));
+      assert!(errors[0].message.starts_with("unknown field `priorty`"));
+      let errors = parse("- title: Fix login\n  priority: urgent\n", &kinds).unwrap_err();
+      assert!(errors[0].to_string().starts_with(
+         "line 2, column 13 (item 1): priority: unknown variant `urgent`, expected one of"
+      ));
+
+      let bad = "- title: Fix login\n  issue: Broken\n  impact: Users\n  acceptance: Works\n- \
+                 title: Sync\n  kind: epic\n  effort: lots\n  issue: Slow\n";
+      let errors: Vec<String> = parse(bad, &kinds)
+         .unwrap_err()
+         .iter()
+         .map(ToString::to_string)
+         .collect();
+      assert_eq!(errors, [
+         "line 6, column 3 (item 2): Unknown kind 'epic'. Known kinds: bug, feature, chore, spike",
+         "line 7, column 3 (item 2): No number found in effort string: lots",
+         "line 5 (item 2): missing field `impact`",
+         "line 5 (item 2): missing field `acceptance`",
+      ]);
+   }
+}
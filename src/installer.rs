@@ -7,25 +7,18 @@ use std::{
 use anyhow::{Context, Result};
 use serde_json::json;
 
-const SAFE_TOOLS: &[&str] = &[
-   "issues_context",
-   "issues_create",
-   "issues_status",
-   "issues_show",
-   "issues_checkpoint",
-   "issues_wins",
-   "issues_search",
-   "issues_query",
-];
+use crate::mcp_simple::SimpleMcpServer;
 
-/// Get the MCP server config for stdio transport
+/// Get the MCP server config for stdio transport, auto-approving the tools
+/// that are not destructive
 fn get_mcp_config(exe_path: &Path) -> Result<serde_json::Value> {
+   let safe_tools = SimpleMcpServer::auto_approved_tools();
    Ok(json!({
       "agentx": {
          "command": exe_path.to_str().context("Invalid executable path")?,
          "args": ["serve"],
-         "autoApprove": SAFE_TOOLS,
-         "alwaysAllow": SAFE_TOOLS,
+         "autoApprove": safe_tools,
+         "alwaysAllow": safe_tools,
       }
    }))
 }
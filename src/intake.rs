@@ -0,0 +1,159 @@
+//! Issue drafts from pasted reports
+//!
+//! `agentx new --from-stdin` takes a stack trace, compiler error or bug
+//! report as it was pasted. The first line becomes the title, the whole text
+//! the Issue section (fenced when it reads like a log), and the file paths
+//! it mentions that exist in the working tree become the issue's `files`.
+//! Paths into toolchains and dependencies, and URLs, are skipped.
+
+use std::{path::Path, sync::LazyLock};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::normalize_path;
+
+/// Longest derived title, in characters
+const TITLE_CHARS: usize = 80;
+
+/// Most files taken from one report
+const MAX_FILES: usize = 10;
+
+/// Path fragments of code that isn't the project's own
+const FOREIGN_PATHS: &[&str] = &[
+   "/rustc/",
+   ".cargo/registry/",
+   ".rustup/",
+   "node_modules/",
+   "site-packages/",
+   "dist-packages/",
+   "/usr/lib/",
+   "/usr/local/lib/",
+];
+
+static URL: LazyLock<Regex> =
+   LazyLock::new(|| Regex::new(r"\b[a-zA-Z][a-zA-Z0-9+.-]*://\S+").unwrap());
+
+/// A path-like token: at least one `/` or a `:line` after it, and an
+/// extension starting with a letter so version numbers don't match
+static PATH: LazyLock<Regex> = LazyLock::new(|| {
+   Regex::new(r#"(?:[A-Za-z]:)?[\w./\\-]*[\w-]\.[A-Za-z][A-Za-z0-9]{0,7}\b(:\d+|", line \d+)?"#)
+      .unwrap()
+});
+
+/// Lines shaped like stack frames or compiler locations
+static LOG_LINE: LazyLock<Regex> =
+   LazyLock::new(|| Regex::new(r#"^\s+(at |File "|--> )|\.\w+:\d+"#).unwrap());
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Draft {
+   pub title: String,
+   pub issue: String,
+   pub files: Vec<String>,
+}
+
+/// Draft an issue from `report`; `exists` says whether a normalized path is
+/// a file in the working tree at `root`
+pub fn draft(report: &str, root: Option<&Path>, exists: impl Fn(&str) -> bool) -> Draft {
+   let report = report.trim_matches('\n').trim_end();
+   Draft { title: title(report), issue: issue_text(report), files: files(report, root, exists) }
+}
+
+/// The first non-blank line, squeezed and cut to fit a title
+fn title(report: &str) -> String {
+   let first = report
+      .lines()
+      .map(str::trim)
+      .find(|line| !line.is_empty())
+      .unwrap_or_default();
+   let first = first.split_whitespace().collect::<Vec<_>>().join(" ");
+   let first = first.trim_end_matches(':');
+   if first.chars().count() <= TITLE_CHARS {
+      return first.to_string();
+   }
+   let kept: String = first.chars().take(TITLE_CHARS - 1).collect();
+   let kept = match kept.rfind(' ') {
+      Some(space) if space > TITLE_CHARS / 2 => &kept[..space],
+      _ => &kept,
+   };
+   format!("{}…", kept.trim_end())
+}
+
+/// `report`, fenced as a code block on its own lines when it reads like a
+/// log, since it follows the `**Issue**:` label
+fn issue_text(report: &str) -> String {
+   if report.lines().any(|line| LOG_LINE.is_match(line)) {
+      format!("\n\n```\n{report}\n```")
+   } else {
+      report.to_string()
+   }
+}
+
+fn files(report: &str, root: Option<&Path>, exists: impl Fn(&str) -> bool) -> Vec<String> {
+   let text = URL.replace_all(report, " ");
+   let mut files: Vec<String> = Vec::new();
+   for found in PATH.captures_iter(&text) {
+      let whole = found.get(0).map_or("", |m| m.as_str());
+      let path = match found.get(1) {
+         Some(location) => &whole[..whole.len() - location.as_str().len()],
+         None => whole,
+      };
+      if !path.contains(['/', '\\']) && found.get(1).is_none() {
+         continue;
+      }
+      if FOREIGN_PATHS
+         .iter()
+         .any(|p| path.replace('\\', "/").contains(p))
+      {
+         continue;
+      }
+      let path = normalize_path(path, root);
+      if path.starts_with('/') || files.contains(&path) || !exists(&path) {
+         continue;
+      }
+      files.push(path);
+      if files.len() == MAX_FILES {
+         break;
+      }
+   }
+   files
+}
+
+#[cfg(test)]
+mod tests {
+
+   use super::*;
+
+   #[test]
+   fn test_draft_from_stack_trace() {
+      let report = [
+         "",
+         "thread 'main' panicked at src/storage.rs:210:14:",
+         "called `Option::unwrap()` on a `None` value",
+         "   0: std::panicking::begin_panic",
+         "             at /rustc/abc123/library/std/src/panicking.rs:652:5",
+         "   1: agentx::commands::Commands::show",
+         "             at /work/agentx/src/commands.rs:88:9",
+         "             at ./src/storage.rs:211:1",
+         "  File \"tools/gen.py\", line 3, in <module>",
+         "see https://example.com/src/docs.rs and version 1.2.3, notes.txt",
+      ]
+      .join("\n");
+      let present = ["src/storage.rs", "src/commands.rs", "tools/gen.py", "notes.txt"];
+      let draft = draft(&report, Some(Path::new("/work/agentx")), |p| present.contains(&p));
+
+      assert_eq!(draft.title, "thread 'main' panicked at src/storage.rs:210:14");
+      assert_eq!(draft.files, ["src/storage.rs", "src/commands.rs", "tools/gen.py"]);
+      assert!(draft.issue.starts_with("\n\n```\nthread 'main'"));
+      assert!(draft.issue.ends_with("notes.txt\n```"));
+
+      let prose =
+         super::draft("Login button does nothing on Safari\n\nClicking it…", None, |_| true);
+      assert_eq!(prose.title, "Login button does nothing on Safari");
+      assert!(!prose.issue.contains("```"));
+      assert!(prose.files.is_empty());
+
+      let long = super::draft(&"word ".repeat(30), None, |_| true);
+      assert!(long.title.ends_with("word…") && long.title.chars().count() <= TITLE_CHARS);
+   }
+}
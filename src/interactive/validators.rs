@@ -1,5 +1,7 @@
 use anyhow::{Result, anyhow};
 
+use crate::utils::parse_effort;
+
 /// Validate that input is not empty
 pub fn validate_non_empty(input: &str) -> Result<()> {
    if input.trim().is_empty() {
@@ -19,28 +21,12 @@ pub fn validate_priority(input: &str) -> Result<()> {
 
 /// Validate effort estimation
 pub fn validate_effort(input: &str) -> Result<()> {
-   // T-shirt sizes
-   if matches!(input.to_uppercase().as_str(), "XS" | "S" | "M" | "L" | "XL") {
-      return Ok(());
-   }
-
-   // Story points
-   if matches!(input, "1" | "2" | "3" | "5" | "8" | "13" | "21") {
-      return Ok(());
-   }
-
-   // Time estimates (e.g., "2h", "1d", "30m")
-   if input.ends_with('h') || input.ends_with('d') || input.ends_with('m') {
-      let num_part = &input[..input.len() - 1];
-      if num_part.parse::<u32>().is_ok() {
-         return Ok(());
-      }
-   }
-
-   Err(anyhow!(
-      "Effort must be T-shirt size (XS/S/M/L/XL), story points (1/2/3/5/8/13/21), or time (e.g., \
-       2h, 1d)"
-   ))
+   parse_effort(input).map(|_| ()).map_err(|_| {
+      anyhow!(
+         "Effort must be a T-shirt size (XS/S/M/L/XL), time (e.g., 2h, 1d), a range (e.g., 2-4h) \
+          or story points (e.g., 3pt)"
+      )
+   })
 }
 
 /// Validate file path exists
@@ -2,7 +2,9 @@ use std::path::PathBuf;
 
 use anyhow::Result;
 use console::Style;
-use dialoguer::{Confirm, Editor, Input, MultiSelect, Select, theme::ColorfulTheme};
+use dialoguer::{
+   Completion, Confirm, Editor, FuzzySelect, Input, MultiSelect, Select, theme::ColorfulTheme,
+};
 use fuzzy_matcher::{FuzzyMatcher, skim::SkimMatcherV2};
 
 /// Create a styled theme for dialoguer prompts
@@ -67,6 +69,44 @@ pub fn prompt_optional(prompt: &str, default: Option<&str>) -> Result<String> {
    input.allow_empty(true).interact_text().map_err(Into::into)
 }
 
+/// Tab completion of the comma-separated entry being typed from known values
+struct ListCompletion<'a> {
+   candidates: &'a [String],
+}
+
+impl Completion for ListCompletion<'_> {
+   fn get(&self, input: &str) -> Option<String> {
+      let (head, entry) = match input.rsplit_once(',') {
+         Some((head, entry)) => (Some(head), entry.trim_start()),
+         None => (None, input),
+      };
+      if entry.is_empty() {
+         return None;
+      }
+      let entry = entry.to_lowercase();
+      let found = self
+         .candidates
+         .iter()
+         .find(|c| c.to_lowercase().starts_with(&entry) && c.len() > entry.len())?;
+      Some(match head {
+         Some(head) => format!("{head}, {found}"),
+         None => found.clone(),
+      })
+   }
+}
+
+/// Prompt for optional text input, Tab completing the entry being typed
+/// (after the last comma) from `candidates`
+pub fn prompt_completing(prompt: &str, candidates: &[String]) -> Result<String> {
+   let completion = ListCompletion { candidates };
+   Input::with_theme(&create_theme())
+      .with_prompt(prompt)
+      .completion_with(&completion)
+      .allow_empty(true)
+      .interact_text()
+      .map_err(Into::into)
+}
+
 /// Prompt for multi-line text using an editor
 pub fn prompt_editor(prompt: &str, initial_text: Option<&str>) -> Result<Option<String>> {
    println!("{}", Style::new().bold().cyan().apply_to(prompt));
@@ -86,6 +126,18 @@ pub fn prompt_select<T: ToString>(prompt: &str, items: &[T]) -> Result<usize> {
       .map_err(Into::into)
 }
 
+/// Prompt for a selection from a list filtered by typing; `None` if cancelled
+/// with Esc
+pub fn prompt_fuzzy_select<T: ToString>(prompt: &str, items: &[T]) -> Result<Option<usize>> {
+   FuzzySelect::with_theme(&create_theme())
+      .with_prompt(prompt)
+      .items(items)
+      .default(0)
+      .max_length(15)
+      .interact_opt()
+      .map_err(Into::into)
+}
+
 /// Prompt for multiple selections from a list
 pub fn prompt_multi_select<T: ToString>(
    prompt: &str,
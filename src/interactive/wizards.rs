@@ -1,88 +1,139 @@
+use std::collections::BTreeSet;
+
 use anyhow::Result;
 
 use crate::{
-   commands::Commands,
+   api::RankedIssue,
+   commands::{CheckpointMark, Commands},
+   config::Config,
+   intake::Draft,
    interactive::{validators, wizard},
+   issue::{IssueWithId, Status},
    storage::Storage,
 };
 
-/// Interactive wizard for creating a new issue
-pub fn new_issue_wizard(storage: &Storage, json: bool) -> Result<()> {
+/// What an issue is being picked for, which decides the candidates offered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickFor {
+   /// Ready issues, best next pick first
+   Start,
+   /// Open issues, active ones first
+   Close,
+   /// Open issues followed by closed ones
+   Show,
+}
+
+/// Fuzzy-searchable picker over issues with priority and effort columns
+///
+/// Returns the picked issue number as a reference, `None` if the user
+/// cancelled or there was nothing to pick from.
+pub fn pick_issue(commands: &Commands, pick_for: PickFor) -> Result<Option<String>> {
+   let candidates: Vec<RankedIssue> = match pick_for {
+      PickFor::Start => commands.tracker().ready_data()?,
+      PickFor::Close | PickFor::Show => {
+         let status = if pick_for == PickFor::Show {
+            "all"
+         } else {
+            "open"
+         };
+         let mut issues: Vec<RankedIssue> = commands
+            .tracker()
+            .list_data(status, None)?
+            .issues
+            .iter()
+            .map(|issue_with_id| RankedIssue::new(issue_with_id, 0))
+            .collect();
+         let order = |status: Status| match status {
+            Status::InProgress => 0,
+            Status::Blocked => 1,
+            Status::NotStarted => 2,
+            Status::Backlog => 3,
+            Status::Done | Status::Closed => 4,
+         };
+         issues.sort_by_key(|i| (order(i.status), i.priority.sort_key(), i.num));
+         issues
+      },
+   };
+
+   if candidates.is_empty() {
+      wizard::info(match pick_for {
+         PickFor::Start => "No issues are ready to start",
+         PickFor::Close => "No open issues",
+         PickFor::Show => "No issues",
+      });
+      return Ok(None);
+   }
+
+   let config = commands.config();
+   let refs: Vec<String> = candidates
+      .iter()
+      .map(|i| config.format_issue_ref(i.num))
+      .collect();
+   let ref_width = refs.iter().map(String::len).max().unwrap_or(0);
+   let rows: Vec<String> = candidates
+      .iter()
+      .zip(&refs)
+      .map(|(issue, issue_ref)| {
+         let status = match pick_for {
+            PickFor::Start => String::new(),
+            _ => format!("{:<8} ", issue.status.to_string()),
+         };
+         format!(
+            "{issue_ref:<ref_width$}  {:<8}  {:<5}  {status}{}",
+            issue.priority.to_string(),
+            issue.effort.as_deref().unwrap_or("-"),
+            issue.title
+         )
+      })
+      .collect();
+
+   let prompt = match pick_for {
+      PickFor::Start => "Issue to start",
+      PickFor::Close => "Issue to close",
+      PickFor::Show => "Issue to show",
+   };
+   Ok(wizard::prompt_fuzzy_select(prompt, &rows)?.map(|idx| candidates[idx].num.to_string()))
+}
+
+/// Interactive wizard for creating a new issue; returns the new issue's
+/// number, `None` if cancelled
+pub fn new_issue_wizard(storage: &Storage, json: bool) -> Result<Option<u32>> {
    wizard::section("🚀 Create New Issue");
 
    // Title
    let title = wizard::prompt_required("Title", validators::validate_non_empty)?;
 
    // Priority selection
-   let priorities = vec![
-      "Critical - Production outage",
-      "High - Major feature blocked",
-      "Medium - Standard priority",
-      "Low - Nice to have",
-   ];
-   let priority_idx = wizard::prompt_select("Priority", &priorities)?;
-   let priority = match priority_idx {
-      0 => "critical",
-      1 => "high",
-      2 => "medium",
-      3 => "low",
-      _ => "medium",
-   };
+   let priority = prompt_priority()?;
+
+   // Kind, whose template seeds the editors below
+   let commands = Commands::new(storage.clone());
+   let kind = prompt_kind(commands.config())?;
+   let template = kind
+      .as_deref()
+      .map(|k| commands.config().kind(k))
+      .transpose()?
+      .map(|k| k.template.clone())
+      .unwrap_or_default();
 
    // Issue description (multi-line editor)
    wizard::info("Opening editor for issue description...");
-   let issue = wizard::prompt_editor("📝 Issue Description", None)?
+   let issue = wizard::prompt_editor("📝 Issue Description", template.issue.as_deref())?
       .unwrap_or_else(|| "No description provided".to_string());
 
    // Impact description
    wizard::info("Opening editor for impact description...");
-   let impact = wizard::prompt_editor("💥 Impact", None)?
+   let impact = wizard::prompt_editor("💥 Impact", template.impact.as_deref())?
       .unwrap_or_else(|| "No impact description provided".to_string());
 
    // Acceptance criteria
    wizard::info("Opening editor for acceptance criteria...");
-   let acceptance = wizard::prompt_editor("✓ Acceptance Criteria", None)?
+   let acceptance = wizard::prompt_editor("✓ Acceptance Criteria", template.acceptance.as_deref())?
       .unwrap_or_else(|| "No acceptance criteria provided".to_string());
 
    // Effort estimation
    wizard::section("📊 Effort Estimation");
-   let effort_options = vec![
-      "XS - Very small (< 1h)",
-      "S - Small (1-2h)",
-      "M - Medium (2-4h)",
-      "L - Large (1 day)",
-      "XL - Very large (2+ days)",
-      "Skip",
-   ];
-   let effort_idx = wizard::prompt_select("T-shirt size", &effort_options)?;
-   let effort = if effort_idx < 5 {
-      Some(
-         match effort_idx {
-            0 => "XS",
-            1 => "S",
-            2 => "M",
-            3 => "L",
-            4 => "XL",
-            _ => "M",
-         }
-         .to_string(),
-      )
-   } else {
-      None
-   };
-
-   // Tags (optional)
-   wizard::section("🏷️  Tags");
-   let tags_input = wizard::prompt_optional("Tags (comma-separated, optional)", None)?;
-   let tags: Vec<String> = if tags_input.trim().is_empty() {
-      Vec::new()
-   } else {
-      tags_input
-         .split(',')
-         .map(|s| s.trim().to_string())
-         .filter(|s| !s.is_empty())
-         .collect()
-   };
+   let effort = prompt_effort()?;
 
    // Related files (optional)
    wizard::section("📁 Related Files");
@@ -106,6 +157,95 @@ pub fn new_issue_wizard(storage: &Storage, json: bool) -> Result<()> {
       Vec::new()
    };
 
+   // Tags, milestones and agents already in use, for completion
+   let known = commands.tracker().list_data("all", None)?.issues;
+   let known_values = |values: &dyn Fn(&IssueWithId) -> Vec<String>| {
+      let set: BTreeSet<String> = known.iter().flat_map(values).collect();
+      set.into_iter().collect::<Vec<_>>()
+   };
+
+   // Tags (optional), seeded with suggestions from tagging history
+   wizard::section("🏷️  Tags");
+   let suggestions = commands
+      .tracker()
+      .suggest_tags_data(&title, &files, &[] as &[&str])?;
+   let mut tags: Vec<String> = if suggestions.is_empty() {
+      Vec::new()
+   } else {
+      let items: Vec<String> = suggestions
+         .iter()
+         .map(|s| {
+            format!("#{} ({:.0}%, via {})", s.tag, s.confidence * 100.0, s.reasons.join(", "))
+         })
+         .collect();
+      let defaults: Vec<bool> = suggestions.iter().map(|s| s.confidence >= 0.5).collect();
+      wizard::prompt_multi_select("Suggested tags", &items, &defaults)?
+         .into_iter()
+         .map(|i| suggestions[i].tag.clone())
+         .collect()
+   };
+   let known_tags = known_values(&|i| {
+      i.issue
+         .metadata
+         .tags
+         .iter()
+         .map(|t| t.to_string())
+         .collect()
+   });
+   let tags_input =
+      wizard::prompt_completing("Tags (comma-separated, Tab completes, optional)", &known_tags)?;
+   for tag in tags_input.split(',').map(|s| s.trim().to_string()) {
+      if !tag.is_empty() && !tags.contains(&tag) {
+         tags.push(tag);
+      }
+   }
+
+   // Dependencies (optional), picked from the open issues
+   wizard::section("🔗 Dependencies");
+   let open: Vec<&IssueWithId> = known
+      .iter()
+      .filter(|i| i.issue.metadata.status != Status::Closed)
+      .collect();
+   let mut depends_on: Vec<u32> = Vec::new();
+   if !open.is_empty() && wizard::prompt_confirm("Does it depend on other issues?", false)? {
+      let config = commands.config();
+      let rows: Vec<String> = open
+         .iter()
+         .map(|i| format!("{}  {}", config.format_issue_ref(i.id), i.issue.metadata.title))
+         .collect();
+      while let Some(idx) = wizard::prompt_fuzzy_select("Depends on (Esc to finish)", &rows)? {
+         if !depends_on.contains(&open[idx].id) {
+            depends_on.push(open[idx].id);
+            wizard::success(&format!("Added: {}", rows[idx]));
+         }
+      }
+   }
+
+   // Milestone and assignee (optional)
+   wizard::section("🎯 Planning");
+   let milestones = known_values(&|i| {
+      i.issue
+         .metadata
+         .milestone
+         .iter()
+         .map(|m| m.to_string())
+         .collect()
+   });
+   let milestone = wizard::prompt_completing("Milestone (Tab completes, optional)", &milestones)?;
+   let milestone = Some(milestone.trim().to_string()).filter(|m| !m.is_empty());
+   let agents = known_values(&|i| {
+      i.issue
+         .metadata
+         .claim
+         .iter()
+         .map(|c| c.agent.to_string())
+         .chain(i.issue.metadata.assignee.iter().map(|a| a.to_string()))
+         .chain(i.issue.metadata.watchers.iter().map(|w| w.to_string()))
+         .collect()
+   });
+   let assignee = wizard::prompt_completing("Assignee (Tab completes, optional)", &agents)?;
+   let assignee = Some(assignee.trim().to_string()).filter(|a| !a.is_empty());
+
    // Context (optional)
    let add_context = wizard::prompt_confirm("Add additional context?", false)?;
    let context = if add_context {
@@ -117,15 +257,28 @@ pub fn new_issue_wizard(storage: &Storage, json: bool) -> Result<()> {
    // Preview
    wizard::section("✨ Preview");
    let preview = format!(
-      "Title: {}\nPriority: {}\nEffort: {}\nTags: {}\nFiles: {}\nDescription: {}",
+      "Title: {}\nPriority: {}\nKind: {}\nEffort: {}\nTags: {}\nDepends on: {}\nMilestone: \
+       {}\nAssignee: {}\nFiles: {}\nDescription: {}",
       title,
       priority,
+      kind.as_deref().unwrap_or("None"),
       effort.as_deref().unwrap_or("Not specified"),
       if tags.is_empty() {
          "None".to_string()
       } else {
          tags.join(", ")
       },
+      if depends_on.is_empty() {
+         "None".to_string()
+      } else {
+         depends_on
+            .iter()
+            .map(|&n| commands.config().format_issue_ref(n))
+            .collect::<Vec<_>>()
+            .join(", ")
+      },
+      milestone.as_deref().unwrap_or("None"),
+      assignee.as_deref().unwrap_or("None"),
       if files.is_empty() {
          "None".to_string()
       } else {
@@ -142,17 +295,151 @@ pub fn new_issue_wizard(storage: &Storage, json: bool) -> Result<()> {
    // Confirmation
    if !wizard::prompt_confirm("Create this issue?", true)? {
       wizard::info("Cancelled");
-      return Ok(());
+      return Ok(None);
    }
 
    // Create the issue
-   let commands = Commands::new(storage.clone());
-   commands.create_issue(
-      title, priority, tags, files, issue, impact, acceptance, effort, context, json,
+   let created = commands.create_issue(
+      title, priority, tags, files, issue, impact, acceptance, effort, context, milestone, kind,
+      json,
    )?;
+   let deps = depends_on.iter().map(u32::to_string).collect();
+   commands.link_new_issue(created.bug_num, deps, assignee.as_deref())?;
 
    wizard::success("Issue created successfully!");
-   Ok(())
+   Ok(Some(created.bug_num))
+}
+
+/// Wizard confirming an issue drafted from a pasted report (`new
+/// --from-stdin`); returns the new issue's number, `None` if cancelled
+///
+/// Stdin holds the report, so everything is asked on the terminal line by
+/// line rather than in an editor.
+pub fn draft_issue_wizard(storage: &Storage, draft: Draft, json: bool) -> Result<Option<u32>> {
+   wizard::section("🧾 New Issue from Report");
+   let lines: Vec<&str> = draft.issue.lines().collect();
+   let mut shown = lines[..lines.len().min(20)].join("\n");
+   if lines.len() > 20 {
+      shown.push_str(&format!("\n... ({} more lines)", lines.len() - 20));
+   }
+   wizard::display_preview("Issue", &shown);
+
+   let title = wizard::prompt_optional("Title", Some(&draft.title))?;
+   let title = if title.trim().is_empty() {
+      draft.title.clone()
+   } else {
+      title.trim().to_string()
+   };
+   let priority = prompt_priority()?;
+   let impact = wizard::prompt_optional("Impact", None)?;
+   let impact = Some(impact.trim().to_string())
+      .filter(|s| !s.is_empty())
+      .unwrap_or_else(|| "No impact description provided".to_string());
+   let acceptance = wizard::prompt_optional("Acceptance criteria", None)?;
+   let acceptance = Some(acceptance.trim().to_string())
+      .filter(|s| !s.is_empty())
+      .unwrap_or_else(|| "No acceptance criteria provided".to_string());
+
+   let files: Vec<String> = if draft.files.is_empty() {
+      Vec::new()
+   } else {
+      let keep = vec![true; draft.files.len()];
+      wizard::prompt_multi_select("Files from the report", &draft.files, &keep)?
+         .into_iter()
+         .map(|i| draft.files[i].clone())
+         .collect()
+   };
+   let effort = prompt_effort()?;
+
+   let commands = Commands::new(storage.clone());
+   let tags: Vec<String> = commands
+      .tracker()
+      .suggest_tags_data(&title, &files, &[] as &[&str])?
+      .into_iter()
+      .filter(|s| s.confidence >= 0.5)
+      .map(|s| s.tag)
+      .collect();
+
+   wizard::section("✨ Preview");
+   let none = |list: &[String]| {
+      if list.is_empty() {
+         "None".to_string()
+      } else {
+         list.join(", ")
+      }
+   };
+   let preview = format!(
+      "Title: {title}\nPriority: {priority}\nEffort: {}\nTags: {}\nFiles: {}",
+      effort.as_deref().unwrap_or("Not specified"),
+      none(&tags),
+      none(&files)
+   );
+   wizard::display_preview("New Issue", &preview);
+   if !wizard::prompt_confirm("Create this issue?", true)? {
+      wizard::info("Cancelled");
+      return Ok(None);
+   }
+
+   let created = commands.create_issue(
+      title,
+      priority,
+      tags,
+      files,
+      draft.issue,
+      impact,
+      acceptance,
+      effort,
+      None,
+      None,
+      None,
+      json,
+   )?;
+   wizard::success("Issue created successfully!");
+   Ok(Some(created.bug_num))
+}
+
+fn prompt_priority() -> Result<&'static str> {
+   let priorities = [
+      "Critical - Production outage",
+      "High - Major feature blocked",
+      "Medium - Standard priority",
+      "Low - Nice to have",
+   ];
+   Ok(match wizard::prompt_select("Priority", &priorities)? {
+      0 => "critical",
+      1 => "high",
+      3 => "low",
+      _ => "medium",
+   })
+}
+
+/// One of the configured kinds, `None` if skipped
+fn prompt_kind(config: &Config) -> Result<Option<String>> {
+   let mut items: Vec<&str> = config.kinds.iter().map(|k| k.name.as_str()).collect();
+   items.push("None");
+   let picked = wizard::prompt_select("Kind", &items)?;
+   Ok(config.kinds.get(picked).map(|k| k.name.clone()))
+}
+
+/// T-shirt size, `None` if skipped
+fn prompt_effort() -> Result<Option<String>> {
+   let effort_options = [
+      "XS - Very small (< 1h)",
+      "S - Small (1-2h)",
+      "M - Medium (2-4h)",
+      "L - Large (1 day)",
+      "XL - Very large (2+ days)",
+      "Skip",
+   ];
+   let effort = match wizard::prompt_select("T-shirt size", &effort_options)? {
+      0 => "XS",
+      1 => "S",
+      2 => "M",
+      3 => "L",
+      4 => "XL",
+      _ => return Ok(None),
+   };
+   Ok(Some(effort.to_string()))
 }
 
 /// Interactive wizard for importing issues
@@ -179,7 +466,7 @@ pub fn import_wizard(storage: &Storage, json: bool) -> Result<()> {
    }
 
    let commands = Commands::new(storage.clone());
-   commands.import(Some(file), json)?;
+   commands.import(Some(file), false, json)?;
 
    wizard::success("Issues imported successfully!");
    Ok(())
@@ -255,61 +542,128 @@ pub fn depend_wizard(storage: &Storage, bug_ref: Option<String>, json: bool) ->
 }
 
 /// Interactive wizard for adding checkpoint
+///
+/// Without a reference it offers the in-progress issues, most recently
+/// updated first. Blocking or finishing the issue is picked explicitly
+/// alongside the note.
 pub fn checkpoint_wizard(storage: &Storage, bug_ref: Option<String>, json: bool) -> Result<()> {
    wizard::section("📍 Add Checkpoint");
+   let commands = Commands::new(storage.clone());
 
    // Get bug reference
-   let bug_ref = if let Some(ref_id) = bug_ref {
-      ref_id
-   } else {
-      wizard::prompt_required("Issue reference", validators::validate_issue_ref)?
+   let bug_ref = match bug_ref {
+      Some(ref_id) => ref_id,
+      None => match pick_in_progress(&commands)? {
+         Some(bug_num) => bug_num.to_string(),
+         None => wizard::prompt_required("Issue reference", validators::validate_issue_ref)?,
+      },
    };
 
-   // Message templates
-   wizard::info("Quick templates (or write custom message):");
-   let templates = vec![
+   // What the checkpoint does to the issue's status
+   let actions = ["Add a note", "Mark blocked", "Mark done"];
+   let action = wizard::prompt_select("Checkpoint", &actions)?;
+
+   // Message templates, or a note written in the editor
+   let templates = [
       "Started investigation",
       "Found root cause",
       "Implemented fix",
       "Testing in progress",
       "Ready for review",
-      "Custom message",
+      "Write a note in the editor",
    ];
-
-   let template_idx = wizard::prompt_select("Select template or custom", &templates)?;
-   let message = if template_idx < 5 {
+   let template_idx = wizard::prompt_select("Select template or write a note", &templates)?;
+   let message = if template_idx < templates.len() - 1 {
       templates[template_idx].to_string()
    } else {
-      wizard::prompt_required("Checkpoint message", validators::validate_non_empty)?
+      wizard::prompt_editor("📝 Checkpoint Note", None)?
+         .map(|note| note.trim().to_string())
+         .filter(|note| !note.is_empty())
+         .ok_or_else(|| anyhow::anyhow!("The checkpoint note is empty"))?
+   };
+
+   let mark = match action {
+      1 => Some(CheckpointMark::Blocked(wizard::prompt_required(
+         "What is it blocked on?",
+         validators::validate_non_empty,
+      )?)),
+      2 => Some(CheckpointMark::Done),
+      _ => None,
    };
 
    // Preview
-   wizard::display_preview("Checkpoint", &format!("{}: {}", bug_ref, message));
+   let status = match &mark {
+      Some(CheckpointMark::Blocked(reason)) => format!("\nStatus: blocked ({reason})"),
+      Some(CheckpointMark::Done) => "\nStatus: done".to_string(),
+      None => String::new(),
+   };
+   wizard::display_preview("Checkpoint", &format!("{bug_ref}: {message}{status}"));
 
    if !wizard::prompt_confirm("Add this checkpoint?", true)? {
       wizard::info("Cancelled");
       return Ok(());
    }
 
-   let commands = Commands::new(storage.clone());
-   commands.checkpoint(&bug_ref, message, json)?;
+   commands.checkpoint_marked(&bug_ref, message, mark, false, json)?;
 
    wizard::success("Checkpoint added!");
    Ok(())
 }
 
+/// Fuzzy picker over the in-progress issues, most recently updated first;
+/// `None` if there are none or the user cancelled
+fn pick_in_progress(commands: &Commands) -> Result<Option<u32>> {
+   let mut active: Vec<IssueWithId> = commands
+      .tracker()
+      .list_data("open", None)?
+      .issues
+      .into_iter()
+      .filter(|i| i.issue.metadata.status == Status::InProgress)
+      .collect();
+   if active.is_empty() {
+      wizard::info("No issues in progress");
+      return Ok(None);
+   }
+   active.sort_by_key(|i| {
+      std::cmp::Reverse(
+         i.issue
+            .metadata
+            .last_updated
+            .unwrap_or(i.issue.metadata.created),
+      )
+   });
+   let config = commands.config();
+   let rows: Vec<String> = active
+      .iter()
+      .map(|i| {
+         let updated = i
+            .issue
+            .metadata
+            .last_updated
+            .unwrap_or(i.issue.metadata.created);
+         format!(
+            "{:<6}  {}  {}",
+            config.format_issue_ref(i.id),
+            updated.format("%Y-%m-%d %H:%M"),
+            i.issue.metadata.title
+         )
+      })
+      .collect();
+   Ok(wizard::prompt_fuzzy_select("Issue to checkpoint", &rows)?.map(|idx| active[idx].id))
+}
+
 /// Interactive wizard for init command
 pub fn init_wizard() -> Result<()> {
    wizard::section("⚙️ Initialize Configuration");
 
    let location_options =
-      vec!["Current directory (.agentxrc.yaml)", "Home directory (~/.agentxrc.yaml)"];
+      vec!["Current directory (.agentxrc.yaml)", "User level (~/.config/agentx/config.yaml)"];
    let location_idx = wizard::prompt_select("Configuration location", &location_options)?;
    let global = location_idx == 1;
 
    // Issue directory
    let default_dir = if global {
-      "~/.agentx/issues".to_string()
+      crate::config::global_store_dir()?.display().to_string()
    } else {
       "./.agentx/issues".to_string()
    };
@@ -344,7 +698,7 @@ pub fn init_wizard() -> Result<()> {
    let preview = format!(
       "Location: {}\nIssues directory: {}\nGit integration: {}\nID format: {}-###",
       if global {
-         "Global (~/.agentxrc.yaml)"
+         "User level (~/.config/agentx/config.yaml)"
       } else {
          "Local (./.agentxrc.yaml)"
       },
@@ -373,9 +727,12 @@ pub fn init_wizard() -> Result<()> {
    };
 
    let config_path = if global {
-      dirs::home_dir()
-         .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
-         .join(".agentxrc.yaml")
+      let path = crate::config::user_config_path()
+         .ok_or_else(|| anyhow::anyhow!("Could not determine the config directory"))?;
+      if let Some(dir) = path.parent() {
+         std::fs::create_dir_all(dir)?;
+      }
+      path
    } else {
       std::env::current_dir()?.join(".agentxrc.yaml")
    };
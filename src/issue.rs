@@ -1,9 +1,11 @@
-use std::fmt;
+use std::{collections::BTreeMap, fmt};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use smol_str::SmolStr;
 
+use crate::body::{IssueBody, SectionKind};
+
 mod datetime_rfc3339 {
    use chrono::{DateTime, SecondsFormat, Utc};
    use serde::{Deserialize, Deserializer, Serializer};
@@ -54,33 +56,71 @@ mod datetime_rfc3339_option {
    }
 }
 
+/// Where an issue is in its life; written as [`Status::as_query_str`]
+/// everywhere (files, JSON, filters), read with [`Status::parse`]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Status {
-   #[serde(rename = "open")]
+   #[serde(rename = "open", alias = "not_started")]
    NotStarted,
-   #[serde(rename = "active")]
+   #[serde(rename = "active", alias = "in_progress")]
    InProgress,
    Blocked,
    Done,
    Closed,
+   #[serde(alias = "deferred")]
    Backlog,
 }
 
 impl fmt::Display for Status {
    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-      match self {
-         Self::NotStarted => write!(f, "open"),
-         Self::InProgress => write!(f, "active"),
-         Self::Blocked => write!(f, "blocked"),
-         Self::Done => write!(f, "done"),
-         Self::Closed => write!(f, "closed"),
-         Self::Backlog => write!(f, "backlog"),
-      }
+      f.write_str(self.as_query_str())
+   }
+}
+
+impl std::str::FromStr for Status {
+   type Err = anyhow::Error;
+
+   fn from_str(s: &str) -> anyhow::Result<Self> {
+      Self::parse(s)
    }
 }
 
 impl Status {
+   pub const ALL: [Self; 6] =
+      [Self::NotStarted, Self::InProgress, Self::Blocked, Self::Done, Self::Closed, Self::Backlog];
+
+   /// The canonical name, as stored and printed and as filters take it
+   pub fn as_query_str(&self) -> &'static str {
+      match self {
+         Self::NotStarted => "open",
+         Self::InProgress => "active",
+         Self::Blocked => "blocked",
+         Self::Done => "done",
+         Self::Closed => "closed",
+         Self::Backlog => "backlog",
+      }
+   }
+
+   /// A status by its canonical name or a synonym, in any case and with
+   /// `_`, `-` or a space between words: `not_started`, `todo` and `ready`
+   /// are open; `in_progress`, `started`, `wip` and `doing` are active;
+   /// `deferred` is backlog
+   pub fn parse(s: &str) -> anyhow::Result<Self> {
+      let name = s.trim().to_ascii_lowercase().replace(['-', ' '], "_");
+      Ok(match name.as_str() {
+         "open" | "not_started" | "todo" | "ready" => Self::NotStarted,
+         "active" | "in_progress" | "started" | "wip" | "doing" => Self::InProgress,
+         "blocked" => Self::Blocked,
+         "done" => Self::Done,
+         "closed" => Self::Closed,
+         "backlog" | "deferred" => Self::Backlog,
+         _ => {
+            anyhow::bail!("Unknown status: {s}. Use: open, active, blocked, done, closed, backlog")
+         },
+      })
+   }
+
    pub fn marker(&self) -> &'static str {
       match self {
          Self::NotStarted => "⭕",
@@ -124,13 +164,48 @@ impl Priority {
    }
 }
 
+/// A recorded status change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transition {
+   pub from: Status,
+   pub to:   Status,
+   #[serde(with = "datetime_rfc3339")]
+   pub at:   DateTime<Utc>,
+}
+
+/// An agent's exclusive hold on an issue, which lapses at `until` unless
+/// renewed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Claim {
+   pub agent: SmolStr,
+   #[serde(with = "datetime_rfc3339")]
+   pub since: DateTime<Utc>,
+   #[serde(with = "datetime_rfc3339")]
+   pub until: DateTime<Utc>,
+}
+
+impl Claim {
+   pub fn is_live(&self, now: DateTime<Utc>) -> bool {
+      self.until > now
+   }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IssueMetadata {
    pub title:          SmolStr,
+   /// Translations of the title by language, e.g. `de`; `--lang` shows one
+   #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+   pub title_i18n:     BTreeMap<String, SmolStr>,
    pub priority:       Priority,
+   /// What sort of work this is, one of the configured `kinds`
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub kind:           Option<SmolStr>,
    pub status:         Status,
    #[serde(with = "datetime_rfc3339")]
    pub created:        DateTime<Utc>,
+   /// Set by storage on every save
+   #[serde(skip_serializing_if = "Option::is_none", with = "datetime_rfc3339_option", default)]
+   pub last_updated:   Option<DateTime<Utc>>,
    #[serde(skip_serializing_if = "Vec::is_empty", default)]
    pub tags:           Vec<SmolStr>,
    pub files:          Vec<SmolStr>,
@@ -148,12 +223,66 @@ pub struct IssueMetadata {
    pub depends_on:     Vec<u32>,
    #[serde(skip_serializing_if = "Vec::is_empty", default)]
    pub blocks:         Vec<u32>,
+   /// Issues the body mentions (`#12`, `ISSUE-12`); set by storage on
+   /// every save
+   #[serde(skip_serializing_if = "Vec::is_empty", default)]
+   pub references:     Vec<u32>,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub milestone:      Option<SmolStr>,
+   /// Release the issue is planned for, for `agentx release`
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub target_version: Option<SmolStr>,
+   /// When the issue should be done, for `agentx notify`
+   #[serde(skip_serializing_if = "Option::is_none", with = "datetime_rfc3339_option", default)]
+   pub due:            Option<DateTime<Utc>>,
+   #[serde(skip_serializing_if = "Vec::is_empty", default)]
+   pub transitions:    Vec<Transition>,
+   /// Users notified when the status changes or a checkpoint is added
+   #[serde(skip_serializing_if = "Vec::is_empty", default)]
+   pub watchers:       Vec<SmolStr>,
+   /// Who the issue is assigned to; unlike a claim it does not expire
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub assignee:       Option<SmolStr>,
+   /// Lease of the agent working on the issue; ignored once expired
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub claim:          Option<Claim>,
+   /// Where the issue was imported from, for `agentx import url`
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub source_url:     Option<SmolStr>,
+   /// Key of the issue in an imported spec; importing it again updates
+   /// this issue
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub external_id:    Option<SmolStr>,
+}
+
+impl IssueMetadata {
+   /// Change the status, recording the transition if it actually changed
+   pub fn set_status(&mut self, status: Status) {
+      if self.status != status {
+         self
+            .transitions
+            .push(Transition { from: self.status, to: status, at: Utc::now() });
+         self.status = status;
+      }
+   }
+
+   /// When the issue last changed; for issues saved before `last_updated`
+   /// was tracked, the latest status change or creation
+   pub fn updated_at(&self) -> DateTime<Utc> {
+      self.last_updated.unwrap_or_else(|| {
+         self
+            .transitions
+            .iter()
+            .map(|t| t.at)
+            .fold(self.created, DateTime::max)
+      })
+   }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Issue {
    pub metadata: IssueMetadata,
-   pub body:     String,
+   pub body:     IssueBody,
 }
 
 /// Issue with its ID (extracted from filename)
@@ -163,6 +292,15 @@ pub struct IssueWithId {
    pub issue: Issue,
 }
 
+impl IssueWithId {
+   /// Where the issue goes among others that nothing else sets apart:
+   /// higher priority first, then older, then lower number
+   pub fn stable_key(&self) -> (u8, DateTime<Utc>, u32) {
+      let meta = &self.issue.metadata;
+      (meta.priority.sort_key(), meta.created, self.id)
+   }
+}
+
 impl Issue {
    #[allow(clippy::too_many_arguments)]
    pub fn new(
@@ -178,9 +316,12 @@ impl Issue {
    ) -> Self {
       let metadata = IssueMetadata {
          title: title.clone().into(),
+         title_i18n: BTreeMap::new(),
          priority,
+         kind: None,
          status: Status::NotStarted,
          created: Utc::now(),
+         last_updated: None,
          tags: tags.into_iter().map(|s| s.into()).collect(),
          files: files.into_iter().map(|s| s.into()).collect(),
          effort: effort.map(|s| s.into()),
@@ -190,24 +331,211 @@ impl Issue {
          closed: None,
          depends_on: Vec::new(),
          blocks: Vec::new(),
+         references: Vec::new(),
+         milestone: None,
+         target_version: None,
+         due: None,
+         transitions: Vec::new(),
+         watchers: Vec::new(),
+         assignee: None,
+         claim: None,
+         source_url: None,
+         external_id: None,
       };
 
-      let mut body = String::new();
-      if !issue.is_empty() {
-         body.push_str(&format!("**Issue**: {issue}\n\n"));
-      }
-      if !impact.is_empty() {
-         body.push_str(&format!("**Impact**: {impact}\n\n"));
-      }
-      if !acceptance.is_empty() {
-         body.push_str(&format!("**Acceptance**: {acceptance}\n\n"));
+      let mut body = IssueBody::default();
+      for (kind, text) in [
+         (SectionKind::Issue, issue),
+         (SectionKind::Impact, impact),
+         (SectionKind::Acceptance, acceptance),
+      ] {
+         if !text.is_empty() {
+            body.set(kind, &text);
+         }
       }
 
       Self { metadata, body }
    }
 
+   /// Latest status change or checkpoint, or creation if there was neither
+   pub fn last_activity(&self) -> DateTime<Utc> {
+      self
+         .metadata
+         .transitions
+         .iter()
+         .map(|t| t.at)
+         .chain(self.body.checkpoints().filter_map(|c| c.timestamp()))
+         .fold(self.metadata.created, DateTime::max)
+   }
+
+   /// The issue as read in `lang`, with its translated title and sections
+   /// where it has them; for display only, since saving it would lose the
+   /// translations
+   pub fn localized(&self, lang: &str) -> Self {
+      let mut metadata = self.metadata.clone();
+      if let Some(title) = metadata.title_i18n.get(lang) {
+         metadata.title = title.clone();
+      }
+      Self { metadata, body: self.body.localized(lang) }
+   }
+
    pub fn to_mdx(&self) -> String {
       let yaml = serde_yaml::to_string(&self.metadata).unwrap_or_default();
       format!("---\n{yaml}---\n\n{}", self.body)
    }
 }
+
+/// Issues for unit tests, built from only the fields a test cares about
+#[cfg(test)]
+pub mod test_support {
+   use super::*;
+
+   /// "Issue {id}": medium priority, not started, no text, until set otherwise
+   pub fn issue(id: u32) -> IssueBuilder {
+      let issue = Issue::new(
+         format!("Issue {id}"),
+         Priority::Medium,
+         vec![],
+         vec![],
+         String::new(),
+         String::new(),
+         String::new(),
+         None,
+         None,
+      );
+      IssueBuilder(IssueWithId { id, issue })
+   }
+
+   pub struct IssueBuilder(IssueWithId);
+
+   impl IssueBuilder {
+      fn meta(mut self, set: impl FnOnce(&mut IssueMetadata)) -> Self {
+         set(&mut self.0.issue.metadata);
+         self
+      }
+
+      fn section(mut self, kind: SectionKind, text: &str) -> Self {
+         if !text.is_empty() {
+            self.0.issue.body.set(kind, text);
+         }
+         self
+      }
+
+      pub fn title(self, title: &str) -> Self {
+         self.meta(|m| m.title = title.into())
+      }
+
+      pub fn priority(self, priority: Priority) -> Self {
+         self.meta(|m| m.priority = priority)
+      }
+
+      pub fn status(self, status: Status) -> Self {
+         self.meta(|m| m.status = status)
+      }
+
+      pub fn tags(self, tags: &[&str]) -> Self {
+         self.meta(|m| m.tags = tags.iter().map(|t| (*t).into()).collect())
+      }
+
+      pub fn files(self, files: &[&str]) -> Self {
+         self.meta(|m| m.files = files.iter().map(|f| (*f).into()).collect())
+      }
+
+      pub fn effort<'e>(self, effort: impl Into<Option<&'e str>>) -> Self {
+         self.meta(|m| m.effort = effort.into().map(Into::into))
+      }
+
+      /// Move to `status` at `at`, recording the transition
+      pub fn moved(self, status: Status, at: DateTime<Utc>) -> Self {
+         self.meta(|m| {
+            m.transitions
+               .push(Transition { from: m.status, to: status, at });
+            m.status = status;
+         })
+      }
+
+      pub fn blocked_reason(self, reason: &str) -> Self {
+         self.meta(|m| m.blocked_reason = Some(reason.into()))
+      }
+
+      pub fn depends_on(self, ids: &[u32]) -> Self {
+         self.meta(|m| m.depends_on = ids.to_vec())
+      }
+
+      pub fn created(self, at: DateTime<Utc>) -> Self {
+         self.meta(|m| m.created = at)
+      }
+
+      pub fn closed(self, at: impl Into<Option<DateTime<Utc>>>) -> Self {
+         self.meta(|m| m.closed = at.into())
+      }
+
+      pub fn target_version(self, version: &str) -> Self {
+         self.meta(|m| m.target_version = Some(version.into()))
+      }
+
+      /// The `Issue` section
+      pub fn body(self, text: &str) -> Self {
+         self.section(SectionKind::Issue, text)
+      }
+
+      pub fn impact(self, text: &str) -> Self {
+         self.section(SectionKind::Impact, text)
+      }
+
+      pub fn acceptance(self, text: &str) -> Self {
+         self.section(SectionKind::Acceptance, text)
+      }
+
+      pub fn build(self) -> IssueWithId {
+         self.0
+      }
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn test_status_vocabulary() {
+      for status in Status::ALL {
+         assert_eq!(Status::parse(status.as_query_str()).unwrap(), status);
+         assert_eq!(status.to_string(), status.as_query_str());
+      }
+      assert_eq!(Status::parse("In-Progress").unwrap(), Status::InProgress);
+      assert_eq!(Status::parse("not started").unwrap(), Status::NotStarted);
+      assert_eq!("deferred".parse::<Status>().unwrap(), Status::Backlog);
+      assert_eq!(
+         Status::parse("later").unwrap_err().to_string(),
+         "Unknown status: later. Use: open, active, blocked, done, closed, backlog"
+      );
+
+      // Files written with a synonym still load, and are written back canonical
+      let read: Status = serde_yaml::from_str("in_progress").unwrap();
+      assert_eq!(read, Status::InProgress);
+      assert_eq!(serde_json::to_string(&read).unwrap(), "\"active\"");
+   }
+
+   #[test]
+   fn test_status_round_trips_through_a_saved_file() {
+      let storage = crate::storage::Storage::in_memory();
+      for status in Status::ALL {
+         let mdx = test_support::issue(1).status(status).build().issue.to_mdx();
+         assert!(mdx.contains(&format!("\nstatus: {}\n", status.as_query_str())), "{mdx}");
+         assert_eq!(storage.parse_issue(&mdx).unwrap().metadata.status, status);
+      }
+
+      // Files from before the rename, with the old names, still load
+      for (alias, status) in [
+         ("not_started", Status::NotStarted),
+         ("in_progress", Status::InProgress),
+         ("deferred", Status::Backlog),
+      ] {
+         let mdx = test_support::issue(1).status(status).build().issue.to_mdx();
+         let old =
+            mdx.replace(&format!("status: {}", status.as_query_str()), &format!("status: {alias}"));
+         assert_eq!(storage.parse_issue(&old).unwrap().metadata.status, status);
+      }
+   }
+}
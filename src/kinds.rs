@@ -0,0 +1,85 @@
+//! Issue kinds
+//!
+//! An issue's `kind` says what sort of work it is: a bug, a feature, a chore
+//! or a spike by default, or the kinds listed under `kinds` in the config.
+//! Each kind can bring a template for the sections of a new issue, and the
+//! changelog heading its issues go under when a release is cut, instead of
+//! guessing both from tags.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KindConfig {
+   pub name:      String,
+   /// Changelog heading for closed issues of this kind, e.g. `Fixed`; tags
+   /// decide when unset
+   #[serde(default, skip_serializing_if = "Option::is_none")]
+   pub changelog: Option<String>,
+   /// Starting text for the sections of a new issue
+   #[serde(default)]
+   pub template:  KindTemplate,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KindTemplate {
+   #[serde(default, skip_serializing_if = "Option::is_none")]
+   pub issue:      Option<String>,
+   #[serde(default, skip_serializing_if = "Option::is_none")]
+   pub impact:     Option<String>,
+   #[serde(default, skip_serializing_if = "Option::is_none")]
+   pub acceptance: Option<String>,
+}
+
+pub fn default_kinds() -> Vec<KindConfig> {
+   let kind = |name: &str, changelog: Option<&str>, template: KindTemplate| KindConfig {
+      name: name.to_string(),
+      changelog: changelog.map(String::from),
+      template,
+   };
+   vec![
+      kind("bug", Some("Fixed"), KindTemplate {
+         issue:      Some("Steps to reproduce:\n1. \n\nExpected:\n\nActual:".to_string()),
+         impact:     None,
+         acceptance: Some("The steps no longer reproduce it, and a test covers it".to_string()),
+      }),
+      kind("feature", Some("Added"), KindTemplate::default()),
+      kind("chore", Some("Changed"), KindTemplate::default()),
+      kind("spike", None, KindTemplate {
+         issue:      Some("Question to answer:".to_string()),
+         impact:     None,
+         acceptance: Some("A written recommendation, with the options considered".to_string()),
+      }),
+   ]
+}
+
+/// The configured kind named `name`, ignoring case
+pub fn find<'a>(kinds: &'a [KindConfig], name: &str) -> Result<&'a KindConfig> {
+   let name = name.trim();
+   kinds
+      .iter()
+      .find(|k| k.name.eq_ignore_ascii_case(name))
+      .ok_or_else(|| {
+         let known: Vec<&str> = kinds.iter().map(|k| k.name.as_str()).collect();
+         anyhow::anyhow!("Unknown kind '{name}'. Known kinds: {}", known.join(", "))
+      })
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn test_find_kind() {
+      let kinds = default_kinds();
+      assert_eq!(find(&kinds, " Bug").unwrap().changelog.as_deref(), Some("Fixed"));
+      assert!(find(&kinds, "spike").unwrap().template.acceptance.is_some());
+      let err = find(&kinds, "epic").unwrap_err().to_string();
+      assert!(err.contains("bug, feature, chore, spike"), "{err}");
+
+      let custom: Vec<KindConfig> =
+         serde_yaml::from_str("- name: docs\n  changelog: Documentation\n").unwrap();
+      assert!(custom[0].template.issue.is_none());
+      assert!(find(&custom, "bug").is_err());
+   }
+}
@@ -1,13 +1,72 @@
+pub mod alias;
+pub mod api;
+pub mod audit;
+pub mod batch;
+pub mod blockers;
+pub mod board;
+pub mod body;
+pub mod brief;
+pub mod bundle;
+pub mod calendar;
+pub mod calibration;
 pub mod cli;
 pub mod commands;
+pub mod commit_msg;
+pub mod compact;
+pub mod complete;
 pub mod config;
+pub mod crypt;
+pub mod cycles;
+pub mod daemon;
+pub mod digest;
+pub mod export;
+pub mod file_match;
+pub mod files;
+pub mod flow;
+pub mod forecast;
+pub mod form;
+pub mod frontmatter;
 pub mod fuzzy;
+pub mod gate;
 pub mod git;
+pub mod github;
+pub mod graph;
 pub mod guide;
+pub mod history;
+pub mod impact;
+pub mod import;
 pub mod installer;
+pub mod intake;
 pub mod interactive;
 pub mod issue;
+pub mod kinds;
+pub mod lint;
+pub mod listing;
+pub mod markdown;
 pub mod mcp_simple;
+pub mod merge;
+pub mod notify;
+pub mod order;
+pub mod placeholders;
+pub mod plan;
+pub mod pomodoro;
+pub mod prompt;
+pub mod query;
+pub mod references;
+pub mod release;
+pub mod sampling;
+pub mod search;
+pub mod session;
+pub mod similarity;
+pub mod standup;
 pub mod storage;
+pub mod store;
+pub mod tags;
+pub mod trace;
+pub mod transitions;
 pub mod tui;
 pub mod utils;
+pub mod watch;
+#[cfg(feature = "web")]
+pub mod web;
+pub mod workflow;
@@ -0,0 +1,213 @@
+//! Issue linting
+//!
+//! `agentx lint` checks open issues against the rules in the `lint` config
+//! section: acceptance criteria are written down, high-priority issues have
+//! an effort estimate, active issues keep moving, titles say enough, listed
+//! files exist and tags come from the allowed set. It exits non-zero when
+//! anything is found, so it can gate CI.
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+   crypt,
+   issue::{IssueWithId, Priority, Status},
+   utils::strip_line_suffix,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LintConfig {
+   /// Require an `Acceptance` section with some text
+   #[serde(default = "default_true")]
+   pub acceptance:      bool,
+   /// Require an effort estimate on high and critical issues
+   #[serde(default = "default_true")]
+   pub effort_for_high: bool,
+   /// Flag active issues not updated for this many days; 0 turns the rule
+   /// off
+   #[serde(default = "default_stale_days")]
+   pub stale_days:      u64,
+   /// Flag titles with fewer words than this; 0 turns the rule off
+   #[serde(default = "default_min_title_words")]
+   pub min_title_words: usize,
+   /// Require the paths in `files` to exist
+   #[serde(default = "default_true")]
+   pub files_exist:     bool,
+   /// Tags issues may use; empty allows any
+   #[serde(default)]
+   pub allowed_tags:    Vec<String>,
+}
+
+fn default_true() -> bool {
+   true
+}
+
+fn default_stale_days() -> u64 {
+   7
+}
+
+fn default_min_title_words() -> usize {
+   3
+}
+
+impl Default for LintConfig {
+   fn default() -> Self {
+      Self {
+         acceptance:      true,
+         effort_for_high: true,
+         stale_days:      default_stale_days(),
+         min_title_words: default_min_title_words(),
+         files_exist:     true,
+         allowed_tags:    Vec::new(),
+      }
+   }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rule {
+   MissingAcceptance,
+   MissingEffort,
+   StaleInProgress,
+   VagueTitle,
+   MissingFile,
+   UnknownTag,
+}
+
+impl fmt::Display for Rule {
+   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      let name = match self {
+         Self::MissingAcceptance => "missing_acceptance",
+         Self::MissingEffort => "missing_effort",
+         Self::StaleInProgress => "stale_in_progress",
+         Self::VagueTitle => "vague_title",
+         Self::MissingFile => "missing_file",
+         Self::UnknownTag => "unknown_tag",
+      };
+      f.write_str(name)
+   }
+}
+
+/// One rule an issue breaks
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Finding {
+   pub bug_num: u32,
+   pub title:   String,
+   pub rule:    Rule,
+   pub message: String,
+}
+
+/// What the issues in `issues` break, by issue then rule; `file_exists`
+/// tells whether a path listed in `files` is there
+pub fn lint(
+   issues: &[IssueWithId],
+   config: &LintConfig,
+   now: DateTime<Utc>,
+   file_exists: impl Fn(&str) -> bool,
+) -> Vec<Finding> {
+   let mut findings = Vec::new();
+   for issue in issues {
+      let meta = &issue.issue.metadata;
+      let mut found = |rule: Rule, message: String| {
+         findings.push(Finding { bug_num: issue.id, title: meta.title.to_string(), rule, message });
+      };
+
+      // A locked body can't be read, so only the frontmatter is checked
+      let readable = !crypt::is_encrypted(&issue.issue.body.to_string());
+      if config.acceptance
+         && readable
+         && issue
+            .issue
+            .body
+            .acceptance()
+            .is_none_or(|text| text.trim().is_empty())
+      {
+         found(Rule::MissingAcceptance, "no acceptance criteria".to_string());
+      }
+
+      if config.effort_for_high
+         && matches!(meta.priority, Priority::High | Priority::Critical)
+         && meta.effort.as_ref().is_none_or(|e| e.trim().is_empty())
+      {
+         found(
+            Rule::MissingEffort,
+            format!("{} priority without an effort estimate", meta.priority),
+         );
+      }
+
+      if config.stale_days > 0 && meta.status == Status::InProgress {
+         let idle = (now - meta.updated_at()).num_days();
+         if idle >= config.stale_days as i64 {
+            found(Rule::StaleInProgress, format!("active but not updated for {idle} days"));
+         }
+      }
+
+      let words = meta.title.split_whitespace().count();
+      if words < config.min_title_words {
+         found(
+            Rule::VagueTitle,
+            format!("title has {words} word(s); at least {} wanted", config.min_title_words),
+         );
+      }
+
+      if config.files_exist {
+         for file in &meta.files {
+            let path = strip_line_suffix(file);
+            if !path.is_empty() && !file_exists(path) {
+               found(Rule::MissingFile, format!("file `{path}` does not exist"));
+            }
+         }
+      }
+
+      if !config.allowed_tags.is_empty() {
+         for tag in &meta.tags {
+            if !config.allowed_tags.iter().any(|t| t == tag.as_str()) {
+               found(Rule::UnknownTag, format!("tag `{tag}` is not in lint.allowed_tags"));
+            }
+         }
+      }
+   }
+   findings
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::issue::test_support::issue;
+
+   #[test]
+   fn test_lint() {
+      let now = Utc::now();
+      let config = LintConfig { allowed_tags: vec!["auth".into()], ..LintConfig::default() };
+
+      let clean = issue(1)
+         .title("Fix login timeout on mobile")
+         .acceptance("Login works")
+         .build();
+      let mut sloppy = issue(2).title("Login").priority(Priority::High).build();
+      sloppy.issue.metadata.tags = vec!["auth".into(), "misc".into()];
+      sloppy.issue.metadata.files = vec!["src/auth.rs:42".into(), "src/gone.rs:3:1".into()];
+      let mut stale = issue(3)
+         .title("Migrate the sessions table")
+         .priority(Priority::Low)
+         .acceptance("Migrated")
+         .build();
+      stale.issue.metadata.set_status(Status::InProgress);
+      stale.issue.metadata.last_updated = Some(now - chrono::Duration::days(10));
+
+      let findings = lint(&[clean, sloppy, stale], &config, now, |path| path == "src/auth.rs");
+      let rules: Vec<(u32, Rule)> = findings.iter().map(|f| (f.bug_num, f.rule)).collect();
+      assert_eq!(rules, [
+         (2, Rule::MissingAcceptance),
+         (2, Rule::MissingEffort),
+         (2, Rule::VagueTitle),
+         (2, Rule::MissingFile),
+         (2, Rule::UnknownTag),
+         (3, Rule::StaleInProgress),
+      ]);
+      assert_eq!(findings[3].message, "file `src/gone.rs` does not exist");
+      assert_eq!(findings[5].message, "active but not updated for 10 days");
+   }
+}
@@ -0,0 +1,281 @@
+//! List grouping and sorting
+//!
+//! `agentx list` groups by priority unless `--group-by` picks status, tag,
+//! milestone, assignee (or, for unassigned issues, the agent holding a live
+//! claim) or no grouping at
+//! all, and keeps issues in the usual order (priority, then age, then
+//! number) unless `--sort` picks a key, which that order breaks ties for. An
+//! issue with several tags is listed under each of them. Issues without a
+//! value for the key, such as unestimated ones when sorting by effort, go
+//! last whichever way the sort runs.
+
+use std::{cmp::Ordering, collections::BTreeMap};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+use crate::{
+   issue::{IssueWithId, Priority, Status},
+   utils::parse_effort,
+};
+
+/// Group name for issues without a tag, milestone or assignee
+const NO_VALUE: &str = "none";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GroupBy {
+   #[default]
+   Priority,
+   Status,
+   Tag,
+   Milestone,
+   Assignee,
+   None,
+}
+
+impl GroupBy {
+   pub fn name(&self) -> &'static str {
+      match self {
+         Self::Priority => "priority",
+         Self::Status => "status",
+         Self::Tag => "tag",
+         Self::Milestone => "milestone",
+         Self::Assignee => "assignee",
+         Self::None => "none",
+      }
+   }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+   Created,
+   /// Latest change of any kind (`last_updated`)
+   Updated,
+   Effort,
+   Priority,
+}
+
+/// How `list` arranges the issues it shows
+#[derive(Debug, Clone, Default)]
+pub struct ListView {
+   /// `None` groups by priority, except that JSON stays a flat array
+   pub group_by:   Option<GroupBy>,
+   /// `None` keeps the usual order, see [`IssueWithId::stable_key`]
+   pub sort:       Option<SortKey>,
+   pub descending: bool,
+   /// Language to show titles and sections in where translated
+   pub lang:       Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Group<'a> {
+   pub name:   String,
+   pub issues: Vec<&'a IssueWithId>,
+}
+
+/// Parse `--group-by`
+pub fn parse_group_by(s: &str) -> Result<GroupBy> {
+   Ok(match s.to_ascii_lowercase().as_str() {
+      "priority" => GroupBy::Priority,
+      "status" => GroupBy::Status,
+      "tag" | "tags" => GroupBy::Tag,
+      "milestone" => GroupBy::Milestone,
+      "assignee" | "agent" => GroupBy::Assignee,
+      "none" => GroupBy::None,
+      _ => anyhow::bail!(
+         "Unknown grouping: {s}. Use: priority, status, tag, milestone, assignee, none"
+      ),
+   })
+}
+
+/// Parse `--sort`
+pub fn parse_sort(s: &str) -> Result<SortKey> {
+   Ok(match s.to_ascii_lowercase().as_str() {
+      "created" => SortKey::Created,
+      "updated" => SortKey::Updated,
+      "effort" => SortKey::Effort,
+      "priority" => SortKey::Priority,
+      _ => anyhow::bail!("Unknown sort: {s}. Use: created, updated, effort, priority"),
+   })
+}
+
+/// Sort `issues` by `key`, the usual order breaking ties
+pub fn sort(issues: &mut [IssueWithId], key: SortKey, descending: bool) {
+   let value = |i: &IssueWithId| -> Option<i64> {
+      let meta = &i.issue.metadata;
+      match key {
+         SortKey::Created => Some(meta.created.timestamp()),
+         SortKey::Updated => Some(meta.updated_at().timestamp()),
+         SortKey::Effort => meta
+            .effort
+            .as_ref()
+            .and_then(|e| parse_effort(e).ok())
+            .map(i64::from),
+         SortKey::Priority => Some(i64::from(meta.priority.sort_key())),
+      }
+   };
+   issues.sort_by(|a, b| {
+      let order = match (value(a), value(b)) {
+         (Some(x), Some(y)) if descending => y.cmp(&x),
+         (Some(x), Some(y)) => x.cmp(&y),
+         (Some(_), None) => Ordering::Less,
+         (None, Some(_)) => Ordering::Greater,
+         (None, None) => Ordering::Equal,
+      };
+      order.then_with(|| a.stable_key().cmp(&b.stable_key()))
+   });
+}
+
+/// Who `issue` is assigned to, or else the agent holding a live claim on it
+pub fn assignee(issue: &IssueWithId, now: DateTime<Utc>) -> Option<&str> {
+   let meta = &issue.issue.metadata;
+   meta.assignee.as_deref().or_else(|| {
+      meta
+         .claim
+         .as_ref()
+         .filter(|claim| claim.is_live(now))
+         .map(|claim| claim.agent.as_str())
+   })
+}
+
+/// `issues` split into groups, keeping their order within each group; empty
+/// groups are left out
+pub fn group(issues: &[IssueWithId], by: GroupBy, now: DateTime<Utc>) -> Vec<Group<'_>> {
+   let fixed = |names: Vec<String>, of: &dyn Fn(&IssueWithId) -> String| {
+      names
+         .into_iter()
+         .map(|name| Group { issues: issues.iter().filter(|i| of(i) == name).collect(), name })
+         .filter(|g| !g.issues.is_empty())
+         .collect()
+   };
+   match by {
+      GroupBy::None => vec![Group { name: "all".to_string(), issues: issues.iter().collect() }],
+      GroupBy::Priority => fixed(
+         [Priority::Critical, Priority::High, Priority::Medium, Priority::Low]
+            .iter()
+            .map(|p| p.to_string())
+            .collect(),
+         &|i| i.issue.metadata.priority.to_string(),
+      ),
+      GroupBy::Status => fixed(
+         [
+            Status::InProgress,
+            Status::Blocked,
+            Status::NotStarted,
+            Status::Backlog,
+            Status::Done,
+            Status::Closed,
+         ]
+         .iter()
+         .map(|s| s.to_string())
+         .collect(),
+         &|i| i.issue.metadata.status.to_string(),
+      ),
+      GroupBy::Tag => by_values(issues, |i| {
+         i.issue
+            .metadata
+            .tags
+            .iter()
+            .map(|t| t.to_string())
+            .collect()
+      }),
+      GroupBy::Milestone => by_values(issues, |i| {
+         i.issue
+            .metadata
+            .milestone
+            .iter()
+            .map(|m| m.to_string())
+            .collect()
+      }),
+      GroupBy::Assignee => {
+         by_values(issues, |i| assignee(i, now).map(String::from).into_iter().collect())
+      },
+   }
+}
+
+/// Alphabetical groups by the values `of` gives each issue, with the
+/// issues that have none in a last group
+fn by_values(issues: &[IssueWithId], of: impl Fn(&IssueWithId) -> Vec<String>) -> Vec<Group<'_>> {
+   let mut named: BTreeMap<String, Vec<&IssueWithId>> = BTreeMap::new();
+   let mut rest = Vec::new();
+   for issue in issues {
+      let values = of(issue);
+      if values.is_empty() {
+         rest.push(issue);
+      }
+      for value in values {
+         named.entry(value).or_default().push(issue);
+      }
+   }
+   let mut groups: Vec<Group> = named
+      .into_iter()
+      .map(|(name, issues)| Group { name, issues })
+      .collect();
+   if !rest.is_empty() {
+      groups.push(Group { name: NO_VALUE.to_string(), issues: rest });
+   }
+   groups
+}
+
+#[cfg(test)]
+mod tests {
+   use chrono::Duration;
+
+   use super::*;
+   use crate::issue::{Claim, test_support::issue};
+
+   #[test]
+   fn test_sort_and_group() {
+      let now = Utc::now();
+      let mut issues = vec![
+         issue(1)
+            .priority(Priority::Low)
+            .tags(&["ui"])
+            .effort("2h")
+            .build(),
+         issue(2).priority(Priority::High).build(),
+         issue(3)
+            .priority(Priority::High)
+            .tags(&["api", "ui"])
+            .effort("30m")
+            .build(),
+      ];
+      issues[1].issue.metadata.claim =
+         Some(Claim { agent: "alpha".into(), since: now, until: now + Duration::hours(1) });
+
+      let nums = |issues: &[IssueWithId]| issues.iter().map(|i| i.id).collect::<Vec<_>>();
+      sort(&mut issues, SortKey::Effort, false);
+      assert_eq!(nums(&issues), [3, 1, 2]);
+      sort(&mut issues, SortKey::Effort, true);
+      assert_eq!(nums(&issues), [1, 3, 2]);
+      sort(&mut issues, SortKey::Priority, false);
+      assert_eq!(nums(&issues), [2, 3, 1]);
+      // Ties go by priority, then age, then number
+      issues[1].issue.metadata.created = issues[0].issue.metadata.created - Duration::days(1);
+      sort(&mut issues, SortKey::Priority, false);
+      assert_eq!(nums(&issues), [3, 2, 1]);
+      let mut unestimated =
+         vec![issue(5).priority(Priority::Low).build(), issue(4).priority(Priority::Low).build()];
+      unestimated[1].issue.metadata.created = unestimated[0].issue.metadata.created;
+      unestimated.push(issue(6).priority(Priority::High).build());
+      sort(&mut unestimated, SortKey::Effort, true);
+      assert_eq!(nums(&unestimated), [6, 4, 5]);
+
+      let names = |by| {
+         group(&issues, by, now)
+            .into_iter()
+            .map(|g| format!("{}:{}", g.name, g.issues.len()))
+            .collect::<Vec<_>>()
+      };
+      assert_eq!(names(GroupBy::Tag), ["api:1", "ui:2", "none:1"]);
+      assert_eq!(names(GroupBy::Priority), ["high:2", "low:1"]);
+      assert_eq!(names(GroupBy::Assignee), ["alpha:1", "none:2"]);
+      // An assignee outranks whoever holds the claim
+      let mut assigned = issues.iter().find(|i| i.id == 2).unwrap().clone();
+      assigned.issue.metadata.assignee = Some("beta".into());
+      assert_eq!(assignee(&assigned, now), Some("beta"));
+      assert_eq!(names(GroupBy::None), ["all:3"]);
+      assert!(parse_group_by("owner").is_err());
+      assert_eq!(parse_sort("Updated").unwrap(), SortKey::Updated);
+   }
+}
@@ -1,31 +1,252 @@
 use agentx::{
-   cli::{AliasAction, Cli, Command},
+   api::{ContextScope, ListScope, RefMode},
+   audit::Actor,
+   cli::{
+      AliasAction, BundleAction, Cli, Command, CryptAction, FilesAction, ImportAction,
+      ReleaseAction, SessionAction, TagsAction, WorkflowAction,
+   },
    commands::Commands,
-   config::Config,
+   complete,
+   config::{self, Config},
+   daemon, export,
+   form::{CheckpointForm, DependForm, FormFormat, NewForm},
+   graph::{self, GraphFormat},
    guide,
-   interactive::wizards,
-   storage::Storage,
+   interactive::{
+      is_interactive_terminal,
+      wizards::{self, PickFor},
+   },
+   listing::{self, ListView},
+   merge::IssueConflict,
+   prompt,
+   storage::{ReadOnlyError, Storage},
+   trace,
 };
 use anyhow::Result;
-use clap::{CommandFactory, Parser};
+use clap::{CommandFactory, FromArgMatches};
 use clap_complete::{Shell, generate};
+use smol_str::SmolStr;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-   let cli = Cli::try_parse()?;
-   let config = Config::load();
-   let issues_dir = config.resolve_issues_directory();
-   let storage = Storage::new(issues_dir.clone());
-   let commands = Commands::new(storage);
+   let matches = Cli::command().try_get_matches()?;
+   // The audit trail credits changes to the subcommand that made them
+   let via = matches.subcommand_name().map(String::from);
+   let cli = Cli::from_arg_matches(&matches)?;
+   let json = cli.json;
+   let timings = trace::init(cli.trace, cli.timings);
+   let result = run(cli, via).await;
+   if let Some(timings) = &timings {
+      timings.print();
+   }
+   match result {
+      Err(err) if json => match err.downcast_ref::<IssueConflict>() {
+         Some(conflict) => {
+            println!("{}", serde_json::to_string_pretty(&conflict.to_json())?);
+            std::process::exit(1);
+         },
+         None => Err(err),
+      },
+      result => result,
+   }
+}
+
+async fn run(cli: Cli, via: Option<String>) -> Result<()> {
+   let (config, issues_dir) = if cli.global {
+      (Config::load_global(), config::global_store_dir()?)
+   } else {
+      let config = Config::load();
+      let issues_dir = config.resolve_issues_directory();
+      (config, issues_dir)
+   };
+   let read_only = config.is_read_only();
+   if read_only && cli.command.is_mutating() {
+      return Err(ReadOnlyError.into());
+   }
+   let storage = Storage::new(issues_dir.clone())
+      .with_daemon()
+      .with_notifications(config.watch.clone())
+      .with_read_only(read_only);
+   let ref_mode = if cli.exact {
+      RefMode::Exact
+   } else if !cli.json && is_interactive_terminal() {
+      RefMode::Interactive
+   } else {
+      RefMode::Fuzzy
+   };
+   let commands = Commands::new(storage)
+      .with_config(config.clone())
+      .with_ref_mode(ref_mode)
+      .with_force(cli.force)
+      .with_yes(cli.yes)
+      .with_agent(cli.agent.as_deref())
+      .with_actor(Actor::resolve(None, via.as_deref()));
+
+   let quiet = matches!(
+      cli.command,
+      Command::Prompt { .. }
+         | Command::Serve { .. }
+         | Command::Daemon { .. }
+         | Command::Completions { .. }
+         | Command::Complete { .. }
+   );
+   if !quiet && ref_mode == RefMode::Interactive {
+      // A broken banner shouldn't stop the command itself
+      let _ = commands.watched_banner();
+   }
+
+   let stdin_form = cli
+      .stdin_form
+      .as_deref()
+      .map(FormFormat::parse)
+      .transpose()?;
+   if stdin_form.is_some()
+      && !matches!(
+         cli.command,
+         Command::New { .. } | Command::Checkpoint { .. } | Command::Depend { .. }
+      )
+   {
+      anyhow::bail!("--stdin-form works with new, checkpoint and depend");
+   }
 
    match cli.command {
-      Command::List { status, verbose } => {
-         commands.list(&status, verbose, cli.json)?;
-      },
-      Command::Show { bug_ref } => {
-         commands.show(&bug_ref, cli.json)?;
+      Command::List {
+         status,
+         verbose,
+         where_clause,
+         include_closed,
+         created_since,
+         closed_since,
+         file_glob,
+         file_regex,
+         group_by,
+         sort,
+         lang,
+         order,
+      } => {
+         let scope = ListScope {
+            include_closed,
+            created_since: created_since.map(|s| s.to_string()),
+            closed_since: closed_since.map(|s| s.to_string()),
+            file_glob: file_glob.map(|s| s.to_string()),
+            file_regex: file_regex.map(|s| s.to_string()),
+         };
+         let view = ListView {
+            group_by:   group_by
+               .as_deref()
+               .map(listing::parse_group_by)
+               .transpose()?,
+            sort:       sort.as_deref().map(listing::parse_sort).transpose()?,
+            descending: match order.as_str() {
+               "asc" => false,
+               "desc" => true,
+               _ => anyhow::bail!("Invalid order: {order}. Use: asc, desc"),
+            },
+            lang:       lang.map(|s| s.to_string()),
+         };
+         commands.list(&status, verbose, where_clause.as_deref(), &scope, &view, cli.json)?;
+      },
+      Command::Show { bug_ref, copy, open, raw, lang, context_budget, checkpoints } => {
+         if let Some(bug_ref) = ref_or_pick(bug_ref, &commands, PickFor::Show)? {
+            let bug_num = commands.tracker().resolve_ref(&bug_ref)?;
+            match context_budget {
+               Some(budget) => {
+                  commands.show_brief(&bug_num.to_string(), budget, checkpoints, cli.json)?
+               },
+               None => commands.show(&bug_num.to_string(), raw, lang.as_deref(), cli.json)?,
+            }
+            copy_or_open(&commands, bug_num, copy, open)?;
+         }
       },
-      Command::New { title, priority, tags, files, issue, impact, acceptance, effort, context } => {
+      Command::New {
+         title,
+         priority,
+         tags,
+         files,
+         issue,
+         impact,
+         acceptance,
+         effort,
+         context,
+         milestone,
+         kind,
+         copy,
+         open,
+         from_stdin,
+      } => {
+         if let Some(format) = stdin_form {
+            if from_stdin {
+               anyhow::bail!("--stdin-form and --from-stdin both read stdin; use one");
+            }
+            let bug_num = format.read::<NewForm>()?.apply(&commands, cli.json)?;
+            copy_or_open(&commands, bug_num, copy, open)?;
+            return Ok(());
+         }
+         let kind = kind.map(|k| k.to_string());
+         if from_stdin {
+            let mut report = String::new();
+            std::io::Read::read_to_string(&mut std::io::stdin(), &mut report)?;
+            let mut draft = commands.tracker().draft_data(&report)?;
+            if let Some(title) = title {
+               draft.title = title.to_string();
+            }
+            for file in files {
+               if !draft.files.iter().any(|f| f == file.as_str()) {
+                  draft.files.push(file.to_string());
+               }
+            }
+
+            // Stdin holds the report, so the wizard talks to the terminal
+            // directly; it runs whenever output goes to one
+            let fields_given = impact.is_some() && acceptance.is_some();
+            let created =
+               if (cli.interactive || !fields_given) && !cli.json && atty::is(atty::Stream::Stdout)
+               {
+                  let wizard_storage = Storage::new(issues_dir.clone()).with_daemon();
+                  wizards::draft_issue_wizard(&wizard_storage, draft, cli.json)?
+               } else {
+                  Some(
+                     commands
+                        .create_issue(
+                           draft.title,
+                           &priority,
+                           tags.into_iter().map(|s| s.to_string()).collect(),
+                           draft.files,
+                           draft.issue,
+                           impact.map_or_else(
+                              || "No impact description provided".to_string(),
+                              |s| s.to_string(),
+                           ),
+                           acceptance.map_or_else(
+                              || "No acceptance criteria provided".to_string(),
+                              |s| s.to_string(),
+                           ),
+                           effort.map(|s| s.to_string()),
+                           context.map(|s| s.to_string()),
+                           milestone.map(|s| s.to_string()),
+                           kind,
+                           cli.json,
+                        )?
+                        .bug_num,
+                  )
+               };
+            if let Some(bug_num) = created {
+               copy_or_open(&commands, bug_num, copy, open)?;
+            }
+            return Ok(());
+         }
+
+         // The kind's template stands in for sections not given
+         let template = kind
+            .as_deref()
+            .map(|k| commands.config().kind(k))
+            .transpose()?
+            .map(|k| k.template.clone())
+            .unwrap_or_default();
+         let issue = issue.map(|s| s.to_string()).or(template.issue);
+         let impact = impact.map(|s| s.to_string()).or(template.impact);
+         let acceptance = acceptance.map(|s| s.to_string()).or(template.acceptance);
+
          // Check if we should use interactive mode
          // Interactive mode triggers if: --interactive flag OR missing required fields
          let use_interactive = cli.interactive
@@ -34,9 +255,9 @@ async fn main() -> Result<()> {
             || impact.is_none()
             || acceptance.is_none();
 
-         if use_interactive && atty::is(atty::Stream::Stdin) {
-            let wizard_storage = Storage::new(issues_dir.clone());
-            wizards::new_issue_wizard(&wizard_storage, cli.json)?;
+         let created = if use_interactive && atty::is(atty::Stream::Stdin) {
+            let wizard_storage = Storage::new(issues_dir.clone()).with_daemon();
+            wizards::new_issue_wizard(&wizard_storage, cli.json)?
          } else {
             // All fields must be present for non-interactive mode
             let title = title.ok_or_else(|| {
@@ -52,72 +273,161 @@ async fn main() -> Result<()> {
                anyhow::anyhow!("--acceptance is required (use -i for interactive mode)")
             })?;
 
-            commands.create_issue(
-               title.to_string(),
-               &priority,
-               tags.into_iter().map(|s| s.to_string()).collect(),
-               files.into_iter().map(|s| s.to_string()).collect(),
-               issue.to_string(),
-               impact.to_string(),
-               acceptance.to_string(),
-               effort.map(|s| s.to_string()),
-               context.map(|s| s.to_string()),
-               cli.json,
-            )?;
+            Some(
+               commands
+                  .create_issue(
+                     title.to_string(),
+                     &priority,
+                     tags.into_iter().map(|s| s.to_string()).collect(),
+                     files.into_iter().map(|s| s.to_string()).collect(),
+                     issue,
+                     impact,
+                     acceptance,
+                     effort.map(|s| s.to_string()),
+                     context.map(|s| s.to_string()),
+                     milestone.map(|s| s.to_string()),
+                     kind,
+                     cli.json,
+                  )?
+                  .bug_num,
+            )
+         };
+         if let Some(bug_num) = created {
+            copy_or_open(&commands, bug_num, copy, open)?;
          }
       },
       Command::Start { bug_ref, branch, no_branch } => {
-         commands.start(&bug_ref, branch, no_branch, cli.json)?;
+         if let Some(bug_ref) = ref_or_pick(bug_ref, &commands, PickFor::Start)? {
+            commands.start(&bug_ref, branch, no_branch, cli.json)?;
+         }
       },
       Command::Block { bug_ref, reason } => {
          commands.block(&bug_ref, reason.to_string(), cli.json)?;
       },
-      Command::Close { bug_ref, message, commit, no_commit } => {
-         commands.close(&bug_ref, message.map(|s| s.to_string()), commit, no_commit, cli.json)?;
+      Command::Close { bug_ref, message, commit, no_commit, auto_unblock, auto_files } => {
+         if let Some(bug_ref) = ref_or_pick(bug_ref, &commands, PickFor::Close)? {
+            let message = message.map(|s| s.to_string());
+            commands.close(
+               &bug_ref,
+               message,
+               commit,
+               no_commit,
+               auto_unblock,
+               auto_files,
+               cli.json,
+            )?;
+         }
       },
       Command::Open { bug_ref } => {
          commands.open(&bug_ref, cli.json)?;
       },
-      Command::Checkpoint { bug_ref, message } => {
-         let use_interactive = cli.interactive || (bug_ref.is_empty() && message.is_empty());
+      Command::Checkpoint { bug_ref, message, auto_files } => {
+         if let Some(format) = stdin_form {
+            let form = format.read::<CheckpointForm>()?;
+            return form.apply(&commands, bug_ref.as_deref(), cli.json);
+         }
+         let use_interactive = cli.interactive || (bug_ref.is_none() && message.is_empty());
 
          if use_interactive && atty::is(atty::Stream::Stdin) {
-            let wizard_storage = Storage::new(issues_dir.clone());
-            let bug_ref_opt = if bug_ref.is_empty() {
-               None
-            } else {
-               Some(bug_ref.to_string())
-            };
+            let wizard_storage = Storage::new(issues_dir.clone()).with_daemon();
+            let bug_ref_opt = bug_ref.map(|r| r.to_string());
             wizards::checkpoint_wizard(&wizard_storage, bug_ref_opt, cli.json)?;
          } else {
+            let bug_ref = bug_ref.ok_or_else(|| {
+               anyhow::anyhow!("An issue reference is required (use -i for interactive mode)")
+            })?;
             let note = message
                .iter()
                .map(|s| s.as_str())
                .collect::<Vec<_>>()
                .join(" ");
-            commands.checkpoint(&bug_ref, note, cli.json)?;
+            commands.checkpoint(&bug_ref, note, auto_files, cli.json)?;
          }
       },
-      Command::Context => {
-         commands.context(cli.json)?;
+      Command::Compact { bug_ref, keep } => {
+         commands.compact(&bug_ref, keep, cli.json)?;
+      },
+      Command::Claim { bug_ref, ttl } => {
+         commands.claim(&bug_ref, &ttl, cli.json)?;
+      },
+      Command::CommitMsg { bug_ref } => {
+         commands.commit_msg(bug_ref.as_deref(), cli.json)?;
+      },
+      Command::Bundle { bug_ref: Some(bug_ref), out, action: None } => {
+         commands.bundle(&bug_ref, out.as_deref(), cli.json)?;
+      },
+      Command::Bundle { action: Some(BundleAction::Import { file }), .. } => {
+         commands.bundle_import(&file, cli.json)?;
+      },
+      Command::Bundle { .. } => {
+         anyhow::bail!("Pass an issue to bundle, or `import` with a bundle file");
       },
-      Command::Focus => {
+      Command::Release { bug_ref: Some(bug_ref), action: None } => {
+         commands.release(&bug_ref, cli.json)?;
+      },
+      Command::Context { tags, milestone, max_items } => {
+         let scope = ContextScope {
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            milestone: milestone.map(|m| m.to_string()),
+            max_items,
+         };
+         commands.context(&scope, cli.json)?;
+      },
+      Command::Focus { pomodoro: false, .. } => {
          commands.focus(cli.json)?;
       },
-      Command::Blocked => {
-         commands.blocked(cli.json)?;
+      Command::Focus { pomodoro: true, issue, work, rest, rounds } => {
+         commands.pomodoro(issue.as_deref(), &work, &rest, rounds, cli.json)?;
+      },
+      Command::Path { bug_ref } => {
+         commands.path(&bug_ref, cli.json)?;
+      },
+      Command::Diff { bug_ref, since } => {
+         commands.diff(&bug_ref, since.as_deref(), cli.json)?;
+      },
+      Command::Audit { actor, issue, since, until, limit } => {
+         commands.audit(
+            actor.as_deref(),
+            issue.as_deref(),
+            since.as_deref(),
+            until.as_deref(),
+            limit,
+            cli.json,
+         )?;
+      },
+      Command::Why { bug_ref } => {
+         commands.why(&bug_ref, cli.json)?;
+      },
+      Command::Blocked { analyze } => {
+         if analyze {
+            commands.blocked_analysis(cli.json)?;
+         } else {
+            commands.blocked(cli.json)?;
+         }
+      },
+      Command::ForFile { path } => {
+         commands.for_file(path.as_deref(), cli.json)?;
+      },
+      Command::Files { action: FilesAction::Remap { old, new, dry_run } } => {
+         commands.files_remap(&old, &new, dry_run, cli.json)?;
       },
       Command::Ready => {
          commands.ready(cli.json)?;
       },
-      Command::Import { file } => {
+      Command::Batch { source } => {
+         commands.batch(&source)?;
+      },
+      Command::Import { action: Some(ImportAction::Url { url }), .. } => {
+         commands.import_url(&url, cli.json)?;
+      },
+      Command::Import { file, dry_run, action: None } => {
          let use_interactive = cli.interactive || file.is_none();
 
          if use_interactive && atty::is(atty::Stream::Stdin) {
-            let wizard_storage = Storage::new(issues_dir.clone());
+            let wizard_storage = Storage::new(issues_dir.clone()).with_daemon();
             wizards::import_wizard(&wizard_storage, cli.json)?;
          } else {
-            commands.import(file.map(|s| s.to_string()), cli.json)?;
+            commands.import(file.map(|s| s.to_string()), dry_run, cli.json)?;
          }
       },
       Command::Alias { action } => match action {
@@ -130,35 +440,105 @@ async fn main() -> Result<()> {
          AliasAction::Remove { alias } => {
             commands.alias_remove(&alias, cli.json)?;
          },
+         AliasAction::Rename { old, new } => {
+            commands.alias_rename(&old, &new, cli.json)?;
+         },
+      },
+      Command::Watch { bug_ref: Some(bug_ref), remove } => {
+         commands.watch(&bug_ref, remove, cli.json)?;
+      },
+      Command::Watch { bug_ref: None, .. } => {
+         commands.watched(cli.json)?;
       },
       Command::Guide => {
          guide::print_guide();
       },
-      Command::QuickWins { threshold } => {
-         commands.quick_wins(&threshold, cli.json)?;
+      Command::Doctor { fix } => {
+         commands.doctor(fix, cli.json)?;
+      },
+      Command::Lint => {
+         if !commands.lint(cli.json)? {
+            std::process::exit(1);
+         }
+      },
+      Command::Gate { fail_on, max } => {
+         if !commands.gate(&fail_on, max, cli.json)? {
+            std::process::exit(1);
+         }
+      },
+      Command::Crypt { action } => match action {
+         CryptAction::Init => {
+            commands.crypt_init(cli.json)?;
+         },
+         CryptAction::Lock => {
+            commands.crypt_set_locked(true, cli.json)?;
+         },
+         CryptAction::Unlock => {
+            commands.crypt_set_locked(false, cli.json)?;
+         },
+      },
+      Command::QuickWins { threshold, only_ready, sort } => {
+         commands.quick_wins(&threshold, only_ready, sort.parse()?, cli.json)?;
+      },
+      Command::Duplicates { status, threshold } => {
+         commands.duplicates(&status, threshold, cli.json)?;
       },
       Command::BulkStart { bug_refs } => {
          commands.bulk_start(bug_refs.into_iter().map(|s| s.to_string()).collect(), cli.json)?;
       },
-      Command::BulkClose { bug_refs, message } => {
+      Command::BulkClose { bug_refs, message, auto_unblock } => {
          commands.bulk_close(
             bug_refs.into_iter().map(|s| s.to_string()).collect(),
             message.map(|s| s.to_string()),
+            auto_unblock,
+            cli.json,
+         )?;
+      },
+      Command::BulkEdit {
+         bug_refs,
+         where_clause,
+         priority,
+         add_tags,
+         remove_tags,
+         milestone,
+         target_version,
+         due,
+         assignee,
+      } => {
+         commands.bulk_edit(
+            bug_refs.into_iter().map(|s| s.to_string()).collect(),
+            where_clause.as_deref(),
+            priority.as_deref(),
+            add_tags.into_iter().map(|s| s.to_string()).collect(),
+            remove_tags.into_iter().map(|s| s.to_string()).collect(),
+            milestone.as_deref(),
+            target_version.as_deref(),
+            due.as_deref(),
+            assignee.as_deref(),
             cli.json,
          )?;
       },
       Command::Summary { hours } => {
          commands.summary(hours, cli.json)?;
       },
+      Command::Session { action } => match action {
+         SessionAction::Start { note } => commands.session_start(note, cli.json)?,
+         SessionAction::Status => commands.session_status(cli.json)?,
+         SessionAction::End { annotate } => commands.session_end(annotate, cli.json)?,
+      },
       Command::Dependencies { bug_ref } => {
          commands.dependencies(&bug_ref, cli.json)?;
       },
       Command::Depend { bug_ref, on, remove } => {
+         if let Some(format) = stdin_form {
+            let form = format.read::<DependForm>()?;
+            return form.apply(&commands, Some(bug_ref.as_str()), cli.json);
+         }
          let use_interactive =
             cli.interactive || (bug_ref.is_empty() && on.is_empty() && remove.is_empty());
 
          if use_interactive && atty::is(atty::Stream::Stdin) {
-            let wizard_storage = Storage::new(issues_dir.clone());
+            let wizard_storage = Storage::new(issues_dir.clone()).with_daemon();
             let bug_ref_opt = if bug_ref.is_empty() {
                None
             } else {
@@ -183,16 +563,110 @@ async fn main() -> Result<()> {
             cli.json,
          )?;
       },
+      Command::Tags { action } => match action {
+         TagsAction::Stats => {
+            commands.tags_stats(cli.json)?;
+         },
+         TagsAction::Suggest { title, files } => {
+            commands.tags_suggest(
+               &title,
+               files.into_iter().map(|s| s.to_string()).collect(),
+               cli.json,
+            )?;
+         },
+      },
       Command::CriticalPath => {
          commands.critical_path(cli.json)?;
       },
-      Command::DepsGraph { issue } => {
-         commands.deps_graph(issue.as_deref(), cli.json)?;
+      Command::Cycles { fix } => {
+         commands.cycles(fix, cli.yes, cli.json)?;
+      },
+      Command::DepsGraph { issue, depth, cluster, format, out } => {
+         let format = match (&format, &out) {
+            (Some(format), _) => graph::parse_format(format)?,
+            (None, Some(out)) => graph::format_for_path(out).unwrap_or_default(),
+            (None, None) => GraphFormat::Ascii,
+         };
+         let cluster = cluster.as_deref().map(graph::parse_cluster).transpose()?;
+         commands.deps_graph(issue.as_deref(), depth, cluster, format, out.as_deref(), cli.json)?;
+      },
+      Command::Search { query, status, include_closed, limit } => {
+         let query = query.join(" ");
+         let scope = ListScope { include_closed, ..Default::default() };
+         let limit = (limit > 0).then_some(limit);
+         commands.search(&query, &status, &scope, limit, cli.json)?;
+      },
+      Command::Workflow { action: WorkflowAction::Show { format } } => {
+         commands.workflow_show(&format, cli.json)?;
+      },
+      Command::Metrics { period, by_tag, by_assignee, by_kind } => {
+         commands.metrics(&period, by_tag, by_assignee, by_kind, cli.json)?;
+      },
+      Command::Board { columns, watch } => {
+         commands.board(columns.as_deref(), watch, cli.json)?;
+      },
+      Command::Order { milestone, export } => {
+         commands.order(milestone.as_deref(), export.as_deref(), cli.json)?;
+      },
+      Command::Plan { capacity, milestone, tag } => {
+         commands.plan(&capacity, milestone.as_deref(), tag, cli.json)?;
+      },
+      Command::Digest { period, out, stale_days } => {
+         commands.digest(&period, stale_days, out.as_deref(), cli.json)?;
+      },
+      Command::Standup { since, ready, format } => {
+         commands.standup(since.as_deref(), ready, &format, cli.json)?;
+      },
+      Command::Notify { every, no_desktop } => {
+         commands.notify(every.as_deref(), !no_desktop, cli.json)?;
+      },
+      Command::Export {
+         format,
+         out,
+         status,
+         where_clause,
+         include_closed,
+         created_since,
+         closed_since,
+      } => {
+         let format = match (&format, &out) {
+            (Some(format), _) => export::parse_format(format)?,
+            (None, Some(out)) => export::format_for_path(out).unwrap_or_default(),
+            (None, None) => Default::default(),
+         };
+         let scope = ListScope {
+            include_closed,
+            created_since: created_since.map(|s| s.to_string()),
+            closed_since: closed_since.map(|s| s.to_string()),
+            ..Default::default()
+         };
+         commands.export(&status, &scope, where_clause.as_deref(), format, out.as_deref())?;
+      },
+      Command::Calibration => {
+         commands.calibration(cli.json)?;
+      },
+      Command::Forecast { milestone, chain, weeks } => {
+         commands.forecast(milestone.as_deref(), chain.as_deref(), weeks, cli.json)?;
+      },
+      Command::Release { action: Some(action), .. } => match action {
+         ReleaseAction::Status { version } => {
+            commands.release_status(&version, cli.json)?;
+         },
+         ReleaseAction::Cut { version, changelog } => {
+            if !commands.release_cut(&version, changelog.as_deref(), cli.json)? {
+               std::process::exit(1);
+            }
+         },
       },
-      Command::Metrics { period } => {
-         commands.metrics(&period, cli.json)?;
+      Command::Release { .. } => {
+         anyhow::bail!("Pass an issue to release its claim, or `status`/`cut` with a version");
       },
       Command::Completions { shell } => {
+         // bash, zsh and fish ask `__complete` for issues, aliases and tags
+         if let Some(script) = complete::script(&shell.to_lowercase()) {
+            print!("{script}");
+            return Ok(());
+         }
          let shell_type = match shell.to_lowercase().as_str() {
             "bash" => Shell::Bash,
             "zsh" => Shell::Zsh,
@@ -209,15 +683,24 @@ async fn main() -> Result<()> {
          let mut cmd = Cli::command();
          generate(shell_type, &mut cmd, "agentx", &mut std::io::stdout());
       },
-      Command::Init { global } => {
+      Command::Complete { shell, words } => {
+         // A store that can't be read just completes nothing
+         if let Ok(completions) = commands.tracker().completions_data(&words) {
+            print!("{}", complete::format(&shell, &completions));
+         }
+      },
+      Command::Init => {
          if cli.interactive && atty::is(atty::Stream::Stdin) {
             wizards::init_wizard()?;
          } else {
             let config = Config::default();
-            let config_path = if global {
-               dirs::home_dir()
-                  .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
-                  .join(".agentxrc.yaml")
+            let config_path = if cli.global {
+               let path = config::user_config_path()
+                  .ok_or_else(|| anyhow::anyhow!("Could not determine the config directory"))?;
+               if let Some(dir) = path.parent() {
+                  std::fs::create_dir_all(dir)?;
+               }
+               path
             } else {
                std::env::current_dir()?.join(".agentxrc.yaml")
             };
@@ -232,8 +715,63 @@ async fn main() -> Result<()> {
             println!("Created config file at: {}", config_path.display());
          }
       },
-      Command::Serve => {
-         agentx::mcp_simple::SimpleMcpServer::serve_stdio().await?;
+      Command::Serve { web: false, .. } => {
+         agentx::mcp_simple::SimpleMcpServer::serve_stdio(cli.global).await?;
+      },
+      Command::Serve { web: true, addr, token } => {
+         let token = token
+            .or_else(|| std::env::var("AGENTX_WEB_TOKEN").ok())
+            .filter(|t| !t.is_empty());
+         if read_only && token.is_some() {
+            return Err(ReadOnlyError.into());
+         }
+         serve_web(&issues_dir, config, &addr, token).await?;
+      },
+      Command::Prompt { init } => match init {
+         Some(shell) => match prompt::shell_snippet(&shell.to_lowercase()) {
+            Some(snippet) => print!("{snippet}"),
+            None => {
+               eprintln!("Unsupported shell: {shell}");
+               eprintln!("Supported: zsh, bash, fish, starship");
+               std::process::exit(1);
+            },
+         },
+         None => commands.prompt(cli.json)?,
+      },
+      Command::Daemon { stop, status } => {
+         if stop {
+            match daemon::request(&issues_dir, &daemon::Request::Shutdown) {
+               Some(_) => println!("Daemon stopped"),
+               None => println!("No daemon running"),
+            }
+         } else if status {
+            match daemon::request(&issues_dir, &daemon::Request::Status) {
+               Some(daemon::Response::Status(status)) if cli.json => {
+                  println!("{}", serde_json::to_string_pretty(&status)?);
+               },
+               Some(daemon::Response::Status(status)) => {
+                  println!("Daemon running (pid {})", status.pid);
+                  println!("Started: {}", status.started.format("%Y-%m-%d %H:%M:%S UTC"));
+                  println!("Cache generation: {}", status.generation);
+                  let count =
+                     |n: Option<usize>| n.map_or("not loaded".to_string(), |n| n.to_string());
+                  println!("Cached open issues: {}", count(status.open));
+                  println!("Cached closed issues: {}", count(status.closed));
+               },
+               _ => println!("No daemon running"),
+            }
+         } else {
+            if let Some(every) = config.notify.daemon_every.clone() {
+               // Scheduled notifications ride along with the cache
+               let notifier = commands.clone();
+               std::thread::spawn(move || {
+                  if let Err(e) = notifier.notify(Some(&every), true, false) {
+                     eprintln!("Notifications stopped: {e}");
+                  }
+               });
+            }
+            daemon::run(&issues_dir)?;
+         }
       },
       Command::Defer { bug_ref } => {
          commands.defer(&bug_ref, cli.json)?;
@@ -242,7 +780,10 @@ async fn main() -> Result<()> {
          commands.activate(&bug_ref, cli.json)?;
       },
       Command::Ui => {
-         let dashboard_storage = Storage::new(issues_dir);
+         let dashboard_storage = Storage::new(issues_dir)
+            .with_daemon()
+            .with_notifications(config.watch.clone())
+            .with_read_only(read_only);
          agentx::tui::launch_dashboard(dashboard_storage)?;
       },
       Command::Install { uninstall } => {
@@ -252,3 +793,49 @@ async fn main() -> Result<()> {
 
    Ok(())
 }
+
+/// The given bug ref, or one picked interactively when it was left out
+fn ref_or_pick(
+   bug_ref: Option<SmolStr>,
+   commands: &Commands,
+   pick_for: PickFor,
+) -> Result<Option<String>> {
+   match bug_ref {
+      Some(bug_ref) => Ok(Some(bug_ref.to_string())),
+      None if is_interactive_terminal() => wizards::pick_issue(commands, pick_for),
+      None => anyhow::bail!("An issue reference is required when not running in a terminal"),
+   }
+}
+
+/// Handle the `--copy` and `--open` flags shared by `new` and `show`
+fn copy_or_open(commands: &Commands, bug_num: u32, copy: bool, open: bool) -> Result<()> {
+   if copy {
+      commands.copy_issue(bug_num)?;
+   }
+   if open {
+      commands.open_issue(bug_num)?;
+   }
+   Ok(())
+}
+
+/// Serve the web dashboard for the issues in `issues_dir`
+#[cfg(feature = "web")]
+async fn serve_web(
+   issues_dir: &std::path::Path,
+   config: Config,
+   addr: &str,
+   token: Option<String>,
+) -> Result<()> {
+   let tracker = agentx::api::IssueTracker::open(issues_dir)?.with_config(config);
+   agentx::web::serve(tracker, addr, token).await
+}
+
+#[cfg(not(feature = "web"))]
+async fn serve_web(
+   _issues_dir: &std::path::Path,
+   _config: Config,
+   _addr: &str,
+   _token: Option<String>,
+) -> Result<()> {
+   anyhow::bail!("This agentx was built without the web dashboard; rebuild with `--features web`")
+}
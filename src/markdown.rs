@@ -0,0 +1,520 @@
+//! Terminal rendering of issue bodies
+//!
+//! `show` renders the GitHub-flavored markdown in an issue body instead of
+//! printing it raw: headings, emphasis, lists and checklists, block quotes,
+//! tables, links and fenced code blocks with syntax highlighting, wrapped to
+//! the terminal width.
+
+use std::sync::LazyLock;
+
+use colored::{ColoredString, Colorize};
+use pulldown_cmark::{Alignment, CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use syntect::{
+   easy::HighlightLines, highlighting::ThemeSet, parsing::SyntaxSet,
+   util::as_24_bit_terminal_escaped,
+};
+use unicode_width::UnicodeWidthStr;
+
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+static THEMES: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+
+const CODE_THEME: &str = "base16-ocean.dark";
+const MIN_WIDTH: usize = 20;
+const MIN_COLUMN_WIDTH: usize = 3;
+
+/// Width to wrap at: the terminal's, or 80 when there is no terminal
+pub fn terminal_width() -> usize {
+   crossterm::terminal::size()
+      .map_or(80, |(cols, _)| cols as usize)
+      .max(MIN_WIDTH)
+}
+
+/// `text` rendered for a terminal `width` columns wide
+pub fn render(text: &str, width: usize) -> String {
+   render_with(text, width, colored::control::SHOULD_COLORIZE.should_colorize())
+}
+
+fn render_with(text: &str, width: usize, color: bool) -> String {
+   let options = Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS | Options::ENABLE_STRIKETHROUGH;
+   let mut renderer = Renderer::new(width.max(MIN_WIDTH), color);
+   for event in Parser::new_ext(text, options) {
+      renderer.event(event);
+   }
+   renderer.finish()
+}
+
+struct Table {
+   alignments: Vec<Alignment>,
+   rows:       Vec<Vec<String>>,
+   row:        Vec<String>,
+   cell:       String,
+}
+
+struct Renderer {
+   width:          usize,
+   color:          bool,
+   out:            String,
+   line:           String,
+   line_width:     usize,
+   /// Whether `line` holds more than its prefix
+   content:        bool,
+   pending_space:  bool,
+   /// Indents for block quotes and list items, with their widths
+   prefixes:       Vec<(String, usize)>,
+   /// Bullet or number replacing the innermost indent on an item's first line
+   pending_marker: Option<(String, usize)>,
+   /// Next number of each open list, `None` for bullet lists
+   lists:          Vec<Option<u64>>,
+   bold:           usize,
+   italic:         usize,
+   strike:         usize,
+   heading:        Option<HeadingLevel>,
+   link:           Option<(String, String)>,
+   code_block:     Option<(String, String)>,
+   table:          Option<Table>,
+}
+
+impl Renderer {
+   fn new(width: usize, color: bool) -> Self {
+      Self {
+         width,
+         color,
+         out: String::new(),
+         line: String::new(),
+         line_width: 0,
+         content: false,
+         pending_space: false,
+         prefixes: Vec::new(),
+         pending_marker: None,
+         lists: Vec::new(),
+         bold: 0,
+         italic: 0,
+         strike: 0,
+         heading: None,
+         link: None,
+         code_block: None,
+         table: None,
+      }
+   }
+
+   fn event(&mut self, event: Event) {
+      match event {
+         Event::Start(tag) => self.start(tag),
+         Event::End(tag) => self.end(tag),
+         Event::Text(text) => self.text(&text),
+         Event::Code(code) => {
+            if let Some(table) = &mut self.table {
+               table.cell.push_str(&code);
+            } else {
+               let space = self.take_space();
+               let styled = self.paint(code.to_string(), |s| s.yellow());
+               self.word(&styled, code.width(), space);
+            }
+         },
+         Event::Html(html) => {
+            self.flush();
+            for line in html.lines() {
+               self.text_line(&self.paint(line.to_string(), |s| s.dimmed()), line.width());
+            }
+         },
+         Event::InlineHtml(html) => {
+            let space = self.take_space();
+            let styled = self.paint(html.to_string(), |s| s.dimmed());
+            self.word(&styled, html.width(), space);
+         },
+         Event::SoftBreak => self.pending_space = true,
+         Event::HardBreak => self.flush(),
+         Event::Rule => {
+            self.flush();
+            let rule = "─".repeat(self.width.saturating_sub(self.prefix_width()));
+            self.text_line(&self.paint(rule, |s| s.dimmed()), 0);
+            self.blank_line();
+         },
+         Event::TaskListMarker(checked) => {
+            let marker = if checked {
+               self.paint("☑ ".into(), |s| s.green())
+            } else {
+               "☐ ".to_string()
+            };
+            self.pending_marker = Some((marker, 2));
+         },
+         _ => {},
+      }
+   }
+
+   fn start(&mut self, tag: Tag) {
+      match tag {
+         Tag::Paragraph => self.pending_space = false,
+         Tag::Heading { level, .. } => {
+            self.flush();
+            self.heading = Some(level);
+         },
+         Tag::BlockQuote(_) => {
+            self.flush();
+            let bar = self.paint("│ ".into(), |s| s.dimmed());
+            self.prefixes.push((bar, 2));
+         },
+         Tag::CodeBlock(kind) => {
+            self.flush();
+            let lang = match kind {
+               CodeBlockKind::Fenced(info) => {
+                  info.split_whitespace().next().unwrap_or("").to_string()
+               },
+               CodeBlockKind::Indented => String::new(),
+            };
+            self.code_block = Some((lang, String::new()));
+         },
+         Tag::List(start) => {
+            self.flush();
+            self.lists.push(start);
+         },
+         Tag::Item => {
+            self.flush();
+            let marker = match self.lists.last_mut() {
+               Some(Some(next)) => {
+                  let marker = format!("{next}. ");
+                  *next += 1;
+                  marker
+               },
+               _ => "• ".to_string(),
+            };
+            let width = marker.width();
+            self.prefixes.push((" ".repeat(width), width));
+            self.pending_marker = Some((marker, width));
+         },
+         Tag::Table(alignments) => {
+            self.flush();
+            self.table =
+               Some(Table { alignments, rows: Vec::new(), row: Vec::new(), cell: String::new() });
+         },
+         Tag::TableCell => {
+            if let Some(table) = &mut self.table {
+               table.cell.clear();
+            }
+         },
+         Tag::Emphasis => self.italic += 1,
+         Tag::Strong => self.bold += 1,
+         Tag::Strikethrough => self.strike += 1,
+         Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. } => {
+            self.link = Some((dest_url.to_string(), String::new()));
+         },
+         _ => {},
+      }
+   }
+
+   fn end(&mut self, tag: TagEnd) {
+      match tag {
+         TagEnd::Paragraph | TagEnd::Heading(_) => {
+            self.heading = None;
+            self.blank_line();
+         },
+         TagEnd::BlockQuote(_) => {
+            self.flush();
+            self.prefixes.pop();
+            self.blank_line();
+         },
+         TagEnd::CodeBlock => {
+            if let Some((lang, code)) = self.code_block.take() {
+               self.code(&lang, &code);
+            }
+            self.blank_line();
+         },
+         TagEnd::List(_) => {
+            self.flush();
+            self.lists.pop();
+            if self.lists.is_empty() {
+               self.blank_line();
+            }
+         },
+         TagEnd::Item => {
+            self.flush();
+            self.prefixes.pop();
+            self.pending_marker = None;
+         },
+         TagEnd::TableHead | TagEnd::TableRow => {
+            if let Some(table) = &mut self.table {
+               let row = std::mem::take(&mut table.row);
+               table.rows.push(row);
+            }
+         },
+         TagEnd::TableCell => {
+            if let Some(table) = &mut self.table {
+               let cell = std::mem::take(&mut table.cell);
+               table.row.push(cell.trim().to_string());
+            }
+         },
+         TagEnd::Table => {
+            if let Some(table) = self.table.take() {
+               self.render_table(&table);
+            }
+            self.blank_line();
+         },
+         TagEnd::Emphasis => self.italic = self.italic.saturating_sub(1),
+         TagEnd::Strong => self.bold = self.bold.saturating_sub(1),
+         TagEnd::Strikethrough => self.strike = self.strike.saturating_sub(1),
+         TagEnd::Link | TagEnd::Image => {
+            if let Some((url, text)) = self.link.take()
+               && !url.is_empty()
+               && url != text
+            {
+               let shown = format!("({url})");
+               let styled = self.paint(shown.clone(), |s| s.dimmed());
+               self.word(&styled, shown.width(), true);
+            }
+         },
+         _ => {},
+      }
+   }
+
+   fn text(&mut self, text: &str) {
+      if let Some((_, code)) = &mut self.code_block {
+         code.push_str(text);
+         return;
+      }
+      if let Some(table) = &mut self.table {
+         table.cell.push_str(text);
+         return;
+      }
+      if let Some((_, link_text)) = &mut self.link {
+         link_text.push_str(text);
+      }
+
+      if text.starts_with(char::is_whitespace) {
+         self.pending_space = true;
+      }
+      for word in text.split_whitespace() {
+         let space = self.take_space();
+         let styled = self.style(word);
+         self.word(&styled, word.width(), space);
+         self.pending_space = true;
+      }
+      self.pending_space = text.ends_with(char::is_whitespace)
+         || (self.pending_space && text.split_whitespace().next().is_none());
+   }
+
+   fn take_space(&mut self) -> bool {
+      std::mem::take(&mut self.pending_space)
+   }
+
+   fn paint(&self, text: String, style: impl FnOnce(ColoredString) -> ColoredString) -> String {
+      if self.color {
+         style(text.normal()).to_string()
+      } else {
+         text
+      }
+   }
+
+   /// `word` in the current inline style
+   fn style(&self, word: &str) -> String {
+      if !self.color {
+         return word.to_string();
+      }
+      let mut styled = word.normal();
+      match self.heading {
+         Some(HeadingLevel::H1) => styled = styled.bright_cyan().bold().underline(),
+         Some(HeadingLevel::H2) => styled = styled.cyan().bold(),
+         Some(_) => styled = styled.bold(),
+         None => {},
+      }
+      if self.bold > 0 {
+         styled = styled.bold();
+      }
+      if self.italic > 0 {
+         styled = styled.italic();
+      }
+      if self.strike > 0 {
+         styled = styled.strikethrough();
+      }
+      if self.link.is_some() {
+         styled = styled.blue().underline();
+      }
+      styled.to_string()
+   }
+
+   fn prefix_width(&self) -> usize {
+      self.prefixes.iter().map(|(_, width)| width).sum()
+   }
+
+   /// Start the line with the block quote bars and list indents it sits in
+   fn begin_line(&mut self) {
+      if !self.line.is_empty() || self.content {
+         return;
+      }
+      let inner = match self.pending_marker.take() {
+         Some(marker) => {
+            let outer = self.prefixes.len().saturating_sub(1);
+            self.prefixes[..outer]
+               .iter()
+               .chain(std::iter::once(&marker))
+               .map(|(text, width)| (text.clone(), *width))
+               .collect::<Vec<_>>()
+         },
+         None => self.prefixes.clone(),
+      };
+      for (text, width) in inner {
+         self.line.push_str(&text);
+         self.line_width += width;
+      }
+   }
+
+   fn word(&mut self, styled: &str, width: usize, space: bool) {
+      self.begin_line();
+      // Only break where there is a space, so punctuation stays with its word
+      let mut space = space && self.content;
+      if space && self.line_width + 1 + width > self.width {
+         self.flush();
+         self.begin_line();
+         space = false;
+      }
+      if space {
+         self.line.push(' ');
+         self.line_width += 1;
+      }
+      self.line.push_str(styled);
+      self.line_width += width;
+      self.content = true;
+   }
+
+   /// A whole line that isn't wrapped
+   fn text_line(&mut self, styled: &str, width: usize) {
+      self.begin_line();
+      self.line.push_str(styled);
+      self.line_width += width;
+      self.content = true;
+      self.flush();
+   }
+
+   fn flush(&mut self) {
+      if self.content {
+         self.out.push_str(self.line.trim_end());
+         self.out.push('\n');
+      }
+      self.line.clear();
+      self.line_width = 0;
+      self.content = false;
+      self.pending_space = false;
+   }
+
+   fn blank_line(&mut self) {
+      self.flush();
+      if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+         self.out.push('\n');
+      }
+   }
+
+   fn code(&mut self, lang: &str, code: &str) {
+      let syntax = SYNTAXES
+         .find_syntax_by_token(lang)
+         .unwrap_or_else(|| SYNTAXES.find_syntax_plain_text());
+      let mut highlighter = HighlightLines::new(syntax, &THEMES.themes[CODE_THEME]);
+      for line in code.lines() {
+         let styled = match highlighter.highlight_line(line, &SYNTAXES) {
+            Ok(ranges) if self.color => {
+               format!("    {}\x1b[0m", as_24_bit_terminal_escaped(&ranges, false))
+            },
+            _ => format!("    {line}"),
+         };
+         self.text_line(&styled, line.width() + 4);
+      }
+   }
+
+   fn render_table(&mut self, table: &Table) {
+      let columns = table.rows.iter().map(Vec::len).max().unwrap_or(0);
+      if columns == 0 {
+         return;
+      }
+      let mut widths = vec![0; columns];
+      for row in &table.rows {
+         for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.width());
+         }
+      }
+      // Borders and padding take three columns per cell plus one
+      let available = self
+         .width
+         .saturating_sub(self.prefix_width() + 3 * columns + 1);
+      while widths.iter().sum::<usize>() > available {
+         let widest = (0..columns).max_by_key(|&i| widths[i]).unwrap();
+         if widths[widest] <= MIN_COLUMN_WIDTH {
+            break;
+         }
+         widths[widest] -= 1;
+      }
+
+      let border = |left: &str, mid: &str, right: &str| {
+         let segments: Vec<String> = widths.iter().map(|w| "─".repeat(w + 2)).collect();
+         format!("{left}{}{right}", segments.join(mid))
+      };
+      let top = self.paint(border("┌", "┬", "┐"), |s| s.dimmed());
+      let separator = self.paint(border("├", "┼", "┤"), |s| s.dimmed());
+      let bottom = self.paint(border("└", "┴", "┘"), |s| s.dimmed());
+      let bar = self.paint("│".into(), |s| s.dimmed());
+
+      self.text_line(&top, 0);
+      for (r, row) in table.rows.iter().enumerate() {
+         let mut line = bar.clone();
+         for (i, width) in widths.iter().enumerate() {
+            let cell = fit(row.get(i).map_or("", String::as_str), *width);
+            let padding = width - cell.width();
+            let (before, after) = match table.alignments.get(i) {
+               Some(Alignment::Right) => (padding, 0),
+               Some(Alignment::Center) => (padding / 2, padding - padding / 2),
+               _ => (0, padding),
+            };
+            let text = if r == 0 {
+               self.paint(cell, |s| s.bold())
+            } else {
+               cell
+            };
+            line.push_str(&format!(" {}{text}{} {bar}", " ".repeat(before), " ".repeat(after)));
+         }
+         self.text_line(&line, 0);
+         if r == 0 && table.rows.len() > 1 {
+            self.text_line(&separator, 0);
+         }
+      }
+      self.text_line(&bottom, 0);
+   }
+
+   fn finish(mut self) -> String {
+      self.flush();
+      let mut out = self.out.trim_end().to_string();
+      out.push('\n');
+      out
+   }
+}
+
+/// `text` cut to `width` columns, ending in `…` if it had to be cut
+fn fit(text: &str, width: usize) -> String {
+   if text.width() <= width {
+      return text.to_string();
+   }
+   let mut fitted = String::new();
+   for c in text.chars() {
+      if fitted.width() + c.to_string().width() + 1 > width {
+         break;
+      }
+      fitted.push(c);
+   }
+   fitted.push('…');
+   fitted
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn test_render_wraps_lists_tables_and_code() {
+      let text = "## Plan\n\nThe login page times out after thirty seconds on slow networks.\n\n- [x] \
+                  reproduce\n- [ ] fix\n\n| Step | Owner |\n|------|------:|\n| api | ana |\n\n```rust\nfn \
+                  main() {}\n```\n\nSee [docs](https://example.com).\n";
+      let rendered = render_with(text, 30, false);
+      assert_eq!(
+         rendered,
+         "Plan\n\nThe login page times out after\nthirty seconds on slow\nnetworks.\n\n☑ \
+          reproduce\n☐ fix\n\n┌──────┬───────┐\n│ Step │ Owner │\n├──────┼───────┤\n│ api  │   \
+          ana │\n└──────┴───────┘\n\n    fn main() {}\n\nSee docs (https://example.com).\n"
+      );
+   }
+}
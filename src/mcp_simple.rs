@@ -1,14 +1,160 @@
-use anyhow::Result;
+use std::{
+   collections::HashMap,
+   io::BufRead,
+   path::PathBuf,
+   sync::{
+      Arc, Mutex, OnceLock, RwLock,
+      atomic::{AtomicBool, AtomicUsize, Ordering},
+   },
+   time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use chrono::Utc;
 use serde_json::{Value, json};
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::{
+   io::AsyncWriteExt,
+   sync::{mpsc, oneshot},
+};
 
 use crate::{
-   commands::Commands, config::Config, fuzzy::filter_by_tags, issue::IssueWithId, storage::Storage,
-   utils::parse_effort,
+   api::{CancelToken, ContextScope, IssueListResult, IssueTracker, ListScope, RefMode},
+   audit::Actor,
+   config::{self, Config},
+   crypt,
+   export::{self, CHUNK_BYTES},
+   file_match::FileMatcher,
+   fuzzy::filter_by_tags,
+   issue::{IssueWithId, Status},
+   merge::IssueConflict,
+   query::Query,
+   sampling,
+   storage::{EventLog, ReadOnlyError, Storage, StoreEvent, VersionConflict},
+   utils::{repo_root, short_age},
 };
 
+/// The project the server keeps issues for
+#[derive(Clone)]
+struct Project {
+   tracker:    IssueTracker,
+   read_only:  bool,
+   /// Directory the project was resolved from, whose `.agentxrc.yaml` applies
+   root:       PathBuf,
+   issues_dir: PathBuf,
+   /// Where `root` came from: `roots` (the client's), `cwd`, or `global`
+   /// for the global store, which client roots don't replace
+   source:     &'static str,
+}
+
+impl Project {
+   fn open(root: PathBuf, source: &'static str, events: &EventLog) -> Self {
+      let config = Config::load_from(&root);
+      let issues_dir = config.resolve_issues_directory_in(&root);
+      Self::with_config(config, root, issues_dir, source, events)
+   }
+
+   /// The global store, with the user-level config
+   fn global(events: &EventLog) -> Result<Self> {
+      let dir = config::global_store_dir()?;
+      Ok(Self::with_config(Config::load_global(), dir.clone(), dir, "global", events))
+   }
+
+   fn with_config(
+      config: Config,
+      root: PathBuf,
+      issues_dir: PathBuf,
+      source: &'static str,
+      events: &EventLog,
+   ) -> Self {
+      // Encrypted bodies are only served once `agentx crypt unlock` has
+      // decrypted them on disk
+      let storage = Storage::new(&issues_dir)
+         .with_events(events.clone())
+         .with_daemon()
+         .without_decryption()
+         .with_notifications(config.watch.clone())
+         .with_read_only(config.is_read_only());
+      Self {
+         read_only: config.is_read_only(),
+         tracker: IssueTracker::new(storage).with_config(config),
+         root,
+         issues_dir,
+         source,
+      }
+   }
+}
+
 pub struct SimpleMcpServer {
-   commands: Commands,
+   project:      RwLock<Project>,
+   /// Whether the client offered MCP roots at `initialize`
+   roots:        AtomicBool,
+   /// The client's roots as it last listed them
+   client_roots: Mutex<Vec<Value>>,
+   /// Name the client gave at `initialize`, credited in the audit trail
+   client:       OnceLock<String>,
+   /// What the store did while handling the current request
+   events:       EventLog,
+   /// Index into [`LOG_LEVELS`] of the least severe message to send
+   log_level:    AtomicUsize,
+   /// `notifications/message`s to send before the next response
+   logs:         Mutex<Vec<Value>>,
+   /// Cancellation tokens of the requests received and not yet answered,
+   /// by request id
+   running:      Mutex<HashMap<String, CancelToken>>,
+   /// Whether the client offered MCP sampling at `initialize`
+   sampling:     AtomicBool,
+   /// Messages for the client, written in order by the stdout task
+   outgoing:     Mutex<Option<mpsc::UnboundedSender<Value>>>,
+   /// Requests sent to the client and waiting on its answer, by id
+   pending:      Mutex<HashMap<String, oneshot::Sender<Value>>>,
+   next_id:      AtomicUsize,
+}
+
+/// How long to wait for the client to answer a request, such as running
+/// its model for a draft
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// How long to wait for the client's roots before keeping the current
+/// project
+const ROOTS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// MCP log levels, least severe first
+const LOG_LEVELS: &[&str] =
+   &["debug", "info", "notice", "warning", "error", "critical", "alert", "emergency"];
+const DEBUG: usize = 0;
+const INFO: usize = 1;
+const NOTICE: usize = 2;
+const WARNING: usize = 3;
+
+/// Tools refused up front in read-only mode
+const MUTATING_TOOLS: &[&str] = &[
+   "issues_create",
+   "issues_status",
+   "issues_checkpoint",
+   "issues_claim",
+   "issues_release",
+   "issues_import",
+   "issues_batch",
+];
+
+/// Tools that reach outside the tracker, such as to the client's model
+const OPEN_WORLD_TOOLS: &[&str] = &["issues_draft"];
+
+/// Mutating tools that change or take over state instead of only adding to
+/// it; the others are marked safe to approve automatically
+const DESTRUCTIVE_TOOLS: &[&str] =
+   &["issues_status", "issues_claim", "issues_release", "issues_batch"];
+
+/// MCP tool annotations for `tool`, so clients can run reads without asking
+/// and confirm destructive changes
+fn annotations(tool: &str) -> Value {
+   let read_only = !MUTATING_TOOLS.contains(&tool);
+   json!({
+      "readOnlyHint": read_only,
+      "destructiveHint": DESTRUCTIVE_TOOLS.contains(&tool),
+      "idempotentHint": read_only,
+      "openWorldHint": OPEN_WORLD_TOOLS.contains(&tool),
+   })
 }
 
 impl Default for SimpleMcpServer {
@@ -19,12 +165,262 @@ impl Default for SimpleMcpServer {
 
 impl SimpleMcpServer {
    pub fn new() -> Self {
-      let config = Config::load();
-      let issues_dir = config.resolve_issues_directory();
-      let storage = Storage::new(issues_dir);
-      let commands = Commands::new(storage);
+      let events = EventLog::default();
+      let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+
+      Self {
+         project: RwLock::new(Project::open(cwd, "cwd", &events)),
+         roots: AtomicBool::new(false),
+         client_roots: Mutex::new(Vec::new()),
+         client: OnceLock::new(),
+         events,
+         log_level: AtomicUsize::new(INFO),
+         logs: Mutex::new(Vec::new()),
+         running: Mutex::new(HashMap::new()),
+         sampling: AtomicBool::new(false),
+         outgoing: Mutex::new(None),
+         pending: Mutex::new(HashMap::new()),
+         next_id: AtomicUsize::new(1),
+      }
+   }
+
+   fn project(&self) -> Project {
+      match self.project.read() {
+         Ok(project) => project.clone(),
+         Err(poisoned) => poisoned.into_inner().clone(),
+      }
+   }
+
+   fn tracker(&self) -> IssueTracker {
+      self.project().tracker
+   }
+
+   /// Ask the client for its roots and keep issues for the first local one
+   ///
+   /// Without an answer, or without a `file://` root, the current project
+   /// stays.
+   async fn refresh_roots(&self) {
+      let roots = match self
+         .request_client("roots/list", json!({}), ROOTS_TIMEOUT)
+         .await
+      {
+         Ok(result) => result["roots"].as_array().cloned().unwrap_or_default(),
+         Err(e) => {
+            self.log(WARNING, format!("Couldn't list the client's roots: {e}"));
+            return;
+         },
+      };
+      if let Ok(mut client_roots) = self.client_roots.lock() {
+         client_roots.clone_from(&roots);
+      }
+
+      let Some(root) = roots
+         .iter()
+         .filter_map(|r| r["uri"].as_str().and_then(path_from_uri))
+         .find(|path| path.is_dir())
+      else {
+         self.log(
+            NOTICE,
+            "No local directory among the client's roots; keeping the current one".into(),
+         );
+         return;
+      };
+      if root == self.project().root {
+         return;
+      }
+      let project = Project::open(root, "roots", &self.events);
+      self.log(
+         INFO,
+         format!(
+            "Using issues in {} for root {}",
+            project.issues_dir.display(),
+            project.root.display()
+         ),
+      );
+      match self.project.write() {
+         Ok(mut current) => *current = project,
+         Err(poisoned) => *poisoned.into_inner() = project,
+      }
+   }
+
+   /// The active project and where it came from, for `issues_info`
+   fn info(&self) -> Value {
+      let project = self.project();
+      let config = project.tracker.config();
+      json!({
+         "root": project.root,
+         "issues_dir": project.issues_dir,
+         "source": match project.source {
+            "global" => "global",
+            _ if config.has_fixed_location() => "config",
+            source => source,
+         },
+         "client_roots": self.client_roots.lock().map(|r| r.clone()).unwrap_or_default(),
+         "read_only": project.read_only,
+         "client": self.client.get(),
+         "sampling": self.sampling.load(Ordering::Relaxed),
+         "version": env!("CARGO_PKG_VERSION"),
+      })
+   }
+
+   /// Queue a message for the client
+   fn send(&self, message: Value) -> bool {
+      self
+         .outgoing
+         .lock()
+         .ok()
+         .and_then(|outgoing| outgoing.as_ref().map(|o| o.send(message).is_ok()))
+         .unwrap_or(false)
+   }
+
+   /// Send the client a request and wait for its result
+   async fn request_client(&self, method: &str, params: Value, wait: Duration) -> Result<Value> {
+      let id = format!("agentx-{}", self.next_id.fetch_add(1, Ordering::Relaxed));
+      let (answer, answered) = oneshot::channel();
+      if let Ok(mut pending) = self.pending.lock() {
+         pending.insert(id.clone(), answer);
+      }
+      let forget = || {
+         if let Ok(mut pending) = self.pending.lock() {
+            pending.remove(&id);
+         }
+      };
+      if !self.send(json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params})) {
+         forget();
+         anyhow::bail!("No client connected to send {method} to");
+      }
 
-      Self { commands }
+      let response = match tokio::time::timeout(wait, answered).await {
+         Ok(Ok(response)) => response,
+         Ok(Err(_)) => anyhow::bail!("The client disconnected before answering {method}"),
+         Err(_) => {
+            forget();
+            anyhow::bail!("The client didn't answer {method} within {}s", wait.as_secs());
+         },
+      };
+      if let Some(error) = response.get("error") {
+         anyhow::bail!(
+            "The client refused {method}: {}",
+            error["message"].as_str().unwrap_or("unknown error")
+         );
+      }
+      Ok(response["result"].clone())
+   }
+
+   /// Hand a client's answer to the request waiting on it
+   fn deliver(&self, response: Value) {
+      let id = response["id"].as_str().unwrap_or_default();
+      if let Some(answer) = self.pending.lock().ok().and_then(|mut p| p.remove(id)) {
+         let _ = answer.send(response);
+      }
+   }
+
+   /// Draft an issue from a one-line description with the client's model
+   async fn draft_issue(&self, description: &str) -> Result<Value> {
+      if description.trim().is_empty() {
+         anyhow::bail!("description is required");
+      }
+      if !self.sampling.load(Ordering::Relaxed) {
+         anyhow::bail!(
+            "The client doesn't support MCP sampling; write the issue yourself and call \
+             issues_create"
+         );
+      }
+      let tags: Vec<String> = self
+         .tracker()
+         .tags_stats_data()?
+         .into_iter()
+         .map(|t| t.tag)
+         .collect();
+      let result = self
+         .request_client(
+            "sampling/createMessage",
+            sampling::request(description, &tags),
+            CLIENT_TIMEOUT,
+         )
+         .await?;
+      let reply = result["content"]["text"]
+         .as_str()
+         .context("The client's model sent no text")?;
+      Ok(json!({
+         "draft": sampling::parse(reply)?,
+         "model": result["model"],
+         "next": "Nothing was created. Confirm or edit the draft, then pass it to issues_create"
+      }))
+   }
+
+   /// Give request `id` a cancellation token, as it is received
+   fn track(&self, id: &Value) {
+      if !id.is_null()
+         && let Ok(mut running) = self.running.lock()
+      {
+         running.insert(id.to_string(), CancelToken::default());
+      }
+   }
+
+   /// Cancel request `id` (from `notifications/cancelled`): long tools stop
+   /// between items and report what they got done
+   fn cancel(&self, id: &Value) {
+      if let Ok(running) = self.running.lock()
+         && let Some(token) = running.get(&id.to_string())
+      {
+         token.cancel();
+         self.log(NOTICE, format!("Cancelling request {id}"));
+      }
+   }
+
+   /// Queue a log message for the client, unless its level filters it out
+   fn log(&self, level: usize, message: String) {
+      if level < self.log_level.load(Ordering::Relaxed) {
+         return;
+      }
+      if let Ok(mut logs) = self.logs.lock() {
+         logs.push(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/message",
+            "params": {"level": LOG_LEVELS[level], "logger": "agentx", "data": message}
+         }));
+      }
+   }
+
+   /// Log what the store did since the last call
+   fn log_store_events(&self) {
+      let events = match self.events.lock() {
+         Ok(mut events) => std::mem::take(&mut *events),
+         Err(_) => return,
+      };
+      for event in events {
+         match event {
+            StoreEvent::Wrote(path) => self.log(DEBUG, format!("Wrote {}", path.display())),
+            StoreEvent::LockWait(waited) => self
+               .log(NOTICE, format!("Waited {}ms for another writer's lock", waited.as_millis())),
+         }
+      }
+   }
+
+   /// Log messages queued while handling the last request
+   pub fn take_logs(&self) -> Vec<Value> {
+      self
+         .logs
+         .lock()
+         .map(|mut logs| std::mem::take(&mut *logs))
+         .unwrap_or_default()
+   }
+
+   fn handle_set_level(&self, params: &Value) -> Value {
+      let level = params["level"].as_str().unwrap_or("");
+      match LOG_LEVELS.iter().position(|l| *l == level) {
+         Some(index) => {
+            self.log_level.store(index, Ordering::Relaxed);
+            json!({})
+         },
+         None => json!({
+             "error": {
+                 "code": -32602,
+                 "message": format!("Unknown log level: {level}. Use: {}", LOG_LEVELS.join(", "))
+             }
+         }),
+      }
    }
 
    async fn handle_request(&self, request: Value) -> Value {
@@ -32,14 +428,45 @@ impl SimpleMcpServer {
       let params = &request["params"];
       let id = &request["id"];
 
-      if method == "notifications/initialized" {
+      if method == "notifications/initialized" || method == "notifications/roots/list_changed" {
+         if self.roots.load(Ordering::Relaxed) && self.project().source != "global" {
+            self.refresh_roots().await;
+         }
          return Value::Null;
       }
 
       let result = match method {
-         "initialize" => self.handle_initialize(),
-         "tools/list" => self.handle_list_tools(),
-         "tools/call" => self.handle_tool_call(params).await,
+         "initialize" => self.handle_initialize(params),
+         "tools/list" => Self::tool_list(),
+         "tools/call" => {
+            let started = Instant::now();
+            let cancel = self
+               .running
+               .lock()
+               .ok()
+               .and_then(|running| running.get(&id.to_string()).cloned())
+               .unwrap_or_default();
+            let result = self.handle_tool_call(params, &cancel).await;
+            let tool = params["name"].as_str().unwrap_or("");
+            let elapsed = started.elapsed().as_millis();
+            self.log_store_events();
+            if result["isError"] == true {
+               let text = result["content"][0]["text"].as_str().unwrap_or("");
+               let error = text
+                  .lines()
+                  .next()
+                  .unwrap_or("")
+                  .trim_start_matches("Error: ");
+               self.log(WARNING, format!("{tool} failed after {elapsed}ms: {error}"));
+            } else {
+               self.log(INFO, format!("{tool} ran in {elapsed}ms"));
+            }
+            if let Ok(mut running) = self.running.lock() {
+               running.remove(&id.to_string());
+            }
+            result
+         },
+         "logging/setLevel" => self.handle_set_level(params),
          _ => json!({
              "error": {
                  "code": -32601,
@@ -55,22 +482,45 @@ impl SimpleMcpServer {
       })
    }
 
-   fn handle_initialize(&self) -> Value {
+   fn handle_initialize(&self, params: &Value) -> Value {
+      if let Some(client) = params["clientInfo"]["name"].as_str() {
+         let _ = self.client.set(client.to_string());
+      }
+      self
+         .sampling
+         .store(params["capabilities"]["sampling"].is_object(), Ordering::Relaxed);
+      self
+         .roots
+         .store(params["capabilities"]["roots"].is_object(), Ordering::Relaxed);
       json!({
           "protocolVersion": "2024-11-05",
           "capabilities": {
-              "tools": {}
+              "tools": {},
+              "logging": {}
           },
           "serverInfo": {
               "name": "agentx-mcp",
               "version": "0.1.0"
           },
-          "instructions": "Issue tracker MCP server providing tools for managing tasks and bugs. Use issues_context to see current work, issues_create to add tasks, issues_status to update status (start, block, close, defer, activate), issues_checkpoint for progress notes, issues_search for full-text search, issues_query for advanced filtering, and issues_wins to find quick-win tasks. Defer non-urgent tasks to backlog with 'defer' status."
+          "instructions": "Issue tracker MCP server providing tools for managing tasks and bugs. Use issues_context to see current work, issues_create to add tasks, issues_status to update status (start, block, close, defer, activate), issues_checkpoint for progress notes, issues_search for full-text search, issues_query for advanced filtering, issues_query_expr for query expressions, and issues_wins to find quick-win tasks. Defer non-urgent tasks to backlog with 'defer' status."
       })
    }
 
-   fn handle_list_tools(&self) -> Value {
-      json!({
+   /// Names of the tools that are not destructive, for clients' auto-approve
+   /// lists
+   pub fn auto_approved_tools() -> Vec<String> {
+      Self::tool_list()["tools"]
+         .as_array()
+         .into_iter()
+         .flatten()
+         .filter(|tool| tool["annotations"]["destructiveHint"] == false)
+         .filter_map(|tool| tool["name"].as_str().map(String::from))
+         .collect()
+   }
+
+   /// Every tool's schema and annotations, as `tools/list` returns them
+   fn tool_list() -> Value {
+      let mut list = json!({
           "tools": [
               {
                   "name": "issues_list",
@@ -80,17 +530,47 @@ impl SimpleMcpServer {
                       "properties": {
                           "status": {
                               "type": "string",
-                              "description": "Filter by status: 'open' or 'closed' (default: 'open')"
+                              "description": "Filter by status: 'open' (not closed), 'closed', 'all', or a single status such as 'active' or 'blocked' (default: 'open')"
+                          },
+                          "include_closed": {
+                              "type": "boolean",
+                              "description": "Also include closed issues (closed issues are the archive)"
+                          },
+                          "include_archived": {
+                              "type": "boolean",
+                              "description": "Alias for include_closed"
+                          },
+                          "created_since": {
+                              "type": "string",
+                              "description": "Only issues created on or after a date (YYYY-MM-DD or 30d)"
+                          },
+                          "closed_since": {
+                              "type": "string",
+                              "description": "Only issues closed on or after a date (YYYY-MM-DD or 30d); implies include_closed"
                           }
                       }
                   }
               },
               {
                   "name": "issues_context",
-                  "description": "Get current work context - in-progress, blocked, and priority tasks",
+                  "description": "Get current work context - in-progress, blocked, and priority tasks, with how many issues each unblocks. Deferred issues are left out, and each section is ranked by priority then issues unblocked and capped, with the cut counts in omitted. With daily_capacity configured, budget compares the estimated effort of in-progress and ready issues against it and sets overcommitted",
                   "inputSchema": {
                       "type": "object",
-                      "properties": {}
+                      "properties": {
+                          "tags": {
+                              "type": "array",
+                              "items": {"type": "string"},
+                              "description": "Only issues with any of these tags"
+                          },
+                          "milestone": {
+                              "type": "string",
+                              "description": "Only issues in this milestone"
+                          },
+                          "max_items": {
+                              "type": "number",
+                              "description": "Most issues per section (default: all, and 5 ready to start)"
+                          }
+                      }
                   }
               },
               {
@@ -119,20 +599,89 @@ impl SimpleMcpServer {
                               "type": "string",
                               "description": "Priority level",
                               "enum": ["critical", "high", "medium", "low"]
+                          },
+                          "milestone": {
+                              "type": "string",
+                              "description": "Milestone this issue belongs to"
+                          },
+                          "kind": {
+                              "type": "string",
+                              "description": "What sort of work this is, one of the configured kinds (default: bug, feature, chore, spike); empty sections start from its template"
+                          },
+                          "tags": {
+                              "type": "array",
+                              "items": {"type": "string"},
+                              "description": "Tags for the issue"
+                          },
+                          "effort": {
+                              "type": "string",
+                              "description": "Effort estimate (e.g. '30m', '2h', '1d')"
                           }
                       },
                       "required": ["title", "issue", "impact", "acceptance"]
                   }
               },
+              {
+                  "name": "issues_info",
+                  "description": "Which project the server keeps issues for: the root directory, the issues directory, whether they came from the client's MCP roots, the config's issues_location or the working directory, and the client's roots",
+                  "inputSchema": {
+                      "type": "object",
+                      "properties": {}
+                  }
+              },
+              {
+                  "name": "issues_draft",
+                  "description": "Draft a complete issue (title, priority, tags, issue, impact, acceptance, effort) from a rough one-line description, using the client's own model through MCP sampling. Creates nothing: confirm or edit the draft, then pass it to issues_create",
+                  "inputSchema": {
+                      "type": "object",
+                      "properties": {
+                          "description": {
+                              "type": "string",
+                              "description": "Rough description of the issue, e.g. 'login times out on slow networks'"
+                          }
+                      },
+                      "required": ["description"]
+                  }
+              },
+              {
+                  "name": "issues_path",
+                  "description": "Path of an issue's markdown file, to open or edit it directly",
+                  "inputSchema": {
+                      "type": "object",
+                      "properties": {
+                          "bug_ref": {
+                              "type": ["number", "string"],
+                              "description": "Bug number, alias, or title fragment"
+                          },
+                          "exact": {
+                              "type": "boolean",
+                              "description": "Only accept numbers and aliases, not title fragments (default: false)"
+                          }
+                      },
+                      "required": ["bug_ref"]
+                  }
+              },
               {
                   "name": "issues_show",
-                  "description": "Show full details of a specific issue",
+                  "description": "Show full details of a specific issue, including the path of its markdown file and its version (pass it back as expected_version to status and checkpoint to detect concurrent changes). With context_budget, returns the issue trimmed to about that many tokens instead",
                   "inputSchema": {
                       "type": "object",
                       "properties": {
                           "bug_ref": {
+                              "type": ["number", "string"],
+                              "description": "Bug number, alias, or title fragment"
+                          },
+                          "exact": {
+                              "type": "boolean",
+                              "description": "Only accept numbers and aliases, not title fragments (default: false)"
+                          },
+                          "context_budget": {
                               "type": "number",
-                              "description": "Bug reference number"
+                              "description": "Token budget: metadata and acceptance criteria in full, then the other sections and latest checkpoints cut to fit, with ellipsis markers"
+                          },
+                          "checkpoints": {
+                              "type": "number",
+                              "description": "Latest checkpoints to include with context_budget (default: 3)"
                           }
                       },
                       "required": ["bug_ref"]
@@ -145,8 +694,12 @@ impl SimpleMcpServer {
                       "type": "object",
                       "properties": {
                           "bug_ref": {
-                              "type": "number",
-                              "description": "Bug reference number"
+                              "type": ["number", "string"],
+                              "description": "Bug number, alias, or title fragment"
+                          },
+                          "exact": {
+                              "type": "boolean",
+                              "description": "Only accept numbers and aliases, not title fragments (default: false)"
                           },
                           "status": {
                               "type": "string",
@@ -156,11 +709,45 @@ impl SimpleMcpServer {
                           "reason": {
                               "type": "string",
                               "description": "Reason (required for 'block', optional for 'close')"
+                          },
+                          "force": {
+                              "type": "boolean",
+                              "description": "Allow a status change the transition rules refuse (default: false)"
+                          },
+                          "agent": {
+                              "type": "string",
+                              "description": "Name of the agent making the call, checked against claims (default: $AGENTX_AGENT or the git user)"
+                          },
+                          "auto_unblock": {
+                              "type": "boolean",
+                              "description": "On 'close', also unblock blocked issues that were waiting on this one; otherwise they are listed in unblock_candidates (default: false)"
+                          },
+                          "expected_version": {
+                              "type": "string",
+                              "description": "The version from the issue's last read; the change fails with a version_conflict error if the issue changed since"
                           }
                       },
                       "required": ["bug_ref", "status"]
                   }
               },
+              {
+                  "name": "issues_conflicts",
+                  "description": "List other in-progress issues whose files overlap this issue's, to avoid editing the same files as another agent",
+                  "inputSchema": {
+                      "type": "object",
+                      "properties": {
+                          "bug_ref": {
+                              "type": ["number", "string"],
+                              "description": "Bug number, alias, or title fragment"
+                          },
+                          "exact": {
+                              "type": "boolean",
+                              "description": "Only accept numbers and aliases, not title fragments (default: false)"
+                          }
+                      },
+                      "required": ["bug_ref"]
+                  }
+              },
               {
                   "name": "issues_checkpoint",
                   "description": "Add a progress checkpoint note to an issue",
@@ -168,20 +755,88 @@ impl SimpleMcpServer {
                       "type": "object",
                       "properties": {
                           "bug_ref": {
-                              "type": "number",
-                              "description": "Bug reference number"
+                              "type": ["number", "string"],
+                              "description": "Bug number, alias, or title fragment"
+                          },
+                          "exact": {
+                              "type": "boolean",
+                              "description": "Only accept numbers and aliases, not title fragments (default: false)"
                           },
                           "note": {
                               "type": "string",
                               "description": "Progress note (prefix with BLOCKED: or DONE: to auto-update status)"
+                          },
+                          "agent": {
+                              "type": "string",
+                              "description": "Name of the agent making the call, checked against claims (default: $AGENTX_AGENT or the git user)"
+                          },
+                          "expected_version": {
+                              "type": "string",
+                              "description": "The version from the issue's last read; the change fails with a version_conflict error if the issue changed since"
                           }
                       },
                       "required": ["bug_ref", "note"]
                   }
               },
+              {
+                  "name": "issues_claim",
+                  "description": "Claim an issue for an agent with a lease that expires after ttl; while it is live, other agents can't start or checkpoint the issue. Claiming again renews the lease",
+                  "inputSchema": {
+                      "type": "object",
+                      "properties": {
+                          "bug_ref": {
+                              "type": ["number", "string"],
+                              "description": "Bug number, alias, or title fragment"
+                          },
+                          "exact": {
+                              "type": "boolean",
+                              "description": "Only accept numbers and aliases, not title fragments (default: false)"
+                          },
+                          "agent": {
+                              "type": "string",
+                              "description": "Name of the agent making the call, checked against claims (default: $AGENTX_AGENT or the git user)"
+                          },
+                          "ttl": {
+                              "type": "string",
+                              "description": "How long the lease lasts (e.g., '30m', '2h', '1d'). Default: '2h'"
+                          },
+                          "force": {
+                              "type": "boolean",
+                              "description": "Take over another agent's live claim (default: false)"
+                          }
+                      },
+                      "required": ["bug_ref"]
+                  }
+              },
+              {
+                  "name": "issues_release",
+                  "description": "Release an agent's claim on an issue",
+                  "inputSchema": {
+                      "type": "object",
+                      "properties": {
+                          "bug_ref": {
+                              "type": ["number", "string"],
+                              "description": "Bug number, alias, or title fragment"
+                          },
+                          "exact": {
+                              "type": "boolean",
+                              "description": "Only accept numbers and aliases, not title fragments (default: false)"
+                          },
+                          "agent": {
+                              "type": "string",
+                              "description": "Name of the agent making the call, checked against claims (default: $AGENTX_AGENT or the git user)"
+                          },
+                          "force": {
+                              "type": "boolean",
+                              "description": "Release another agent's live claim (default: false)"
+                          }
+                      },
+                      "required": ["bug_ref"]
+                  }
+              },
               {
                   "name": "issues_search",
-                  "description": "Full-text search across issue titles and bodies",
+                  "description": "Full-text search across issue titles, tags and bodies; every word must match, ranked title > tag > body, with a body snippet per hit",
                   "inputSchema": {
                       "type": "object",
                       "properties": {
@@ -189,9 +844,29 @@ impl SimpleMcpServer {
                               "type": "string",
                               "description": "Search query (case-insensitive)"
                           },
+                          "limit": {
+                              "type": "integer",
+                              "description": "Most results to return (default: all)"
+                          },
                           "status": {
                               "type": "string",
-                              "description": "Filter by status: 'open', 'closed', or 'all' (default: 'open')"
+                              "description": "Filter by status: 'open' (not closed), 'closed', 'all', or a single status such as 'active' or 'blocked' (default: 'open')"
+                          },
+                          "include_closed": {
+                              "type": "boolean",
+                              "description": "Also include closed issues (closed issues are the archive)"
+                          },
+                          "include_archived": {
+                              "type": "boolean",
+                              "description": "Alias for include_closed"
+                          },
+                          "created_since": {
+                              "type": "string",
+                              "description": "Only issues created on or after a date (YYYY-MM-DD or 30d)"
+                          },
+                          "closed_since": {
+                              "type": "string",
+                              "description": "Only issues closed on or after a date (YYYY-MM-DD or 30d); implies include_closed"
                           }
                       },
                       "required": ["query"]
@@ -199,7 +874,7 @@ impl SimpleMcpServer {
               },
               {
                   "name": "issues_query",
-                  "description": "Advanced query with filters for tags, priority, and status",
+                  "description": "Advanced query with filters for tags, priority, status, and files",
                   "inputSchema": {
                       "type": "object",
                       "properties": {
@@ -215,40 +890,239 @@ impl SimpleMcpServer {
                           },
                           "status": {
                               "type": "string",
-                              "description": "Filter by status",
-                              "enum": ["open", "in_progress", "blocked", "backlog", "closed"]
+                              "description": "Filter by status; synonyms such as 'in_progress', 'not_started' and 'deferred' are accepted",
+                              "enum": ["open", "active", "blocked", "done", "backlog", "closed"]
+                          },
+                          "file_glob": {
+                              "type": "string",
+                              "description": "Only issues with a file matching this glob, relative to the repository root (e.g. 'src/tui/**')"
+                          },
+                          "file_regex": {
+                              "type": "string",
+                              "description": "Only issues with a file matching this regex"
                           }
                       }
                   }
               },
+              {
+                  "name": "issues_query_expr",
+                  "description": "Filter issues with a query expression, e.g. 'status:open AND (priority>=high OR tag:security) AND effort<2h AND created>-30d'. Fields: status, priority, tag, file, file_glob, file_regex, title, effort, id, created, started, closed; operators : = != < <= > >=; AND/OR/NOT, parentheses, '-' negation; bare words search title and body",
+                  "inputSchema": {
+                      "type": "object",
+                      "properties": {
+                          "expr": {
+                              "type": "string",
+                              "description": "Query expression"
+                          },
+                          "status": {
+                              "type": "string",
+                              "description": "Which issues to search: 'open' (not closed), 'closed', 'all', or a single status such as 'active' (default: 'open')"
+                          }
+                      },
+                      "required": ["expr"]
+                  }
+              },
+              {
+                  "name": "issues_resolve",
+                  "description": "Resolve a bug reference (number, alias, or title fragment) and list the issues whose titles match it",
+                  "inputSchema": {
+                      "type": "object",
+                      "properties": {
+                          "bug_ref": {
+                              "type": ["number", "string"],
+                              "description": "Bug number, alias, or title fragment"
+                          },
+                          "exact": {
+                              "type": "boolean",
+                              "description": "Only accept numbers and aliases, not title fragments (default: false)"
+                          }
+                      },
+                      "required": ["bug_ref"]
+                  }
+              },
               {
                   "name": "issues_wins",
-                  "description": "Find quick-win tasks based on effort estimate",
+                  "description": "Find quick-win tasks based on effort estimate, leaving out issues that wait on unfinished dependencies; ranked by priority and how many issues each unblocks unless sorted otherwise",
                   "inputSchema": {
                       "type": "object",
                       "properties": {
                           "threshold": {
                               "type": "string",
                               "description": "Maximum effort threshold (e.g., '30m', '1h', '2h'). Default: '1h'"
+                          },
+                          "only_ready": {
+                              "type": "boolean",
+                              "description": "Only issues not started yet. Default: false"
+                          },
+                          "sort": {
+                              "type": "string",
+                              "enum": ["impact", "effort", "age"],
+                              "description": "impact: priority boosted by issues unblocked; effort: smallest first; age: longest untouched first. Default: 'impact'"
+                          }
+                      }
+                  }
+              },
+              {
+                  "name": "issues_import",
+                  "description": "Create many issues from a YAML list (external_id, title, priority, kind, tags, files, issue, impact, acceptance, effort, context, milestone per item); an item whose external_id an issue already has updates that issue instead of creating one. The whole list is checked first, reporting each problem with its line and column, and the issues are created all or none; dry_run only lists what would be created. Honors notifications/cancelled: a cancelled import stops between issues and removes the ones it created",
+                  "inputSchema": {
+                      "type": "object",
+                      "properties": {
+                          "yaml": {
+                              "type": "string",
+                              "description": "YAML list of issues, as `agentx import` reads"
+                          },
+                          "dry_run": {
+                              "type": "boolean",
+                              "description": "Only check the list and report what would be created"
+                          }
+                      },
+                      "required": ["yaml"]
+                  }
+              },
+              {
+                  "name": "issues_batch",
+                  "description": "Run newline-delimited JSON commands (e.g. {\"op\":\"close\",\"ref\":\"12\"}) as one transaction, as `agentx batch` does: a failing command or a cancellation (notifications/cancelled) rolls back the ones before it",
+                  "inputSchema": {
+                      "type": "object",
+                      "properties": {
+                          "commands": {
+                              "type": "string",
+                              "description": "One JSON command per line; ops are create, show, start, block, close, open, defer, activate, checkpoint, depend, claim and release"
+                          }
+                      },
+                      "required": ["commands"]
+                  }
+              },
+              {
+                  "name": "issues_export",
+                  "description": "Export the issues issues_list would return as one JSON, YAML, markdown or CSV document, to snapshot the tracker into docs or hand-offs. Long documents come in chunks: call again with the next chunk until chunk + 1 == chunks",
+                  "inputSchema": {
+                      "type": "object",
+                      "properties": {
+                          "format": {
+                              "type": "string",
+                              "enum": ["json", "yaml", "markdown", "csv"],
+                              "description": "Document format (default: 'json')"
+                          },
+                          "status": {
+                              "type": "string",
+                              "description": "Filter by status: 'open' (not closed), 'closed', 'all', or a single status such as 'active' or 'blocked' (default: 'open')"
+                          },
+                          "where": {
+                              "type": "string",
+                              "description": "Query expression to filter with (e.g. 'priority>=high tag:api')"
+                          },
+                          "include_closed": {
+                              "type": "boolean",
+                              "description": "Also include closed issues (closed issues are the archive)"
+                          },
+                          "created_since": {
+                              "type": "string",
+                              "description": "Only issues created on or after a date (YYYY-MM-DD or 30d)"
+                          },
+                          "closed_since": {
+                              "type": "string",
+                              "description": "Only issues closed on or after a date (YYYY-MM-DD or 30d); implies include_closed"
+                          },
+                          "chunk": {
+                              "type": "number",
+                              "description": "Which chunk of the document to return, from 0 (default: 0)"
+                          }
+                      }
+                  }
+              },
+              {
+                  "name": "issues_metrics_series",
+                  "description": "Daily or weekly counts of issues opened, closed, open at the end of each period (net_open) and blocked, as an array of points for plotting burndown or spotting trends",
+                  "inputSchema": {
+                      "type": "object",
+                      "properties": {
+                          "from": {
+                              "type": "string",
+                              "description": "Start: a date (YYYY-MM-DD), an age like '30d', or a git revision. Default: 30 days (or 12 weeks) before 'to'"
+                          },
+                          "to": {
+                              "type": "string",
+                              "description": "End, in the same forms as 'from'. Default: now"
+                          },
+                          "granularity": {
+                              "type": "string",
+                              "enum": ["day", "week"],
+                              "description": "Width of each point; weeks start on Monday. Default: 'day'"
                           }
                       }
                   }
               }
           ]
-      })
+      });
+      for tool in list["tools"].as_array_mut().into_iter().flatten() {
+         let name = tool["name"].as_str().unwrap_or_default().to_string();
+         tool["annotations"] = annotations(&name);
+      }
+      list
+   }
+
+   /// The tracker, crediting its changes to the client through `tool`
+   fn tracker_for(&self, tool: &str) -> IssueTracker {
+      let via = format!("mcp:{tool}");
+      self
+         .tracker()
+         .with_actor(Actor::resolve(self.client.get().map(String::as_str), Some(&via)))
    }
 
-   async fn handle_tool_call(&self, params: &Value) -> Value {
+   /// Add the issue's current `version` to a tool result naming it by
+   /// `num` or `bug_num`
+   fn with_version(&self, mut value: Value) -> Value {
+      let num = value
+         .get("num")
+         .or_else(|| value.get("bug_num"))
+         .and_then(Value::as_u64);
+      if let (Some(num), Value::Object(map)) = (num, &mut value)
+         && let Ok(version) = self.tracker().issue_version(num as u32)
+      {
+         map.insert("version".to_string(), json!(version));
+      }
+      value
+   }
+
+   async fn handle_tool_call(&self, params: &Value, cancel: &CancelToken) -> Value {
       let name = params["name"].as_str().unwrap_or("");
       let arguments = &params["arguments"];
 
       let result = match name {
+         _ if self.project().read_only && MUTATING_TOOLS.contains(&name) => {
+            Err(ReadOnlyError.into())
+         },
          "issues_list" => {
             let status = arguments["status"].as_str().unwrap_or("open");
-            self.commands.list_data(status).map(|r| serde_json::to_value(r).unwrap_or_else(|_| json!({"error": "serialization failed"})))
+            let scope = list_scope_arg(arguments);
+            scope
+               .filter(None)
+               .and_then(|filter| {
+                  self
+                     .tracker()
+                     .list_data(scope.status(status), filter.as_ref())
+               })
+               .map(list_result_json)
          },
          "issues_context" => {
-            self.commands.context_data().map(|r| serde_json::to_value(r).unwrap_or_else(|_| json!({"error": "serialization failed"})))
+            let tags: Vec<String> = arguments["tags"]
+               .as_array()
+               .map(|arr| {
+                  arr.iter()
+                     .filter_map(|v| v.as_str().map(String::from))
+                     .collect()
+               })
+               .unwrap_or_default();
+            let scope = ContextScope {
+               tags,
+               milestone: arguments["milestone"].as_str().map(String::from),
+               max_items: arguments["max_items"].as_u64().map(|n| n as usize),
+            };
+            self.tracker().context_data(&scope).map(|r| {
+               serde_json::to_value(r).unwrap_or_else(|_| json!({"error": "serialization failed"}))
+            })
          },
          "issues_create" => {
             let title = arguments["title"].as_str().unwrap_or("");
@@ -256,49 +1130,147 @@ impl SimpleMcpServer {
             let impact = arguments["impact"].as_str().unwrap_or("");
             let acceptance = arguments["acceptance"].as_str().unwrap_or("");
             let priority = arguments["priority"].as_str().unwrap_or("medium");
+            let milestone = arguments["milestone"].as_str().map(|s| s.to_string());
+            let kind = arguments["kind"].as_str().map(String::from);
+            let tags: Vec<String> = arguments["tags"]
+               .as_array()
+               .map(|arr| {
+                  arr.iter()
+                     .filter_map(|v| v.as_str().map(String::from))
+                     .collect()
+               })
+               .unwrap_or_default();
+            let effort = arguments["effort"].as_str().map(String::from);
 
-            self.commands.create_issue_data(
-               title.to_string(),
-               priority,
-               vec![],
-               vec![],
-               issue.to_string(),
-               impact.to_string(),
-               acceptance.to_string(),
-               None,
-               None,
-            ).map(|r| serde_json::to_value(r).unwrap_or_else(|_| json!({"error": "serialization failed"})))
-         },
-         "issues_show" => {
-            let bug_ref = arguments["bug_ref"].as_u64().map(|n| n.to_string()).unwrap_or_default();
-            self.commands.show_data(&bug_ref).map(|r| serde_json::to_value(r).unwrap_or_else(|_| json!({"error": "serialization failed"})))
+            self
+               .tracker_for(name)
+               .create_issue_data(
+                  title.to_string(),
+                  priority,
+                  tags,
+                  vec![],
+                  issue.to_string(),
+                  impact.to_string(),
+                  acceptance.to_string(),
+                  effort,
+                  None,
+                  milestone,
+                  kind,
+               )
+               .map(|r| {
+                  self.with_version(
+                     serde_json::to_value(r)
+                        .unwrap_or_else(|_| json!({"error": "serialization failed"})),
+                  )
+               })
          },
+         "issues_show" => self
+            .resolve_bug_ref_arg(arguments)
+            .and_then(|num| self.tracker().show_data(&num.to_string()))
+            .and_then(|r| {
+               if crypt::is_encrypted(&r.body) {
+                  anyhow::bail!(
+                     "Issue #{} is encrypted; run `agentx crypt unlock` to serve it over MCP",
+                     r.num
+                  );
+               }
+               Ok(r)
+            })
+            .and_then(|r| match arguments["context_budget"].as_u64() {
+               Some(budget) => {
+                  let checkpoints = arguments["checkpoints"].as_u64().unwrap_or(3) as usize;
+                  let brief =
+                     self
+                        .tracker()
+                        .brief_data(&r.num.to_string(), budget as usize, checkpoints)?;
+                  Ok(serde_json::to_value(brief)?)
+               },
+               None => Ok(serde_json::to_value(r)?),
+            })
+            .map(|value| self.with_version(value)),
+         "issues_path" => self
+            .resolve_bug_ref_arg(arguments)
+            .and_then(|num| self.tracker().path_data(&num.to_string()))
+            .map(|(bug_num, path)| json!({"bug_num": bug_num, "path": path})),
          "issues_status" => {
-            let bug_ref = arguments["bug_ref"].as_u64().map(|n| n.to_string()).unwrap_or_default();
             let status = arguments["status"].as_str().unwrap_or("");
             let reason = arguments["reason"].as_str().map(|s| s.to_string());
+            let force = arguments["force"].as_bool().unwrap_or(false);
+            let auto_unblock = arguments["auto_unblock"].as_bool().unwrap_or(false);
+            let tracker = self
+               .tracker_for(name)
+               .with_force(force)
+               .with_agent(arguments["agent"].as_str());
 
-            let data_result = match status {
-               "start" => self.commands.start_data(&bug_ref),
-               "block" => self.commands.block_data(&bug_ref, reason.unwrap_or_default()),
-               "done" | "close" => self.commands.close_data(&bug_ref, reason),
-               "reopen" => self.commands.open_data(&bug_ref),
-               "defer" => self.commands.defer_data(&bug_ref),
-               "activate" => self.commands.activate_data(&bug_ref),
-               _ => Err(anyhow::anyhow!("Unknown status: {}", status)),
-            };
+            let expected = arguments["expected_version"].as_str();
+
+            let data_result = self.resolve_bug_ref_arg(arguments).and_then(|num| {
+               let bug_ref = num.to_string();
+               tracker.with_expected_version(num, expected, || match status {
+                  "start" => tracker.start_data(&bug_ref),
+                  "block" => tracker.block_data(&bug_ref, reason.unwrap_or_default()),
+                  "done" | "close" => tracker.close_data(&bug_ref, reason, auto_unblock),
+                  "reopen" => tracker.open_data(&bug_ref),
+                  "defer" => tracker.defer_data(&bug_ref),
+                  "activate" => tracker.activate_data(&bug_ref),
+                  _ => Err(anyhow::anyhow!("Unknown status: {}", status)),
+               })
+            });
 
-            data_result.map(|r| serde_json::to_value(r).unwrap_or_else(|_| json!({"error": "serialization failed"})))
+            data_result.map(|r| {
+               self.with_version(
+                  serde_json::to_value(r)
+                     .unwrap_or_else(|_| json!({"error": "serialization failed"})),
+               )
+            })
          },
+         "issues_conflicts" => self
+            .resolve_bug_ref_arg(arguments)
+            .and_then(|num| self.tracker().file_conflicts_data(&num.to_string()))
+            .map(|conflicts| json!({"conflicts": conflicts})),
          "issues_checkpoint" => {
-            let bug_ref = arguments["bug_ref"].as_u64().map(|n| n.to_string()).unwrap_or_default();
             let note = arguments["note"].as_str().unwrap_or("");
-            self.commands.checkpoint_data(&bug_ref, note.to_string()).map(|r| serde_json::to_value(r).unwrap_or_else(|_| json!({"error": "serialization failed"})))
+            let tracker = self
+               .tracker_for(name)
+               .with_agent(arguments["agent"].as_str());
+            let expected = arguments["expected_version"].as_str();
+            self
+               .resolve_bug_ref_arg(arguments)
+               .and_then(|num| {
+                  tracker.with_expected_version(num, expected, || {
+                     tracker.checkpoint_data(&num.to_string(), note.to_string())
+                  })
+               })
+               .map(|r| {
+                  self.with_version(
+                     serde_json::to_value(r)
+                        .unwrap_or_else(|_| json!({"error": "serialization failed"})),
+                  )
+               })
+         },
+         "issues_claim" | "issues_release" => {
+            let tracker = self
+               .tracker_for(name)
+               .with_force(arguments["force"].as_bool().unwrap_or(false))
+               .with_agent(arguments["agent"].as_str());
+            let ttl = arguments["ttl"].as_str().unwrap_or("2h");
+            self
+               .resolve_bug_ref_arg(arguments)
+               .and_then(|num| match name {
+                  "issues_claim" => tracker.claim_data(&num.to_string(), ttl),
+                  _ => tracker.release_data(&num.to_string()),
+               })
+               .map(|r| {
+                  serde_json::to_value(r)
+                     .unwrap_or_else(|_| json!({"error": "serialization failed"}))
+               })
          },
          "issues_search" => {
             let query = arguments["query"].as_str().unwrap_or("");
             let status = arguments["status"].as_str().unwrap_or("open");
-            Ok(json!({"result": self.search_issues(query, status)}))
+            let scope = list_scope_arg(arguments);
+            let limit = arguments["limit"].as_u64().map(|n| n as usize);
+            Ok(json!({"result": self.search_issues(query, status, &scope, limit)}))
          },
          "issues_query" => {
             let tags: Vec<String> = arguments["tags"]
@@ -311,11 +1283,103 @@ impl SimpleMcpServer {
                .unwrap_or_default();
             let priority = arguments["priority"].as_str();
             let status = arguments["status"].as_str();
-            Ok(json!({"result": self.query_issues(&tags, priority, status)}))
+            file_matcher_arg(arguments).and_then(|files| {
+               self
+                  .query_issues(&tags, priority, status, &files)
+                  .map(|result| json!({"result": result}))
+            })
+         },
+         "issues_query_expr" => {
+            let expr = arguments["expr"].as_str().unwrap_or("");
+            let status = arguments["status"].as_str().unwrap_or("open");
+            Query::parse(expr)
+               .and_then(|query| self.tracker().list_data(status, Some(&query)))
+               .map(list_result_json)
+         },
+         "issues_resolve" => {
+            let exact = arguments["exact"].as_bool().unwrap_or(false);
+            let resolved = self.resolve_bug_ref_arg(arguments).ok();
+            let candidates = match (&arguments["bug_ref"], exact) {
+               (Value::String(fragment), false) => self.tracker().ref_candidates_data(fragment),
+               _ => Ok(vec![]),
+            };
+            candidates.map(|candidates| json!({"resolved": resolved, "candidates": candidates}))
+         },
+         "issues_export" => {
+            let status = arguments["status"].as_str().unwrap_or("open");
+            let index = arguments["chunk"].as_u64().unwrap_or(0) as usize;
+            export::parse_format(arguments["format"].as_str().unwrap_or("json"))
+               .and_then(|format| {
+                  self.tracker().export_data(
+                     status,
+                     &list_scope_arg(arguments),
+                     arguments["where"].as_str(),
+                     format,
+                  )
+               })
+               .and_then(|r| {
+                  let Some((text, chunks)) = export::chunk(&r.text, CHUNK_BYTES, index) else {
+                     anyhow::bail!("No chunk {index}: the export has fewer chunks");
+                  };
+                  Ok(json!({
+                     "format": r.format,
+                     "mime_type": r.mime_type,
+                     "count": r.count,
+                     "chunk": index,
+                     "chunks": chunks,
+                     "text": text,
+                  }))
+               })
+         },
+         "issues_info" => Ok(self.info()),
+         "issues_draft" => {
+            self
+               .draft_issue(arguments["description"].as_str().unwrap_or(""))
+               .await
          },
+         "issues_import" => self
+            .tracker_for(name)
+            .with_cancel(cancel.clone())
+            .import_data(
+               arguments["yaml"].as_str().unwrap_or(""),
+               arguments["dry_run"].as_bool().unwrap_or(false),
+            )
+            .and_then(|r| {
+               if r.cancelled {
+                  self.log(
+                     WARNING,
+                     format!("issues_import cancelled: created {}, skipped {}", r.count, r.skipped),
+                  );
+               }
+               Ok(serde_json::to_value(r)?)
+            }),
+         "issues_batch" => self
+            .tracker_for(name)
+            .with_cancel(cancel.clone())
+            .batch_data(arguments["commands"].as_str().unwrap_or(""))
+            .map(|results| {
+               let cancelled = results
+                  .iter()
+                  .any(|r| r.error.as_deref() == Some("not run: cancelled"));
+               if cancelled {
+                  self.log(WARNING, "issues_batch cancelled: nothing was changed".to_string());
+               }
+               let applied = results.iter().all(|r| r.ok);
+               json!({"applied": applied, "results": results})
+            }),
+         "issues_metrics_series" => self
+            .tracker()
+            .metrics_series_data(
+               arguments["from"].as_str(),
+               arguments["to"].as_str(),
+               arguments["granularity"].as_str().unwrap_or("day"),
+            )
+            .and_then(|r| Ok(serde_json::to_value(r)?)),
          "issues_wins" => {
             let threshold = arguments["threshold"].as_str().unwrap_or("1h");
-            Ok(json!({"result": self.find_quick_wins(threshold)}))
+            let only_ready = arguments["only_ready"].as_bool().unwrap_or(false);
+            let sort = arguments["sort"].as_str().unwrap_or("impact");
+            Ok(json!({"result": self.find_quick_wins(threshold, only_ready, sort)}))
          },
          _ => Err(anyhow::anyhow!("Unknown tool: {}", name)),
       };
@@ -327,114 +1391,125 @@ impl SimpleMcpServer {
                "text": serde_json::to_string_pretty(&data).unwrap_or_else(|_| "{}".to_string())
             }]
          }),
-         Err(e) => json!({
-            "content": [{
-               "type": "text",
-               "text": format!("Error: {}", e)
-            }],
-            "isError": true
-         }),
+         Err(e) => {
+            let structured = e
+               .downcast_ref::<IssueConflict>()
+               .map(IssueConflict::to_json)
+               .or_else(|| {
+                  e.downcast_ref::<VersionConflict>()
+                     .map(VersionConflict::to_json)
+               });
+            let text = match structured {
+               Some(error) => {
+                  serde_json::to_string_pretty(&error).unwrap_or_else(|_| format!("Error: {}", e))
+               },
+               None => format!("Error: {}", e),
+            };
+            json!({
+               "content": [{
+                  "type": "text",
+                  "text": text
+               }],
+               "isError": true
+            })
+         },
       }
    }
 
-   fn search_issues(&self, query: &str, status_filter: &str) -> String {
-      let query_lower = query.to_lowercase();
-      let config = Config::load();
-      let issues_dir = config.resolve_issues_directory();
-      let storage = Storage::new(issues_dir);
-
-      let issues: Vec<IssueWithId> = match status_filter {
-         "open" => storage.list_open_issues().unwrap_or_default(),
-         "closed" => storage.list_closed_issues().unwrap_or_default(),
-         "all" => {
-            let mut all = storage.list_open_issues().unwrap_or_default();
-            all.extend(storage.list_closed_issues().unwrap_or_default());
-            all
-         },
-         _ => storage.list_open_issues().unwrap_or_default(),
+   /// Resolve the `bug_ref` argument (number, alias, or title fragment) to an
+   /// issue number, honouring the optional `exact` flag
+   fn resolve_bug_ref_arg(&self, arguments: &Value) -> Result<u32> {
+      let bug_ref = match &arguments["bug_ref"] {
+         Value::Number(n) => n.to_string(),
+         Value::String(s) => s.clone(),
+         _ => anyhow::bail!("bug_ref is required"),
+      };
+      let mode = if arguments["exact"].as_bool().unwrap_or(false) {
+         RefMode::Exact
+      } else {
+         RefMode::Fuzzy
       };
 
-      let matches: Vec<_> = issues
-         .into_iter()
-         .filter(|issue| {
-            issue
-               .issue
-               .metadata
-               .title
-               .to_lowercase()
-               .contains(&query_lower)
-               || issue.issue.body.to_lowercase().contains(&query_lower)
-         })
-         .collect();
-
-      let results: Vec<_> = matches
-         .iter()
-         .map(|issue| {
-            json!({
-                "num": issue.id,
-                "title": issue.issue.metadata.title,
-                "priority": issue.issue.metadata.priority.to_string(),
-                "status": issue.issue.metadata.status.to_string(),
-            })
-         })
-         .collect();
+      self
+         .tracker()
+         .clone()
+         .with_ref_mode(mode)
+         .resolve_ref(&bug_ref)
+   }
 
-      serde_json::to_string_pretty(&json!({
-          "query": query,
-          "count": results.len(),
-          "results": results,
-      }))
-      .unwrap_or_else(|e| format!("Error: {}", e))
+   fn search_issues(
+      &self,
+      query: &str,
+      status_filter: &str,
+      scope: &ListScope,
+      limit: Option<usize>,
+   ) -> String {
+      let status_filter = match status_filter {
+         status @ ("open" | "closed" | "all") => status,
+         _ => "open",
+      };
+      match self
+         .tracker()
+         .search_data(query, status_filter, scope, limit)
+      {
+         Ok(result) => {
+            serde_json::to_string_pretty(&result).unwrap_or_else(|e| format!("Error: {}", e))
+         },
+         Err(e) => format!("Error: {}", e),
+      }
    }
 
-   fn query_issues(&self, tags: &[String], priority: Option<&str>, status: Option<&str>) -> String {
-      let config = Config::load();
-      let issues_dir = config.resolve_issues_directory();
-      let storage = Storage::new(issues_dir);
+   fn query_issues(
+      &self,
+      tags: &[String],
+      priority: Option<&str>,
+      status: Option<&str>,
+      files: &[FileMatcher],
+   ) -> Result<String> {
+      let tracker = self.tracker();
+      let storage = tracker.storage();
+      let status = status.map(Status::parse).transpose()?;
 
-      let mut issues = storage.list_open_issues().unwrap_or_default();
+      let mut issues = if status == Some(Status::Closed) {
+         storage.list_closed_issues().unwrap_or_default()
+      } else {
+         storage.list_open_issues().unwrap_or_default()
+      };
 
       if !tags.is_empty() {
          issues = filter_by_tags(issues, tags);
       }
 
+      for matcher in files {
+         issues.retain(|issue| matcher.matches(&issue.issue.metadata.files));
+      }
+
       if let Some(p) = priority {
          issues.retain(|issue| {
             issue.issue.metadata.priority.to_string().to_lowercase() == p.to_lowercase()
          });
       }
 
-      if let Some(s) = status {
-         issues.retain(|issue| {
-            let status_str = match s {
-               "open" => "not_started",
-               "in_progress" => "in_progress",
-               "blocked" => "blocked",
-               "backlog" => "backlog",
-               "closed" => "closed",
-               _ => s,
-            };
-            issue.issue.metadata.status.to_string().to_lowercase() == status_str.to_lowercase()
-               || issue
-                  .issue
-                  .metadata
-                  .status
-                  .to_string()
-                  .to_lowercase()
-                  .replace('_', " ")
-                  == s.to_lowercase().replace('_', " ")
-         });
+      if let Some(status) = status {
+         issues.retain(|issue| issue.issue.metadata.status == status);
       }
+      issues.sort_by_key(IssueWithId::stable_key);
 
+      let now = Utc::now();
       let results: Vec<_> = issues
          .iter()
          .map(|issue| {
+            let meta = &issue.issue.metadata;
             json!({
                 "num": issue.id,
-                "title": issue.issue.metadata.title,
-                "priority": issue.issue.metadata.priority.to_string(),
-                "status": issue.issue.metadata.status.to_string(),
-                "tags": issue.issue.metadata.tags,
+                "title": meta.title,
+                "priority": meta.priority.to_string(),
+                "status": meta.status.to_string(),
+                "tags": meta.tags,
+                "path": storage.find_issue_file(issue.id).ok(),
+                "last_updated": meta.updated_at(),
+                "age": short_age(meta.created, now),
+                "updated_ago": short_age(meta.updated_at(), now),
             })
          })
          .collect();
@@ -443,53 +1518,23 @@ impl SimpleMcpServer {
           "filters": {
               "tags": tags,
               "priority": priority,
-              "status": status,
+              "status": status.map(|s| s.as_query_str()),
           },
           "count": results.len(),
           "results": results,
       }))
-      .unwrap_or_else(|e| format!("Error: {}", e))
+      .map_err(Into::into)
    }
 
-   fn find_quick_wins(&self, threshold: &str) -> String {
-      let threshold_minutes = match parse_effort(threshold) {
-         Ok(m) => m,
-         Err(e) => return format!("Error parsing threshold: {}", e),
+   fn find_quick_wins(&self, threshold: &str, only_ready: bool, sort: &str) -> String {
+      let results = match sort
+         .parse()
+         .and_then(|sort| self.tracker().quick_wins_data(threshold, only_ready, sort))
+      {
+         Ok(results) => results,
+         Err(e) => return format!("Error: {}", e),
       };
 
-      let config = Config::load();
-      let issues_dir = config.resolve_issues_directory();
-      let storage = Storage::new(issues_dir);
-
-      let issues = storage.list_open_issues().unwrap_or_default();
-
-      let quick: Vec<_> = issues
-         .into_iter()
-         .filter(|issue| {
-            issue
-               .issue
-               .metadata
-               .effort
-               .as_ref()
-               .and_then(|e| parse_effort(e).ok())
-               .map(|m| m <= threshold_minutes)
-               .unwrap_or(false)
-         })
-         .collect();
-
-      let results: Vec<_> = quick
-         .iter()
-         .map(|issue| {
-            json!({
-                "num": issue.id,
-                "title": issue.issue.metadata.title,
-                "priority": issue.issue.metadata.priority.to_string(),
-                "effort": issue.issue.metadata.effort,
-                "status": issue.issue.metadata.status.to_string(),
-            })
-         })
-         .collect();
-
       serde_json::to_string_pretty(&json!({
           "threshold": threshold,
           "count": results.len(),
@@ -498,50 +1543,227 @@ impl SimpleMcpServer {
       .unwrap_or_else(|e| format!("Error: {}", e))
    }
 
-   pub async fn serve_stdio() -> Result<()> {
+   /// Serve MCP on stdio, for the current project or with `global` the
+   /// global store
+   pub async fn serve_stdio(global: bool) -> Result<()> {
       eprintln!("Starting agentx MCP server on stdio...");
 
       let server = Self::new();
+      if global {
+         let project = Project::global(&server.events)?;
+         match server.project.write() {
+            Ok(mut current) => *current = project,
+            Err(poisoned) => *poisoned.into_inner() = project,
+         }
+      }
+      let server = Arc::new(server);
 
-      let stdin = tokio::io::stdin();
-      let mut stdin = BufReader::new(stdin);
-      let mut stdout = tokio::io::stdout();
-
-      let mut line = String::new();
+      // Requests are read on their own thread, so a cancellation can get
+      // through while a long tool call is running; they are still handled
+      // one at a time, in order
+      let (requests, mut received) = mpsc::unbounded_channel::<Value>();
+      let reader = Arc::clone(&server);
+      std::thread::spawn(move || {
+         for line in std::io::stdin().lock().lines() {
+            let line = match line {
+               Ok(line) => line,
+               Err(e) => {
+                  eprintln!("Error reading stdin: {}", e);
+                  break;
+               },
+            };
+            if line.trim().is_empty() {
+               continue;
+            }
 
-      loop {
-         line.clear();
+            match serde_json::from_str::<Value>(&line) {
+               Ok(request) if request["method"] == "notifications/cancelled" => {
+                  reader.cancel(&request["params"]["requestId"]);
+               },
+               // An answer to a request of ours, like sampling
+               Ok(response) if response.get("method").is_none() => reader.deliver(response),
+               Ok(request) => {
+                  reader.track(&request["id"]);
+                  if requests.send(request).is_err() {
+                     break;
+                  }
+               },
+               Err(e) => {
+                  eprintln!("Failed to parse JSON: {}", e);
+               },
+            }
+         }
+      });
 
-         match stdin.read_line(&mut line).await {
-            Ok(0) => break,
-            Ok(_) => {
-               if line.trim().is_empty() {
-                  continue;
-               }
+      let (outgoing, mut to_write) = mpsc::unbounded_channel::<Value>();
+      if let Ok(mut slot) = server.outgoing.lock() {
+         *slot = Some(outgoing);
+      }
+      let writer = tokio::spawn(async move {
+         let mut stdout = tokio::io::stdout();
+         while let Some(message) = to_write.recv().await {
+            stdout.write_all(format!("{message}\n").as_bytes()).await?;
+            stdout.flush().await?;
+         }
+         std::io::Result::Ok(())
+      });
 
-               match serde_json::from_str::<Value>(&line) {
-                  Ok(request) => {
-                     let response = server.handle_request(request).await;
-                     if !response.is_null()
-                        && let Ok(response_str) = serde_json::to_string(&response)
-                     {
-                        stdout.write_all(response_str.as_bytes()).await?;
-                        stdout.write_all(b"\n").await?;
-                        stdout.flush().await?;
-                     }
-                  },
-                  Err(e) => {
-                     eprintln!("Failed to parse JSON: {}", e);
-                  },
-               }
-            },
-            Err(e) => {
-               eprintln!("Error reading stdin: {}", e);
-               break;
-            },
+      while let Some(request) = received.recv().await {
+         let response = server.handle_request(request).await;
+         for log in server.take_logs() {
+            server.send(log);
+         }
+         if !response.is_null() {
+            server.send(response);
          }
       }
 
+      // Closing the channel lets the writer finish what is queued
+      if let Ok(mut slot) = server.outgoing.lock() {
+         slot.take();
+      }
+      writer.await??;
       Ok(())
    }
 }
+
+/// The local path a `file://` URI names, decoding `%XX` escapes
+fn path_from_uri(uri: &str) -> Option<PathBuf> {
+   let encoded = uri.strip_prefix("file://")?;
+   // `file://host/path`: only the local host is ours
+   let encoded = match encoded.find('/') {
+      Some(0) => encoded,
+      Some(slash) if &encoded[..slash] == "localhost" => &encoded[slash..],
+      _ => return None,
+   };
+   let bytes = encoded.as_bytes();
+   let mut decoded = Vec::with_capacity(bytes.len());
+   let mut i = 0;
+   while i < bytes.len() {
+      let escaped = (bytes[i] == b'%')
+         .then(|| encoded.get(i + 1..i + 3))
+         .flatten()
+         .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+      match escaped {
+         Some(byte) => {
+            decoded.push(byte);
+            i += 3;
+         },
+         None => {
+            decoded.push(bytes[i]);
+            i += 1;
+         },
+      }
+   }
+   let path = String::from_utf8(decoded).ok()?;
+   // `file:///C:/repo` on Windows
+   let path = match path.strip_prefix('/') {
+      Some(rest) if cfg!(windows) && rest.get(1..2) == Some(":") => rest,
+      _ => &path,
+   };
+   Some(PathBuf::from(path))
+}
+
+/// A list result with `age` and `updated_ago` (such as `12d`) added to each
+/// issue, so agents can spot stale work without comparing dates
+fn list_result_json(result: IssueListResult) -> Value {
+   let now = Utc::now();
+   let ages: Vec<(String, String)> = result
+      .issues
+      .iter()
+      .map(|i| {
+         let meta = &i.issue.metadata;
+         (short_age(meta.created, now), short_age(meta.updated_at(), now))
+      })
+      .collect();
+   let mut value =
+      serde_json::to_value(result).unwrap_or_else(|_| json!({"error": "serialization failed"}));
+   if let Some(issues) = value["issues"].as_array_mut() {
+      for (issue, (age, updated_ago)) in issues.iter_mut().zip(ages) {
+         issue["age"] = json!(age);
+         issue["updated_ago"] = json!(updated_ago);
+      }
+   }
+   value
+}
+
+/// Read the `include_closed`/`include_archived`/`created_since`/`closed_since`
+/// scoping arguments shared by the list and search tools
+/// Matchers for the `file_glob` and `file_regex` arguments; an issue must
+/// match all of them
+fn file_matcher_arg(arguments: &Value) -> Result<Vec<FileMatcher>> {
+   let root = repo_root().ok();
+   let mut matchers = Vec::new();
+   if let Some(glob) = arguments["file_glob"].as_str() {
+      matchers.push(FileMatcher::glob(glob, root.as_deref())?);
+   }
+   if let Some(re) = arguments["file_regex"].as_str() {
+      matchers.push(FileMatcher::regex(re, root.as_deref())?);
+   }
+   Ok(matchers)
+}
+
+fn list_scope_arg(arguments: &Value) -> ListScope {
+   ListScope {
+      include_closed: arguments["include_closed"].as_bool().unwrap_or(false)
+         || arguments["include_archived"].as_bool().unwrap_or(false),
+      created_since:  arguments["created_since"].as_str().map(String::from),
+      closed_since:   arguments["closed_since"].as_str().map(String::from),
+      file_glob:      arguments["file_glob"].as_str().map(String::from),
+      file_regex:     arguments["file_regex"].as_str().map(String::from),
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::api::NewIssue;
+
+   /// A server for a fresh project in a temporary directory
+   fn server() -> SimpleMcpServer {
+      let nanos = std::time::SystemTime::now()
+         .duration_since(std::time::UNIX_EPOCH)
+         .unwrap()
+         .as_nanos();
+      let dir = std::env::temp_dir().join(format!("agentx-mcp-{nanos}"));
+      std::fs::create_dir_all(&dir).unwrap();
+      let server = SimpleMcpServer::new();
+      *server.project.write().unwrap() = Project::open(dir, "cwd", &server.events);
+      server
+   }
+
+   async fn call(server: &SimpleMcpServer, tool: &str, arguments: Value) -> Value {
+      let params = json!({"name": tool, "arguments": arguments});
+      let result = server
+         .handle_tool_call(&params, &CancelToken::default())
+         .await;
+      assert!(result["isError"].is_null(), "{result}");
+      serde_json::from_str(result["content"][0]["text"].as_str().unwrap()).unwrap()
+   }
+
+   #[tokio::test]
+   async fn test_tools_take_status_synonyms() {
+      let server = server();
+      let tracker = server.tracker();
+      tracker.create(NewIssue::new("Design schema")).unwrap();
+      tracker.create(NewIssue::new("Write migration")).unwrap();
+      tracker.start("schema").unwrap();
+
+      for status in ["in_progress", "active", "In-Progress"] {
+         let listed = call(&server, "issues_list", json!({"status": status})).await;
+         assert_eq!(listed["count"], 1, "{status}");
+         assert_eq!(listed["issues"][0]["issue"]["metadata"]["status"], "active");
+      }
+      let listed =
+         call(&server, "issues_query_expr", json!({"expr": "priority:medium", "status": "wip"}))
+            .await;
+      assert_eq!(listed["count"], 1);
+
+      let params = json!({"name": "issues_list", "arguments": {"status": "someday"}});
+      let result = server
+         .handle_tool_call(&params, &CancelToken::default())
+         .await;
+      assert_eq!(result["isError"], true);
+      std::fs::remove_dir_all(server.project().root).unwrap();
+   }
+}
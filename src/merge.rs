@@ -0,0 +1,351 @@
+//! Three-way merge of two edits made to the same issue
+//!
+//! Frontmatter is merged field by field and the body section by section, so
+//! edits to different parts of an issue combine cleanly; only a part changed
+//! differently on both sides is a conflict.
+
+use std::{collections::HashMap, fmt};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use crate::{body::IssueBody, issue::Issue};
+
+/// Which side of a conflict to keep
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+   Mine,
+   Theirs,
+}
+
+/// A part of the issue that both sides changed differently
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeConflict {
+   /// Frontmatter field name, or `section <heading>`
+   pub part:   String,
+   /// Empty when that side deleted the part
+   pub mine:   String,
+   pub theirs: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Section {
+   key:  String,
+   text: String,
+}
+
+#[derive(Debug)]
+enum Slot {
+   Field { key: String, theirs: Option<Value> },
+   Section { index: usize, theirs: Option<String> },
+}
+
+/// Result of merging; apply [`Merge::resolve`] to pick sides for conflicts
+#[derive(Debug)]
+pub struct Merge {
+   meta:      Map<String, Value>,
+   sections:  Vec<Option<String>>,
+   slots:     Vec<Slot>,
+   conflicts: Vec<MergeConflict>,
+}
+
+/// Key each section by its heading label, language tag included, and how
+/// many sections with that label came before it (checkpoints repeat)
+fn split_sections(body: &IssueBody) -> Vec<Section> {
+   let mut seen: HashMap<String, usize> = HashMap::new();
+   body
+      .sections()
+      .iter()
+      .map(|section| {
+         let label = match &section.lang {
+            Some(lang) => format!("{} [{lang}]", section.kind.label()),
+            None => section.kind.label().to_string(),
+         };
+         let count = seen.entry(label.clone()).or_default();
+         let key = format!("{label}#{count}");
+         *count += 1;
+         Section { key, text: section.as_str().to_string() }
+      })
+      .collect()
+}
+
+fn section_name(key: &str) -> String {
+   let label = key.split('#').next().unwrap_or_default();
+   if label.is_empty() {
+      "section (preamble)".to_string()
+   } else {
+      format!("section {label}")
+   }
+}
+
+fn to_map(issue: &Issue) -> Result<Map<String, Value>> {
+   match serde_json::to_value(&issue.metadata)? {
+      Value::Object(map) => Ok(map),
+      _ => anyhow::bail!("Issue metadata is not an object"),
+   }
+}
+
+fn show_value(value: &Option<Value>) -> String {
+   match value {
+      None => String::new(),
+      Some(Value::String(s)) => s.clone(),
+      Some(other) => other.to_string(),
+   }
+}
+
+/// Combine the changes `mine` and `theirs` each made to `base`
+pub fn merge_issues(base: &Issue, mine: &Issue, theirs: &Issue) -> Result<Merge> {
+   let mut merge = Merge {
+      meta:      Map::new(),
+      sections:  Vec::new(),
+      slots:     Vec::new(),
+      conflicts: Vec::new(),
+   };
+
+   let (base_meta, mine_meta, theirs_meta) = (to_map(base)?, to_map(mine)?, to_map(theirs)?);
+   let mut keys: Vec<&String> = base_meta.keys().collect();
+   for key in mine_meta.keys().chain(theirs_meta.keys()) {
+      if !keys.contains(&key) {
+         keys.push(key);
+      }
+   }
+   for key in keys {
+      let (b, m, t) = (base_meta.get(key), mine_meta.get(key), theirs_meta.get(key));
+      let merged = if m == t || t == b {
+         m
+      } else if m == b {
+         t
+      } else if key == "last_updated" {
+         // RFC 3339 in UTC to the second, so the later one sorts last
+         std::cmp::max_by_key(m, t, |v| v.and_then(Value::as_str))
+      } else {
+         merge.conflicts.push(MergeConflict {
+            part:   key.clone(),
+            mine:   show_value(&m.cloned()),
+            theirs: show_value(&t.cloned()),
+         });
+         merge
+            .slots
+            .push(Slot::Field { key: key.clone(), theirs: t.cloned() });
+         m
+      };
+      if let Some(value) = merged {
+         merge.meta.insert(key.clone(), value.clone());
+      }
+   }
+
+   let (base_sections, mine_sections, theirs_sections) =
+      (split_sections(&base.body), split_sections(&mine.body), split_sections(&theirs.body));
+   let find = |sections: &[Section], key: &str| {
+      sections
+         .iter()
+         .find(|s| s.key == key)
+         .map(|s| s.text.clone())
+   };
+
+   // Trailing blank lines move around when sections are appended after them
+   let trimmed = |text: &Option<String>| text.as_deref().map(str::trim_end).map(str::to_string);
+   for section in &base_sections {
+      let b = Some(section.text.clone());
+      let (m, t) = (find(&mine_sections, &section.key), find(&theirs_sections, &section.key));
+      let (tb, tm, tt) = (trimmed(&b), trimmed(&m), trimmed(&t));
+      let merged = if tm == tt || tt == tb {
+         m
+      } else if tm == tb {
+         t
+      } else {
+         merge.conflicts.push(MergeConflict {
+            part:   section_name(&section.key),
+            mine:   m.clone().unwrap_or_default(),
+            theirs: t.clone().unwrap_or_default(),
+         });
+         merge
+            .slots
+            .push(Slot::Section { index: merge.sections.len(), theirs: t });
+         m
+      };
+      merge.sections.push(merged);
+   }
+
+   // Sections new on either side are kept, ours first; both sides appending a
+   // checkpoint is the common case and not a conflict
+   let is_new = |s: &&Section| !base_sections.iter().any(|b| b.key == s.key);
+   let added_mine: Vec<&Section> = mine_sections.iter().filter(is_new).collect();
+   for section in &added_mine {
+      merge.sections.push(Some(section.text.clone()));
+   }
+   for section in theirs_sections.iter().filter(is_new) {
+      if !added_mine.iter().any(|m| m.text == section.text) {
+         merge.sections.push(Some(section.text.clone()));
+      }
+   }
+
+   Ok(merge)
+}
+
+impl Merge {
+   pub fn conflicts(&self) -> &[MergeConflict] {
+      &self.conflicts
+   }
+
+   /// Build the merged issue, taking `choices[i]` for the i-th conflict
+   /// (`Mine` for any conflict without a choice)
+   pub fn resolve(mut self, choices: &[Side]) -> Result<Issue> {
+      for (i, slot) in self.slots.into_iter().enumerate() {
+         if choices.get(i) != Some(&Side::Theirs) {
+            continue;
+         }
+         match slot {
+            Slot::Field { key, theirs: Some(value) } => {
+               self.meta.insert(key, value);
+            },
+            Slot::Field { key, theirs: None } => {
+               self.meta.remove(&key);
+            },
+            Slot::Section { index, theirs } => self.sections[index] = theirs,
+         }
+      }
+
+      let mut body = String::new();
+      for text in self.sections.into_iter().flatten() {
+         if !body.is_empty() && !body.ends_with('\n') {
+            body.push('\n');
+         }
+         body.push_str(&text);
+      }
+      let metadata = serde_json::from_value(Value::Object(self.meta))?;
+      Ok(Issue { metadata, body: body.into() })
+   }
+}
+
+/// Another process saved an issue after we loaded it, and some of its changes
+/// overlap ours
+#[derive(Debug)]
+pub struct IssueConflict {
+   pub bug_num: u32,
+   pub is_open: bool,
+   /// Our version of the issue, as it would have been saved
+   pub mine:    Issue,
+   pub merge:   Merge,
+}
+
+impl IssueConflict {
+   /// Structured form for `--json` output and MCP tool errors
+   pub fn to_json(&self) -> Value {
+      json!({
+         "error": "conflict",
+         "bug_num": self.bug_num,
+         "message": self.to_string(),
+         "conflicts": self.merge.conflicts(),
+      })
+   }
+}
+
+impl fmt::Display for IssueConflict {
+   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      let parts: Vec<&str> = self
+         .merge
+         .conflicts()
+         .iter()
+         .map(|c| c.part.as_str())
+         .collect();
+      write!(
+         f,
+         "Issue #{} was changed by another process; conflicting changes to {}",
+         self.bug_num,
+         parts.join(", ")
+      )
+   }
+}
+
+impl std::error::Error for IssueConflict {}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::{
+      body::SectionKind,
+      issue::{Priority, Status, test_support::issue},
+   };
+
+   #[test]
+   fn test_merge_combines_separate_edits_and_flags_overlaps() {
+      let base = issue(1)
+         .title("Fix login")
+         .body("Times out")
+         .impact("Users locked out")
+         .acceptance("Login works")
+         .build()
+         .issue;
+
+      let mut mine = base.clone();
+      mine.metadata.priority = Priority::High;
+      mine.body.set(SectionKind::Impact, "All users locked out");
+      mine
+         .body
+         .append("\n\n## Checkpoint - 2024-01-01\n\nFound it");
+
+      let mut theirs = base.clone();
+      theirs.metadata.status = Status::InProgress;
+      theirs
+         .body
+         .set(SectionKind::Impact, "Some users locked out");
+      theirs
+         .body
+         .append("\n\n## Checkpoint - 2024-01-02\n\nLooking");
+
+      // Both sides saved: the later save time wins without a conflict
+      let day = |d| chrono::TimeZone::with_ymd_and_hms(&chrono::Utc, 2024, 1, d, 9, 0, 0).unwrap();
+      mine.metadata.last_updated = Some(day(1));
+      theirs.metadata.last_updated = Some(day(2));
+
+      let merge = merge_issues(&base, &mine, &theirs).unwrap();
+      assert_eq!(merge.conflicts().len(), 1);
+      assert_eq!(merge.conflicts()[0].part, "section Impact");
+
+      let merged = merge.resolve(&[Side::Theirs]).unwrap();
+      assert_eq!(merged.metadata.priority, Priority::High);
+      assert_eq!(merged.metadata.status, Status::InProgress);
+      assert_eq!(merged.metadata.last_updated, Some(day(2)));
+      assert_eq!(merged.body.impact(), Some("Some users locked out"));
+      assert_eq!(merged.body.checkpoints().count(), 2);
+   }
+
+   #[test]
+   fn test_save_issue_from_reports_overlapping_changes() {
+      let storage = crate::storage::Storage::in_memory();
+      let base = issue(1)
+         .title("Fix login")
+         .body("Times out")
+         .impact("Users locked out")
+         .acceptance("Login works")
+         .build()
+         .issue;
+      storage.save_issue(&base, 1, true).unwrap();
+
+      let mut theirs = base.clone();
+      theirs.metadata.priority = Priority::Low;
+      storage.save_issue(&theirs, 1, true).unwrap();
+
+      let mut mine = base.clone();
+      mine
+         .body
+         .append("\n\n## Checkpoint - 2024-01-01\n\nFound it");
+      storage.save_issue_from(&base, &mine, 1, true).unwrap();
+      let saved = storage.load_issue(1).unwrap();
+      assert_eq!(saved.metadata.priority, Priority::Low);
+      assert_eq!(saved.body.checkpoints().next().unwrap().content(), "Found it");
+
+      let mut mine = saved.clone();
+      mine.metadata.priority = Priority::High;
+      let mut theirs = saved.clone();
+      theirs.metadata.priority = Priority::Critical;
+      storage.save_issue(&theirs, 1, true).unwrap();
+      let err = storage.save_issue_from(&saved, &mine, 1, true).unwrap_err();
+      let conflict = err.downcast_ref::<IssueConflict>().unwrap();
+      assert_eq!(conflict.to_json()["conflicts"][0]["part"], "priority");
+      assert_eq!(storage.load_issue(1).unwrap().metadata.priority, Priority::Critical);
+   }
+}
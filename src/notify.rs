@@ -0,0 +1,265 @@
+//! Scheduled notifications
+//!
+//! `agentx notify` checks the tracker against the rules in the `notify`
+//! config section and raises a desktop notification for each hit: issues
+//! due within some hours, issues blocked for more than some days, and
+//! changes to issues the current user watches. It is meant for cron, or to
+//! run inside `agentx daemon`; a state file remembers what was raised, so
+//! each alert fires once rather than on every run.
+
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+   issue::{IssueWithId, Status},
+   watch::{Seen, WatchEvent},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotifyConfig {
+   /// Warn about issues due within this many hours (or overdue); 0 turns
+   /// the rule off
+   #[serde(default = "default_due_within_hours")]
+   pub due_within_hours: u64,
+   /// Warn about issues blocked for more than this many days; 0 turns the
+   /// rule off
+   #[serde(default = "default_blocked_days")]
+   pub blocked_days:     u64,
+   /// Report changes to issues the current user watches
+   #[serde(default = "default_true")]
+   pub watched:          bool,
+   /// Show desktop notifications, rather than only printing the alerts
+   #[serde(default = "default_true")]
+   pub desktop:          bool,
+   /// Check on this interval (e.g. `15m`) while `agentx daemon` runs
+   #[serde(default)]
+   pub daemon_every:     Option<String>,
+}
+
+fn default_due_within_hours() -> u64 {
+   24
+}
+
+fn default_blocked_days() -> u64 {
+   3
+}
+
+fn default_true() -> bool {
+   true
+}
+
+impl Default for NotifyConfig {
+   fn default() -> Self {
+      Self {
+         due_within_hours: default_due_within_hours(),
+         blocked_days:     default_blocked_days(),
+         watched:          true,
+         desktop:          true,
+         daemon_every:     None,
+      }
+   }
+}
+
+/// Something worth interrupting the user for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum Alert {
+   Due { bug_num: u32, title: String, due: DateTime<Utc> },
+   Blocked { bug_num: u32, title: String, since: DateTime<Utc>, reason: Option<String> },
+   Watched { bug_num: u32, title: String, events: Vec<WatchEvent> },
+}
+
+impl Alert {
+   pub fn bug_num(&self) -> u32 {
+      match self {
+         Self::Due { bug_num, .. }
+         | Self::Blocked { bug_num, .. }
+         | Self::Watched { bug_num, .. } => *bug_num,
+      }
+   }
+
+   pub fn title(&self) -> &str {
+      match self {
+         Self::Due { title, .. } | Self::Blocked { title, .. } | Self::Watched { title, .. } => {
+            title
+         },
+      }
+   }
+
+   /// What happened, e.g. `due in 5h` or `blocked for 4d: waiting on review`
+   pub fn describe(&self, now: DateTime<Utc>) -> String {
+      match self {
+         Self::Due { due, .. } if *due <= now => {
+            format!("overdue by {}", crate::utils::short_age(*due, now))
+         },
+         Self::Due { due, .. } => format!("due in {}", crate::utils::short_age(now, *due)),
+         Self::Blocked { since, reason, .. } => {
+            let days = (now - *since).num_days();
+            match reason {
+               Some(reason) => format!("blocked for {days}d: {reason}"),
+               None => format!("blocked for {days}d"),
+            }
+         },
+         Self::Watched { events, .. } => {
+            let events: Vec<String> = events.iter().map(ToString::to_string).collect();
+            events.join("; ")
+         },
+      }
+   }
+
+   /// Identity of the condition, so the same due date or blocked spell is
+   /// raised once
+   fn key(&self) -> Option<String> {
+      match self {
+         Self::Due { bug_num, due, .. } => Some(format!("due:{bug_num}:{}", due.timestamp())),
+         Self::Blocked { bug_num, since, .. } => {
+            Some(format!("blocked:{bug_num}:{}", since.timestamp()))
+         },
+         Self::Watched { .. } => None,
+      }
+   }
+}
+
+/// What earlier runs saw and raised
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotifyState {
+   /// Watched issues as last seen
+   #[serde(default)]
+   pub seen:   HashMap<u32, Seen>,
+   /// Keys of the due and blocked alerts already raised
+   #[serde(default)]
+   pub raised: BTreeSet<String>,
+}
+
+/// When an issue last became blocked
+fn blocked_since(issue: &IssueWithId) -> DateTime<Utc> {
+   let meta = &issue.issue.metadata;
+   meta
+      .transitions
+      .iter()
+      .rev()
+      .find(|t| t.to == Status::Blocked)
+      .map(|t| t.at)
+      .unwrap_or_else(|| meta.updated_at())
+}
+
+/// New alerts for `issues`, open and closed, updating `state` with what they
+/// raise; a watched issue's first sighting only records it
+pub fn check(
+   issues: &[IssueWithId],
+   config: &NotifyConfig,
+   me: &str,
+   state: &mut NotifyState,
+   now: DateTime<Utc>,
+) -> Vec<Alert> {
+   let mut current = Vec::new();
+   for issue in issues {
+      let meta = &issue.issue.metadata;
+      if matches!(meta.status, Status::Done | Status::Closed) {
+         continue;
+      }
+      let title = meta.title.to_string();
+      if config.due_within_hours > 0
+         && let Some(due) = meta.due
+         && due - now <= chrono::Duration::hours(config.due_within_hours as i64)
+      {
+         current.push(Alert::Due { bug_num: issue.id, title: title.clone(), due });
+      }
+      if config.blocked_days > 0 && meta.status == Status::Blocked {
+         let since = blocked_since(issue);
+         if now - since > chrono::Duration::days(config.blocked_days as i64) {
+            current.push(Alert::Blocked {
+               bug_num: issue.id,
+               title: title.clone(),
+               since,
+               reason: meta.blocked_reason.as_ref().map(|r| r.to_string()),
+            });
+         }
+      }
+   }
+
+   // Conditions that cleared up are forgotten, so they alert again if they
+   // come back
+   let keys: BTreeSet<String> = current.iter().filter_map(Alert::key).collect();
+   state.raised.retain(|key| keys.contains(key));
+   let mut alerts: Vec<Alert> = current
+      .into_iter()
+      .filter(|alert| alert.key().is_some_and(|key| state.raised.insert(key)))
+      .collect();
+
+   if config.watched {
+      let mut seen = HashMap::new();
+      for issue in issues {
+         if !issue.issue.metadata.watchers.iter().any(|w| w == me) {
+            continue;
+         }
+         if let Some(last) = state.seen.get(&issue.id) {
+            let events = last.events(&issue.issue);
+            if !events.is_empty() {
+               alerts.push(Alert::Watched {
+                  bug_num: issue.id,
+                  title: issue.issue.metadata.title.to_string(),
+                  events,
+               });
+            }
+         }
+         seen.insert(issue.id, Seen::of(&issue.issue));
+      }
+      state.seen = seen;
+   }
+
+   alerts
+}
+
+/// Show `alerts` as desktop notifications; failures are ignored, like
+/// watchers' notifications
+pub fn show_desktop(alerts: &[Alert], issue_ref: impl Fn(u32) -> String, now: DateTime<Utc>) {
+   for alert in alerts {
+      let _ = notify_rust::Notification::new()
+         .appname("agentx")
+         .summary(&format!("{} {}", issue_ref(alert.bug_num()), alert.title()))
+         .body(&alert.describe(now))
+         .show();
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::issue::test_support::issue;
+
+   #[test]
+   fn test_check_raises_each_alert_once() {
+      let now = Utc::now();
+      let config = NotifyConfig::default();
+      let mut state = NotifyState::default();
+
+      let mut due = issue(1).title("Ship release").build();
+      due.issue.metadata.due = Some(now + chrono::Duration::hours(5));
+      let mut later = issue(2).title("Plan Q3").build();
+      later.issue.metadata.due = Some(now + chrono::Duration::days(5));
+      let mut blocked = issue(3).title("Migrate DB").build();
+      blocked.issue.metadata.set_status(Status::Blocked);
+      blocked.issue.metadata.transitions[0].at = now - chrono::Duration::days(4);
+      let mut watched = issue(4).title("Fix login").build();
+      watched.issue.metadata.watchers = vec!["ann".into()];
+      let mut issues = vec![due, later, blocked, watched];
+
+      let alerts = check(&issues, &config, "ann", &mut state, now);
+      let nums: Vec<u32> = alerts.iter().map(Alert::bug_num).collect();
+      assert_eq!(nums, [1, 3]);
+      assert_eq!(alerts[0].describe(now), "due in 5h");
+      assert!(check(&issues, &config, "ann", &mut state, now).is_empty());
+
+      issues[3].issue.metadata.set_status(Status::InProgress);
+      let alerts = check(&issues, &config, "ann", &mut state, now);
+      assert_eq!(alerts[0].describe(now), "status open → active");
+
+      // A new due date is a new alert
+      issues[0].issue.metadata.due = Some(now - chrono::Duration::hours(2));
+      let alerts = check(&issues, &config, "ann", &mut state, now);
+      assert_eq!(alerts[0].describe(now), "overdue by 2h");
+   }
+}
@@ -0,0 +1,224 @@
+//! Execution order
+//!
+//! `agentx order` layers open issues by their dependencies: every issue in a
+//! batch depends only on issues in earlier batches or on closed ones, so the
+//! issues within one batch can be worked on in parallel. Issues in a
+//! dependency cycle, or waiting on one, can't be ordered and are reported
+//! separately.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+   impact::{rank_key, unblock_counts},
+   issue::{IssueWithId, Priority, Status},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderedIssue {
+   pub num:          u32,
+   pub title:        String,
+   pub priority:     Priority,
+   pub status:       Status,
+   /// Open issues this one waits for
+   pub depends_on:   Vec<u32>,
+   /// Pulled in as a prerequisite of the selected issues
+   #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+   pub prerequisite: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionOrder {
+   pub batches:   Vec<Vec<OrderedIssue>>,
+   /// Issues in or behind a dependency cycle
+   pub unordered: Vec<OrderedIssue>,
+}
+
+/// Layer `selected` (plus the open issues they transitively depend on) into
+/// batches, given every `open` issue
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn order(selected: &[IssueWithId], open: &[IssueWithId]) -> ExecutionOrder {
+   let unfinished: HashMap<u32, &IssueWithId> = open
+      .iter()
+      .filter(|i| !matches!(i.issue.metadata.status, Status::Done | Status::Closed))
+      .map(|i| (i.id, i))
+      .collect();
+   let chosen: HashSet<u32> = selected
+      .iter()
+      .map(|i| i.id)
+      .filter(|id| unfinished.contains_key(id))
+      .collect();
+
+   let mut scope = chosen.clone();
+   let mut to_visit: Vec<u32> = chosen.iter().copied().collect();
+   while let Some(id) = to_visit.pop() {
+      for dep in &unfinished[&id].issue.metadata.depends_on {
+         if unfinished.contains_key(dep) && scope.insert(*dep) {
+            to_visit.push(*dep);
+         }
+      }
+   }
+
+   let unblocks = unblock_counts(open);
+   let entry = |id: u32| {
+      let issue = unfinished[&id];
+      let meta = &issue.issue.metadata;
+      OrderedIssue {
+         num:          id,
+         title:        meta.title.to_string(),
+         priority:     meta.priority,
+         status:       meta.status,
+         depends_on:   meta
+            .depends_on
+            .iter()
+            .copied()
+            .filter(|dep| scope.contains(dep))
+            .collect(),
+         prerequisite: !chosen.contains(&id),
+      }
+   };
+   let rank = |id: &u32| {
+      let priority = unfinished[id].issue.metadata.priority;
+      (rank_key(priority, unblocks.get(id).copied().unwrap_or(0)), *id)
+   };
+
+   let mut remaining = scope.clone();
+   let mut batches = Vec::new();
+   loop {
+      let mut ready: Vec<u32> = remaining
+         .iter()
+         .copied()
+         .filter(|id| {
+            unfinished[id]
+               .issue
+               .metadata
+               .depends_on
+               .iter()
+               .all(|dep| !remaining.contains(dep))
+         })
+         .collect();
+      if ready.is_empty() {
+         break;
+      }
+      ready.sort_by_key(rank);
+      for id in &ready {
+         remaining.remove(id);
+      }
+      batches.push(ready.into_iter().map(entry).collect());
+   }
+
+   let mut unordered: Vec<u32> = remaining.into_iter().collect();
+   unordered.sort_unstable();
+   ExecutionOrder { batches, unordered: unordered.into_iter().map(entry).collect() }
+}
+
+impl ExecutionOrder {
+   fn edges(&self) -> Vec<(u32, u32)> {
+      let mut edges: Vec<(u32, u32)> = self
+         .batches
+         .iter()
+         .flatten()
+         .chain(&self.unordered)
+         .flat_map(|issue| issue.depends_on.iter().map(move |&dep| (dep, issue.num)))
+         .collect();
+      edges.sort_unstable();
+      edges
+   }
+
+   /// Mermaid flowchart with one subgraph per batch
+   pub fn to_mermaid(&self, issue_ref: impl Fn(u32) -> String) -> String {
+      let label = |issue: &OrderedIssue| {
+         format!("{}: {}", issue_ref(issue.num), issue.title).replace('"', "#quot;")
+      };
+      let mut text = String::from("flowchart LR\n");
+      for (i, batch) in self.batches.iter().enumerate() {
+         text.push_str(&format!("  subgraph batch{}[\"Batch {}\"]\n", i + 1, i + 1));
+         for issue in batch {
+            text.push_str(&format!("    i{}[\"{}\"]\n", issue.num, label(issue)));
+         }
+         text.push_str("  end\n");
+      }
+      if !self.unordered.is_empty() {
+         text.push_str("  subgraph cycle[\"Cycle\"]\n");
+         for issue in &self.unordered {
+            text.push_str(&format!("    i{}[\"{}\"]\n", issue.num, label(issue)));
+         }
+         text.push_str("  end\n");
+      }
+      for (from, to) in self.edges() {
+         text.push_str(&format!("  i{from} --> i{to}\n"));
+      }
+      text
+   }
+
+   /// Graphviz digraph with one cluster per batch
+   pub fn to_dot(&self, issue_ref: impl Fn(u32) -> String) -> String {
+      let label = |issue: &OrderedIssue| {
+         format!("{}: {}", issue_ref(issue.num), issue.title)
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+      };
+      let mut text = String::from("digraph order {\n  rankdir=LR;\n  node [shape=box];\n");
+      let clusters = self
+         .batches
+         .iter()
+         .enumerate()
+         .map(|(i, batch)| (format!("Batch {}", i + 1), batch))
+         .chain((!self.unordered.is_empty()).then(|| ("Cycle".to_string(), &self.unordered)));
+      for (i, (name, issues)) in clusters.enumerate() {
+         text.push_str(&format!("  subgraph cluster_{} {{\n    label=\"{name}\";\n", i + 1));
+         for issue in issues {
+            text.push_str(&format!("    i{} [label=\"{}\"];\n", issue.num, label(issue)));
+         }
+         text.push_str("  }\n");
+      }
+      for (from, to) in self.edges() {
+         text.push_str(&format!("  i{from} -> i{to};\n"));
+      }
+      text.push_str("}\n");
+      text
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::issue::test_support::issue;
+
+   #[test]
+   fn test_order_batches_and_exports() {
+      let open = vec![
+         issue(1).priority(Priority::Low).build(),
+         issue(2).priority(Priority::High).build(),
+         issue(3).depends_on(&[1, 2]).build(),
+         issue(4).depends_on(&[5]).build(),
+         issue(5).depends_on(&[4]).build(),
+         issue(6).depends_on(&[3]).build(),
+      ];
+      let order = order(&open, &open);
+      let nums = |issues: &[OrderedIssue]| issues.iter().map(|i| i.num).collect::<Vec<_>>();
+      assert_eq!(order.batches.len(), 3);
+      assert_eq!(nums(&order.batches[0]), [2, 1]);
+      assert_eq!(nums(&order.batches[1]), [3]);
+      assert_eq!(nums(&order.batches[2]), [6]);
+      assert_eq!(nums(&order.unordered), [4, 5]);
+
+      let selected = order_of(&open, 3);
+      assert_eq!(nums(&selected.batches[0]), [2, 1]);
+      assert!(selected.batches[0][0].prerequisite);
+      assert!(!selected.batches[1][0].prerequisite);
+
+      let mermaid = selected.to_mermaid(|n| format!("#{n}"));
+      assert!(mermaid.contains("  subgraph batch2[\"Batch 2\"]\n    i3[\"#3: Issue 3\"]\n  end\n"));
+      assert!(mermaid.ends_with("  i1 --> i3\n  i2 --> i3\n"));
+      let dot = selected.to_dot(|n| format!("#{n}"));
+      assert!(dot.contains("    i3 [label=\"#3: Issue 3\"];\n"));
+      assert!(dot.contains("  i1 -> i3;\n"));
+   }
+
+   fn order_of(open: &[IssueWithId], id: u32) -> ExecutionOrder {
+      let selected: Vec<IssueWithId> = open.iter().filter(|i| i.id == id).cloned().collect();
+      order(&selected, open)
+   }
+}
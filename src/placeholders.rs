@@ -0,0 +1,127 @@
+//! Placeholders in new issues
+//!
+//! The title and body sections of a new issue may use `{{name}}`
+//! placeholders, filled in from the environment at creation time so an issue
+//! opened mid-session records where it came from:
+//!
+//! - `{{branch}}`: the checked-out git branch
+//! - `{{date}}`: today's date (UTC)
+//! - `{{cwd_files}}`: files with uncommitted changes, one bullet each
+//! - `{{git_diff_stat}}`: `git diff --stat` of the working tree against HEAD
+//!
+//! Unknown names are left as they were written.
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use git2::{DiffOptions, DiffStatsFormat, Repository, StatusOptions};
+
+/// Most files listed by `{{cwd_files}}`
+const MAX_FILES: usize = 50;
+
+/// Width of the `{{git_diff_stat}}` graph
+const STAT_WIDTH: usize = 72;
+
+/// Where placeholders take their values from
+pub struct Env {
+   repo: Option<Repository>,
+   now:  DateTime<Utc>,
+}
+
+impl Env {
+   /// The environment of `dir`, the repository containing it if any
+   pub fn at(dir: &Path, now: DateTime<Utc>) -> Self {
+      Self { repo: Repository::discover(dir).ok(), now }
+   }
+
+   /// Value of placeholder `name`; empty for git placeholders outside a
+   /// repository, `None` for unknown names
+   pub fn value(&self, name: &str) -> Option<String> {
+      let value = match name {
+         "date" => self.now.format("%Y-%m-%d").to_string(),
+         "branch" => self.repo.as_ref().and_then(branch).unwrap_or_default(),
+         "cwd_files" => self
+            .repo
+            .as_ref()
+            .and_then(changed_files)
+            .unwrap_or_default(),
+         "git_diff_stat" => self.repo.as_ref().and_then(diff_stat).unwrap_or_default(),
+         _ => return None,
+      };
+      Some(value)
+   }
+}
+
+/// `text` with each `{{name}}` that `value` knows replaced
+pub fn expand(text: &str, value: impl Fn(&str) -> Option<String>) -> String {
+   let mut out = String::with_capacity(text.len());
+   let mut rest = text;
+   while let Some(open) = rest.find("{{") {
+      let after = &rest[open + 2..];
+      let Some(close) = after.find("}}") else {
+         break;
+      };
+      out.push_str(&rest[..open]);
+      match value(after[..close].trim()) {
+         Some(v) => out.push_str(v.trim_end()),
+         None => out.push_str(&rest[open..open + close + 4]),
+      }
+      rest = &after[close + 2..];
+   }
+   out.push_str(rest);
+   out
+}
+
+fn branch(repo: &Repository) -> Option<String> {
+   repo.head().ok()?.shorthand().map(str::to_string)
+}
+
+fn changed_files(repo: &Repository) -> Option<String> {
+   let mut options = StatusOptions::new();
+   options.include_untracked(true).recurse_untracked_dirs(true);
+   let statuses = repo.statuses(Some(&mut options)).ok()?;
+   let mut lines: Vec<String> = statuses
+      .iter()
+      .filter_map(|entry| entry.path().map(|path| format!("- `{path}`")))
+      .collect();
+   if lines.len() > MAX_FILES {
+      let more = lines.len() - MAX_FILES;
+      lines.truncate(MAX_FILES);
+      lines.push(format!("- … and {more} more"));
+   }
+   Some(lines.join("\n"))
+}
+
+fn diff_stat(repo: &Repository) -> Option<String> {
+   let head = repo.head().ok()?.peel_to_tree().ok()?;
+   let diff = repo
+      .diff_tree_to_workdir_with_index(Some(&head), Some(&mut DiffOptions::new()))
+      .ok()?;
+   let stats = diff.stats().ok()?;
+   if stats.files_changed() == 0 {
+      return Some(String::new());
+   }
+   let buf = stats.to_buf(DiffStatsFormat::FULL, STAT_WIDTH).ok()?;
+   buf.as_str().map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn test_expand() {
+      let value = |name: &str| match name {
+         "branch" => Some("fix/login".to_string()),
+         "cwd_files" => Some("- `src/a.rs`\n".to_string()),
+         _ => None,
+      };
+      assert_eq!(
+         expand("Seen on {{branch}} ({{ branch }})", value),
+         "Seen on fix/login (fix/login)"
+      );
+      assert_eq!(expand("Files:\n{{cwd_files}}\nEnd", value), "Files:\n- `src/a.rs`\nEnd");
+      assert_eq!(expand("Keep {{unknown}} and {{open", value), "Keep {{unknown}} and {{open");
+      assert_eq!(expand("{{}}", value), "{{}}");
+   }
+}
@@ -0,0 +1,277 @@
+//! Sprint planning
+//!
+//! `agentx plan` fills a capacity budget with open issues. It repeatedly
+//! takes the best-ranked issue (the same ranking `focus` uses) whose open
+//! dependencies are closed or already planned, so the plan is also a valid
+//! order to work in. A dependency ranks at least as well as the issues
+//! waiting on it, so a critical issue isn't starved by a low-priority
+//! prerequisite. Issues without an effort estimate can't be budgeted and
+//! are left out with a reason, as are those that don't fit.
+//!
+//! A [`DailyBudget`] weighs one day's work, the issues in progress plus the
+//! top ready ones, against the configured `daily_capacity`.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+   impact::{rank_key, unblock_counts},
+   issue::{IssueWithId, Priority, Status},
+   utils::parse_effort,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedIssue {
+   pub num:                u32,
+   pub title:              String,
+   pub priority:           Priority,
+   pub status:             Status,
+   pub effort_minutes:     u32,
+   /// Planned effort up to and including this issue
+   pub cumulative_minutes: u32,
+   /// Planned issues this one has to wait for
+   #[serde(skip_serializing_if = "Vec::is_empty", default)]
+   pub after:              Vec<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedIssue {
+   pub num:    u32,
+   pub title:  String,
+   pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Plan {
+   pub capacity_minutes: u32,
+   pub planned_minutes:  u32,
+   /// In the order to work on them
+   pub issues:           Vec<PlannedIssue>,
+   pub skipped:          Vec<SkippedIssue>,
+   /// Tag added to the planned issues, when asked to
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub sprint_tag:       Option<String>,
+}
+
+/// Today's planned effort against the daily capacity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyBudget {
+   pub capacity_minutes: u32,
+   /// Estimated effort of the issues counted
+   pub planned_minutes:  u32,
+   /// Issues counted
+   pub issues:           usize,
+   /// Issues counted without a readable effort estimate
+   #[serde(skip_serializing_if = "is_zero", default)]
+   pub unestimated:      usize,
+   pub overcommitted:    bool,
+}
+
+fn is_zero(n: &usize) -> bool {
+   *n == 0
+}
+
+impl DailyBudget {
+   /// Weigh issues with these effort estimates against `capacity_minutes`
+   pub fn new<'a>(
+      capacity_minutes: u32,
+      efforts: impl IntoIterator<Item = Option<&'a str>>,
+   ) -> Self {
+      let mut budget = Self {
+         capacity_minutes,
+         planned_minutes: 0,
+         issues: 0,
+         unestimated: 0,
+         overcommitted: false,
+      };
+      for effort in efforts {
+         budget.issues += 1;
+         match effort.and_then(|e| parse_effort(e).ok()) {
+            Some(minutes) => budget.planned_minutes += minutes,
+            None => budget.unestimated += 1,
+         }
+      }
+      budget.overcommitted = budget.planned_minutes > capacity_minutes;
+      budget
+   }
+}
+
+/// Plan `capacity_minutes` of work from `candidates`, given every `open`
+/// issue for dependency checks
+pub fn plan(candidates: &[IssueWithId], open: &[IssueWithId], capacity_minutes: u32) -> Plan {
+   let unblocks = unblock_counts(open);
+   let open_ids: HashSet<u32> = open
+      .iter()
+      .filter(|i| !matches!(i.issue.metadata.status, Status::Done | Status::Closed))
+      .map(|i| i.id)
+      .collect();
+
+   let mut skipped = Vec::new();
+   let mut pending: Vec<(&IssueWithId, u32)> = Vec::new();
+   for issue in candidates {
+      let meta = &issue.issue.metadata;
+      let reason = match meta.status {
+         Status::Blocked => Some("blocked".to_string()),
+         Status::Backlog => Some("in the backlog".to_string()),
+         Status::Done | Status::Closed => continue,
+         Status::NotStarted | Status::InProgress => None,
+      };
+      let effort = meta.effort.as_deref().and_then(|e| parse_effort(e).ok());
+      match (reason, effort) {
+         (Some(reason), _) => skipped.push(skip(issue, reason)),
+         (None, None) => skipped.push(skip(issue, "no effort estimate".to_string())),
+         (None, Some(minutes)) => pending.push((issue, minutes)),
+      }
+   }
+   let own_rank: HashMap<u32, _> = pending
+      .iter()
+      .map(|(issue, _)| {
+         let unblocks = unblocks.get(&issue.id).copied().unwrap_or(0);
+         (issue.id, rank_key(issue.issue.metadata.priority, unblocks))
+      })
+      .collect();
+   let mut dependents: HashMap<u32, Vec<u32>> = HashMap::new();
+   for (issue, _) in &pending {
+      for &dep in &issue.issue.metadata.depends_on {
+         dependents.entry(dep).or_default().push(issue.id);
+      }
+   }
+   let rank = |id: u32| {
+      let mut seen = HashSet::from([id]);
+      let mut to_visit = vec![id];
+      let mut best = own_rank[&id];
+      while let Some(next) = to_visit.pop() {
+         for &dependent in dependents.get(&next).into_iter().flatten() {
+            if seen.insert(dependent) {
+               best = best.min(own_rank[&dependent]);
+               to_visit.push(dependent);
+            }
+         }
+      }
+      best
+   };
+   pending.sort_by_cached_key(|(issue, _)| (rank(issue.id), issue.id));
+
+   let mut planned: Vec<PlannedIssue> = Vec::new();
+   let mut used = 0;
+   let waits_on = |issue: &IssueWithId, planned: &[PlannedIssue]| -> Vec<u32> {
+      issue
+         .issue
+         .metadata
+         .depends_on
+         .iter()
+         .copied()
+         .filter(|dep| open_ids.contains(dep) && !planned.iter().any(|p| p.num == *dep))
+         .collect()
+   };
+   // Planning an issue can make a better-ranked dependent ready, so rescan
+   // from the top after every pick
+   while let Some(index) = pending.iter().position(|(issue, minutes)| {
+      used + minutes <= capacity_minutes && waits_on(issue, &planned).is_empty()
+   }) {
+      let (issue, minutes) = pending.remove(index);
+      used += minutes;
+      let meta = &issue.issue.metadata;
+      planned.push(PlannedIssue {
+         num:                issue.id,
+         title:              meta.title.to_string(),
+         priority:           meta.priority,
+         status:             meta.status,
+         effort_minutes:     minutes,
+         cumulative_minutes: used,
+         after:              meta
+            .depends_on
+            .iter()
+            .copied()
+            .filter(|dep| planned.iter().any(|p| p.num == *dep))
+            .collect(),
+      });
+   }
+
+   for (issue, minutes) in pending {
+      let waiting = waits_on(issue, &planned);
+      let reason = if waiting.is_empty() {
+         format!("needs {}, {} left", hours(minutes), hours(capacity_minutes - used))
+      } else {
+         let deps: Vec<String> = waiting.iter().map(|dep| format!("#{dep}")).collect();
+         format!("waits on {}", deps.join(", "))
+      };
+      skipped.push(skip(issue, reason));
+   }
+   skipped.sort_by_key(|s| s.num);
+
+   Plan { capacity_minutes, planned_minutes: used, issues: planned, skipped, sprint_tag: None }
+}
+
+/// `sprint-N` one past the highest sprint tag on any issue
+pub fn next_sprint_tag(issues: &[IssueWithId]) -> String {
+   let last = issues
+      .iter()
+      .flat_map(|i| &i.issue.metadata.tags)
+      .filter_map(|tag| tag.strip_prefix("sprint-")?.parse::<u32>().ok())
+      .max()
+      .unwrap_or(0);
+   format!("sprint-{}", last + 1)
+}
+
+/// Minutes as hours, e.g. `1.5h`
+pub fn hours(minutes: u32) -> String {
+   let hours = minutes as f64 / 60.0;
+   if hours.fract() == 0.0 {
+      format!("{hours:.0}h")
+   } else {
+      format!("{hours:.1}h")
+   }
+}
+
+fn skip(issue: &IssueWithId, reason: String) -> SkippedIssue {
+   SkippedIssue { num: issue.id, title: issue.issue.metadata.title.to_string(), reason }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::issue::test_support::issue;
+
+   #[test]
+   fn test_plan_fills_capacity_in_dependency_order() {
+      let open = vec![
+         issue(1).priority(Priority::Low).effort("2h").build(),
+         issue(2)
+            .priority(Priority::Critical)
+            .effort("4h")
+            .depends_on(&[1])
+            .build(),
+         issue(3).priority(Priority::High).effort("8h").build(),
+         issue(4).priority(Priority::High).build(),
+         issue(5).effort("1h").depends_on(&[3]).build(),
+      ];
+      let plan = plan(&open, &open, 10 * 60);
+
+      let nums: Vec<u32> = plan.issues.iter().map(|i| i.num).collect();
+      assert_eq!(nums, [1, 2]);
+      assert_eq!(plan.issues[1].after, [1]);
+      assert_eq!(plan.planned_minutes, 6 * 60);
+      let reasons: Vec<(u32, &str)> = plan
+         .skipped
+         .iter()
+         .map(|s| (s.num, s.reason.as_str()))
+         .collect();
+      assert_eq!(reasons, [
+         (3, "needs 8h, 4h left"),
+         (4, "no effort estimate"),
+         (5, "waits on #3")
+      ]);
+
+      let efforts = open.iter().map(|i| i.issue.metadata.effort.as_deref());
+      let budget = DailyBudget::new(6 * 60, efforts);
+      assert_eq!((budget.planned_minutes, budget.issues, budget.unestimated), (15 * 60, 5, 1));
+      assert!(budget.overcommitted);
+      assert!(!DailyBudget::new(6 * 60, [Some("2h"), Some("4h")]).overcommitted);
+
+      let mut tagged = open.clone();
+      tagged[0].issue.metadata.tags = vec!["sprint-2".into()];
+      assert_eq!(next_sprint_tag(&tagged), "sprint-3");
+   }
+}
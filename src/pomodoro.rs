@@ -0,0 +1,137 @@
+//! Pomodoro timer
+//!
+//! `agentx focus --pomodoro` times work intervals on the issue in progress:
+//! it counts down in the terminal, and at the end of each interval asks for
+//! a progress note, checkpoints it on the issue and logs the interval to the
+//! session journal. The running timer is kept in a state file so the TUI
+//! dashboard can show it in its status bar.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Phase {
+   Work,
+   Break,
+}
+
+/// The running interval
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pomodoro {
+   pub bug_num: u32,
+   pub title:   String,
+   pub phase:   Phase,
+   /// 1-based round, out of `rounds`
+   pub round:   u32,
+   pub rounds:  u32,
+   pub started: DateTime<Utc>,
+   pub ends:    DateTime<Utc>,
+}
+
+/// A finished work interval
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interval {
+   pub bug_num: u32,
+   pub round:   u32,
+   pub started: DateTime<Utc>,
+   pub ended:   DateTime<Utc>,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub note:    Option<String>,
+}
+
+impl Pomodoro {
+   /// Seconds left, `None` once the interval is over
+   pub fn remaining(&self, now: DateTime<Utc>) -> Option<i64> {
+      let left = (self.ends - now).num_seconds();
+      (left > 0).then_some(left)
+   }
+
+   /// The status bar text, e.g. `🍅 2/4 12:05 ISSUE-3`, while running
+   pub fn status(&self, now: DateTime<Utc>, issue_ref: impl Fn(u32) -> String) -> Option<String> {
+      let left = self.remaining(now)?;
+      let icon = match self.phase {
+         Phase::Work => "🍅",
+         Phase::Break => "☕",
+      };
+      Some(format!(
+         "{icon} {}/{} {} {}",
+         self.round,
+         self.rounds,
+         clock(left),
+         issue_ref(self.bug_num)
+      ))
+   }
+}
+
+impl Interval {
+   fn minutes(&self) -> i64 {
+      (self.ended - self.started).num_minutes()
+   }
+
+   /// The checkpoint added to the issue
+   pub fn checkpoint(&self) -> String {
+      let head = format!("🍅 Pomodoro {} ({}m)", self.round, self.minutes());
+      match &self.note {
+         Some(note) => format!("{head}: {note}"),
+         None => head,
+      }
+   }
+
+   /// The session journal line
+   pub fn journal_line(&self, issue_ref: impl Fn(u32) -> String, title: &str) -> String {
+      format!(
+         "- {}–{} 🍅 {} {title} ({}m){}\n",
+         self.started.format("%Y-%m-%d %H:%M"),
+         self.ended.format("%H:%M UTC"),
+         issue_ref(self.bug_num),
+         self.minutes(),
+         self
+            .note
+            .as_ref()
+            .map_or(String::new(), |n| format!(": {n}"))
+      )
+   }
+}
+
+/// `mm:ss` for a countdown
+pub fn clock(seconds: i64) -> String {
+   format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn test_pomodoro_texts() {
+      let started = "2026-10-17T09:00:00Z".parse::<DateTime<Utc>>().unwrap();
+      let pomodoro = Pomodoro {
+         bug_num: 3,
+         title: "Fix login".into(),
+         phase: Phase::Work,
+         round: 2,
+         rounds: 4,
+         started,
+         ends: started + chrono::Duration::minutes(25),
+      };
+      let issue_ref = |n: u32| format!("ISSUE-{n}");
+      let now = started + chrono::Duration::seconds(55);
+      assert_eq!(pomodoro.status(now, issue_ref).unwrap(), "🍅 2/4 24:05 ISSUE-3");
+      assert!(pomodoro.status(pomodoro.ends, issue_ref).is_none());
+
+      let interval = Interval {
+         bug_num: 3,
+         round: 2,
+         started,
+         ended: pomodoro.ends,
+         note: Some("Narrowed it to the token refresh".into()),
+      };
+      assert_eq!(interval.checkpoint(), "🍅 Pomodoro 2 (25m): Narrowed it to the token refresh");
+      assert_eq!(
+         interval.journal_line(issue_ref, "Fix login"),
+         "- 2026-10-17 09:00–09:25 UTC 🍅 ISSUE-3 Fix login (25m): Narrowed it to the token \
+          refresh\n"
+      );
+   }
+}
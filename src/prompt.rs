@@ -0,0 +1,147 @@
+//! Compact status segment for shell prompts
+//!
+//! `agentx prompt` runs on every prompt redraw, so it only counts statuses and
+//! looks at the current branch; it reads through the daemon when one is
+//! running.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+   issue::{IssueWithId, Status},
+   storage::Storage,
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PromptStatus {
+   pub active:       usize,
+   pub blocked:      usize,
+   /// Not started, excluding the backlog
+   pub todo:         usize,
+   /// Open issue whose branch is checked out
+   pub branch_issue: Option<u32>,
+   pub branch_ref:   Option<String>,
+}
+
+impl PromptStatus {
+   pub fn from_issues(open: &[IssueWithId]) -> Self {
+      let mut status = Self::default();
+      for issue in open {
+         match issue.issue.metadata.status {
+            Status::InProgress => status.active += 1,
+            Status::Blocked => status.blocked += 1,
+            Status::NotStarted => status.todo += 1,
+            _ => {},
+         }
+      }
+      status
+   }
+
+   /// The segment itself, e.g. `AX:3▶ 1🚫 12○ ⎇ ISSUE-7`; empty when there is
+   /// nothing to show so prompts can hide it
+   pub fn render(&self) -> String {
+      let counts: Vec<String> = [(self.active, "▶"), (self.blocked, "🚫"), (self.todo, "○")]
+         .iter()
+         .filter(|(n, _)| *n > 0)
+         .map(|(n, symbol)| format!("{n}{symbol}"))
+         .collect();
+      let mut segment = if counts.is_empty() {
+         String::new()
+      } else {
+         format!("AX:{}", counts.join(" "))
+      };
+      if let Some(issue_ref) = &self.branch_ref {
+         if !segment.is_empty() {
+            segment.push(' ');
+         }
+         segment.push_str(&format!("⎇ {issue_ref}"));
+      }
+      segment
+   }
+}
+
+/// The open issue a branch was created for by `agentx start --branch`, i.e.
+/// `<prefix><slug of title>`, or failing that the first number after the
+/// prefix
+pub fn branch_issue(branch: &str, branch_prefix: &str, open: &[IssueWithId]) -> Option<u32> {
+   let name = branch.strip_prefix(branch_prefix)?;
+   if let Some(issue) = open
+      .iter()
+      .find(|i| Storage::slugify(&i.issue.metadata.title) == name)
+   {
+      return Some(issue.id);
+   }
+   let digits: String = name
+      .chars()
+      .skip_while(|c| !c.is_ascii_digit())
+      .take_while(char::is_ascii_digit)
+      .collect();
+   let id = digits.parse().ok()?;
+   open.iter().any(|i| i.id == id).then_some(id)
+}
+
+const ZSH_SNIPPET: &str = r#"# ~/.zshrc
+setopt PROMPT_SUBST
+RPROMPT='$(agentx prompt 2>/dev/null)'
+"#;
+
+const BASH_SNIPPET: &str = r#"# ~/.bashrc
+PS1='$(agentx prompt 2>/dev/null) '"$PS1"
+"#;
+
+const FISH_SNIPPET: &str = r#"# ~/.config/fish/functions/fish_right_prompt.fish
+function fish_right_prompt
+    agentx prompt 2>/dev/null
+end
+"#;
+
+const STARSHIP_SNIPPET: &str = r#"# ~/.config/starship.toml
+[custom.agentx]
+command = "agentx prompt"
+when = true
+format = "[$output]($style) "
+style = "bold yellow"
+"#;
+
+/// Snippet wiring `agentx prompt` into a shell prompt
+pub fn shell_snippet(shell: &str) -> Option<&'static str> {
+   match shell {
+      "zsh" => Some(ZSH_SNIPPET),
+      "bash" => Some(BASH_SNIPPET),
+      "fish" => Some(FISH_SNIPPET),
+      "starship" => Some(STARSHIP_SNIPPET),
+      _ => None,
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::issue::test_support::issue;
+
+   #[test]
+   fn test_prompt_segment_and_branch_issue() {
+      let open = vec![
+         issue(3)
+            .title("Fix login timeout")
+            .status(Status::InProgress)
+            .build(),
+         issue(7).title("Add rate limiting").build(),
+         issue(9)
+            .title("Vendor integration")
+            .status(Status::Blocked)
+            .build(),
+         issue(12).title("Someday").status(Status::Backlog).build(),
+      ];
+      let mut status = PromptStatus::from_issues(&open);
+      assert_eq!(status.render(), "AX:1▶ 1🚫 1○");
+
+      assert_eq!(branch_issue("issue-fix-login-timeout", "issue-", &open), Some(3));
+      assert_eq!(branch_issue("issue-7-rate-limits", "issue-", &open), Some(7));
+      assert_eq!(branch_issue("issue-42-gone", "issue-", &open), None);
+      assert_eq!(branch_issue("main", "issue-", &open), None);
+
+      status.branch_ref = Some("ISSUE-3".into());
+      assert_eq!(status.render(), "AX:1▶ 1🚫 1○ ⎇ ISSUE-3");
+      assert_eq!(PromptStatus::default().render(), "");
+   }
+}
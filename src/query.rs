@@ -0,0 +1,683 @@
+//! Small filter expression language shared by the CLI, TUI and MCP server.
+//!
+//! ```text
+//! status:open AND (priority>=high OR tag:security) AND effort<2h AND created>-30d
+//! ```
+//!
+//! Grammar:
+//! - `expr := and ("OR" and)*`
+//! - `and := unary ("AND"? unary)*` (adjacent terms are implicitly AND-ed)
+//! - `unary := ("NOT" | "-") unary | "(" expr ")" | term`
+//! - `term := field op value | word`
+//!
+//! Operators are `:`, `=`, `!=`, `<`, `<=`, `>`, `>=`. A bare word (or quoted
+//! phrase) matches the title or body case-insensitively.
+//!
+//! Fields:
+//! - `status` — `open`, `active`, `blocked`, `done`, `closed`, `backlog`
+//! - `priority` — ordered `low < medium < high < critical`
+//! - `tag` — `:` is a fuzzy match, `=` is exact
+//! - `kind` — `bug`, `feature`, `chore`, `spike` or a configured kind
+//! - `file`, `title`, `milestone`, `version` (the target version) — `:` is a
+//!   substring match, `=` is exact
+//! - `file_glob`, `file_regex` — a file matches the glob (`src/tui/**`) or
+//!   regex; paths are compared relative to the repository root
+//! - `effort` — durations such as `30m`, `2h`, `1d`
+//! - `created`, `started`, `closed`, `updated` — `YYYY-MM-DD` or relative
+//!   offsets such as `-30d`, `-12h`, `-2w` (relative to now); `updated<-14d`
+//!   finds stale issues
+//! - `id` — issue number
+
+use std::fmt;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+
+use crate::{
+   file_match::FileMatcher,
+   fuzzy::fuzzy_match_tag,
+   issue::{IssueWithId, Priority, Status},
+   release::same_version,
+   utils::{parse_effort, repo_root},
+};
+
+/// A parsed filter expression
+#[derive(Debug, Clone)]
+pub struct Query {
+   source: String,
+   expr:   Expr,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+   And(Box<Expr>, Box<Expr>),
+   Or(Box<Expr>, Box<Expr>),
+   Not(Box<Expr>),
+   Pred(Predicate),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CmpOp {
+   Match,
+   Eq,
+   Ne,
+   Lt,
+   Le,
+   Gt,
+   Ge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateField {
+   Created,
+   Started,
+   Closed,
+   Updated,
+}
+
+#[derive(Debug, Clone)]
+enum DateValue {
+   /// A whole calendar day (`2025-01-31`)
+   Day(DateTime<Utc>),
+   /// A point in time (`-30d`)
+   Instant(DateTime<Utc>),
+}
+
+#[derive(Debug, Clone)]
+enum Predicate {
+   Status(CmpOp, Status),
+   Priority(CmpOp, Priority),
+   Tag(CmpOp, String),
+   File(CmpOp, String),
+   FileMatch(CmpOp, FileMatcher),
+   Title(CmpOp, String),
+   Milestone(CmpOp, String),
+   Kind(CmpOp, String),
+   Version(CmpOp, String),
+   Effort(CmpOp, u32),
+   Id(CmpOp, u32),
+   Date(DateField, CmpOp, DateValue),
+   Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+   LParen,
+   RParen,
+   And,
+   Or,
+   Not,
+   /// A term; `quoted` is set when the whole word was a quoted phrase
+   Word {
+      text:   String,
+      quoted: bool,
+   },
+}
+
+impl Query {
+   /// Parse a filter expression
+   pub fn parse(input: &str) -> Result<Self> {
+      let tokens = tokenize(input)?;
+      if tokens.is_empty() {
+         anyhow::bail!("Empty query expression");
+      }
+
+      let now = Utc::now();
+      let mut parser = Parser { tokens: &tokens, pos: 0, now };
+      let expr = parser.parse_or()?;
+
+      if let Some(tok) = parser.peek() {
+         anyhow::bail!("Unexpected {} in query expression", describe(tok));
+      }
+
+      Ok(Self { source: input.trim().to_string(), expr })
+   }
+
+   /// Issues whose `field` (`created`, `started`, `closed` or `updated`) is
+   /// at or after `since`: `YYYY-MM-DD`, or an age such as `30d` or `-30d`
+   pub fn since(field: &str, since: &str) -> Result<Self> {
+      let date_field = match field {
+         "created" => DateField::Created,
+         "started" => DateField::Started,
+         "closed" => DateField::Closed,
+         "updated" => DateField::Updated,
+         _ => anyhow::bail!("Unknown date field: {field}. Use: created, started, closed, updated"),
+      };
+      let since = since.trim();
+      let value = if NaiveDate::parse_from_str(since, "%Y-%m-%d").is_ok() {
+         since.to_string()
+      } else {
+         format!("-{}", since.strip_prefix('-').unwrap_or(since))
+      };
+      let date = parse_date(&value, Utc::now()).map_err(|_| {
+         anyhow::anyhow!("Invalid date: {since} (use YYYY-MM-DD or an age like 30d)")
+      })?;
+      Ok(Self {
+         source: format!("{field}>={value}"),
+         expr:   Expr::Pred(Predicate::Date(date_field, CmpOp::Ge, date)),
+      })
+   }
+
+   /// Issues with a file matching `matcher`, described as `source`
+   pub fn file(matcher: FileMatcher, source: String) -> Self {
+      Self { source, expr: Expr::Pred(Predicate::FileMatch(CmpOp::Match, matcher)) }
+   }
+
+   /// Issues matching both queries
+   pub fn and(self, other: Self) -> Self {
+      Self {
+         source: format!("{} AND {}", self.source, other.source),
+         expr:   Expr::And(Box::new(self.expr), Box::new(other.expr)),
+      }
+   }
+
+   /// The expression text this query was parsed from
+   pub fn source(&self) -> &str {
+      &self.source
+   }
+
+   /// Check whether an issue satisfies the expression
+   pub fn matches(&self, issue: &IssueWithId) -> bool {
+      self.expr.eval(issue)
+   }
+
+   /// Keep only the issues matching the expression
+   pub fn filter(&self, issues: Vec<IssueWithId>) -> Vec<IssueWithId> {
+      issues.into_iter().filter(|i| self.matches(i)).collect()
+   }
+}
+
+impl fmt::Display for Query {
+   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      f.write_str(&self.source)
+   }
+}
+
+impl std::str::FromStr for Query {
+   type Err = anyhow::Error;
+
+   fn from_str(s: &str) -> Result<Self> {
+      Self::parse(s)
+   }
+}
+
+fn describe(tok: &Token) -> String {
+   match tok {
+      Token::LParen => "'('".to_string(),
+      Token::RParen => "')'".to_string(),
+      Token::And => "AND".to_string(),
+      Token::Or => "OR".to_string(),
+      Token::Not => "NOT".to_string(),
+      Token::Word { text, .. } => format!("'{text}'"),
+   }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+   let mut tokens = Vec::new();
+   let mut chars = input.chars().peekable();
+
+   while let Some(&c) = chars.peek() {
+      if c.is_whitespace() {
+         chars.next();
+         continue;
+      }
+
+      if c == '(' {
+         chars.next();
+         tokens.push(Token::LParen);
+         continue;
+      }
+
+      if c == ')' {
+         chars.next();
+         tokens.push(Token::RParen);
+         continue;
+      }
+
+      // Leading '-' negates the following term ("created>-30d" never starts a word)
+      if c == '-' {
+         chars.next();
+         if chars.peek().is_some_and(|c| !c.is_whitespace()) {
+            tokens.push(Token::Not);
+            continue;
+         }
+         anyhow::bail!("Dangling '-' in query expression");
+      }
+
+      let quoted = c == '"';
+      let mut text = String::new();
+      let mut in_quotes = false;
+
+      while let Some(&c) = chars.peek() {
+         if c == '"' {
+            in_quotes = !in_quotes;
+            chars.next();
+            continue;
+         }
+         if !in_quotes && (c.is_whitespace() || c == '(' || c == ')') {
+            break;
+         }
+         text.push(c);
+         chars.next();
+      }
+
+      if in_quotes {
+         anyhow::bail!("Unterminated quote in query expression");
+      }
+
+      let token = if quoted {
+         Token::Word { text, quoted: true }
+      } else {
+         match text.to_ascii_uppercase().as_str() {
+            "AND" | "&&" => Token::And,
+            "OR" | "||" => Token::Or,
+            "NOT" | "!" => Token::Not,
+            _ => Token::Word { text, quoted: false },
+         }
+      };
+      tokens.push(token);
+   }
+
+   Ok(tokens)
+}
+
+struct Parser<'a> {
+   tokens: &'a [Token],
+   pos:    usize,
+   now:    DateTime<Utc>,
+}
+
+impl Parser<'_> {
+   fn peek(&self) -> Option<&Token> {
+      self.tokens.get(self.pos)
+   }
+
+   fn next(&mut self) -> Option<&Token> {
+      let tok = self.tokens.get(self.pos);
+      self.pos += 1;
+      tok
+   }
+
+   fn parse_or(&mut self) -> Result<Expr> {
+      let mut lhs = self.parse_and()?;
+      while self.peek() == Some(&Token::Or) {
+         self.next();
+         let rhs = self.parse_and()?;
+         lhs = Expr::Or(Box::new(lhs), Box::new(rhs));
+      }
+      Ok(lhs)
+   }
+
+   fn parse_and(&mut self) -> Result<Expr> {
+      let mut lhs = self.parse_unary()?;
+      loop {
+         match self.peek() {
+            Some(Token::And) => {
+               self.next();
+            },
+            Some(Token::Word { .. } | Token::Not | Token::LParen) => {},
+            _ => break,
+         }
+         let rhs = self.parse_unary()?;
+         lhs = Expr::And(Box::new(lhs), Box::new(rhs));
+      }
+      Ok(lhs)
+   }
+
+   fn parse_unary(&mut self) -> Result<Expr> {
+      let now = self.now;
+      match self.next().cloned() {
+         Some(Token::Not) => Ok(Expr::Not(Box::new(self.parse_unary()?))),
+         Some(Token::LParen) => {
+            let inner = self.parse_or()?;
+            match self.next() {
+               Some(Token::RParen) => Ok(inner),
+               _ => anyhow::bail!("Missing ')' in query expression"),
+            }
+         },
+         Some(Token::Word { text, quoted }) => {
+            if quoted {
+               Ok(Expr::Pred(Predicate::Text(text.to_lowercase())))
+            } else {
+               parse_term(&text, now).map(Expr::Pred)
+            }
+         },
+         Some(tok) => anyhow::bail!("Unexpected {} in query expression", describe(&tok)),
+         None => anyhow::bail!("Unexpected end of query expression"),
+      }
+   }
+}
+
+fn split_operator(term: &str) -> Option<(&str, CmpOp, &str)> {
+   let field_end = term.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))?;
+   if field_end == 0 {
+      return None;
+   }
+
+   let (field, rest) = term.split_at(field_end);
+   let (op, len) = if rest.starts_with(">=") {
+      (CmpOp::Ge, 2)
+   } else if rest.starts_with("<=") {
+      (CmpOp::Le, 2)
+   } else if rest.starts_with("!=") {
+      (CmpOp::Ne, 2)
+   } else if rest.starts_with('>') {
+      (CmpOp::Gt, 1)
+   } else if rest.starts_with('<') {
+      (CmpOp::Lt, 1)
+   } else if rest.starts_with('=') {
+      (CmpOp::Eq, 1)
+   } else if rest.starts_with(':') {
+      (CmpOp::Match, 1)
+   } else {
+      return None;
+   };
+
+   Some((field, op, &rest[len..]))
+}
+
+fn parse_term(term: &str, now: DateTime<Utc>) -> Result<Predicate> {
+   let Some((field, op, value)) = split_operator(term) else {
+      return Ok(Predicate::Text(term.to_lowercase()));
+   };
+
+   if value.is_empty() {
+      anyhow::bail!("Missing value for '{field}' in query expression");
+   }
+
+   let field = field.to_ascii_lowercase();
+   let equality_only = |name: &str| -> Result<()> {
+      if matches!(op, CmpOp::Match | CmpOp::Eq | CmpOp::Ne) {
+         Ok(())
+      } else {
+         anyhow::bail!("Field '{name}' only supports ':', '=' and '!='")
+      }
+   };
+
+   let pred = match field.as_str() {
+      "status" | "is" => {
+         equality_only("status")?;
+         Predicate::Status(op, Status::parse(value)?)
+      },
+      "priority" | "p" => Predicate::Priority(op, parse_priority(value)?),
+      "tag" | "t" => {
+         equality_only("tag")?;
+         Predicate::Tag(op, value.trim_start_matches('#').to_lowercase())
+      },
+      "file" | "f" => {
+         equality_only("file")?;
+         Predicate::File(op, value.to_string())
+      },
+      "file_glob" | "glob" => {
+         equality_only("file_glob")?;
+         Predicate::FileMatch(op, FileMatcher::glob(value, repo_root().ok().as_deref())?)
+      },
+      "file_regex" | "regex" => {
+         equality_only("file_regex")?;
+         Predicate::FileMatch(op, FileMatcher::regex(value, repo_root().ok().as_deref())?)
+      },
+      "title" => {
+         equality_only("title")?;
+         Predicate::Title(op, value.to_lowercase())
+      },
+      "kind" | "k" => {
+         equality_only("kind")?;
+         Predicate::Kind(op, value.to_lowercase())
+      },
+      "milestone" | "m" => {
+         equality_only("milestone")?;
+         Predicate::Milestone(op, value.to_lowercase())
+      },
+      "version" | "target_version" => {
+         equality_only("version")?;
+         Predicate::Version(op, value.to_lowercase())
+      },
+      "effort" | "e" => Predicate::Effort(op, parse_effort(value)?),
+      "id" | "num" => Predicate::Id(
+         op,
+         value
+            .trim_start_matches('#')
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid issue number in query: {value}"))?,
+      ),
+      "created" => Predicate::Date(DateField::Created, op, parse_date(value, now)?),
+      "started" => Predicate::Date(DateField::Started, op, parse_date(value, now)?),
+      "closed" => Predicate::Date(DateField::Closed, op, parse_date(value, now)?),
+      "updated" => Predicate::Date(DateField::Updated, op, parse_date(value, now)?),
+      _ => anyhow::bail!(
+         "Unknown query field '{field}'. Known fields: status, priority, tag, file, file_glob, \
+          file_regex, title, kind, milestone, version, effort, id, created, started, closed, \
+          updated"
+      ),
+   };
+
+   Ok(pred)
+}
+
+fn parse_priority(value: &str) -> Result<Priority> {
+   Ok(match value.to_ascii_lowercase().as_str() {
+      "critical" | "crit" => Priority::Critical,
+      "high" => Priority::High,
+      "medium" | "med" => Priority::Medium,
+      "low" => Priority::Low,
+      _ => anyhow::bail!("Unknown priority in query: {value}. Use: critical, high, medium, low"),
+   })
+}
+
+fn parse_date(value: &str, now: DateTime<Utc>) -> Result<DateValue> {
+   if let Some(offset) = value.strip_prefix('-') {
+      let split = offset
+         .find(|c: char| !c.is_ascii_digit())
+         .unwrap_or(offset.len());
+      let (num, unit) = offset.split_at(split);
+      let num: i64 = num
+         .parse()
+         .map_err(|_| anyhow::anyhow!("Invalid relative date in query: {value}"))?;
+      let delta = match unit {
+         "h" => Duration::hours(num),
+         "d" | "" => Duration::days(num),
+         "w" => Duration::weeks(num),
+         _ => anyhow::bail!("Unknown relative date unit in query: {value} (use h, d, w)"),
+      };
+      return Ok(DateValue::Instant(now - delta));
+   }
+
+   let day = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+      .map_err(|_| anyhow::anyhow!("Invalid date in query: {value} (use YYYY-MM-DD or -30d)"))?;
+   let start = Utc.from_utc_datetime(&day.and_hms_opt(0, 0, 0).unwrap_or_default());
+   Ok(DateValue::Day(start))
+}
+
+/// Severity rank where higher means more urgent
+fn priority_rank(priority: Priority) -> u8 {
+   3 - priority.sort_key()
+}
+
+fn compare<T: PartialOrd>(op: CmpOp, lhs: T, rhs: T) -> bool {
+   match op {
+      CmpOp::Match | CmpOp::Eq => lhs == rhs,
+      CmpOp::Ne => lhs != rhs,
+      CmpOp::Lt => lhs < rhs,
+      CmpOp::Le => lhs <= rhs,
+      CmpOp::Gt => lhs > rhs,
+      CmpOp::Ge => lhs >= rhs,
+   }
+}
+
+fn compare_date(op: CmpOp, actual: DateTime<Utc>, expected: &DateValue) -> bool {
+   match expected {
+      DateValue::Instant(at) => match op {
+         CmpOp::Match => actual >= *at,
+         _ => compare(op, actual, *at),
+      },
+      DateValue::Day(start) => {
+         let end = *start + Duration::days(1);
+         match op {
+            CmpOp::Match | CmpOp::Eq => actual >= *start && actual < end,
+            CmpOp::Ne => actual < *start || actual >= end,
+            CmpOp::Lt => actual < *start,
+            CmpOp::Le => actual < end,
+            CmpOp::Gt => actual >= end,
+            CmpOp::Ge => actual >= *start,
+         }
+      },
+   }
+}
+
+fn match_string(op: CmpOp, actual: &str, expected: &str) -> bool {
+   match op {
+      CmpOp::Match => actual.to_lowercase().contains(expected),
+      CmpOp::Eq => actual.eq_ignore_ascii_case(expected),
+      CmpOp::Ne => !actual.eq_ignore_ascii_case(expected),
+      _ => false,
+   }
+}
+
+impl Expr {
+   fn eval(&self, issue: &IssueWithId) -> bool {
+      match self {
+         Self::And(lhs, rhs) => lhs.eval(issue) && rhs.eval(issue),
+         Self::Or(lhs, rhs) => lhs.eval(issue) || rhs.eval(issue),
+         Self::Not(inner) => !inner.eval(issue),
+         Self::Pred(pred) => pred.eval(issue),
+      }
+   }
+}
+
+impl Predicate {
+   fn eval(&self, issue: &IssueWithId) -> bool {
+      let meta = &issue.issue.metadata;
+      match self {
+         Self::Status(CmpOp::Ne, status) => meta.status != *status,
+         Self::Status(_, status) => meta.status == *status,
+         Self::Priority(op, priority) => {
+            compare(*op, priority_rank(meta.priority), priority_rank(*priority))
+         },
+         Self::Tag(CmpOp::Match, query) => meta.tags.iter().any(|t| fuzzy_match_tag(query, t)),
+         Self::Tag(CmpOp::Ne, tag) => !meta.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)),
+         Self::Tag(_, tag) => meta.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)),
+         Self::File(CmpOp::Ne, path) => !meta.files.iter().any(|f| f == path),
+         Self::File(op, path) => meta.files.iter().any(|f| match op {
+            CmpOp::Match => f.contains(path.as_str()),
+            _ => f == path,
+         }),
+         Self::FileMatch(CmpOp::Ne, matcher) => !matcher.matches(&meta.files),
+         Self::FileMatch(_, matcher) => matcher.matches(&meta.files),
+         Self::Title(op, title) => match_string(*op, &meta.title, title),
+         Self::Kind(CmpOp::Ne, kind) => !meta
+            .kind
+            .as_deref()
+            .is_some_and(|k| k.eq_ignore_ascii_case(kind)),
+         Self::Kind(_, kind) => meta
+            .kind
+            .as_deref()
+            .is_some_and(|k| k.eq_ignore_ascii_case(kind)),
+         Self::Milestone(op, milestone) => match meta.milestone.as_deref() {
+            Some(actual) => match_string(*op, actual, milestone),
+            None => *op == CmpOp::Ne,
+         },
+         Self::Version(op, version) => match meta.target_version.as_deref() {
+            // `v1.3` and `1.3` are the same release
+            Some(actual) if *op == CmpOp::Eq => same_version(actual, version),
+            Some(actual) if *op == CmpOp::Ne => !same_version(actual, version),
+            Some(actual) => match_string(*op, actual, version),
+            None => *op == CmpOp::Ne,
+         },
+         Self::Effort(op, minutes) => meta
+            .effort
+            .as_deref()
+            .and_then(|e| parse_effort(e).ok())
+            .is_some_and(|actual| compare(*op, actual, *minutes)),
+         Self::Id(op, id) => compare(*op, issue.id, *id),
+         Self::Date(field, op, value) => {
+            let actual = match field {
+               DateField::Created => Some(meta.created),
+               DateField::Started => meta.started,
+               DateField::Closed => meta.closed,
+               DateField::Updated => Some(meta.updated_at()),
+            };
+            actual.is_some_and(|at| compare_date(*op, at, value))
+         },
+         Self::Text(text) => {
+            meta.title.to_lowercase().contains(text)
+               || issue.issue.body.to_string().to_lowercase().contains(text)
+         },
+      }
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::issue::test_support::issue;
+
+   #[test]
+   fn test_parse_and_match() {
+      let q = Query::parse(
+         "status:open AND (priority>=high OR tag:security) AND effort<2h AND created>-30d",
+      )
+      .unwrap();
+
+      assert!(q.matches(&issue(1).priority(Priority::High).effort("1h").build()));
+      assert!(q.matches(&issue(2).tags(&["security"]).effort("30m").build()));
+      assert!(!q.matches(&issue(3).tags(&["docs"]).effort("30m").build()));
+      assert!(
+         !q.matches(
+            &issue(4)
+               .priority(Priority::Critical)
+               .status(Status::Blocked)
+               .effort("30m")
+               .build()
+         )
+      );
+      assert!(!q.matches(&issue(5).priority(Priority::Critical).effort("1d").build()));
+      let wip = Query::parse("status:in_progress").unwrap();
+      assert!(wip.matches(&issue(8).status(Status::InProgress).build()));
+
+      let mut bug = issue(6).build();
+      bug.issue.metadata.kind = Some("bug".into());
+      assert!(Query::parse("kind:Bug").unwrap().matches(&bug));
+      assert!(!Query::parse("kind!=bug").unwrap().matches(&bug));
+      assert!(
+         Query::parse("kind!=bug")
+            .unwrap()
+            .matches(&issue(7).build())
+      );
+   }
+
+   #[test]
+   fn test_implicit_and_negation_and_text() {
+      let q = Query::parse("priority:critical -tag:wontfix \"token refresh\"").unwrap();
+      let critical = || {
+         issue(1)
+            .priority(Priority::Critical)
+            .body("Token refresh fails")
+      };
+      assert!(q.matches(&critical().build()));
+      assert!(!q.matches(&critical().tags(&["wontfix"]).build()));
+      assert!(!q.matches(&critical().priority(Priority::High).build()));
+
+      let q = Query::parse("NOT file:src/tui OR id=7").unwrap();
+      assert!(q.matches(&issue(7).files(&["src/tui/mod.rs"]).build()));
+      assert!(!q.matches(&issue(8).files(&["src/tui/mod.rs"]).build()));
+   }
+
+   #[test]
+   fn test_parse_errors() {
+      assert!(Query::parse("").is_err());
+      assert!(Query::parse("(status:open").is_err());
+      assert!(Query::parse("color:red").is_err());
+      assert!(Query::parse("status>open").is_err());
+      assert!(Query::parse("priority:urgent").is_err());
+      assert!(Query::parse("title:\"unterminated").is_err());
+
+      // Bounds from flags are checked as values, not pasted into an expression
+      let since = Query::since("created", "30d").unwrap();
+      assert_eq!(since.source(), "created>=-30d");
+      assert!(since.matches(&issue(1).build()));
+      assert_eq!(Query::since("closed", "2025-01-31").unwrap().source(), "closed>=2025-01-31");
+      assert!(Query::since("created", "30d OR id:1").is_err());
+      assert!(Query::since("created", "30d) OR (id:1").is_err());
+      assert!(Query::since("due", "30d").is_err());
+   }
+}
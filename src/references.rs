@@ -0,0 +1,111 @@
+//! Cross-references between issues
+//!
+//! An issue body that mentions another issue as `#12` or by its full ref
+//! (`ISSUE-12`, with the configured prefix) references it. Storage records
+//! the mentioned issues in the `references` frontmatter field on every save,
+//! so `show`, `dependencies` and the web dashboard can list both directions
+//! without rereading every body. Mentions inside code are ignored.
+
+use regex::Regex;
+
+/// Matches a mention: what precedes it, `#` or the full ref's prefix, and
+/// the number
+fn mention_regex(prefix: Option<&str>) -> Regex {
+   let refs = match prefix {
+      Some(prefix) => format!(r"#|{}-", regex::escape(prefix)),
+      None => "#".to_string(),
+   };
+   // Not preceded by a word character, so `abc#1`, `v2-3` and URL fragments
+   // like `page#12` don't count
+   Regex::new(&format!(r"(^|[^\w&#/-])({refs})(\d+)\b")).unwrap()
+}
+
+/// Issue numbers mentioned in `text`, ascending and without repeats
+pub fn mentions(text: &str, prefix: Option<&str>) -> Vec<u32> {
+   let mention = mention_regex(prefix);
+
+   let mut nums = Vec::new();
+   let mut in_fence = false;
+   for line in text.lines() {
+      if line.trim_start().starts_with("```") {
+         in_fence = !in_fence;
+         continue;
+      }
+      if in_fence {
+         continue;
+      }
+      // Odd-numbered pieces between backticks are inline code
+      for prose in line.split('`').step_by(2) {
+         nums.extend(
+            mention
+               .captures_iter(prose)
+               .filter_map(|caps| caps[3].parse::<u32>().ok()),
+         );
+      }
+   }
+   nums.sort_unstable();
+   nums.dedup();
+   nums
+}
+
+/// `text` with each mention renumbered by `map`, full refs switching from
+/// prefix `from` to `to`; mentions `map` has no number for, and those inside
+/// code, stay as they are
+pub fn rewrite(text: &str, from: &str, to: &str, map: impl Fn(u32) -> Option<u32>) -> String {
+   let mention = mention_regex(Some(from));
+   let mut in_fence = false;
+   let lines: Vec<String> = text
+      .split('\n')
+      .map(|line| {
+         if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            return line.to_string();
+         }
+         if in_fence {
+            return line.to_string();
+         }
+         let pieces: Vec<String> = line
+            .split('`')
+            .enumerate()
+            .map(|(i, piece)| {
+               if i % 2 == 1 {
+                  return piece.to_string();
+               }
+               mention
+                  .replace_all(piece, |caps: &regex::Captures| {
+                     let renumbered = caps[3].parse().ok().and_then(&map);
+                     match renumbered {
+                        Some(num) if &caps[2] == "#" => format!("{}#{num}", &caps[1]),
+                        Some(num) => format!("{}{to}-{num}", &caps[1]),
+                        None => caps[0].to_string(),
+                     }
+                  })
+                  .into_owned()
+            })
+            .collect();
+         pieces.join("`")
+      })
+      .collect();
+   lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn test_mentions() {
+      let body = "Follows #12 and AX-3 (see also #12).\nNot `#40`, page#7, v2-9 or \
+                  #x.\n```\nAX-41\n```\nFixed once AX-5, #2 land";
+      assert_eq!(mentions(body, Some("AX")), [2, 3, 5, 12]);
+      assert_eq!(mentions(body, None), [2, 12]);
+      assert!(mentions("Heading\n# 1 step", Some("AX")).is_empty());
+
+      let renumbered = rewrite(body, "AX", "BUG", |n| (n != 5).then_some(n + 100));
+      assert_eq!(
+         renumbered,
+         "Follows #112 and BUG-103 (see also #112).\nNot `#40`, page#7, v2-9 or \
+          #x.\n```\nAX-41\n```\nFixed once AX-5, #102 land"
+      );
+   }
+}
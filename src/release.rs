@@ -0,0 +1,293 @@
+//! Release planning
+//!
+//! Issues are tied to a release with their `target_version`. `agentx
+//! release status v1.3` compares what is closed against what is left, with
+//! the remaining effort; `agentx release cut v1.3` refuses while a critical
+//! issue for the release is open, and otherwise writes the release's
+//! changelog section from its closed issues, grouped by their kind (see
+//! `kinds`) or else their tags.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+   issue::{IssueWithId, Priority, Status},
+   kinds::KindConfig,
+   utils::parse_effort,
+};
+
+/// Changelog sections in the order they are written, with the tags that put
+/// an issue in them; issues without any of these tags are `Changed`
+const SECTIONS: &[(&str, &[&str])] = &[
+   ("Added", &["feature", "feat", "enhancement"]),
+   ("Changed", &[]),
+   ("Fixed", &["bug", "fix", "bugfix", "regression"]),
+   ("Security", &["security"]),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseIssue {
+   pub bug_num:  u32,
+   pub title:    String,
+   pub priority: Priority,
+   pub status:   String,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub effort:   Option<String>,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub kind:     Option<String>,
+   #[serde(skip_serializing_if = "Vec::is_empty", default)]
+   pub tags:     Vec<String>,
+}
+
+/// Where a release stands
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseStatus {
+   pub version:           String,
+   pub open:              Vec<ReleaseIssue>,
+   pub closed:            Vec<ReleaseIssue>,
+   /// Estimated effort left on the open issues, in minutes
+   pub remaining_minutes: u32,
+   /// Open issues without an effort estimate, not in `remaining_minutes`
+   pub unestimated:       usize,
+}
+
+impl ReleaseStatus {
+   /// Open critical issues, which stop the release from being cut
+   pub fn critical_open(&self) -> Vec<&ReleaseIssue> {
+      self
+         .open
+         .iter()
+         .filter(|i| i.priority == Priority::Critical)
+         .collect()
+   }
+}
+
+/// Whether `target` names release `version`; case and a leading `v` don't
+/// matter, so `v1.3` and `1.3` are the same release
+pub fn same_version(target: &str, version: &str) -> bool {
+   let bare = |v: &str| v.trim().trim_start_matches(['v', 'V']).to_lowercase();
+   bare(target) == bare(version)
+}
+
+/// The issues targeting `version`, split into open and closed
+pub fn status(version: &str, issues: &[IssueWithId]) -> ReleaseStatus {
+   let mut targeting: Vec<&IssueWithId> = issues
+      .iter()
+      .filter(|i| {
+         i.issue
+            .metadata
+            .target_version
+            .as_deref()
+            .is_some_and(|v| same_version(v, version))
+      })
+      .collect();
+   targeting.sort_by_key(|i| (i.issue.metadata.priority, i.id));
+
+   let mut result = ReleaseStatus {
+      version:           version.to_string(),
+      open:              Vec::new(),
+      closed:            Vec::new(),
+      remaining_minutes: 0,
+      unestimated:       0,
+   };
+   for issue in targeting {
+      let meta = &issue.issue.metadata;
+      let entry = ReleaseIssue {
+         bug_num:  issue.id,
+         title:    meta.title.to_string(),
+         priority: meta.priority,
+         status:   meta.status.to_string(),
+         effort:   meta.effort.as_ref().map(|e| e.to_string()),
+         kind:     meta.kind.as_ref().map(|k| k.to_string()),
+         tags:     meta.tags.iter().map(|t| t.to_string()).collect(),
+      };
+      if matches!(meta.status, Status::Done | Status::Closed) {
+         result.closed.push(entry);
+         continue;
+      }
+      match meta.effort.as_deref().and_then(|e| parse_effort(e).ok()) {
+         Some(minutes) => result.remaining_minutes += minutes,
+         None => result.unestimated += 1,
+      }
+      result.open.push(entry);
+   }
+   result
+}
+
+/// The changelog section for a release of `closed` issues on `date`;
+/// headings `kinds` add come after the standard ones
+pub fn changelog(
+   version: &str,
+   closed: &[ReleaseIssue],
+   kinds: &[KindConfig],
+   date: NaiveDate,
+   issue_ref: impl Fn(u32) -> String,
+) -> String {
+   let mut headings: Vec<&str> = SECTIONS.iter().map(|(heading, _)| *heading).collect();
+   for heading in kinds.iter().filter_map(|k| k.changelog.as_deref()) {
+      if !headings.contains(&heading) {
+         headings.push(heading);
+      }
+   }
+
+   let mut text = format!("## {version} ({date})\n");
+   for heading in headings {
+      let entries: Vec<&ReleaseIssue> = closed
+         .iter()
+         .filter(|i| section_of(i, kinds) == heading)
+         .collect();
+      if entries.is_empty() {
+         continue;
+      }
+      text.push_str(&format!("\n### {heading}\n\n"));
+      for issue in entries {
+         text.push_str(&format!("- {} ({})\n", issue.title, issue_ref(issue.bug_num)));
+      }
+   }
+   text
+}
+
+/// The changelog heading an issue goes under: its kind's, else one from
+/// its tags
+fn section_of<'a>(issue: &ReleaseIssue, kinds: &'a [KindConfig]) -> &'a str {
+   let by_kind = issue.kind.as_deref().and_then(|kind| {
+      kinds
+         .iter()
+         .find(|k| k.name.eq_ignore_ascii_case(kind))
+         .and_then(|k| k.changelog.as_deref())
+   });
+   if let Some(heading) = by_kind {
+      return heading;
+   }
+   SECTIONS
+      .iter()
+      .find(|(_, tags)| {
+         issue
+            .tags
+            .iter()
+            .any(|tag| tags.iter().any(|t| tag.eq_ignore_ascii_case(t)))
+      })
+      .map_or("Changed", |(heading, _)| heading)
+}
+
+/// Whether `changelog` already has a section for `version`
+pub fn has_section(changelog: &str, version: &str) -> bool {
+   changelog.lines().any(|line| {
+      line
+         .strip_prefix("## ")
+         .and_then(|heading| heading.split_whitespace().next())
+         .is_some_and(|v| same_version(v, version))
+   })
+}
+
+/// `changelog` with `section` added: below the document's `# ` title if it
+/// has one, otherwise at the top
+pub fn insert_section(changelog: &str, section: &str) -> String {
+   let lines: Vec<&str> = changelog.lines().collect();
+   let Some(title) = lines.iter().position(|l| l.starts_with("# ")) else {
+      return if changelog.trim().is_empty() {
+         section.to_string()
+      } else {
+         format!("{section}\n{changelog}")
+      };
+   };
+   // Keep the title's intro paragraph above the new section
+   let mut at = title + 1;
+   while at < lines.len() && !lines[at].starts_with("## ") {
+      at += 1;
+   }
+   let before = lines[..at].join("\n");
+   let after = lines[at..].join("\n");
+   let mut out = format!("{}\n\n{section}", before.trim_end());
+   if !after.is_empty() {
+      out.push('\n');
+      out.push_str(&after);
+      out.push('\n');
+   }
+   out
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::{issue::test_support::issue, kinds::default_kinds};
+
+   #[test]
+   fn test_status_and_changelog() {
+      let mut login = issue(1)
+         .title("Fix login timeout")
+         .priority(Priority::High)
+         .target_version("v1.3")
+         .tags(&["bug"])
+         .effort("2h")
+         .build();
+      login.issue.metadata.set_status(Status::Closed);
+      let mut export = issue(2)
+         .title("CSV export")
+         .target_version("1.3")
+         .tags(&["feature"])
+         .effort("2h")
+         .build();
+      export.issue.metadata.set_status(Status::Done);
+      let crash = issue(3)
+         .title("Crash on start")
+         .priority(Priority::Critical)
+         .target_version("V1.3")
+         .effort("2h")
+         .build();
+      let mut docs = issue(4)
+         .title("Document flags")
+         .priority(Priority::Low)
+         .target_version("v1.3")
+         .tags(&["docs"])
+         .effort("2h")
+         .build();
+      docs.issue.metadata.effort = None;
+      let later = issue(5)
+         .title("Plugins")
+         .priority(Priority::High)
+         .target_version("v1.4")
+         .effort("2h")
+         .build();
+
+      let status = status("v1.3", &[login, export, crash, docs, later]);
+      let open: Vec<u32> = status.open.iter().map(|i| i.bug_num).collect();
+      assert_eq!(open, [3, 4]);
+      assert_eq!(status.closed.len(), 2);
+      assert_eq!(status.remaining_minutes, 120);
+      assert_eq!(status.unestimated, 1);
+      assert_eq!(status.critical_open()[0].bug_num, 3);
+
+      let date = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+      let kinds = default_kinds();
+      let section = changelog("v1.3", &status.closed, &kinds, date, |n| format!("ISSUE-{n}"));
+      assert_eq!(
+         section,
+         "## v1.3 (2026-10-17)\n\n### Added\n\n- CSV export (ISSUE-2)\n\n### Fixed\n\n- Fix login \
+          timeout (ISSUE-1)\n"
+      );
+      // The kind wins over tags, and adds its own heading
+      let mut by_kind = status.closed.clone();
+      by_kind[0].kind = Some("chore".into());
+      by_kind[1].kind = Some("docs".into());
+      let docs: Vec<KindConfig> =
+         serde_yaml::from_str("- name: docs\n  changelog: Documentation").unwrap();
+      let kinds = [kinds, docs].concat();
+      assert_eq!(
+         changelog("v1.3", &by_kind, &kinds, date, |n| format!("#{n}")),
+         "## v1.3 (2026-10-17)\n\n### Changed\n\n- Fix login timeout (#1)\n\n### \
+          Documentation\n\n- CSV export (#2)\n"
+      );
+
+      let existing = "# Changelog\n\nAll notable changes.\n\n## v1.2 (2026-09-01)\n\n- Old\n";
+      assert_eq!(
+         insert_section(existing, &section),
+         format!(
+            "# Changelog\n\nAll notable changes.\n\n{section}\n## v1.2 (2026-09-01)\n\n- Old\n"
+         )
+      );
+      assert_eq!(insert_section("", &section), section);
+      assert!(has_section(existing, "1.2"));
+      assert!(!has_section(existing, "v1.3"));
+   }
+}
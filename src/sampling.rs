@@ -0,0 +1,120 @@
+//! Issue drafts written by the client's model
+//!
+//! The `issues_draft` MCP tool turns a rough one-line description into a
+//! full issue by asking the client to run its own model through MCP
+//! sampling (`sampling/createMessage`), so agentx never holds an API key.
+//! The draft comes back for the agent or user to confirm and pass to
+//! `issues_create`; nothing is created here.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+/// Most tokens the client's model may spend on a draft
+const MAX_TOKENS: u32 = 1000;
+
+/// Existing tags named in the prompt, most used first
+const MAX_TAGS: usize = 30;
+
+const PRIORITIES: &[&str] = &["critical", "high", "medium", "low"];
+
+const SYSTEM_PROMPT: &str =
+   "You write issues for a software project's issue tracker. Reply with one JSON object and \
+    nothing else, with these keys: title (short, imperative), priority (critical, high, medium or \
+    low), tags (list of short lowercase words), issue (what is wrong or missing), impact (who or \
+    what it affects and why it matters), acceptance (concrete criteria for calling it done, as a \
+    markdown list) and effort (an estimate such as 30m, 2h or 1d). Don't invent facts the \
+    description doesn't support; say what needs confirming instead.";
+
+/// A drafted issue, in the shape `issues_create` takes
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IssueDraft {
+   pub title:      String,
+   #[serde(default)]
+   pub priority:   String,
+   #[serde(default)]
+   pub tags:       Vec<String>,
+   #[serde(default)]
+   pub issue:      String,
+   #[serde(default)]
+   pub impact:     String,
+   #[serde(default)]
+   pub acceptance: String,
+   #[serde(default)]
+   pub effort:     Option<String>,
+}
+
+/// `sampling/createMessage` parameters asking for a draft of `description`,
+/// preferring the tracker's `known_tags`
+pub fn request(description: &str, known_tags: &[String]) -> Value {
+   let mut prompt = format!("Draft an issue for: {}", description.trim());
+   if !known_tags.is_empty() {
+      let tags = &known_tags[..known_tags.len().min(MAX_TAGS)];
+      prompt.push_str(&format!("\n\nTags already in use (prefer these): {}", tags.join(", ")));
+   }
+   json!({
+      "messages": [{"role": "user", "content": {"type": "text", "text": prompt}}],
+      "systemPrompt": SYSTEM_PROMPT,
+      "includeContext": "none",
+      "maxTokens": MAX_TOKENS,
+   })
+}
+
+/// The draft in a model's reply, which may wrap the JSON object in prose or
+/// a code fence
+pub fn parse(reply: &str) -> Result<IssueDraft> {
+   let (start, end) = reply
+      .find('{')
+      .zip(reply.rfind('}'))
+      .filter(|(start, end)| start < end)
+      .context("The model's reply has no JSON object")?;
+   let mut draft: IssueDraft = serde_json::from_str(&reply[start..=end])
+      .context("The model's reply is not a draft issue")?;
+   if draft.title.trim().is_empty() {
+      anyhow::bail!("The model's draft has no title");
+   }
+   draft.priority = draft.priority.to_lowercase();
+   if !PRIORITIES.contains(&draft.priority.as_str()) {
+      draft.priority = "medium".to_string();
+   }
+   draft.tags = draft
+      .tags
+      .iter()
+      .map(|t| t.trim().trim_start_matches('#').to_lowercase())
+      .filter(|t| !t.is_empty())
+      .collect();
+   Ok(draft)
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn test_request_and_parse() {
+      let params = request(" login times out on slow networks ", &["auth".to_string()]);
+      let prompt = params["messages"][0]["content"]["text"].as_str().unwrap();
+      assert!(prompt.starts_with("Draft an issue for: login times out on slow networks\n"));
+      assert!(prompt.ends_with("(prefer these): auth"));
+
+      let reply = "Here you go:\n```json\n{\"title\": \"Raise login timeout\", \"priority\": \
+                   \"High\", \"tags\": [\"#Auth\", \" \"], \"issue\": \"Times out\", \
+                   \"acceptance\": \"- [ ] Works on 3G\", \"effort\": \"2h\"}\n```";
+      let draft = parse(reply).unwrap();
+      assert_eq!(draft.title, "Raise login timeout");
+      assert_eq!(
+         (draft.priority.as_str(), draft.tags.as_slice()),
+         ("high", &["auth".to_string()][..])
+      );
+      assert_eq!(draft.impact, "");
+
+      assert_eq!(
+         parse("{\"title\": \"x\", \"priority\": \"urgent\"}")
+            .unwrap()
+            .priority,
+         "medium"
+      );
+      assert!(parse("no idea").is_err());
+      assert!(parse("{\"title\": \" \"}").is_err());
+   }
+}
@@ -0,0 +1,229 @@
+//! Full-text search
+//!
+//! Shared by `agentx search` and the `issues_search` MCP tool. A query is
+//! split into words, and an issue matches when every word appears in its
+//! title, tags or body (ignoring case). Matches rank by where the words were
+//! found, title above tag above body, so an issue named after the query
+//! comes before one that merely mentions it. Each hit carries a snippet of
+//! the body around the first match.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::issue::{IssueWithId, Priority};
+
+/// Score of a word found in the title; enough that no number of tag or body
+/// matches outranks it
+const TITLE_WEIGHT: u32 = 10_000;
+const TAG_WEIGHT: u32 = 100;
+/// Score of each body occurrence of a word, counted up to `MAX_BODY_HITS`
+const BODY_WEIGHT: u32 = 1;
+const MAX_BODY_HITS: u32 = 50;
+/// Extra score when the whole query appears in the title as typed
+const PHRASE_BONUS: u32 = TITLE_WEIGHT / 2;
+
+/// Characters of context kept on each side of a snippet's match
+const SNIPPET_CONTEXT: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Field {
+   Title,
+   Tag,
+   Body,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+   pub num:        u32,
+   pub title:      String,
+   pub priority:   Priority,
+   pub status:     String,
+   #[serde(skip_serializing_if = "Vec::is_empty", default)]
+   pub tags:       Vec<String>,
+   pub score:      u32,
+   /// The best place a query word was found
+   pub matched_in: Field,
+   /// Body text around the first match, on one line
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub snippet:    Option<String>,
+}
+
+/// A parsed query: its words, and a pattern finding any of them
+#[derive(Debug, Clone)]
+pub struct Terms {
+   phrase:  String,
+   words:   Vec<String>,
+   pattern: Option<Regex>,
+}
+
+impl Terms {
+   pub fn parse(query: &str) -> Self {
+      let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+      let pattern = (!words.is_empty()).then(|| {
+         let alternatives: Vec<String> = words.iter().map(|w| regex::escape(w)).collect();
+         Regex::new(&format!("(?i){}", alternatives.join("|"))).unwrap()
+      });
+      Self { phrase: words.join(" "), words, pattern }
+   }
+
+   pub fn is_empty(&self) -> bool {
+      self.words.is_empty()
+   }
+
+   /// `text` with each occurrence of a query word passed through `mark`
+   pub fn highlight(&self, text: &str, mark: impl Fn(&str) -> String) -> String {
+      match &self.pattern {
+         Some(pattern) => pattern
+            .replace_all(text, |caps: &regex::Captures| mark(&caps[0]))
+            .into_owned(),
+         None => text.to_string(),
+      }
+   }
+
+   /// One line of `body` around the first match, cut at word boundaries
+   fn snippet(&self, body: &str) -> Option<String> {
+      let found = self.pattern.as_ref()?.find(body)?;
+      let start = floor_boundary(body, found.start().saturating_sub(SNIPPET_CONTEXT));
+      let end = floor_boundary(body, (found.end() + SNIPPET_CONTEXT).min(body.len()));
+      // Drop the partial words at either edge
+      let mut window = &body[start..end];
+      if start > 0
+         && let Some(space) = window[..found.start() - start].find(char::is_whitespace)
+      {
+         window = &window[space..];
+      }
+      let window_start = end - window.len();
+      if end < body.len()
+         && let Some(space) = window[found.end() - window_start..].rfind(char::is_whitespace)
+      {
+         window = &window[..found.end() - window_start + space];
+      }
+      let text = window.split_whitespace().collect::<Vec<_>>().join(" ");
+      let prefix = if start > 0 { "…" } else { "" };
+      let suffix = if end < body.len() { "…" } else { "" };
+      Some(format!("{prefix}{text}{suffix}"))
+   }
+}
+
+fn floor_boundary(text: &str, mut at: usize) -> usize {
+   while !text.is_char_boundary(at) {
+      at -= 1;
+   }
+   at
+}
+
+/// Issues matching every word of `terms`, best first and then in the usual
+/// order ([`IssueWithId::stable_key`]); at most `limit`
+pub fn search(terms: &Terms, issues: &[IssueWithId], limit: Option<usize>) -> Vec<SearchHit> {
+   let mut hits: Vec<_> = issues
+      .iter()
+      .filter_map(|issue| Some((score(terms, issue)?, issue.stable_key())))
+      .collect();
+   hits.sort_by(|(a, a_key), (b, b_key)| b.score.cmp(&a.score).then(a_key.cmp(b_key)));
+   if let Some(limit) = limit {
+      hits.truncate(limit);
+   }
+   hits.into_iter().map(|(hit, _)| hit).collect()
+}
+
+fn score(terms: &Terms, issue: &IssueWithId) -> Option<SearchHit> {
+   if terms.is_empty() {
+      return None;
+   }
+   let meta = &issue.issue.metadata;
+   let title = meta.title.to_lowercase();
+   let tags: Vec<String> = meta.tags.iter().map(|t| t.to_lowercase()).collect();
+   // Section text only, so a search for "issue" doesn't match every
+   // `**Issue**:` heading
+   let body = issue
+      .issue
+      .body
+      .sections()
+      .iter()
+      .map(|s| s.content())
+      .collect::<Vec<_>>()
+      .join("\n");
+   let body_lower = body.to_lowercase();
+
+   let mut score = 0;
+   let mut matched_in = Field::Body;
+   for word in &terms.words {
+      let body_hits = (body_lower.matches(word.as_str()).count() as u32).min(MAX_BODY_HITS);
+      let word_score = if title.contains(word.as_str()) {
+         matched_in = Field::Title;
+         TITLE_WEIGHT
+      } else if tags.iter().any(|t| t.contains(word.as_str())) {
+         if matched_in == Field::Body {
+            matched_in = Field::Tag;
+         }
+         TAG_WEIGHT
+      } else if body_hits > 0 {
+         0
+      } else {
+         return None;
+      };
+      score += word_score + body_hits * BODY_WEIGHT;
+   }
+   if terms.words.len() > 1 && title.contains(&terms.phrase) {
+      score += PHRASE_BONUS;
+   }
+
+   Some(SearchHit {
+      num: issue.id,
+      title: meta.title.to_string(),
+      priority: meta.priority,
+      status: meta.status.to_string(),
+      tags: meta.tags.iter().map(|t| t.to_string()).collect(),
+      score,
+      matched_in,
+      snippet: terms.snippet(&body),
+   })
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::issue::test_support::issue;
+
+   #[test]
+   fn test_search_ranks_and_highlights() {
+      let issues = vec![
+         issue(1)
+            .title("Refactor config")
+            .body("The login timeout is read here; login is slow")
+            .build(),
+         issue(2)
+            .title("Session handling")
+            .tags(&["login"])
+            .body("Tokens expire")
+            .build(),
+         issue(3)
+            .title("Login timeout too short")
+            .body("Users get logged out")
+            .build(),
+         issue(4).title("Unrelated").body("Nothing to see").build(),
+      ];
+      assert!(search(&Terms::parse("issue"), &issues, None).is_empty());
+
+      let terms = Terms::parse("Login timeout");
+      let hits = search(&terms, &issues, None);
+      let nums: Vec<u32> = hits.iter().map(|h| h.num).collect();
+      // #2 has no "timeout" anywhere, so it doesn't match
+      assert_eq!(nums, [3, 1]);
+      assert_eq!(hits[0].matched_in, Field::Title);
+      assert_eq!(hits[1].matched_in, Field::Body);
+
+      let login = search(&Terms::parse("login"), &issues, Some(2));
+      let nums: Vec<u32> = login.iter().map(|h| h.num).collect();
+      assert_eq!(nums, [3, 2]);
+      assert_eq!(login[1].matched_in, Field::Tag);
+
+      assert_eq!(terms.highlight("LOGIN Timeout", |m| format!("[{m}]")), "[LOGIN] [Timeout]");
+      let long = format!("{} the login timeout {}", "word ".repeat(20), "tail ".repeat(20));
+      let snippet = Terms::parse("timeout").snippet(&long).unwrap();
+      assert!(snippet.starts_with("…word") && snippet.ends_with("tail…"), "{snippet}");
+      assert!(snippet.contains("the login timeout"));
+      assert!(search(&Terms::parse("  "), &issues, None).is_empty());
+   }
+}
@@ -0,0 +1,194 @@
+//! Work sessions
+//!
+//! `session start` records when work began; `session end` attributes the
+//! status changes and checkpoints made since then to that session and sums
+//! up the time each issue spent in progress during it.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+   flow::status_intervals,
+   issue::{IssueWithId, Status, Transition},
+};
+
+/// A running session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+   pub started: DateTime<Utc>,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub note:    Option<String>,
+}
+
+/// What happened to one issue during a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssueActivity {
+   pub bug_num:      u32,
+   pub title:        String,
+   pub transitions:  Vec<Transition>,
+   pub checkpoints:  Vec<String>,
+   /// Time spent in progress within the session
+   pub active_hours: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+   pub started: DateTime<Utc>,
+   pub ended:   DateTime<Utc>,
+   pub hours:   f64,
+   #[serde(skip_serializing_if = "Option::is_none", default)]
+   pub note:    Option<String>,
+   pub issues:  Vec<IssueActivity>,
+}
+
+fn hours(start: DateTime<Utc>, end: DateTime<Utc>) -> f64 {
+   (end - start).num_minutes().max(0) as f64 / 60.0
+}
+
+/// Attribute the activity on `issues` between the session's start and
+/// `ended` to the session; issues nothing happened to are left out
+pub fn summarize(
+   session: &Session,
+   ended: DateTime<Utc>,
+   issues: &[IssueWithId],
+) -> SessionSummary {
+   let within = |at: DateTime<Utc>| at >= session.started && at <= ended;
+
+   let mut activity = Vec::new();
+   for issue in issues {
+      let transitions: Vec<Transition> = issue
+         .issue
+         .metadata
+         .transitions
+         .iter()
+         .filter(|t| within(t.at))
+         .copied()
+         .collect();
+      let checkpoints: Vec<String> = issue
+         .issue
+         .body
+         .checkpoints()
+         .filter(|c| c.timestamp().is_some_and(within))
+         .map(|c| c.content().to_string())
+         .collect();
+      let active_hours: f64 = status_intervals(issue, ended)
+         .iter()
+         .filter(|i| i.status == Status::InProgress)
+         .map(|i| hours(i.start.max(session.started), i.end.min(ended)))
+         .sum();
+
+      if !transitions.is_empty() || !checkpoints.is_empty() || active_hours > 0.0 {
+         activity.push(IssueActivity {
+            bug_num: issue.id,
+            title: issue.issue.metadata.title.to_string(),
+            transitions,
+            checkpoints,
+            active_hours,
+         });
+      }
+   }
+   activity.sort_by(|a, b| b.active_hours.total_cmp(&a.active_hours));
+
+   SessionSummary {
+      started: session.started,
+      ended,
+      hours: hours(session.started, ended),
+      note: session.note.clone(),
+      issues: activity,
+   }
+}
+
+impl IssueActivity {
+   /// One line per status change and checkpoint, e.g. `open → active`
+   pub fn lines(&self) -> Vec<String> {
+      let mut lines: Vec<String> = self
+         .transitions
+         .iter()
+         .map(|t| format!("{} → {}", t.from, t.to))
+         .collect();
+      lines.extend(self.checkpoints.iter().map(|c| format!("checkpoint: {c}")));
+      lines
+   }
+}
+
+impl SessionSummary {
+   fn span(&self) -> String {
+      format!("{} → {}", self.started.format("%Y-%m-%d %H:%M"), self.ended.format("%Y-%m-%d %H:%M"))
+   }
+
+   /// Journal entry for the whole session, with `issue_ref` formatting issue
+   /// numbers
+   pub fn to_markdown(&self, issue_ref: impl Fn(u32) -> String) -> String {
+      let mut text = format!("## Session - {} ({:.1}h)\n\n", self.span(), self.hours);
+      if let Some(note) = &self.note {
+         text.push_str(&format!("{note}\n\n"));
+      }
+      if self.issues.is_empty() {
+         text.push_str("No issue activity\n\n");
+      }
+      for issue in &self.issues {
+         text.push_str(&format!(
+            "- {} {} ({:.1}h active)\n",
+            issue_ref(issue.bug_num),
+            issue.title,
+            issue.active_hours
+         ));
+         for line in issue.lines() {
+            text.push_str(&format!("  - {line}\n"));
+         }
+      }
+      if !self.issues.is_empty() {
+         text.push('\n');
+      }
+      text
+   }
+
+   /// Section appended to an issue the session touched
+   pub fn issue_section(&self, issue: &IssueActivity) -> String {
+      let mut text =
+         format!("\n\n## Session - {}\n\n{:.1}h active", self.span(), issue.active_hours);
+      for line in issue.lines() {
+         text.push_str(&format!("\n- {line}"));
+      }
+      text
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use chrono::Duration;
+
+   use super::*;
+   use crate::issue::{Priority, test_support::issue};
+
+   #[test]
+   fn test_summary_attributes_activity_inside_the_session() {
+      let now = Utc::now();
+      let mut active = issue(1)
+         .title("Fix login")
+         .body("Times out")
+         .created(now - Duration::hours(5))
+         .moved(Status::InProgress, now - Duration::hours(3))
+         .build();
+      let stamp = |at: DateTime<Utc>| at.format("%Y-%m-%d %H:%M").to_string();
+      active.issue.body.append(&format!(
+         "\n\n**Checkpoint** ({}): Before\n\n**Checkpoint** ({}): During",
+         stamp(now - Duration::hours(4)),
+         stamp(now - Duration::minutes(30))
+      ));
+      let issues = [active, issue(2).title("Untouched").priority(Priority::Low).build()];
+
+      let session = Session { started: now - Duration::hours(2), note: None };
+      let summary = summarize(&session, now, &issues);
+      assert_eq!(summary.issues.len(), 1);
+      let activity = &summary.issues[0];
+      assert!(activity.transitions.is_empty());
+      assert_eq!(activity.checkpoints, ["During"]);
+      assert!((activity.active_hours - 2.0).abs() < 0.05);
+      assert!(
+         summary
+            .to_markdown(|n| format!("#{n}"))
+            .contains("#1 Fix login (2.0h active)")
+      );
+   }
+}
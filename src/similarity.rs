@@ -0,0 +1,219 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::issue::{Issue, IssueWithId};
+
+/// Weights of the individual signals in the blended score; a signal only
+/// counts when both issues have data for it, so sparse issues aren't penalized
+/// for missing fields
+const TITLE_WEIGHT: f64 = 0.55;
+const BODY_WEIGHT: f64 = 0.25;
+const TAGS_WEIGHT: f64 = 0.1;
+const FILES_WEIGHT: f64 = 0.1;
+
+/// Number of words per body shingle
+const SHINGLE_SIZE: usize = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarIssue {
+   pub id:         u32,
+   pub title:      String,
+   pub similarity: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicatePair {
+   pub first:        u32,
+   pub first_title:  String,
+   pub second:       u32,
+   pub second_title: String,
+   pub similarity:   f64,
+}
+
+/// Pre-processed view of an issue used for pairwise comparison
+struct Fingerprint {
+   title:    String,
+   tags:     HashSet<String>,
+   files:    HashSet<String>,
+   shingles: HashSet<String>,
+}
+
+impl Fingerprint {
+   fn new(issue: &Issue) -> Self {
+      let meta = &issue.metadata;
+      Self {
+         title:    meta.title.to_lowercase(),
+         tags:     meta.tags.iter().map(|t| t.to_lowercase()).collect(),
+         files:    meta
+            .files
+            .iter()
+            .map(|f| f.trim_start_matches("./").to_string())
+            .collect(),
+         shingles: shingles(&issue.body.to_string()),
+      }
+   }
+
+   /// The larger of the title similarity and the blend of all signals, so a
+   /// near-identical title is always caught while matching bodies, tags and
+   /// files can surface dupes whose titles were worded differently
+   fn similarity(&self, other: &Self) -> f64 {
+      let title = strsim::jaro_winkler(&self.title, &other.title);
+      let mut score = TITLE_WEIGHT * title;
+      let mut weight = TITLE_WEIGHT;
+
+      for (a, b, w) in [
+         (&self.shingles, &other.shingles, BODY_WEIGHT),
+         (&self.tags, &other.tags, TAGS_WEIGHT),
+         (&self.files, &other.files, FILES_WEIGHT),
+      ] {
+         if !a.is_empty() && !b.is_empty() {
+            score += w * jaccard(a, b);
+            weight += w;
+         }
+      }
+
+      title.max(score / weight)
+   }
+}
+
+/// Word shingles of the body, ignoring markdown section labels like
+/// `**Issue**:` that every issue shares
+fn shingles(body: &str) -> HashSet<String> {
+   const LABELS: [&str; 4] = ["issue", "impact", "acceptance", "context"];
+
+   let words: Vec<String> = body
+      .split(|c: char| !c.is_alphanumeric())
+      .filter(|w| !w.is_empty())
+      .map(str::to_lowercase)
+      .filter(|w| !LABELS.contains(&w.as_str()))
+      .collect();
+
+   if words.len() < SHINGLE_SIZE {
+      return words.into_iter().collect();
+   }
+   words.windows(SHINGLE_SIZE).map(|w| w.join(" ")).collect()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+   let union = a.union(b).count();
+   if union == 0 {
+      return 0.0;
+   }
+   a.intersection(b).count() as f64 / union as f64
+}
+
+/// Similarity between two issues in `0.0..=1.0`, combining title
+/// (jaro-winkler), body shingles, tags and files (jaccard)
+pub fn similarity(a: &Issue, b: &Issue) -> f64 {
+   Fingerprint::new(a).similarity(&Fingerprint::new(b))
+}
+
+/// Existing issues at least `threshold` similar to `candidate`, most similar
+/// first
+pub fn find_similar(
+   candidate: &Issue,
+   existing: &[IssueWithId],
+   threshold: f64,
+) -> Vec<SimilarIssue> {
+   let fingerprint = Fingerprint::new(candidate);
+   let mut similar: Vec<_> = existing
+      .iter()
+      .filter_map(|other| {
+         let score = fingerprint.similarity(&Fingerprint::new(&other.issue));
+         (score >= threshold).then(|| SimilarIssue {
+            id:         other.id,
+            title:      other.issue.metadata.title.to_string(),
+            similarity: score,
+         })
+      })
+      .collect();
+
+   similar.sort_by(|a, b| b.similarity.total_cmp(&a.similarity).then(a.id.cmp(&b.id)));
+   similar
+}
+
+/// All pairs of issues at least `threshold` similar, most similar first
+pub fn find_duplicates(issues: &[IssueWithId], threshold: f64) -> Vec<DuplicatePair> {
+   let fingerprints: Vec<_> = issues.iter().map(|i| Fingerprint::new(&i.issue)).collect();
+   let mut pairs = Vec::new();
+
+   for (i, a) in issues.iter().enumerate() {
+      for (j, b) in issues.iter().enumerate().skip(i + 1) {
+         let score = fingerprints[i].similarity(&fingerprints[j]);
+         if score >= threshold {
+            let (first, second) = if a.id <= b.id { (a, b) } else { (b, a) };
+            pairs.push(DuplicatePair {
+               first:        first.id,
+               first_title:  first.issue.metadata.title.to_string(),
+               second:       second.id,
+               second_title: second.issue.metadata.title.to_string(),
+               similarity:   score,
+            });
+         }
+      }
+   }
+
+   pairs.sort_by(|a, b| {
+      b.similarity
+         .total_cmp(&a.similarity)
+         .then(a.first.cmp(&b.first))
+         .then(a.second.cmp(&b.second))
+   });
+   pairs
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::issue::test_support::issue;
+
+   #[test]
+   fn test_similarity_signals() {
+      let a = issue(1)
+         .title("Token refresh fails")
+         .tags(&["auth"])
+         .files(&["src/auth.rs"])
+         .body("refresh token is not rotated after expiry")
+         .build()
+         .issue;
+      let same_area = issue(1)
+         .title("Refresh of tokens broken")
+         .tags(&["auth"])
+         .files(&["src/auth.rs"])
+         .body("the refresh token is not rotated after expiry")
+         .build()
+         .issue;
+      let unrelated = issue(1)
+         .title("Dark mode colors")
+         .tags(&["ui"])
+         .files(&["src/theme.rs"])
+         .body("contrast is too low on buttons")
+         .build()
+         .issue;
+
+      assert!(similarity(&a, &same_area) > similarity(&a, &unrelated));
+      assert!(
+         similarity(&a, &same_area)
+            > strsim::jaro_winkler("token refresh fails", "refresh of tokens broken")
+      );
+      assert!((similarity(&a, &a) - 1.0).abs() < 1e-9);
+   }
+
+   #[test]
+   fn test_find_duplicates_orders_pairs() {
+      let issues: Vec<_> = [
+         issue(1).title("Fix login timeout").build().issue,
+         issue(1).title("Add export to CSV").build().issue,
+         issue(1).title("Fix login timeouts").build().issue,
+      ]
+      .into_iter()
+      .enumerate()
+      .map(|(i, issue)| IssueWithId { id: i as u32 + 1, issue })
+      .collect();
+
+      let pairs = find_duplicates(&issues, 0.9);
+      assert_eq!(pairs.len(), 1);
+      assert_eq!((pairs[0].first, pairs[0].second), (1, 3));
+   }
+}
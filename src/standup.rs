@@ -0,0 +1,188 @@
+//! Standup notes
+//!
+//! `agentx standup` writes the three answers of a daily standup from the
+//! tracker: what moved since the last working day (issues closed or
+//! checkpointed), what's planned today (issues in progress, then the top
+//! ready ones) and what's blocked, with the reasons. Plain text reads well
+//! in a terminal; markdown pastes into chat.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::issue::{IssueWithId, Status};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StandupIssue {
+   pub num:    u32,
+   pub title:  String,
+   pub status: Status,
+   /// Checkpoints in the window (yesterday) or the blocked reason
+   #[serde(skip_serializing_if = "Vec::is_empty", default)]
+   pub notes:  Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Standup {
+   pub from:      DateTime<Utc>,
+   pub to:        DateTime<Utc>,
+   /// Closed or checkpointed between `from` and `to`
+   pub yesterday: Vec<StandupIssue>,
+   /// In progress, then the top ready issues
+   pub today:     Vec<StandupIssue>,
+   pub blockers:  Vec<StandupIssue>,
+}
+
+/// The working day before `today`: Friday on weekends and Mondays
+pub fn previous_working_day(today: NaiveDate) -> NaiveDate {
+   let back = match today.weekday() {
+      Weekday::Mon => 3,
+      Weekday::Sun => 2,
+      _ => 1,
+   };
+   today - Duration::days(back)
+}
+
+/// The standup for `now` covering activity since `from`, with `ready` (best
+/// first) planned after the issues in progress
+pub fn standup(
+   issues: &[IssueWithId],
+   ready: &[u32],
+   from: DateTime<Utc>,
+   now: DateTime<Utc>,
+) -> Standup {
+   let within = |at: DateTime<Utc>| at >= from && at <= now;
+   let entry = |issue: &IssueWithId, notes: Vec<String>| StandupIssue {
+      num: issue.id,
+      title: issue.issue.metadata.title.to_string(),
+      status: issue.issue.metadata.status,
+      notes,
+   };
+
+   let mut yesterday = Vec::new();
+   let mut active = Vec::new();
+   let mut blockers = Vec::new();
+   for issue in issues {
+      let meta = &issue.issue.metadata;
+      let checkpoints: Vec<String> = issue
+         .issue
+         .body
+         .checkpoints()
+         .filter(|c| c.timestamp().is_some_and(within))
+         .filter_map(|c| c.content().lines().next().map(String::from))
+         .collect();
+      if !checkpoints.is_empty() || meta.closed.is_some_and(within) {
+         yesterday.push(entry(issue, checkpoints));
+      }
+      match meta.status {
+         Status::InProgress => active.push(entry(issue, Vec::new())),
+         Status::Blocked => {
+            let reason = meta.blocked_reason.iter().map(|r| r.to_string()).collect();
+            blockers.push(entry(issue, reason));
+         },
+         _ => {},
+      }
+   }
+   for section in [&mut yesterday, &mut active, &mut blockers] {
+      section.sort_by_key(|issue| issue.num);
+   }
+   let mut today = active;
+   today.extend(ready.iter().filter_map(|&num| {
+      let issue = issues.iter().find(|i| i.id == num)?;
+      Some(entry(issue, Vec::new()))
+   }));
+
+   Standup { from, to: now, yesterday, today, blockers }
+}
+
+impl Standup {
+   /// Plain text, or markdown for chat, with issues referred to by
+   /// `issue_ref`
+   pub fn render(&self, markdown: bool, issue_ref: impl Fn(u32) -> String) -> String {
+      let mut text = if markdown {
+         format!("## Standup {}\n", self.to.format("%Y-%m-%d"))
+      } else {
+         format!("Standup {}\n", self.to.format("%Y-%m-%d"))
+      };
+      let since = format!("Yesterday (since {})", self.from.format("%a %Y-%m-%d"));
+      for (heading, issues) in
+         [(since.as_str(), &self.yesterday), ("Today", &self.today), ("Blockers", &self.blockers)]
+      {
+         if markdown {
+            text.push_str(&format!("\n**{heading}**\n"));
+         } else {
+            text.push_str(&format!("\n{heading}:\n"));
+         }
+         if issues.is_empty() {
+            text.push_str("- None\n");
+         }
+         for issue in issues {
+            text.push_str(&format!(
+               "- {} {} ({})",
+               issue_ref(issue.num),
+               issue.title,
+               issue.status
+            ));
+            match issue.notes.as_slice() {
+               [reason] if issue.status == Status::Blocked => {
+                  text.push_str(&format!(": {reason}\n"));
+               },
+               notes => {
+                  text.push('\n');
+                  for note in notes {
+                     text.push_str(&format!("  - {note}\n"));
+                  }
+               },
+            }
+         }
+      }
+      text
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::issue::test_support::issue;
+
+   #[test]
+   fn test_standup_sections() {
+      let monday = NaiveDate::from_ymd_opt(2026, 10, 12).unwrap();
+      assert_eq!(previous_working_day(monday), NaiveDate::from_ymd_opt(2026, 10, 9).unwrap());
+      assert_eq!(previous_working_day(monday + Duration::days(1)), monday);
+
+      let now = Utc::now();
+      let from = now - Duration::days(1);
+      let mut done = issue(1).title("Fix login").status(Status::Closed).build();
+      done.issue.metadata.closed = Some(now - Duration::hours(3));
+      let mut active = issue(2)
+         .title("Migrate sessions")
+         .status(Status::InProgress)
+         .build();
+      let stamp = (now - Duration::hours(2)).format("%Y-%m-%d %H:%M");
+      active
+         .issue
+         .body
+         .append(&format!("\n**Checkpoint** ({stamp}): Schema drafted\nMore detail\n"));
+      let mut stuck = issue(3)
+         .title("Upgrade vendor SDK")
+         .status(Status::Blocked)
+         .build();
+      stuck.issue.metadata.blocked_reason = Some("waiting on vendor".into());
+      let mut old = issue(4).title("Old work").status(Status::Closed).build();
+      old.issue.metadata.closed = Some(now - Duration::days(5));
+      let next = issue(5).title("Add audit log").build();
+
+      let standup = standup(&[done, active, stuck, old, next], &[5], from, now);
+      let nums = |issues: &[StandupIssue]| issues.iter().map(|i| i.num).collect::<Vec<_>>();
+      assert_eq!(nums(&standup.yesterday), [1, 2]);
+      assert_eq!(standup.yesterday[1].notes, ["Schema drafted"]);
+      assert_eq!(nums(&standup.today), [2, 5]);
+      assert_eq!(nums(&standup.blockers), [3]);
+
+      let text = standup.render(true, |n| format!("#{n}"));
+      assert!(text.contains("- #2 Migrate sessions (active)\n  - Schema drafted\n"));
+      assert!(
+         text.contains("**Blockers**\n- #3 Upgrade vendor SDK (blocked): waiting on vendor\n")
+      );
+   }
+}
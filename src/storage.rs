@@ -1,20 +1,51 @@
 use std::{
-   collections::HashMap,
-   fs,
+   cell::RefCell,
+   collections::{BTreeMap, BTreeSet, HashMap},
+   fmt,
    path::{Path, PathBuf},
-   sync::LazyLock,
+   sync::{
+      Arc, LazyLock, Mutex,
+      atomic::{AtomicUsize, Ordering},
+   },
+   time::Duration,
 };
 
 use anyhow::{Context, Result};
+use chrono::Utc;
 use git2::Repository;
 use regex::Regex;
-
-use crate::issue::{Issue, IssueMetadata, IssueWithId};
+use serde::{Deserialize, Serialize};
+use smol_str::SmolStr;
+
+use crate::{
+   alias,
+   audit::{self, Actor, AuditEntry},
+   body::IssueBody,
+   crypt::{self, Key},
+   daemon, frontmatter,
+   issue::{Issue, IssueMetadata, IssueWithId, Status},
+   merge::{IssueConflict, merge_issues},
+   notify::NotifyState,
+   pomodoro::Pomodoro,
+   references,
+   session::Session,
+   store::{IssueStore, LockGuard, MarkdownStore, MemoryStore},
+   utils::normalize_path,
+   watch::{self, SeenState, WatchConfig},
+};
 
 const ISSUES_DIR: &str = "issues";
-const OPEN_DIR: &str = "issues/open";
-const CLOSED_DIR: &str = "issues/closed";
+pub(crate) const OPEN_DIR: &str = "issues/open";
+pub(crate) const CLOSED_DIR: &str = "issues/closed";
 const ALIASES_FILE: &str = "issues/.aliases.yaml";
+const WATCH_SEEN_FILE: &str = "issues/.watch-seen.yaml";
+const NOTIFY_STATE_FILE: &str = "issues/.notify-state.yaml";
+const SESSION_FILE: &str = "issues/.session.yaml";
+const POMODORO_FILE: &str = "issues/.pomodoro.yaml";
+const JOURNAL_FILE: &str = "issues/sessions.md";
+const AUDIT_FILE: &str = "issues/audit.jsonl";
+const HISTORY_DIR: &str = "issues/history";
+const LOCK_FILE: &str = "issues/.agentx.lock";
 
 macro_rules! static_regex {
     ($(static $name:ident: Regex = $regex:expr;)*) => {
@@ -26,22 +57,278 @@ macro_rules! static_regex {
 
 static_regex! {
     static FRONTMATTER_RE: Regex = r"(?s)^---\s*\n(.*?)\n---\s*\n(.*)";
-    static BUG_NUMBER_RE: Regex = r"^(\d+)-";
     static FILENAME_RE: Regex = r"^(\d+)-.*\.mdx?$";
     static SLUG_RE: Regex = r"[^a-zA-Z0-9]+";
 }
 
+/// A write attempted while the tracker is read-only
+#[derive(Debug, Clone, Copy)]
+pub struct ReadOnlyError;
+
+impl fmt::Display for ReadOnlyError {
+   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      write!(
+         f,
+         "issues are read-only here (AGENTX_READONLY or read_only in .agentxrc.yaml); unset it to \
+          make changes"
+      )
+   }
+}
+
+impl std::error::Error for ReadOnlyError {}
+
+/// A change made against an issue version that is no longer current
+#[derive(Debug, Clone)]
+pub struct VersionConflict {
+   pub bug_num:  u32,
+   pub expected: String,
+   pub current:  String,
+}
+
+impl VersionConflict {
+   /// Structured form for MCP tool errors
+   pub fn to_json(&self) -> serde_json::Value {
+      serde_json::json!({
+         "error": "version_conflict",
+         "bug_num": self.bug_num,
+         "expected_version": self.expected,
+         "current_version": self.current,
+         "message": self.to_string(),
+      })
+   }
+}
+
+impl fmt::Display for VersionConflict {
+   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      write!(
+         f,
+         "issue #{} changed since version {} (now {}); show it again and retry",
+         self.bug_num, self.expected, self.current
+      )
+   }
+}
+
+impl std::error::Error for VersionConflict {}
+
+/// Something the store did that a caller such as the MCP server may log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoreEvent {
+   /// A file written or removed under `issues/`
+   Wrote(PathBuf),
+   /// Time spent waiting for another writer to release the store's lock
+   LockWait(Duration),
+}
+
+/// Events shared by a storage and its clones, drained by whoever logs them
+pub type EventLog = Arc<Mutex<Vec<StoreEvent>>>;
+
+/// Exclusive hold on the issue store, released when dropped
+pub struct StoreLock {
+   path:   PathBuf,
+   /// `None` when this thread already held the lock further out
+   _guard: Option<LockGuard>,
+}
+
+thread_local! {
+   /// How deeply this thread holds each store's lock, so taking it again
+   /// while holding it (creating an issue inside `batch`) doesn't deadlock
+   static HELD_LOCKS: RefCell<HashMap<PathBuf, usize>> = RefCell::new(HashMap::new());
+}
+
+impl Drop for StoreLock {
+   fn drop(&mut self) {
+      HELD_LOCKS.with(|held| {
+         let mut held = held.borrow_mut();
+         if let Some(depth) = held.get_mut(&self.path) {
+            *depth -= 1;
+            if *depth == 0 {
+               held.remove(&self.path);
+            }
+         }
+      });
+   }
+}
+
+/// Every file under `issues/` at one moment, to roll changes back to
+#[derive(Debug, Clone, Default)]
+pub struct Snapshot {
+   files: BTreeMap<PathBuf, Vec<u8>>,
+}
+
+/// An issue whose `blocks` disagrees with the `depends_on` lists naming it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlocksRepair {
+   pub bug_num: u32,
+   /// `blocks` as stored
+   pub stored:  Vec<u32>,
+   /// `blocks` rebuilt from `depends_on`
+   pub rebuilt: Vec<u32>,
+}
+
+/// Rebuild `blocks` from the `depends_on` lists of `issues`, keeping the
+/// issues where the stored list differs
+pub fn blocks_repairs(issues: &[IssueWithId]) -> Vec<BlocksRepair> {
+   let mut rebuilt: BTreeMap<u32, BTreeSet<u32>> =
+      issues.iter().map(|i| (i.id, BTreeSet::new())).collect();
+   for issue in issues {
+      for dep in &issue.issue.metadata.depends_on {
+         if let Some(blocks) = rebuilt.get_mut(dep) {
+            blocks.insert(issue.id);
+         }
+      }
+   }
+   issues
+      .iter()
+      .filter_map(|issue| {
+         let rebuilt: Vec<u32> = rebuilt[&issue.id].iter().copied().collect();
+         let mut stored = issue.issue.metadata.blocks.clone();
+         stored.sort_unstable();
+         (stored != rebuilt).then(|| BlocksRepair {
+            bug_num: issue.id,
+            stored: issue.issue.metadata.blocks.clone(),
+            rebuilt,
+         })
+      })
+      .collect()
+}
+
 #[derive(Debug, Clone)]
 pub struct Storage {
-   base_dir: PathBuf,
+   base_dir:     PathBuf,
+   /// Keeps the files under `issues/`
+   store:        Arc<dyn IssueStore>,
+   use_daemon:   bool,
+   watch:        Option<WatchConfig>,
+   read_only:    bool,
+   decrypt:      bool,
+   /// Who the audit trail credits with changes; resolved on each write
+   /// when unset
+   actor:        Option<Actor>,
+   events:       Option<EventLog>,
+   /// Prefix of full issue refs, recognized as mentions besides `#12`
+   issue_prefix: Option<String>,
 }
 
 impl Storage {
    pub fn new(base_dir: impl Into<PathBuf>) -> Self {
-      Self { base_dir: base_dir.into() }
+      Self {
+         base_dir:     base_dir.into(),
+         store:        Arc::new(MarkdownStore),
+         use_daemon:   false,
+         watch:        None,
+         read_only:    false,
+         decrypt:      true,
+         actor:        None,
+         events:       None,
+         issue_prefix: None,
+      }
+   }
+
+   /// Storage that keeps everything in memory, for tests and throwaway
+   /// trackers; its base directory is a fresh path that is never created
+   pub fn in_memory() -> Self {
+      static NEXT: AtomicUsize = AtomicUsize::new(0);
+      let id = NEXT.fetch_add(1, Ordering::Relaxed);
+      let base_dir =
+         std::env::temp_dir().join(format!("agentx-memory-{}-{id}", std::process::id()));
+      Self::new(base_dir).with_store(MemoryStore::new())
+   }
+
+   /// Keep the files under `issues/` in `store` rather than on disk; paths
+   /// still name where they would be under the base directory
+   pub fn with_store(mut self, store: impl IssueStore + 'static) -> Self {
+      self.store = Arc::new(store);
+      self
+   }
+
+   /// Credit changes in the audit trail to `actor`
+   pub fn with_actor(mut self, actor: Actor) -> Self {
+      self.actor = Some(actor);
+      self
+   }
+
+   /// Leave encrypted issue bodies as ciphertext even when the key is
+   /// available
+   pub fn without_decryption(mut self) -> Self {
+      self.decrypt = false;
+      self
+   }
+
+   /// Refuse every write with a [`ReadOnlyError`]
+   pub fn with_read_only(mut self, read_only: bool) -> Self {
+      self.read_only = read_only;
+      self
+   }
+
+   fn check_writable(&self) -> Result<()> {
+      if self.read_only {
+         return Err(ReadOnlyError.into());
+      }
+      Ok(())
+   }
+
+   /// Read issue lists from a running `agentx daemon` when there is one, and
+   /// tell it about writes
+   pub fn with_daemon(mut self) -> Self {
+      self.use_daemon = true;
+      self
+   }
+
+   /// Notify watchers when a save changes an issue's status or adds a
+   /// checkpoint
+   pub fn with_notifications(mut self, config: WatchConfig) -> Self {
+      self.watch = Some(config);
+      self
+   }
+
+   /// Record files written and lock waits in `events`
+   pub fn with_events(mut self, events: EventLog) -> Self {
+      self.events = Some(events);
+      self
+   }
+
+   /// Also take `PREFIX-12` in issue bodies as a mention of issue 12
+   pub fn with_issue_prefix(mut self, prefix: &str) -> Self {
+      self.issue_prefix = Some(prefix.to_string());
+      self
+   }
+
+   fn record(&self, event: StoreEvent) {
+      if let Some(events) = &self.events
+         && let Ok(mut events) = events.lock()
+      {
+         events.push(event);
+      }
+   }
+
+   /// The saved version of an issue about to be overwritten, when its
+   /// watchers need to hear about the difference
+   fn watched_before(&self, issue: &Issue, bug_num: u32) -> Option<Issue> {
+      if self.watch.is_none() || issue.metadata.watchers.is_empty() {
+         return None;
+      }
+      let path = self.find_issue_file(bug_num).ok()?;
+      self.read_issue(&path, self.crypt_key().ok()?.as_ref()).ok()
+   }
+
+   fn notify_watchers(&self, before: Option<Issue>, after: &Issue, bug_num: u32) {
+      if let (Some(config), Some(before)) = (&self.watch, before) {
+         watch::notify(config, bug_num, after, &watch::Seen::of(&before).events(after));
+      }
+   }
+
+   /// Directory holding `issues/`
+   pub fn base_dir(&self) -> &Path {
+      &self.base_dir
    }
 
-   fn issues_dir(&self) -> PathBuf {
+   fn notify_daemon(&self) {
+      if self.use_daemon {
+         daemon::invalidate(&self.base_dir);
+      }
+   }
+
+   pub fn issues_dir(&self) -> PathBuf {
       self.base_dir.join(ISSUES_DIR)
    }
 
@@ -57,9 +344,76 @@ impl Storage {
       self.base_dir.join(ALIASES_FILE)
    }
 
+   fn watch_seen_file(&self) -> PathBuf {
+      self.base_dir.join(WATCH_SEEN_FILE)
+   }
+
+   fn notify_state_file(&self) -> PathBuf {
+      self.base_dir.join(NOTIFY_STATE_FILE)
+   }
+
+   fn session_file(&self) -> PathBuf {
+      self.base_dir.join(SESSION_FILE)
+   }
+
+   fn pomodoro_file(&self) -> PathBuf {
+      self.base_dir.join(POMODORO_FILE)
+   }
+
+   /// Markdown journal that `session end` appends its summaries to
+   pub fn journal_file(&self) -> PathBuf {
+      self.base_dir.join(JOURNAL_FILE)
+   }
+
+   /// Trail of who changed which issue, one JSON line per save
+   pub fn audit_file(&self) -> PathBuf {
+      self.base_dir.join(AUDIT_FILE)
+   }
+
+   /// Entries of the audit trail, oldest first
+   pub fn load_audit(&self) -> Result<Vec<AuditEntry>> {
+      let text = self
+         .store
+         .read_string(&self.audit_file())
+         .context("Failed to read audit trail")?;
+      Ok(text.map(|text| audit::parse(&text)).unwrap_or_default())
+   }
+
+   /// Append what changed from `before` to `after` to the audit trail,
+   /// returning the file to stage when there was something to record
+   fn record_audit(
+      &self,
+      bug_num: u32,
+      before: Option<&Issue>,
+      after: &Issue,
+   ) -> Result<Option<PathBuf>> {
+      let changes = audit::changes(before, after);
+      if changes.is_empty() {
+         return Ok(None);
+      }
+      let actor = self
+         .actor
+         .clone()
+         .unwrap_or_else(|| Actor::resolve(None, None));
+      let entry =
+         AuditEntry { at: Utc::now(), actor: actor.name, via: actor.via, issue: bug_num, changes };
+      let path = self.audit_file();
+      self
+         .store
+         .append(&path, audit::to_line(&entry)?.as_bytes())?;
+      Ok(Some(path))
+   }
+
    /// Stage file(s) in git if repository exists and issue storage is within
    /// repo
+   #[tracing::instrument(level = "debug", skip_all)]
    fn stage_in_git(&self, paths: &[&Path]) -> Result<()> {
+      for path in paths {
+         self.record(StoreEvent::Wrote(path.to_path_buf()));
+      }
+      if !self.store.on_disk() {
+         return Ok(());
+      }
       let repo = match Repository::discover(&self.base_dir) {
          Ok(repo) => repo,
          Err(_) => return Ok(()), // No git repo, skip silently
@@ -91,6 +445,106 @@ impl Storage {
       Ok(())
    }
 
+   /// Drop removed file(s) from the git index, the counterpart of
+   /// [`Self::stage_in_git`]
+   fn unstage_in_git(&self, paths: &[&Path]) -> Result<()> {
+      for path in paths {
+         self.record(StoreEvent::Wrote(path.to_path_buf()));
+      }
+      if !self.store.on_disk() {
+         return Ok(());
+      }
+      let Ok(repo) = Repository::discover(&self.base_dir) else {
+         return Ok(());
+      };
+      let Some(workdir) = repo.workdir() else {
+         return Ok(());
+      };
+      let mut index = repo.index()?;
+      for path in paths {
+         if let Ok(rel_path) = path.strip_prefix(workdir) {
+            // Files that were never staged have nothing to unstage
+            let _ = index.remove_path(rel_path);
+         }
+      }
+      index.write()?;
+      Ok(())
+   }
+
+   /// Take the store's lock, waiting for whoever holds it; writers that go
+   /// through it (like `agentx batch`) run one at a time. A thread already
+   /// holding it takes it again at once
+   pub fn lock(&self) -> Result<StoreLock> {
+      self.check_writable()?;
+      let path = self.base_dir.join(LOCK_FILE);
+      let nested = HELD_LOCKS.with(|held| {
+         let mut held = held.borrow_mut();
+         match held.get_mut(&path) {
+            Some(depth) => {
+               *depth += 1;
+               true
+            },
+            None => false,
+         }
+      });
+      if nested {
+         return Ok(StoreLock { path, _guard: None });
+      }
+      let (guard, waited) = self.store.lock(&path)?;
+      if let Some(waited) = waited {
+         self.record(StoreEvent::LockWait(waited));
+      }
+      HELD_LOCKS.with(|held| held.borrow_mut().insert(path.clone(), 1));
+      Ok(StoreLock { path, _guard: Some(guard) })
+   }
+
+   /// Files under `issues/`, leaving out the lock and the daemon's socket
+   fn store_files(&self) -> Result<Vec<PathBuf>> {
+      let skip = [self.base_dir.join(LOCK_FILE), self.base_dir.join(daemon::SOCKET_FILE)];
+      let mut files = self.store.files(&self.issues_dir())?;
+      files.retain(|path| !skip.contains(path));
+      Ok(files)
+   }
+
+   #[tracing::instrument(level = "debug", skip_all)]
+   pub fn snapshot(&self) -> Result<Snapshot> {
+      let mut files = BTreeMap::new();
+      for path in self.store_files()? {
+         if let Some(content) = self.store.read(&path)? {
+            files.insert(path, content);
+         }
+      }
+      Ok(Snapshot { files })
+   }
+
+   /// Put every file under `issues/` back the way `snapshot` saw it, in the
+   /// git index too; returns how many files changed
+   #[tracing::instrument(level = "debug", skip_all)]
+   pub fn restore(&self, snapshot: &Snapshot) -> Result<usize> {
+      self.check_writable()?;
+      let mut written = Vec::new();
+      let mut removed = Vec::new();
+      for path in self.store_files()? {
+         if !snapshot.files.contains_key(&path) {
+            self.store.remove(&path)?;
+            removed.push(path);
+         }
+      }
+      for (path, content) in &snapshot.files {
+         if self.store.read(path).ok().flatten().as_ref() != Some(content) {
+            self.store.write(path, content)?;
+            written.push(path.clone());
+         }
+      }
+      self.notify_daemon();
+
+      let written_refs: Vec<&Path> = written.iter().map(PathBuf::as_path).collect();
+      self.stage_in_git(&written_refs)?;
+      let removed_refs: Vec<&Path> = removed.iter().map(PathBuf::as_path).collect();
+      self.unstage_in_git(&removed_refs)?;
+      Ok(written.len() + removed.len())
+   }
+
    /// Extract issue ID from filename (e.g., "01-fix-bug.mdx" -> 1)
    pub fn extract_id_from_filename(filename: &str) -> Option<u32> {
       FILENAME_RE
@@ -108,41 +562,178 @@ impl Storage {
    }
 
    pub fn load_aliases(&self) -> Result<HashMap<String, u32>> {
-      let path = self.aliases_file();
-      if !path.exists() {
+      let Some(content) = self.store.read_string(&self.aliases_file())? else {
          return Ok(HashMap::new());
-      }
-
-      let content = fs::read_to_string(&path)?;
+      };
       Ok(serde_yaml::from_str(&content).unwrap_or_default())
    }
 
    pub fn save_aliases(&self, aliases: &HashMap<String, u32>) -> Result<()> {
-      fs::create_dir_all(self.issues_dir())?;
+      self.check_writable()?;
       let content = serde_yaml::to_string(aliases)?;
-      fs::write(self.aliases_file(), content)?;
+      self.store.write(&self.aliases_file(), content.as_bytes())
+   }
+
+   pub fn load_watch_seen(&self) -> Result<SeenState> {
+      let Some(content) = self.store.read_string(&self.watch_seen_file())? else {
+         return Ok(SeenState::new());
+      };
+      Ok(serde_yaml::from_str(&content).unwrap_or_default())
+   }
+
+   /// Does nothing when read-only; what a guest has seen isn't worth failing
+   /// a read over
+   pub fn save_watch_seen(&self, seen: &SeenState) -> Result<()> {
+      if self.read_only {
+         return Ok(());
+      }
+      let content = serde_yaml::to_string(seen)?;
+      self
+         .store
+         .write(&self.watch_seen_file(), content.as_bytes())
+   }
+
+   pub fn load_notify_state(&self) -> Result<NotifyState> {
+      let Some(content) = self.store.read_string(&self.notify_state_file())? else {
+         return Ok(NotifyState::default());
+      };
+      Ok(serde_yaml::from_str(&content).unwrap_or_default())
+   }
+
+   /// Does nothing when read-only, like [`Storage::save_watch_seen`]
+   pub fn save_notify_state(&self, state: &NotifyState) -> Result<()> {
+      if self.read_only {
+         return Ok(());
+      }
+      let content = serde_yaml::to_string(state)?;
+      self
+         .store
+         .write(&self.notify_state_file(), content.as_bytes())
+   }
+
+   /// The running work session, if any
+   pub fn load_session(&self) -> Result<Option<Session>> {
+      let Some(content) = self.store.read_string(&self.session_file())? else {
+         return Ok(None);
+      };
+      Ok(Some(serde_yaml::from_str(&content).context("Failed to parse session file")?))
+   }
+
+   /// Record the running session, or end it with `None`
+   pub fn save_session(&self, session: Option<&Session>) -> Result<()> {
+      self.check_writable()?;
+      match session {
+         Some(session) => {
+            let content = serde_yaml::to_string(session)?;
+            self.store.write(&self.session_file(), content.as_bytes())
+         },
+         None => self.store.remove(&self.session_file()),
+      }
+   }
+
+   /// The running pomodoro interval, if any
+   pub fn load_pomodoro(&self) -> Result<Option<Pomodoro>> {
+      let Some(content) = self.store.read_string(&self.pomodoro_file())? else {
+         return Ok(None);
+      };
+      Ok(Some(serde_yaml::from_str(&content).context("Failed to parse pomodoro file")?))
+   }
+
+   /// Record the running pomodoro interval, or clear it with `None`
+   pub fn save_pomodoro(&self, pomodoro: Option<&Pomodoro>) -> Result<()> {
+      self.check_writable()?;
+      match pomodoro {
+         Some(pomodoro) => {
+            let content = serde_yaml::to_string(pomodoro)?;
+            self.store.write(&self.pomodoro_file(), content.as_bytes())
+         },
+         None => self.store.remove(&self.pomodoro_file()),
+      }
+   }
+
+   pub fn append_journal(&self, entry: &str) -> Result<()> {
+      self.check_writable()?;
+      let path = self.journal_file();
+      self.store.append(&path, entry.as_bytes())?;
+      self.stage_in_git(&[&path])?;
       Ok(())
    }
 
-   pub fn resolve_bug_ref(&self, bug_ref: &str) -> Result<u32> {
+   /// Sidecar file that compacted checkpoints of issue `bug_num` are
+   /// archived in
+   pub fn checkpoint_archive(&self, bug_num: u32) -> PathBuf {
+      self
+         .base_dir
+         .join(HISTORY_DIR)
+         .join(format!("{bug_num:02}.md"))
+   }
+
+   /// Append compacted checkpoints to the issue's archive, encrypted like
+   /// bodies when the store is locked
+   pub fn archive_checkpoints(&self, bug_num: u32, text: &str) -> Result<PathBuf> {
+      self.check_writable()?;
+      let path = self.checkpoint_archive(bug_num);
+      let entry = match self.crypt_config()? {
+         Some(config) if config.locked => {
+            crypt::encrypt(&crypt::require_key(&config.key_id)?, text)?
+         },
+         _ => format!("{}\n\n", text.trim_end()),
+      };
+      self.store.append(&path, entry.as_bytes())?;
+      self.stage_in_git(&[&path])?;
+      Ok(path)
+   }
+
+   /// Checkpoints archived for issue `bug_num`, decrypting the entries
+   /// written while the store was locked
+   pub fn read_checkpoint_archive(&self, bug_num: u32) -> Result<Option<String>> {
+      let Some(content) = self.store.read_string(&self.checkpoint_archive(bug_num))? else {
+         return Ok(None);
+      };
+      if !content.contains(crypt::MARKER) {
+         return Ok(Some(content));
+      }
+      let key = self
+         .crypt_key()?
+         .context("Archived checkpoints are encrypted; the key is needed to read them")?;
+      let mut text = String::new();
+      for (i, part) in content.split(crypt::MARKER).enumerate() {
+         // Every part after a marker starts with its ciphertext line
+         let (blob, rest) = match i {
+            0 => ("", part),
+            _ => part
+               .trim_start()
+               .split_once('\n')
+               .unwrap_or((part.trim(), "")),
+         };
+         if !blob.is_empty() {
+            let entry = crypt::decrypt(&key, &format!("{}\n{blob}", crypt::MARKER))?;
+            text.push_str(&format!("{}\n\n", entry.trim_end()));
+         }
+         text.push_str(rest.trim_start_matches('\n'));
+      }
+      Ok(Some(text))
+   }
+
+   /// A bug number, or an alias looked up in `namespace` first
+   pub fn resolve_bug_ref(&self, bug_ref: &str, namespace: Option<&str>) -> Result<u32> {
       // Try parsing as number
       if let Ok(num) = bug_ref.parse::<u32>() {
          return Ok(num);
       }
 
       // Try resolving as alias
-      let aliases = self.load_aliases()?;
-      aliases
-         .get(bug_ref)
-         .copied()
+      alias::lookup(&self.load_aliases()?, namespace, bug_ref)
          .ok_or_else(|| anyhow::anyhow!("Unknown bug reference: {bug_ref}"))
    }
 
-   pub fn parse_mdx(&self, content: &str) -> Result<(IssueMetadata, String)> {
+   #[tracing::instrument(level = "debug", skip_all)]
+   pub fn parse_mdx(&self, content: &str) -> Result<(IssueMetadata, IssueBody)> {
       if let Some(caps) = FRONTMATTER_RE.captures(content) {
          let yaml_text = &caps[1];
-         let body = caps[2].to_string();
+         let body = IssueBody::parse(&caps[2]);
 
+         frontmatter::refuse_errors(&frontmatter::check(yaml_text, None))?;
          let metadata: IssueMetadata =
             serde_yaml::from_str(yaml_text).context("Failed to parse YAML frontmatter")?;
 
@@ -152,25 +743,22 @@ impl Storage {
       }
    }
 
-   pub fn find_issue_file(&self, bug_num: u32) -> Result<PathBuf> {
-      let padded = format!("{bug_num:02}");
+   /// Short hash of the issue's file as stored, which changes with every
+   /// save that changes it
+   pub fn issue_version(&self, bug_num: u32) -> Result<String> {
+      let content = self.read_file(&self.find_issue_file(bug_num)?)?;
+      let oid = git2::Oid::hash_object(git2::ObjectType::Blob, content.as_bytes())?;
+      Ok(oid.to_string()[..12].to_string())
+   }
 
+   pub fn find_issue_file(&self, bug_num: u32) -> Result<PathBuf> {
       for dir in [self.open_dir(), self.closed_dir()] {
-         if !dir.exists() {
-            continue;
-         }
-
-         if let Ok(entries) = fs::read_dir(&dir) {
-            for entry in entries.flatten() {
-               let name = entry.file_name();
-               let name_str = name.to_string_lossy();
-
-               if name_str.starts_with(&format!("{padded}-"))
-                  && (name_str.ends_with(".mdx") || name_str.ends_with(".md"))
-               {
-                  return Ok(entry.path());
-               }
-            }
+         if let Some((_, path)) = self
+            .issue_files(&dir)?
+            .into_iter()
+            .find(|(num, _)| *num == bug_num)
+         {
+            return Ok(path);
          }
       }
 
@@ -208,136 +796,423 @@ impl Storage {
       }
    }
 
+   #[tracing::instrument(level = "debug", skip(self))]
    pub fn load_issue(&self, bug_num: u32) -> Result<Issue> {
       let path = self.find_issue_file(bug_num)?;
-      let content = fs::read_to_string(&path)?;
-      let (metadata, body) = self.parse_mdx(&content)?;
+      self.read_issue(&path, self.crypt_key()?.as_ref())
+   }
+
+   /// Parse an issue file's content, decrypting its body when the key is
+   /// available
+   pub fn parse_issue(&self, content: &str) -> Result<Issue> {
+      self.decode(content, self.crypt_key()?.as_ref())
+   }
+
+   #[tracing::instrument(level = "debug", skip_all, fields(path = %path.display()))]
+   fn read_issue(&self, path: &Path, key: Option<&Key>) -> Result<Issue> {
+      self
+         .decode(&self.read_file(path)?, key)
+         .with_context(|| format!("Failed to read {}", path.display()))
+   }
+
+   /// Content of a file the store should hold
+   pub(crate) fn read_file(&self, path: &Path) -> Result<String> {
+      self
+         .store
+         .read_string(path)?
+         .with_context(|| format!("{} does not exist", path.display()))
+   }
+
+   /// Issue files directly in `dir`, with their numbers, in path order
+   fn issue_files(&self, dir: &Path) -> Result<Vec<(u32, PathBuf)>> {
+      Ok(self
+         .store
+         .files(dir)?
+         .into_iter()
+         .filter(|path| path.parent() == Some(dir))
+         .filter_map(|path| Some((Self::extract_id_from_path(&path)?, path)))
+         .collect())
+   }
 
+   /// Parse an issue file, decrypting its body when `key` is given
+   fn decode(&self, content: &str, key: Option<&Key>) -> Result<Issue> {
+      let (metadata, mut body) = self.parse_mdx(content)?;
+      if let Some(key) = key
+         && crypt::is_encrypted(&body.to_string())
+      {
+         body = IssueBody::parse(&crypt::decrypt(key, &body.to_string())?);
+      }
       Ok(Issue { metadata, body })
    }
 
-   pub fn next_bug_number(&self) -> Result<u32> {
-      let mut max_num = 0u32;
+   /// The key to decrypt bodies with, if encryption is set up, decryption is
+   /// wanted and the key is available
+   fn crypt_key(&self) -> Result<Option<Key>> {
+      if !self.decrypt {
+         return Ok(None);
+      }
+      match self.crypt_config()? {
+         Some(config) => crypt::load_key(&config.key_id),
+         None => Ok(None),
+      }
+   }
 
-      for dir in [self.open_dir(), self.closed_dir()] {
-         if !dir.exists() {
-            continue;
+   /// The issue as it goes on disk: encrypted when the store is locked, and
+   /// left alone when its body is still ciphertext
+   fn sealed(&self, issue: &Issue) -> Result<Option<Issue>> {
+      let body = issue.body.to_string();
+      if crypt::is_encrypted(&body) {
+         if !crypt::is_sealed(&body) {
+            let key_id = self
+               .crypt_config()?
+               .map_or_else(|| "(unknown)".to_string(), |config| config.key_id);
+            crypt::require_key(&key_id)?;
+            anyhow::bail!("Cannot add to an encrypted issue body without decrypting it first");
          }
+         return Ok(None);
+      }
+      match self.crypt_config()? {
+         Some(config) if config.locked => {
+            let key = crypt::require_key(&config.key_id)?;
+            let body = IssueBody::parse(&crypt::encrypt(&key, &body)?);
+            Ok(Some(Issue { metadata: issue.metadata.clone(), body }))
+         },
+         _ => Ok(None),
+      }
+   }
 
-         if let Ok(entries) = fs::read_dir(&dir) {
-            for entry in entries.flatten() {
-               let name = entry.file_name();
-               let name_str = name.to_string_lossy();
+   /// Issues a body mentions; none for bodies stored encrypted, whose
+   /// plaintext frontmatter shouldn't reveal what they mention
+   fn references_in(&self, issue: &Issue, bug_num: u32) -> Result<Vec<u32>> {
+      let body = issue.body.to_string();
+      if crypt::is_encrypted(&body) || self.crypt_config()?.is_some_and(|config| config.locked) {
+         return Ok(Vec::new());
+      }
+      let mut nums = references::mentions(&body, self.issue_prefix.as_deref());
+      nums.retain(|&num| num != bug_num);
+      Ok(nums)
+   }
 
-               if let Some(caps) = BUG_NUMBER_RE.captures(&name_str)
-                  && let Ok(num) = caps[1].parse::<u32>()
-               {
-                  max_num = max_num.max(num);
-               }
-            }
+   /// Encryption settings, if `crypt init` was run
+   pub fn crypt_config(&self) -> Result<Option<crypt::CryptConfig>> {
+      crypt::load_config(&*self.store, &self.issues_dir())
+   }
+
+   /// Create a key for this store and record its id, returning the config and
+   /// where the key was written
+   pub fn init_crypt(&self) -> Result<(crypt::CryptConfig, PathBuf)> {
+      self.check_writable()?;
+      if let Some(config) = self.crypt_config()? {
+         anyhow::bail!("Encryption is already set up with key {}", config.key_id);
+      }
+      let config = crypt::CryptConfig { key_id: crypt::new_key_id(), locked: false };
+      let key_file = crypt::store_key(&config.key_id, &Key::generate())?;
+      let path = crypt::save_config(&*self.store, &self.issues_dir(), &config)?;
+      self.stage_in_git(&[&path])?;
+      Ok((config, key_file))
+   }
+
+   /// Encrypt (`locked`) or decrypt every issue body and record the choice
+   /// in `issues/.crypt.yaml`, returning how many files were rewritten
+   pub fn set_locked(&self, locked: bool) -> Result<usize> {
+      self.check_writable()?;
+      let mut config = self
+         .crypt_config()?
+         .context("Encryption is not set up; run `agentx crypt init` first")?;
+      let key = crypt::require_key(&config.key_id)?;
+
+      let mut changed = Vec::new();
+      for dir in [self.open_dir(), self.closed_dir()] {
+         for (_, path) in self.issue_files(&dir)? {
+            let (metadata, body) = self.parse_mdx(&self.read_file(&path)?)?;
+            let text = body.to_string();
+            let body = match (locked, crypt::is_encrypted(&text)) {
+               (true, false) => crypt::encrypt(&key, &text)?,
+               (false, true) => crypt::decrypt(&key, &text)?,
+               _ => continue,
+            };
+            let issue = Issue { metadata, body: IssueBody::parse(&body) };
+            self.store.write(&path, issue.to_mdx().as_bytes())?;
+            changed.push(path);
          }
       }
 
+      config.locked = locked;
+      let config_path = crypt::save_config(&*self.store, &self.issues_dir(), &config)?;
+      self.notify_daemon();
+
+      let mut staged: Vec<&Path> = changed.iter().map(PathBuf::as_path).collect();
+      staged.push(&config_path);
+      self.stage_in_git(&staged)?;
+      Ok(changed.len())
+   }
+
+   pub fn next_bug_number(&self) -> Result<u32> {
+      let max_num = self.list_all_bug_numbers()?.last().copied().unwrap_or(0);
       Ok(max_num + 1)
    }
 
+   /// Save a new issue under the next free number, which is returned with
+   /// its path; the number is picked and taken under the store's lock, so
+   /// writers creating issues at once never share one
+   pub fn create_issue(&self, issue: &Issue) -> Result<(u32, PathBuf)> {
+      let _lock = self.lock()?;
+      let bug_num = self.next_bug_number()?;
+      let is_open = issue.metadata.status != Status::Closed;
+      let path = self.save_issue(issue, bug_num, is_open)?;
+      Ok((bug_num, path))
+   }
+
    pub fn slugify(title: &str) -> String {
       let lower = title.trim().to_lowercase();
       let slug = SLUG_RE.replace_all(&lower, "-");
       slug.trim_matches('-').to_string()
    }
 
+   #[tracing::instrument(level = "debug", skip(self, issue))]
    pub fn save_issue(&self, issue: &Issue, bug_num: u32, is_open: bool) -> Result<PathBuf> {
+      self.check_writable()?;
+      let mut issue = issue.clone();
+      issue.metadata.last_updated = Some(Utc::now());
+      issue.metadata.references = self.references_in(&issue, bug_num)?;
+      self.normalize_files(&mut issue);
+      self.check_frontmatter(&issue.metadata, bug_num)?;
+      let issue = &issue;
       let dir = if is_open {
          self.open_dir()
       } else {
          self.closed_dir()
       };
-      fs::create_dir_all(&dir)?;
 
       let slug = Self::slugify(&issue.metadata.title);
       let filename = format!("{bug_num:02}-{slug}.mdx");
       let path = dir.join(filename);
 
-      fs::write(&path, issue.to_mdx())?;
+      let before = self.watched_before(issue, bug_num);
+      let stored = self.stored_issue(bug_num);
+      // A new title or status renames the file; the old one goes
+      let renamed = self
+         .find_issue_file(bug_num)
+         .ok()
+         .filter(|old| *old != path);
+      let depended_on = stored
+         .as_ref()
+         .map(|s| s.metadata.depends_on.clone())
+         .unwrap_or_default();
+      let sealed = self.sealed(issue)?;
+      let content = sealed.as_ref().unwrap_or(issue).to_mdx();
+      self.store.write(&path, content.as_bytes())?;
+      if let Some(old) = &renamed {
+         self.store.remove(old)?;
+      }
+      let audit = self.record_audit(bug_num, stored.as_ref(), issue)?;
+      self.notify_daemon();
+      self.notify_watchers(before, issue, bug_num);
 
       // Auto-stage the new/modified file in git
-      self.stage_in_git(&[&path])?;
+      let mut staged = vec![path.as_path()];
+      staged.extend(audit.as_deref());
+      self.stage_in_git(&staged)?;
+      if let Some(old) = &renamed {
+         self.unstage_in_git(&[old])?;
+      }
 
+      self.sync_blocks(bug_num, &depended_on, &issue.metadata.depends_on)?;
       Ok(path)
    }
 
+   /// Store `files` entries the way they are matched: relative to the project
+   /// root when they are inside it, with forward slashes and no `./`
+   pub(crate) fn normalize_files(&self, issue: &mut Issue) {
+      let root = self
+         .base_dir
+         .canonicalize()
+         .unwrap_or_else(|_| self.base_dir.clone());
+      issue.metadata.files = issue
+         .metadata
+         .files
+         .iter()
+         .map(|f| normalize_path(f, Some(&root)))
+         .filter(|f| !f.is_empty())
+         .map(SmolStr::from)
+         .collect();
+   }
+
+   /// Save an issue that was loaded as `base`, folding in whatever another
+   /// process changed on disk since then
+   ///
+   /// Fails with an [`IssueConflict`] when both sides changed the same field or
+   /// section differently.
+   pub fn save_issue_from(
+      &self,
+      base: &Issue,
+      issue: &Issue,
+      bug_num: u32,
+      is_open: bool,
+   ) -> Result<PathBuf> {
+      self.check_writable()?;
+      let Ok(path) = self.find_issue_file(bug_num) else {
+         return self.save_issue(issue, bug_num, is_open);
+      };
+      let theirs = self.read_issue(&path, self.crypt_key()?.as_ref())?;
+      if theirs.to_mdx() == base.to_mdx() {
+         return self.save_issue(issue, bug_num, is_open);
+      }
+
+      let merge = merge_issues(base, issue, &theirs)?;
+      if !merge.conflicts().is_empty() {
+         return Err(IssueConflict { bug_num, is_open, mine: issue.clone(), merge }.into());
+      }
+      self.save_issue(&merge.resolve(&[])?, bug_num, is_open)
+   }
+
+   /// Load, modify and save an issue's frontmatter
+   ///
+   /// A status change made by `update_fn` is recorded in the transition log,
+   /// so callers can simply assign `meta.status`.
+   #[tracing::instrument(level = "debug", skip(self, update_fn))]
    pub fn update_issue_metadata<F>(&self, bug_num: u32, update_fn: F) -> Result<()>
    where
       F: FnOnce(&mut IssueMetadata),
    {
+      self.check_writable()?;
       let path = self.find_issue_file(bug_num)?;
-      let content = fs::read_to_string(&path)?;
-      let (mut metadata, body) = self.parse_mdx(&content)?;
+      let (mut metadata, body) = self.parse_mdx(&self.read_file(&path)?)?;
+      let watched = self.watch.is_some() && !metadata.watchers.is_empty();
+      let original = Issue { metadata: metadata.clone(), body: body.clone() };
 
+      let depended_on = metadata.depends_on.clone();
+      let before = metadata.status;
       update_fn(&mut metadata);
+      let after = std::mem::replace(&mut metadata.status, before);
+      metadata.set_status(after);
+      metadata.last_updated = Some(Utc::now());
 
+      self.check_frontmatter(&metadata, bug_num)?;
       let issue = Issue { metadata, body };
-      fs::write(&path, issue.to_mdx())?;
+      self.store.write(&path, issue.to_mdx().as_bytes())?;
+      let audit = self.record_audit(bug_num, Some(&original), &issue)?;
+      self.notify_daemon();
+      self.notify_watchers(watched.then_some(original), &issue, bug_num);
 
       // Auto-stage the modified file in git
-      self.stage_in_git(&[&path])?;
+      let mut staged = vec![path.as_path()];
+      staged.extend(audit.as_deref());
+      self.stage_in_git(&staged)?;
 
-      Ok(())
+      self.sync_blocks(bug_num, &depended_on, &issue.metadata.depends_on)
    }
 
-   pub fn move_issue(&self, bug_num: u32, to_open: bool) -> Result<PathBuf> {
-      let src_path = self.find_issue_file(bug_num)?;
-      let content = fs::read_to_string(&src_path)?;
-      let (metadata, body) = self.parse_mdx(&content)?;
+   /// Refuse to save `metadata` as issue `bug_num` when its frontmatter
+   /// is invalid, and warn about keys in the file on disk that saving is
+   /// about to drop, such as hand-edited typos
+   fn check_frontmatter(&self, metadata: &IssueMetadata, bug_num: u32) -> Result<()> {
+      let yaml = serde_yaml::to_string(metadata)?;
+      frontmatter::refuse_errors(&frontmatter::check(&yaml, Some(bug_num)))
+         .with_context(|| format!("Refusing to save #{bug_num}"))?;
+
+      let stored = self
+         .find_issue_file(bug_num)
+         .ok()
+         .and_then(|path| self.store.read_string(&path).ok().flatten())
+         .unwrap_or_default();
+      if let Some(caps) = FRONTMATTER_RE.captures(&stored) {
+         for problem in frontmatter::check(&caps[1], None) {
+            if problem.severity == frontmatter::Severity::Warning {
+               eprintln!("⚠️  #{bug_num}: {problem}");
+            }
+         }
+      }
+      Ok(())
+   }
 
-      let issue = Issue { metadata, body };
-      let dest_path = self.save_issue(&issue, bug_num, to_open)?;
+   /// Issue `bug_num` as it is on disk, `None` for a new issue
+   fn stored_issue(&self, bug_num: u32) -> Option<Issue> {
+      let path = self.find_issue_file(bug_num).ok()?;
+      self
+         .read_issue(&path, self.crypt_key().ok().flatten().as_ref())
+         .ok()
+   }
 
-      fs::remove_file(&src_path)?;
+   /// Keep the reverse links in step after issue `bug_num`'s dependencies
+   /// went from `before` to `after`, so every way of editing `depends_on`
+   /// (commands, merges, imports) maintains `blocks`
+   fn sync_blocks(&self, bug_num: u32, before: &[u32], after: &[u32]) -> Result<()> {
+      for &dep in after.iter().filter(|dep| !before.contains(dep)) {
+         if self.find_issue_file(dep).is_ok() {
+            self.update_issue_metadata(dep, |meta| {
+               if !meta.blocks.contains(&bug_num) {
+                  meta.blocks.push(bug_num);
+                  meta.blocks.sort_unstable();
+               }
+            })?;
+         }
+      }
+      for &dep in before.iter().filter(|dep| !after.contains(dep)) {
+         if self.find_issue_file(dep).is_ok() {
+            self.update_issue_metadata(dep, |meta| meta.blocks.retain(|&b| b != bug_num))?;
+         }
+      }
+      Ok(())
+   }
 
-      // Stage the removal of old file in git
-      if let Ok(repo) = Repository::discover(&self.base_dir) {
-         let mut index = repo.index()?;
-         if let Some(workdir) = repo.workdir()
-            && let Ok(rel_path) = src_path.strip_prefix(workdir)
-         {
-            index.remove_path(rel_path)?;
-            index.write()?;
+   /// Rebuild `blocks` for every issue from the `depends_on` lists, for
+   /// links that hand edits left out of step; returns what was (or, with
+   /// `dry_run`, would be) changed
+   pub fn repair_blocks(&self, dry_run: bool) -> Result<Vec<BlocksRepair>> {
+      let mut issues = self.list_open_issues()?;
+      issues.extend(self.list_closed_issues()?);
+      let repairs = blocks_repairs(&issues);
+      if !dry_run {
+         for repair in &repairs {
+            let rebuilt = repair.rebuilt.clone();
+            self.update_issue_metadata(repair.bug_num, |meta| meta.blocks = rebuilt)?;
          }
       }
+      Ok(repairs)
+   }
+
+   #[tracing::instrument(level = "debug", skip(self))]
+   pub fn move_issue(&self, bug_num: u32, to_open: bool) -> Result<PathBuf> {
+      self.check_writable()?;
+      let src_path = self.find_issue_file(bug_num)?;
+      let (metadata, body) = self.parse_mdx(&self.read_file(&src_path)?)?;
 
-      Ok(dest_path)
+      // Saving into the other directory removes the source file
+      let issue = Issue { metadata, body };
+      self.save_issue(&issue, bug_num, to_open)
    }
 
+   // The daemon's cache holds decrypted bodies, so storage that keeps them
+   // encrypted reads the files itself
+   #[tracing::instrument(level = "debug", skip_all)]
    pub fn list_open_issues(&self) -> Result<Vec<IssueWithId>> {
+      if self.use_daemon
+         && self.decrypt
+         && let Some(issues) = daemon::cached_issues(&self.base_dir, false)
+      {
+         return Ok(issues);
+      }
       self.list_issues_in_dir(&self.open_dir())
    }
 
+   #[tracing::instrument(level = "debug", skip_all)]
    pub fn list_closed_issues(&self) -> Result<Vec<IssueWithId>> {
+      if self.use_daemon
+         && self.decrypt
+         && let Some(issues) = daemon::cached_issues(&self.base_dir, true)
+      {
+         return Ok(issues);
+      }
       self.list_issues_in_dir(&self.closed_dir())
    }
 
+   #[tracing::instrument(level = "debug", skip_all, fields(dir = %dir.display()))]
    fn list_issues_in_dir(&self, dir: &Path) -> Result<Vec<IssueWithId>> {
-      if !dir.exists() {
-         return Ok(Vec::new());
-      }
-
+      let key = self.crypt_key()?;
       let mut issues = Vec::new();
-
-      for entry in fs::read_dir(dir)? {
-         let entry = entry?;
-         let path = entry.path();
-         let name = entry.file_name();
-         let name_str = name.to_string_lossy();
-
-         if FILENAME_RE.is_match(&name_str)
-            && let Some(id) = Self::extract_id_from_path(&path)
-         {
-            let content = fs::read_to_string(&path)?;
-            let (metadata, body) = self.parse_mdx(&content)?;
-            issues.push(IssueWithId { id, issue: Issue { metadata, body } });
-         }
+      for (id, path) in self.issue_files(dir)? {
+         issues.push(IssueWithId { id, issue: self.read_issue(&path, key.as_ref())? });
       }
 
       issues.sort_by_key(|issue_with_id| issue_with_id.id);
@@ -346,27 +1221,125 @@ impl Storage {
 
    pub fn list_all_bug_numbers(&self) -> Result<Vec<u32>> {
       let mut bug_nums = Vec::new();
-
       for dir in [self.open_dir(), self.closed_dir()] {
-         if !dir.exists() {
-            continue;
-         }
-
-         if let Ok(entries) = fs::read_dir(&dir) {
-            for entry in entries.flatten() {
-               let name = entry.file_name();
-               let name_str = name.to_string_lossy();
-
-               if let Some(caps) = BUG_NUMBER_RE.captures(&name_str)
-                  && let Ok(num) = caps[1].parse::<u32>()
-               {
-                  bug_nums.push(num);
-               }
-            }
-         }
+         bug_nums.extend(self.issue_files(&dir)?.into_iter().map(|(num, _)| num));
       }
-
       bug_nums.sort_unstable();
       Ok(bug_nums)
    }
 }
+
+#[cfg(test)]
+mod tests {
+   use std::fs;
+
+   use super::*;
+   use crate::issue::test_support::issue;
+
+   #[test]
+   fn test_read_only_refuses_writes_and_moves_keep_files() {
+      let nanos = std::time::SystemTime::now()
+         .duration_since(std::time::UNIX_EPOCH)
+         .unwrap()
+         .as_nanos();
+      let dir = std::env::temp_dir().join(format!("agentx-storage-{nanos}"));
+      let events = EventLog::default();
+      let storage = Storage::new(&dir).with_events(events.clone());
+      let login = issue(1).title("Fix login").build().issue;
+      storage.save_issue(&login, 1, false).unwrap();
+      let version = storage.issue_version(1).unwrap();
+      assert_eq!(version.len(), 12);
+      assert!(
+         events
+            .lock()
+            .unwrap()
+            .contains(&StoreEvent::Wrote(storage.find_issue_file(1).unwrap()))
+      );
+      storage.move_issue(1, false).unwrap();
+      assert!(storage.load_issue(1).is_ok());
+      assert_eq!(storage.issue_version(1).unwrap(), version);
+      storage
+         .update_issue_metadata(1, |m| m.effort = Some("1h".into()))
+         .unwrap();
+      assert_ne!(storage.issue_version(1).unwrap(), version);
+
+      let guest = storage.clone().with_read_only(true);
+      let err = guest.move_issue(1, true).unwrap_err();
+      assert!(err.downcast_ref::<ReadOnlyError>().is_some());
+      assert!(guest.update_issue_metadata(1, |_| {}).is_err());
+      assert!(guest.list_closed_issues().unwrap().len() == 1);
+
+      fs::remove_dir_all(&dir).unwrap();
+   }
+
+   #[test]
+   fn test_blocks_follow_depends_on() {
+      let storage = Storage::in_memory();
+      let schema = issue(1).title("Schema").build().issue;
+      storage.save_issue(&schema, 1, true).unwrap();
+      let api = issue(2).title("API").depends_on(&[1]).build().issue;
+      storage.save_issue(&api, 2, true).unwrap();
+      assert_eq!(storage.load_issue(1).unwrap().metadata.blocks, [2]);
+
+      storage
+         .update_issue_metadata(2, |meta| meta.depends_on.clear())
+         .unwrap();
+      assert!(storage.load_issue(1).unwrap().metadata.blocks.is_empty());
+
+      // A hand edit that skipped the reverse link
+      let mut schema = storage.load_issue(1).unwrap();
+      schema.metadata.blocks = vec![2, 7];
+      let path = storage.find_issue_file(1).unwrap();
+      storage
+         .store
+         .write(&path, schema.to_mdx().as_bytes())
+         .unwrap();
+      let repairs = storage.repair_blocks(true).unwrap();
+      assert_eq!(repairs.len(), 1);
+      assert_eq!((repairs[0].bug_num, repairs[0].rebuilt.as_slice()), (1, [].as_slice()));
+      storage.repair_blocks(false).unwrap();
+      assert!(storage.repair_blocks(true).unwrap().is_empty());
+
+      // Frontmatter is checked on save and on read
+      let err = storage
+         .update_issue_metadata(2, |meta| meta.depends_on = vec![2])
+         .unwrap_err();
+      assert!(format!("{err:#}").contains("`depends_on`: #2 can't depend on itself"));
+      let content = storage.read_file(&path).unwrap();
+      let content = content.replacen("priority:", "depends_on: [-1]\npriority:", 1);
+      storage.store.write(&path, content.as_bytes()).unwrap();
+      let err = storage.load_issue(1).unwrap_err();
+      assert!(format!("{err:#}").contains("`depends_on`: -1 is not an issue number"));
+      assert!(!storage.base_dir().exists());
+   }
+
+   #[test]
+   fn test_create_issue_takes_distinct_numbers() {
+      let nanos = std::time::SystemTime::now()
+         .duration_since(std::time::UNIX_EPOCH)
+         .unwrap()
+         .as_nanos();
+      let dir = std::env::temp_dir().join(format!("agentx-create-{nanos}"));
+      let storage = Storage::new(&dir);
+
+      // Writers racing each other, each on its own handle to the lock file
+      let handles: Vec<_> = (0..8)
+         .map(|i| {
+            let storage = storage.clone();
+            std::thread::spawn(move || storage.create_issue(&issue(i).build().issue).unwrap().0)
+         })
+         .collect();
+      let mut nums: Vec<u32> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+      nums.sort_unstable();
+      assert_eq!(nums, (1..=8).collect::<Vec<_>>());
+
+      // Creating while already holding the lock nests rather than deadlocks
+      let _lock = storage.lock().unwrap();
+      let nested = issue(9).title("Nested").build().issue;
+      let (num, path) = storage.create_issue(&nested).unwrap();
+      assert_eq!(num, 9);
+      assert!(path.ends_with("09-nested.mdx"));
+
+      fs::remove_dir_all(&dir).unwrap();
+   }
+}
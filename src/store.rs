@@ -0,0 +1,293 @@
+//! Backends that keep the issue store's files
+//!
+//! [`Storage`](crate::storage::Storage) decides what goes in which file under
+//! `issues/`: issue documents, aliases, the audit trail, sessions, the
+//! encryption settings. An [`IssueStore`] keeps those files.
+//! [`MarkdownStore`], the default, keeps them on disk where git can stage
+//! them; [`MemoryStore`] keeps them in a map and is gone with the process,
+//! which suits tests. Other backends, such as a
+//! single SQLite file or a remote store, implement the same few operations.
+//!
+//! Files are named by the path they would have on disk, so paths shown to
+//! users and staged in git read the same whichever backend holds them.
+
+use std::{
+   any::Any,
+   collections::BTreeMap,
+   fmt, fs,
+   io::{ErrorKind, Write},
+   path::{Path, PathBuf},
+   sync::{Arc, Condvar, Mutex},
+   time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+
+/// A writer's hold on a store, released when dropped
+pub type LockGuard = Box<dyn Any + Send>;
+
+/// Where the files under `issues/` are kept
+pub trait IssueStore: fmt::Debug + Send + Sync {
+   /// Content of the file at `path`, `None` when there is none
+   fn read(&self, path: &Path) -> Result<Option<Vec<u8>>>;
+
+   /// Replace the file at `path`, creating it as needed
+   fn write(&self, path: &Path, content: &[u8]) -> Result<()>;
+
+   /// Add to the end of the file at `path`, creating it as needed
+   fn append(&self, path: &Path, content: &[u8]) -> Result<()>;
+
+   /// Remove the file at `path`; removing a missing file is not an error
+   fn remove(&self, path: &Path) -> Result<()>;
+
+   /// Every file under `dir` at any depth, in path order
+   fn files(&self, dir: &Path) -> Result<Vec<PathBuf>>;
+
+   /// Take the store for one writer at a time, with `path` naming the lock;
+   /// also returns how long another writer kept it waiting, if it did
+   fn lock(&self, path: &Path) -> Result<(LockGuard, Option<Duration>)>;
+
+   /// Whether the files are real files in the worktree that git can stage
+   fn on_disk(&self) -> bool;
+
+   /// Content of the file at `path` as text
+   fn read_string(&self, path: &Path) -> Result<Option<String>> {
+      self
+         .read(path)?
+         .map(|bytes| {
+            String::from_utf8(bytes).with_context(|| format!("{} is not UTF-8", path.display()))
+         })
+         .transpose()
+   }
+}
+
+/// Issue files as markdown on disk, the default store
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MarkdownStore;
+
+impl IssueStore for MarkdownStore {
+   fn read(&self, path: &Path) -> Result<Option<Vec<u8>>> {
+      match fs::read(path) {
+         Ok(content) => Ok(Some(content)),
+         Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+         Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+      }
+   }
+
+   fn write(&self, path: &Path, content: &[u8]) -> Result<()> {
+      if let Some(parent) = path.parent() {
+         fs::create_dir_all(parent)?;
+      }
+      fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+   }
+
+   fn append(&self, path: &Path, content: &[u8]) -> Result<()> {
+      if let Some(parent) = path.parent() {
+         fs::create_dir_all(parent)?;
+      }
+      fs::OpenOptions::new()
+         .create(true)
+         .append(true)
+         .open(path)
+         .and_then(|mut file| file.write_all(content))
+         .with_context(|| format!("Failed to write {}", path.display()))
+   }
+
+   fn remove(&self, path: &Path) -> Result<()> {
+      match fs::remove_file(path) {
+         Err(e) if e.kind() != ErrorKind::NotFound => {
+            Err(e).with_context(|| format!("Failed to remove {}", path.display()))
+         },
+         _ => Ok(()),
+      }
+   }
+
+   fn files(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+      let mut files = Vec::new();
+      let mut dirs = vec![dir.to_path_buf()];
+      while let Some(dir) = dirs.pop() {
+         let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+         };
+         for entry in entries {
+            let entry = entry?;
+            let kind = entry.file_type()?;
+            if kind.is_dir() {
+               dirs.push(entry.path());
+            } else if kind.is_file() {
+               files.push(entry.path());
+            }
+         }
+      }
+      files.sort();
+      Ok(files)
+   }
+
+   fn lock(&self, path: &Path) -> Result<(LockGuard, Option<Duration>)> {
+      if let Some(parent) = path.parent() {
+         fs::create_dir_all(parent)?;
+      }
+      let file = fs::OpenOptions::new()
+         .create(true)
+         .truncate(false)
+         .write(true)
+         .open(path)
+         .with_context(|| format!("Failed to open {}", path.display()))?;
+      let waited = match file.try_lock() {
+         Ok(()) => None,
+         Err(fs::TryLockError::WouldBlock) => {
+            let waiting = Instant::now();
+            file
+               .lock()
+               .with_context(|| format!("Failed to lock {}", path.display()))?;
+            Some(waiting.elapsed())
+         },
+         Err(fs::TryLockError::Error(e)) => {
+            return Err(e).with_context(|| format!("Failed to lock {}", path.display()));
+         },
+      };
+      Ok((Box::new(file), waited))
+   }
+
+   fn on_disk(&self) -> bool {
+      true
+   }
+}
+
+/// Issue files held in memory, shared by every clone of the store
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+   files:  Arc<Mutex<BTreeMap<PathBuf, Vec<u8>>>>,
+   /// Whether a writer holds the store, and the writers waiting for it
+   locked: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl MemoryStore {
+   pub fn new() -> Self {
+      Self::default()
+   }
+
+   fn files_mut(&self) -> std::sync::MutexGuard<'_, BTreeMap<PathBuf, Vec<u8>>> {
+      self.files.lock().unwrap_or_else(|e| e.into_inner())
+   }
+}
+
+/// [`MemoryStore`]'s lock, handed to the next writer when dropped
+struct MemoryLock(Arc<(Mutex<bool>, Condvar)>);
+
+impl Drop for MemoryLock {
+   fn drop(&mut self) {
+      let (locked, released) = &*self.0;
+      *locked.lock().unwrap_or_else(|e| e.into_inner()) = false;
+      released.notify_one();
+   }
+}
+
+impl IssueStore for MemoryStore {
+   fn read(&self, path: &Path) -> Result<Option<Vec<u8>>> {
+      Ok(self.files_mut().get(path).cloned())
+   }
+
+   fn write(&self, path: &Path, content: &[u8]) -> Result<()> {
+      self
+         .files_mut()
+         .insert(path.to_path_buf(), content.to_vec());
+      Ok(())
+   }
+
+   fn append(&self, path: &Path, content: &[u8]) -> Result<()> {
+      self
+         .files_mut()
+         .entry(path.to_path_buf())
+         .or_default()
+         .extend_from_slice(content);
+      Ok(())
+   }
+
+   fn remove(&self, path: &Path) -> Result<()> {
+      self.files_mut().remove(path);
+      Ok(())
+   }
+
+   fn files(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+      Ok(self
+         .files_mut()
+         .keys()
+         .filter(|path| path.starts_with(dir))
+         .cloned()
+         .collect())
+   }
+
+   fn lock(&self, _path: &Path) -> Result<(LockGuard, Option<Duration>)> {
+      let (locked, released) = &*self.locked;
+      let mut held = locked.lock().unwrap_or_else(|e| e.into_inner());
+      let waiting = (*held).then(Instant::now);
+      while *held {
+         held = released.wait(held).unwrap_or_else(|e| e.into_inner());
+      }
+      *held = true;
+      Ok((Box::new(MemoryLock(self.locked.clone())), waiting.map(|w| w.elapsed())))
+   }
+
+   fn on_disk(&self) -> bool {
+      false
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn test_backends_agree() {
+      let nanos = std::time::SystemTime::now()
+         .duration_since(std::time::UNIX_EPOCH)
+         .unwrap()
+         .as_nanos();
+      let dir = std::env::temp_dir().join(format!("agentx-store-{nanos}"));
+      let stores: [&dyn IssueStore; 2] = [&MarkdownStore, &MemoryStore::new()];
+      for store in stores {
+         let issue = dir.join("issues/open/01-fix.mdx");
+         assert_eq!(store.read(&issue).unwrap(), None);
+         store.write(&issue, b"---\ntitle: Fix\n---\n").unwrap();
+         store
+            .append(&dir.join("issues/audit.jsonl"), b"one\n")
+            .unwrap();
+         store
+            .append(&dir.join("issues/audit.jsonl"), b"two\n")
+            .unwrap();
+         assert_eq!(
+            store
+               .read_string(&dir.join("issues/audit.jsonl"))
+               .unwrap()
+               .unwrap(),
+            "one\ntwo\n"
+         );
+         assert_eq!(store.files(&dir.join("issues")).unwrap(), [
+            dir.join("issues/audit.jsonl"),
+            issue.clone()
+         ]);
+         assert!(store.files(&dir.join("issues/closed")).unwrap().is_empty());
+         store.remove(&issue).unwrap();
+         store.remove(&issue).unwrap();
+         assert_eq!(store.files(&dir.join("issues/open")).unwrap(), Vec::<PathBuf>::new());
+
+         let (guard, waited) = store.lock(&dir.join("issues/.agentx.lock")).unwrap();
+         assert_eq!(waited, None);
+         drop(guard);
+      }
+      assert!(dir.exists());
+      fs::remove_dir_all(&dir).unwrap();
+
+      // A second writer waits for the first to drop its hold
+      let store = MemoryStore::new();
+      let (guard, _) = store.lock(Path::new("lock")).unwrap();
+      let waiter = {
+         let store = store.clone();
+         std::thread::spawn(move || store.lock(Path::new("lock")).unwrap().1)
+      };
+      std::thread::sleep(Duration::from_millis(20));
+      drop(guard);
+      assert!(waiter.join().unwrap().is_some());
+   }
+}
@@ -0,0 +1,212 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::issue::{IssueWithId, Status};
+
+/// Minimum confidence for a tag to be suggested
+const MIN_CONFIDENCE: f64 = 0.3;
+/// Maximum number of suggestions returned
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Title words too common to say anything about an issue's area
+const STOPWORDS: &[&str] = &[
+   "add", "and", "are", "bug", "but", "can", "doesn", "don", "fix", "for", "from", "handle",
+   "into", "issue", "make", "not", "now", "should", "support", "the", "this", "update", "use",
+   "when", "with", "without",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagStat {
+   pub tag:             String,
+   pub count:           usize,
+   pub open:            usize,
+   pub closed:          usize,
+   /// Average hours from creation to close, over closed issues with the tag
+   pub avg_close_hours: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagSuggestion {
+   pub tag:        String,
+   /// Share of matching historical issues that carried the tag (0.0-1.0)
+   pub confidence: f64,
+   /// Title keywords and paths that led to the suggestion
+   pub reasons:    Vec<String>,
+}
+
+/// Per-tag usage counts across `issues`, most used first
+pub fn tag_stats(issues: &[IssueWithId]) -> Vec<TagStat> {
+   let mut by_tag: BTreeMap<String, (usize, usize, Vec<f64>)> = BTreeMap::new();
+
+   for issue_with_id in issues {
+      let meta = &issue_with_id.issue.metadata;
+      let is_closed = matches!(meta.status, Status::Done | Status::Closed);
+      for tag in &meta.tags {
+         let (open, closed, durations) = by_tag.entry(tag.to_lowercase()).or_default();
+         if is_closed {
+            *closed += 1;
+            if let Some(closed_at) = meta.closed {
+               durations.push((closed_at - meta.created).num_minutes() as f64 / 60.0);
+            }
+         } else {
+            *open += 1;
+         }
+      }
+   }
+
+   let mut stats: Vec<_> = by_tag
+      .into_iter()
+      .map(|(tag, (open, closed, durations))| TagStat {
+         tag,
+         count: open + closed,
+         open,
+         closed,
+         avg_close_hours: (!durations.is_empty())
+            .then(|| durations.iter().sum::<f64>() / durations.len() as f64),
+      })
+      .collect();
+
+   stats.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+   stats
+}
+
+/// Features of an issue that tend to predict its tags: significant title
+/// words, the directories its files live in, and the file stems
+fn features(title: &str, files: &[impl AsRef<str>]) -> HashSet<String> {
+   let mut features: HashSet<String> = title
+      .split(|c: char| !c.is_alphanumeric())
+      .map(str::to_lowercase)
+      .filter(|w| w.len() >= 3 && !STOPWORDS.contains(&w.as_str()))
+      .collect();
+
+   for file in files {
+      let path = file.as_ref().trim_start_matches("./");
+      if let Some((dir, name)) = path.rsplit_once('/') {
+         features.insert(format!("{dir}/"));
+         let stem = name.split('.').next().unwrap_or(name);
+         if !matches!(stem, "mod" | "lib" | "main" | "index") {
+            features.insert(stem.to_lowercase());
+         }
+      }
+   }
+
+   features
+}
+
+/// Suggest tags for a new issue from tags that historically co-occur with the
+/// same title keywords and file paths
+///
+/// Tags already in `present` are never suggested.
+pub fn suggest_tags(
+   title: &str,
+   files: &[impl AsRef<str>],
+   present: &[impl AsRef<str>],
+   history: &[IssueWithId],
+) -> Vec<TagSuggestion> {
+   let wanted = features(title, files);
+   if wanted.is_empty() {
+      return Vec::new();
+   }
+
+   // feature -> (issues with the feature, tag -> co-occurrences)
+   let mut seen: HashMap<&str, (usize, HashMap<String, usize>)> = HashMap::new();
+   for issue_with_id in history {
+      let meta = &issue_with_id.issue.metadata;
+      if meta.tags.is_empty() {
+         continue;
+      }
+      for feature in features(&meta.title, &meta.files) {
+         let Some(feature) = wanted.get(&feature) else {
+            continue;
+         };
+         let entry = seen.entry(feature.as_str()).or_default();
+         entry.0 += 1;
+         for tag in &meta.tags {
+            *entry.1.entry(tag.to_lowercase()).or_default() += 1;
+         }
+      }
+   }
+
+   if seen.is_empty() {
+      return Vec::new();
+   }
+
+   let present: HashSet<String> = present
+      .iter()
+      .map(|t| t.as_ref().trim_start_matches('#').to_lowercase())
+      .collect();
+   let mut scores: HashMap<String, (f64, Vec<String>)> = HashMap::new();
+   for (feature, (total, tags)) in &seen {
+      for (tag, hits) in tags {
+         let entry = scores.entry(tag.clone()).or_default();
+         entry.0 += *hits as f64 / *total as f64;
+         entry.1.push(feature.to_string());
+      }
+   }
+
+   let mut suggestions: Vec<_> = scores
+      .into_iter()
+      .filter(|(tag, _)| !present.contains(tag))
+      .map(|(tag, (score, mut reasons))| {
+         reasons.sort();
+         TagSuggestion { tag, confidence: score / seen.len() as f64, reasons }
+      })
+      .filter(|s| s.confidence >= MIN_CONFIDENCE)
+      .collect();
+
+   suggestions.sort_by(|a, b| {
+      b.confidence
+         .total_cmp(&a.confidence)
+         .then_with(|| a.tag.cmp(&b.tag))
+   });
+   suggestions.truncate(MAX_SUGGESTIONS);
+   suggestions
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::issue::test_support::issue;
+
+   #[test]
+   fn test_suggest_tags_from_history() {
+      let history = vec![
+         issue(1)
+            .title("Session token expires early")
+            .tags(&["auth"])
+            .files(&["src/auth/session.rs"])
+            .build(),
+         issue(2)
+            .title("Login form validation")
+            .tags(&["auth", "ui"])
+            .files(&["src/auth/login.rs"])
+            .build(),
+         issue(3)
+            .title("Dark mode colors")
+            .tags(&["ui"])
+            .files(&["src/theme.rs"])
+            .build(),
+      ];
+
+      let suggestions =
+         suggest_tags("Refresh token rotation", &["src/auth/token.rs"], &[] as &[&str], &history);
+      assert_eq!(suggestions.first().map(|s| s.tag.as_str()), Some("auth"));
+
+      let suggestions =
+         suggest_tags("Refresh token rotation", &["src/auth/token.rs"], &["auth"], &history);
+      assert!(suggestions.iter().all(|s| s.tag != "auth"));
+   }
+
+   #[test]
+   fn test_tag_stats_counts() {
+      let issues = vec![
+         issue(1).title("One").tags(&["auth"]).build(),
+         issue(2).title("Two").tags(&["auth", "ui"]).build(),
+      ];
+      let stats = tag_stats(&issues);
+      assert_eq!(stats[0].tag, "auth");
+      assert_eq!((stats[0].count, stats[0].open, stats[0].closed), (2, 2, 0));
+      assert!(stats[0].avg_close_hours.is_none());
+   }
+}
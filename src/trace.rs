@@ -0,0 +1,181 @@
+//! Tracing and timings
+//!
+//! Storage IO, issue parsing, graph computations and git calls run inside
+//! `tracing` spans. `--trace` logs them to stderr as they close, with their
+//! durations; `AGENTX_LOG` picks what to log with `tracing` filter
+//! directives (e.g. `agentx::storage=trace`). `--timings` adds up the time
+//! spent in each kind of span and prints it as a footer when the command
+//! finishes, to see where the time went on big trees.
+
+use std::{
+   collections::HashMap,
+   io::IsTerminal,
+   sync::{Arc, Mutex},
+   time::{Duration, Instant},
+};
+
+use tracing::{Subscriber, span};
+use tracing_subscriber::{
+   EnvFilter, Layer,
+   fmt::format::FmtSpan,
+   layer::{Context, SubscriberExt},
+   registry::LookupSpan,
+   util::SubscriberInitExt,
+};
+
+/// Filter for `--trace` and `--timings` when `AGENTX_LOG` is unset
+const DEFAULT_FILTER: &str = "agentx=debug";
+
+/// Most span kinds listed in the footer
+const FOOTER_ROWS: usize = 15;
+
+/// Calls and total time of one kind of span
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpanTotal {
+   pub calls: u32,
+   pub time:  Duration,
+}
+
+type Totals = Arc<Mutex<HashMap<String, SpanTotal>>>;
+
+/// Time spent per span kind since [`init`]
+pub struct Timings {
+   totals:  Totals,
+   started: Instant,
+}
+
+/// When a span was created, kept in its extensions
+struct Opened(Instant);
+
+struct TimingsLayer {
+   totals: Totals,
+}
+
+impl<S> Layer<S> for TimingsLayer
+where
+   S: Subscriber + for<'a> LookupSpan<'a>,
+{
+   fn on_new_span(&self, _attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+      if let Some(span) = ctx.span(id) {
+         span.extensions_mut().insert(Opened(Instant::now()));
+      }
+   }
+
+   fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+      let Some(span) = ctx.span(&id) else {
+         return;
+      };
+      let Some(elapsed) = span.extensions().get::<Opened>().map(|o| o.0.elapsed()) else {
+         return;
+      };
+      let meta = span.metadata();
+      let key = span_key(meta.target(), meta.name());
+      if let Ok(mut totals) = self.totals.lock() {
+         let total = totals.entry(key).or_default();
+         total.calls += 1;
+         total.time += elapsed;
+      }
+   }
+}
+
+/// `storage::load_issue` for span `load_issue` in `agentx::storage`
+fn span_key(target: &str, name: &str) -> String {
+   let module = target.strip_prefix("agentx::").unwrap_or(target);
+   format!("{module}::{name}")
+}
+
+/// Install the subscriber for `--trace`/`AGENTX_LOG` and `--timings`;
+/// nothing is installed, and spans cost next to nothing, without either
+pub fn init(trace: bool, timings: bool) -> Option<Timings> {
+   let directives = std::env::var("AGENTX_LOG")
+      .ok()
+      .filter(|d| !d.trim().is_empty());
+   let log = (trace || directives.is_some()).then(|| {
+      tracing_subscriber::fmt::layer()
+         .with_writer(std::io::stderr)
+         .with_ansi(std::io::stderr().is_terminal())
+         .with_span_events(FmtSpan::CLOSE)
+         .with_filter(EnvFilter::new(directives.as_deref().unwrap_or(DEFAULT_FILTER)))
+   });
+   let totals = Totals::default();
+   let footer = timings.then(|| {
+      TimingsLayer { totals: Arc::clone(&totals) }.with_filter(EnvFilter::new(DEFAULT_FILTER))
+   });
+   if log.is_none() && footer.is_none() {
+      return None;
+   }
+   // Fails only if a subscriber is already installed
+   let _ = tracing_subscriber::registry()
+      .with(log)
+      .with(footer)
+      .try_init();
+   timings.then(|| Timings { totals, started: Instant::now() })
+}
+
+impl Timings {
+   /// Span kinds by total time, longest first
+   pub fn totals(&self) -> Vec<(String, SpanTotal)> {
+      let mut totals: Vec<(String, SpanTotal)> = self
+         .totals
+         .lock()
+         .map(|totals| totals.iter().map(|(k, v)| (k.clone(), *v)).collect())
+         .unwrap_or_default();
+      totals.sort_by(|a, b| b.1.time.cmp(&a.1.time).then_with(|| a.0.cmp(&b.0)));
+      totals
+   }
+
+   /// The footer, on stderr so it stays out of JSON output
+   pub fn print(&self) {
+      let totals = self.totals();
+      eprintln!(
+         "\n⏱  {} total; time per span includes the spans inside it",
+         millis(self.started.elapsed())
+      );
+      if totals.is_empty() {
+         eprintln!("   (no spans recorded)");
+      }
+      let width = totals
+         .iter()
+         .take(FOOTER_ROWS)
+         .map(|(key, _)| key.len())
+         .max()
+         .unwrap_or(0);
+      for (key, total) in totals.iter().take(FOOTER_ROWS) {
+         eprintln!("   {key:<width$}  {:>5}×  {:>9}", total.calls, millis(total.time));
+      }
+      if totals.len() > FOOTER_ROWS {
+         eprintln!("   … and {} more", totals.len() - FOOTER_ROWS);
+      }
+   }
+}
+
+fn millis(duration: Duration) -> String {
+   format!("{:.1}ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn test_timings_layer() {
+      let totals = Totals::default();
+      let subscriber =
+         tracing_subscriber::registry().with(TimingsLayer { totals: Arc::clone(&totals) });
+      tracing::subscriber::with_default(subscriber, || {
+         for _ in 0..3 {
+            let _span = tracing::debug_span!(target: "agentx::storage", "load_issue").entered();
+         }
+         let _span = tracing::debug_span!(target: "agentx::graph", "svg").entered();
+      });
+
+      let timings = Timings { totals, started: Instant::now() };
+      let mut calls: Vec<(String, u32)> = timings
+         .totals()
+         .into_iter()
+         .map(|(key, total)| (key, total.calls))
+         .collect();
+      calls.sort();
+      assert_eq!(calls, [("graph::svg".to_string(), 1), ("storage::load_issue".to_string(), 3)]);
+   }
+}
@@ -0,0 +1,118 @@
+//! Which status changes are allowed
+//!
+//! The `transitions` config maps a status to the statuses an issue may move
+//! to from it. Statuses left out of the config keep their defaults.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::issue::Status;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "HashMap<Status, Vec<Status>>", into = "HashMap<Status, Vec<Status>>")]
+pub struct TransitionRules {
+   allowed: HashMap<Status, Vec<Status>>,
+}
+
+impl Default for TransitionRules {
+   fn default() -> Self {
+      use Status::*;
+
+      // Listed in the order a way around a refused transition is looked for
+      let allowed = HashMap::from([
+         (NotStarted, vec![InProgress, Blocked, Backlog, Done, Closed]),
+         (InProgress, vec![NotStarted, Blocked, Backlog, Done, Closed]),
+         (Blocked, vec![InProgress, NotStarted, Backlog]),
+         (Done, vec![InProgress, NotStarted, Closed]),
+         (Closed, vec![NotStarted]),
+         (Backlog, vec![NotStarted, InProgress, Closed]),
+      ]);
+      Self { allowed }
+   }
+}
+
+impl From<HashMap<Status, Vec<Status>>> for TransitionRules {
+   fn from(configured: HashMap<Status, Vec<Status>>) -> Self {
+      let mut rules = Self::default();
+      rules.allowed.extend(configured);
+      rules
+   }
+}
+
+impl From<TransitionRules> for HashMap<Status, Vec<Status>> {
+   fn from(rules: TransitionRules) -> Self {
+      rules.allowed
+   }
+}
+
+/// The command that moves an issue from `from` to `to`
+fn command_for(from: Status, to: Status) -> &'static str {
+   match to {
+      Status::NotStarted if from == Status::Backlog => "activate",
+      Status::NotStarted => "open",
+      Status::InProgress => "start",
+      Status::Blocked => "block",
+      Status::Done => "checkpoint \"DONE: ...\"",
+      Status::Closed => "close",
+      Status::Backlog => "defer",
+   }
+}
+
+impl TransitionRules {
+   /// The statuses an issue may move to from `from`
+   pub fn next(&self, from: Status) -> &[Status] {
+      self.allowed.get(&from).map_or(&[], Vec::as_slice)
+   }
+
+   pub fn allows(&self, from: Status, to: Status) -> bool {
+      from == to
+         || self
+            .allowed
+            .get(&from)
+            .is_some_and(|next| next.contains(&to))
+   }
+
+   /// `Err` with advice on how to get there when `from` can't move to `to`
+   pub fn check(&self, from: Status, to: Status) -> Result<(), String> {
+      if self.allows(from, to) {
+         return Ok(());
+      }
+
+      let via = self
+         .allowed
+         .get(&from)
+         .into_iter()
+         .flatten()
+         .find(|&&via| self.allows(via, to));
+      Err(match via {
+         Some(&via) => format!("use {} first, or pass --force", command_for(from, via)),
+         None => "pass --force to override".to_string(),
+      })
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn test_rules_refuse_with_a_way_around() {
+      let rules = TransitionRules::default();
+      assert!(rules.allows(Status::InProgress, Status::InProgress));
+      assert!(rules.allows(Status::Blocked, Status::InProgress));
+      assert_eq!(
+         rules.check(Status::Closed, Status::InProgress),
+         Err("use open first, or pass --force".to_string())
+      );
+      assert_eq!(
+         rules.check(Status::Blocked, Status::Closed),
+         Err("use start first, or pass --force".to_string())
+      );
+
+      let custom: TransitionRules = serde_yaml::from_str("blocked: [closed]").unwrap();
+      assert!(custom.allows(Status::Blocked, Status::Closed));
+      assert!(!custom.allows(Status::Blocked, Status::InProgress));
+      assert!(!custom.allows(Status::Closed, Status::InProgress));
+   }
+}
@@ -50,6 +50,7 @@ pub enum Action {
    Help,
    Refresh,
    Filter,
+   FilterQuery,
    Sort,
    Search,
    New,
@@ -105,6 +106,9 @@ pub fn key_to_action(key: KeyEvent) -> Action {
       // Command palette
       KeyCode::Char('/') | KeyCode::Char(':') => Action::Search,
 
+      // Query filter bar
+      KeyCode::Char('f') => Action::FilterQuery,
+
       // Quick actions
       KeyCode::Char('n') => Action::New,
       KeyCode::Char('e') => Action::Edit,
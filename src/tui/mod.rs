@@ -3,9 +3,17 @@ pub mod theme;
 pub mod views;
 pub mod widgets;
 
-use std::{io, time::Duration};
+use std::{
+   io,
+   sync::{
+      Arc,
+      atomic::{AtomicBool, Ordering},
+   },
+   time::Duration,
+};
 
 use anyhow::Result;
+use chrono::Utc;
 use crossterm::{
    event::{DisableMouseCapture, EnableMouseCapture, KeyCode, KeyEvent},
    execute,
@@ -16,11 +24,46 @@ use ratatui::{Terminal, backend::CrosstermBackend};
 use theme::Theme;
 use views::DashboardView;
 
-use crate::{config::Config, issue::IssueWithId, storage::Storage};
+use crate::{
+   api::{BlockedIssue, IssueTracker, MetricsResult, RankedIssue, WinsSort},
+   config::Config,
+   daemon,
+   issue::IssueWithId,
+   query::Query,
+   storage::Storage,
+};
+
+/// What the metrics pane shows, from the same `*_data` calls the CLI and
+/// the MCP server make
+pub struct Insights {
+   pub metrics:     MetricsResult,
+   /// Issues open at the end of each of the last seven days
+   pub open_by_day: Vec<u64>,
+   pub focus:       Vec<RankedIssue>,
+   pub ready:       usize,
+   pub blocked:     Vec<BlockedIssue>,
+   pub quick_wins:  Vec<RankedIssue>,
+}
+
+impl Insights {
+   fn load(tracker: &IssueTracker) -> Result<Self> {
+      let series = tracker.metrics_series_data(Some("7d"), None, "day")?;
+      Ok(Self {
+         metrics:     tracker.metrics_data("week", false, false, false)?,
+         open_by_day: series.points.iter().map(|p| p.net_open as u64).collect(),
+         focus:       tracker.focus_data()?,
+         ready:       tracker.ready_data()?.len(),
+         blocked:     tracker.blocked_data()?,
+         quick_wins:  tracker.quick_wins_data("1h", true, WinsSort::Impact)?,
+      })
+   }
+}
 
 pub struct App {
    storage:             Storage,
+   tracker:             IssueTracker,
    issues:              Vec<IssueWithId>,
+   insights:            Insights,
    theme:               Theme,
    config:              Config,
    current_view:        ViewMode,
@@ -35,6 +78,9 @@ pub struct App {
    current_search_idx:  usize,
    sort_mode:           SortMode,
    filter_priority:     Option<String>,
+   filter_input:        String,
+   filter_query:        Option<Query>,
+   filter_error:        Option<String>,
    should_quit:         bool,
 }
 
@@ -44,24 +90,30 @@ enum SortMode {
    Priority,
    Effort,
    Created,
+   Updated,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum AppMode {
    Normal,
    Search,
+   Filter,
 }
 
 impl App {
    pub fn new(storage: Storage) -> Result<Self> {
       let mut issues = storage.list_open_issues()?;
       issues.extend(storage.list_closed_issues()?);
+      let config = Config::load();
+      let tracker = IssueTracker::new(storage.clone()).with_config(config.clone());
 
       Ok(Self {
          storage,
          issues,
+         insights: Insights::load(&tracker)?,
+         tracker,
          theme: Theme::default(),
-         config: Config::load(),
+         config,
          current_view: ViewMode::Dashboard,
          selected_pane: 0,
          selected_column: 1,
@@ -74,6 +126,9 @@ impl App {
          current_search_idx: 0,
          sort_mode: SortMode::Status,
          filter_priority: None,
+         filter_input: String::new(),
+         filter_query: None,
+         filter_error: None,
          should_quit: false,
       })
    }
@@ -85,6 +140,7 @@ impl App {
             let mut issues = self.storage.list_open_issues()?;
             issues.extend(self.storage.list_closed_issues()?);
             self.issues = issues;
+            self.insights = Insights::load(&self.tracker)?;
          },
          Action::SwitchView(view) => {
             self.current_view = view;
@@ -165,6 +221,15 @@ impl App {
          Action::Filter => {
             self.cycle_filter_priority();
          },
+         Action::FilterQuery => {
+            self.mode = AppMode::Filter;
+            self.filter_input = self
+               .filter_query
+               .as_ref()
+               .map(|q| q.source().to_string())
+               .unwrap_or_default();
+            self.filter_error = None;
+         },
          _ => {},
       }
 
@@ -189,6 +254,7 @@ impl App {
             .issues
             .iter()
             .filter(|i| i.issue.metadata.status == *status)
+            .filter(|i| self.filter_query.as_ref().is_none_or(|q| q.matches(i)))
             .collect();
 
          if let Some(ref priority_filter) = self.filter_priority {
@@ -229,6 +295,12 @@ impl App {
                      .cmp(&effort_hours(&b.issue.metadata.effort))
                },
                SortMode::Created => a.issue.metadata.created.cmp(&b.issue.metadata.created),
+               // Most recently changed first
+               SortMode::Updated => b
+                  .issue
+                  .metadata
+                  .updated_at()
+                  .cmp(&a.issue.metadata.updated_at()),
                SortMode::Status => std::cmp::Ordering::Equal,
             });
          }
@@ -332,7 +404,8 @@ impl App {
          SortMode::Status => SortMode::Priority,
          SortMode::Priority => SortMode::Effort,
          SortMode::Effort => SortMode::Created,
-         SortMode::Created => SortMode::Status,
+         SortMode::Created => SortMode::Updated,
+         SortMode::Updated => SortMode::Status,
       };
    }
 
@@ -346,6 +419,54 @@ impl App {
       };
    }
 
+   /// Issues passing the query filter bar (all issues when no filter is set)
+   fn visible_issues(&self) -> Vec<IssueWithId> {
+      match &self.filter_query {
+         Some(query) => self
+            .issues
+            .iter()
+            .filter(|i| query.matches(i))
+            .cloned()
+            .collect(),
+         None => self.issues.clone(),
+      }
+   }
+
+   fn handle_filter_key(&mut self, key: KeyEvent) {
+      match key.code {
+         KeyCode::Esc => {
+            self.mode = AppMode::Normal;
+            self.filter_error = None;
+         },
+         KeyCode::Enter => {
+            if self.filter_input.trim().is_empty() {
+               self.filter_query = None;
+               self.filter_error = None;
+               self.mode = AppMode::Normal;
+            } else {
+               match Query::parse(&self.filter_input) {
+                  Ok(query) => {
+                     self.filter_query = Some(query);
+                     self.filter_error = None;
+                     self.mode = AppMode::Normal;
+                  },
+                  Err(e) => self.filter_error = Some(e.to_string()),
+               }
+            }
+            self.selected_item = 0;
+            self.column_scroll_state[self.selected_column] = 0;
+            self.move_selection_vertical(1);
+         },
+         KeyCode::Backspace => {
+            self.filter_input.pop();
+         },
+         KeyCode::Char(c) => {
+            self.filter_input.push(c);
+         },
+         _ => {},
+      }
+   }
+
    fn handle_search_key(&mut self, key: KeyEvent) -> Result<()> {
       match key.code {
          KeyCode::Esc => {
@@ -450,8 +571,25 @@ impl App {
       // Event handler
       let event_handler = EventHandler::new(Duration::from_millis(250));
 
+      // Reload when a running daemon reports changes; without one this thread
+      // exits straight away
+      let changed = Arc::new(AtomicBool::new(false));
+      let flag = Arc::clone(&changed);
+      let base_dir = self.storage.base_dir().to_path_buf();
+      std::thread::spawn(move || {
+         let _ = daemon::subscribe(&base_dir, |_| flag.store(true, Ordering::Relaxed));
+      });
+
       // Main loop
       while !self.should_quit {
+         let visible_issues = self.visible_issues();
+         // Written by `agentx focus --pomodoro` in another terminal
+         let pomodoro = self
+            .storage
+            .load_pomodoro()
+            .ok()
+            .flatten()
+            .and_then(|p| p.status(Utc::now(), |n| self.config.format_issue_ref(n)));
          terminal.draw(|f| {
             let size = f.area();
 
@@ -475,20 +613,27 @@ impl App {
                      SortMode::Priority => Some("Priority"),
                      SortMode::Effort => Some("Effort"),
                      SortMode::Created => Some("Created"),
+                     SortMode::Updated => Some("Updated"),
                   };
 
                   let filter_info = self.filter_priority.as_deref();
+                  let filter_input =
+                     (self.mode == AppMode::Filter).then_some(self.filter_input.as_str());
+                  let filter_applied = self.filter_query.as_ref().map(|q| q.source());
 
-                  let dashboard = DashboardView::new(&self.issues, self.theme, &self.config)
+                  let dashboard = DashboardView::new(&visible_issues, self.theme, &self.config)
                      .selected_pane(self.selected_pane)
                      .selection(self.selected_column, self.selected_item)
                      .scroll_state(self.scroll_offset, self.column_scroll_state)
                      .search_state(search_query, search_count)
-                     .sort_filter_state(sort_info, filter_info);
+                     .sort_filter_state(sort_info, filter_info)
+                     .query_state(filter_input, filter_applied, self.filter_error.as_deref())
+                     .pomodoro_state(pomodoro.as_deref())
+                     .insights(&self.insights);
                   f.render_widget(dashboard, size);
                },
                ViewMode::Kanban => {
-                  let kanban = widgets::KanbanBoard::new(&self.issues, self.theme, &self.config);
+                  let kanban = widgets::KanbanBoard::new(&visible_issues, self.theme, &self.config);
                   f.render_widget(kanban, size);
                },
                _ => {
@@ -514,10 +659,16 @@ impl App {
                AppMode::Search => {
                   self.handle_search_key(key)?;
                },
+               AppMode::Filter => {
+                  self.handle_filter_key(key);
+               },
             },
             Event::Resize => {
                // Terminal was resized, will redraw on next iteration
             },
+            Event::Tick if changed.swap(false, Ordering::Relaxed) => {
+               self.handle_action(Action::Refresh)?;
+            },
             _ => {},
          }
       }
@@ -1,14 +1,16 @@
 use ratatui::{
    buffer::Buffer,
    layout::{Constraint, Direction, Layout, Rect},
+   style::Style,
    text::{Line, Span},
    widgets::{Block, Borders, Gauge, Paragraph, Widget},
 };
 
 use crate::{
    config::Config,
-   issue::IssueWithId,
+   issue::{IssueWithId, Status},
    tui::{
+      Insights,
       theme::Theme,
       widgets::{DependencyGraph, KanbanBoard, MiniChart},
    },
@@ -27,6 +29,12 @@ pub struct DashboardView<'a> {
    search_count:        Option<(usize, usize)>,
    sort_by:             Option<&'a str>,
    filter_by:           Option<&'a str>,
+   query_input:         Option<&'a str>,
+   query_applied:       Option<&'a str>,
+   query_error:         Option<&'a str>,
+   /// Running pomodoro, e.g. `🍅 1/4 12:05 ISSUE-3`
+   pomodoro:            Option<&'a str>,
+   insights:            Option<&'a Insights>,
 }
 
 impl<'a> DashboardView<'a> {
@@ -44,6 +52,11 @@ impl<'a> DashboardView<'a> {
          search_count: None,
          sort_by: None,
          filter_by: None,
+         query_input: None,
+         query_applied: None,
+         query_error: None,
+         pomodoro: None,
+         insights: None,
       }
    }
 
@@ -76,6 +89,28 @@ impl<'a> DashboardView<'a> {
       self
    }
 
+   pub fn pomodoro_state(mut self, pomodoro: Option<&'a str>) -> Self {
+      self.pomodoro = pomodoro;
+      self
+   }
+
+   pub fn insights(mut self, insights: &'a Insights) -> Self {
+      self.insights = Some(insights);
+      self
+   }
+
+   pub fn query_state(
+      mut self,
+      input: Option<&'a str>,
+      applied: Option<&'a str>,
+      error: Option<&'a str>,
+   ) -> Self {
+      self.query_input = input;
+      self.query_applied = applied;
+      self.query_error = error;
+      self
+   }
+
    fn render_header(&self, area: Rect, buf: &mut Buffer) {
       let total = self.issues.len();
       let critical = self
@@ -141,6 +176,22 @@ impl<'a> DashboardView<'a> {
          lines.push(Line::from(search_line));
       }
 
+      if let Some(q) = self.query_input {
+         let mut filter_line = vec![
+            Span::raw("  "),
+            Span::styled("where ", self.theme.dim_style()),
+            Span::styled(q, self.theme.title_style()),
+            Span::raw("_"),
+         ];
+
+         if let Some(err) = self.query_error {
+            filter_line.push(Span::raw("  "));
+            filter_line.push(Span::styled(err, self.theme.error()));
+         }
+
+         lines.push(Line::from(filter_line));
+      }
+
       let block = Block::default()
          .borders(Borders::NONE)
          .style(self.theme.header_style());
@@ -150,7 +201,17 @@ impl<'a> DashboardView<'a> {
    }
 
    fn render_footer(&self, area: Rect, buf: &mut Buffer) {
-      let mut footer_spans = if self.search_query.is_some() {
+      let mut footer_spans = if self.query_input.is_some() {
+         vec![
+            Span::raw("  "),
+            Span::styled("[Filter Mode]", self.theme.title_style()),
+            Span::raw("  "),
+            Span::styled("Enter", self.theme.dim_style()),
+            Span::raw(" Apply (empty clears)  "),
+            Span::styled("Esc", self.theme.dim_style()),
+            Span::raw(" Cancel"),
+         ]
+      } else if self.search_query.is_some() {
          vec![
             Span::raw("  "),
             Span::styled("[Search Mode]", self.theme.title_style()),
@@ -169,6 +230,8 @@ impl<'a> DashboardView<'a> {
             Span::raw(" Nav  "),
             Span::styled("/", self.theme.dim_style()),
             Span::raw(" Search  "),
+            Span::styled("f", self.theme.dim_style()),
+            Span::raw(" Where  "),
             Span::styled("F2", self.theme.dim_style()),
             Span::raw(" Filter  "),
             Span::styled("F3", self.theme.dim_style()),
@@ -190,6 +253,16 @@ impl<'a> DashboardView<'a> {
          footer_spans.push(Span::styled(format!("🔍 {}", filter), self.theme.success()));
       }
 
+      if let Some(query) = self.query_applied {
+         footer_spans.push(Span::raw("  "));
+         footer_spans.push(Span::styled(format!("⧩ {}", query), self.theme.success()));
+      }
+
+      if let Some(pomodoro) = self.pomodoro {
+         footer_spans.push(Span::raw("  "));
+         footer_spans.push(Span::styled(pomodoro, self.theme.warning()));
+      }
+
       Paragraph::new(Line::from(footer_spans))
          .style(self.theme.dim_style())
          .render(area, buf);
@@ -211,25 +284,34 @@ impl<'a> DashboardView<'a> {
       let inner = block.inner(area);
       block.render(area, buf);
 
-      // Sample metrics data (in real implementation, calculate from issues)
-      let burndown_data = [20, 18, 15, 13, 10, 8, 5];
+      let Some(insights) = self.insights else {
+         return;
+      };
+      let metrics = &insights.metrics;
 
       let metrics_layout = Layout::default()
          .direction(Direction::Vertical)
          .constraints([
-            Constraint::Length(3), // Velocity gauge
+            Constraint::Length(3), // Closed this week
             Constraint::Length(1), // Spacer
-            Constraint::Length(3), // Burndown
+            Constraint::Length(3), // Open issues over the week
             Constraint::Length(1), // Spacer
             Constraint::Min(0),    // Stats
          ])
          .split(inner);
 
-      // Velocity gauge
-      let velocity = 8u64;
-      let velocity_gauge = Gauge::default()
-         .ratio((velocity as f64 / 10.0).min(1.0))
-         .label(format!(" Velocity {} pts/day ", velocity))
+      // Share of this week's open work that got closed
+      let worked = metrics.closed_in_period + metrics.total_open;
+      let closed_gauge = Gauge::default()
+         .ratio(if worked == 0 {
+            0.0
+         } else {
+            metrics.closed_in_period as f64 / worked as f64
+         })
+         .label(format!(
+            " {} closed, {} opened this week ",
+            metrics.closed_in_period, metrics.opened_in_period
+         ))
          .use_unicode(true)
          .style(self.theme.normal_style())
          .gauge_style(
@@ -239,47 +321,50 @@ impl<'a> DashboardView<'a> {
                .bg(self.theme.bg())
                .add_modifier(ratatui::style::Modifier::BOLD),
          );
-      velocity_gauge.render(metrics_layout[0], buf);
+      closed_gauge.render(metrics_layout[0], buf);
 
-      // Burndown chart
-      MiniChart::new("Burndown", 5, &burndown_data, " pts", self.theme)
+      MiniChart::new("Open", metrics.total_open as u64, &insights.open_by_day, "", self.theme)
          .render(metrics_layout[2], buf);
 
-      // Quick stats with better formatting
-      use crate::issue::Status;
-      let done_count = self
-         .issues
-         .iter()
-         .filter(|i| matches!(i.issue.metadata.status, Status::Done | Status::Closed))
-         .count();
-      let wip_count = self
-         .issues
-         .iter()
-         .filter(|i| matches!(i.issue.metadata.status, Status::InProgress))
-         .count();
-      let blocked_count = self
-         .issues
-         .iter()
-         .filter(|i| matches!(i.issue.metadata.status, Status::Blocked))
-         .count();
-
-      let stats = vec![
-         Line::from(""),
-         Line::from(vec![
-            Span::styled("  Done:    ", self.theme.dim_style()),
-            Span::styled(format!("{}", done_count), self.theme.success()),
-         ]),
-         Line::from(""),
-         Line::from(vec![
-            Span::styled("  WIP:     ", self.theme.dim_style()),
-            Span::styled(format!("{}", wip_count), self.theme.warning()),
-         ]),
-         Line::from(""),
-         Line::from(vec![
-            Span::styled("  Blocked: ", self.theme.dim_style()),
-            Span::styled(format!("{}", blocked_count), self.theme.error()),
-         ]),
+      let count = |status: Status| {
+         metrics
+            .by_status
+            .get(&status.to_string())
+            .copied()
+            .unwrap_or(0)
+      };
+      let stat = |label: &'static str, value: String, style: Style| {
+         Line::from(vec![Span::styled(label, self.theme.dim_style()), Span::styled(value, style)])
+      };
+      let mut stats = vec![
+         stat(
+            "  Done:    ",
+            (metrics.total_closed + count(Status::Done)).to_string(),
+            Style::default().fg(self.theme.success()),
+         ),
+         stat(
+            "  WIP:     ",
+            count(Status::InProgress).to_string(),
+            Style::default().fg(self.theme.warning()),
+         ),
+         stat(
+            "  Blocked: ",
+            insights.blocked.len().to_string(),
+            Style::default().fg(self.theme.error()),
+         ),
+         stat("  Ready:   ", insights.ready.to_string(), self.theme.normal_style()),
+         stat("  Wins:    ", insights.quick_wins.len().to_string(), self.theme.normal_style()),
       ];
+      if let Some(top) = insights.focus.first() {
+         stats.push(Line::from(""));
+         stats.push(stat(
+            "  Focus:   ",
+            self.config.format_issue_ref(top.num),
+            self.theme.title_style(),
+         ));
+         stats
+            .push(Line::from(Span::styled(format!("  {}", top.title), self.theme.normal_style())));
+      }
 
       Paragraph::new(stats).render(metrics_layout[4], buf);
    }
@@ -287,7 +372,8 @@ impl<'a> DashboardView<'a> {
 
 impl Widget for DashboardView<'_> {
    fn render(self, area: Rect, buf: &mut Buffer) {
-      let header_height = if self.search_query.is_some() { 3 } else { 2 };
+      let header_height =
+         2 + self.search_query.is_some() as u16 + self.query_input.is_some() as u16;
 
       let main_layout = Layout::default()
          .direction(Direction::Vertical)
@@ -130,7 +130,7 @@ impl<'a> DetailView<'a> {
       // Body content
       lines.push(Line::from(Span::styled("Description:", self.theme.title_style())));
       lines.push(Line::from(""));
-      for line in self.issue.issue.body.lines() {
+      for line in self.issue.issue.body.to_string().lines() {
          lines.push(Line::from(Span::styled(line.to_string(), self.theme.normal_style())));
       }
 
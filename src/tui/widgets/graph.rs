@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 
 use ratatui::{
    buffer::Buffer,
@@ -7,7 +7,7 @@ use ratatui::{
    widgets::{Block, Borders, Paragraph, Widget},
 };
 
-use crate::{config::Config, issue::IssueWithId, tui::theme::Theme};
+use crate::{config::Config, graph::IssueGraph, issue::IssueWithId, tui::theme::Theme};
 
 pub struct DependencyGraph<'a> {
    issues:      &'a [IssueWithId],
@@ -29,19 +29,7 @@ impl<'a> DependencyGraph<'a> {
    fn build_graph_text(&self) -> Vec<Line<'_>> {
       let mut lines = Vec::new();
 
-      // Build dependency map
-      let mut dep_map: HashMap<u32, Vec<u32>> = HashMap::new();
-      let mut reverse_dep_map: HashMap<u32, Vec<u32>> = HashMap::new();
-
-      for issue in self.issues {
-         let id = issue.id;
-         dep_map.insert(id, Vec::new());
-
-         for dep in &issue.issue.metadata.depends_on {
-            dep_map.get_mut(&id).unwrap().push(*dep);
-            reverse_dep_map.entry(*dep).or_default().push(id);
-         }
-      }
+      let graph = IssueGraph::from_issues(self.issues);
 
       // If focus issue is set, only show that issue and its dependencies
       let issues_to_show: Vec<u32> = if let Some(focus) = self.focus_issue {
@@ -54,19 +42,9 @@ impl<'a> DependencyGraph<'a> {
          let mut to_show = HashSet::new();
          to_show.insert(focus_id);
 
-         // Add dependencies (what it depends on)
-         if let Some(deps) = dep_map.get(&focus_id) {
-            for dep in deps {
-               to_show.insert(*dep);
-            }
-         }
-
-         // Add reverse dependencies (what depends on it)
-         if let Some(rdeps) = reverse_dep_map.get(&focus_id) {
-            for rdep in rdeps {
-               to_show.insert(*rdep);
-            }
-         }
+         // Add dependencies (what it depends on) and dependents
+         to_show.extend(graph.depends_on(focus_id));
+         to_show.extend(graph.dependents(focus_id));
 
          to_show.into_iter().collect()
       } else {
@@ -80,10 +58,7 @@ impl<'a> DependencyGraph<'a> {
       for issue_id in issues_to_show.iter() {
          // Skip if we're not in focus mode and this issue has no dependencies
          if self.focus_issue.is_none() {
-            let has_deps = dep_map.get(issue_id).is_some_and(|d| !d.is_empty());
-            let has_rdeps = reverse_dep_map.get(issue_id).is_some_and(|d| !d.is_empty());
-
-            if !has_deps && !has_rdeps {
+            if graph.depends_on(*issue_id).is_empty() && graph.dependents(*issue_id).is_empty() {
                continue;
             }
 
@@ -111,9 +86,8 @@ impl<'a> DependencyGraph<'a> {
          lines.push(node_line);
 
          // Dependencies
-         if let Some(deps) = dep_map.get(issue_id)
-            && !deps.is_empty()
-         {
+         let deps = graph.depends_on(*issue_id);
+         if !deps.is_empty() {
             for (idx, dep) in deps.iter().enumerate() {
                let is_last = idx == deps.len() - 1;
                let connector = if is_last {
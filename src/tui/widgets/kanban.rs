@@ -1,3 +1,4 @@
+use chrono::Utc;
 use ratatui::{
    buffer::Buffer,
    layout::Rect,
@@ -10,6 +11,7 @@ use crate::{
    config::Config,
    issue::{IssueWithId, Status},
    tui::theme::Theme,
+   utils::short_age,
 };
 
 pub struct KanbanBoard<'a> {
@@ -158,12 +160,20 @@ impl Widget for KanbanBoard<'_> {
 
                lines.push(Line::from(title_spans));
 
-               if let Some(effort) = &issue.issue.metadata.effort {
-                  lines.push(Line::from(vec![
-                     Span::raw("   "),
-                     Span::styled(format!("⏱ {effort}"), self.theme.dim_style()),
-                  ]));
+               let meta = &issue.issue.metadata;
+               let now = Utc::now();
+               let mut details = format!(
+                  "{} old · updated {}",
+                  short_age(meta.created, now),
+                  short_age(meta.updated_at(), now)
+               );
+               if let Some(effort) = &meta.effort {
+                  details = format!("⏱ {effort} · {details}");
                }
+               lines.push(Line::from(vec![
+                  Span::raw("   "),
+                  Span::styled(details, self.theme.dim_style()),
+               ]));
 
                lines.push(Line::from(""));
 
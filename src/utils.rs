@@ -1,44 +1,314 @@
-use anyhow::Result;
+use std::{
+   collections::BTreeMap,
+   io::Write,
+   path::{Path, PathBuf},
+   process::{Command, Stdio},
+   sync::OnceLock,
+};
 
-/// Parse effort string like "2h", "30m", "1d" into minutes
-pub fn parse_effort(s: &str) -> Result<u32> {
-   let s = s.trim();
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Minute equivalents for estimates that are not given as time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffortScale {
+   /// T-shirt sizes (matched case-insensitively) and their length in minutes
+   #[serde(default = "default_sizes")]
+   pub sizes:          BTreeMap<String, u32>,
+   /// Team velocity: story points finished in one 8-hour day
+   #[serde(default = "default_points_per_day")]
+   pub points_per_day: f64,
+   /// What an issue without an estimate counts as where effort is summed,
+   /// e.g. along the critical path
+   #[serde(default = "default_unestimated")]
+   pub unestimated:    String,
+}
+
+fn default_sizes() -> BTreeMap<String, u32> {
+   // Midpoints of the ranges the new-issue wizard shows for each size
+   [("XS", 30), ("S", 90), ("M", 180), ("L", 480), ("XL", 960)]
+      .into_iter()
+      .map(|(size, minutes)| (size.to_string(), minutes))
+      .collect()
+}
+
+fn default_points_per_day() -> f64 {
+   2.0
+}
+
+fn default_unestimated() -> String {
+   "M".to_string()
+}
 
-   if s.is_empty() {
-      anyhow::bail!("Empty effort string");
+impl Default for EffortScale {
+   fn default() -> Self {
+      Self {
+         sizes:          default_sizes(),
+         points_per_day: default_points_per_day(),
+         unestimated:    default_unestimated(),
+      }
    }
+}
 
-   // Find where the number ends and unit begins
-   let mut num_end = 0;
-   for (i, c) in s.chars().enumerate() {
-      if c.is_ascii_digit() || c == '.' {
-         num_end = i + 1;
-      } else {
-         break;
+impl EffortScale {
+   /// Parse an estimate into minutes: time like "2h", "30m" or "1d", a range
+   /// like "2-4h" or "4h-2d" (its midpoint), a T-shirt size like "M", or story
+   /// points like "3pt"
+   pub fn parse(&self, s: &str) -> Result<u32> {
+      let s = s.trim();
+
+      if s.is_empty() {
+         anyhow::bail!("Empty effort string");
       }
+
+      if let Some((_, minutes)) = self
+         .sizes
+         .iter()
+         .find(|(size, _)| size.eq_ignore_ascii_case(s))
+      {
+         return Ok(*minutes);
+      }
+
+      if let Some((low, high)) = s.split_once('-') {
+         let (low, high) = (low.trim(), high.trim());
+         let high_minutes = self.parse_single(high)?;
+         // "2-4h" shares the unit of the upper bound
+         let low_minutes = if low.parse::<f64>().is_ok() {
+            let unit = high.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+            self.parse_single(&format!("{low}{unit}"))?
+         } else {
+            self.parse_single(low)?
+         };
+         if low_minutes > high_minutes {
+            anyhow::bail!("Effort range is backwards: {s}");
+         }
+         return Ok((low_minutes + high_minutes) / 2);
+      }
+
+      self.parse_single(s)
    }
 
-   if num_end == 0 {
-      anyhow::bail!("No number found in effort string: {s}");
+   /// Minutes for `effort`, or for the `unestimated` default when it is
+   /// missing or can't be parsed; the `bool` says whether it was estimated
+   pub fn minutes_or_default(&self, effort: Option<&str>) -> Result<(u32, bool)> {
+      if let Some(minutes) = effort.and_then(|e| self.parse(e).ok()) {
+         return Ok((minutes, true));
+      }
+      let minutes = self
+         .parse(&self.unestimated)
+         .with_context(|| format!("Invalid effort.unestimated: {}", self.unestimated))?;
+      Ok((minutes, false))
+   }
+
+   fn parse_single(&self, s: &str) -> Result<u32> {
+      // Find where the number ends and unit begins
+      let num_end = s
+         .find(|c: char| !c.is_ascii_digit() && c != '.')
+         .unwrap_or(s.len());
+
+      if num_end == 0 {
+         anyhow::bail!("No number found in effort string: {s}");
+      }
+
+      let num_part = &s[..num_end];
+      let unit_part = s[num_end..].trim();
+
+      let value: f64 = num_part
+         .parse()
+         .map_err(|_| anyhow::anyhow!("Invalid number in effort: {num_part}"))?;
+
+      let minutes = match unit_part.to_lowercase().as_str() {
+         "m" | "min" | "mins" | "minute" | "minutes" => value,
+         "h" | "hr" | "hrs" | "hour" | "hours" => value * 60.0,
+         "d" | "day" | "days" => value * 60.0 * 8.0, // 8-hour workday
+         "w" | "week" | "weeks" => value * 60.0 * 8.0 * 5.0, // 5-day work week
+         "p" | "pt" | "pts" | "sp" | "point" | "points" => {
+            if self.points_per_day <= 0.0 {
+               anyhow::bail!("points_per_day must be positive to estimate in story points");
+            }
+            value * 60.0 * 8.0 / self.points_per_day
+         },
+         "" => value, // Default to minutes if no unit
+         _ => anyhow::bail!("Unknown effort unit: {unit_part}"),
+      };
+
+      Ok(minutes as u32)
    }
+}
+
+static EFFORT_SCALE: OnceLock<EffortScale> = OnceLock::new();
 
-   let num_part = &s[..num_end];
-   let unit_part = s[num_end..].trim();
+/// Install the configured effort scale for the rest of the process; only the
+/// first call has an effect
+pub fn set_effort_scale(scale: EffortScale) {
+   let _ = EFFORT_SCALE.set(scale);
+}
 
-   let value: f64 = num_part
+/// Parse an effort estimate into minutes with the configured scale (see
+/// [`EffortScale::parse`])
+pub fn parse_effort(s: &str) -> Result<u32> {
+   EFFORT_SCALE.get_or_init(EffortScale::default).parse(s)
+}
+
+/// Parse a wall-clock duration such as `90m`, `2h`, `1d` or `1w`; unlike an
+/// effort, a day here is 24 hours
+pub fn parse_duration(s: &str) -> Result<chrono::Duration> {
+   let s = s.trim();
+   let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+   let (num, unit) = s.split_at(split);
+   let num: i64 = num
       .parse()
-      .map_err(|_| anyhow::anyhow!("Invalid number in effort: {num_part}"))?;
-
-   let minutes = match unit_part {
-      "m" | "min" | "mins" | "minute" | "minutes" => value,
-      "h" | "hr" | "hrs" | "hour" | "hours" => value * 60.0,
-      "d" | "day" | "days" => value * 60.0 * 8.0, // 8-hour workday
-      "w" | "week" | "weeks" => value * 60.0 * 8.0 * 5.0, // 5-day work week
-      "" => value,                                // Default to minutes if no unit
-      _ => anyhow::bail!("Unknown effort unit: {unit_part}"),
+      .with_context(|| format!("No number found in duration: {s}"))?;
+   Ok(match unit.trim().to_lowercase().as_str() {
+      "m" | "min" | "mins" | "minute" | "minutes" => chrono::Duration::minutes(num),
+      "h" | "hr" | "hrs" | "hour" | "hours" => chrono::Duration::hours(num),
+      "d" | "day" | "days" => chrono::Duration::days(num),
+      "w" | "week" | "weeks" => chrono::Duration::weeks(num),
+      _ => anyhow::bail!("Unknown duration unit in {s}: use m, h, d or w"),
+   })
+}
+
+/// Parse a due time: a date (`2026-11-01`, the end of that day in UTC), an
+/// RFC 3339 time, or a duration from `now` such as `3d`
+pub fn parse_due(s: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+   let s = s.trim().trim_start_matches('+');
+   if let Ok(date) = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+      let end = date.and_hms_opt(23, 59, 59).context("Invalid date")?;
+      return Ok(end.and_utc());
+   }
+   if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+      return Ok(time.with_timezone(&Utc));
+   }
+   parse_duration(s).map(|d| now + d).map_err(|_| {
+      anyhow::anyhow!("Invalid due time: {s}. Use a date, an RFC 3339 time or a duration like 3d")
+   })
+}
+
+/// Time from `then` to `now` in the largest whole unit, such as `45m`,
+/// `3h`, `12d` or `5w`, for age columns
+pub fn short_age(then: DateTime<Utc>, now: DateTime<Utc>) -> String {
+   let elapsed = now - then;
+   match elapsed.num_minutes() {
+      m if m < 1 => "now".to_string(),
+      m if m < 60 => format!("{m}m"),
+      m if m < 60 * 24 => format!("{}h", m / 60),
+      m if m < 60 * 24 * 7 * 2 => format!("{}d", m / (60 * 24)),
+      m if m < 60 * 24 * 365 => format!("{}w", m / (60 * 24 * 7)),
+      m => format!("{}y", m / (60 * 24 * 365)),
+   }
+}
+
+/// Render a duration in hours compactly, switching to days past two days
+pub fn format_hours(hours: f64) -> String {
+   if hours < 48.0 {
+      format!("{hours:.1}h")
+   } else {
+      format!("{:.1}d", hours / 24.0)
+   }
+}
+
+/// Normalize a file path for comparison with the `files` stored on issues:
+/// forward slashes, no leading `./` or trailing `/`, and absolute paths made
+/// relative to `root` when they live under it
+pub fn normalize_path(path: &str, root: Option<&Path>) -> String {
+   let path = path.trim();
+   let relative = root
+      .and_then(|root| Path::new(path).strip_prefix(root).ok())
+      .map(|rel| rel.to_string_lossy().into_owned())
+      .unwrap_or_else(|| path.to_string());
+
+   let mut normalized = relative.replace('\\', "/");
+   while let Some(rest) = normalized.strip_prefix("./") {
+      normalized = rest.to_string();
+   }
+   normalized.trim_end_matches('/').to_string()
+}
+
+/// The path of a `files` entry, without a `:line` (or `:line:column`) suffix
+pub fn strip_line_suffix(entry: &str) -> &str {
+   let mut path = entry.trim();
+   while let Some((rest, suffix)) = path.rsplit_once(':') {
+      if suffix.is_empty() || !suffix.bytes().all(|b| b.is_ascii_digit()) {
+         break;
+      }
+      path = rest;
+   }
+   path
+}
+
+/// The repository the current directory is in, else the current directory;
+/// what stored `files` are relative to
+pub fn repo_root() -> Result<PathBuf> {
+   let cwd = std::env::current_dir()?;
+   Ok(git2::Repository::discover(&cwd)
+      .ok()
+      .and_then(|repo| repo.workdir().map(|dir| dir.to_path_buf()))
+      .unwrap_or(cwd))
+}
+
+/// Whether two normalized paths refer to the same file, or one is a directory
+/// containing the other
+pub fn paths_overlap(a: &str, b: &str) -> bool {
+   let within = |dir: &str, path: &str| {
+      path
+         .strip_prefix(dir)
+         .is_some_and(|rest| rest.starts_with('/'))
    };
+   a == b || within(a, b) || within(b, a)
+}
+
+/// Clipboard commands to try, in order
+const CLIPBOARD_COMMANDS: &[&[&str]] = &[
+   &["pbcopy"],
+   &["wl-copy"],
+   &["xclip", "-selection", "clipboard"],
+   &["xsel", "--clipboard", "--input"],
+   &["clip.exe"],
+];
+
+/// Put `text` on the system clipboard through the first clipboard tool found
+pub fn copy_to_clipboard(text: &str) -> Result<()> {
+   for command in CLIPBOARD_COMMANDS {
+      let Ok(mut child) = Command::new(command[0])
+         .args(&command[1..])
+         .stdin(Stdio::piped())
+         .stdout(Stdio::null())
+         .stderr(Stdio::null())
+         .spawn()
+      else {
+         continue;
+      };
+      if let Some(mut stdin) = child.stdin.take() {
+         stdin.write_all(text.as_bytes())?;
+      }
+      if child.wait()?.success() {
+         return Ok(());
+      }
+   }
+   let tried: Vec<&str> = CLIPBOARD_COMMANDS.iter().map(|c| c[0]).collect();
+   anyhow::bail!("No working clipboard tool found (tried {})", tried.join(", "))
+}
 
-   Ok(minutes as u32)
+/// Open `path` in `$VISUAL`, `$EDITOR` or `vi`, waiting for it to exit
+pub fn open_in_editor(path: &Path) -> Result<()> {
+   let editor = std::env::var("VISUAL")
+      .or_else(|_| std::env::var("EDITOR"))
+      .ok()
+      .filter(|e| !e.trim().is_empty())
+      .unwrap_or_else(|| "vi".to_string());
+   // Editors are often configured with arguments, e.g. `code --wait`
+   let mut parts = editor.split_whitespace();
+   let program = parts.next().context("Empty editor command")?;
+   let status = Command::new(program)
+      .args(parts)
+      .arg(path)
+      .status()
+      .with_context(|| format!("Failed to run editor '{editor}'"))?;
+   if !status.success() {
+      anyhow::bail!("Editor '{editor}' exited with {status}");
+   }
+   Ok(())
 }
 
 #[cfg(test)]
@@ -52,5 +322,40 @@ mod tests {
       assert_eq!(parse_effort("1d").unwrap(), 480);
       assert_eq!(parse_effort("0.5h").unwrap(), 30);
       assert_eq!(parse_effort("1.5 hours").unwrap(), 90);
+
+      let scale = EffortScale::default();
+      assert_eq!(scale.parse("2-4h").unwrap(), 180);
+      assert_eq!(scale.parse("4h-2d").unwrap(), 600);
+      assert_eq!(scale.parse("xl").unwrap(), 960);
+      assert_eq!(scale.parse("3pt").unwrap(), 720);
+      assert_eq!(scale.parse("45").unwrap(), 45);
+      assert!(scale.parse("4-2h").is_err());
+      assert_eq!(scale.minutes_or_default(Some("2h")).unwrap(), (120, true));
+      assert_eq!(scale.minutes_or_default(None).unwrap(), (180, false));
+
+      assert_eq!(parse_duration("2h").unwrap(), chrono::Duration::hours(2));
+      assert_eq!(parse_duration("1d").unwrap(), chrono::Duration::hours(24));
+      assert!(parse_duration("soon").is_err());
+
+      let now = Utc::now();
+      assert_eq!(parse_due("2d", now).unwrap(), now + chrono::Duration::days(2));
+      assert_eq!(parse_due("2026-11-01", now).unwrap().to_rfc3339(), "2026-11-01T23:59:59+00:00");
+      assert!(parse_due("someday", now).is_err());
+
+      assert_eq!(short_age(now, now), "now");
+      assert_eq!(short_age(now - chrono::Duration::minutes(90), now), "1h");
+      assert_eq!(short_age(now - chrono::Duration::days(10), now), "10d");
+      assert_eq!(short_age(now - chrono::Duration::days(30), now), "4w");
+   }
+
+   #[test]
+   fn test_path_matching() {
+      let root = Path::new("/repo");
+      assert_eq!(normalize_path("/repo/src/tui/app.rs", Some(root)), "src/tui/app.rs");
+      assert_eq!(normalize_path("./src/tui/", None), "src/tui");
+
+      assert!(paths_overlap("src/tui", "src/tui/app.rs"));
+      assert!(paths_overlap("src/tui/app.rs", "src/tui"));
+      assert!(!paths_overlap("src/tui", "src/tuition.rs"));
    }
 }
@@ -0,0 +1,150 @@
+//! Issue watchers
+//!
+//! Watching an issue adds the current user to its `watchers`. Whenever a
+//! watched issue changes status or gets a checkpoint, the process making the
+//! change sends a desktop notification and/or a webhook, and the next CLI run
+//! of a watcher shows what changed since they last looked.
+
+use std::{collections::HashMap, fmt, time::Duration};
+
+use git2::Config as GitConfig;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::issue::{Issue, Status};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchConfig {
+   /// Show a desktop notification when an issue you watch changes
+   #[serde(default = "default_desktop")]
+   pub desktop: bool,
+
+   /// URL that gets a JSON POST for every change to a watched issue
+   #[serde(default)]
+   pub webhook: Option<String>,
+}
+
+fn default_desktop() -> bool {
+   true
+}
+
+impl Default for WatchConfig {
+   fn default() -> Self {
+      Self { desktop: default_desktop(), webhook: None }
+   }
+}
+
+/// Who "me" is when watching: git's `user.name`, falling back to the login
+/// name
+pub fn current_user() -> String {
+   GitConfig::open_default()
+      .and_then(|config| config.get_string("user.name"))
+      .ok()
+      .or_else(|| std::env::var("USER").ok())
+      .or_else(|| std::env::var("USERNAME").ok())
+      .unwrap_or_else(|| "me".to_string())
+}
+
+/// A change watchers are told about
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum WatchEvent {
+   Status { from: Status, to: Status },
+   Checkpoint { note: String },
+}
+
+impl fmt::Display for WatchEvent {
+   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      match self {
+         Self::Status { from, to } => write!(f, "status {from} → {to}"),
+         Self::Checkpoint { note } => write!(f, "checkpoint: {note}"),
+      }
+   }
+}
+
+/// What a watcher last saw of an issue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Seen {
+   pub status:      Status,
+   pub checkpoints: usize,
+}
+
+impl Seen {
+   pub fn of(issue: &Issue) -> Self {
+      Self { status: issue.metadata.status, checkpoints: issue.body.checkpoints().count() }
+   }
+
+   /// Changes between this and the issue as it is now
+   pub fn events(&self, issue: &Issue) -> Vec<WatchEvent> {
+      let mut events = Vec::new();
+      if self.status != issue.metadata.status {
+         events.push(WatchEvent::Status { from: self.status, to: issue.metadata.status });
+      }
+      for checkpoint in issue.body.checkpoints().skip(self.checkpoints) {
+         events.push(WatchEvent::Checkpoint { note: checkpoint.content().to_string() });
+      }
+      events
+   }
+}
+
+/// Per-user record of what each watched issue looked like at the last banner
+pub type SeenState = HashMap<String, HashMap<u32, Seen>>;
+
+/// Tell the watchers of `issue` about `events`; failures are ignored, a
+/// notification never gets in the way of the change itself
+pub fn notify(config: &WatchConfig, bug_num: u32, issue: &Issue, events: &[WatchEvent]) {
+   if events.is_empty() || issue.metadata.watchers.is_empty() {
+      return;
+   }
+
+   let summary = format!("#{bug_num} {}", issue.metadata.title);
+   let lines: Vec<String> = events.iter().map(ToString::to_string).collect();
+
+   let me = current_user();
+   if config.desktop && issue.metadata.watchers.iter().any(|w| *w == me) {
+      let _ = notify_rust::Notification::new()
+         .appname("agentx")
+         .summary(&summary)
+         .body(&lines.join("\n"))
+         .show();
+   }
+
+   if let Some(url) = &config.webhook {
+      let payload = json!({
+         "bug_num": bug_num,
+         "title": issue.metadata.title,
+         "status": issue.metadata.status,
+         "watchers": issue.metadata.watchers,
+         "events": events,
+         "text": format!("{summary}: {}", lines.join("; ")),
+      });
+      let _ = ureq::post(url)
+         .timeout(Duration::from_secs(5))
+         .send_json(payload);
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::issue::test_support::issue;
+
+   #[test]
+   fn test_seen_reports_status_and_new_checkpoints() {
+      let mut issue = issue(1).title("Fix login").body("Times out").build().issue;
+      issue
+         .body
+         .append("\n\n**Checkpoint** (2024-01-01 10:00): Found it");
+      let seen = Seen::of(&issue);
+      assert!(seen.events(&issue).is_empty());
+
+      issue.metadata.set_status(Status::InProgress);
+      issue
+         .body
+         .append("\n\n## Checkpoint - 2024-01-02 09:00\n\nFixing");
+      assert_eq!(seen.events(&issue), [
+         WatchEvent::Status { from: Status::NotStarted, to: Status::InProgress },
+         WatchEvent::Checkpoint { note: "Fixing".into() },
+      ]);
+   }
+}
@@ -0,0 +1,235 @@
+//! Web dashboard
+//!
+//! `agentx serve --web` (built with the `web` feature) shows the kanban
+//! board, issue details, the dependency graph and metrics in a browser: one
+//! embedded page over a small JSON API on [`IssueTracker`]. The dashboard is
+//! read-only unless the server is started with a token; changes must then
+//! send it as `Authorization: Bearer <token>`.
+
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::{
+   Json, Router,
+   extract::{Path, Query, State},
+   http::{HeaderMap, StatusCode, header},
+   response::{Html, IntoResponse, Response},
+   routing::{get, post},
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::{api::IssueTracker, graph};
+
+const INDEX: &str = include_str!("web/index.html");
+
+struct Dashboard {
+   tracker: IssueTracker,
+   token:   Option<String>,
+}
+
+/// An error sent as `{"error": ...}` with its HTTP status
+struct WebError(StatusCode, String);
+
+impl IntoResponse for WebError {
+   fn into_response(self) -> Response {
+      (self.0, Json(json!({"error": self.1}))).into_response()
+   }
+}
+
+impl From<anyhow::Error> for WebError {
+   fn from(e: anyhow::Error) -> Self {
+      Self(StatusCode::BAD_REQUEST, e.to_string())
+   }
+}
+
+type WebResult<T> = Result<T, WebError>;
+
+#[derive(Deserialize)]
+struct BoardParams {
+   columns: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct MetricsParams {
+   period: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StatusChange {
+   /// `start`, `block`, `close`, `reopen`, `defer` or `activate`
+   action: String,
+   /// Block reason or closing message
+   note:   Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Checkpoint {
+   note: String,
+}
+
+/// Serve the dashboard for `tracker` on `addr` until interrupted
+pub async fn serve(tracker: IssueTracker, addr: &str, token: Option<String>) -> anyhow::Result<()> {
+   let listener = tokio::net::TcpListener::bind(addr)
+      .await
+      .with_context(|| format!("Failed to listen on {addr}"))?;
+   let mode = if token.is_some() {
+      "changes need the token"
+   } else {
+      "read-only"
+   };
+   eprintln!("Serving the agentx dashboard on http://{} ({mode})", listener.local_addr()?);
+   axum::serve(listener, router(tracker, token)).await?;
+   Ok(())
+}
+
+fn router(tracker: IssueTracker, token: Option<String>) -> Router {
+   Router::new()
+      .route("/", get(index))
+      .route("/api/info", get(info))
+      .route("/api/board", get(board))
+      .route("/api/issues/{issue}", get(show))
+      .route("/api/issues/{issue}/status", post(change_status))
+      .route("/api/issues/{issue}/checkpoint", post(checkpoint))
+      .route("/api/graph.svg", get(graph_svg))
+      .route("/api/metrics", get(metrics))
+      .with_state(Arc::new(Dashboard { tracker, token }))
+}
+
+async fn index() -> Html<&'static str> {
+   Html(INDEX)
+}
+
+async fn info(State(dashboard): State<Arc<Dashboard>>) -> Json<Value> {
+   Json(json!({
+      "writable": dashboard.token.is_some(),
+      "prefix": dashboard.tracker.config().issue_prefix,
+      "version": env!("CARGO_PKG_VERSION"),
+   }))
+}
+
+async fn board(
+   State(dashboard): State<Arc<Dashboard>>,
+   Query(params): Query<BoardParams>,
+) -> WebResult<Json<Value>> {
+   let columns = dashboard.tracker.board_data(params.columns.as_deref())?;
+   Ok(Json(json!(columns)))
+}
+
+async fn show(
+   State(dashboard): State<Arc<Dashboard>>,
+   Path(issue): Path<String>,
+) -> WebResult<Json<Value>> {
+   let shown = dashboard
+      .tracker
+      .show(&issue)
+      .map_err(|e| WebError(StatusCode::NOT_FOUND, e.to_string()))?;
+   Ok(Json(json!(shown)))
+}
+
+async fn graph_svg(State(dashboard): State<Arc<Dashboard>>) -> WebResult<Response> {
+   let nodes = dashboard.tracker.graph(None)?;
+   let config = dashboard.tracker.config();
+   let svg = graph::svg(&nodes, |n| config.format_issue_ref(n), None)?;
+   Ok(([(header::CONTENT_TYPE, "image/svg+xml")], svg).into_response())
+}
+
+async fn metrics(
+   State(dashboard): State<Arc<Dashboard>>,
+   Query(params): Query<MetricsParams>,
+) -> WebResult<Json<Value>> {
+   let metrics = dashboard
+      .tracker
+      .metrics(params.period.as_deref().unwrap_or("week"))?;
+   Ok(Json(json!(metrics)))
+}
+
+async fn change_status(
+   State(dashboard): State<Arc<Dashboard>>,
+   Path(issue): Path<String>,
+   headers: HeaderMap,
+   Json(change): Json<StatusChange>,
+) -> WebResult<Json<Value>> {
+   authorize(dashboard.token.as_deref(), &headers)?;
+   let tracker = &dashboard.tracker;
+   let updated = match change.action.as_str() {
+      "start" => tracker.start(&issue)?,
+      "block" => {
+         let reason = change.note.filter(|n| !n.trim().is_empty());
+         let reason = reason.ok_or_else(|| {
+            WebError(StatusCode::BAD_REQUEST, "Blocking needs a reason in `note`".to_string())
+         })?;
+         tracker.block(&issue, reason)?
+      },
+      "close" => tracker.close(&issue, change.note)?,
+      "reopen" => tracker.reopen(&issue)?,
+      "defer" => tracker.defer(&issue)?,
+      "activate" => tracker.activate(&issue)?,
+      action => {
+         return Err(WebError(
+            StatusCode::BAD_REQUEST,
+            format!("Unknown action: {action}. Use: start, block, close, reopen, defer, activate"),
+         ));
+      },
+   };
+   Ok(Json(json!(updated)))
+}
+
+async fn checkpoint(
+   State(dashboard): State<Arc<Dashboard>>,
+   Path(issue): Path<String>,
+   headers: HeaderMap,
+   Json(checkpoint): Json<Checkpoint>,
+) -> WebResult<Json<Value>> {
+   authorize(dashboard.token.as_deref(), &headers)?;
+   Ok(Json(json!(dashboard.tracker.checkpoint(&issue, checkpoint.note)?)))
+}
+
+/// Let a change through only with the server's token
+fn authorize(token: Option<&str>, headers: &HeaderMap) -> WebResult<()> {
+   let Some(token) = token else {
+      return Err(WebError(
+         StatusCode::FORBIDDEN,
+         "The dashboard is read-only; start it with --token to allow changes".to_string(),
+      ));
+   };
+   let sent = headers
+      .get(header::AUTHORIZATION)
+      .and_then(|value| value.to_str().ok())
+      .and_then(|value| value.strip_prefix("Bearer "));
+   match sent {
+      Some(sent) if same_secret(sent, token) => Ok(()),
+      _ => Err(WebError(StatusCode::UNAUTHORIZED, "Missing or wrong token".to_string())),
+   }
+}
+
+/// Compare without stopping at the first difference, so response times
+/// don't reveal how much of a guess was right
+fn same_secret(a: &str, b: &str) -> bool {
+   a.len() == b.len()
+      && a
+         .bytes()
+         .zip(b.bytes())
+         .fold(0, |diff, (x, y)| diff | (x ^ y))
+         == 0
+}
+
+#[cfg(test)]
+mod tests {
+   use axum::http::HeaderValue;
+
+   use super::*;
+
+   #[test]
+   fn test_authorize() {
+      let mut headers = HeaderMap::new();
+      assert_eq!(authorize(None, &headers).unwrap_err().0, StatusCode::FORBIDDEN);
+      assert_eq!(authorize(Some("s3cret"), &headers).unwrap_err().0, StatusCode::UNAUTHORIZED);
+
+      headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer s3cre"));
+      assert_eq!(authorize(Some("s3cret"), &headers).unwrap_err().0, StatusCode::UNAUTHORIZED);
+      headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer s3cret"));
+      assert!(authorize(Some("s3cret"), &headers).is_ok());
+      assert_eq!(authorize(None, &headers).unwrap_err().0, StatusCode::FORBIDDEN);
+   }
+}
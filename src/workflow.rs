@@ -0,0 +1,188 @@
+//! The configured workflow
+//!
+//! `agentx workflow show` draws the status graph the `transitions` config
+//! allows, as ASCII or a Mermaid state diagram, with the number of issues in
+//! each status and how long issues stay in it on average. States nothing
+//! leads to, and states an issue can't leave, are pointed out, since those
+//! are usually a mistake in the config.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+   flow::status_intervals,
+   issue::{IssueWithId, Status},
+   transitions::TransitionRules,
+   utils::format_hours,
+};
+
+/// Statuses in the order they are shown, roughly the way work flows
+const STATUSES: [Status; 6] = [
+   Status::Backlog,
+   Status::NotStarted,
+   Status::InProgress,
+   Status::Blocked,
+   Status::Done,
+   Status::Closed,
+];
+
+/// One status and the moves out of it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowState {
+   pub status:          String,
+   /// Issues in this status now
+   pub count:           usize,
+   /// Mean length of the stays in this status that have ended
+   pub avg_dwell_hours: Option<f64>,
+   pub next:            Vec<String>,
+   /// No other status leads here, so only new issues can be in it
+   pub unreachable:     bool,
+   /// Issues can't leave this status
+   pub dead_end:        bool,
+}
+
+/// Every status with its transitions, counts and dwell times
+pub fn workflow(
+   rules: &TransitionRules,
+   issues: &[IssueWithId],
+   now: DateTime<Utc>,
+) -> Vec<WorkflowState> {
+   let intervals: Vec<_> = issues
+      .iter()
+      .flat_map(|issue| {
+         let running = issue.issue.metadata.status;
+         let mut intervals = status_intervals(issue, now);
+         // The last interval of an issue still in that status hasn't ended
+         if intervals
+            .last()
+            .is_some_and(|i| i.status == running && i.end == now)
+         {
+            intervals.pop();
+         }
+         intervals
+      })
+      .collect();
+
+   STATUSES
+      .iter()
+      .map(|&status| {
+         let stays: Vec<f64> = intervals
+            .iter()
+            .filter(|i| i.status == status)
+            .map(|i| (i.end - i.start).num_minutes().max(0) as f64 / 60.0)
+            .collect();
+         let next: Vec<Status> = STATUSES
+            .iter()
+            .copied()
+            .filter(|to| rules.next(status).contains(to))
+            .collect();
+         WorkflowState {
+            status:          status.to_string(),
+            count:           issues
+               .iter()
+               .filter(|i| i.issue.metadata.status == status)
+               .count(),
+            avg_dwell_hours: (!stays.is_empty())
+               .then(|| stays.iter().sum::<f64>() / stays.len() as f64),
+            next:            next.iter().map(Status::to_string).collect(),
+            // New issues start open
+            unreachable:     status != Status::NotStarted
+               && !STATUSES
+                  .iter()
+                  .any(|&from| from != status && rules.allows(from, status)),
+            dead_end:        next.iter().all(|&to| to == status),
+         }
+      })
+      .collect()
+}
+
+/// A state's label: its status, issue count and average dwell
+fn label(state: &WorkflowState) -> String {
+   match state.avg_dwell_hours {
+      Some(hours) => format!("{} ({}, avg {})", state.status, state.count, format_hours(hours)),
+      None => format!("{} ({})", state.status, state.count),
+   }
+}
+
+/// The graph as a tree of moves out of each state
+pub fn ascii(states: &[WorkflowState]) -> String {
+   let mut out = String::new();
+   for state in states {
+      out.push_str(&label(state));
+      if state.unreachable {
+         out.push_str("  ⚠ nothing leads here");
+      }
+      if state.dead_end {
+         out.push_str("  ⚠ no way out");
+      }
+      out.push('\n');
+      for (i, next) in state.next.iter().enumerate() {
+         let branch = if i + 1 == state.next.len() {
+            "└─▶"
+         } else {
+            "├─▶"
+         };
+         out.push_str(&format!("  {branch} {next}\n"));
+      }
+   }
+   out
+}
+
+/// Mermaid state diagram source, as GitHub renders in a ```mermaid block
+pub fn mermaid(states: &[WorkflowState]) -> String {
+   let mut out = String::from("stateDiagram-v2\n");
+   out.push_str(&format!("  [*] --> {}\n", Status::NotStarted));
+   for state in states {
+      let dwell = state
+         .avg_dwell_hours
+         .map(|hours| format!(", avg {}", format_hours(hours)))
+         .unwrap_or_default();
+      out.push_str(&format!("  {} : {} issues{dwell}\n", state.status, state.count));
+   }
+   for state in states {
+      for next in &state.next {
+         out.push_str(&format!("  {} --> {next}\n", state.status));
+      }
+   }
+   out
+}
+
+#[cfg(test)]
+mod tests {
+   use chrono::Duration;
+
+   use super::*;
+   use crate::issue::{Priority, test_support::issue};
+
+   #[test]
+   fn test_workflow() {
+      let now = Utc::now();
+      let issues = vec![
+         issue(1)
+            .title("Crash")
+            .priority(Priority::High)
+            .created(now - Duration::hours(10))
+            .moved(Status::InProgress, now - Duration::hours(4))
+            .build(),
+      ];
+
+      let rules: TransitionRules = serde_yaml::from_str("closed: []\nbacklog: [open]").unwrap();
+      let states = workflow(&rules, &issues, now);
+      let open = states.iter().find(|s| s.status == "open").unwrap();
+      assert_eq!(open.avg_dwell_hours, Some(6.0));
+      assert_eq!(open.next, ["backlog", "active", "blocked", "done", "closed"]);
+      let active = states.iter().find(|s| s.status == "active").unwrap();
+      // Still running, so it has no finished stay yet
+      assert_eq!((active.count, active.avg_dwell_hours), (1, None));
+      let closed = states.iter().find(|s| s.status == "closed").unwrap();
+      assert!(closed.dead_end && !closed.unreachable);
+
+      let text = ascii(&states);
+      assert!(text.contains("open (0, avg 6.0h)\n  ├─▶ backlog\n"));
+      assert!(text.contains("closed (0)  ⚠ no way out\n"));
+      let diagram = mermaid(&states);
+      assert!(diagram.starts_with("stateDiagram-v2\n  [*] --> open\n"));
+      assert!(diagram.contains("  active : 1 issues\n"));
+      assert!(diagram.contains("  blocked --> active\n"));
+   }
+}